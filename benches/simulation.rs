@@ -0,0 +1,144 @@
+//! Benchmarks for the simulation engines, on generated circuits of a few sizes: a single
+//! `update_signals_once` pass, settling with it, with a `SimDebugger`, and with an `EventSim`,
+//! and a `BitParallel` run, which evaluates 64 input patterns where the others settle one.
+//!
+//! Run with `cargo bench`; criterion keeps the previous run's results in `target/criterion`, and
+//! reports when a benchmark gets slower.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use nannou_sketches::circuits::debugger::SimDebugger;
+use nannou_sketches::circuits::events::EventSim;
+use nannou_sketches::circuits::parallel::{counting, BitParallel};
+use nannou_sketches::circuits::*;
+use petgraph::graph::NodeIndex;
+
+/// A generated circuit, with the inputs to flip to set it going.
+struct Generated {
+    circuit: Circuit,
+    inputs: Vec<NodeIndex>,
+}
+
+fn ripple_carry(width: usize) -> Generated {
+    let mut circuit = Circuit::new();
+    let inputs = (0..width * 2)
+        .map(|_| circuit.add_input())
+        .collect::<Vec<_>>();
+    let (a, b) = inputs.split_at(width);
+    let (sum, carry) = circuit.ripple_carry(a, b);
+    for s in sum.iter().chain(Some(&carry)) {
+        circuit.add_output(*s);
+    }
+    Generated { circuit, inputs }
+}
+
+fn multiplier(width: usize) -> Generated {
+    let mut circuit = Circuit::new();
+    let inputs = (0..width * 2)
+        .map(|_| circuit.add_input())
+        .collect::<Vec<_>>();
+    let (a, b) = inputs.split_at(width);
+    let product = circuit.wallace_multiplier(a, b);
+    for p in &product {
+        circuit.add_output(*p);
+    }
+    Generated { circuit, inputs }
+}
+
+/// The circuit, settled with every input off, then with every input switched on, ready to
+/// settle again.
+fn switched_on(generated: &Generated) -> Circuit {
+    let mut circuit = generated.circuit.clone();
+    circuit.evaluate(&[]);
+    for input in &generated.inputs {
+        circuit.set_input(*input, true);
+    }
+    circuit
+}
+
+fn bench_circuits(
+    c: &mut Criterion,
+    name: &str,
+    sizes: &[usize],
+    generate: fn(usize) -> Generated,
+) {
+    let mut group = c.benchmark_group(name);
+    for size in sizes {
+        let generated = generate(*size);
+        let order = generated.circuit.update_order();
+        let limit = generated.circuit.settle_limit();
+
+        group.bench_with_input(
+            BenchmarkId::new("update_signals_once", size),
+            &generated,
+            |b, generated| {
+                let mut circuit = switched_on(generated);
+                b.iter(|| circuit.update_signals_once(&order))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("settle/update_signals_once", size),
+            &generated,
+            |b, generated| {
+                b.iter_batched(
+                    || switched_on(generated),
+                    |mut circuit| circuit.settle(&order, limit),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("settle/debugger", size),
+            &generated,
+            |b, generated| {
+                let debugger = SimDebugger::new(&generated.circuit);
+                b.iter_batched(
+                    || (switched_on(generated), debugger.clone()),
+                    |(mut circuit, mut debugger)| debugger.continue_until_settled(&mut circuit),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("settle/events", size),
+            &generated,
+            |b, generated| {
+                // settled with every input off, so only what switching them on changes is updated.
+                let mut settled = generated.circuit.clone();
+                let mut sim = EventSim::new(&settled);
+                sim.settle(&mut settled);
+                b.iter_batched(
+                    || {
+                        let (mut circuit, mut sim) = (settled.clone(), sim.clone());
+                        for input in &generated.inputs {
+                            sim.set_input(&mut circuit, *input, true);
+                        }
+                        (circuit, sim)
+                    },
+                    |(mut circuit, mut sim)| sim.settle(&mut circuit),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("bit_parallel", size),
+            &generated,
+            |b, generated| {
+                let mut sim = BitParallel::new(&generated.circuit);
+                let inputs = counting(generated.inputs.len(), 0);
+                b.iter(|| sim.run(&inputs))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn ripple_carry_benches(c: &mut Criterion) {
+    bench_circuits(c, "ripple_carry", &[8, 32, 128], ripple_carry);
+}
+
+fn multiplier_benches(c: &mut Criterion) {
+    bench_circuits(c, "wallace_multiplier", &[4, 8, 16], multiplier);
+}
+
+criterion_group!(benches, ripple_carry_benches, multiplier_benches);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Compiles the GLSL compute shaders in `src/viz/shaders` to SPIR-V in `OUT_DIR`, where the
+//! modules using them `include_bytes!` it from, so the two can't drift apart.
+
+use naga::back::spv;
+use naga::front::glsl;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const SHADERS: &[&str] = &["imageops"];
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    for name in SHADERS {
+        let path = format!("src/viz/shaders/{}.comp", name);
+        println!("cargo:rerun-if-changed={}", path);
+        let source =
+            fs::read_to_string(&path).unwrap_or_else(|e| panic!("can't read {}: {}", path, e));
+        let words = compile(&source).unwrap_or_else(|e| panic!("can't compile {}:\n{}", path, e));
+        let bytes = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+        fs::write(Path::new(&out_dir).join(format!("{}.spv", name)), bytes).unwrap();
+    }
+}
+
+// a compute shader's GLSL source, as SPIR-V words.
+fn compile(source: &str) -> Result<Vec<u32>, String> {
+    let options = glsl::Options::from(naga::ShaderStage::Compute);
+    let module = glsl::Frontend::default()
+        .parse(&options, source)
+        .map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+    let info = Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| format!("{:?}", e))?;
+    spv::write_vec(&module, &info, &spv::Options::default(), None).map_err(|e| e.to_string())
+}
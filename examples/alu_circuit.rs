@@ -0,0 +1,312 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit_recorded, CircuitStyle};
+use nannou_sketches::viz::svg::VectorRecorder;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
+
+const BITS: usize = 4;
+const OP_NAMES: [&str; 4] = ["add", "sub", "and", "or"];
+
+static HELP: &str = "drag the sliders to set the operands; click an operation to pick it";
+
+struct Model {
+    circuit: Circuit,
+    operands: [Vec<NodeIndex>; 2],
+    op: [NodeIndex; 2],
+    alu: Alu,
+    // the Output nodes for the result, carry and zero flag.
+    outputs: Vec<NodeIndex>,
+    update_order: Vec<NodeIndex>,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+
+    values: [usize; 2],
+    operation: AluOp,
+    // the slider being dragged.
+    dragging: Option<usize>,
+    // V exports the circuit, without the controls, as SVG.
+    svg: VectorRecorder,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+    let a = (0..BITS).map(|_| circuit.add_input()).collect::<Vec<_>>();
+    let b = (0..BITS).map(|_| circuit.add_input()).collect::<Vec<_>>();
+    let op = [circuit.add_input(), circuit.add_input()];
+    for i in 0..BITS {
+        circuit.set_label(a[i], &format!("a{}", i));
+        circuit.set_label(b[i], &format!("b{}", i));
+    }
+    circuit.set_label(op[0], "op0");
+    circuit.set_label(op[1], "op1");
+
+    let alu = circuit.alu(&a, &b, op);
+    let mut outputs = alu
+        .result
+        .iter()
+        .enumerate()
+        .map(|(i, r)| circuit.add_labeled_output(*r, &format!("r{}", i)))
+        .collect::<Vec<_>>();
+    outputs.push(circuit.add_labeled_output(alu.carry, "carry"));
+    outputs.push(circuit.add_labeled_output(alu.zero, "zero"));
+
+    let update_order = circuit.update_order();
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    Model {
+        circuit,
+        operands: [a, b],
+        op,
+        alu,
+        outputs,
+        update_order,
+        positions,
+        routes,
+        values: [0, 0],
+        operation: AluOp::Add,
+        dragging: None,
+        svg: VectorRecorder::new("alu_circuit"),
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    let win = app.window_rect();
+    match event {
+        Event::Update(_) => {
+            model.circuit.update_signals_once(&model.update_order);
+        }
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            let mouse = app.mouse.position();
+            model.dragging = (0..2).find(|i| slider_rect(win, *i).pad(-10.0).contains(mouse));
+            if let Some(i) = model.dragging {
+                slide(model, win, i, mouse);
+            }
+            for operation in AluOp::ALL.iter() {
+                if button_rect(win, *operation).contains(mouse) {
+                    set_operation(model, *operation);
+                }
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(MouseMoved(position)),
+            ..
+        } => {
+            if let Some(i) = model.dragging {
+                slide(model, win, i, position);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => {
+            model.dragging = None;
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => model.svg.key(key),
+        _ => (),
+    }
+}
+
+/// Set operand `i` from the mouse position along its slider.
+fn slide(model: &mut Model, win: Rect, i: usize, mouse: Vector2) {
+    let rect = slider_rect(win, i);
+    let max = (1 << BITS) - 1;
+    let fraction = ((mouse.x - rect.left()) / rect.w()).max(0.0).min(1.0);
+    let value = (fraction * max as f32).round() as usize;
+
+    model.values[i] = value;
+    for (bit, input) in model.operands[i].iter().enumerate() {
+        model.circuit.set_input(*input, get_bit(value, bit));
+    }
+}
+
+fn set_operation(model: &mut Model, operation: AluOp) {
+    model.operation = operation;
+    for (bit, input) in model.op.iter().enumerate() {
+        model
+            .circuit
+            .set_input(*input, get_bit(operation.opcode(), bit));
+    }
+}
+
+/// The gates along the path from the inputs, through the unit doing the current operation, to
+/// the outputs: everything the multiplexers are letting through.
+fn active_gates(model: &Model) -> HashSet<NodeIndex> {
+    let unit = &model.alu.units[model.operation.opcode()];
+    let downstream = model
+        .alu
+        .select
+        .iter()
+        .chain(&model.outputs)
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    let mut active = unit
+        .iter()
+        .chain(model.operands[0].iter())
+        .chain(model.operands[1].iter())
+        .cloned()
+        .collect::<HashSet<_>>();
+    let mut stack = unit.clone();
+    while let Some(node) = stack.pop() {
+        for next in model.circuit.graph().neighbors(node) {
+            if downstream.contains(&next) && active.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    active
+}
+
+fn slider_rect(win: Rect, i: usize) -> Rect {
+    Rect::from_x_y_w_h(
+        -win.w() * 0.1,
+        win.bottom() + 170.0 - 50.0 * i as f32,
+        win.w() * 0.5,
+        6.0,
+    )
+}
+
+fn button_rect(win: Rect, operation: AluOp) -> Rect {
+    let (w, gap) = (70.0, 10.0);
+    let i = operation.opcode() as f32;
+    Rect::from_x_y_w_h(
+        -win.w() * 0.1 + (i - 1.5) * (w + gap),
+        win.bottom() + 70.0,
+        w,
+        30.0,
+    )
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // leave the bottom of the window for the controls.
+    let bl = win.bottom_left() + vec2(win.w() * 0.08, 240.0);
+    let size = vec2(win.w() * 0.84, win.h() - 280.0);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+    let svg = &model.svg;
+    svg.begin(win);
+    svg.background(&draw, rgb8(28, 28, 34));
+
+    // a glow under the active datapath.
+    let active = active_gates(model);
+    for edge in model.circuit.graph().edge_references() {
+        if !active.contains(&edge.source()) || !active.contains(&edge.target()) {
+            continue;
+        }
+        let points = match model.routes.get(&edge.id()) {
+            Some(route) => route.clone(),
+            None => vec![
+                model.positions[&edge.source()],
+                model.positions[&edge.target()],
+            ],
+        };
+        let points = points.into_iter().map(&map_pos).collect::<Vec<_>>();
+        svg.polyline(&draw, &points, 8.0, rgba(0.3, 0.7, 1.0, 0.35));
+    }
+    for node in &active {
+        let pos = map_pos(model.positions[node]);
+        svg.ellipse(&draw, pos, vec2(22.0, 22.0), rgba(0.3, 0.7, 1.0, 0.35));
+    }
+
+    let style = CircuitStyle {
+        gate_size: 12.0,
+        wire_weight: 2.0,
+        font_size: 10,
+        ..CircuitStyle::default()
+    };
+    draw_circuit_recorded(
+        &draw,
+        svg,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+    svg.finish();
+
+    // controls.
+    for i in 0..2 {
+        let rect = slider_rect(win, i);
+        let max = (1 << BITS) - 1;
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.wh())
+            .color(rgb8(90, 90, 100));
+        let x = rect.left() + rect.w() * model.values[i] as f32 / max as f32;
+        draw.ellipse()
+            .x_y(x, rect.y())
+            .w_h(20.0, 20.0)
+            .color(rgb8(230, 230, 240));
+        draw.text(&format!(
+            "{} = {:2} = {}",
+            ["a", "b"][i],
+            model.values[i],
+            BusDisplay::Binary.format(model.values[i], BITS)
+        ))
+        .x_y(rect.right() + 110.0, rect.y())
+        .w(180.0)
+        .left_justify()
+        .color(rgb8(255, 255, 255));
+    }
+    for (operation, name) in AluOp::ALL.iter().zip(OP_NAMES.iter()) {
+        let rect = button_rect(win, *operation);
+        let selected = *operation == model.operation;
+        draw.rect().xy(rect.xy()).wh(rect.wh()).color(if selected {
+            rgb8(80, 170, 255)
+        } else {
+            rgb8(70, 70, 80)
+        });
+        draw.text(name).xy(rect.xy()).color(rgb8(255, 255, 255));
+    }
+
+    let mut result = 0;
+    for i in 0..BITS {
+        result = set_bit(result, i, model.circuit.get_1_in(model.outputs[i]));
+    }
+    let carry = model.circuit.get_1_in(model.outputs[BITS]);
+    let zero = model.circuit.get_1_in(model.outputs[BITS + 1]);
+    draw.text(&format!(
+        "result = {:2} = {}\ncarry {}, zero {}",
+        result,
+        BusDisplay::Binary.format(result, BITS),
+        carry as u8,
+        zero as u8
+    ))
+    .x_y(
+        button_rect(win, AluOp::Or).right() + 110.0,
+        win.bottom() + 70.0,
+    )
+    .w(180.0)
+    .left_justify()
+    .color(rgb8(255, 220, 120));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,269 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::faults::{Fault, TestVectors};
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
+
+// seconds each test vector is shown for.
+const TICK: f32 = 1.0;
+
+static HELP: &str = "space: pause, R: start over";
+
+struct Model {
+    circuit: Circuit,
+    vectors: TestVectors,
+    // the vector being applied; once they've all been applied, the count of vectors.
+    current: usize,
+    // faults detected by the vectors applied so far.
+    tested: HashSet<Fault>,
+    undetectable: HashSet<Fault>,
+    paused: bool,
+    // when the current vector was applied.
+    applied: f32,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+/// A full adder, with a redundant term added to the carry: a & b & c is already covered by
+/// a & b, so faults on it can't change the outputs.
+fn circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    let a = circuit.add_input();
+    let b = circuit.add_input();
+    let c = circuit.add_input();
+    circuit.set_label(a, "a");
+    circuit.set_label(b, "b");
+    circuit.set_label(c, "c");
+
+    let (sum, carry) = circuit.full_adder(a, b, c);
+    let ab = circuit.add_and(a, b);
+    let abc = circuit.add_and(ab, c);
+    let carry = circuit.add_or(carry, abc);
+    circuit.add_labeled_output(sum, "sum");
+    circuit.add_labeled_output(carry, "carry");
+    circuit
+}
+
+fn model(_app: &App) -> Model {
+    let circuit = circuit();
+    let vectors = circuit.generate_test_vectors();
+    let undetectable = vectors.undetectable.iter().cloned().collect();
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    let mut model = Model {
+        circuit,
+        vectors,
+        current: 0,
+        tested: HashSet::new(),
+        undetectable,
+        paused: false,
+        applied: 0.0,
+        positions,
+        routes,
+    };
+    apply(&mut model, 0, 0.0);
+    model
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    let t = app.duration.since_start.as_secs_f32();
+    match event {
+        Event::Update(_) => update(model, t),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => {
+            model.paused = !model.paused;
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => apply(model, 0, t),
+        _ => (),
+    }
+}
+
+/// Apply test vector `vector`, crediting it with the faults it detects. Going back to the start
+/// forgets everything tested so far.
+fn apply(model: &mut Model, vector: usize, t: f32) {
+    if vector == 0 {
+        model.tested.clear();
+    }
+    model.current = vector;
+    model.applied = t;
+    if vector == model.vectors.vectors.len() {
+        return;
+    }
+    model.circuit.evaluate(&model.vectors.assignment(vector));
+    let detected = model
+        .vectors
+        .detected
+        .iter()
+        .filter(|(_, v)| **v == vector)
+        .map(|(fault, _)| *fault);
+    model.tested.extend(detected);
+}
+
+fn update(model: &mut Model, t: f32) {
+    if model.paused {
+        model.applied = t;
+        return;
+    }
+    if t - model.applied < TICK {
+        return;
+    }
+    // linger on the finished coverage for a couple of ticks before starting over.
+    let count = model.vectors.vectors.len();
+    if model.current < count {
+        apply(model, model.current + 1, t);
+    } else if t - model.applied >= TICK * 3.0 {
+        apply(model, 0, t);
+    }
+}
+
+/// How well the wire `edge` has been tested, as a color: green once both its stuck-at faults have
+/// been detected, yellow for one, red for none, and purple if either can't be detected at all.
+fn coverage_color(model: &Model, edge: EdgeIndex) -> Rgba {
+    let faults = [false, true].iter().map(|value| Fault {
+        edge,
+        value: *value,
+    });
+    let mut tested = 0;
+    for fault in faults {
+        if model.undetectable.contains(&fault) {
+            return rgba(0.7, 0.3, 1.0, 0.5);
+        }
+        if model.tested.contains(&fault) {
+            tested += 1;
+        }
+    }
+    match tested {
+        0 => rgba(1.0, 0.2, 0.2, 0.35),
+        1 => rgba(1.0, 0.85, 0.2, 0.4),
+        _ => rgba(0.2, 1.0, 0.4, 0.45),
+    }
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // leave the right of the window for the test vectors.
+    let bl = win.bottom_left() + vec2(win.w() * 0.05, win.h() * 0.12);
+    let size = vec2(win.w() * 0.6, win.h() * 0.76);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(22, 22, 30));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+
+    // a glow under each wire, showing its coverage.
+    for edge in model.circuit.graph().edge_references() {
+        if edge.source() == Circuit::meta_input() {
+            continue;
+        }
+        let points = match model.routes.get(&edge.id()) {
+            Some(route) => route.clone(),
+            None => vec![
+                model.positions[&edge.source()],
+                model.positions[&edge.target()],
+            ],
+        };
+        draw.polyline()
+            .weight(12.0)
+            .points(points.into_iter().map(&map_pos))
+            .color(coverage_color(model, edge.id()));
+    }
+
+    let style = CircuitStyle {
+        gate_size: 24.0,
+        wire_weight: 3.0,
+        on: rgba(1.0, 0.86, 0.47, 1.0),
+        off: rgba(0.35, 0.35, 0.43, 1.0),
+        gate_fill: rgba(0.25, 0.25, 0.3, 1.0),
+        ..CircuitStyle::default()
+    };
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+
+    // the test vectors, with the one applied marked and the faults each detects.
+    let panel = Rect::from_w_h(win.w() * 0.28, win.h() * 0.8)
+        .top_right_of(win)
+        .shift(vec2(-20.0, -20.0));
+    let mut y = panel.top();
+    let mut line = |text: &str, color: Rgb8| {
+        draw.text(text)
+            .x_y(panel.x(), y)
+            .w(panel.w())
+            .left_justify()
+            .color(color);
+        y -= 20.0;
+    };
+    line("  a b c   detects", rgb8(160, 160, 180));
+    for (i, vector) in model.vectors.vectors.iter().enumerate() {
+        let bits = vector
+            .iter()
+            .map(|v| if *v { "1" } else { "0" })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let detects = model.vectors.detected.values().filter(|v| **v == i).count();
+        let current = i == model.current;
+        line(
+            &format!(
+                "{} {}   {} faults",
+                if current { ">" } else { " " },
+                bits,
+                detects
+            ),
+            if i <= model.current {
+                rgb8(255, 255, 255)
+            } else {
+                rgb8(110, 110, 130)
+            },
+        );
+    }
+
+    let detectable = model.vectors.detected.len();
+    let coverage = model.tested.len() as f32 / detectable.max(1) as f32;
+    line("", rgb8(0, 0, 0));
+    line(
+        &format!(
+            "coverage: {}/{} ({:.0}%)",
+            model.tested.len(),
+            detectable,
+            coverage * 100.0
+        ),
+        rgb8(120, 255, 150),
+    );
+    line(
+        &format!("{} faults undetectable", model.undetectable.len()),
+        rgb8(190, 120, 255),
+    );
+    if model.paused {
+        line("paused", rgb8(255, 220, 120));
+    }
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(220, 220, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,243 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::attractors::{Clifford, DeJong, Density, Lorenz};
+use nannou_sketches::viz::imageops::{ImageOp, ImageOps};
+use nannou_sketches::viz::palette::Palette;
+
+// px per pixel of the density image.
+const PIXEL: f32 = 2.0;
+const POINTS_PER_FRAME: usize = 200_000;
+// how fast the parameters wander, in radians per second of the slowest one.
+const DRIFT: f32 = 0.05;
+
+static HELP: &str =
+    "1-3 for Clifford, de Jong or Lorenz, up/down for exposure, G for effects, space to pause";
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Kind {
+    Clifford,
+    DeJong,
+    Lorenz,
+}
+
+/// Post-processing on the GPU, with `viz::imageops`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Post {
+    Off,
+    // the brightest parts blurred and added back on.
+    Glow,
+    // edges between bright and faint, thickened and added back on.
+    Outlines,
+}
+
+impl Post {
+    fn next(self) -> Post {
+        match self {
+            Post::Off => Post::Glow,
+            Post::Glow => Post::Outlines,
+            Post::Outlines => Post::Off,
+        }
+    }
+
+    fn ops(self) -> Vec<ImageOp> {
+        match self {
+            Post::Off => vec![],
+            Post::Glow => vec![
+                ImageOp::Threshold { level: 0.35 },
+                ImageOp::Blur { radius: 12 },
+                ImageOp::AddToOriginal { strength: 1.5 },
+            ],
+            Post::Outlines => vec![
+                ImageOp::EdgeDetect { strength: 3.0 },
+                ImageOp::Dilate { radius: 1 },
+                ImageOp::AddToOriginal { strength: 0.6 },
+            ],
+        }
+    }
+}
+
+struct Model {
+    kind: Kind,
+    // how far the parameters have wandered.
+    time: f32,
+    exposure: f32,
+    paused: bool,
+    palette: Palette,
+    post: Post,
+    // renders the attractor offscreen in `update` and post-processes it, while `post` is on.
+    ops: ImageOps,
+    // the parameters of what `ops` rendered last.
+    parameters: String,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let window = app.main_window();
+    Model {
+        kind: Kind::Clifford,
+        time: 0.0,
+        exposure: 1.2,
+        paused: false,
+        palette: Palette::new(&[
+            Srgb::new(5, 5, 15),
+            Srgb::new(0, 110, 255),
+            Srgb::new(249, 0, 229),
+            Srgb::new(255, 245, 235),
+        ]),
+        post: Post::Off,
+        ops: ImageOps::new(&window, ops_size(&window)),
+        parameters: String::new(),
+    }
+}
+
+// the size to post-process at in `window`, a pixel to a point like `ImageOps` draws.
+fn ops_size(window: &Window) -> [u32; 2] {
+    let (w, h) = window.inner_size_points();
+    [(w as u32).max(1), (h as u32).max(1)]
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => {
+            if !model.paused {
+                model.time += upd.since_last.as_secs_f32() * DRIFT;
+            }
+            if model.post != Post::Off {
+                let draw = Draw::new();
+                draw.background().color(rgb8(5, 5, 15));
+                model.parameters = draw_attractor(app, model, &draw);
+                let ops = model.post.ops();
+                let window = app.main_window();
+                // the textures are made for one size, so make new ones when the window's resized.
+                if model.ops.size() != ops_size(&window) {
+                    model.ops = ImageOps::new(&window, ops_size(&window));
+                }
+                model.ops.render(&window, &draw, &ops);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::Key1 => model.kind = Kind::Clifford,
+            Key::Key2 => model.kind = Kind::DeJong,
+            Key::Key3 => model.kind = Kind::Lorenz,
+            Key::Up => model.exposure *= 1.25,
+            Key::Down => model.exposure /= 1.25,
+            Key::G => model.post = model.post.next(),
+            Key::Space => model.paused = !model.paused,
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+/// `base` wandering by up to `range`, at `rate` times the slowest speed.
+fn wander(time: f32, base: f32, range: f32, rate: f32, phase: f32) -> f32 {
+    base + range * (time * rate + phase).sin()
+}
+
+/// An empty image of `bounds`, as big as fits in the window.
+fn image_of(app: &App, bounds: Rect) -> Density {
+    let win = app.window_rect().pad(20.0);
+    let scale = (win.w() / bounds.w()).min(win.h() / bounds.h()) / PIXEL;
+    let (w, h) = ((bounds.w() * scale) as usize, (bounds.h() * scale) as usize);
+    Density::new(w.max(1), h.max(1), bounds)
+}
+
+/// Draw the attractor, as its parameters are now, and return them to show.
+fn draw_attractor(app: &App, model: &Model, draw: &Draw) -> String {
+    let t = model.time;
+
+    let (density, parameters) = match model.kind {
+        Kind::Clifford => {
+            let attractor = Clifford::new(
+                wander(t, -1.4, 0.3, 1.0, 0.0),
+                wander(t, 1.6, 0.3, 1.4, 1.0),
+                wander(t, 1.0, 0.2, 2.3, 2.0),
+                wander(t, 0.7, 0.2, 3.1, 3.0),
+            );
+            let parameters = format!(
+                "a {:.3}, b {:.3}, c {:.3}, d {:.3}",
+                attractor.a, attractor.b, attractor.c, attractor.d
+            );
+            let mut density = image_of(app, attractor.bounds());
+            density.extend(attractor.take(POINTS_PER_FRAME));
+            (density, parameters)
+        }
+        Kind::DeJong => {
+            let attractor = DeJong::new(
+                wander(t, 1.4, 0.4, 1.0, 0.0),
+                wander(t, -2.3, 0.4, 1.4, 1.0),
+                wander(t, 2.4, 0.4, 2.3, 2.0),
+                wander(t, -2.1, 0.4, 3.1, 3.0),
+            );
+            let parameters = format!(
+                "a {:.3}, b {:.3}, c {:.3}, d {:.3}",
+                attractor.a, attractor.b, attractor.c, attractor.d
+            );
+            let mut density = image_of(app, attractor.bounds());
+            density.extend(attractor.take(POINTS_PER_FRAME));
+            (density, parameters)
+        }
+        Kind::Lorenz => {
+            let mut attractor = Lorenz::new();
+            attractor.sigma = wander(t, 10.0, 3.0, 1.0, 0.0);
+            attractor.rho = wander(t, 28.0, 8.0, 1.4, 1.0);
+            attractor.beta = wander(t, 8.0 / 3.0, 1.0, 2.3, 2.0);
+            let parameters = format!(
+                "sigma {:.3}, rho {:.3}, beta {:.3}",
+                attractor.sigma, attractor.rho, attractor.beta
+            );
+            // seen from the side, looking along y.
+            let bounds = Rect::from_corners(vec2(-30.0, 0.0), vec2(30.0, 60.0));
+            let mut density = image_of(app, bounds);
+            density.extend(
+                attractor
+                    .skip(1000)
+                    .take(POINTS_PER_FRAME)
+                    .map(|p| vec2(p.x, p.z)),
+            );
+            (density, parameters)
+        }
+    };
+    // images go from the top down, and the density from the bottom up.
+    let (w, h) = (density.width(), density.height());
+    let pixels = RgbaImage::from_fn(w as u32, h as u32, |x, y| {
+        let (x, y) = (x as usize, h - 1 - y as usize);
+        let [r, g, b] = model
+            .palette
+            .sample_u8(density.tonemap(x, y, model.exposure));
+        image::Rgba([r, g, b, 255])
+    });
+    let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+    draw.texture(&texture)
+        .w_h(w as f32 * PIXEL, h as f32 * PIXEL);
+    parameters
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(5, 5, 15));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let parameters = match model.post {
+        Post::Off => draw_attractor(app, model, &draw),
+        _ => {
+            draw.texture(model.ops.output()).wh(win.wh());
+            model.parameters.clone()
+        }
+    };
+
+    let paused = if model.paused { ", paused" } else { "" };
+    draw.text(&format!("{}{}\n{}", parameters, paused, HELP))
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
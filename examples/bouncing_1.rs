@@ -1,28 +1,46 @@
-use nannou::color::Lab;
 use nannou::geom::Range;
 use nannou::prelude::*;
-use rand::{Rng, SeedableRng};
-use rand_xorshift::XorShiftRng;
+use nannou_sketches::capture::{Capture, Headless, Metadata, Screenshot};
+use nannou_sketches::cli::Cli;
+use nannou_sketches::physics::{FixedTimestep, Particle, World};
+use nannou_sketches::rng::Seeds;
+use nannou_sketches::viz::capture::{draw_image, FrameCapture};
+use nannou_sketches::viz::palette::Palette;
+use nannou_sketches::viz::pixelsort::PixelSort;
+use nannou_sketches::viz::scale::window_scale;
+use rand::Rng;
+use std::rc::Rc;
 
-const FOR_RENDER: bool = false;
-const RENDER_DT: f32 = 1.0 / 60.0;
+// when rendering with `--capture`, saving frames is slower than real time; each one is this long
+// in the video.
+const RENDER_FRAME: f32 = 1.0 / 60.0;
+// how many times the hidden window's resolution `--headless` renders at.
+const HEADLESS_SCALE: f32 = 2.0;
 const RENDER_START: u64 = 240;
 const RENDER_END: u64 = 620;
-
-#[derive(Debug)]
-struct Ball {
-    pos: Vector2<f32>,
-    prev_pos: Vector2<f32>,
-    vel: Vector2<f32>,
-    r: f32,
-    color: Rgb8,
-}
+// how much of the shorter side of the window the simulation spans, when rendering.
+const RENDER_SCALE: f32 = 0.97;
 
 struct Model {
-    balls: Vec<Ball>,
+    world: World,
+    timestep: FixedTimestep,
+    // where each ball was before the last step, to interpolate from.
+    before: Vec<Vector2<f32>>,
+    // where each ball was drawn last frame, to draw its motion blur from.
+    prev_pos: Vec<Vector2<f32>>,
+    // P sorts the pixels of the trails into streaks; the sketch is drawn offscreen meanwhile.
+    sorting: bool,
+    capture: FrameCapture,
+    // whether the offscreen drawing's been cleared to white yet.
+    captured: bool,
+    // saves frames `RENDER_START` to `RENDER_END`, with `--capture`.
+    render: Option<Capture>,
+    // or renders them offscreen, with `--headless` too.
+    headless: Option<Headless>,
+    screenshot: Screenshot,
 }
 
-const N: u32 = 30;
+const STEP: f32 = 1.0 / 120.0;
 const GRAVITY: Vector2<f32> = Vector2 { x: 0.0, y: -1.0 };
 
 // domain is (-.5, .5) x (-.5, .5)
@@ -38,76 +56,167 @@ const SIM_BOUNDS: Rect<f32> = Rect {
 };
 
 fn main() {
-    nannou::app(model).event(event).simple_window(view).run();
+    nannou::app(model).event(event).exit(exit).run();
 }
 
-fn model(_app: &App) -> Model {
-    let color_a: Lab = rgb8(249, 0, 229).into_format::<f32>().into();
-    let color_b: Lab = rgb8(0, 110, 255).into_format::<f32>().into();
+/// `--count` balls, 30 by default; `--capture` renders frames `RENDER_START` to `RENDER_END`, at
+/// a steady rate and zoom, and `--headless` renders them offscreen, at `HEADLESS_SCALE` times the
+/// size, without showing the window.
+fn cli() -> Cli {
+    Cli::new("bouncing_1").flag(
+        "headless",
+        "Render the --capture frames offscreen, without showing a window",
+    )
+}
 
-    let lerp = |a: f32| {
-        let result = color_a * a + color_b * (1.0 - a);
-        let result: Rgb = result.into();
-        result.into_format::<u8>()
-    };
+fn model(app: &App) -> Model {
+    let args = cli().parse();
+    let headless = args.flag("headless");
+    assert!(
+        !headless || args.capture().is_some(),
+        "--headless needs --capture, to save the frames somewhere"
+    );
+    let mut window = app.new_window().visible(!headless).view(view);
+    if let Some((width, height)) = args.size() {
+        window = window.size(width, height);
+    }
+    window.build().unwrap();
+    if headless {
+        // a hidden window's never redrawn, so don't wait for it between updates; each update
+        // renders a frame itself, as fast as they can be saved.
+        app.set_loop_mode(LoopMode::rate_fps(1000.0));
+    }
 
-    let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
-    let balls = (0..N)
-        .map(|_| {
-            let pos = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
-            Ball {
-                pos,
-                prev_pos: pos,
-                vel: rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32),
-                r: rng.gen::<f32>() / 30.0 + 0.02,
-                color: lerp(rng.gen()),
-            }
-        })
+    let seeds = Seeds::from_args(&args);
+    let mut rng = seeds.fork("balls");
+    let mut world = World::new();
+    world.gravity = GRAVITY;
+    world.bounds = Some(Rc::new(SIM_BOUNDS));
+    world.collisions = true;
+    for _ in 0..args.count(30) {
+        let pos = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+        let vel = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+        let mut ball = Particle::new(pos, vel);
+        // drawn as big as it is, so balls touch when they collide.
+        ball.radius = (rng.gen::<f32>() / 30.0 + 0.02) / 2.0;
+        // as heavy as it is big.
+        ball.mass = ball.radius * ball.radius / (0.01 * 0.01);
+        world.add_particle(ball);
+    }
+    let before = world
+        .particles
+        .iter()
+        .map(|ball| ball.pos)
         .collect::<Vec<_>>();
 
-    Model { balls }
+    let mut render = Capture::from_args(&args);
+    if let Some(render) = &mut render {
+        render.frames = Some(RENDER_START..RENDER_END);
+        render.digits = 3;
+        render.write_metadata(&Metadata::new("bouncing_1", Some(seeds.seed())));
+    }
+
+    let window = app.main_window();
+    let (w, h) = window.inner_size_pixels();
+    let (render, headless) = match render {
+        Some(render) if headless => {
+            let mut headless = Headless::new(&window, HEADLESS_SCALE, render);
+            headless.dt = RENDER_FRAME;
+            (None, Some(headless))
+        }
+        render => (render, None),
+    };
+    Model {
+        world,
+        timestep: FixedTimestep::new(STEP),
+        prev_pos: before.clone(),
+        before,
+        sorting: false,
+        capture: FrameCapture::new(&window, [w, h]),
+        captured: false,
+        render,
+        headless,
+        screenshot: Screenshot::new(),
+    }
 }
 
-fn event(_app: &App, model: &mut Model, event: Event) {
+fn event(app: &App, model: &mut Model, event: Event) {
     match event {
-        Event::Update(upd) => update(model, upd),
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::P)),
+            ..
+        } => model.sorting = !model.sorting,
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => model.screenshot.key(app, key),
         _ => (),
     }
 }
 
-fn update(model: &mut Model, upd: Update) {
-    let dt = if FOR_RENDER {
-        RENDER_DT
-    } else {
-        upd.since_last.as_secs_f32()
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let elapsed = match &model.headless {
+        Some(headless) => headless.dt,
+        None if model.render.is_some() => RENDER_FRAME,
+        None => upd.since_last.as_secs_f32(),
     };
 
-    for ball in model.balls.iter_mut() {
-        ball.prev_pos = ball.pos;
+    model.prev_pos = positions(model);
+    for _ in 0..model.timestep.advance(elapsed) {
+        model.before = model.world.particles.iter().map(|ball| ball.pos).collect();
+        model.world.step(model.timestep.dt);
+    }
 
-        ball.vel += GRAVITY * dt;
-        ball.pos += ball.vel * dt;
+    if model.sorting {
+        let draw = Draw::new();
+        if !model.captured {
+            draw.background().color(WHITE);
+            model.captured = true;
+        }
+        balls(app, model, &draw);
+        model.capture.capture(&app.main_window(), &draw);
+    }
 
-        if ball.pos.y - ball.r < SIM_BOUNDS.y.start {
-            ball.pos.y += (SIM_BOUNDS.y.start - (ball.pos.y - ball.r)) * 2.0;
-            ball.vel.y *= -1.0;
+    if let Some(headless) = &model.headless {
+        let draw = headless.draw();
+        if headless.frame() == 0 {
+            draw.background().color(WHITE);
         }
-        if ball.pos.x - ball.r < SIM_BOUNDS.x.start {
-            ball.pos.x += (SIM_BOUNDS.x.start - (ball.pos.x - ball.r)) * 2.0;
-            ball.vel.x *= -1.0;
-        } else if ball.pos.x + ball.r > SIM_BOUNDS.x.end {
-            ball.pos.x -= ((ball.pos.x + ball.r) - SIM_BOUNDS.x.end) * 2.0;
-            ball.vel.x *= -1.0;
+        balls(app, model, &draw);
+        let headless = model.headless.as_mut().unwrap();
+        headless.render(&app.main_window(), &draw);
+        if headless.done() {
+            app.quit();
         }
     }
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    if app.elapsed_frames() == 1 {
-        frame.clear(nannou::color::named::WHITE);
+fn exit(app: &App, model: Model) {
+    model.capture.finish(&app.main_window());
+    if let Some(headless) = &model.headless {
+        headless.finish(&app.main_window());
     }
+}
+
+/// Whether frames are being rendered, at a steady rate and zoom.
+fn rendering(model: &Model) -> bool {
+    model.render.is_some() || model.headless.is_some()
+}
+
+/// Where to draw each ball, between steps.
+fn positions(model: &Model) -> Vec<Vector2<f32>> {
+    model
+        .before
+        .iter()
+        .zip(&model.world.particles)
+        .map(|(before, ball)| model.timestep.interpolate(*before, ball.pos))
+        .collect()
+}
+
+/// Fade the trails so far a little, and draw each ball's latest stretch of trail.
+fn balls(app: &App, model: &Model, draw: &Draw) {
     let win = app.window_rect();
-    let draw = app.draw();
     draw.rect()
         .x_y(0.0, 0.0)
         .w_h(win.x.len(), win.y.len())
@@ -116,49 +225,56 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
     let m = app.mouse.position();
 
-    let draw = if FOR_RENDER {
-        draw.scale(745.0)
+    let draw = if rendering(model) {
+        draw.scale(window_scale(&app.main_window(), RENDER_SCALE))
     } else {
         draw.scale(m.x - win.x.start)
     };
 
-    let color_a: Lab = rgb8(249, 0, 229).into_format::<f32>().into();
-    let color_b: Lab = rgb8(0, 110, 255).into_format::<f32>().into();
+    let palette = Palette::blue_pink();
 
-    for ball in &model.balls {
+    let positions = positions(model);
+    for ((ball, pos), prev_pos) in model
+        .world
+        .particles
+        .iter()
+        .zip(positions)
+        .zip(&model.prev_pos)
+    {
         // 1/2 m v^2
         let kinetic = 0.5 * ball.vel.magnitude2();
         // m g h
-        let potential = GRAVITY.magnitude() * ((ball.pos.y - ball.r) - SIM_BOUNDS.y.start);
+        let potential = GRAVITY.magnitude() * ((ball.pos.y - ball.radius) - SIM_BOUNDS.y.start);
 
         let ratio = potential / (potential + kinetic);
 
         draw.line()
-            .start(ball.prev_pos)
-            .end(ball.pos)
-            .weight(ball.r)
+            .start(*prev_pos)
+            .end(pos)
+            .weight(ball.radius * 2.0)
             .caps_round()
             .tolerance(0.001)
-            .color(color_a * ratio + (color_b * (1.0 - ratio)))
+            .color(palette.sample(ratio))
             .finish();
     }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    if app.elapsed_frames() == 1 {
+        frame.clear(nannou::color::named::WHITE);
+    }
+    let draw = app.draw();
+    match model.capture.latest() {
+        // sorted afresh every frame, as the trails underneath keep building up unsorted.
+        Some(mut image) if model.sorting => {
+            PixelSort::new(0.2, 0.9).apply(&mut image);
+            draw_image(app, &draw, image, app.window_rect());
+        }
+        _ => balls(app, model, &draw),
+    }
     draw.to_frame(app, &frame).unwrap();
-    if FOR_RENDER && frame.nth() >= RENDER_START && frame.nth() < RENDER_END {
-        // Capture the frame!
-        let file_path = captured_frame_path(app, &frame);
-        app.main_window().capture_frame(file_path);
+    if let Some(render) = &model.render {
+        render.save(app);
     }
     frame.submit();
 }
-
-fn captured_frame_path(app: &App, frame: &Frame) -> std::path::PathBuf {
-    // Create a path that we want to save this frame to.
-    app.project_path()
-        .expect("failed to locate `project_path`")
-        // Capture all frames to a directory called `/<path_to_nannou>/nannou/simple_capture`.
-        .join(app.exe_name().unwrap())
-        // Name each file after the number of the frame.
-        .join(format!("{:03}", frame.nth() - RENDER_START))
-        // The extension will be PNG. We also support tiff, bmp, gif, jpeg, webp and some others.
-        .with_extension("png")
-}
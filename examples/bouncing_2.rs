@@ -1,34 +1,83 @@
-use nannou::color::Lab;
 use nannou::geom::Range;
 use nannou::prelude::*;
-use rand::{Rng, SeedableRng};
-use rand_xorshift::XorShiftRng;
-
-#[derive(Debug)]
-struct Ball {
-    pos: Vector2<f32>,
-    vel: Vector2<f32>,
-    r: f32,
-    color: Rgb8,
+use nannou_osc::Type;
+use nannou_sketches::capture::{Capture, Metadata, Screenshot};
+use nannou_sketches::cli::Cli;
+use nannou_sketches::config::Config;
+use nannou_sketches::hud::Hud;
+use nannou_sketches::osc::Osc;
+use nannou_sketches::physics::boundary::{circle, Boundary, Polygon};
+use nannou_sketches::physics::drag::Dragger;
+use nannou_sketches::physics::{FixedTimestep, IntegrationMethod, Particle, Spring, World};
+use nannou_sketches::rng::Seeds;
+use nannou_sketches::viz::gradient::{draw_gradient_rect, Gradient};
+use nannou_sketches::viz::plot::Plot;
+use nannou_sketches::viz::scale::window_scale;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+struct Model {
+    world: World,
+    timestep: FixedTimestep,
+    // where each ball was before the last step, to interpolate from.
+    before: Vec<Vector2<f32>>,
+    // which of `walls` the balls are in.
+    walls: usize,
+    dragger: Dragger,
+    // pixels per unit of simulation; follows the mouse, except while dragging.
+    zoom: f32,
+    // the total energy, and how close the most stretched spring is to snapping, over time; P
+    // shows them.
+    energy: Plot,
+    strain: Plot,
+    plots: bool,
+    // F3 shows the zoom, how many balls and springs there are, and whether frames are being saved.
+    hud: Hud,
+    // with `--osc`, every collision's sent on as `/bouncing_2/collision`, with how hard it was and
+    // where.
+    osc: Osc,
+    config: Config<Constants>,
+    // with `--capture`, C starts and stops saving frames.
+    capture: Option<Capture>,
+    screenshot: Screenshot,
+    seeds: Seeds,
 }
 
-#[derive(Debug)]
-struct Connection {
-    equilibrium: f32,
-    hooke: f32,
-    a: usize,
-    b: usize,
+/// The constants `bouncing_2.toml` can change while the sketch runs.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct Constants {
+    gravity: f32,
+    // how much speed the balls keep each step.
+    damping: f32,
+    // how far springs stretch, past their rest length, before they snap; the most stretched
+    // settle at about 7.
+    break_strain: f32,
+    drag_stiffness: f32,
+    // the balls' colors, when their energy's all in their speed, and all in their height.
+    fast: [u8; 3],
+    high: [u8; 3],
 }
 
-struct Model {
-    balls: Vec<Ball>,
-    connections: Vec<Connection>,
+impl Default for Constants {
+    fn default() -> Self {
+        Constants {
+            gravity: 1.0,
+            damping: 0.99,
+            break_strain: 8.0,
+            drag_stiffness: 300.0,
+            fast: [0, 110, 255],
+            high: [249, 0, 229],
+        }
+    }
 }
 
-const N: usize = 30;
 const M: usize = 20;
 const FIXED: usize = 5;
-const GRAVITY: Vector2<f32> = Vector2 { x: 0.0, y: -1.0 };
+const STEP: f32 = 1.0 / 120.0;
+// how many frames the plots show.
+const PLOT_LENGTH: usize = 300;
 
 // domain is (-.5, .5) x (-.5, .5)
 const SIM_BOUNDS: Rect<f32> = Rect {
@@ -42,112 +91,260 @@ const SIM_BOUNDS: Rect<f32> = Rect {
     },
 };
 
+/// The shapes B switches between for the balls to bounce around in, and the outline to draw for
+/// each (the box has an open top, so it doesn't get one).
+fn walls(which: usize) -> (Rc<dyn Boundary>, Vec<Vector2<f32>>) {
+    match which % 3 {
+        0 => (Rc::new(SIM_BOUNDS), vec![]),
+        1 => {
+            let outline = (0..64)
+                .map(|i| {
+                    let angle = i as f32 / 64.0 * 2.0 * PI;
+                    vec2(angle.cos(), angle.sin()) * 0.5
+                })
+                .collect();
+            (Rc::new(circle(vec2(0.0, 0.0), 0.5)), outline)
+        }
+        _ => {
+            let star = Polygon::star(vec2(0.0, 0.0), 5, 0.6, 0.3);
+            let outline = star.rings[0].clone();
+            (Rc::new(star), outline)
+        }
+    }
+}
+
 fn main() {
-    nannou::app(model).event(event).simple_window(view).run();
+    let mut app = nannou::app(model).event(event).simple_window(view);
+    if let Some((width, height)) = cli().parse().size() {
+        app = app.size(width, height);
+    }
+    app.run();
 }
 
-fn model(_app: &App) -> Model {
-    let color_a: Lab = rgb8(249, 0, 229).into_format::<f32>().into();
-    let color_b: Lab = rgb8(0, 110, 255).into_format::<f32>().into();
+/// `--count` balls, 30 by default.
+fn cli() -> Cli {
+    Cli::new("bouncing_2")
+        .flag("plots", "Start with the energy and strain plots shown")
+        .option::<String>("osc", "Send each collision over OSC to HOST:PORT")
+}
 
-    let lerp = |a: f32| {
-        let result = color_a * a + color_b * (1.0 - a);
-        let result: Rgb = result.into();
-        result.into_format::<u8>()
+fn model(app: &App) -> Model {
+    let args = cli().parse();
+    let n = args.count(30);
+    assert!(n > FIXED, "there have to be more than {} balls", FIXED);
+    let seeds = Seeds::from_args(&args);
+    let mut capture = Capture::from_args(&args);
+    if let Some(capture) = &mut capture {
+        capture.toggle = Some(Key::C);
+    }
+    let mut osc = Osc::new("bouncing_2");
+    if let Some(addr) = args.value::<String>("osc") {
+        osc.send_to(&addr)
+            .unwrap_or_else(|e| panic!("can't send OSC to {}: {}", addr, e));
+    }
+    let mut rng = seeds.fork("balls");
+    let mut world = World::new();
+    world.integrator = IntegrationMethod::Verlet;
+    world.bounds = Some(walls(0).0);
+    world.collisions = true;
+    for i in 0..n {
+        let pos = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+        let vel = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+        let mut ball = Particle::new(pos, vel);
+        // drawn as big as it is, so balls touch when they collide.
+        ball.radius = (rng.gen::<f32>() / 30.0 + 0.02) / 2.0;
+        // as heavy as it is big, so the springs fling small balls around and barely move big ones.
+        ball.mass = ball.radius * ball.radius / (0.01 * 0.01);
+        ball.pinned = i < FIXED;
+        world.add_particle(ball);
+    }
+    let mut rng = seeds.fork("springs");
+    for _ in 0..M {
+        let a = rng.gen::<usize>() % n;
+        let mut b = rng.gen::<usize>() % n;
+        while b == a {
+            b = rng.gen::<usize>() % n;
+        }
+        let distance = (world.particles[a].pos - world.particles[b].pos).magnitude();
+        let stiffness = rng.gen::<f32>() * 30.0;
+        world.add_spring(Spring::new(a, b, distance / 3.0, stiffness));
+    }
+
+    let before = world.particles.iter().map(|ball| ball.pos).collect();
+    let mut dragger = Dragger::new(0.0);
+    dragger.reach = 0.02;
+    // placed in the corner each frame, as the window changes.
+    let mut energy = Plot::new(Rect::from_w_h(0.0, 0.0), PLOT_LENGTH);
+    energy.add_series("energy", rgb8(0, 110, 255));
+    let mut strain = Plot::new(Rect::from_w_h(0.0, 0.0), PLOT_LENGTH);
+    strain.add_series("most stretched", rgb8(220, 0, 0));
+    let mut model = Model {
+        world,
+        timestep: FixedTimestep::new(STEP),
+        before,
+        walls: 0,
+        dragger,
+        // until the mouse moves.
+        zoom: window_scale(&app.main_window(), 0.97),
+        energy,
+        strain,
+        plots: args.flag("plots"),
+        hud: Hud::new(),
+        osc,
+        config: Config::load("bouncing_2.toml"),
+        capture,
+        screenshot: Screenshot::new(),
+        seeds,
     };
+    apply(&mut model);
+    model
+}
 
-    let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
-    let balls = (0..N)
-        .map(|_| Ball {
-            pos: rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32),
-            vel: rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32),
-            r: rng.gen::<f32>() / 30.0 + 0.02,
-            color: lerp(rng.gen()),
-        })
-        .collect::<Vec<_>>();
-    let connections = (0..M)
-        .map(|_| {
-            let a = rng.gen::<usize>() % N;
-            let mut b = rng.gen::<usize>() % N;
-            while b == a {
-                b = rng.gen::<usize>() % N;
-            }
-            Connection {
-                equilibrium: (balls[a].pos - balls[b].pos).magnitude() / 10.0,
-                hooke: rng.gen::<f32>() / 2.0,
-                a,
-                b,
-            }
+/// Set everything from the config's constants.
+fn apply(model: &mut Model) {
+    let constants = model.config.get();
+    model.world.gravity = vec2(0.0, -constants.gravity);
+    model.world.damping = constants.damping;
+    for spring in &mut model.world.springs {
+        spring.break_strain = Some(constants.break_strain);
+    }
+    model.dragger.stiffness = constants.drag_stiffness;
+    model.strain.range = Some((0.0, constants.break_strain));
+    // the frames saved from now on are made with these.
+    if let Some(capture) = &model.capture {
+        let mut metadata = Metadata::new("bouncing_2", Some(model.seeds.seed()));
+        metadata.param("constants", constants);
+        capture.write_metadata(&metadata);
+    }
+}
+
+/// Balls are colored by how much of their energy is potential, blue when it's all in their
+/// speed, to pink when it's all in their height.
+fn energy_colors(constants: &Constants) -> Gradient {
+    let [r, g, b] = constants.fast;
+    let fast = Srgb::new(r, g, b);
+    let [r, g, b] = constants.high;
+    Gradient::new(&[(0.0, fast), (1.0, Srgb::new(r, g, b))])
+}
+
+/// Kinetic energy, plus potential energy from gravity (0 at the origin) and in the springs.
+fn energy(world: &World) -> f32 {
+    let balls = world
+        .particles
+        .iter()
+        .filter(|ball| !ball.pinned)
+        .map(|ball| ball.mass * (0.5 * ball.vel.magnitude2() - world.gravity.dot(ball.pos)))
+        .sum::<f32>();
+    let springs = world
+        .springs
+        .iter()
+        .map(|spring| {
+            let stretch = spring.strain(&world.particles) * spring.rest_length;
+            0.5 * spring.stiffness * stretch * stretch
         })
-        .collect::<Vec<_>>();
+        .sum::<f32>();
+    balls + springs
+}
 
-    Model { balls, connections }
+/// Where the mouse is, in simulation coordinates.
+fn mouse(app: &App, model: &Model) -> Vector2<f32> {
+    app.mouse.position() / model.zoom
 }
 
-fn event(_app: &App, model: &mut Model, event: Event) {
+fn event(app: &App, model: &mut Model, event: Event) {
     match event {
-        Event::Update(upd) => update(model, upd),
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            let mouse = mouse(app, model);
+            model.dragger.press(&model.world.particles, mouse);
+        }
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => model.dragger.release(),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::B)),
+            ..
+        } => {
+            model.walls += 1;
+            model.world.bounds = Some(walls(model.walls).0);
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::P)),
+            ..
+        } => model.plots = !model.plots,
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            model.screenshot.key(app, key);
+            model.hud.key(key);
+            if let Some(capture) = &mut model.capture {
+                capture.key(app, key);
+            }
+        }
         _ => (),
     }
 }
 
-fn update(model: &mut Model, upd: Update) {
-    let dt = upd.since_last.as_secs_f32();
-    for (i, ball) in model.balls.iter_mut().enumerate() {
-        if i < FIXED {
-            continue;
-        }
-        ball.vel += GRAVITY * dt;
+fn update(app: &App, model: &mut Model, upd: Update) {
+    if model.config.poll() {
+        apply(model);
     }
-    for connection in model.connections.iter() {
-        let pa = model.balls[connection.a].pos;
-        let pb = model.balls[connection.b].pos;
-        let pa_to_pb = pb - pa;
-        let length = pa_to_pb.magnitude2();
-        let f = connection.hooke * (length - connection.equilibrium);
-        let pa_to_pb_n = pa_to_pb / length;
-
-        // TODO mass?
-        model.balls[connection.a].vel += pa_to_pb_n * f;
-        model.balls[connection.b].vel -= pa_to_pb_n * f;
+    if model.dragger.held().is_none() {
+        model.zoom = (app.mouse.x - app.window_rect().left()).max(1.0);
     }
-
-    for (i, ball) in model.balls.iter_mut().enumerate() {
-        if i < FIXED {
-            continue;
+    let mouse = mouse(app, model);
+    model.dragger.move_to(mouse);
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        model.before = model.world.particles.iter().map(|ball| ball.pos).collect();
+        model.dragger.pull(&mut model.world, model.timestep.dt);
+        model.world.step(model.timestep.dt);
+        for collision in &model.world.collided {
+            let pos = model.world.particles[collision.a].pos;
+            model.osc.send(
+                "collision",
+                vec![
+                    Type::Float(collision.speed),
+                    Type::Float(pos.x),
+                    Type::Float(pos.y),
+                ],
+            );
         }
-        ball.pos += ball.vel * dt;
-
-        if ball.pos.y - ball.r < SIM_BOUNDS.y.start {
-            ball.pos.y += (SIM_BOUNDS.y.start - (ball.pos.y - ball.r)) * 2.0;
-            ball.vel.y *= -1.0;
-        }
-        if ball.pos.x - ball.r < SIM_BOUNDS.x.start {
-            ball.pos.x += (SIM_BOUNDS.x.start - (ball.pos.x - ball.r)) * 2.0;
-            ball.vel.x *= -1.0;
-        } else if ball.pos.x + ball.r > SIM_BOUNDS.x.end {
-            ball.pos.x -= ((ball.pos.x + ball.r) - SIM_BOUNDS.x.end) * 2.0;
-            ball.vel.x *= -1.0;
-        }
-        ball.vel *= 0.99;
     }
 
-    /*
-    for (i, ball1) in model.balls.iter_mut().enumerate() {
-        for (j, ball2) in model.balls.iter_mut().enumerate() {
-            if i == j {
-                continue;
-            }
-        }
+    let corner = app.window_rect().pad(10.0);
+    model.energy.rect = Rect::from_w_h(240.0, 80.0).top_left_of(corner);
+    model.strain.rect = Rect::from_w_h(240.0, 80.0)
+        .below(model.energy.rect)
+        .align_left_of(corner)
+        .shift_y(-10.0);
+    model.energy.push(0, energy(&model.world));
+    let world = &model.world;
+    let most_stretched = world
+        .springs
+        .iter()
+        .map(|spring| spring.strain(&world.particles))
+        .fold(0.0, f32::max);
+    model.strain.push(0, most_stretched);
+
+    let hud = &mut model.hud;
+    hud.frame(app.window_rect(), upd.since_last.as_secs_f32());
+    hud.stat("zoom", format!("{:.2}", model.zoom));
+    hud.stat("balls", model.world.particles.len());
+    hud.stat("springs", model.world.springs.len());
+    if let Some(capture) = &model.capture {
+        hud.capture(capture, app.elapsed_frames());
     }
-     */
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     if app.elapsed_frames() == 1 {
         frame.clear(nannou::color::named::WHITE);
     }
-    let win = app.window_rect();
     let draw = app.draw();
     //draw.rect()
     //    .x_y(0.0, 0.0)
@@ -156,44 +353,87 @@ fn view(app: &App, model: &Model, frame: Frame) {
     //    .finish();
     draw.background().color(rgb8(255, 255, 255));
 
-    let m = app.mouse.position();
-    //draw.text(&format!("[{:.2}, {:.2}]", m.x, m.y)).xy(m).finish();
-    draw.text(&format!("{:.2}", m.x - win.x.start))
-        .xy(m)
-        .finish();
+    // unscaled, for the plots and HUD over the top; asking `app` for another would clear the frame's
+    // drawing so far.
+    let overlay = draw.clone();
+    let draw = draw.scale(model.zoom);
+    //let draw = draw.scale(window_scale(&app.main_window(), 0.97));
 
-    let draw = draw.scale(m.x - win.x.start);
-    //let draw = draw.scale(745.0);
+    let constants = model.config.get();
+    let colors = energy_colors(constants);
 
-    let color_a: Lab = rgb8(249, 0, 229).into_format::<f32>().into();
-    let color_b: Lab = rgb8(0, 110, 255).into_format::<f32>().into();
-    //let color_b: Lab = rgb8(0, 230, 10).into_format::<f32>().into();
+    let outline = walls(model.walls).1;
+    if !outline.is_empty() {
+        let closed = outline.iter().chain(outline.first()).cloned();
+        draw.polyline()
+            .weight(0.004)
+            .points(closed)
+            .color(rgba8(0, 0, 0, 80));
+    }
 
-    for connection in &model.connections {
+    let balls = &model.world.particles;
+    // where to draw each ball, between steps.
+    let positions = model
+        .before
+        .iter()
+        .zip(balls)
+        .map(|(before, ball)| model.timestep.interpolate(*before, ball.pos))
+        .collect::<Vec<_>>();
+    for spring in &model.world.springs {
+        // redder and stronger the closer it is to snapping.
+        let strain = (spring.strain(balls) / constants.break_strain)
+            .max(0.0)
+            .min(1.0);
         draw.line()
-            .start(model.balls[connection.a].pos)
-            .end(model.balls[connection.b].pos)
+            .start(positions[spring.a])
+            .end(positions[spring.b])
             .weight(0.01)
-            .color(rgba8(0, 0, 0, 40))
+            .color(rgba8(
+                (255.0 * strain) as u8,
+                0,
+                0,
+                (40.0 + 200.0 * strain) as u8,
+            ))
             .finish();
     }
-    for ball in &model.balls {
+    for (ball, pos) in balls.iter().zip(&positions) {
         // 1/2 m v^2
         let kinetic = 0.5 * ball.vel.magnitude2();
         // m g h
-        let potential = GRAVITY.magnitude() * ((ball.pos.y - ball.r) - SIM_BOUNDS.y.start);
+        let potential = constants.gravity * ((ball.pos.y - ball.radius) - SIM_BOUNDS.y.start);
 
         let ratio = potential / (potential + kinetic);
 
         draw.ellipse()
-            .xy(ball.pos)
-            //.color(ball.color)
+            .xy(*pos)
             //.color(rgb(r2, 0, 255 - r2))
-            .color(color_a * ratio + (color_b * (1.0 - ratio)))
-            .w_h(ball.r, ball.r)
+            .color(colors.sample(ratio))
+            .w_h(ball.radius * 2.0, ball.radius * 2.0)
             .resolution(16)
             .finish();
     }
+
+    model.hud.draw(&overlay);
+    if model.plots {
+        let draw = overlay;
+        model.energy.draw(&draw);
+        model.strain.draw(&draw);
+        // and a key to the balls' colors.
+        let key = Rect::from_w_h(model.strain.rect.w(), 8.0)
+            .below(model.strain.rect)
+            .align_left_of(model.strain.rect)
+            .shift_y(-10.0);
+        draw_gradient_rect(&draw, key, &colors);
+        for (text, at) in [("fast", key.bottom_left()), ("high", key.bottom_right())].iter() {
+            draw.text(text)
+                .xy(*at + vec2(0.0, -10.0))
+                .font_size(11)
+                .color(rgb8(60, 60, 60));
+        }
+    }
+    if let Some(capture) = &model.capture {
+        capture.save(app);
+    }
     draw.to_frame(app, &frame).unwrap();
     frame.submit();
 }
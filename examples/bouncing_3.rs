@@ -1,24 +1,46 @@
 use nannou::geom::Range;
 use nannou::prelude::*;
+use nannou_sketches::cli::Cli;
+use nannou_sketches::gamepad::{Button, Gamepad};
+use nannou_sketches::image_sample::{Filter, Sampler};
+use nannou_sketches::midi::Midi;
+use nannou_sketches::osc::Osc;
+use nannou_sketches::params::Params;
+use nannou_sketches::physics::rigid::{RigidBody, RigidWorld};
+use nannou_sketches::physics::FixedTimestep;
+use nannou_sketches::viz::scale::window_scale;
+use nannou_sketches::webcam::Webcam;
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 
-#[derive(Debug)]
-struct Triangle {
-    pos: Vector2<f32>,
-    vel: Vector2<f32>,
-    r: f32,
-    angle: f32,
-    ang_vel: f32,
-}
-
 struct Model {
-    triangles: Vec<Triangle>,
+    world: RigidWorld,
+    timestep: FixedTimestep,
     image: nannou::image::RgbImage,
+    // with `--webcam`, the picture's its latest frame instead of bluebird.jpg.
+    webcam: Option<Webcam>,
+    // gravity, friction, how many triangles there are, and whether the picture's colors are
+    // blended; Tab shows them.
+    params: Params,
+    // the `KNOBS` turn the params too, if there's a controller plugged in.
+    midi: Option<Midi>,
+    // and so do OSC messages to `/bouncing_3/<param>` on `OSC_PORT`.
+    osc: Osc,
+    // and a game controller's triggers set gravity and friction, and A flips smooth colors.
+    gamepad: Gamepad,
 }
 
-const N: usize = 50;
-const GRAVITY: Vector2<f32> = Vector2 { x: 0.0, y: -5.0 };
+const STEP: f32 = 1.0 / 120.0;
+// the param each of a MIDI controller's first four knobs turns.
+const KNOBS: [(u8, &str); 4] = [
+    (21, "gravity"),
+    (22, "friction"),
+    (23, "triangles"),
+    (24, "smooth colors"),
+];
+const OSC_PORT: u16 = 9000;
+// how much of the shorter side of the window the box spans.
+const SCALE: f32 = 0.91;
 
 // domain is (-.5, .5) x (-.5, .5)
 const SIM_BOUNDS: Rect<f32> = Rect {
@@ -36,53 +58,124 @@ fn main() {
     nannou::app(model).event(event).simple_window(view).run();
 }
 
+fn cli() -> Cli {
+    Cli::new("bouncing_3").option::<usize>(
+        "webcam",
+        "Color the triangles from webcam INDEX (0 for the first) instead of bluebird.jpg",
+    )
+}
+
 fn model(_app: &App) -> Model {
-    let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
-    let triangles = (0..N)
-        .map(|_| Triangle {
-            pos: rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32),
-            vel: rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32),
-            r: rng.gen::<f32>() / 100.0 + 0.001,
-            angle: rng.gen::<f32>() * 2.0 * PI,
-            ang_vel: (rng.gen::<f32>() - 0.5) * 4.0 * PI,
-        })
-        .collect::<Vec<_>>();
+    let args = cli().parse();
+    let mut params = Params::new();
+    params.add_float("gravity", 5.0, 0.0, 20.0);
+    // how much the walls grip sliding corners, turning spin into throw and back.
+    params.add_float("friction", 0.3, 0.0, 1.0);
+    params.add_int("triangles", 50, 1, 500);
+    params.add_bool("smooth colors", true);
 
     let image = nannou::image::open("bluebird.jpg").unwrap().to_rgb();
+    let webcam = args.value::<usize>("webcam").map(Webcam::open);
+
+    let midi = match Midi::connect("") {
+        Ok(mut midi) => {
+            for (cc, name) in KNOBS.iter() {
+                midi.map(*cc, name);
+            }
+            Some(midi)
+        }
+        Err(e) => {
+            println!("no MIDI controller: {}", e);
+            None
+        }
+    };
+
+    let mut osc = Osc::new("bouncing_3");
+    if let Err(e) = osc.listen(OSC_PORT) {
+        println!("not listening for OSC: {}", e);
+    }
 
-    Model { triangles, image }
+    let mut gamepad = Gamepad::new();
+    gamepad.map_trigger(Button::RightTrigger2, "gravity");
+    gamepad.map_trigger(Button::LeftTrigger2, "friction");
+    gamepad.map_button(Button::South, "smooth colors");
+
+    Model {
+        world: world(params.int("triangles") as usize),
+        timestep: FixedTimestep::new(STEP),
+        image,
+        webcam,
+        params,
+        midi,
+        osc,
+        gamepad,
+    }
 }
 
-fn event(_app: &App, model: &mut Model, event: Event) {
+/// `n` triangles thrown about the box.
+fn world(n: usize) -> RigidWorld {
+    let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
+    let mut world = RigidWorld::new();
+    world.bounds = Some(SIM_BOUNDS);
+    world.bodies = (0..n)
+        .map(|_| {
+            let pos = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+            let r = rng.gen::<f32>() / 100.0 + 0.001;
+            let shape = [vec2(-r, -r), vec2(r, -r), vec2(0.0, r)];
+            // as heavy as it is big.
+            let mut tri = RigidBody::polygon(pos, &shape, 2.0 * r * r / (0.01 * 0.01));
+            tri.vel = rng.gen::<Vector2<f32>>() - vec2(0.5f32, 0.5f32);
+            tri.angle = rng.gen::<f32>() * 2.0 * PI;
+            tri.ang_vel = (rng.gen::<f32>() - 0.5) * 4.0 * PI;
+            tri
+        })
+        .collect::<Vec<_>>();
+    world
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    let win = app.window_rect();
     match event {
         Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            model.params.press(win, app.mouse.position());
+        }
+        Event::WindowEvent {
+            simple: Some(MouseMoved(pos)),
+            ..
+        } => model.params.move_to(win, pos),
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => model.params.release(),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Tab)),
+            ..
+        } => model.params.visible = !model.params.visible,
         _ => (),
     }
 }
 
 fn update(model: &mut Model, upd: Update) {
-    let dt = upd.since_last.as_secs_f32();
-    for tri in model.triangles.iter_mut() {
-        tri.vel += GRAVITY * dt;
+    if let Some(midi) = &model.midi {
+        midi.apply(&mut model.params);
     }
-    for tri in model.triangles.iter_mut() {
-        tri.pos += tri.vel * dt;
-        tri.angle += tri.ang_vel * dt;
-
-        if tri.pos.y - tri.r < SIM_BOUNDS.y.start {
-            tri.pos.y += (SIM_BOUNDS.y.start - (tri.pos.y - tri.r)) * 2.0;
-            tri.vel.y *= -1.0;
-        } else if tri.pos.y + tri.r > SIM_BOUNDS.y.end {
-            tri.pos.y -= ((tri.pos.y + tri.r) - SIM_BOUNDS.y.end) * 2.0;
-            tri.vel.y *= -1.0;
-        }
-        if tri.pos.x - tri.r < SIM_BOUNDS.x.start {
-            tri.pos.x += (SIM_BOUNDS.x.start - (tri.pos.x - tri.r)) * 2.0;
-            tri.vel.x *= -1.0;
-        } else if tri.pos.x + tri.r > SIM_BOUNDS.x.end {
-            tri.pos.x -= ((tri.pos.x + tri.r) - SIM_BOUNDS.x.end) * 2.0;
-            tri.vel.x *= -1.0;
-        }
+    model.osc.apply(&mut model.params);
+    model.gamepad.update();
+    model.gamepad.apply(&mut model.params);
+    if let Some(frame) = model.webcam.as_mut().and_then(Webcam::latest) {
+        model.image = frame;
+    }
+    if model.params.changed("triangles") {
+        model.world = world(model.params.int("triangles") as usize);
+    }
+    model.world.gravity = vec2(0.0, -model.params.float("gravity"));
+    model.world.friction = model.params.float("friction");
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        model.world.step(model.timestep.dt);
     }
 }
 
@@ -99,27 +192,35 @@ fn view(app: &App, model: &Model, frame: Frame) {
     let m = app.mouse.position();
     draw.ellipse().w_h(40.0, 40.0).xy(m).finish();
 
+    // unscaled, for the panel over the top.
+    let overlay = draw.clone();
+
     //let draw = draw.scale(m.x - win.x.start);
-    let draw = draw.scale(697.0);
-
-    let w = model.image.width() as f32;
-    let h = model.image.height() as f32;
-
-    for tri in &model.triangles {
-        let in_0_1 = (tri.pos - SIM_BOUNDS.bottom_left()) / SIM_BOUNDS.wh();
-        let color = model
-            .image
-            .get_pixel((in_0_1.x * w) as u32, ((1.0 - in_0_1.y) * h) as u32);
-
-        draw.translate(tri.pos.into())
-            .rotate(tri.angle.into())
-            .tri()
-            .points(
-                Point2::new(-tri.r, -tri.r),
-                Point2::new(tri.r, -tri.r),
-                Point2::new(0.0, tri.r),
-            )
-            .color(rgba(color.0[0], color.0[1], color.0[2], 255));
+    let draw = draw.scale(window_scale(&app.main_window(), SCALE));
+
+    // the picture's stretched over the box; triangles flung past its walls take its edge color.
+    let mut sampler = Sampler::new();
+    if !model.params.bool("smooth colors") {
+        sampler.filter = Filter::Nearest;
+    }
+    for tri in &model.world.bodies {
+        let color = sampler.sample_in(&model.image, SIM_BOUNDS, tri.pos);
+
+        let corners = tri.vertices();
+        draw.tri()
+            .points(corners[0], corners[1], corners[2])
+            .color(color);
+    }
+    model.params.draw(&overlay, app.window_rect());
+    // the picture stays as it was if the webcam fails, so say why it's not moving.
+    if let Some(error) = model.webcam.as_ref().and_then(|webcam| webcam.error.as_ref()) {
+        let win = app.window_rect();
+        overlay
+            .text(error)
+            .xy(win.mid_bottom() + vec2(0.0, 12.0))
+            .w(win.w())
+            .font_size(12)
+            .color(rgb8(200, 40, 40));
     }
     draw.to_frame(app, &frame).unwrap();
     frame.submit();
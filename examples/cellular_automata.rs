@@ -0,0 +1,151 @@
+use nannou::prelude::*;
+use nannou_sketches::automata::{CellGrid, Edges, Elementary, Life};
+use nannou_sketches::physics::FixedTimestep;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// px per cell.
+const CELL: f32 = 8.0;
+const STEP: f32 = 1.0 / 15.0;
+
+static HELP: &str =
+    "drag to paint, right drag to erase, M for Life or elementary, up/down to change rule, \
+     W to wrap, space to pause, R to randomize, C to clear";
+
+struct Model {
+    grid: CellGrid<bool>,
+    timestep: FixedTimestep,
+    // which elementary automaton to run, or `None` for Life.
+    elementary: Option<u8>,
+    paused: bool,
+    rng: XorShiftRng,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let win = app.window_rect();
+    let grid = CellGrid::new((win.w() / CELL) as usize, (win.h() / CELL) as usize, false);
+    let mut model = Model {
+        grid,
+        timestep: FixedTimestep::new(STEP),
+        elementary: None,
+        paused: false,
+        rng: SeedableRng::seed_from_u64(12345),
+    };
+    randomize(&mut model);
+    model
+}
+
+fn randomize(model: &mut Model) {
+    let grid = &mut model.grid;
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            grid.set(x, y, model.rng.gen::<f32>() < 0.25);
+        }
+    }
+}
+
+/// Which cell `pos`, on screen, is over, if any.
+fn cell(app: &App, model: &Model, pos: Vector2) -> Option<(usize, usize)> {
+    let cell = (pos - app.window_rect().bottom_left()) / CELL;
+    if cell.x < 0.0 || cell.y < 0.0 {
+        return None;
+    }
+    let (x, y) = (cell.x as usize, cell.y as usize);
+    if x < model.grid.width && y < model.grid.height {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::M => {
+                model.elementary = match model.elementary {
+                    None => Some(110),
+                    Some(_) => None,
+                }
+            }
+            Key::Up => model.elementary = model.elementary.map(|rule| rule.wrapping_add(1)),
+            Key::Down => model.elementary = model.elementary.map(|rule| rule.wrapping_sub(1)),
+            Key::W => {
+                model.grid.edges = match model.grid.edges {
+                    Edges::Wrap => Edges::Clamp,
+                    Edges::Clamp => Edges::Wrap,
+                }
+            }
+            Key::Space => model.paused = !model.paused,
+            Key::R => randomize(model),
+            Key::C => model.grid.fill(false),
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let paint = if app.mouse.buttons.left().is_down() {
+        Some(true)
+    } else if app.mouse.buttons.right().is_down() {
+        Some(false)
+    } else {
+        None
+    };
+    if let (Some(alive), Some((x, y))) = (paint, cell(app, model, app.mouse.position())) {
+        model.grid.set(x, y, alive);
+    }
+
+    let steps = model.timestep.advance(upd.since_last.as_secs_f32());
+    if model.paused {
+        return;
+    }
+    for _ in 0..steps {
+        match model.elementary {
+            Some(rule) => model.grid.step(&Elementary(rule)),
+            None => model.grid.step(&Life),
+        }
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(12, 12, 24));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    let corner = win.bottom_left() + vec2(CELL, CELL) / 2.0;
+    for (x, y, alive) in model.grid.cells() {
+        if *alive {
+            draw.rect()
+                .xy(corner + vec2(x as f32, y as f32) * CELL)
+                .w_h(CELL - 1.0, CELL - 1.0)
+                .color(rgb8(120, 230, 160));
+        }
+    }
+
+    let rule = match model.elementary {
+        Some(rule) => format!("rule {}", rule),
+        None => "Life".to_string(),
+    };
+    let edges = match model.grid.edges {
+        Edges::Wrap => "wrapping",
+        Edges::Clamp => "clamped",
+    };
+    let paused = if model.paused { ", paused" } else { "" };
+    draw.text(&format!("{}, {}{}\n{}", rule, edges, paused, HELP))
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,152 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::chladni::{Mode, Plate, Sand};
+use nannou_sketches::viz::palette::Palette;
+
+const GRAINS: usize = 8000;
+// the highest mode the plate rings in.
+const ORDER: u32 = 8;
+// how fast the frequency rises when sweeping, per second.
+const SWEEP_RATE: f32 = 1.5;
+// how many px across the picture of the plate's shaking is.
+const SHAKING_RESOLUTION: u32 = 128;
+
+static HELP: &str = "left/right and up/down to pick a mode, S to sweep the frequency, \
+                     V to show the shaking, space to scatter the sand";
+
+struct Model {
+    plate: Plate,
+    sand: Sand,
+    mode: Mode,
+    sweeping: bool,
+    show_shaking: bool,
+    palette: Palette,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let mode = Mode { m: 1, n: 4 };
+    let mut plate = Plate::new(ORDER);
+    plate.frequency = mode.frequency();
+    Model {
+        plate,
+        sand: Sand::new(GRAINS, 1),
+        mode,
+        sweeping: false,
+        show_shaking: false,
+        palette: Palette::new(&[Srgb::new(30, 25, 20), Srgb::new(120, 70, 40)]),
+    }
+}
+
+fn event(_app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            let Mode { m, n } = model.mode;
+            let mode = match key {
+                Key::Left => Some(Mode {
+                    m: m.saturating_sub(1),
+                    n,
+                }),
+                Key::Right => Some(Mode {
+                    m: (m + 1).min(n - 1),
+                    n,
+                }),
+                Key::Down => {
+                    let n = (n - 1).max(1);
+                    Some(Mode { m: m.min(n - 1), n })
+                }
+                Key::Up => Some(Mode {
+                    m,
+                    n: (n + 1).min(ORDER),
+                }),
+                Key::S => {
+                    model.sweeping = !model.sweeping;
+                    None
+                }
+                Key::V => {
+                    model.show_shaking = !model.show_shaking;
+                    None
+                }
+                Key::Space => {
+                    model.sand.scatter();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(mode) = mode {
+                model.mode = mode;
+                model.plate.frequency = mode.frequency();
+                model.sweeping = false;
+            }
+        }
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32().min(0.1);
+    if model.sweeping {
+        let modes = model.plate.modes();
+        let (lowest, highest) = (modes[0].frequency(), modes[modes.len() - 1].frequency());
+        model.plate.frequency += SWEEP_RATE * dt;
+        if model.plate.frequency > highest {
+            model.plate.frequency = lowest;
+        }
+    }
+    model.sand.step(&model.plate, dt);
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(10, 8, 6));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let size = win.w().min(win.h()) * 0.9;
+    // the plate goes from -1 to 1.
+    let scale = size / 2.0;
+
+    if model.show_shaking {
+        // images go from the top down, and the plate from the bottom up.
+        let pixels = RgbaImage::from_fn(SHAKING_RESOLUTION, SHAKING_RESOLUTION, |x, y| {
+            let pos = vec2(x as f32, (SHAKING_RESOLUTION - 1 - y) as f32) + vec2(0.5, 0.5);
+            let pos = pos / SHAKING_RESOLUTION as f32 * 2.0 - vec2(1.0, 1.0);
+            let shaking = model.plate.amplitude(pos).abs() / 2.0;
+            let [r, g, b] = model.palette.sample_u8(shaking);
+            image::Rgba([r, g, b, 255])
+        });
+        let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+        draw.texture(&texture).w_h(size, size);
+    } else {
+        draw.rect().w_h(size, size).color(rgb8(30, 25, 20));
+    }
+
+    for grain in model.sand.grains() {
+        draw.rect()
+            .xy(*grain * scale)
+            .w_h(1.5, 1.5)
+            .color(rgb8(240, 220, 170));
+    }
+
+    let mode = if model.sweeping {
+        "sweeping".to_string()
+    } else {
+        format!("mode ({}, {})", model.mode.m, model.mode.n)
+    };
+    draw.text(&format!(
+        "{}, frequency {:.2}\n{}",
+        mode, model.plate.frequency, HELP
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 20.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
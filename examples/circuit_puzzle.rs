@@ -0,0 +1,421 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::challenge::{ChallengePack, Progress};
+use nannou_sketches::circuits::history::History;
+use nannou_sketches::circuits::*;
+use nannou_sketches::render_circuit::{draw_circuit, node_at, CircuitStyle};
+use petgraph::graph::NodeIndex;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const CHALLENGE_DIR: &str = "challenges";
+const SELECT_RADIUS: f32 = 20.0;
+
+static HELP: &str = "click: select    right click: toggle input
+a / o / x: and / or / xor of 2 selected    n: not of 1 selected
+enter: wire selected to next output    backspace: start over    z / y: undo / redo
+c: check    h: hint    left / right: challenge    up / down: pack";
+
+struct Pack {
+    path: PathBuf,
+    pack: ChallengePack,
+    progress: Progress,
+}
+
+struct Model {
+    packs: Vec<Pack>,
+    pack: usize,
+    challenge: usize,
+
+    circuit: Circuit,
+    history: History,
+    inputs: Vec<NodeIndex>,
+    outputs: Vec<NodeIndex>,
+    // positions in (0, 1) x (0, 1), by id, so they follow gates renumbered by undo and redo.
+    // undone gates keep their positions, in case they're redone; see `live_positions`.
+    positions: HashMap<GateId, Vector2>,
+    update_order: Vec<NodeIndex>,
+
+    selected: Vec<NodeIndex>,
+    hints_shown: usize,
+    message: String,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(CHALLENGE_DIR));
+    let (packs, errors) = ChallengePack::load_dir(&dir)
+        .unwrap_or_else(|e| panic!("failed to read challenge dir {:?}: {}", dir, e));
+    for (path, error) in &errors {
+        println!("skipping {:?}: {}", path, error);
+    }
+    let packs = packs
+        .into_iter()
+        .filter(|(_, pack)| !pack.challenges.is_empty())
+        .map(|(path, pack)| Pack {
+            progress: Progress::load(&path),
+            path,
+            pack,
+        })
+        .collect::<Vec<_>>();
+    assert!(!packs.is_empty(), "no challenge packs found in {:?}", dir);
+
+    let mut model = Model {
+        packs,
+        pack: 0,
+        challenge: 0,
+        circuit: Circuit::new(),
+        history: History::new(),
+        inputs: vec![],
+        outputs: vec![],
+        positions: HashMap::new(),
+        update_order: vec![],
+        selected: vec![],
+        hints_shown: 0,
+        message: String::new(),
+    };
+    reset(&mut model);
+    model
+}
+
+/// Throw away the player's circuit and start the current challenge from scratch.
+fn reset(model: &mut Model) {
+    let challenge = &model.packs[model.pack].pack.challenges[model.challenge];
+
+    model.circuit = Circuit::new();
+    model.history.clear();
+    model.positions.clear();
+    model.inputs = (0..challenge.inputs.len())
+        .map(|_| model.circuit.add_input())
+        .collect();
+    for (i, input) in model.inputs.iter().enumerate() {
+        let y = 1.0 - (i + 1) as f32 / (model.inputs.len() + 1) as f32;
+        model
+            .positions
+            .insert(model.circuit.id(*input), vec2(0.0, y));
+    }
+    model.outputs.clear();
+    model.update_order = model.circuit.update_order();
+    model.selected.clear();
+    model.hints_shown = 0;
+    model.message = String::new();
+}
+
+/// Catch up with the circuit after undo or redo.
+fn history_changed(model: &mut Model) {
+    let circuit = &model.circuit;
+    model.outputs = circuit
+        .graph()
+        .node_indices()
+        .filter(|n| circuit.graph()[*n] == Gate::Output)
+        .collect();
+    model.update_order = circuit.update_order();
+    model.selected.clear();
+}
+
+/// Positions of the gates currently in the circuit.
+fn live_positions(model: &Model) -> HashMap<NodeIndex, Vector2> {
+    model
+        .positions
+        .iter()
+        .filter_map(|(id, p)| model.circuit.node(*id).map(|n| (n, *p)))
+        .collect()
+}
+
+fn output_position(model: &Model, i: usize) -> Vector2 {
+    let n = model.packs[model.pack].pack.challenges[model.challenge]
+        .outputs
+        .len();
+    vec2(1.0, 1.0 - (i + 1) as f32 / (n + 1) as f32)
+}
+
+fn switch_to(model: &mut Model, pack: usize, challenge: usize) {
+    model.pack = pack;
+    model.challenge = challenge;
+    reset(model);
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => {
+            model.circuit.update_signals_once(&model.update_order);
+        }
+        Event::WindowEvent {
+            simple: Some(MousePressed(button)),
+            ..
+        } => mouse_pressed(app, model, button),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => key_pressed(app, model, key),
+        _ => (),
+    }
+}
+
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    let map_pos = make_map_pos(app.window_rect());
+    let mouse = app.mouse.position();
+    let nearest = node_at(&live_positions(model), map_pos, mouse, SELECT_RADIUS);
+
+    match (button, nearest) {
+        (MouseButton::Left, Some(node)) => {
+            if let Some(i) = model.selected.iter().position(|n| *n == node) {
+                model.selected.remove(i);
+            } else {
+                model.selected.push(node);
+                if model.selected.len() > 2 {
+                    model.selected.remove(0);
+                }
+            }
+        }
+        (MouseButton::Left, None) => model.selected.clear(),
+        (MouseButton::Right, Some(node)) if model.circuit.graph()[node] == Gate::Input => {
+            let current = model.circuit.get_1_in(node);
+            model.circuit.set_input(node, !current);
+        }
+        _ => (),
+    }
+}
+
+fn key_pressed(app: &App, model: &mut Model, key: Key) {
+    let unmap_pos = make_unmap_pos(app.window_rect());
+    let at = unmap_pos(app.mouse.position());
+
+    let (circuit, history) = (&mut model.circuit, &mut model.history);
+    let added = match (key, &model.selected[..]) {
+        (Key::A, &[a, b]) => Some(history.record(circuit, |c| c.add_and(a, b))),
+        (Key::O, &[a, b]) => Some(history.record(circuit, |c| c.add_or(a, b))),
+        (Key::X, &[a, b]) => Some(history.record(circuit, |c| c.add_xor(a, b))),
+        (Key::N, &[a]) => Some(history.record(circuit, |c| c.add_not(a))),
+        (Key::A, _) | (Key::O, _) | (Key::X, _) => {
+            model.message = "select 2 nodes first".into();
+            None
+        }
+        (Key::N, _) => {
+            model.message = "select 1 node first".into();
+            None
+        }
+        _ => None,
+    };
+    if let Some(node) = added {
+        model.positions.insert(model.circuit.id(node), at);
+        model.update_order = model.circuit.update_order();
+        model.selected = vec![node];
+        return;
+    }
+
+    let n_packs = model.packs.len();
+    let n_challenges = model.packs[model.pack].pack.challenges.len();
+    match key {
+        Key::Return => {
+            let n_outputs = model.packs[model.pack].pack.challenges[model.challenge]
+                .outputs
+                .len();
+            match &model.selected[..] {
+                _ if model.outputs.len() == n_outputs => {
+                    model.message = "all outputs are wired".into();
+                }
+                &[node] if model.circuit.graph()[node] != Gate::Output => {
+                    let output = model
+                        .history
+                        .record(&mut model.circuit, |c| c.add_output(node));
+                    let position = output_position(model, model.outputs.len());
+                    model.positions.insert(model.circuit.id(output), position);
+                    model.outputs.push(output);
+                    model.update_order = model.circuit.update_order();
+                    model.selected.clear();
+                }
+                _ => model.message = "select 1 gate or input first".into(),
+            }
+        }
+        Key::Back => reset(model),
+        Key::Z => {
+            if model.history.undo(&mut model.circuit) {
+                history_changed(model);
+            } else {
+                model.message = "nothing to undo".into();
+            }
+        }
+        Key::Y => {
+            if model.history.redo(&mut model.circuit) {
+                history_changed(model);
+            } else {
+                model.message = "nothing to redo".into();
+            }
+        }
+        Key::C => check(model),
+        Key::H => {
+            let hints = &model.packs[model.pack].pack.challenges[model.challenge].hints;
+            if model.hints_shown < hints.len() {
+                model.hints_shown += 1;
+            } else {
+                model.message = "no more hints".into();
+            }
+        }
+        Key::Right => switch_to(model, model.pack, (model.challenge + 1) % n_challenges),
+        Key::Left => switch_to(
+            model,
+            model.pack,
+            (model.challenge + n_challenges - 1) % n_challenges,
+        ),
+        Key::Down => switch_to(model, (model.pack + 1) % n_packs, 0),
+        Key::Up => switch_to(model, (model.pack + n_packs - 1) % n_packs, 0),
+        _ => (),
+    }
+}
+
+fn check(model: &mut Model) {
+    let pack = &mut model.packs[model.pack];
+    let challenge = &pack.pack.challenges[model.challenge];
+
+    // checking drives the inputs itself; put them back afterwards.
+    let before = model
+        .inputs
+        .iter()
+        .map(|i| model.circuit.get_1_in(*i))
+        .collect::<Vec<_>>();
+    let result = challenge.check(&mut model.circuit, &model.inputs, &model.outputs);
+    for (input, value) in model.inputs.iter().zip(before) {
+        model.circuit.set_input(*input, value);
+    }
+
+    model.message = match result {
+        Ok(()) => {
+            pack.progress.completed.insert(challenge.name.clone());
+            match pack.progress.save(&pack.path) {
+                Ok(()) => "solved!".into(),
+                Err(e) => format!("solved! (but failed to save progress: {})", e),
+            }
+        }
+        Err(failure) => format!("not quite: {}", failure),
+    };
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // leave the left third of the window for text.
+    let bl = win.bottom_left() + vec2(win.w() * 0.4, win.h() * 0.1);
+    let size = vec2(win.w() * 0.5, win.h() * 0.8);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn make_unmap_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    let bl = win.bottom_left() + vec2(win.w() * 0.4, win.h() * 0.1);
+    let size = vec2(win.w() * 0.5, win.h() * 0.8);
+    move |p: Vector2| vec2((p.x - bl.x) / size.x, (p.y - bl.y) / size.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(50, 50, 50));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+
+    let pack = &model.packs[model.pack];
+    let challenge = &pack.pack.challenges[model.challenge];
+
+    let style = CircuitStyle {
+        labels: false,
+        ..CircuitStyle::default()
+    };
+    let positions = live_positions(model);
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &positions,
+        &HashMap::new(),
+        &map_pos,
+        &style,
+    );
+
+    // outputs that haven't been wired yet.
+    for i in model.outputs.len()..challenge.outputs.len() {
+        draw.ellipse()
+            .xy(map_pos(output_position(model, i)))
+            .w_h(20.0, 20.0)
+            .no_fill()
+            .stroke(rgb8(100, 100, 100))
+            .stroke_weight(2.0);
+    }
+
+    for node in &model.selected {
+        draw.ellipse()
+            .xy(map_pos(positions[node]))
+            .w_h(30.0, 30.0)
+            .no_fill()
+            .stroke(rgb8(100, 100, 200))
+            .stroke_weight(3.0);
+    }
+    for (input, label) in model.inputs.iter().zip(&challenge.inputs) {
+        draw.text(label)
+            .xy(map_pos(positions[input]) - vec2(40.0, 0.0))
+            .color(rgb8(255, 255, 255));
+    }
+    for (i, label) in challenge.outputs.iter().enumerate() {
+        draw.text(label)
+            .xy(map_pos(output_position(model, i)) + vec2(40.0, 0.0))
+            .color(rgb8(255, 255, 255));
+    }
+
+    let mut text = format!(
+        "{} ({}/{})\n\n{}{}\n{}\n\n",
+        pack.pack.name,
+        model.challenge + 1,
+        pack.pack.challenges.len(),
+        challenge.name,
+        if pack.progress.completed.contains(&challenge.name) {
+            " [done]"
+        } else {
+            ""
+        },
+        challenge.description,
+    );
+    text += &format!(
+        "{} | {}\n",
+        challenge.inputs.join(" "),
+        challenge.outputs.join(" ")
+    );
+    for (ins, outs) in &challenge.truth_table {
+        let bits = |values: &Vec<Value>| {
+            values
+                .iter()
+                .map(|v| if *v { "1" } else { "0" })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        text += &format!("{} | {}\n", bits(ins), bits(outs));
+    }
+    if let Some(total) = challenge.budget.total {
+        text += &format!("\nat most {} gates\n", total);
+    }
+    for (gate, allowed) in &challenge.budget.gates {
+        text += &format!("at most {} {:?} gates\n", allowed, gate);
+    }
+    for hint in &challenge.hints[..model.hints_shown] {
+        text += &format!("\nhint: {}\n", hint);
+    }
+    text += &format!("\n{}", model.message);
+
+    let panel = Rect::from_w_h(win.w() * 0.3, win.h() * 0.8)
+        .top_left_of(win)
+        .shift(vec2(20.0, -20.0));
+    draw.text(&text)
+        .xy(panel.xy())
+        .wh(panel.wh())
+        .left_justify()
+        .align_text_top()
+        .color(rgb8(255, 255, 255));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 40.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
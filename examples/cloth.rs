@@ -0,0 +1,146 @@
+use nannou::prelude::*;
+use nannou_sketches::physics::cloth::{Cloth, SpringKind};
+use nannou_sketches::physics::drag::Dragger;
+use nannou_sketches::physics::FixedTimestep;
+use nannou_sketches::wind::Wind;
+
+const WIDTH: usize = 40;
+const HEIGHT: usize = 25;
+const SPACING: f32 = 14.0;
+const GRAVITY: Vector2 = Vector2 { x: 0.0, y: -400.0 };
+// the same wind as pattern_2, scaled up to this gravity.
+const WIND_MAG: f32 = 50.0;
+const TEAR_STRETCH: f32 = 2.5;
+// how close to the mouse springs are cut while dragging.
+const CUT_RADIUS: f32 = 6.0;
+const STEP: f32 = 1.0 / 240.0;
+const DRAG_STIFFNESS: f32 = 2000.0;
+
+static HELP: &str = "drag to cut, right-drag to grab, R to start over";
+
+struct Model {
+    cloth: Cloth,
+    timestep: FixedTimestep,
+    cutting: bool,
+    dragger: Dragger,
+    wind: Wind,
+    // simulated time, for the wind.
+    elapsed: f32,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let mut dragger = Dragger::new(DRAG_STIFFNESS);
+    dragger.reach = SPACING;
+    Model {
+        cloth: new_cloth(app.window_rect()),
+        timestep: FixedTimestep::new(STEP),
+        cutting: false,
+        dragger,
+        wind: Wind::new(WIND_MAG),
+        elapsed: 0.0,
+    }
+}
+
+/// A fresh cloth, hanging from near the top of the window.
+fn new_cloth(win: Rect) -> Cloth {
+    let mut cloth = Cloth::grid(WIDTH, HEIGHT, SPACING);
+    cloth.world.gravity = GRAVITY;
+    cloth.world.damping = 0.995;
+    cloth.tear_stretch = Some(TEAR_STRETCH);
+    let top_left = vec2(-(WIDTH - 1) as f32 * SPACING / 2.0, win.top() - 40.0);
+    for particle in &mut cloth.world.particles {
+        particle.pos += top_left;
+    }
+    cloth
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => model.cutting = true,
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => model.cutting = false,
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Right)),
+            ..
+        } => {
+            model
+                .dragger
+                .press(&model.cloth.world.particles, app.mouse.position());
+        }
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Right)),
+            ..
+        } => model.dragger.release(),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => {
+            model.cloth = new_cloth(app.window_rect());
+            model.dragger.release();
+        }
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    model.dragger.move_to(app.mouse.position());
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        let dt = model.timestep.dt;
+        model.elapsed += dt;
+        for particle in &mut model.cloth.world.particles {
+            if particle.pinned {
+                continue;
+            }
+            let wind = model.wind.force(particle.pos, model.elapsed);
+            particle.vel += wind / particle.mass * dt;
+        }
+        model.dragger.pull(&mut model.cloth.world, dt);
+        model.cloth.step(dt);
+    }
+    if model.cutting {
+        model.cloth.cut(app.mouse.position(), CUT_RADIUS);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(244, 234, 172));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    let particles = &model.cloth.world.particles;
+    for spring in model.cloth.springs(SpringKind::Structural) {
+        let (a, b) = (particles[spring.a].pos, particles[spring.b].pos);
+        // darker the more it's stretched, up to where it tears.
+        let stretch = ((a - b).magnitude() / spring.rest_length - 1.0) / (TEAR_STRETCH - 1.0);
+        let stretch = stretch.max(0.0).min(1.0);
+        draw.line().start(a).end(b).weight(1.5).color(rgb8(
+            238,
+            (168.0 * (1.0 - stretch)) as u8,
+            0,
+        ));
+    }
+    for particle in particles.iter().filter(|p| p.pinned) {
+        draw.ellipse()
+            .xy(particle.pos)
+            .w_h(5.0, 5.0)
+            .color(rgb8(197, 50, 0));
+    }
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(56, 26, 6));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,211 @@
+use nannou::event::MouseScrollDelta;
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+
+const BITS: usize = 6;
+
+// seconds per clock cycle.
+const START_PERIOD: f32 = 0.5;
+const MIN_PERIOD: f32 = 0.02;
+const MAX_PERIOD: f32 = 4.0;
+// each line of mouse wheel scrolling changes the period by this factor.
+const PERIOD_PER_LINE: f32 = 1.1;
+
+// how quickly a light's flash fades after its bit toggles, per second.
+const FLASH_DECAY: f32 = 6.0;
+
+static HELP: &str = "scroll: change the clock period";
+
+struct Model {
+    circuit: Circuit,
+    clock: NodeIndex,
+    q: Vec<NodeIndex>,
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+
+    period: f32,
+    // how far through the current clock cycle we are, in seconds.
+    phase: f32,
+    // when each bit last changed.
+    last_toggle: Vec<f32>,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+    let clock = circuit.add_input();
+    circuit.set_label(clock, "clock");
+    let q = circuit.counter(BITS, clock);
+    for (i, bit) in q.iter().enumerate() {
+        circuit.set_label(*bit, &format!("q{}", i));
+    }
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    Model {
+        circuit,
+        clock,
+        q,
+        update_order,
+        settle_steps,
+        positions,
+        routes,
+        period: START_PERIOD,
+        phase: 0.0,
+        last_toggle: vec![-1.0; BITS],
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MouseWheel(delta, _)),
+            ..
+        } => {
+            let lines = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+            };
+            // scrolling up speeds the clock up.
+            model.period = (model.period * PERIOD_PER_LINE.powf(-lines))
+                .max(MIN_PERIOD)
+                .min(MAX_PERIOD);
+            model.phase = model.phase.min(model.period);
+        }
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let t = app.duration.since_start.as_secs_f32();
+    model.phase += upd.since_last.as_secs_f32();
+    while model.phase >= model.period {
+        model.phase -= model.period;
+    }
+
+    // high for the first half of each cycle.
+    let clock = model.phase < model.period / 2.0;
+    if clock == model.circuit.get_1_in(model.clock) {
+        return;
+    }
+    let before = read_count(model);
+    model.circuit.set_input(model.clock, clock);
+    for _ in 0..model.settle_steps {
+        model.circuit.update_signals_once(&model.update_order);
+    }
+    let after = read_count(model);
+    for i in 0..BITS {
+        if get_bit(before, i) != get_bit(after, i) {
+            model.last_toggle[i] = t;
+        }
+    }
+}
+
+fn read_count(model: &Model) -> usize {
+    let mut count = 0;
+    for (i, q) in model.q.iter().enumerate() {
+        count = set_bit(count, i, model.circuit.get_1_in(*q));
+    }
+    count
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // the top half of the window, leaving the bottom for the lights.
+    let bl = win.bottom_left() + vec2(win.w() * 0.1, win.h() * 0.5);
+    let size = vec2(win.w() * 0.8, win.h() * 0.4);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(20, 20, 28));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+    let t = app.duration.since_start.as_secs_f32();
+
+    let style = CircuitStyle {
+        gate_size: 14.0,
+        wire_weight: 2.0,
+        ..CircuitStyle::default()
+    };
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+
+    // make the clock hard to miss.
+    let clock = model.circuit.get_1_in(model.clock);
+    draw.ellipse()
+        .xy(map_pos(model.positions[&model.clock]))
+        .w_h(36.0, 36.0)
+        .no_fill()
+        .stroke(if clock {
+            rgb8(255, 220, 80)
+        } else {
+            rgb8(80, 80, 90)
+        })
+        .stroke_weight(4.0);
+
+    // a light per bit, most significant on the left like a written number.
+    let spacing = win.w() * 0.8 / BITS as f32;
+    let y = win.bottom() + win.h() * 0.25;
+    for i in 0..BITS {
+        let x = win.w() * 0.4 - spacing * (i as f32 + 0.5);
+        let on = model.circuit.get_1_in(model.q[i]);
+        let flash = (-(t - model.last_toggle[i]) * FLASH_DECAY).exp();
+        let brightness = if on { 0.8 } else { 0.12 } + 0.2 * flash;
+        let size = spacing * 0.5 * (1.0 + 0.15 * flash);
+
+        draw.ellipse().x_y(x, y).w_h(size, size).color(rgba(
+            brightness,
+            brightness * 0.85,
+            brightness * 0.3,
+            1.0,
+        ));
+
+        // bit i toggles every 2^i cycles.
+        let toggles_per_second = 1.0 / (model.period * (1 << i) as f32);
+        draw.text(&format!("q{}\n{:.2} /s", i, toggles_per_second))
+            .x_y(x, y - spacing * 0.5)
+            .w(spacing)
+            .font_size(12)
+            .color(rgb8(180, 180, 180));
+    }
+
+    draw.text(&format!(
+        "count {}    clock period {:.3}s",
+        read_count(model),
+        model.period
+    ))
+    .xy(win.mid_top() - vec2(0.0, 30.0))
+    .w(win.w())
+    .font_size(16)
+    .color(rgb8(255, 255, 255));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,468 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
+
+const BITS: usize = 4;
+const REGISTERS: usize = 4;
+// the instruction bus: opcode, destination register, source register, immediate.
+const OP_BITS: usize = 3;
+const REG_BITS: usize = 2;
+const INSTRUCTION_BITS: usize = OP_BITS + 2 * REG_BITS + BITS;
+// seconds per instruction.
+const STEP_EVERY: f32 = 0.8;
+
+/// The instruction set. The first four are `AluOp`s, with the same opcodes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Op {
+    /// rd = rd + rs
+    Add = 0,
+    /// rd = rd - rs
+    Sub = 1,
+    /// rd = rd & rs
+    And = 2,
+    /// rd = rd | rs
+    Or = 3,
+    /// rd = imm
+    Ldi = 4,
+    /// rd = rs
+    Mov = 5,
+    /// if rd != 0, jump to imm
+    Jnz = 6,
+    /// jump to imm
+    Jmp = 7,
+}
+
+/// (op, rd, rs, imm)
+type Instruction = (Op, usize, usize, usize);
+
+// multiply 3 by 5, by adding 3 up 5 times. The product ends up in r2.
+const PROGRAM: [Instruction; 8] = [
+    (Op::Ldi, 0, 0, 5),
+    (Op::Ldi, 1, 0, 3),
+    (Op::Ldi, 2, 0, 0),
+    (Op::Ldi, 3, 0, 1),
+    (Op::Add, 2, 1, 0),
+    (Op::Sub, 0, 3, 0),
+    (Op::Jnz, 0, 0, 4),
+    (Op::Jmp, 0, 0, 7),
+];
+
+static HELP: &str = "space: pause; right: step one instruction; R: reset";
+
+/// Gates making up each part of the CPU, for highlighting the datapath.
+struct Parts {
+    registers: Vec<Vec<NodeIndex>>,
+    pc: Vec<NodeIndex>,
+    read_a: Vec<NodeIndex>,
+    read_b: Vec<NodeIndex>,
+    alu: Alu,
+    write_select: Vec<NodeIndex>,
+    write_decode: Vec<NodeIndex>,
+    // the load logic in front of each register.
+    register_in: Vec<Vec<NodeIndex>>,
+    next_pc: Vec<NodeIndex>,
+}
+
+struct Model {
+    circuit: Circuit,
+    clock: NodeIndex,
+    instruction: Vec<NodeIndex>,
+    parts: Parts,
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+
+    paused: bool,
+    cycles: u32,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+/// Build part of the CPU, noting which gates it added.
+fn part<T>(circuit: &mut Circuit, build: impl FnOnce(&mut Circuit) -> T) -> (T, Vec<NodeIndex>) {
+    let start = circuit.graph().node_count();
+    let result = build(circuit);
+    let gates = (start..circuit.graph().node_count())
+        .map(NodeIndex::new)
+        .collect();
+    (result, gates)
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+    let clock = circuit.add_input();
+    circuit.set_label(clock, "clock");
+
+    let instruction = (0..INSTRUCTION_BITS)
+        .map(|_| circuit.add_input())
+        .collect::<Vec<_>>();
+    let (op, rest) = instruction.split_at(OP_BITS);
+    let (rd, rest) = rest.split_at(REG_BITS);
+    let (rs, imm) = rest.split_at(REG_BITS);
+    for (name, field) in [("op", op), ("rd", rd), ("rs", rs), ("imm", imm)].iter() {
+        for (i, bit) in field.iter().enumerate() {
+            circuit.set_label(*bit, &format!("{}{}", name, i));
+        }
+    }
+
+    let registers = (0..REGISTERS)
+        .map(|r| {
+            (0..BITS)
+                .map(|i| {
+                    let q = circuit.add_flip_flop(false);
+                    circuit.set_label(q, &format!("r{}.{}", r, i));
+                    q
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let pc = (0..BITS)
+        .map(|i| {
+            let q = circuit.add_flip_flop(false);
+            circuit.set_label(q, &format!("pc{}", i));
+            q
+        })
+        .collect::<Vec<_>>();
+
+    // read the two operands out of the register file, and do arithmetic on them.
+    let (a, read_a) = part(&mut circuit, |c| c.mux_tree(rd, &registers));
+    let (b, read_b) = part(&mut circuit, |c| c.mux_tree(rs, &registers));
+    let alu = circuit.alu(&a, &b, [op[0], op[1]]);
+
+    // write back the ALU's result, the immediate (ldi) or the source register (mov).
+    let (data, write_select) = part(&mut circuit, |c| {
+        let loaded = c.mux(op[0], imm, &b);
+        c.mux(op[2], &alu.result, &loaded)
+    });
+    // ...to the destination register, unless this is a jump.
+    let ((jump, lines), write_decode) = part(&mut circuit, |c| {
+        let jump = c.add_and(op[2], op[1]);
+        let write = c.add_not(jump);
+        let lines = c
+            .decoder(rd)
+            .into_iter()
+            .map(|line| c.add_and(line, write))
+            .collect::<Vec<_>>();
+        (jump, lines)
+    });
+    let register_in = registers
+        .iter()
+        .zip(lines)
+        .map(|(register, load)| {
+            part(&mut circuit, |c| {
+                c.connect_register(register, &data, load, clock)
+            })
+            .1
+        })
+        .collect();
+
+    // jmp always jumps; jnz jumps if rd isn't 0.
+    let ((), next_pc) = part(&mut circuit, |c| {
+        let (next, _) = c.incrementer(&pc);
+        let nonzero = c.any(&a);
+        let condition = c.add_or(op[0], nonzero);
+        let taken = c.add_and(jump, condition);
+        let target = c.mux(taken, &next, imm);
+        for (q, d) in pc.iter().zip(target) {
+            c.connect_flip_flop(*q, d, clock);
+        }
+    });
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    let mut model = Model {
+        circuit,
+        clock,
+        instruction,
+        parts: Parts {
+            registers,
+            pc,
+            read_a,
+            read_b,
+            alu,
+            write_select,
+            write_decode,
+            register_in,
+            next_pc,
+        },
+        update_order,
+        settle_steps,
+        positions,
+        routes,
+        paused: false,
+        cycles: 0,
+    };
+    fetch(&mut model);
+    model
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => {
+            model.paused = !model.paused;
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Right)),
+            ..
+        } => {
+            step(model);
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => {
+            let parts = &model.parts;
+            for q in parts.registers.iter().flatten().chain(&parts.pc) {
+                model.circuit.set_flip_flop(*q, false);
+            }
+            model.cycles = 0;
+            fetch(model);
+        }
+        _ => (),
+    }
+}
+
+fn epoch(t: f32) -> u32 {
+    (t / STEP_EVERY).floor() as u32
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32();
+    let t = app.duration.since_start.as_secs_f32();
+    if !model.paused && epoch(t - dt) < epoch(t) {
+        step(model);
+    }
+}
+
+fn settle(model: &mut Model) {
+    for _ in 0..model.settle_steps {
+        model.circuit.update_signals_once(&model.update_order);
+    }
+}
+
+/// The instruction at an address. Past the end of the program, the CPU halts by jumping to
+/// where it already is.
+fn instruction_at(address: usize) -> Instruction {
+    PROGRAM
+        .get(address)
+        .cloned()
+        .unwrap_or((Op::Jmp, 0, 0, address))
+}
+
+fn encode((op, rd, rs, imm): Instruction) -> usize {
+    op as usize | rd << OP_BITS | rs << (OP_BITS + REG_BITS) | imm << (OP_BITS + 2 * REG_BITS)
+}
+
+fn disassemble((op, rd, rs, imm): Instruction) -> String {
+    match op {
+        Op::Ldi => format!("ldi r{}, {}", rd, imm),
+        Op::Mov => format!("mov r{}, r{}", rd, rs),
+        Op::Jnz => format!("jnz r{}, {}", rd, imm),
+        Op::Jmp => format!("jmp {}", imm),
+        _ => {
+            let name = format!("{:?}", op).to_lowercase();
+            format!("{} r{}, r{}", name, rd, rs)
+        }
+    }
+}
+
+fn read(circuit: &Circuit, bits: &[NodeIndex]) -> usize {
+    let mut value = 0;
+    for (i, bit) in bits.iter().enumerate() {
+        value = set_bit(value, i, circuit.get_1_in(*bit));
+    }
+    value
+}
+
+/// Put the instruction the program counter points at on the instruction bus.
+/// The memory holding the program lives outside the circuit.
+fn fetch(model: &mut Model) {
+    let pc = read(&model.circuit, &model.parts.pc);
+    let word = encode(instruction_at(pc));
+    for (i, bit) in model.instruction.iter().enumerate() {
+        model.circuit.set_input(*bit, get_bit(word, i));
+    }
+    settle(model);
+}
+
+/// Run the instruction on the bus, with one clock cycle, and fetch the next one.
+fn step(model: &mut Model) {
+    for value in [true, false].iter() {
+        model.circuit.set_input(model.clock, *value);
+        settle(model);
+    }
+    model.cycles += 1;
+    fetch(model);
+}
+
+/// The gates the current instruction is using.
+fn active_gates(model: &Model) -> HashSet<NodeIndex> {
+    let parts = &model.parts;
+    let pc = read(&model.circuit, &parts.pc);
+    let (op, rd, rs, _) = instruction_at(pc);
+
+    let mut active = model.instruction.clone();
+    active.extend(parts.pc.iter().chain(&parts.next_pc));
+    let write = |active: &mut Vec<NodeIndex>| {
+        active.extend(parts.write_select.iter().chain(&parts.write_decode));
+        active.extend(parts.register_in[rd].iter().chain(&parts.registers[rd]));
+    };
+    match op {
+        Op::Add | Op::Sub | Op::And | Op::Or => {
+            active.extend(parts.registers[rs].iter().chain(&parts.read_b));
+            active.extend(parts.read_a.iter().chain(&parts.alu.select));
+            active.extend(&parts.alu.units[op as usize]);
+            write(&mut active);
+        }
+        Op::Ldi => write(&mut active),
+        Op::Mov => {
+            active.extend(parts.registers[rs].iter().chain(&parts.read_b));
+            write(&mut active);
+        }
+        Op::Jnz => active.extend(parts.registers[rd].iter().chain(&parts.read_a)),
+        Op::Jmp => (),
+    }
+    active.into_iter().collect()
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // leave the right of the window for the program and registers.
+    let bl = win.bottom_left() + vec2(win.w() * 0.03, win.h() * 0.08);
+    let size = vec2(win.w() * 0.67, win.h() * 0.84);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(22, 22, 30));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+
+    // a glow under the active instruction's datapath.
+    let active = active_gates(model);
+    for edge in model.circuit.graph().edge_references() {
+        if !active.contains(&edge.source()) || !active.contains(&edge.target()) {
+            continue;
+        }
+        let points = match model.routes.get(&edge.id()) {
+            Some(route) => route.clone(),
+            None => vec![
+                model.positions[&edge.source()],
+                model.positions[&edge.target()],
+            ],
+        };
+        draw.polyline()
+            .weight(6.0)
+            .points(points.into_iter().map(&map_pos))
+            .color(rgba(0.3, 0.7, 1.0, 0.3));
+    }
+    for node in &active {
+        draw.ellipse()
+            .xy(map_pos(model.positions[node]))
+            .w_h(14.0, 14.0)
+            .color(rgba(0.3, 0.7, 1.0, 0.3));
+    }
+
+    let style = CircuitStyle {
+        gate_size: 7.0,
+        wire_weight: 1.0,
+        font_size: 8,
+        on: rgba(1.0, 0.86, 0.47, 0.8),
+        off: rgba(0.35, 0.35, 0.43, 0.3),
+        gate_fill: rgba(0.25, 0.25, 0.3, 1.0),
+        ..CircuitStyle::default()
+    };
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+
+    // the program, with the instruction on the bus marked, and the registers.
+    let pc = read(&model.circuit, &model.parts.pc);
+    let panel = Rect::from_w_h(win.w() * 0.25, win.h() * 0.8)
+        .top_right_of(win)
+        .shift(vec2(-20.0, -20.0));
+    let mut y = panel.top();
+    let mut line = |text: &str, color: Rgb8| {
+        draw.text(text)
+            .x_y(panel.x(), y)
+            .w(panel.w())
+            .left_justify()
+            .color(color);
+        y -= 20.0;
+    };
+    for (address, instruction) in PROGRAM.iter().enumerate() {
+        let current = address == pc;
+        line(
+            &format!(
+                "{} {:2}  {}",
+                if current { ">" } else { " " },
+                address,
+                disassemble(*instruction)
+            ),
+            if current {
+                rgb8(120, 200, 255)
+            } else {
+                rgb8(160, 160, 170)
+            },
+        );
+    }
+    line("", rgb8(255, 255, 255));
+    for (r, register) in model.parts.registers.iter().enumerate() {
+        let value = read(&model.circuit, register);
+        line(
+            &format!(
+                "r{} = {:2} = {}",
+                r,
+                value,
+                BusDisplay::Binary.format(value, BITS)
+            ),
+            rgb8(255, 220, 120),
+        );
+    }
+    line("", rgb8(255, 255, 255));
+    let halted = instruction_at(pc) == (Op::Jmp, 0, 0, pc);
+    line(
+        &format!(
+            "pc = {}, {} cycles{}",
+            pc,
+            model.cycles,
+            if halted {
+                " (halted)"
+            } else if model.paused {
+                " (paused)"
+            } else {
+                ""
+            }
+        ),
+        rgb8(255, 255, 255),
+    );
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,107 @@
+use nannou::prelude::*;
+use nannou_sketches::dla::Aggregate;
+use nannou_sketches::viz::palette::Palette;
+
+const RADIUS: f32 = 2.0;
+const WALKERS: usize = 400;
+const STEPS_PER_FRAME: usize = 20;
+
+static HELP: &str = "W to show the walkers, space to pause, R to grow another";
+
+struct Model {
+    aggregate: Aggregate,
+    palette: Palette,
+    // which aggregate this is, to seed the next from.
+    seed: u64,
+    show_walkers: bool,
+    paused: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    Model {
+        aggregate: Aggregate::new(RADIUS, WALKERS, 1),
+        palette: Palette::new(&[
+            Srgb::new(255, 250, 235),
+            Srgb::new(0, 190, 220),
+            Srgb::new(0, 110, 255),
+            Srgb::new(249, 0, 229),
+        ]),
+        seed: 1,
+        show_walkers: false,
+        paused: false,
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => update(app, model),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::W => model.show_walkers = !model.show_walkers,
+            Key::Space => model.paused = !model.paused,
+            Key::R => {
+                model.seed += 1;
+                model.aggregate = Aggregate::new(RADIUS, WALKERS, model.seed);
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model) {
+    let win = app.window_rect();
+    // stop once it fills the window.
+    let full = model.aggregate.extent() > win.w().min(win.h()) / 2.0 - RADIUS * 4.0;
+    if model.paused || full {
+        return;
+    }
+    for _ in 0..STEPS_PER_FRAME {
+        model.aggregate.step();
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(8, 8, 20));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let aggregate = &model.aggregate;
+
+    if model.show_walkers {
+        for walker in aggregate.walkers() {
+            draw.ellipse()
+                .xy(*walker)
+                .radius(RADIUS)
+                .resolution(8)
+                .color(rgba(1.0, 1.0, 1.0, 0.25));
+        }
+    }
+    // the seed in the middle is the first color, and the newest growth the last.
+    let latest = aggregate.steps().max(1) as f32;
+    for deposit in aggregate.deposits() {
+        draw.ellipse()
+            .xy(deposit.pos)
+            .radius(RADIUS)
+            .resolution(8)
+            .color(model.palette.sample(deposit.step as f32 / latest));
+    }
+
+    draw.text(&format!(
+        "{} particles\n{}",
+        aggregate.deposits().len(),
+        HELP
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 20.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(150, 150, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,157 @@
+use nannou::prelude::*;
+use nannou_sketches::physics::pendulum::PendulumChain;
+use nannou_sketches::physics::FixedTimestep;
+use nannou_sketches::viz::trail::Trail;
+use std::collections::VecDeque;
+
+// pendulums let go from almost, but not quite, the same place.
+const N: usize = 4;
+const NUDGE: f32 = 1e-4;
+const STEP: f32 = 1.0 / 480.0;
+// px per unit of link length.
+const SCALE: f32 = 110.0;
+// how many frames of its path each pendulum's end leaves behind it.
+const TRAIL_LENGTH: usize = 200;
+// how many frames of their angles the phase space plot keeps.
+const PHASE_LENGTH: usize = 3000;
+
+static HELP: &str = "R to let go again";
+
+struct Model {
+    pendulums: Vec<PendulumChain>,
+    timestep: FixedTimestep,
+    // where each pendulum's end has been.
+    trails: Vec<Trail>,
+    // each pendulum's (top angle, bottom angle), each from -pi to pi.
+    phases: Vec<VecDeque<Vector2>>,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let pendulums = (0..N)
+        .map(|i| {
+            let mut pendulum = PendulumChain::hanging(vec2(0.0, 0.0), &[1.0, 1.0]);
+            pendulum.gravity = vec2(0.0, -9.8);
+            pendulum.links[0].angle = PI * 0.75 + i as f32 * NUDGE;
+            pendulum.links[1].angle = PI * 0.75;
+            pendulum
+        })
+        .collect();
+    let mut trail = Trail::new(TRAIL_LENGTH);
+    // the trails are kept unscaled, and drawn scaled up.
+    trail.weight = 3.0 / SCALE;
+    Model {
+        pendulums,
+        timestep: FixedTimestep::new(STEP),
+        trails: vec![trail; N],
+        phases: vec![VecDeque::new(); N],
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => *model = self::model(app),
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        for pendulum in &mut model.pendulums {
+            pendulum.step(model.timestep.dt);
+        }
+    }
+    for (i, pendulum) in model.pendulums.iter().enumerate() {
+        model.trails[i].push(pendulum.positions()[1]);
+        let phase = &mut model.phases[i];
+        phase.push_back(vec2(
+            wrap(pendulum.links[0].angle),
+            wrap(pendulum.links[1].angle),
+        ));
+        if phase.len() > PHASE_LENGTH {
+            phase.pop_front();
+        }
+    }
+}
+
+/// `angle`, turned whole turns until it's between -pi and pi.
+fn wrap(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(10, 10, 20));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let (left, right) = (win.pad_right(win.w() / 2.0), win.pad_left(win.w() / 2.0));
+
+    // the pendulums themselves, hanging from the middle of the left half.
+    let anchor = left.xy() + vec2(0.0, left.h() * 0.1);
+    let scaled = draw.xy(anchor).scale(SCALE);
+    for (i, (pendulum, trail)) in model.pendulums.iter().zip(&model.trails).enumerate() {
+        let hue = i as f32 / N as f32;
+        trail.draw(&scaled, hsla(hue, 0.8, 0.6, 0.7));
+        let mut joint = anchor;
+        for bob in pendulum.positions() {
+            let bob = anchor + bob * SCALE;
+            draw.line()
+                .start(joint)
+                .end(bob)
+                .weight(2.0)
+                .color(rgba(1.0, 1.0, 1.0, 0.4));
+            draw.ellipse().xy(bob).radius(6.0).color(hsl(hue, 0.8, 0.6));
+            joint = bob;
+        }
+    }
+    draw.ellipse().xy(anchor).radius(3.0).color(WHITE);
+
+    // the phase space: the top link's angle across, the bottom link's up.
+    let size = right.w().min(right.h()) * 0.8;
+    let plot = Rect::from_xy_wh(right.xy(), vec2(size, size));
+    draw.rect()
+        .xy(plot.xy())
+        .wh(plot.wh())
+        .no_fill()
+        .stroke_weight(1.0)
+        .stroke(rgba(1.0, 1.0, 1.0, 0.3));
+    let to_plot = |phase: Vector2| plot.xy() + phase / (2.0 * PI) * size;
+    for (i, phase) in model.phases.iter().enumerate() {
+        let hue = i as f32 / N as f32;
+        for (j, (a, b)) in phase.iter().zip(phase.iter().skip(1)).enumerate() {
+            // an angle going round past pi jumps to the other side; don't draw a line across.
+            if (*b - *a).x.abs() > PI || (*b - *a).y.abs() > PI {
+                continue;
+            }
+            let fade = j as f32 / phase.len() as f32;
+            draw.line()
+                .start(to_plot(*a))
+                .end(to_plot(*b))
+                .weight(1.0)
+                .color(hsla(hue, 0.8, 0.6, fade * 0.7));
+        }
+    }
+    draw.text("top angle")
+        .xy(plot.mid_bottom() - vec2(0.0, 12.0))
+        .font_size(12)
+        .color(rgb8(120, 120, 150));
+    draw.text("bottom angle")
+        .xy(plot.mid_left() - vec2(40.0, 0.0))
+        .font_size(12)
+        .color(rgb8(120, 120, 150));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 12.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(120, 120, 150));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
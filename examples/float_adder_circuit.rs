@@ -0,0 +1,323 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{
+    draw_circuit_pulses, Camera, CircuitStyle, PulseSpeed, Pulses,
+};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+
+// 1 sign bit, 4 exponent bits, 3 mantissa bits.
+const EXP_BITS: usize = 4;
+const MANTISSA_BITS: usize = 3;
+const WIDTH: usize = EXP_BITS + MANTISSA_BITS + 1;
+const BIAS: i32 = 7;
+
+const UPDATE_EVERY: f32 = 1.0 / 30.0;
+const SELECT_RADIUS: f32 = 15.0;
+
+static HELP: &str = "click an input bit to flip it; R: random inputs; space: settle instantly
+scroll: zoom; middle drag: pan; tab: binary / decimal / hex";
+
+struct Model {
+    circuit: Circuit,
+    a: Vec<NodeIndex>,
+    b: Vec<NodeIndex>,
+    adder: FloatAdder,
+    sum: Vec<NodeIndex>,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+    pulses: Pulses,
+    camera: Camera,
+    /// Where the mouse was, while panning with the middle button.
+    panning: Option<Vector2>,
+    bus_display: BusDisplay,
+
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+
+    let a = (0..WIDTH).map(|_| circuit.add_input()).collect::<Vec<_>>();
+    let b = (0..WIDTH).map(|_| circuit.add_input()).collect::<Vec<_>>();
+    for i in 0..WIDTH {
+        circuit.set_label(a[i], &format!("a{}", i));
+        circuit.set_label(b[i], &format!("b{}", i));
+    }
+    let adder = circuit.float_adder(&a, &b, EXP_BITS);
+    let sum = adder
+        .sum
+        .iter()
+        .map(|s| circuit.add_output(*s))
+        .collect::<Vec<_>>();
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+    let (mut positions, waypoints) = layered_with_waypoints(&circuit);
+    // inputs and outputs are drawn most significant bit first, like the numbers are written.
+    for i in 0..WIDTH {
+        let y = (i + 1) as f32 / (WIDTH * 2 + 2) as f32;
+        positions.insert(a[i], vec2(0.0, 0.5 + y));
+        positions.insert(b[i], vec2(0.0, y));
+        positions.insert(sum[i], vec2(1.0, 0.25 + y));
+    }
+    let routes = route(&circuit, &positions, &waypoints);
+    let pulses = Pulses::new(&circuit, PulseSpeed::PerWire(UPDATE_EVERY));
+
+    Model {
+        circuit,
+        a,
+        b,
+        adder,
+        sum,
+        positions,
+        routes,
+        pulses,
+        camera: Camera::default(),
+        panning: None,
+        bus_display: BusDisplay::Binary,
+        update_order,
+        settle_steps,
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            let map_pos = model.camera.map_pos(circuit_rect(app.window_rect()));
+            let mouse = app.mouse.position();
+            let nearest = model
+                .a
+                .iter()
+                .chain(model.b.iter())
+                .map(|n| (*n, (map_pos(model.positions[n]) - mouse).magnitude()))
+                .filter(|(_, d)| *d < SELECT_RADIUS)
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .map(|(n, _)| n);
+            if let Some(node) = nearest {
+                let current = model.circuit.get_1_in(node);
+                model.circuit.set_input(node, !current);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Middle)),
+            ..
+        } => {
+            model.panning = Some(app.mouse.position());
+        }
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Middle)),
+            ..
+        } => {
+            model.panning = None;
+        }
+        Event::WindowEvent {
+            simple: Some(MouseMoved(position)),
+            ..
+        } => {
+            if let Some(last) = model.panning {
+                model.camera.pan += position - last;
+                model.panning = Some(position);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(MouseWheel(delta, _)),
+            ..
+        } => {
+            model.camera.scroll(delta, app.mouse.position());
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Tab)),
+            ..
+        } => {
+            model.bus_display = model.bus_display.next();
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => {
+            for input in model.a.iter().chain(model.b.iter()) {
+                model.circuit.set_input(*input, random());
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => {
+            for _ in 0..model.settle_steps {
+                model.circuit.update_signals_once(&model.update_order);
+            }
+            model
+                .pulses
+                .observe(&model.circuit, app.duration.since_start.as_secs_f32());
+        }
+        _ => (),
+    }
+}
+
+fn epoch(t: f32) -> u32 {
+    (t / UPDATE_EVERY).floor() as u32
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32();
+    let t = app.duration.since_start.as_secs_f32();
+
+    if epoch(t - dt) < epoch(t) {
+        model.circuit.update_signals_once(&model.update_order);
+        model.pulses.observe(&model.circuit, t);
+    }
+}
+
+/// The part of the window the circuit fills when the camera hasn't moved.
+fn circuit_rect(win: Rect) -> Rect {
+    // leave the right third of the window for text.
+    Rect::from_w_h(win.w() * 0.6, win.h() * 0.8)
+        .bottom_left_of(win)
+        .shift(vec2(win.w() * 0.05, win.h() * 0.1))
+}
+
+/// Read an internal bus, which is driving other gates.
+fn read_bus(circuit: &Circuit, bus: &[NodeIndex]) -> usize {
+    let mut v = 0;
+    for (i, bit) in bus.iter().enumerate() {
+        v = set_bit(v, i, circuit.get_out(*bit).unwrap());
+    }
+    v
+}
+
+/// Read the nodes feeding a set of inputs or outputs.
+fn read_io(circuit: &Circuit, nodes: &[NodeIndex]) -> usize {
+    let mut v = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        v = set_bit(v, i, circuit.get_1_in(*node));
+    }
+    v
+}
+
+fn decode(x: usize) -> f32 {
+    let exponent = (x >> MANTISSA_BITS) & ((1 << EXP_BITS) - 1);
+    if exponent == 0 {
+        return 0.0;
+    }
+    let mantissa = (x & ((1 << MANTISSA_BITS) - 1)) as f32 / (1 << MANTISSA_BITS) as f32;
+    let v = (1.0 + mantissa) * 2.0f32.powi(exponent as i32 - BIAS);
+    if get_bit(x, WIDTH - 1) {
+        -v
+    } else {
+        v
+    }
+}
+
+fn describe(x: usize) -> String {
+    format!(
+        "{:>9} = {} {:0e$b} {:0m$b}",
+        decode(x),
+        x >> (WIDTH - 1),
+        (x >> MANTISSA_BITS) & ((1 << EXP_BITS) - 1),
+        x & ((1 << MANTISSA_BITS) - 1),
+        e = EXP_BITS,
+        m = MANTISSA_BITS,
+    )
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(30, 30, 35));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = model.camera.map_pos(circuit_rect(win));
+
+    let style = CircuitStyle {
+        gate_size: 6.0,
+        wire_weight: 1.0,
+        on: rgba(1.0, 0.86, 0.47, 0.8),
+        off: rgba(0.35, 0.35, 0.43, 0.25),
+        gate_fill: rgba(0.25, 0.25, 0.3, 1.0),
+        ..CircuitStyle::default()
+    }
+    .zoomed(model.camera.zoom);
+    draw_circuit_pulses(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+        model.pulses.at(app.duration.since_start.as_secs_f32()),
+    );
+
+    // ring the buses of each stage, and show their values alongside.
+    let adder = &model.adder;
+    let stages: [(&str, &[NodeIndex], Rgb8); 5] = [
+        ("exponent difference", &adder.shift, rgb8(120, 200, 255)),
+        ("larger significand", &adder.big, rgb8(120, 255, 160)),
+        ("aligned significand", &adder.aligned, rgb8(255, 140, 220)),
+        ("significand sum", &adder.significand, rgb8(255, 170, 90)),
+        (
+            "normalization shift",
+            &adder.normalize_shift,
+            rgb8(200, 150, 255),
+        ),
+    ];
+    for (_, bus, color) in stages.iter() {
+        for node in bus.iter() {
+            draw.ellipse()
+                .xy(map_pos(model.positions[node]))
+                .w_h(12.0 * model.camera.zoom, 12.0 * model.camera.zoom)
+                .no_fill()
+                .stroke(*color)
+                .stroke_weight(2.0);
+        }
+    }
+
+    let a = read_io(&model.circuit, &model.a);
+    let b = read_io(&model.circuit, &model.b);
+    let sum = read_io(&model.circuit, &model.sum);
+
+    let panel = Rect::from_w_h(win.w() * 0.3, win.h() * 0.8)
+        .top_right_of(win)
+        .shift(vec2(-20.0, -20.0));
+    let mut y = panel.top();
+    let mut line = |text: &str, color: Rgb8| {
+        draw.text(text)
+            .x_y(panel.x(), y)
+            .w(panel.w())
+            .left_justify()
+            .color(color);
+        y -= 20.0;
+    };
+    line(&format!("a   {}", describe(a)), rgb8(255, 255, 255));
+    line(&format!("b   {}", describe(b)), rgb8(255, 255, 255));
+    line("", rgb8(255, 255, 255));
+    for (name, bus, color) in stages.iter() {
+        let value = read_bus(&model.circuit, bus);
+        let value = model.bus_display.format(value, bus.len());
+        line(&format!("{}: {}", name, value), *color);
+    }
+    line("", rgb8(255, 255, 255));
+    line(&format!("sum {}", describe(sum)), rgb8(255, 220, 120));
+    line(
+        &format!("exact sum {}", decode(a) + decode(b)),
+        rgb8(180, 180, 180),
+    );
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 40.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
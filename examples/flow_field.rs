@@ -0,0 +1,134 @@
+use nannou::prelude::*;
+use nannou_sketches::flow::FlowField;
+use nannou_sketches::hud::Hud;
+use nannou_sketches::sketch::{run_sketch, Sketch};
+use nannou_sketches::viz::flow::{draw_arrows, draw_streamlines};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+const N: usize = 2000;
+const CELL_SIZE: f32 = 20.0;
+// particles are dropped somewhere new after this many seconds, so they don't all end up
+// circling the same few whirls.
+const LIFETIME: f32 = 6.0;
+
+static HELP: &str = "S for streamlines, A for arrows";
+
+struct Dot {
+    pos: Vector2,
+    prev_pos: Vector2,
+    age: f32,
+}
+
+struct Model {
+    field: FlowField,
+    dots: Vec<Dot>,
+    rng: XorShiftRng,
+    streamlines: bool,
+    arrows: bool,
+}
+
+fn main() {
+    run_sketch::<Model>();
+}
+
+fn random_pos(rng: &mut XorShiftRng, bounds: Rect) -> Vector2 {
+    bounds.bottom_left() + rng.gen::<Vector2>() * bounds.wh()
+}
+
+impl Sketch for Model {
+    fn init(app: &App) -> Model {
+        let field = FlowField::new(app.window_rect(), CELL_SIZE);
+        let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
+        let dots = (0..N)
+            .map(|_| {
+                let pos = random_pos(&mut rng, field.bounds);
+                Dot {
+                    pos,
+                    prev_pos: pos,
+                    // staggered, so they don't all move on at once.
+                    age: rng.gen::<f32>() * LIFETIME,
+                }
+            })
+            .collect();
+        Model {
+            field,
+            dots,
+            rng,
+            streamlines: false,
+            arrows: false,
+        }
+    }
+
+    fn on_key(&mut self, _app: &App, key: Key) {
+        match key {
+            Key::S => self.streamlines = !self.streamlines,
+            Key::A => self.arrows = !self.arrows,
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, app: &App, dt: f32) {
+        self.field.update(app.time);
+        for dot in &mut self.dots {
+            dot.age += dt;
+            dot.prev_pos = dot.pos;
+            dot.pos = self.field.advect(dot.pos, dt);
+            if dot.age > LIFETIME || !self.field.bounds.contains(dot.pos) {
+                dot.pos = random_pos(&mut self.rng, self.field.bounds);
+                dot.prev_pos = dot.pos;
+                dot.age = 0.0;
+            }
+        }
+    }
+
+    fn hud(&self, hud: &mut Hud) {
+        hud.stat("dots", self.dots.len());
+        hud.stat("streamlines", self.streamlines);
+        hud.stat("arrows", self.arrows);
+    }
+
+    fn background(&self) -> Srgb<u8> {
+        rgb8(12, 14, 30)
+    }
+
+    fn draw(&self, _app: &App, draw: &Draw, win: Rect) {
+        // fade out what's there, so the dots leave trails.
+        draw.rect()
+            .xy(win.xy())
+            .wh(win.wh())
+            .color(rgba8(12, 14, 30, 12));
+
+        if self.streamlines {
+            draw_streamlines(
+                draw,
+                &self.field,
+                CELL_SIZE * 2.0,
+                CELL_SIZE * 6.0,
+                1.0,
+                rgba(0.5, 0.6, 1.0, 0.1),
+            );
+        }
+        if self.arrows {
+            draw_arrows(draw, &self.field, 0.2, 1.0, rgba(1.0, 1.0, 1.0, 0.2));
+        }
+
+        for dot in &self.dots {
+            // brightest halfway through their lives.
+            let life = (dot.age / LIFETIME * PI).sin();
+            draw.line()
+                .start(dot.prev_pos)
+                .end(dot.pos)
+                .weight(1.5)
+                .color(rgba(0.6, 0.9, 1.0, life * 0.8));
+        }
+    }
+
+    fn draw_overlay(&self, _app: &App, draw: &Draw, win: Rect) {
+        draw.text(HELP)
+            .xy(win.mid_bottom() + vec2(0.0, 12.0))
+            .w(win.w())
+            .font_size(12)
+            .color(rgb8(120, 130, 170));
+    }
+}
@@ -0,0 +1,132 @@
+use nannou::geom::Range;
+use nannou::prelude::*;
+use nannou_sketches::fluid::Fluid;
+use nannou_sketches::physics::FixedTimestep;
+use nannou_sketches::viz::palette::Palette;
+
+// domain is (-.5, .5) x (-.5, .5)
+const SIM_BOUNDS: Rect<f32> = Rect {
+    x: Range {
+        start: -0.5,
+        end: 0.5,
+    },
+    y: Range {
+        start: -0.5,
+        end: 0.5,
+    },
+};
+const SPACING: f32 = 0.03;
+const MAX_PARTICLES: usize = 500;
+// seconds between particles leaving the spout.
+const POUR_INTERVAL: f32 = 0.012;
+const SPOUT: Vector2 = Vector2 { x: -0.4, y: 0.4 };
+const POUR_VEL: Vector2 = Vector2 { x: 0.8, y: 0.0 };
+const STIR_RADIUS: f32 = 0.1;
+
+static HELP: &str = "move the mouse to stir, R to start over";
+
+struct Model {
+    fluid: Fluid,
+    timestep: FixedTimestep,
+    // simulated time since the last particle was poured.
+    since_poured: f32,
+    // where the mouse was last update, in simulation coordinates.
+    prev_mouse: Vector2,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let fluid = Fluid::new(SIM_BOUNDS, SPACING);
+    let mut timestep = FixedTimestep::new(fluid.max_dt());
+    // the fluid needs small steps; rather fall behind real time than stall.
+    timestep.max_steps = 20;
+    Model {
+        fluid,
+        timestep,
+        since_poured: 0.0,
+        prev_mouse: vec2(0.0, 0.0),
+    }
+}
+
+/// How many pixels a unit of simulation is.
+fn scale(win: Rect) -> f32 {
+    win.w().min(win.h()) * 0.9
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => {
+            model.fluid.particles.clear();
+            model.since_poured = 0.0;
+        }
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let mouse = app.mouse.position() / scale(app.window_rect());
+    let elapsed = upd.since_last.as_secs_f32();
+    if elapsed > 0.0 {
+        let mouse_vel = (mouse - model.prev_mouse) / elapsed;
+        model.fluid.stir(mouse, mouse_vel, STIR_RADIUS);
+    }
+    model.prev_mouse = mouse;
+
+    for _ in 0..model.timestep.advance(elapsed) {
+        let dt = model.timestep.dt;
+        model.since_poured += dt;
+        if model.since_poured >= POUR_INTERVAL && model.fluid.particles.len() < MAX_PARTICLES {
+            model.since_poured = 0.0;
+            // alternate a little up and down, so the stream doesn't stack in a perfect column.
+            let wobble = if model.fluid.particles.len() % 2 == 0 {
+                SPACING * 0.3
+            } else {
+                -SPACING * 0.3
+            };
+            model
+                .fluid
+                .add_particle(SPOUT + vec2(0.0, wobble), POUR_VEL);
+        }
+        model.fluid.step(dt);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(255, 255, 255));
+    let win = app.window_rect();
+    let draw = app.draw();
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 12.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(80, 80, 80));
+
+    let draw = draw.scale(scale(win));
+    draw.rect()
+        .xy(SIM_BOUNDS.xy())
+        .wh(SIM_BOUNDS.wh())
+        .no_fill()
+        .stroke_weight(0.004)
+        .stroke(rgb8(180, 180, 180));
+
+    // faster particles are lighter.
+    let palette = Palette::ice();
+    for particle in &model.fluid.particles {
+        let ratio = (particle.vel.magnitude() / 2.0).min(1.0);
+        draw.ellipse()
+            .xy(particle.pos)
+            .w_h(SPACING * 1.4, SPACING * 1.4)
+            .resolution(12)
+            .color(palette.sample(ratio));
+    }
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,199 @@
+use nannou::prelude::*;
+use nannou_sketches::harmonograph::{Harmonograph, Pendulum};
+use nannou_sketches::hud::Hud;
+use nannou_sketches::params::Params;
+use nannou_sketches::sketch::{run_sketch, Mouse, Sketch};
+use nannou_sketches::viz::plot::Plot;
+use nannou_sketches::viz::trail::Trail;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// px the harmonograph reaches out to at its widest.
+const SCALE: f32 = 320.0;
+const TRAIL_LENGTH: usize = 4000;
+// how many points along the curve the pen adds to its trail each frame to start with, and how far
+// apart in time.
+const STEPS_PER_FRAME: i32 = 12;
+const STEP: f32 = 0.002;
+// how fast the pendulums' phases and tunings wander, per second, to start with.
+const MORPH: f32 = 0.05;
+// the window the sliders, the plot of the pendulums' reach and the readout are in, keeping them off
+// the canvas.
+const CONTROLS: (u32, u32) = (360, 300);
+// how far the pendulums die down before a new harmonograph starts.
+const RESTART_REACH: f32 = 0.05;
+// frequency ratios which make pleasing figures, one for each axis.
+const RATIOS: [(f32, f32); 5] = [(1.0, 1.0), (2.0, 3.0), (3.0, 4.0), (1.0, 2.0), (3.0, 5.0)];
+
+static HELP: &str = "L for Lissajous figures, space to stop morphing, R for a new harmonograph";
+
+struct Model {
+    harmonograph: Harmonograph,
+    // how far along the curve the pen is.
+    t: f32,
+    trail: Trail,
+    lissajous: bool,
+    morphing: bool,
+    rng: XorShiftRng,
+    params: Params,
+    // how far the pen swings out, dying down until the next restart.
+    reach: Plot,
+}
+
+fn main() {
+    run_sketch::<Model>();
+}
+
+/// Start a new harmonograph swinging, tuned to one of the `RATIOS`.
+fn restart(model: &mut Model) {
+    let rng = &mut model.rng;
+    let (a, b) = RATIOS[rng.gen_range(0, RATIOS.len())];
+    if model.lissajous {
+        model.harmonograph = Harmonograph::lissajous(1.0, a, b, rng.gen::<f32>() * TAU);
+    } else {
+        // two pendulums along each axis, the second nearly in tune with the first, and smaller.
+        let mut pendulums = |frequency: f32| {
+            (0..2)
+                .map(|i| Pendulum {
+                    amplitude: if i == 0 { 0.7 } else { 0.3 },
+                    frequency: frequency * (1.0 + rng.gen_range(-0.005, 0.005)),
+                    phase: rng.gen::<f32>() * TAU,
+                    damping: rng.gen_range(0.02, 0.06),
+                })
+                .collect::<Vec<_>>()
+        };
+        model.harmonograph.x = pendulums(a);
+        model.harmonograph.y = pendulums(b);
+    }
+    model.t = 0.0;
+    model.trail.clear();
+}
+
+impl Sketch for Model {
+    const CONTROLS: Option<(u32, u32)> = Some(CONTROLS);
+
+    fn init(_app: &App) -> Model {
+        let mut trail = Trail::new(TRAIL_LENGTH);
+        // the trail is kept unscaled, and drawn scaled up.
+        trail.weight = 2.0 / SCALE;
+        let mut params = Params::new();
+        params.add_float("morph", MORPH, 0.0, 0.5);
+        params.add_int("steps per frame", STEPS_PER_FRAME, 1, 60);
+        // under the sliders, and above the readout.
+        let (w, h) = CONTROLS;
+        let win = Rect::from_w_h(w as f32, h as f32);
+        let plot_rect = Rect::from_x_y_w_h(0.0, 0.0, w as f32 - 20.0, 100.0)
+            .mid_bottom_of(win)
+            .shift_y(60.0);
+        let mut reach = Plot::new(plot_rect, 600);
+        reach.range = Some((0.0, 1.0));
+        reach.add_series("reach", rgb(0.1, 0.15, 0.35));
+        let mut model = Model {
+            harmonograph: Harmonograph::default(),
+            t: 0.0,
+            trail,
+            lissajous: false,
+            morphing: true,
+            rng: SeedableRng::seed_from_u64(1),
+            params,
+            reach,
+        };
+        restart(&mut model);
+        model
+    }
+
+    fn on_controls_mouse(&mut self, app: &App, win: Rect, mouse: Mouse) {
+        match mouse {
+            Mouse::Pressed(MouseButton::Left) => {
+                self.params.press(win, app.mouse.position());
+            }
+            Mouse::Moved(pos) => self.params.move_to(win, pos),
+            Mouse::Released(_) => self.params.release(),
+            _ => (),
+        }
+    }
+
+    fn on_key(&mut self, _app: &App, key: Key) {
+        match key {
+            Key::L => {
+                self.lissajous = !self.lissajous;
+                restart(self);
+            }
+            Key::Space => self.morphing = !self.morphing,
+            Key::R => restart(self),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _app: &App, dt: f32) {
+        if self.morphing {
+            let morph = self.params.float("morph");
+            let harmonograph = &mut self.harmonograph;
+            // the up and down pendulums drift out of phase with the across ones, turning the
+            // figure, and the second of each pair drifts in and out of tune.
+            for (i, pendulum) in harmonograph.y.iter_mut().enumerate() {
+                pendulum.phase += morph * dt * (i + 1) as f32;
+            }
+            for pendulum in harmonograph.x.iter_mut().skip(1) {
+                pendulum.frequency *= 1.0 + morph * dt * 0.01 * (self.t * 0.5).sin();
+            }
+        }
+        for _ in 0..self.params.int("steps per frame") {
+            self.t += STEP;
+            self.trail.push(self.harmonograph.at(self.t));
+        }
+        let reach = self.harmonograph.reach(self.t);
+        self.reach.push(0, reach);
+        // (a Lissajous figure never dies down, so just carries on.)
+        if reach < RESTART_REACH {
+            restart(self);
+        }
+    }
+
+    fn hud(&self, hud: &mut Hud) {
+        hud.stat("trail points", self.trail.len());
+        hud.stat("t", format!("{:.2}", self.t));
+        hud.params(&self.params);
+    }
+
+    fn background(&self) -> Srgb<u8> {
+        rgb8(245, 240, 230)
+    }
+
+    fn draw(&self, _app: &App, draw: &Draw, _win: Rect) {
+        draw.background().color(self.background());
+        let scaled = draw.scale(SCALE);
+        self.trail.draw(&scaled, rgba(0.1, 0.15, 0.35, 0.9));
+        if let Some(pen) = self.trail.head() {
+            draw.ellipse()
+                .xy(pen * SCALE)
+                .radius(3.0)
+                .color(rgb8(200, 40, 60));
+        }
+    }
+
+    fn draw_controls(&self, _app: &App, draw: &Draw, win: Rect) {
+        self.params.draw(draw, win);
+        self.reach.draw(draw);
+
+        let axis = |pendulums: &[Pendulum]| {
+            pendulums
+                .iter()
+                .map(|p| format!("{:.3}", p.frequency))
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        let morphing = if self.morphing { "" } else { ", not morphing" };
+        draw.text(&format!(
+            "x {}, y {}{}\n{}",
+            axis(&self.harmonograph.x),
+            axis(&self.harmonograph.y),
+            morphing,
+            HELP
+        ))
+        .xy(win.mid_bottom() + vec2(0.0, 24.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(60, 60, 80));
+    }
+}
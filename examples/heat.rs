@@ -0,0 +1,148 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::automata::Edges;
+use nannou_sketches::contour::contour;
+use nannou_sketches::heat::HeatField;
+use nannou_sketches::viz::palette::Palette;
+
+// px per cell.
+const CELL: f32 = 4.0;
+const STEPS_PER_FRAME: usize = 4;
+// how many cells around the mouse dragging paints.
+const BRUSH: f32 = 3.0;
+// the temperature everything starts at, between the cold sinks, at 0, and hot sources, at 1.
+const AMBIENT: f32 = 0.5;
+// how many isotherms are drawn, evenly spaced between 0 and 1.
+const ISOTHERMS: usize = 9;
+
+static HELP: &str = "drag to heat, right drag to cool, shift drag to erase, \
+                     W to wrap, I for isotherms, C to clear";
+
+struct Model {
+    field: HeatField,
+    palette: Palette,
+    isotherms: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let win = app.window_rect();
+    Model {
+        field: HeatField::new(
+            (win.w() / CELL) as usize,
+            (win.h() / CELL) as usize,
+            AMBIENT,
+        ),
+        palette: Palette::thermal(),
+        isotherms: false,
+    }
+}
+
+/// Which cell `pos`, on screen, is over, if any.
+fn cell(app: &App, field: &HeatField, pos: Vector2) -> Option<(usize, usize)> {
+    let cell = (pos - app.window_rect().bottom_left()) / CELL;
+    if cell.x < 0.0 || cell.y < 0.0 {
+        return None;
+    }
+    let (x, y) = (cell.x as usize, cell.y as usize);
+    if x < field.width && y < field.height {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => update(app, model),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::W => {
+                let edges = match model.field.edges() {
+                    Edges::Wrap => Edges::Clamp,
+                    Edges::Clamp => Edges::Wrap,
+                };
+                model.field.set_edges(edges);
+            }
+            Key::I => model.isotherms = !model.isotherms,
+            Key::C => model.field.reset(AMBIENT),
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model) {
+    let buttons = &app.mouse.buttons;
+    let paint = if app.keys.mods.shift() && buttons.left().is_down() {
+        Some(None)
+    } else if buttons.left().is_down() {
+        Some(Some(1.0))
+    } else if buttons.right().is_down() {
+        Some(Some(0.0))
+    } else {
+        None
+    };
+    if let Some(temperature) = paint {
+        let pos = (app.mouse.position() - app.window_rect().bottom_left()) / CELL;
+        model.field.paint_source(pos, BRUSH, temperature);
+    }
+    for _ in 0..STEPS_PER_FRAME {
+        model.field.step(1.0);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    let win = app.window_rect();
+    let draw = app.draw();
+    let field = &model.field;
+    // images go from the top down, and the field from the bottom up.
+    let pixels = RgbaImage::from_fn(field.width as u32, field.height as u32, |x, y| {
+        let (x, y) = (x as usize, field.height - 1 - y as usize);
+        let [r, g, b] = model.palette.sample_u8(field.temperature(x, y));
+        image::Rgba([r, g, b, 255])
+    });
+    let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+    let size = vec2(field.width as f32, field.height as f32) * CELL;
+    draw.texture(&texture)
+        .xy(win.bottom_left() + size / 2.0)
+        .wh(size);
+    if model.isotherms {
+        for i in 1..=ISOTHERMS {
+            let temperature = i as f32 / (ISOTHERMS + 1) as f32;
+            for line in contour(field.grid(), temperature) {
+                let points = line.points.iter().map(|p| win.bottom_left() + *p * CELL);
+                draw.polyline()
+                    .weight(1.0)
+                    .points(points)
+                    .color(rgba(1.0, 1.0, 1.0, 0.6));
+            }
+        }
+    }
+
+    // the temperature under the mouse.
+    let under = match cell(app, field, app.mouse.position()) {
+        Some((x, y)) if field.source(x, y).is_some() => {
+            format!(", {:.3} held", field.temperature(x, y))
+        }
+        Some((x, y)) => format!(", {:.3}", field.temperature(x, y)),
+        None => String::new(),
+    };
+    let edges = match field.edges() {
+        Edges::Wrap => "wrapping",
+        Edges::Clamp => "insulated",
+    };
+    draw.text(&format!("{}    {}{}", HELP, edges, under))
+        .xy(win.mid_bottom() + vec2(0.0, 12.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
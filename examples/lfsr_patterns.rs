@@ -0,0 +1,177 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::recorder::{draw_scrubber, Record, Recorder};
+use nannou_sketches::viz::palette::Palette;
+use petgraph::graph::NodeIndex;
+use std::collections::VecDeque;
+
+// x^16 + x^14 + x^13 + x^11 + 1 is maximal-length.
+const TAPS: &[usize] = &[15, 13, 12, 10];
+const SEED: usize = 0xACE1;
+const HISTORY: usize = 48;
+
+const UPDATE_EVERY: f32 = 1.0 / 20.0;
+// how many shifts can be rewound: half a minute's worth.
+const RECORDED: usize = 600;
+
+/// What's recorded each shift, and put back when scrubbing.
+struct Register {
+    circuit: Circuit,
+    // each row is the register state after one shift, newest first.
+    history: VecDeque<Vec<Value>>,
+}
+
+impl Record for Register {
+    type State = (SimState, VecDeque<Vec<Value>>);
+
+    fn snapshot(&self) -> Self::State {
+        (self.circuit.snapshot(), self.history.clone())
+    }
+
+    fn restore(&mut self, state: &Self::State) {
+        self.circuit.restore(&state.0);
+        self.history = state.1.clone();
+    }
+}
+
+struct Model {
+    register: Register,
+    clock: NodeIndex,
+    q: Vec<NodeIndex>,
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+
+    recorder: Recorder<Register>,
+    // whether the mouse is dragging the scrubber.
+    scrubbing: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+/// The bar along the bottom of the window to scrub through the recording with.
+fn scrubber(win: Rect) -> Rect {
+    Rect::from_w_h(win.w() - 20.0, 8.0).mid_bottom_of(win.pad(10.0))
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+    let clock = circuit.add_input();
+    let q = circuit.lfsr(TAPS, SEED, clock);
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+
+    Model {
+        register: Register {
+            circuit,
+            history: VecDeque::new(),
+        },
+        clock,
+        q,
+        update_order,
+        settle_steps,
+        recorder: Recorder::new(RECORDED),
+        scrubbing: false,
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => model.scrubbing = scrubber(app.window_rect()).contains(app.mouse.position()),
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => model.scrubbing = false,
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => model.recorder.resume(),
+        _ => (),
+    }
+}
+
+fn epoch(t: f32) -> u32 {
+    (t / UPDATE_EVERY).floor() as u32
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32();
+    let t = app.duration.since_start.as_secs_f32();
+
+    let register = &mut model.register;
+    // dragging along the scrubber seeks, and holding the arrow keys rewinds or replays a shift a
+    // frame.
+    if model.scrubbing {
+        let bar = scrubber(app.window_rect());
+        let fraction = (app.mouse.x - bar.left()) / bar.w();
+        model.recorder.seek_fraction(register, fraction);
+    } else if app.keys.down.contains(&Key::Left) {
+        model.recorder.step(register, -1);
+    } else if model.recorder.scrubbing() && app.keys.down.contains(&Key::Right) {
+        model.recorder.step(register, 1);
+    }
+    if model.recorder.scrubbing() || epoch(t - dt) == epoch(t) {
+        return;
+    }
+
+    // one full clock cycle per epoch.
+    let circuit = &mut register.circuit;
+    for value in [true, false].iter() {
+        circuit.set_input(model.clock, *value);
+        for _ in 0..model.settle_steps {
+            circuit.update_signals_once(&model.update_order);
+        }
+    }
+
+    let state = model
+        .q
+        .iter()
+        .map(|q| circuit.get_1_in(*q))
+        .collect::<Vec<_>>();
+    register.history.push_front(state);
+    register.history.truncate(HISTORY);
+    model.recorder.record(register);
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(20, 20, 30));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    let palette = Palette::blue_pink();
+
+    // the register is mirrored around the middle, so the bits shift outwards from the center.
+    let width = model.q.len();
+    let columns = width * 2;
+    let cell = (win.w() / columns as f32).min(win.h() / HISTORY as f32);
+
+    for (row, state) in model.register.history.iter().enumerate() {
+        let age = row as f32 / HISTORY as f32;
+        let y = win.top() - (row as f32 + 0.5) * cell;
+
+        for (i, bit) in state.iter().enumerate() {
+            if !*bit {
+                continue;
+            }
+            let color = palette.sample(1.0 - age);
+            let size = cell * (1.0 - age * 0.7);
+            for x in [-(i as f32) - 0.5, i as f32 + 0.5].iter() {
+                draw.rect()
+                    .x_y(x * cell, y)
+                    .w_h(size, size)
+                    .color(color);
+            }
+        }
+    }
+
+    draw_scrubber(&draw, scrubber(win), &model.recorder);
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,290 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+use std::ops::Range;
+
+// the board is a GRID x GRID torus.
+const GRID: usize = 6;
+// seconds per generation.
+const STEP_EVERY: f32 = 0.5;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+static HELP: &str = "click: toggle a cell; R: randomize; C: clear; space: pause";
+
+struct Model {
+    circuit: Circuit,
+    clock: NodeIndex,
+    // each cell's flip-flop, row by row.
+    cells: Vec<NodeIndex>,
+    // the gates making up each cell's copy of the rule.
+    rules: Vec<Range<usize>>,
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+
+    paused: bool,
+    generation: u32,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+/// The next-state circuit for one cell. Its inputs are the cell itself and then its 8
+/// neighbours; its output is whether the cell is alive next generation.
+fn rule() -> Circuit {
+    let mut rule = Circuit::new();
+    let alive = rule.add_input();
+    let neighbours = (0..8).map(|_| rule.add_input()).collect::<Vec<_>>();
+    let count = rule.popcount(&neighbours);
+
+    // alive with 2 or 3 neighbours, or born with 3: the count is 0b001x, and the low bit
+    // is set or the cell is already alive.
+    let c0_or_alive = rule.add_or(count[0], alive);
+    let not_c2 = rule.add_not(count[2]);
+    let not_c3 = rule.add_not(count[3]);
+    let high_clear = rule.add_and(not_c2, not_c3);
+    let two_or_three = rule.add_and(high_clear, count[1]);
+    let next = rule.add_and(two_or_three, c0_or_alive);
+    rule.add_output(next);
+    rule
+}
+
+fn model(_app: &App) -> Model {
+    let mut circuit = Circuit::new();
+    let clock = circuit.add_input();
+    circuit.set_label(clock, "clock");
+    let cells = (0..GRID * GRID)
+        .map(|_| circuit.add_flip_flop(random()))
+        .collect::<Vec<_>>();
+
+    let rule = rule();
+    let mut rules = vec![];
+    for y in 0..GRID {
+        for x in 0..GRID {
+            let mut inputs = vec![cells[y * GRID + x]];
+            for (dx, dy) in NEIGHBOURS.iter() {
+                let nx = (x as i32 + dx).rem_euclid(GRID as i32) as usize;
+                let ny = (y as i32 + dy).rem_euclid(GRID as i32) as usize;
+                inputs.push(cells[ny * GRID + nx]);
+            }
+            let start = circuit.graph().node_count();
+            let next = circuit.instantiate(&rule, &inputs)[0];
+            rules.push(start..circuit.graph().node_count());
+            circuit.connect_flip_flop(cells[y * GRID + x], next, clock);
+        }
+    }
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    let mut model = Model {
+        circuit,
+        clock,
+        cells,
+        rules,
+        update_order,
+        settle_steps,
+        positions,
+        routes,
+        paused: false,
+        generation: 0,
+    };
+    settle(&mut model);
+    model
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            if let Some(i) = cell_at(app.window_rect(), app.mouse.position()) {
+                let cell = model.cells[i];
+                let alive = model.circuit.get_1_in(cell);
+                model.circuit.set_flip_flop(cell, !alive);
+                settle(model);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => {
+            for cell in &model.cells {
+                model.circuit.set_flip_flop(*cell, random());
+            }
+            model.generation = 0;
+            settle(model);
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::C)),
+            ..
+        } => {
+            for cell in &model.cells {
+                model.circuit.set_flip_flop(*cell, false);
+            }
+            model.generation = 0;
+            settle(model);
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => {
+            model.paused = !model.paused;
+        }
+        _ => (),
+    }
+}
+
+fn epoch(t: f32) -> u32 {
+    (t / STEP_EVERY).floor() as u32
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32();
+    let t = app.duration.since_start.as_secs_f32();
+
+    if model.paused || epoch(t - dt) == epoch(t) {
+        return;
+    }
+
+    // one full clock cycle per generation.
+    for value in [true, false].iter() {
+        model.circuit.set_input(model.clock, *value);
+        settle(model);
+    }
+    model.generation += 1;
+}
+
+/// Let signals propagate until every gate agrees with its inputs.
+/// The clock has to wait for this after cells are edited, or the flip-flops would latch
+/// half-computed next states.
+fn settle(model: &mut Model) {
+    for _ in 0..model.settle_steps {
+        model.circuit.update_signals_once(&model.update_order);
+    }
+}
+
+/// The square holding the board.
+fn board_rect(win: Rect) -> Rect {
+    let size = (win.w() * 0.3).min(win.h() * 0.7);
+    Rect::from_w_h(size, size)
+        .mid_left_of(win)
+        .shift_x(win.w() * 0.05)
+}
+
+fn cell_rect(win: Rect, i: usize) -> Rect {
+    let board = board_rect(win);
+    let size = board.w() / GRID as f32;
+    let (x, y) = (i % GRID, i / GRID);
+    Rect::from_w_h(size, size)
+        .top_left_of(board)
+        .shift(vec2(x as f32 * size, -(y as f32) * size))
+}
+
+fn cell_at(win: Rect, point: Vector2) -> Option<usize> {
+    (0..GRID * GRID).find(|i| cell_rect(win, *i).contains(point))
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // the right side of the window, beside the board.
+    let bl = win.bottom_left() + vec2(win.w() * 0.42, win.h() * 0.08);
+    let size = vec2(win.w() * 0.55, win.h() * 0.84);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(18, 18, 24));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+    let hovered = cell_at(win, app.mouse.position());
+
+    let style = CircuitStyle {
+        gate_size: 3.0,
+        wire_weight: 0.5,
+        labels: false,
+        on: rgba(1.0, 0.86, 0.47, 0.6),
+        off: rgba(0.35, 0.35, 0.43, 0.15),
+        gate_fill: rgba(0.25, 0.25, 0.3, 1.0),
+        ..CircuitStyle::default()
+    };
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+
+    // show where the hovered cell's flip-flop and rule ended up in the layout.
+    if let Some(i) = hovered {
+        for node in model.rules[i].clone().map(NodeIndex::new) {
+            draw.ellipse()
+                .xy(map_pos(model.positions[&node]))
+                .w_h(6.0, 6.0)
+                .color(rgba(0.3, 0.7, 1.0, 0.8));
+        }
+        draw.ellipse()
+            .xy(map_pos(model.positions[&model.cells[i]]))
+            .w_h(14.0, 14.0)
+            .no_fill()
+            .stroke(rgb8(80, 180, 255))
+            .stroke_weight(2.0);
+    }
+
+    for (i, cell) in model.cells.iter().enumerate() {
+        let rect = cell_rect(win, i);
+        let alive = model.circuit.get_1_in(*cell);
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.pad(1.0).wh())
+            .color(if alive {
+                rgb8(255, 220, 120)
+            } else if hovered == Some(i) {
+                rgb8(50, 70, 90)
+            } else {
+                rgb8(40, 40, 50)
+            });
+    }
+
+    let board = board_rect(win);
+    draw.text(&format!(
+        "generation {}{}\n{} gates",
+        model.generation,
+        if model.paused { " (paused)" } else { "" },
+        model.circuit.graph().node_count()
+    ))
+    .xy(board.mid_top() + vec2(0.0, 40.0))
+    .w(board.w())
+    .color(rgb8(255, 255, 255));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
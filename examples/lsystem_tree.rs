@@ -0,0 +1,208 @@
+use nannou::prelude::*;
+use nannou_sketches::lsystem::{Branch, LSystem, Turtle};
+use nannou_sketches::physics::{FixedTimestep, Particle, Spring, World};
+use nannou_sketches::wind::Wind;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+const AXIOM: &str = "X";
+static RULES: [&str; 2] = ["X -> F[+X]F[-X]+X", "F -> FF"];
+const ITERATIONS: usize = 4;
+const MAX_ITERATIONS: usize = 6;
+// the turtle's turn, in degrees, which each new tree picks from.
+const MIN_ANGLE: f32 = 16.0;
+const MAX_ANGLE: f32 = 28.0;
+// how much of the window's height the tree grows to.
+const HEIGHT: f32 = 0.8;
+const STEP: f32 = 1.0 / 240.0;
+// springs along each branch, and from each point to the one two back, which keep it from folding.
+const STIFFNESS: f32 = 4000.0;
+const BEND_STIFFNESS: f32 = 1500.0;
+// how hard each point is pulled back to where it grew, so the tree sways rather than blows away.
+const SWAY: f32 = 10.0;
+const WIND_MAG: f32 = 400.0;
+// px wide the trunk is drawn, and how much thinner each `[` deeper gets.
+const TRUNK_WEIGHT: f32 = 6.0;
+const THINNING: f32 = 0.6;
+
+static HELP: &str =
+    "up/down for more or fewer iterations, W to calm the wind, R to grow a new tree";
+
+struct Model {
+    iterations: usize,
+    angle: f32,
+    branches: Vec<Branch>,
+    // which particle each of the branches' points became.
+    indices: Vec<Vec<usize>>,
+    // where each particle grew.
+    rest: Vec<Vector2>,
+    world: World,
+    timestep: FixedTimestep,
+    wind: Wind,
+    calm: bool,
+    // simulated time, for the wind.
+    elapsed: f32,
+    rng: XorShiftRng,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let mut model = Model {
+        iterations: ITERATIONS,
+        angle: (MIN_ANGLE + MAX_ANGLE) / 2.0,
+        branches: vec![],
+        indices: vec![],
+        rest: vec![],
+        world: World::new(),
+        timestep: FixedTimestep::new(STEP),
+        wind: Wind::new(WIND_MAG),
+        calm: false,
+        elapsed: 0.0,
+        rng: SeedableRng::seed_from_u64(1),
+    };
+    grow(&mut model, app.window_rect());
+    model
+}
+
+/// Grow the tree afresh, scaled to stand in `win`, and string its points together with springs.
+fn grow(model: &mut Model, win: Rect) {
+    let string = LSystem::parse(AXIOM, &RULES).generate(model.iterations);
+    let mut branches = Turtle::new(model.angle).interpret(&string);
+    let top = branches
+        .iter()
+        .flat_map(|b| b.points.iter().map(|p| p.y))
+        .fold(0.0, f32::max);
+    let root = win.mid_bottom() + vec2(0.0, 30.0);
+    let scale = win.h() * HEIGHT / top;
+    for point in branches.iter_mut().flat_map(|b| b.points.iter_mut()) {
+        *point = root + *point * scale;
+    }
+
+    let mut world = World::new();
+    world.gravity = vec2(0.0, 0.0);
+    world.damping = 0.995;
+    // the particle each particle grew from, so the bend springs can reach back past it; the root
+    // grew from a pinned anchor under the ground, to keep the trunk upright.
+    let mut grew_from = vec![None];
+    world.add_particle(Particle::pinned(root - vec2(0.0, scale)));
+    let mut indices: Vec<Vec<usize>> = Vec::with_capacity(branches.len());
+    for branch in &branches {
+        let mut branch_indices = vec![match branch.parent {
+            Some((i, point)) => indices[i][point],
+            None => {
+                grew_from.push(Some(0));
+                world.add_particle(Particle::pinned(branch.points[0]))
+            }
+        }];
+        for point in &branch.points[1..] {
+            let before = *branch_indices.last().unwrap();
+            let index = world.add_particle(Particle::new(*point, vec2(0.0, 0.0)));
+            grew_from.push(Some(before));
+            let length = (*point - world.particles[before].pos).magnitude();
+            world.add_spring(Spring::new(before, index, length, STIFFNESS));
+            if let Some(further) = grew_from[before] {
+                let length = (*point - world.particles[further].pos).magnitude();
+                world.add_spring(Spring::new(further, index, length, BEND_STIFFNESS));
+            }
+            branch_indices.push(index);
+        }
+        indices.push(branch_indices);
+    }
+
+    model.rest = world.particles.iter().map(|p| p.pos).collect();
+    model.branches = branches;
+    model.indices = indices;
+    model.world = world;
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::Up if model.iterations < MAX_ITERATIONS => {
+                model.iterations += 1;
+                grow(model, app.window_rect());
+            }
+            Key::Down if model.iterations > 1 => {
+                model.iterations -= 1;
+                grow(model, app.window_rect());
+            }
+            Key::W => model.calm = !model.calm,
+            Key::R => {
+                model.angle = model.rng.gen_range(MIN_ANGLE, MAX_ANGLE);
+                grow(model, app.window_rect());
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        let dt = model.timestep.dt;
+        model.elapsed += dt;
+        for (particle, rest) in model.world.particles.iter_mut().zip(&model.rest) {
+            if particle.pinned {
+                continue;
+            }
+            let mut force = (*rest - particle.pos) * SWAY;
+            if !model.calm {
+                force += model.wind.force(particle.pos, model.elapsed);
+            }
+            particle.vel += force / particle.mass * dt;
+        }
+        model.world.step(dt);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(225, 236, 240));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    draw.rect()
+        .xy(win.mid_bottom() + vec2(0.0, 15.0))
+        .w_h(win.w(), 30.0)
+        .color(rgb8(120, 140, 90));
+    let particles = &model.world.particles;
+    for (branch, indices) in model.branches.iter().zip(&model.indices) {
+        let weight = (TRUNK_WEIGHT * THINNING.powi(branch.depth as i32)).max(1.0);
+        draw.polyline()
+            .weight(weight)
+            .points(indices.iter().map(|i| particles[*i].pos))
+            .color(rgb8(90, 60, 40));
+    }
+    // a leaf on the end of every twig.
+    for (branch, indices) in model.branches.iter().zip(&model.indices) {
+        if branch.depth > 0 {
+            draw.ellipse()
+                .xy(particles[*indices.last().unwrap()].pos)
+                .radius(3.0)
+                .color(rgba(0.3, 0.6, 0.2, 0.8));
+        }
+    }
+
+    let calm = if model.calm { ", calm" } else { "" };
+    draw.text(&format!(
+        "iterations {}, angle {:.1}°, {} points{}\n{}",
+        model.iterations,
+        model.angle,
+        particles.len(),
+        calm,
+        HELP
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 20.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(40, 40, 50));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
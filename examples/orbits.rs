@@ -0,0 +1,147 @@
+use nannou::prelude::*;
+use nannou_sketches::physics::{FixedTimestep, IntegrationMethod, Particle, World};
+use nannou_sketches::viz::trail::Trail;
+
+const G: f32 = 1000.0;
+const SUN_MASS: f32 = 1500.0;
+const PLANET_MASS: f32 = 2.0;
+// px/s of launch speed per px dragged.
+const LAUNCH_SPEED: f32 = 1.5;
+const STEP: f32 = 1.0 / 240.0;
+// how many frames of its path each body leaves behind it.
+const TRAIL_LENGTH: usize = 240;
+// bodies flung further than this are forgotten.
+const MAX_DISTANCE: f32 = 5000.0;
+
+static HELP: &str = "drag to launch a planet, R to start over";
+
+struct Model {
+    world: World,
+    timestep: FixedTimestep,
+    trails: Vec<Trail>,
+    // where the mouse was pressed, while launching.
+    launch: Option<Vector2>,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    let mut world = World::new();
+    world.gravity = vec2(0.0, 0.0);
+    world.attraction = G;
+    world.softening = 5.0;
+    world.integrator = IntegrationMethod::Verlet;
+    let mut sun = Particle::pinned(vec2(0.0, 0.0));
+    sun.mass = SUN_MASS;
+    sun.radius = 16.0;
+    world.add_particle(sun);
+    // a few planets on circular orbits, each going round at sqrt(G M / r).
+    for (i, r) in [90.0, 150.0, 230.0, 320.0].iter().enumerate() {
+        let angle = i as f32 * 2.1;
+        let out = vec2(angle.cos(), angle.sin());
+        let speed = (G * SUN_MASS / r).sqrt();
+        let mut planet = Particle::new(out * *r, vec2(-out.y, out.x) * speed);
+        planet.mass = PLANET_MASS;
+        planet.radius = 4.0;
+        world.add_particle(planet);
+    }
+    Model {
+        trails: vec![trail(); world.particles.len()],
+        world,
+        timestep: FixedTimestep::new(STEP),
+        launch: None,
+    }
+}
+
+fn trail() -> Trail {
+    let mut trail = Trail::new(TRAIL_LENGTH);
+    trail.weight = 2.0;
+    trail
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => model.launch = Some(app.mouse.position()),
+        Event::WindowEvent {
+            simple: Some(MouseReleased(MouseButton::Left)),
+            ..
+        } => {
+            if let Some(start) = model.launch.take() {
+                let vel = (app.mouse.position() - start) * LAUNCH_SPEED;
+                let mut planet = Particle::new(start, vel);
+                planet.mass = PLANET_MASS;
+                planet.radius = 4.0;
+                model.world.add_particle(planet);
+                model.trails.push(trail());
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::R)),
+            ..
+        } => *model = self::model(app),
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+        model.world.step(model.timestep.dt);
+    }
+    for (trail, body) in model.trails.iter_mut().zip(&model.world.particles) {
+        trail.push(body.pos);
+    }
+    // nothing removed is joined by springs, so the other indices can shift down.
+    let mut i = 0;
+    while i < model.world.particles.len() {
+        if model.world.particles[i].pos.magnitude() > MAX_DISTANCE {
+            model.world.particles.remove(i);
+            model.trails.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(5, 5, 15));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    for (i, (trail, body)) in model.trails.iter().zip(&model.world.particles).enumerate() {
+        let hue = i as f32 * 0.13 % 1.0;
+        trail.draw(&draw, hsla(hue, 0.7, 0.65, 0.6));
+        if body.pinned {
+            draw.ellipse()
+                .xy(body.pos)
+                .radius(body.radius)
+                .color(rgb8(255, 210, 80));
+        } else {
+            draw.ellipse()
+                .xy(body.pos)
+                .radius(body.radius)
+                .color(hsl(hue, 0.7, 0.65));
+        }
+    }
+
+    if let Some(start) = model.launch {
+        draw.arrow()
+            .start(start)
+            .end(app.mouse.position())
+            .weight(1.5)
+            .color(rgba(1.0, 1.0, 1.0, 0.5));
+    }
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 12.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(120, 120, 150));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -1,18 +1,29 @@
 use nannou::prelude::*;
+use nannou_sketches::ease::{map_eased, ping_pong, Curve, Ease, Mode};
+use nannou_sketches::viz::svg::VectorRecorder;
 
-struct Model {}
+struct Model {
+    // V exports the circles as SVG.
+    svg: VectorRecorder,
+}
 
 fn main() {
     nannou::app(model).event(event).simple_window(view).run();
 }
 
 fn model(_app: &App) -> Model {
-    Model {}
+    Model {
+        svg: VectorRecorder::new("pattern_1"),
+    }
 }
 
 fn event(app: &App, model: &mut Model, event: Event) {
     match event {
         Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => model.svg.key(key),
         _ => (),
     }
 }
@@ -21,16 +32,19 @@ fn update(_app: &App, _model: &mut Model, _upd: Update) {}
 
 const N: i32 = 10;
 
-fn view(app: &App, _model: &Model, frame: Frame) {
+fn view(app: &App, model: &Model, frame: Frame) {
     if app.elapsed_frames() == 1 {
         frame.clear(nannou::color::named::WHITE);
     }
-    frame.clear(rgb8(71, 59, 240));
-
     let win = app.window_rect();
     let draw = app.draw();
+    let svg = &model.svg;
+    svg.begin(win);
+    svg.background(&draw, rgb8(71, 59, 240));
 
-    let draw = draw.scale(win.x.len()).translate(vec3(-0.5, -0.5, 0.0));
+    // the grid is a square as wide as the window, centered in it; drawn unscaled, so it's exported
+    // where it is.
+    let size = win.x.len();
 
     for i in 0..N {
         for j in 0..N {
@@ -40,17 +54,26 @@ fn view(app: &App, _model: &Model, frame: Frame) {
             let w_base = 1.0 / N as f32;
             let t = app.duration.since_start.as_secs_f32();
 
-            //let f = ((t + a + b * t) * 0.7).sin();
-            let f = ((t + a - b) * 0.7).sin();
-            let w = w_base * f.abs();
-            draw.ellipse()
-                .resolution(32)
-                .x_y(a, b)
-                .w_h(w, w)
-                .color(rgba(0.5, 1.0, 0.0, 1.0 - f.abs()));
+            // each circle swells and shrinks every PI / 0.7 seconds, out of step with its
+            // neighbours; easing out of a sine is just like |sin|.
+            let phase = (t + a - b) * 0.7 / PI;
+            let f = map_eased(
+                ping_pong(phase),
+                0.0,
+                1.0,
+                Ease::new(Curve::Sine, Mode::Out),
+            );
+            let w = w_base * f * size;
+            svg.ellipse(
+                &draw,
+                vec2(a - 0.5, b - 0.5) * size,
+                vec2(w, w),
+                rgba(0.5, 1.0, 0.0, 1.0 - f),
+            );
         }
     }
 
+    svg.finish();
     draw.to_frame(app, &frame).unwrap();
     frame.submit();
 }
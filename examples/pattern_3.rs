@@ -1,20 +1,71 @@
+use nannou::color::LinSrgb;
+use nannou::image::{DynamicImage, RgbaImage};
 use nannou::prelude::*;
+use nannou_sketches::ease::{Curve, Ease, Mode};
+use nannou_sketches::timeline::{Timeline, Track};
+use nannou_sketches::viz::capture::{draw_image, FrameCapture};
+use nannou_sketches::viz::halftone::{Halftone, Style};
+use nannou_sketches::viz::pixelsort::PixelSort;
+use nannou_sketches::webcam::Webcam;
+
+// px across each halftone cell.
+const CELL: u32 = 8;
+// seconds the intro takes.
+const INTRO: f32 = 3.0;
 
 struct Model {
     centers: Vec<Vec<Vector2>>,
     angles: Vec<Vec<f32>>,
+    // H switches between drawing the pattern as it is, and as halftone dots or dithered pixels.
+    style: Option<Style>,
+    // P sorts each row's pixels, before any halftoning, piling the triangles up on the left.
+    sorting: bool,
+    capture: FrameCapture,
+    // W swaps the pattern for the first webcam's picture, to sort and halftone live; the webcam's
+    // opened the first time, and its latest frame kept.
+    watching: bool,
+    webcam: Option<Webcam>,
+    video: Option<RgbaImage>,
+    // the intro: the grid springs out from the middle as the triangles darken, then starts
+    // wobbling. I plays it again, and dragging across the window scrubs through it.
+    intro: Timeline,
+    spread: Track<f32>,
+    wobble: Track<f32>,
+    ink: Track<LinSrgb>,
 }
 
 fn main() {
-    nannou::app(model).event(event).simple_window(view).run();
+    nannou::app(model)
+        .event(event)
+        .exit(exit)
+        .simple_window(view)
+        .run();
 }
 
 const N: usize = 12;
 
-fn model(_app: &App) -> Model {
+fn model(app: &App) -> Model {
+    let window = app.main_window();
+    let (w, h) = window.inner_size_pixels();
     let mut result = Model {
         centers: vec![],
         angles: vec![],
+        style: None,
+        sorting: false,
+        capture: FrameCapture::new(&window, [w, h]),
+        watching: false,
+        webcam: None,
+        video: None,
+        intro: Timeline::new(INTRO),
+        spread: Track::new(0.0).to(1.5, 1.0, Ease::new(Curve::Elastic, Mode::Out)),
+        wobble: Track::new(0.0)
+            .hold(1.0)
+            .to(INTRO, 30.0, Ease::new(Curve::Bounce, Mode::Out)),
+        ink: Track::new(background().into_linear()).to(
+            1.0,
+            rgb8(197, 50, 0).into_format::<f32>().into_linear(),
+            Ease::new(Curve::Cubic, Mode::Out),
+        ),
     };
 
     let n = (N - 1) as f32;
@@ -34,12 +85,46 @@ fn model(_app: &App) -> Model {
 fn event(app: &App, model: &mut Model, event: Event) {
     match event {
         Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::H)),
+            ..
+        } => {
+            model.style = match model.style {
+                None => Some(Style::Dots),
+                Some(Style::Dots) => Some(Style::Dither),
+                Some(Style::Dither) => None,
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::P)),
+            ..
+        } => model.sorting = !model.sorting,
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::I)),
+            ..
+        } => model.intro.scrub(0.0),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::W)),
+            ..
+        } => {
+            model.watching = !model.watching;
+            if model.webcam.is_none() {
+                model.webcam = Some(Webcam::open(0));
+            }
+        }
         _ => (),
     }
 }
 
-fn update(_app: &App, model: &mut Model, upd: Update) {
+fn update(app: &App, model: &mut Model, upd: Update) {
     let dt = upd.since_last.as_secs_f32();
+    if app.mouse.buttons.left().is_down() {
+        let win = app.window_rect();
+        let x = (app.mouse.x - win.left()) / win.w();
+        model.intro.scrub(x * INTRO);
+    } else {
+        model.intro.advance(dt);
+    }
     for (y, angles) in model.angles.iter_mut().enumerate() {
         for (x, angle) in angles.iter_mut().enumerate() {
             *angle += 0.005
@@ -47,25 +132,88 @@ fn update(_app: &App, model: &mut Model, upd: Update) {
                 + 0.02 * (PI * x as f32 / N as f32 - dt).cos();
         }
     }
+
+    if model.watching {
+        let webcam = model.webcam.as_mut().unwrap();
+        if let Some(frame) = webcam.latest() {
+            model.video = Some(DynamicImage::ImageRgb8(frame).to_rgba());
+        }
+    } else if model.style.is_some() || model.sorting {
+        // drawn offscreen, to be sorted or halftoned once it's read back.
+        let draw = Draw::new();
+        pattern(&draw, app.window_rect(), model);
+        model.capture.capture(&app.main_window(), &draw);
+    }
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    frame.clear(rgb8(238, 168, 0));
-    let win = app.window_rect();
-    let draw = app.draw();
+fn exit(app: &App, model: Model) {
+    model.capture.finish(&app.main_window());
+}
+
+fn background() -> Srgb {
+    rgb8(238, 168, 0).into_format()
+}
+
+fn pattern(draw: &Draw, win: Rect, model: &Model) {
+    draw.background().color(background());
     let draw = draw.translate(Vector3::new(-win.x.len() / 2.0, -win.y.len() / 2.0, 0.0));
 
+    let spread = model.intro.sample(&model.spread);
+    let wobble = model.intro.sample(&model.wobble);
+    let ink = model.intro.sample(&model.ink);
+    let middle = win.top_right();
     let pt = |i: usize, j: usize| {
         let ang: f32 = model.angles[i][j];
-        model.centers[i][j] * win.top_right() * 2.0 + Vector2::new(ang.cos(), ang.sin()) * 30.0
+        let center = middle + (model.centers[i][j] * win.top_right() * 2.0 - middle) * spread;
+        center + Vector2::new(ang.cos(), ang.sin()) * wobble
     };
 
     for i in 0..(N - 1) {
         for j in 0..(N - 1) {
             draw.tri()
                 .points(pt(i, j), pt(i + 1, j), pt(i, j + 1))
-                .color(rgb8(197, 50, 0));
+                .color(ink);
+        }
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(background());
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    // the pattern's drawn as it is until the webcam's first frame comes.
+    let image = if model.watching {
+        model.video.clone()
+    } else if model.style.is_some() || model.sorting {
+        model.capture.latest()
+    } else {
+        None
+    };
+    match image {
+        Some(mut image) => {
+            if model.sorting {
+                PixelSort::new(0.2, 0.9).apply(&mut image);
+            }
+            match model.style {
+                Some(style) => {
+                    let mut halftone = Halftone::new(CELL);
+                    halftone.style = style;
+                    halftone.draw(&draw, &image, win);
+                }
+                None => draw_image(app, &draw, image, win),
+            }
         }
+        None => pattern(&draw, win, model),
+    }
+    // otherwise there'd be no telling a webcam that failed from one that's yet to start.
+    let error = model.webcam.as_ref().and_then(|webcam| webcam.error.as_ref());
+    if let Some(error) = error.filter(|_| model.watching) {
+        draw.text(error)
+            .xy(win.mid_bottom() + vec2(0.0, 12.0))
+            .w(win.w())
+            .font_size(12)
+            .color(rgb8(80, 80, 80));
     }
 
     draw.to_frame(app, &frame).unwrap();
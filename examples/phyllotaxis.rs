@@ -0,0 +1,138 @@
+use nannou::prelude::*;
+use nannou_sketches::phyllotaxis::{Phyllotaxis, Scaling, GOLDEN_ANGLE};
+use nannou_sketches::viz::palette::Palette;
+
+// how far either side of the golden angle the divergence angle swings, in degrees, and how long it
+// takes to swing there and back, in seconds.
+const SWING: f32 = 1.5;
+const PERIOD: f32 = 40.0;
+const COUNT: usize = 1500;
+const MIN_COUNT: usize = 100;
+const MAX_COUNT: usize = 12000;
+// how much of the window the pattern fills.
+const FILL: f32 = 0.45;
+const SCALINGS: [Scaling; 3] = [Scaling::Sqrt, Scaling::Power(0.75), Scaling::Linear];
+// Fibonacci numbers to color every nth seed alike by, which picks out one family of spirals; 0
+// colors by distance out instead.
+const SPIRALS: [usize; 6] = [0, 8, 13, 21, 34, 55];
+
+static HELP: &str = "up/down for more or fewer seeds, S for the spacing, F to pick out spirals, \
+                     G for exactly the golden angle, space to pause";
+
+struct Model {
+    phyllotaxis: Phyllotaxis,
+    scaling: usize,
+    spirals: usize,
+    // how far through its swing the angle is, in seconds.
+    t: f32,
+    paused: bool,
+    palette: Palette,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    Model {
+        phyllotaxis: Phyllotaxis::new(COUNT, 1.0),
+        scaling: 0,
+        spirals: 0,
+        t: 0.0,
+        paused: false,
+        palette: Palette::new(&[
+            Srgb::new(250, 210, 60),
+            Srgb::new(230, 110, 30),
+            Srgb::new(90, 40, 20),
+        ]),
+    }
+}
+
+fn event(_app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            let phyllotaxis = &mut model.phyllotaxis;
+            match key {
+                Key::Up => phyllotaxis.count = (phyllotaxis.count * 2).min(MAX_COUNT),
+                Key::Down => phyllotaxis.count = (phyllotaxis.count / 2).max(MIN_COUNT),
+                Key::S => {
+                    model.scaling = (model.scaling + 1) % SCALINGS.len();
+                    phyllotaxis.scaling = SCALINGS[model.scaling];
+                }
+                Key::F => model.spirals = (model.spirals + 1) % SPIRALS.len(),
+                Key::G => {
+                    // the middle of the swing, and holding there.
+                    model.t = 0.0;
+                    phyllotaxis.angle = GOLDEN_ANGLE;
+                    model.paused = true;
+                }
+                Key::Space => model.paused = !model.paused,
+                _ => (),
+            }
+        }
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    if model.paused {
+        return;
+    }
+    model.t += upd.since_last.as_secs_f32();
+    let swing = (model.t / PERIOD * TAU).sin() * SWING;
+    model.phyllotaxis.angle = GOLDEN_ANGLE + swing.to_radians();
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(30, 40, 25));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    // spaced out to fill the window, whatever the scaling.
+    let mut phyllotaxis = model.phyllotaxis;
+    phyllotaxis.spacing = 1.0;
+    phyllotaxis.spacing = win.w().min(win.h()) * FILL / phyllotaxis.extent();
+    let count = phyllotaxis.count as f32;
+    // about how much room each seed has, if they were spread evenly; seeds further out are bigger.
+    let room = phyllotaxis.extent() / count.sqrt();
+    let spirals = SPIRALS[model.spirals];
+    for (n, point) in phyllotaxis.points().into_iter().enumerate() {
+        let out = n as f32 / count;
+        let color = if spirals == 0 {
+            model.palette.sample_u8(out)
+        } else {
+            model
+                .palette
+                .sample_u8((n % spirals) as f32 / (spirals - 1) as f32)
+        };
+        draw.ellipse()
+            .xy(point)
+            .radius(room * (0.3 + 0.4 * out))
+            .color(rgb8(color[0], color[1], color[2]));
+    }
+
+    let golden = if phyllotaxis.angle == GOLDEN_ANGLE {
+        " (golden)"
+    } else {
+        ""
+    };
+    draw.text(&format!(
+        "{:.3}°{}, {} seeds, {:?}\n{}",
+        phyllotaxis.angle.to_degrees(),
+        golden,
+        phyllotaxis.count,
+        phyllotaxis.scaling,
+        HELP
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 20.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(220, 220, 200));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
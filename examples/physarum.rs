@@ -0,0 +1,129 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::physarum::Physarum;
+use nannou_sketches::viz::palette::Palette;
+
+// px per cell.
+const CELL: f32 = 2.0;
+const AGENTS: usize = 40000;
+// how many cells around the mouse dragging paints food into.
+const BRUSH: f32 = 6.0;
+// how much trail food is worth; more than agents ever leave, so they flock to it.
+const FOOD: f32 = 100.0;
+// how much trail is the brightest color.
+const BRIGHTEST: f32 = 25.0;
+// (sensor angle, sensor distance, turn angle) for the networks the number keys switch between.
+const PRESETS: [(f32, f32, f32); 3] = [
+    // branching veins
+    (PI / 4.0, 9.0, PI / 4.0),
+    // a fine web of long straight strands
+    (PI / 8.0, 24.0, PI / 8.0),
+    // thick, looping cells
+    (PI / 2.0, 5.0, PI / 6.0),
+];
+
+static HELP: &str = "drag to lay food, 1-3 for networks, space to pause, C to clear, R to restart";
+
+struct Model {
+    physarum: Physarum,
+    palette: Palette,
+    // which run this is, to seed the next from.
+    seed: u64,
+    paused: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn physarum(app: &App, seed: u64) -> Physarum {
+    let win = app.window_rect();
+    Physarum::new(
+        (win.w() / CELL) as usize,
+        (win.h() / CELL) as usize,
+        AGENTS,
+        seed,
+    )
+}
+
+fn model(app: &App) -> Model {
+    Model {
+        physarum: physarum(app, 1),
+        palette: Palette::ember(),
+        seed: 1,
+        paused: false,
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => update(app, model),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            let preset = match key {
+                Key::Key1 => Some(0),
+                Key::Key2 => Some(1),
+                Key::Key3 => Some(2),
+                Key::Space => {
+                    model.paused = !model.paused;
+                    None
+                }
+                Key::C => {
+                    model.physarum.trail_mut().fill(0.0);
+                    None
+                }
+                Key::R => {
+                    model.seed += 1;
+                    model.physarum = physarum(app, model.seed);
+                    None
+                }
+                _ => None,
+            };
+            if let Some(preset) = preset {
+                let (sensor_angle, sensor_distance, turn_angle) = PRESETS[preset];
+                model.physarum.sensor_angle = sensor_angle;
+                model.physarum.sensor_distance = sensor_distance;
+                model.physarum.turn_angle = turn_angle;
+            }
+        }
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model) {
+    if app.mouse.buttons.left().is_down() {
+        let pos = (app.mouse.position() - app.window_rect().bottom_left()) / CELL;
+        model.physarum.trail_mut().paint(pos, BRUSH, FOOD);
+    }
+    if !model.paused {
+        model.physarum.step();
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    let win = app.window_rect();
+    let draw = app.draw();
+    let trail = model.physarum.trail();
+    // images go from the top down, and the grid from the bottom up.
+    let pixels = RgbaImage::from_fn(trail.width as u32, trail.height as u32, |x, y| {
+        let (x, y) = (x as usize, trail.height - 1 - y as usize);
+        let [r, g, b] = model.palette.sample_u8(trail.get(x, y) / BRIGHTEST);
+        image::Rgba([r, g, b, 255])
+    });
+    let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+    let size = vec2(trail.width as f32, trail.height as f32) * CELL;
+    draw.texture(&texture)
+        .xy(win.bottom_left() + size / 2.0)
+        .wh(size);
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 12.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -1,17 +1,40 @@
-use nannou::color::Lab;
 use nannou::prelude::*;
+use nannou_sketches::physics::{FixedTimestep, IntegrationMethod, Particle, Spring, World};
+use nannou_sketches::viz::palette::Palette;
+use nannou_sketches::viz::symmetry::Symmetry;
 
 const K: f32 = 30.0;
 const EQUILIBRIUM: f32 = 60.0;
-const GRAVITY: Vector2 = Vector2 {
-    x: 0.0,
-    y: -100000.0,
-};
+const GRAVITY: Vector2 = Vector2 { x: 0.0, y: -1600.0 };
+const STEP: f32 = 1.0 / 240.0;
+// how big a poi's head is, for knocking into the other one.
+const POI_RADIUS: f32 = 5.0;
+// px/s the arrow keys move the second hand.
+const HAND_SPEED: f32 = 200.0;
+// the keys that move the second hand; any other key drops the poi back where they started.
+const ARROWS: [Key; 4] = [Key::Left, Key::Right, Key::Up, Key::Down];
+// how many times round the middle of the window K repeats the poi, turning their trails into
+// mandalas; M mirrors them too.
+const FOLDS: [usize; 5] = [1, 3, 4, 6, 8];
 
 struct Model {
-    pos: Vector2,
-    vel: Vector2,
-    mass: f32,
+    // each hand, followed by the poi swinging from it. The first hand follows the mouse; in dual
+    // mode, the second follows it too, `offset` away, so both hands move together the way a
+    // spinner's do and the two poi can weave around each other.
+    world: World,
+    timestep: FixedTimestep,
+    // where each poi was before the last step, to interpolate from.
+    before: Vec<Vector2>,
+    dual: bool,
+    offset: Vector2,
+    symmetry: Symmetry,
+}
+
+fn hand(i: usize) -> usize {
+    2 * i
+}
+fn poi(i: usize) -> usize {
+    2 * i + 1
 }
 
 fn main() {
@@ -19,40 +42,96 @@ fn main() {
 }
 
 fn model(_app: &App) -> Model {
+    poi_world(false, vec2(120.0, 0.0), Symmetry::new(1))
+}
+
+fn poi_world(dual: bool, offset: Vector2, symmetry: Symmetry) -> Model {
+    let mut world = World::new();
+    world.gravity = GRAVITY;
+    world.damping = 0.99;
+    world.integrator = IntegrationMethod::Rk4;
+    world.collisions = true;
+    let count = if dual { 2 } else { 1 };
+    for i in 0..count {
+        world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+        let mut head = Particle::new(vec2(0.0, 0.0), vec2(0.0, 0.0));
+        head.radius = POI_RADIUS;
+        world.add_particle(head);
+        world.add_spring(Spring::new(hand(i), poi(i), EQUILIBRIUM, K));
+    }
     Model {
-        pos: vec2(0.0, 0.0),
-        vel: vec2(0.0, 0.0),
-        mass: 1.0,
+        world,
+        timestep: FixedTimestep::new(STEP),
+        before: vec![vec2(0.0, 0.0); count],
+        dual,
+        offset,
+        symmetry,
     }
 }
 
+/// Whether a key other than the arrows is held down.
+fn resetting(app: &App) -> bool {
+    app.keys.down.iter().any(|key| !ARROWS.contains(key))
+}
+
 fn event(app: &App, model: &mut Model, event: Event) {
     match event {
         Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Tab)),
+            ..
+        } => *model = poi_world(!model.dual, model.offset, model.symmetry),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::K)),
+            ..
+        } => {
+            let symmetry = &mut model.symmetry;
+            let next = FOLDS.iter().position(|f| *f == symmetry.folds).unwrap_or(0) + 1;
+            symmetry.folds = FOLDS[next % FOLDS.len()];
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(Key::M)),
+            ..
+        } => model.symmetry.mirror = !model.symmetry.mirror,
         _ => (),
     }
 }
 
 fn update(app: &App, model: &mut Model, upd: Update) {
-    if app.elapsed_frames() < 10 || !app.keys.down.is_empty() {
-        model.pos = app.mouse.position() + vec2(-50.0, 0.1);
-        model.vel = vec2(0.0, 0.0);
-    }
     let dt = upd.since_last.as_secs_f32();
-
-    let to_mouse = app.mouse.position() - model.pos;
-
-    let spring = to_mouse.normalize() * (to_mouse.magnitude() - EQUILIBRIUM) * K;
-    let gravity = GRAVITY * dt;
-    let f = spring + gravity;
-    let a = f / model.mass;
-    model.vel += a * dt;
-    model.vel *= 0.99;
-    model.pos += model.vel * dt;
+    let keys = &app.keys.down;
+    let steer = |minus: Key, plus: Key| {
+        (keys.contains(&plus) as i32 - keys.contains(&minus) as i32) as f32 * HAND_SPEED * dt
+    };
+    model.offset += vec2(steer(Key::Left, Key::Right), steer(Key::Down, Key::Up));
+
+    let mouse = app.mouse.position();
+    let reset = app.elapsed_frames() < 10 || resetting(app);
+    let particles = &mut model.world.particles;
+    for (i, before) in model.before.iter_mut().enumerate() {
+        // the second poi starts out to the right of its hand, so they don't start tangled.
+        let (hand_pos, side) = if i == 0 {
+            (mouse, -1.0)
+        } else {
+            (mouse + model.offset, 1.0)
+        };
+        particles[hand(i)].pos = hand_pos;
+        if reset {
+            particles[poi(i)].pos = hand_pos + vec2(50.0 * side, 0.1);
+            particles[poi(i)].vel = vec2(0.0, 0.0);
+            *before = particles[poi(i)].pos;
+        }
+    }
+    for _ in 0..model.timestep.advance(dt) {
+        for (i, before) in model.before.iter_mut().enumerate() {
+            *before = model.world.particles[poi(i)].pos;
+        }
+        model.world.step(model.timestep.dt);
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    if app.elapsed_frames() == 1 || !app.keys.down.is_empty() {
+    if app.elapsed_frames() == 1 || resetting(app) {
         frame.clear(nannou::color::named::WHITE);
     }
 
@@ -80,8 +159,8 @@ fn view(app: &App, model: &Model, frame: Frame) {
             .finish();
     };
 
-    step_(model.pos - model.vel * (0.5 * 1.0 / app.fps()));
-    step_(model.pos);
+    step_(poi.pos - poi.vel * (0.5 * 1.0 / app.fps()));
+    step_(poi.pos);
      */
 
     draw.rect()
@@ -90,35 +169,43 @@ fn view(app: &App, model: &Model, frame: Frame) {
         .color(rgba8(255, 255, 255, 1))
         .finish();
 
-    draw.line()
-        .start(app.mouse.position())
-        .end(model.pos)
-        .color(rgba8(0, 0, 0, 50))
-        .finish();
-
-    let color_a: Lab = rgb8(249, 0, 229).into_format::<f32>().into();
-    let color_b: Lab = rgb8(0, 110, 255).into_format::<f32>().into();
-    // 1/2 m v^2
-    let kinetic = 0.5 * model.mass * model.vel.magnitude2();
-    // 1/2 k d^2
-    let potential = 0.5 * K * (model.pos - app.mouse.position()).magnitude2();
-    let ratio = kinetic / (kinetic + potential);
-    let color = color_a * ratio + color_b * (1.0 - ratio);
-    let color = Rgb::from(color).into_format::<u8>();
-
-    draw.ellipse()
-        .xy(app.mouse.position())
-        .w_h(2.0, 2.0)
-        .color(rgb8(0, 0, 0))
-        .finish();
-
-    draw.line()
-        .start(model.pos)
-        .end(model.pos - (model.vel * (1.0 / app.fps())))
-        .weight(10.0)
-        .caps_round()
-        .color(color)
-        .finish();
+    // each poi fades from blue at rest to pink at full speed; the second from green to orange.
+    let palettes = [Palette::blue_pink(), Palette::green_orange()];
+    // each copy of the poi, round the middle of the window.
+    model.symmetry.draw(&draw, |draw| {
+        for (i, before) in model.before.iter().enumerate() {
+            let hand_pos = model.world.particles[hand(i)].pos;
+            let poi = model.world.particles[poi(i)];
+            let pos = model.timestep.interpolate(*before, poi.pos);
+
+            draw.line()
+                .start(hand_pos)
+                .end(pos)
+                .color(rgba8(0, 0, 0, 50))
+                .finish();
+
+            // 1/2 m v^2
+            let kinetic = 0.5 * poi.mass * poi.vel.magnitude2();
+            // 1/2 k d^2
+            let potential = 0.5 * K * (pos - hand_pos).magnitude2();
+            let ratio = kinetic / (kinetic + potential);
+            let color = palettes[i].sample(ratio).into_format::<u8>();
+
+            draw.ellipse()
+                .xy(hand_pos)
+                .w_h(2.0, 2.0)
+                .color(rgb8(0, 0, 0))
+                .finish();
+
+            draw.line()
+                .start(pos)
+                .end(pos - (poi.vel * (1.0 / app.fps())))
+                .weight(POI_RADIUS * 2.0)
+                .caps_round()
+                .color(color)
+                .finish();
+        }
+    });
 
     draw.to_frame(app, &frame).unwrap();
 
@@ -0,0 +1,129 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::reaction_diffusion::ReactionDiffusion;
+use nannou_sketches::viz::palette::Palette;
+
+// px per cell.
+const CELL: f32 = 4.0;
+const STEPS_PER_FRAME: usize = 8;
+// how many cells around the mouse a click seeds.
+const BRUSH: f32 = 4.0;
+// (feed, kill) for the patterns the number keys switch between.
+const PRESETS: [(f32, f32); 4] = [
+    // coral
+    (0.0545, 0.062),
+    // mitosis: spots that keep dividing.
+    (0.0367, 0.0649),
+    // worms
+    (0.078, 0.061),
+    // spots which drift and don't divide.
+    (0.03, 0.062),
+];
+
+static HELP: &str = "drag to seed, 1-4 for patterns, space to show u or v, C to clear";
+
+struct Model {
+    grid: ReactionDiffusion,
+    palette: Palette,
+    // whether `v` is shown, rather than `u`.
+    show_v: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let win = app.window_rect();
+    let mut grid = ReactionDiffusion::new((win.w() / CELL) as usize, (win.h() / CELL) as usize);
+    let middle = vec2(grid.width as f32, grid.height as f32) / 2.0;
+    grid.seed(middle, BRUSH * 2.0);
+    Model {
+        grid,
+        palette: Palette::neon(),
+        show_v: true,
+    }
+}
+
+/// Which cell `pos`, on screen, is over, in cells from the grid's bottom left corner.
+fn cell(app: &App, pos: Vector2) -> Vector2 {
+    (pos - app.window_rect().bottom_left()) / CELL
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => update(app, model),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            let preset = match key {
+                Key::Key1 => Some(0),
+                Key::Key2 => Some(1),
+                Key::Key3 => Some(2),
+                Key::Key4 => Some(3),
+                Key::Space => {
+                    model.show_v = !model.show_v;
+                    None
+                }
+                Key::C => {
+                    model.grid.clear();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(preset) = preset {
+                let (feed, kill) = PRESETS[preset];
+                model.grid.feed = feed;
+                model.grid.kill = kill;
+            }
+        }
+        _ => (),
+    }
+}
+
+fn update(app: &App, model: &mut Model) {
+    if app.mouse.buttons.left().is_down() {
+        let pos = cell(app, app.mouse.position());
+        model.grid.seed(pos, BRUSH);
+    }
+    for _ in 0..STEPS_PER_FRAME {
+        model.grid.step(1.0);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    let win = app.window_rect();
+    let draw = app.draw();
+    let grid = &model.grid;
+    // images go from the top down, and the grid from the bottom up.
+    let pixels = RgbaImage::from_fn(grid.width as u32, grid.height as u32, |x, y| {
+        let (x, y) = (x as usize, grid.height - 1 - y as usize);
+        // v rarely gets past 0.4, and u rarely below 0.2.
+        let t = if model.show_v {
+            grid.v(x, y) / 0.4
+        } else {
+            (1.0 - grid.u(x, y)) / 0.8
+        };
+        let [r, g, b] = model.palette.sample_u8(t);
+        image::Rgba([r, g, b, 255])
+    });
+    let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+    let size = vec2(grid.width as f32, grid.height as f32) * CELL;
+    draw.texture(&texture)
+        .xy(win.bottom_left() + size / 2.0)
+        .wh(size);
+
+    let field = if model.show_v { "v" } else { "u" };
+    draw.text(&format!(
+        "{}    feed {:.4}, kill {:.4}, showing {}",
+        HELP, grid.feed, grid.kill, field
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 12.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -1,19 +1,41 @@
 use nannou::prelude::*;
+use nannou_sketches::capture::{Capture, Metadata, Screenshot};
+use nannou_sketches::circuits::debugger::{Break, SimDebugger};
 use nannou_sketches::circuits::*;
-use petgraph::graph::NodeIndex;
-use petgraph::visit::EdgeRef;
-use petgraph::Direction;
+use nannou_sketches::cli::Cli;
+use nannou_sketches::gamepad::{Button, Gamepad};
+use nannou_sketches::layout::{layered_with_waypoints, route, Springs};
+use nannou_sketches::render_circuit::{
+    draw_circuit_pulses, draw_tooltip, node_at, Camera, CircuitStyle, PulseSpeed, Pulses,
+};
+use nannou_sketches::replay::Replay;
+use petgraph::graph::{EdgeIndex, NodeIndex};
 use std::collections::HashMap;
 
-const N: usize = 8;
-const K: f32 = 3.0;
-const FRICTION: f32 = 0.96;
-const GOAL_LENGTH: f32 = 1.0 / (N as f32);
+const SELECT_RADIUS: f32 = 20.0;
+/// How far (in pixels) the mouse has to move from where it was pressed to start a drag.
+const DRAG_THRESHOLD: f32 = 4.0;
 
 const UPDATE_EVERY: f32 = 1.0 / 5.0;
 
 const USE_SPRINGS: bool = false;
 
+static HELP: &str = "tab: binary / decimal / hex; M: step a wire / a rank at a time
+W: watch the gate under the mouse; space: pause / resume; N: step one gate while paused";
+
+// keys which do something; any other key resets the inputs.
+const KEYS: [Key; 5] = [Key::Tab, Key::M, Key::W, Key::Space, Key::N];
+
+/// How far the simulation moves each tick.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum StepMode {
+    /// One `update_signals_once`: every gate passes its inputs one wire further.
+    Wire,
+    /// Every gate in the next rank, so evaluation sweeps across the circuit from left to right.
+    /// Only this mode stops at breakpoints.
+    Rank,
+}
+
 struct Model {
     circuit: Circuit,
     a: Vec<NodeIndex>,
@@ -22,87 +44,119 @@ struct Model {
     c: NodeIndex,
 
     positions: HashMap<NodeIndex, Vector2>,
-    velocities: HashMap<NodeIndex, Vector2>,
+    springs: Springs,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+    pulses: Pulses,
 
     update_order: Vec<NodeIndex>,
-
-    selected: NodeIndex,
+    debugger: SimDebugger,
+    ranks: HashMap<NodeIndex, u32>,
+    mode: StepMode,
+    /// The rank the last tick updated, in `StepMode::Rank`.
+    last_rank: Option<u32>,
+    paused: bool,
+    /// The breakpoint which paused the simulation, if it was one.
+    hit: Option<Break>,
+
+    /// The gate under the mouse.
+    selected: Option<NodeIndex>,
+    /// The gate being dragged, where the mouse was pressed, and whether it has moved yet; a press
+    /// and release without moving more than `DRAG_THRESHOLD` toggles an input instead.
+    dragging: Option<(NodeIndex, Vector2, bool)>,
+
+    camera: Camera,
+    /// Where the mouse was, while panning with the middle button.
+    panning: Option<Vector2>,
+    /// A game controller's sticks pan and zoom too, and Start pauses.
+    gamepad: Gamepad,
+
+    bus_display: BusDisplay,
+    /// Records the mouse and keys with `--record`, or plays them back with `--replay`; the
+    /// simulation's clock, the mouse and the keys held are read from it either way.
+    replay: Replay,
+    capture: Option<Capture>,
+    screenshot: Screenshot,
 }
 
 fn main() {
-    nannou::app(model).event(event).simple_window(view).run();
+    let mut app = nannou::app(model)
+        .event(event)
+        .exit(exit)
+        .simple_window(view);
+    if let Some((width, height)) = cli().parse().size() {
+        app = app.size(width, height);
+    }
+    app.run();
+}
+
+/// A `--count`-bit adder, 8 by default.
+fn cli() -> Cli {
+    Cli::new("ripple_carry_circuit")
 }
 
 fn model(_app: &App) -> Model {
+    let args = cli().parse();
+    let n = args.count(8);
+    // the sum, carry and all, has to fit in a `usize` to be shown.
+    assert!(n >= 1 && n < 64, "the adder has to have from 1 to 63 bits");
     let mut circuit = Circuit::new();
 
-    let a = (0..N)
+    let a = (0..n)
         .into_iter()
         .map(|_| circuit.add_input())
         .collect::<Vec<_>>();
-    let b = (0..N)
+    let b = (0..n)
         .into_iter()
         .map(|_| circuit.add_input())
         .collect::<Vec<_>>();
+    for i in 0..n {
+        circuit.set_label(a[i], &format!("a{}", i));
+        circuit.set_label(b[i], &format!("b{}", i));
+    }
     let (s, c) = circuit.ripple_carry(&a, &b);
-    let c = circuit.add_output(c);
+    let c = circuit.add_labeled_output(c, "c");
     let s = s
         .into_iter()
-        .map(|si| circuit.add_output(si))
+        .enumerate()
+        .map(|(i, si)| circuit.add_labeled_output(si, &format!("s{}", i)))
         .collect::<Vec<_>>();
 
     let update_order = circuit.update_order();
-
-    let mut ranks = circuit.ranks();
-    let max = *ranks.values().max().unwrap();
-    for s in &s {
-        ranks.insert(*s, max + 1);
-    }
-    /*
-    for (n, r) in ranks.iter_mut() {
-        if *r == 2 && circuit.0[*n] == Gate::And || *r >= 3 {
-            *r += 1;
-        }
-    }
-     */
-
-    let mut positions = HashMap::new();
-    let mut velocities = HashMap::new();
-
-    if !USE_SPRINGS {
-        let flipped = flip_ranks(&ranks);
-        let x_slots = (flipped.len()) as f32;
-        for (i, rank) in flipped.iter().enumerate() {
-            let y_slots = (rank.len()) as f32;
-
-            for (j, node) in rank.iter().enumerate() {
-                if circuit.0[*node] == Gate::MetaInput {
-                    continue;
-                }
-
-                positions.insert(
-                    *node,
-                    vec2(
-                        (i + 1) as f32 / (x_slots + 1.0),
-                        1.0 - ((j + 1) as f32 / (y_slots + 1.0)),
-                    ),
-                );
-            }
-        }
-    }
-    for node in circuit.0.node_indices() {
-        if USE_SPRINGS {
+    let debugger = SimDebugger::new(&circuit);
+    let ranks = circuit.ranks();
+
+    let (mut positions, waypoints) = if USE_SPRINGS {
+        (HashMap::new(), HashMap::new())
+    } else {
+        layered_with_waypoints(&circuit)
+    };
+    if USE_SPRINGS {
+        for node in circuit.graph().node_indices() {
             positions.insert(node, nannou::rand::rand::random());
         }
-        velocities.insert(node, vec2(0.0, 0.0));
     }
-    for i in 0..N {
-        positions.insert(a[i], vec2(0.0, 1.0 - (i as f32 / (N * 2) as f32)));
-        positions.insert(b[i], vec2(0.0, 0.5 - (i as f32 / (N * 2) as f32)));
-        positions.insert(s[i], vec2(1.0, 1.0 - (i as f32 / (N) as f32)));
+    for i in 0..n {
+        positions.insert(a[i], vec2(0.0, 1.0 - (i as f32 / (n * 2) as f32)));
+        positions.insert(b[i], vec2(0.0, 0.5 - (i as f32 / (n * 2) as f32)));
+        positions.insert(s[i], vec2(1.0, 1.0 - (i as f32 / (n) as f32)));
     }
     positions.insert(c, vec2(1.0, 0.0));
 
+    // springs move the gates around, so wires are only routed when they stay put.
+    let routes = if USE_SPRINGS {
+        HashMap::new()
+    } else {
+        route(&circuit, &positions, &waypoints)
+    };
+    let pulses = Pulses::new(&circuit, PulseSpeed::PerWire(UPDATE_EVERY));
+
+    let capture = Capture::from_args(&args);
+    if let Some(capture) = &capture {
+        let mut metadata = Metadata::new("ripple_carry_circuit", None);
+        metadata.param("bits", &n);
+        capture.write_metadata(&metadata);
+    }
+
     Model {
         circuit,
         a,
@@ -110,32 +164,131 @@ fn model(_app: &App) -> Model {
         s,
         c,
         positions,
-        velocities,
+        springs: Springs::new(1.0 / n as f32),
+        routes,
+        pulses,
         update_order,
-        selected: c,
+        debugger,
+        ranks,
+        mode: StepMode::Wire,
+        last_rank: None,
+        paused: false,
+        hit: None,
+        selected: None,
+        dragging: None,
+        camera: Camera::default(),
+        panning: None,
+        gamepad: Gamepad::new(),
+        bus_display: BusDisplay::default(),
+        replay: Replay::from_args(&args),
+        capture,
+        screenshot: Screenshot::new(),
     }
 }
 
 fn event(app: &App, model: &mut Model, event: Event) {
     match event {
-        Event::Update(upd) => update(app, model, upd),
+        Event::Update(upd) => {
+            // while playing back, the recorded input comes in here instead of from the window.
+            for event in model.replay.update(upd.since_last.as_secs_f32()) {
+                window_event(app, model, event);
+            }
+            update(app, model);
+        }
         Event::WindowEvent {
-            simple: Some(MousePressed(_)),
+            simple: Some(event),
             ..
         } => {
-            let current = model.circuit.get_1_in(model.selected);
-            model.circuit.set_input(model.selected, !current);
+            if let Some(event) = model.replay.input(event) {
+                window_event(app, model, event);
+            }
         }
-        Event::WindowEvent {
-            simple:
-                Some(Touch(TouchEvent {
-                    phase: TouchPhase::Started,
-                    position,
-                    ..
-                })),
+        _ => (),
+    }
+}
+
+fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
+    match event {
+        MousePressed(MouseButton::Left) => {
+            let pressed = model.replay.mouse();
+            model.dragging = model.selected.map(|node| (node, pressed, false));
+        }
+        MousePressed(MouseButton::Middle) => {
+            model.panning = Some(model.replay.mouse());
+        }
+        MouseReleased(MouseButton::Middle) => {
+            model.panning = None;
+        }
+        KeyPressed(Key::Tab) => {
+            model.bus_display = model.bus_display.next();
+        }
+        KeyPressed(Key::W) => {
+            if let Some(node) = model.selected {
+                if model.debugger.watched().contains(&node) {
+                    model.debugger.clear_break(node);
+                } else {
+                    model.debugger.break_on_change(node);
+                    model.mode = StepMode::Rank;
+                }
+            }
+        }
+        KeyPressed(Key::M) => {
+            model.mode = match model.mode {
+                StepMode::Wire => StepMode::Rank,
+                StepMode::Rank => StepMode::Wire,
+            };
+            model.last_rank = None;
+        }
+        KeyPressed(Key::Space) => {
+            model.paused = !model.paused;
+            model.hit = None;
+        }
+        KeyPressed(Key::N) => {
+            if model.paused {
+                model.hit = model.debugger.step_gate(&mut model.circuit);
+                model.pulses.observe(&model.circuit, model.replay.time());
+            }
+        }
+        KeyPressed(key) => model.screenshot.key(app, key),
+        MouseWheel(delta, _) => {
+            model.camera.scroll(delta, model.replay.mouse());
+        }
+        MouseMoved(position) => {
+            if let Some(last) = model.panning {
+                model.camera.pan += position - last;
+                model.panning = Some(position);
+            }
+            if let Some((node, pressed, moved)) = model.dragging {
+                if !moved && (position - pressed).magnitude() <= DRAG_THRESHOLD {
+                    return;
+                }
+                let unmap_pos = model.camera.unmap_pos(circuit_rect(app.window_rect()));
+                model.positions.insert(node, unmap_pos(position));
+                model.springs.pinned.insert(node);
+                // the routes were made for the old position; fall back to straight wires.
+                let circuit = &model.circuit;
+                model.routes.retain(|edge, _| {
+                    let (source, target) = circuit.graph().edge_endpoints(*edge).unwrap();
+                    source != node && target != node
+                });
+                model.dragging = Some((node, pressed, true));
+            }
+        }
+        MouseReleased(MouseButton::Left) => {
+            if let Some((node, _, false)) = model.dragging {
+                if model.circuit.graph()[node] == Gate::Input {
+                    let current = model.circuit.get_1_in(node);
+                    model.circuit.set_input(node, !current);
+                }
+            }
+            model.dragging = None;
+        }
+        Touch(TouchEvent {
+            phase: TouchPhase::Started,
+            position,
             ..
-        } => {
-            let map_pos = make_map_pos(app.window_rect());
+        }) => {
+            let map_pos = model.camera.map_pos(circuit_rect(app.window_rect()));
             let selected = *model
                 .a
                 .iter()
@@ -152,60 +305,60 @@ fn event(app: &App, model: &mut Model, event: Event) {
     }
 }
 
+fn exit(_app: &App, model: Model) {
+    model.replay.finish();
+}
+
 fn epoch(t: f32) -> u32 {
     (t / UPDATE_EVERY).floor() as u32
 }
 
-fn update(app: &App, model: &mut Model, upd: Update) {
-    let dt = upd.since_last.as_secs_f32();
-    let t = app.duration.since_start.as_secs_f32();
-    let map_pos = make_map_pos(app.window_rect());
-
-    model.selected = *model
-        .a
-        .iter()
-        .chain(model.b.iter())
-        .min_by_key(|n| {
-            (((map_pos(model.positions[*n]) - app.mouse.position()).magnitude2()) * 10000.0)
-                as usize
-        })
-        .unwrap();
-
-    if t < 0.2 || !app.keys.down.is_empty() {
-        for i in 0..N {
-            model.circuit.set_input(model.a[i], false);
-            model.circuit.set_input(model.b[i], false);
+fn update(app: &App, model: &mut Model) {
+    let dt = model.replay.dt();
+    let t = model.replay.time();
+    // the game controller isn't recorded, so it's left out of playing back.
+    if !model.replay.playing() {
+        model.gamepad.update();
+        model.gamepad.drive(&mut model.camera, dt);
+        if model.gamepad.pressed(Button::Start) {
+            model.paused = !model.paused;
+            model.hit = None;
         }
     }
+    let map_pos = model.camera.map_pos(circuit_rect(app.window_rect()));
+
+    if model.dragging.is_none() {
+        model.selected = node_at(
+            &model.positions,
+            &map_pos,
+            model.replay.mouse(),
+            SELECT_RADIUS,
+        );
+    }
 
-    if epoch(t - dt) < epoch(t) {
-        model.circuit.update_signals_once(&model.update_order);
+    if t < 0.2 || model.replay.keys().iter().any(|key| !KEYS.contains(key)) {
+        for (a, b) in model.a.iter().zip(&model.b) {
+            model.circuit.set_input(*a, false);
+            model.circuit.set_input(*b, false);
+        }
     }
 
-    if USE_SPRINGS && t < 30.0 {
-        for node in model.circuit.0.node_indices() {
-            let node_type = model.circuit.0[node];
-            if node_type == Gate::MetaInput || node_type == Gate::Input || node_type == Gate::Output
-            {
-                continue;
+    if !model.paused && epoch(t - dt) < epoch(t) {
+        match model.mode {
+            StepMode::Wire => {
+                model.circuit.update_signals_once(&model.update_order);
             }
-            let pos = model.positions[&node];
-            let vel = model.velocities[&node];
-            let mut force = vec2(0.0, 0.0);
-            for edge in model.circuit.0.edges_directed(node, Direction::Incoming) {
-                let d = model.positions[&edge.source()] - pos;
-                force += d.normalize() * (d.magnitude() - GOAL_LENGTH) * K;
+            StepMode::Rank => {
+                model.last_rank = Some(model.debugger.next_rank() as u32);
+                model.hit = model.debugger.step_rank(&mut model.circuit);
+                model.paused = model.hit.is_some();
             }
-            for edge in model.circuit.0.edges_directed(node, Direction::Outgoing) {
-                let d = model.positions[&edge.target()] - pos;
-                force += d.normalize() * (d.magnitude() - GOAL_LENGTH) * K;
-            }
-            force += vec2(1.0 - pos.x, 0.0);
-            let vel = (vel + force * dt) * FRICTION;
-            let pos = pos + vel * dt;
-            model.positions.insert(node, pos);
-            model.velocities.insert(node, vel);
         }
+        model.pulses.observe(&model.circuit, t);
+    }
+
+    if USE_SPRINGS && t < 30.0 {
+        model.springs.step(&model.circuit, &mut model.positions, dt);
     }
     for (n, position) in model.positions.iter() {
         if position.x.is_nan() || position.y.is_nan() {
@@ -214,109 +367,132 @@ fn update(app: &App, model: &mut Model, upd: Update) {
     }
 }
 
-static A_LABELS: &'static [&'static str] = &["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "a8"];
-static B_LABELS: &'static [&'static str] = &["b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8"];
-static S_LABELS: &'static [&'static str] = &["s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8"];
-
-fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
-    let bl = win.bottom_left();
-    let tr = win.top_right();
-    let to_tr = tr - bl;
-    let bl_ = bl + to_tr * 0.1;
-    let to_tr_ = to_tr * 0.8;
+/// A short name for a gate: its label, or its type.
+fn describe(circuit: &Circuit, node: NodeIndex) -> String {
+    match circuit.label(node) {
+        Some(label) => label.to_string(),
+        None => format!("{:?} {}", circuit.graph()[node], node.index()),
+    }
+}
 
-    move |p: Vector2| bl_ + vec2(to_tr_.x * p.x, to_tr_.y * p.y)
+/// The part of the window the circuit fills when the camera hasn't moved.
+fn circuit_rect(win: Rect) -> Rect {
+    Rect::from_xy_wh(win.xy(), win.wh() * 0.8)
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     frame.clear(rgb8(50, 50, 50));
     let win = app.window_rect();
     let draw = app.draw();
-    let map_pos = make_map_pos(win);
-
-    let edges = model.circuit.0.edge_count() as f32;
-
-    for (i, edge) in model.circuit.0.edge_references().enumerate() {
-        if model.circuit.0[edge.target()] == Gate::Input {
-            continue;
+    let map_pos = model.camera.map_pos(circuit_rect(win));
+
+    // a band behind the rank the last tick updated.
+    if let Some(rank) = model.last_rank {
+        let xs = model
+            .ranks
+            .iter()
+            .filter(|(_, r)| **r == rank)
+            .map(|(node, _)| map_pos(model.positions[node]).x)
+            .collect::<Vec<_>>();
+        if !xs.is_empty() {
+            let left = xs.iter().cloned().fold(f32::INFINITY, f32::min);
+            let right = xs.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let pad = 20.0 * model.camera.zoom;
+            draw.rect()
+                .x_y((left + right) / 2.0, win.y())
+                .w_h(right - left + pad * 2.0, win.h())
+                .color(rgba(0.5, 0.7, 1.0, 0.12));
+            draw.text(&format!("rank {}", rank))
+                .x_y((left + right) / 2.0, win.top() - 20.0)
+                .color(rgb8(160, 190, 255));
         }
-        let hue = (i as f32) / edges;
-        let lightness = if *edge.weight() { 0.7 } else { 0.1 };
-        let color = hsl(hue, 1.0, lightness);
-
-        draw.line()
-            .start(map_pos(model.positions[&edge.source()]))
-            .end(map_pos(model.positions[&edge.target()]))
-            .weight(5.0)
-            .color(color);
     }
 
-    for node in model.circuit.0.node_indices() {
-        let text = match model.circuit.0[node] {
-            Gate::MetaInput => continue,
-            Gate::Input | Gate::Output => "",
-            Gate::Or => "|",
-            Gate::And => "&",
-            Gate::Not => "!",
-            Gate::Xor => "^",
-        };
-        let pos = map_pos(model.positions[&node]);
-        let ellipse_color = if node == model.selected {
-            rgb8(100, 100, 200)
-        } else {
-            rgb8(100, 100, 100)
-        };
-        draw.ellipse().xy(pos).w_h(20.0, 20.0).color(ellipse_color);
-
-        draw.text(text).xy(pos).color(rgb8(255, 255, 255));
+    let style = CircuitStyle {
+        wire_weight: 5.0,
+        rainbow: true,
+        ..CircuitStyle::default()
     }
+    .zoomed(model.camera.zoom);
+    draw_circuit_pulses(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+        model.pulses.at(model.replay.time()),
+    );
+    for node in model.debugger.watched() {
+        draw.ellipse()
+            .xy(map_pos(model.positions[node]))
+            .w_h(38.0 * model.camera.zoom, 38.0 * model.camera.zoom)
+            .no_fill()
+            .stroke(rgb8(230, 60, 60))
+            .stroke_weight(3.0);
+    }
+    if let Some(hit) = model.hit {
+        draw.ellipse()
+            .xy(map_pos(model.positions[&hit.gate]))
+            .w_h(34.0 * model.camera.zoom, 34.0 * model.camera.zoom)
+            .color(rgba(1.0, 0.3, 0.3, 0.5));
+    }
+    if model.paused {
+        // the gate the next step will update.
+        draw.ellipse()
+            .xy(map_pos(model.positions[&model.debugger.next_gate()]))
+            .w_h(26.0 * model.camera.zoom, 26.0 * model.camera.zoom)
+            .no_fill()
+            .stroke(rgb8(255, 255, 255))
+            .stroke_weight(2.0);
+    }
+    if let Some(selected) = model.selected {
+        draw.ellipse()
+            .xy(map_pos(model.positions[&selected]))
+            .w_h(30.0 * model.camera.zoom, 30.0 * model.camera.zoom)
+            .no_fill()
+            .stroke(rgb8(100, 100, 200))
+            .stroke_weight(3.0);
+    }
+
     let (mut a_, mut b_, mut s_) = (0, 0, 0);
     for (i, a) in model.a.iter().enumerate() {
-        draw.text(&A_LABELS[i])
-            .xy(map_pos(model.positions[a]))
-            .color(rgb8(255, 255, 255));
-
         a_ = set_bit(a_, i, model.circuit.get_1_in(*a));
     }
     for (i, b) in model.b.iter().enumerate() {
-        draw.text(&B_LABELS[i])
-            .xy(map_pos(model.positions[b]))
-            .color(rgb8(255, 255, 255));
         b_ = set_bit(b_, i, model.circuit.get_1_in(*b));
     }
-    for (i, s) in model.s.iter().enumerate() {
-        draw.text(&S_LABELS[i])
-            .xy(map_pos(model.positions[s]))
-            .color(rgb8(255, 255, 255));
-        s_ = set_bit(s_, i, model.circuit.get_1_in(*s));
+    for i in 0..model.s.len() {
+        s_ = set_bit(s_, i, model.circuit.output_value(&format!("s{}", i)));
     }
-    s_ = set_bit(s_, 8, model.circuit.get_1_in(model.c));
+    let n = model.a.len();
+    s_ = set_bit(s_, n, model.circuit.output_value("c"));
 
-    draw.text(&format!("{}", a_))
+    draw.text(&model.bus_display.format(a_, n))
         .xy(map_pos(vec2(-0.07, 0.785)))
         .font_size(16);
 
-    draw.text(&format!("{}", b_))
+    draw.text(&model.bus_display.format(b_, n))
         .xy(map_pos(vec2(-0.07, 0.285)))
         .font_size(16);
 
-    draw.text(&format!("{}", s_))
+    draw.text(&model.bus_display.format(s_, n + 1))
         .xy(map_pos(vec2(1.07, 0.5)))
         .font_size(16);
 
     draw.line()
-        .start(map_pos(vec2(-0.05, 1.0 - 0.0 / (N as f32 * 2.0))))
+        .start(map_pos(vec2(-0.05, 1.0 - 0.0 / (n as f32 * 2.0))))
         .end(map_pos(vec2(
             -0.05,
-            1.0 - ((N - 1) as f32) / (N as f32 * 2.0),
+            1.0 - ((n - 1) as f32) / (n as f32 * 2.0),
         )))
         .color(rgb8(255, 255, 255));
 
     draw.line()
-        .start(map_pos(vec2(-0.05, 0.5 - 0.0 / (N as f32 * 2.0))))
+        .start(map_pos(vec2(-0.05, 0.5 - 0.0 / (n as f32 * 2.0))))
         .end(map_pos(vec2(
             -0.05,
-            0.5 - ((N - 1) as f32) / (N as f32 * 2.0),
+            0.5 - ((n - 1) as f32) / (n as f32 * 2.0),
         )))
         .color(rgb8(255, 255, 255));
 
@@ -325,11 +501,43 @@ fn view(app: &App, model: &Model, frame: Frame) {
         .end(map_pos(vec2(1.05, 0.0)))
         .color(rgb8(255, 255, 255));
 
-    draw.text("^ click")
+    draw.text("^ click to flip, drag to move")
         .xy(map_pos(vec2(0.0, -0.05)))
         .color(rgb8(255, 255, 255))
         .font_size(16);
 
+    let status = match (model.hit, model.paused) {
+        (Some(hit), _) => format!(
+            "stopped: {} flipped the signal into {}",
+            describe(&model.circuit, hit.gate),
+            describe(&model.circuit, hit.watched)
+        ),
+        (None, true) => "paused".to_string(),
+        (None, false) => match model.mode {
+            StepMode::Wire => String::new(),
+            StepMode::Rank => "stepping a rank at a time".to_string(),
+        },
+    };
+    draw.text(&status)
+        .xy(win.mid_bottom() + vec2(0.0, 60.0))
+        .w(win.w())
+        .font_size(14)
+        .color(rgb8(255, 150, 150));
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 25.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(180, 180, 180));
+
+    if let (Some(node), None) = (model.selected, model.dragging) {
+        let text = model.circuit.describe_node(node, &model.ranks);
+        draw_tooltip(&draw, win, model.replay.mouse(), &text);
+    }
+
+    if let Some(capture) = &model.capture {
+        capture.save(app);
+    }
     draw.to_frame(app, &frame).unwrap();
     frame.submit();
 }
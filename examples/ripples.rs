@@ -0,0 +1,123 @@
+use nannou::image::{self, DynamicImage, RgbaImage};
+use nannou::prelude::*;
+use nannou_sketches::viz::palette::Palette;
+use nannou_sketches::wave::{Boundary, WaveField};
+
+// px per cell.
+const CELL: f32 = 4.0;
+const STEPS_PER_FRAME: usize = 2;
+// how many cells across a click's splash is, and how deep.
+const SPLASH_RADIUS: f32 = 4.0;
+const SPLASH_DEPTH: f32 = 2.0;
+// how far the field moves before it's the darkest or brightest color.
+const RANGE: f32 = 0.5;
+// how many cells apart the rows of lines are, and how many px each row is displaced per unit.
+const ROW_SPACING: usize = 4;
+const LINE_SCALE: f32 = 12.0;
+
+static HELP: &str = "click to drop ripples, B to change the edges, V to switch views, C to calm";
+
+struct Model {
+    field: WaveField,
+    palette: Palette,
+    // whether the field is drawn as rows of lines, rather than colors.
+    lines: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(app: &App) -> Model {
+    let win = app.window_rect();
+    Model {
+        field: WaveField::new((win.w() / CELL) as usize, (win.h() / CELL) as usize),
+        palette: Palette::deep_water(),
+        lines: false,
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(_) => update(model),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        } => {
+            let pos = (app.mouse.position() - app.window_rect().bottom_left()) / CELL;
+            model.field.splash(pos, SPLASH_RADIUS, SPLASH_DEPTH);
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::B => {
+                model.field.boundary = match model.field.boundary {
+                    Boundary::Absorbing => Boundary::Fixed,
+                    Boundary::Fixed => Boundary::Free,
+                    Boundary::Free => Boundary::Wrap,
+                    Boundary::Wrap => Boundary::Absorbing,
+                }
+            }
+            Key::V => model.lines = !model.lines,
+            Key::C => model.field.clear(),
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model) {
+    for _ in 0..STEPS_PER_FRAME {
+        model.field.step();
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(5, 10, 40));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let field = &model.field;
+    let corner = win.bottom_left() + vec2(CELL, CELL) / 2.0;
+
+    if model.lines {
+        for y in (0..field.height).step_by(ROW_SPACING) {
+            let points = (0..field.width).map(|x| {
+                let lift = field.displacement(x, y) * LINE_SCALE;
+                corner + vec2(x as f32, y as f32) * CELL + vec2(0.0, lift)
+            });
+            draw.polyline()
+                .weight(1.5)
+                .points(points)
+                .color(rgb8(200, 240, 255));
+        }
+    } else {
+        // images go from the top down, and the field from the bottom up.
+        let pixels = RgbaImage::from_fn(field.width as u32, field.height as u32, |x, y| {
+            let (x, y) = (x as usize, field.height - 1 - y as usize);
+            let t = 0.5 + field.displacement(x, y) / (2.0 * RANGE);
+            let [r, g, b] = model.palette.sample_u8(t);
+            image::Rgba([r, g, b, 255])
+        });
+        let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(pixels));
+        let size = vec2(field.width as f32, field.height as f32) * CELL;
+        draw.texture(&texture)
+            .xy(win.bottom_left() + size / 2.0)
+            .wh(size);
+    }
+
+    let boundary = match field.boundary {
+        Boundary::Absorbing => "absorbing",
+        Boundary::Fixed => "fixed",
+        Boundary::Free => "free",
+        Boundary::Wrap => "wrapping",
+    };
+    draw.text(&format!("{} edges\n{}", boundary, HELP))
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(200, 200, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
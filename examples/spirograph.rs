@@ -0,0 +1,169 @@
+use nannou::prelude::*;
+use nannou_sketches::spirograph::{Spirograph, Wheel};
+
+// px per unit of radius.
+const SCALE: f32 = 3.0;
+const RING: f32 = 96.0;
+// how far round the ring the first wheel goes each second, and in how many steps.
+const SPEED: f32 = 1.5;
+const STEPS_PER_FRAME: usize = 8;
+// the most points of the curve kept; the oldest are dropped past this.
+const MAX_POINTS: usize = 40000;
+const MAX_WHEELS: usize = 3;
+
+static HELP: &str = "left/right for the last wheel's size, up/down to move the pen, \
+                     O to roll outside, N to nest a wheel, backspace to remove one, \
+                     space to pause, C to clear";
+
+struct Model {
+    spirograph: Spirograph,
+    theta: f32,
+    curve: Vec<Vector2>,
+    paused: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    Model {
+        spirograph: Spirograph::new(RING, 35.0, 25.0),
+        theta: 0.0,
+        curve: vec![],
+        paused: false,
+    }
+}
+
+fn event(_app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(model, upd),
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => {
+            let spirograph = &mut model.spirograph;
+            let last = spirograph.wheels.len() - 1;
+            let changed = match key {
+                Key::Left => {
+                    let wheel = &mut spirograph.wheels[last];
+                    wheel.radius = (wheel.radius - 1.0).max(1.0);
+                    true
+                }
+                Key::Right => {
+                    let wheel = &mut spirograph.wheels[last];
+                    wheel.radius += 1.0;
+                    true
+                }
+                Key::Down => {
+                    spirograph.pen = (spirograph.pen - 1.0).max(0.0);
+                    true
+                }
+                Key::Up => {
+                    spirograph.pen += 1.0;
+                    true
+                }
+                Key::O => {
+                    let wheel = &mut spirograph.wheels[last];
+                    wheel.inside = !wheel.inside;
+                    true
+                }
+                Key::N if spirograph.wheels.len() < MAX_WHEELS => {
+                    // a smaller wheel inside the last one, going round it a little faster.
+                    let radius = (spirograph.wheels[last].radius / 3.0).round().max(1.0);
+                    spirograph.wheels.push(Wheel {
+                        speed: 2.0,
+                        ..Wheel::new(radius)
+                    });
+                    true
+                }
+                Key::Back if spirograph.wheels.len() > 1 => {
+                    spirograph.wheels.pop();
+                    true
+                }
+                Key::Space => {
+                    model.paused = !model.paused;
+                    false
+                }
+                Key::C => true,
+                _ => false,
+            };
+            // the old curve's no longer what this spirograph draws.
+            if changed {
+                model.curve.clear();
+            }
+        }
+        _ => (),
+    }
+}
+
+fn update(model: &mut Model, upd: Update) {
+    if model.paused {
+        return;
+    }
+    let step = upd.since_last.as_secs_f32() * SPEED / STEPS_PER_FRAME as f32;
+    for _ in 0..STEPS_PER_FRAME {
+        model.theta += step;
+        model.curve.push(model.spirograph.pen_at(model.theta));
+    }
+    if model.curve.len() > MAX_POINTS {
+        let extra = model.curve.len() - MAX_POINTS;
+        model.curve.drain(..extra);
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(250, 248, 240));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let scaled = draw.scale(SCALE);
+    let spirograph = &model.spirograph;
+
+    scaled
+        .polyline()
+        .weight(1.0 / SCALE)
+        .points(model.curve.iter().cloned())
+        .color(rgba(0.8, 0.1, 0.3, 0.8));
+
+    // the ring and wheels, and the arms joining their centers to the pen.
+    let wheels = spirograph.wheels_at(model.theta);
+    let radii = std::iter::once(spirograph.ring).chain(spirograph.wheels.iter().map(|w| w.radius));
+    for ((center, _), radius) in wheels.iter().zip(radii) {
+        scaled
+            .ellipse()
+            .xy(*center)
+            .radius(radius)
+            .no_fill()
+            .stroke_weight(1.0 / SCALE)
+            .stroke(rgba(0.2, 0.2, 0.3, 0.5));
+    }
+    let pen = spirograph.pen_at(model.theta);
+    let arm = wheels.iter().map(|(center, _)| *center).chain(Some(pen));
+    scaled
+        .polyline()
+        .weight(1.5 / SCALE)
+        .points(arm)
+        .color(rgba(0.2, 0.2, 0.3, 0.8));
+    draw.ellipse()
+        .xy(pen * SCALE)
+        .radius(3.0)
+        .color(rgb8(200, 20, 70));
+
+    let sizes = spirograph
+        .wheels
+        .iter()
+        .map(|w| format!("{}{}", if w.inside { "" } else { "outside " }, w.radius))
+        .collect::<Vec<_>>()
+        .join(", ");
+    draw.text(&format!(
+        "ring {}, wheels {}, pen {}\n{}",
+        spirograph.ring, sizes, spirograph.pen, HELP
+    ))
+    .xy(win.mid_bottom() + vec2(0.0, 24.0))
+    .w(win.w())
+    .font_size(12)
+    .color(rgb8(60, 60, 80));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,241 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::truth_table::TruthTable;
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use nannou_sketches::viz::gradient::Gradient;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+
+// the segments lit for each digit, segment a (the top) in bit 0 round to f, then g in the middle.
+const DIGITS: [usize; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+// seconds each input is shown for.
+const TICK: f32 = 1.0;
+
+static HELP: &str = "space: pause, up/down: change the input, R: color gates by depth";
+
+/// A seven-segment decoder synthesized from a truth table, and how to draw it.
+struct Decoder {
+    name: &'static str,
+    circuit: Circuit,
+    inputs: Vec<NodeIndex>,
+    segments: Vec<NodeIndex>,
+    gates: usize,
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+}
+
+struct Model {
+    decoders: Vec<Decoder>,
+    input: usize,
+    paused: bool,
+    // whether gates are filled by how many gates deep they are, to show off the sum of products'
+    // inverters, then ands, then ors.
+    ranks: bool,
+    // when the input last changed.
+    changed: f32,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+/// The decoder's truth table: inputs 10-15 aren't digits, so the segments don't matter.
+fn table() -> TruthTable {
+    TruthTable::from_fn(4, 7, |row| {
+        (0..7)
+            .map(|s| DIGITS.get(row).map(|bits| get_bit(*bits, s)))
+            .collect()
+    })
+}
+
+fn decoder(name: &'static str, table: &TruthTable) -> Decoder {
+    let mut circuit = Circuit::new();
+    let inputs = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+    for (i, input) in inputs.iter().enumerate() {
+        circuit.set_label(*input, &format!("{}", 1 << i));
+    }
+    let outputs = circuit.synthesize_table(table, &inputs);
+    let gates = circuit
+        .graph()
+        .node_indices()
+        .filter(|n| !matches!(circuit.graph()[*n], Gate::MetaInput | Gate::Input))
+        .count();
+    let segments = outputs
+        .iter()
+        .zip("abcdefg".chars())
+        .map(|(node, name)| circuit.add_labeled_output(*node, &name.to_string()))
+        .collect();
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+    Decoder {
+        name,
+        circuit,
+        inputs,
+        segments,
+        gates,
+        positions,
+        routes,
+    }
+}
+
+fn model(_app: &App) -> Model {
+    let table = table();
+    let decoders = vec![
+        decoder("don't-cares as 0", &table.without_dont_cares()),
+        decoder("with don't-cares", &table),
+    ];
+    let mut model = Model {
+        decoders,
+        input: 0,
+        paused: false,
+        ranks: false,
+        changed: 0.0,
+    };
+    set_input(&mut model, 0, 0.0);
+    model
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    let t = app.duration.since_start.as_secs_f32();
+    match event {
+        Event::Update(_) => {
+            if !model.paused && t - model.changed >= TICK {
+                set_input(model, (model.input + 1) % 16, t);
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::Space => model.paused = !model.paused,
+            Key::R => model.ranks = !model.ranks,
+            Key::Up => set_input(model, (model.input + 1) % 16, t),
+            Key::Down => set_input(model, (model.input + 15) % 16, t),
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn set_input(model: &mut Model, input: usize, t: f32) {
+    model.input = input;
+    model.changed = t;
+    for decoder in &mut model.decoders {
+        let assignment = decoder
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (*node, get_bit(input, i)))
+            .collect::<Vec<_>>();
+        decoder.circuit.evaluate(&assignment);
+    }
+}
+
+/// Draw a seven-segment display centered on `at`, lit from `segments` (a to g).
+fn draw_display(draw: &Draw, at: Vector2, segments: &[bool]) {
+    let (w, h, t) = (40.0, 40.0, 7.0);
+    // the center and whether it's horizontal, for each segment.
+    let placement = [
+        (vec2(0.0, h), true),
+        (vec2(w / 2.0, h / 2.0), false),
+        (vec2(w / 2.0, -h / 2.0), false),
+        (vec2(0.0, -h), true),
+        (vec2(-w / 2.0, -h / 2.0), false),
+        (vec2(-w / 2.0, h / 2.0), false),
+        (vec2(0.0, 0.0), true),
+    ];
+    draw.rect()
+        .xy(at)
+        .w_h(w + 40.0, h * 2.0 + 40.0)
+        .color(rgb8(15, 15, 18));
+    for (on, (offset, horizontal)) in segments.iter().zip(placement.iter()) {
+        let (sw, sh) = if *horizontal { (w, t) } else { (t, h) };
+        draw.rect().xy(at + *offset).w_h(sw, sh).color(if *on {
+            rgb8(255, 60, 40)
+        } else {
+            rgb8(50, 25, 25)
+        });
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(22, 22, 30));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    let style = CircuitStyle {
+        gate_size: 10.0,
+        wire_weight: 1.5,
+        font_size: 9,
+        on: rgba(1.0, 0.86, 0.47, 0.9),
+        off: rgba(0.35, 0.35, 0.43, 0.6),
+        gate_fill: rgba(0.25, 0.25, 0.3, 1.0),
+        rank_fill: if model.ranks {
+            Some(Gradient::new(&[
+                (0.0, Srgb::new(40, 60, 150)),
+                (0.5, Srgb::new(150, 50, 140)),
+                (1.0, Srgb::new(230, 120, 40)),
+            ]))
+        } else {
+            None
+        },
+        ..CircuitStyle::default()
+    };
+
+    // one decoder per half of the window, with its display on the right.
+    for (i, decoder) in model.decoders.iter().enumerate() {
+        let half = Rect::from_w_h(win.w(), win.h() / 2.0)
+            .top_left_of(win)
+            .shift_y(-win.h() / 2.0 * i as f32);
+        let bl = half.bottom_left() + vec2(half.w() * 0.04, half.h() * 0.1);
+        let size = vec2(half.w() * 0.7, half.h() * 0.75);
+        let map_pos = |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y);
+        draw_circuit(
+            &draw,
+            &decoder.circuit,
+            &decoder.positions,
+            &decoder.routes,
+            map_pos,
+            &style,
+        );
+
+        let segments = decoder
+            .segments
+            .iter()
+            .map(|s| decoder.circuit.get_1_in(*s))
+            .collect::<Vec<_>>();
+        let display = vec2(half.right() - half.w() * 0.12, half.y());
+        draw_display(&draw, display, &segments);
+        draw.text(&format!("{}\n{} gates", decoder.name, decoder.gates))
+            .xy(display - vec2(0.0, 90.0))
+            .w(200.0)
+            .color(rgb8(220, 220, 220));
+    }
+    draw.line()
+        .start(win.mid_left())
+        .end(win.mid_right())
+        .weight(1.0)
+        .color(rgb8(60, 60, 70));
+
+    let input = if model.input < 10 {
+        format!("input: {}", model.input)
+    } else {
+        format!("input: {} (not a digit: don't care)", model.input)
+    };
+    draw.text(&input)
+        .xy(win.mid_top() - vec2(0.0, 20.0))
+        .w(win.w())
+        .color(rgb8(255, 255, 255));
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(220, 220, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
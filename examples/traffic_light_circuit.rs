@@ -0,0 +1,274 @@
+use nannou::prelude::*;
+use nannou_sketches::circuits::fsm::{MachineCircuit, StateMachine};
+use nannou_sketches::circuits::*;
+use nannou_sketches::layout::{layered_with_waypoints, route};
+use nannou_sketches::render_circuit::{draw_circuit, CircuitStyle};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
+
+const STATES: [&str; 4] = ["main green", "main yellow", "side green", "side yellow"];
+// how long each state lasts before the timer fires, in seconds.
+const DURATIONS: [f32; 4] = [4.0, 1.5, 3.0, 1.5];
+// seconds per clock cycle.
+const TICK: f32 = 0.1;
+
+static HELP: &str = "click or space: a car arrives on the side road";
+
+struct Model {
+    circuit: Circuit,
+    machine: StateMachine,
+    built: MachineCircuit,
+    timer: NodeIndex,
+    car: NodeIndex,
+    clock: NodeIndex,
+    // the Output nodes for each lamp: main red, yellow, green, then side red, yellow, green.
+    lamps: Vec<NodeIndex>,
+    update_order: Vec<NodeIndex>,
+    settle_steps: usize,
+
+    positions: HashMap<NodeIndex, Vector2>,
+    routes: HashMap<EdgeIndex, Vec<Vector2>>,
+
+    // when the machine entered its current state.
+    entered: f32,
+    car_waiting: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn machine() -> StateMachine {
+    let mut machine = StateMachine::new(&STATES, &["timer", "car"]);
+    // the main road stays green until a car turns up on the side road.
+    machine.transition(
+        "main green",
+        "main yellow",
+        &[("timer", true), ("car", true)],
+    );
+    machine.transition("main yellow", "side green", &[("timer", true)]);
+    machine.transition("side green", "side yellow", &[("timer", true)]);
+    machine.transition("side yellow", "main green", &[("timer", true)]);
+
+    machine.output("main red", &["side green", "side yellow"]);
+    machine.output("main yellow", &["main yellow"]);
+    machine.output("main green", &["main green"]);
+    machine.output("side red", &["main green", "main yellow"]);
+    machine.output("side yellow", &["side yellow"]);
+    machine.output("side green", &["side green"]);
+    machine
+}
+
+fn model(_app: &App) -> Model {
+    let machine = machine();
+    let mut circuit = Circuit::new();
+    let timer = circuit.add_input();
+    let car = circuit.add_input();
+    let clock = circuit.add_input();
+    circuit.set_label(timer, "timer");
+    circuit.set_label(car, "car");
+    circuit.set_label(clock, "clock");
+
+    let built = machine.synthesize(&mut circuit, &[timer, car], clock);
+    for (q, name) in built.states.iter().zip(STATES.iter()) {
+        circuit.set_label(*q, name);
+    }
+    let lamps = built
+        .outputs
+        .iter()
+        .zip(machine.outputs())
+        .map(|(node, name)| circuit.add_labeled_output(*node, &format!("{} lamp", name)))
+        .collect();
+
+    let update_order = circuit.update_order();
+    let settle_steps = flip_ranks(&circuit.ranks()).len() + 1;
+    let (positions, waypoints) = layered_with_waypoints(&circuit);
+    let routes = route(&circuit, &positions, &waypoints);
+
+    let mut model = Model {
+        circuit,
+        machine,
+        built,
+        timer,
+        car,
+        clock,
+        lamps,
+        update_order,
+        settle_steps,
+        positions,
+        routes,
+        entered: 0.0,
+        car_waiting: false,
+    };
+    settle(&mut model);
+    model
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => update(app, model, upd),
+        Event::WindowEvent {
+            simple: Some(MousePressed(MouseButton::Left)),
+            ..
+        }
+        | Event::WindowEvent {
+            simple: Some(KeyPressed(Key::Space)),
+            ..
+        } => {
+            model.car_waiting = true;
+        }
+        _ => (),
+    }
+}
+
+fn epoch(t: f32) -> u32 {
+    (t / TICK).floor() as u32
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    let dt = upd.since_last.as_secs_f32();
+    let t = app.duration.since_start.as_secs_f32();
+    if epoch(t - dt) == epoch(t) {
+        return;
+    }
+
+    // the timer and the car sensor live outside the circuit.
+    let state = model.built.state(&model.circuit);
+    let timer = t - model.entered >= DURATIONS[state];
+    model.circuit.set_input(model.timer, timer);
+    model.circuit.set_input(model.car, model.car_waiting);
+    settle(model);
+
+    // one full clock cycle per tick.
+    for value in [true, false].iter() {
+        model.circuit.set_input(model.clock, *value);
+        settle(model);
+    }
+
+    let next = model.built.state(&model.circuit);
+    if next != state {
+        model.entered = t;
+        if STATES[next] == "side green" {
+            // the waiting car drives through.
+            model.car_waiting = false;
+        }
+    }
+}
+
+fn settle(model: &mut Model) {
+    for _ in 0..model.settle_steps {
+        model.circuit.update_signals_once(&model.update_order);
+    }
+}
+
+fn make_map_pos(win: Rect) -> impl Fn(Vector2) -> Vector2 {
+    // the right side of the window, leaving the left for the intersection.
+    let bl = win.bottom_left() + vec2(win.w() * 0.45, win.h() * 0.1);
+    let size = vec2(win.w() * 0.5, win.h() * 0.8);
+    move |p: Vector2| bl + vec2(size.x * p.x, size.y * p.y)
+}
+
+/// Draw a traffic light, with lamps top to bottom lit from `lamps` (red, yellow, green).
+fn draw_light(draw: &Draw, at: Vector2, lamps: [bool; 3]) {
+    let size = 22.0;
+    draw.rect()
+        .xy(at)
+        .w_h(size * 1.5, size * 4.0)
+        .color(rgb8(30, 30, 30));
+    let colors = [rgb(1.0, 0.2, 0.15), rgb(1.0, 0.8, 0.1), rgb(0.2, 0.9, 0.3)];
+    for (i, (on, color)) in lamps.iter().zip(colors.iter()).enumerate() {
+        let brightness = if *on { 1.0 } else { 0.15 };
+        draw.ellipse()
+            .xy(at + vec2(0.0, size * (1.0 - i as f32) * 1.2))
+            .w_h(size, size)
+            .color(rgb(
+                color.red * brightness,
+                color.green * brightness,
+                color.blue * brightness,
+            ));
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(40, 60, 40));
+    let win = app.window_rect();
+    let draw = app.draw();
+    let map_pos = make_map_pos(win);
+    let t = app.duration.since_start.as_secs_f32();
+
+    // the intersection: the main road runs left to right, the side road up and down.
+    let center = vec2(win.left() + win.w() * 0.22, 0.0);
+    let road = 70.0;
+    let reach = win.w() * 0.2;
+    draw.rect()
+        .xy(center)
+        .w_h(reach * 2.0, road)
+        .color(rgb8(70, 70, 75));
+    draw.rect()
+        .xy(center)
+        .w_h(road, win.h() * 0.8)
+        .color(rgb8(70, 70, 75));
+    if model.car_waiting {
+        draw.rect()
+            .xy(center + vec2(road * 0.2, -road * 1.2))
+            .w_h(24.0, 40.0)
+            .color(rgb8(80, 160, 255));
+    }
+
+    let lamp = |i: usize| model.circuit.get_1_in(model.lamps[i]);
+    draw_light(
+        &draw,
+        center + vec2(-road * 1.4, road * 1.6),
+        [lamp(0), lamp(1), lamp(2)],
+    );
+    draw_light(
+        &draw,
+        center + vec2(road * 1.4, -road * 1.6),
+        [lamp(3), lamp(4), lamp(5)],
+    );
+
+    let style = CircuitStyle {
+        gate_size: 14.0,
+        wire_weight: 2.0,
+        font_size: 10,
+        on: rgba(1.0, 0.86, 0.47, 0.9),
+        off: rgba(0.2, 0.2, 0.25, 1.0),
+        ..CircuitStyle::default()
+    };
+    draw_circuit(
+        &draw,
+        &model.circuit,
+        &model.positions,
+        &model.routes,
+        &map_pos,
+        &style,
+    );
+
+    // the states, with the current one highlighted and its timer filling up.
+    let state = model.built.state(&model.circuit);
+    for (i, name) in model.machine.states().iter().enumerate() {
+        let at = vec2(center.x, win.top() - 30.0 - 22.0 * i as f32);
+        let current = i == state;
+        draw.text(name).xy(at).w(200.0).color(if current {
+            rgb8(255, 255, 255)
+        } else {
+            rgb8(120, 140, 120)
+        });
+        if current {
+            let progress = ((t - model.entered) / DURATIONS[i]).min(1.0);
+            draw.rect()
+                .xy(at + vec2(110.0 - 40.0 * (1.0 - progress), 0.0))
+                .w_h(80.0 * progress, 6.0)
+                .color(rgb8(255, 220, 120));
+        }
+    }
+
+    draw.text(HELP)
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(220, 220, 220));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
@@ -0,0 +1,93 @@
+use nannou::prelude::*;
+use nannou_sketches::viz::tiling::{Group, Tiling};
+
+// px between neighbouring copies of the motif, and how far up or down changes it.
+const SIZE: f32 = 160.0;
+const MIN_SIZE: f32 = 40.0;
+const SIZE_STEP: f32 = 20.0;
+
+static HELP: &str = "left/right for the wallpaper group, up/down to resize, space to pause";
+
+struct Model {
+    group: usize,
+    size: f32,
+    t: f32,
+    paused: bool,
+}
+
+fn main() {
+    nannou::app(model).event(event).simple_window(view).run();
+}
+
+fn model(_app: &App) -> Model {
+    Model {
+        group: Group::ALL.len() - 1,
+        size: SIZE,
+        t: 0.0,
+        paused: false,
+    }
+}
+
+fn event(_app: &App, model: &mut Model, event: Event) {
+    match event {
+        Event::Update(upd) => {
+            if !model.paused {
+                model.t += upd.since_last.as_secs_f32();
+            }
+        }
+        Event::WindowEvent {
+            simple: Some(KeyPressed(key)),
+            ..
+        } => match key {
+            Key::Right => model.group = (model.group + 1) % Group::ALL.len(),
+            Key::Left => model.group = (model.group + Group::ALL.len() - 1) % Group::ALL.len(),
+            Key::Up => model.size += SIZE_STEP,
+            Key::Down => model.size = (model.size - SIZE_STEP).max(MIN_SIZE),
+            Key::Space => model.paused = !model.paused,
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+/// One copy of the motif, in a lopsided corner of the cell so every turn and mirror of it shows:
+/// one of `pattern_3`'s swinging triangles, and one of `pattern_1`'s pulsing dots.
+fn motif(draw: &Draw, size: f32, t: f32) {
+    let swing = vec2(t.cos(), t.sin()) * 0.06;
+    draw.tri()
+        .points(
+            vec2(0.08, 0.04) * size,
+            (vec2(0.42, 0.1) + swing) * size,
+            vec2(0.2, 0.3) * size,
+        )
+        .color(rgba(0.77, 0.2, 0.0, 0.9));
+    let pulse = (t * 0.7).sin();
+    let w = 0.15 * size * pulse.abs();
+    draw.ellipse()
+        .resolution(32)
+        .xy(vec2(0.3, 0.22 + 0.05 * (t * 0.5).cos()) * size)
+        .w_h(w, w)
+        .color(rgba(0.5, 1.0, 0.0, 1.0 - pulse.abs() * 0.5));
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(rgb8(71, 59, 240));
+    let win = app.window_rect();
+    let draw = app.draw();
+
+    let tiling = Tiling::new(Group::ALL[model.group], model.size);
+    tiling.draw(&draw, win, |draw| motif(draw, model.size, model.t));
+
+    draw.rect()
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w_h(win.w(), 40.0)
+        .color(rgba(0.0, 0.0, 0.0, 0.6));
+    draw.text(&format!("{:?}\n{}", tiling.group, HELP))
+        .xy(win.mid_bottom() + vec2(0.0, 20.0))
+        .w(win.w())
+        .font_size(12)
+        .color(rgb8(240, 240, 250));
+
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
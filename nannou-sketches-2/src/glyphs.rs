@@ -0,0 +1,100 @@
+//! Drawing a field of rank-2 tensors as ellipse glyphs. Each tensor's symmetric part is split into
+//! its principal axes by eigendecomposition, and drawn as an ellipse along them, each semi-axis as
+//! long as its eigenvalue is big. The axes themselves are drawn across the ellipse, in a second
+//! color where the eigenvalue is negative and the tensor turns vectors along it backwards.
+
+use nannou::prelude::*;
+
+/// A rank-2 tensor on the plane, as the matrix `[[xx, xy], [yx, yy]]`.
+pub type Tensor2 = [[f32; 2]; 2];
+
+/// The tensor product `a ⊗ b`.
+pub fn outer(a: Vec2, b: Vec2) -> Tensor2 {
+    [[a.x * b.x, a.x * b.y], [a.y * b.x, a.y * b.y]]
+}
+
+/// The principal axes of a tensor's symmetric part.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Principal {
+    /// The eigenvalues, the larger first.
+    pub values: [f32; 2],
+    /// The unit eigenvector of the larger eigenvalue; the other's perpendicular to it.
+    pub direction: Vec2,
+}
+
+impl Principal {
+    pub fn of(tensor: Tensor2) -> Principal {
+        let [[xx, xy], [yx, yy]] = tensor;
+        let shear = (xy + yx) / 2.0;
+        // The eigenvalues of a symmetric 2x2 matrix lie either side of its mean diagonal, and its
+        // axes are turned half as far as the angle `(xx - yy, 2 shear)` makes.
+        let mean = (xx + yy) / 2.0;
+        let radius = ((xx - yy) / 2.0).hypot(shear);
+        let angle = (2.0 * shear).atan2(xx - yy) / 2.0;
+        Principal {
+            values: [mean + radius, mean - radius],
+            direction: Vec2::new(angle.cos(), angle.sin()),
+        }
+    }
+}
+
+/// A glyph for each of `tensors`, at its point, all scaled alike so the longest semi-axis is `room`
+/// long; `weight` is how thick their lines are.
+pub fn draw_glyphs(draw: &Draw, tensors: &[(Vec2, Tensor2)], room: f32, weight: f32) {
+    const SEGMENTS: usize = 32;
+    let principals = tensors
+        .iter()
+        .map(|&(p, tensor)| (p, Principal::of(tensor)))
+        .collect::<Vec<_>>();
+    let biggest = principals
+        .iter()
+        .flat_map(|(_, principal)| principal.values)
+        .fold(0.0, |biggest: f32, value| biggest.max(value.abs()));
+    if biggest == 0.0 {
+        return;
+    }
+    for (p, principal) in principals {
+        let [major, minor] = principal.values.map(|value| value / biggest * room);
+        let (along, across) = (principal.direction, principal.direction.perp());
+        let outline = (0..=SEGMENTS).map(|i| {
+            let angle = i as f32 / SEGMENTS as f32 * 2.0 * PI;
+            p + along * major.abs() * angle.cos() + across * minor.abs() * angle.sin()
+        });
+        draw.polyline()
+            .weight(weight)
+            .points(outline)
+            .color(DARKSLATEGRAY);
+        for (axis, length) in [(along, major), (across, minor)] {
+            let color = if length < 0.0 { CRIMSON } else { DARKGREEN };
+            draw.line()
+                .start(p - axis * length.abs())
+                .end(p + axis * length.abs())
+                .weight(weight)
+                .color(color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_principal() {
+        let diagonal = Principal::of([[1.0, 0.0], [0.0, 3.0]]);
+        assert_eq!(diagonal.values, [3.0, 1.0]);
+        assert!(diagonal.direction.x.abs() < 1e-6);
+
+        // only the symmetric part counts, here `[[2, 1], [1, 2]]`.
+        let sheared = Principal::of([[2.0, 2.0], [0.0, 2.0]]);
+        assert!((sheared.values[0] - 3.0).abs() < 1e-6);
+        assert!((sheared.values[1] - 1.0).abs() < 1e-6);
+        let diagonal_direction = Vec2::new(1.0, 1.0).normalize();
+        assert!((sheared.direction - diagonal_direction).length() < 1e-6);
+
+        // `a ⊗ b` with `a` and `b` perpendicular stretches one diagonal and flips the other.
+        let product = Principal::of(outer(Vec2::new(2.0, 0.0), Vec2::new(0.0, 3.0)));
+        assert!((product.values[0] - 3.0).abs() < 1e-6);
+        assert!((product.values[1] + 3.0).abs() < 1e-6);
+    }
+}
@@ -2,6 +2,9 @@ use std::ops::{Add, Mul};
 
 use nannou::{geom::Range, prelude::*};
 
+mod glyphs;
+use glyphs::{draw_glyphs, outer, Tensor2};
+
 trait RiemannianDot {
     /// The dimension of the space.
     const DIM: usize;
@@ -26,8 +29,246 @@ impl RiemannianDot for Vec2 {
     }
 }
 
-trait VectorSpace: Add<Self> + Mul<f32, Output = Self> + Copy + RiemannianDot {}
-impl<T: Add<Self> + Mul<f32, Output = Self> + Copy + RiemannianDot> VectorSpace for T {}
+impl RiemannianDot for Vec3 {
+    const DIM: usize = 3;
+
+    fn dot(self, other: Self) -> f32 {
+        self.dot(other)
+    }
+}
+
+impl RiemannianDot for Vec4 {
+    const DIM: usize = 4;
+
+    fn dot(self, other: Self) -> f32 {
+        self.dot(other)
+    }
+}
+
+/// A vector of the plane, measured by the metric the tensor's rectangle defines: the one in which
+/// the sides, `v1` along x and `v2` along y, are orthonormal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RectangleMetric {
+    v: Vec2,
+    /// The lengths of the sides.
+    sides: Vec2,
+}
+
+impl RiemannianDot for RectangleMetric {
+    const DIM: usize = 2;
+
+    fn dot(self, other: Self) -> f32 {
+        (self.v / self.sides).dot(other.v / other.sides)
+    }
+}
+
+/// A vector in `N` dimensions, for when glam runs out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct VecN<const N: usize>([f32; N]);
+
+impl<const N: usize> Add for VecN<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a += b;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Mul<f32> for VecN<N> {
+    type Output = Self;
+
+    fn mul(mut self, by: f32) -> Self {
+        for a in self.0.iter_mut() {
+            *a *= by;
+        }
+        self
+    }
+}
+
+impl<const N: usize> RiemannianDot for VecN<N> {
+    const DIM: usize = N;
+
+    fn dot(self, other: Self) -> f32 {
+        self.0.iter().zip(other.0).map(|(a, b)| a * b).sum()
+    }
+}
+
+/// Access to the standard basis of a space.
+trait Coordinates: Sized {
+    /// The `i`th standard basis vector.
+    fn basis(i: usize) -> Self;
+
+    /// The `i`th component in the standard basis.
+    fn coordinate(self, i: usize) -> f32;
+}
+
+impl Coordinates for f32 {
+    fn basis(i: usize) -> Self {
+        assert_eq!(i, 0);
+        1.0
+    }
+
+    fn coordinate(self, i: usize) -> f32 {
+        assert_eq!(i, 0);
+        self
+    }
+}
+
+impl Coordinates for Vec2 {
+    fn basis(i: usize) -> Self {
+        let mut result = [0.0; 2];
+        result[i] = 1.0;
+        Vec2::from(result)
+    }
+
+    fn coordinate(self, i: usize) -> f32 {
+        self.to_array()[i]
+    }
+}
+
+impl Coordinates for Vec3 {
+    fn basis(i: usize) -> Self {
+        let mut result = [0.0; 3];
+        result[i] = 1.0;
+        Vec3::from(result)
+    }
+
+    fn coordinate(self, i: usize) -> f32 {
+        self.to_array()[i]
+    }
+}
+
+impl Coordinates for Vec4 {
+    fn basis(i: usize) -> Self {
+        let mut result = [0.0; 4];
+        result[i] = 1.0;
+        Vec4::from(result)
+    }
+
+    fn coordinate(self, i: usize) -> f32 {
+        self.to_array()[i]
+    }
+}
+
+impl<const N: usize> Coordinates for VecN<N> {
+    fn basis(i: usize) -> Self {
+        let mut result = [0.0; N];
+        result[i] = 1.0;
+        VecN(result)
+    }
+
+    fn coordinate(self, i: usize) -> f32 {
+        self.0[i]
+    }
+}
+
+trait VectorSpace: Add<Self> + Mul<f32, Output = Self> + Copy + RiemannianDot + Coordinates {}
+impl<T: Add<Self> + Mul<f32, Output = Self> + Copy + RiemannianDot + Coordinates> VectorSpace
+    for T
+{
+}
+
+/// Where the `i`th basis vector of an arbitrary-dimensional space is drawn in 3D.
+/// The first three axes are the usual ones; any further axes are spread out along the diagonals,
+/// an oblique projection like the usual drawings of a tesseract.
+fn axis_direction(i: usize) -> Vec3 {
+    match i {
+        0 => Vec3::X,
+        1 => Vec3::Y,
+        2 => Vec3::Z,
+        _ => {
+            let angle = (i - 3) as f32 * 2.0 * PI / 3.0;
+            Vec3::new(angle.cos(), angle.sin(), 1.0).normalize() * 0.6
+        }
+    }
+}
+
+/// Embed a vector of any dimension into 3D, for drawing.
+fn embed<V: VectorSpace>(v: V) -> Vec3 {
+    (0..V::DIM)
+        .map(|i| axis_direction(i) * v.coordinate(i))
+        .fold(Vec3::ZERO, |a, b| a + b)
+}
+
+/// A camera circling the origin.
+#[derive(Clone, Copy)]
+struct OrbitCamera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    /// Pixels per unit at the origin.
+    zoom: f32,
+}
+
+impl OrbitCamera {
+    fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+
+    /// Project a point in world space to screen space, with perspective.
+    fn project(&self, p: Vec3) -> Vec2 {
+        let p = self.rotation().inverse() * p;
+        let depth = (self.distance - p.z).max(0.01);
+        p.truncate() * (self.zoom * self.distance / depth)
+    }
+}
+
+/// Which space the sketch is currently showing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ViewMode {
+    Flat,
+    Dim3,
+    Dim4,
+    DimN,
+}
+
+impl ViewMode {
+    fn next(self) -> Self {
+        match self {
+            ViewMode::Flat => ViewMode::Dim3,
+            ViewMode::Dim3 => ViewMode::Dim4,
+            ViewMode::Dim4 => ViewMode::DimN,
+            ViewMode::DimN => ViewMode::Flat,
+        }
+    }
+}
+
+/// Which vector field is drawn over the flat view, if any.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Field {
+    Off,
+    Constant,
+    Rotation,
+    Gradient,
+}
+
+impl Field {
+    fn next(self) -> Self {
+        match self {
+            Field::Off => Field::Constant,
+            Field::Constant => Field::Rotation,
+            Field::Rotation => Field::Gradient,
+            Field::Gradient => Field::Off,
+        }
+    }
+
+    /// The field at `p`. In coord system.
+    fn at(self, p: Vec2) -> Vec2 {
+        match self {
+            Field::Off => Vec2::ZERO,
+            Field::Constant => Vec2::new(2.0, 1.0),
+            Field::Rotation => p.perp() * 0.1,
+            // The gradient of `20 sin(x / 8) cos(y / 8)`, a checkerboard of hills and valleys.
+            Field::Gradient => {
+                let (x, y) = (p.x / 8.0, p.y / 8.0);
+                Vec2::new(x.cos() * y.cos(), -x.sin() * y.sin()) * 2.5
+            }
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 struct Pure2Tensor<V> {
@@ -69,13 +310,50 @@ impl<V: VectorSpace> Pure2Tensor<V> {
     pub fn scale_v2(&mut self, by: f32) {
         self.scalar_1 /= by;
     }
+
+    /// Scale `v1` by `by` and `v2` inversely, like `scale_v1`, but by scaling the generators
+    /// rather than the scalar between them.
+    pub fn scale_generators(&mut self, by: f32) {
+        self.generator_1 = self.generator_1 * by;
+        self.generator_2 = self.generator_2 * (1.0 / by);
+    }
 }
 
+/// Pixels per coordinate unit, before the user has rotated or scaled anything.
+const UNIT: f32 = 4.0;
+/// Coordinate units between the grid's minor lines.
+const GRID_STEP: f32 = 2.0;
+/// Every this many minor lines, a major one.
+const GRID_MAJOR: i32 = 5;
+
+/// How stiff the rectangle's sides are, as springs.
+const STIFFNESS: f32 = 4.0;
+/// The mass hung in the middle of the rectangle.
+const MASS: f32 = 1.0;
+/// How hard gravity pulls on the mass, in coordinate units per second per second.
+const GRAVITY: f32 = 30.0;
+/// How quickly the rectangle stops wobbling, per second.
+const DAMPING: f32 = 1.5;
+/// The longest the rectangle is simulated for at a time.
+const STEP: f32 = 1.0 / 240.0;
+
 struct Model {
     x_hat: Vec2,                                    // Screen space.
     dragging_background_from: Option<(Vec2, Vec2)>, // (starting x_hat, starting click). Screen space.
     mouse_position: Vec2,                           // Screen space.
     tensor: Pure2Tensor<f32>,                       // In coord system.
+    stretch_velocity: f32, // How fast the tensor's first side is growing. In coord system.
+    gravity: Vec2,         // In coord system.
+    reference_grid: bool,  // Whether the untransformed grid is drawn underneath.
+    field: Field,
+    glyphs: bool, // Whether the field of tensor glyphs is drawn.
+
+    view_mode: ViewMode,
+    camera: OrbitCamera,
+    orbiting_from: Option<(OrbitCamera, Vec2)>, // (starting camera, starting click). Screen space.
+    tensor_3: Pure2Tensor<Vec3>,
+    tensor_4: Pure2Tensor<Vec4>,
+    tensor_n: Pure2Tensor<VecN<5>>,
 }
 
 impl Model {
@@ -91,18 +369,282 @@ impl Model {
 
 fn model(_app: &App) -> Model {
     Model {
-        x_hat: Vec2::new(4.0, 0.0),
+        x_hat: Vec2::new(UNIT, 0.0),
         dragging_background_from: None,
         tensor: Pure2Tensor::new(10.0, 10.0),
         mouse_position: Vec2::ZERO,
+        stretch_velocity: 0.0,
+        gravity: -Vec2::Y * GRAVITY,
+        reference_grid: false,
+        field: Field::Off,
+        glyphs: false,
+
+        view_mode: ViewMode::Flat,
+        camera: OrbitCamera {
+            yaw: 0.6,
+            pitch: -0.4,
+            distance: 40.0,
+            zoom: 20.0,
+        },
+        orbiting_from: None,
+        tensor_3: Pure2Tensor::new(Vec3::new(8.0, 2.0, 0.0), Vec3::new(0.0, 6.0, 4.0)),
+        tensor_4: Pure2Tensor::new(
+            Vec4::new(8.0, 2.0, 0.0, 3.0),
+            Vec4::new(0.0, 6.0, 4.0, -2.0),
+        ),
+        tensor_n: Pure2Tensor::new(
+            VecN([8.0, 2.0, 0.0, 3.0, 1.0]),
+            VecN([0.0, 6.0, 4.0, -2.0, 5.0]),
+        ),
+    }
+}
+
+/// Draw the basis of `V` and the generators of `tensor`, seen through `camera`.
+fn draw_tensor_nd<V: VectorSpace>(draw: &Draw, camera: &OrbitCamera, tensor: &Pure2Tensor<V>) {
+    let origin = camera.project(Vec3::ZERO);
+    for i in 0..V::DIM {
+        let end = camera.project(embed(V::basis(i)) * 12.0);
+        let color = if i < 3 { BEIGE } else { BROWN };
+        draw.arrow().start(origin).end(end).color(color);
+        draw.text(&format!("e{}", i))
+            .xy(end + (end - origin).normalize_or_zero() * 12.0)
+            .color(BLACK);
+    }
+
+    // The tensor's generators, and the parallelogram they span.
+    let v1 = embed(tensor.v1());
+    let v2 = embed(tensor.v2());
+    draw.arrow()
+        .start(origin)
+        .end(camera.project(v1))
+        .color(CRIMSON);
+    draw.arrow()
+        .start(origin)
+        .end(camera.project(v2))
+        .color(DARKBLUE);
+    draw.polyline()
+        .weight(2.0)
+        .points([Vec3::ZERO, v1, v1 + v2, v2, Vec3::ZERO].map(|p| camera.project(p)))
+        .color(DARKSLATEGRAY);
+}
+
+/// Grid lines of the coordinate system `draw` draws in, at `scale` pixels to a unit, reaching past
+/// the edges of `win` however it's rotated: faint minor lines, stronger major ones, and the axes
+/// through the origin strongest of all.
+fn draw_grid(draw: &Draw, win: Rect, scale: f32, ink: Rgb) {
+    // Minor lines closer than this many pixels are left out, as they'd just be a smear.
+    const CLOSEST: f32 = 4.0;
+    let reach = win.wh().length() / 2.0 / scale;
+    // A limit, for when the basis has been dragged down to almost nothing.
+    let n = ((reach / GRID_STEP).ceil() as i32).min(GRID_MAJOR * 100);
+    let extent = n as f32 * GRID_STEP;
+    let minor = GRID_STEP * scale >= CLOSEST;
+    for i in -n..=n {
+        // Weights in pixels, so the lines look the same however far the grid's zoomed.
+        let (weight, alpha) = match i {
+            0 => (2.5, 0.6),
+            _ if i % GRID_MAJOR == 0 => (1.2, 0.3),
+            _ if minor => (0.5, 0.12),
+            _ => continue,
+        };
+        let at = i as f32 * GRID_STEP;
+        let color = rgba(ink.red, ink.green, ink.blue, alpha);
+        draw.line()
+            .start(Vec2::new(at, -extent))
+            .end(Vec2::new(at, extent))
+            .weight(weight / scale)
+            .color(color);
+        draw.line()
+            .start(Vec2::new(-extent, at))
+            .end(Vec2::new(extent, at))
+            .weight(weight / scale)
+            .color(color);
+    }
+}
+
+/// Points around the unit ball of a quadratic form on the plane, the vectors `v` with
+/// `dot(v, v) == 1`, where `measure` turns a vector into the type whose `dot` is the form.
+fn unit_ball<V: RiemannianDot + Copy>(measure: impl Fn(Vec2) -> V) -> Vec<Vec2> {
+    const SEGMENTS: usize = 96;
+    (0..=SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / SEGMENTS as f32 * 2.0 * PI;
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            let v = measure(direction);
+            direction / v.dot(v).sqrt()
+        })
+        .collect()
+}
+
+/// The unit balls of the coordinate system's own dot product, a circle, and of the metric the
+/// tensor's rectangle defines, an ellipse through its corners on the axes which squashes and
+/// stretches with it.
+fn draw_unit_balls(draw: &Draw, model: &Model) {
+    let weight = 1.5 / model.x_hat().length();
+    draw.polyline()
+        .weight(weight)
+        .points(unit_ball(|v| v))
+        .color(DARKSLATEGRAY);
+    let sides = Vec2::new(model.tensor.v1(), model.tensor.v2());
+    draw.polyline()
+        .weight(weight)
+        .points(unit_ball(|v| RectangleMetric { v, sides }))
+        .color(DARKORANGE);
+}
+
+/// The field's arrows at points of the coordinate system: drawn in it, with their components along
+/// its basis, and pale beside them with the same components along the untransformed basis, to
+/// compare.
+fn draw_field(screen: &Draw, draw: &Draw, win: Rect, model: &Model) {
+    // Coordinate units between the arrows.
+    const SPACING: f32 = 5.0;
+    let scale = model.x_hat().length();
+    let n = ((win.wh().length() / 2.0 / scale / SPACING).ceil() as i32).min(100);
+    for i in -n..=n {
+        for j in -n..=n {
+            let p = Vec2::new(i as f32, j as f32) * SPACING;
+            let on_screen = model.x_hat() * p.x + model.y_hat() * p.y;
+            if !win.contains(on_screen) {
+                continue;
+            }
+            let v = model.field.at(p);
+            screen
+                .arrow()
+                .start(on_screen)
+                .end(on_screen + v * UNIT)
+                .weight(1.5)
+                .color(rgba(1.0, 1.0, 1.0, 0.6));
+            draw.arrow()
+                .start(p)
+                .end(p + v)
+                .weight(1.5 / scale)
+                .color(NAVY);
+        }
+    }
+}
+
+/// The glyph field: at each point, the tensor with `v1` scaled by how far right the point is and
+/// `v2` by how far up, as `Pure2Tensor` scales them. The glyphs are of `v1 ⊗ v2`, with `v1` along
+/// the horizontal axis and `v2` along the vertical, so they're all the same: scaling one factor
+/// scales the other inversely, leaving the tensor alone. The factors are drawn over them as
+/// arrows, which do change.
+fn draw_glyph_field(draw: &Draw, win: Rect, model: &Model) {
+    // Coordinate units between the glyphs, and how far right or up a point is for its factor to
+    // double.
+    const SPACING: f32 = 10.0;
+    const DOUBLING: f32 = 40.0;
+    let scale = model.x_hat().length();
+    let n = ((win.wh().length() / 2.0 / scale / SPACING).ceil() as i32).min(50);
+    let mut tensors = vec![];
+    let mut factors = vec![];
+    for i in -n..=n {
+        for j in -n..=n {
+            let p = Vec2::new(i as f32, j as f32) * SPACING;
+            if !win.contains(model.x_hat() * p.x + model.y_hat() * p.y) {
+                continue;
+            }
+            let mut tensor = model.tensor;
+            tensor.scale_v1(2f32.powf(p.x / DOUBLING));
+            tensor.scale_v2(2f32.powf(p.y / DOUBLING));
+            let (v1, v2) = (Vec2::X * tensor.v1(), Vec2::Y * tensor.v2());
+            tensors.push((p, outer(v1, v2)));
+            factors.push((p, v1, v2));
+        }
+    }
+    let room = SPACING * 0.45;
+    draw_glyphs(draw, &tensors, room, 1.0 / scale);
+
+    // The factors are as long as the glyphs' semi-axes where they're equal.
+    let size = (model.tensor.v1() * model.tensor.v2()).abs().sqrt();
+    if size == 0.0 {
+        return;
+    }
+    for (p, v1, v2) in factors {
+        for v in [v1, v2] {
+            draw.arrow()
+                .start(p)
+                .end(p + (v / size * room).clamp_length_max(SPACING))
+                .weight(1.0 / scale)
+                .color(LIGHTSLATEGRAY);
+        }
+    }
+}
+
+/// A coil spring from `start` to `end`, as a zigzag; it always has the same number of coils, so they
+/// bunch up as it's squashed and spread out as it's stretched.
+fn draw_spring(draw: &Draw, start: Vec2, end: Vec2) {
+    const ZIGS: usize = 24;
+    let along = end - start;
+    let across = along.perp().normalize_or_zero() * 0.4;
+    let points = (0..=ZIGS).map(|i| {
+        let side = match i {
+            0 | ZIGS => 0.0,
+            _ if i % 2 == 0 => 1.0,
+            _ => -1.0,
+        };
+        start + along * (i as f32 / ZIGS as f32) + across * side
+    });
+    draw.polyline()
+        .weight(0.1)
+        .points(points)
+        .color(DARKSLATEGRAY);
+}
+
+/// The tensor's rectangle, in the coordinate system: its sides as springs, and the mass hung in the
+/// middle by strings to each side, with an arrow for the way gravity pulls it.
+fn draw_rectangle(draw: &Draw, model: &Model) {
+    let (w, h) = (model.tensor.v1(), model.tensor.v2());
+    let corners = [
+        Vec2::ZERO,
+        Vec2::new(w, 0.0),
+        Vec2::new(w, h),
+        Vec2::new(0.0, h),
+    ];
+    let middle = Vec2::new(w, h) / 2.0;
+    for i in 0..4 {
+        let (start, end) = (corners[i], corners[(i + 1) % 4]);
+        draw_spring(draw, start, end);
+        draw.line()
+            .start(middle)
+            .end((start + end) / 2.0)
+            .weight(0.05)
+            .color(DIMGRAY);
     }
+    draw.arrow()
+        .start(middle)
+        .end(middle + model.gravity / GRAVITY * 4.0)
+        .weight(0.15)
+        .color(DARKBLUE);
+    draw.ellipse().xy(middle).radius(0.8).color(CRIMSON);
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app
-        .draw()
+    if model.view_mode != ViewMode::Flat {
+        let draw = app.draw();
+        draw.background().color(TURQUOISE);
+        match model.view_mode {
+            ViewMode::Dim3 => draw_tensor_nd(&draw, &model.camera, &model.tensor_3),
+            ViewMode::Dim4 => draw_tensor_nd(&draw, &model.camera, &model.tensor_4),
+            ViewMode::DimN => draw_tensor_nd(&draw, &model.camera, &model.tensor_n),
+            ViewMode::Flat => unreachable!(),
+        }
+        draw.text(&format!("{:?} (tab to switch, drag to orbit)", model.view_mode))
+            .xy(app.window_rect().mid_top() - Vec2::new(0.0, 20.0))
+            .w(400.0)
+            .color(BLACK);
+        draw.to_frame(app, &frame).unwrap();
+        return;
+    }
+
+    let win = app.window_rect();
+    let screen = app.draw();
+    if model.reference_grid {
+        draw_grid(&screen, win, UNIT, rgb(1.0, 1.0, 1.0));
+    }
+    let draw = screen
         .rotate(model.x_hat().angle())
         .scale(model.x_hat().length());
+    draw_grid(&draw, win, model.x_hat().length(), rgb(0.0, 0.0, 0.0));
 
     draw.arrow()
         .start(Vec2::ZERO)
@@ -112,22 +654,78 @@ fn view(app: &App, model: &Model, frame: Frame) {
         .start(Vec2::ZERO)
         .end(Vec2::Y * 12.0)
         .color(BROWN);
+    draw_unit_balls(&draw, model);
+    draw_rectangle(&draw, model);
+    if model.glyphs {
+        draw_glyph_field(&draw, win, model);
+    }
+    if model.field != Field::Off {
+        draw_field(&screen, &draw, win, model);
+    }
     draw.background().color(TURQUOISE);
+    screen
+        .text(&format!(
+            "Flat, {:?} field (tab to switch, drag to rotate and scale, \
+             G for the untransformed grid, F to switch field, T for tensor glyphs, \
+             arrows to scale v1 and v2)",
+            model.field
+        ))
+        .xy(win.mid_top() - Vec2::new(0.0, 20.0))
+        .w(win.w())
+        .color(BLACK);
 
     draw.to_frame(app, &frame).unwrap();
 }
 
-fn update(app: &App, model: &mut Model, update_: Update) {
-    // todo: model the tensor as a rectangle stapled to the coordinate axes, having a mass in the middle with strings attaching to the side of the rectangle.
-    // area is fixed, sides are springs.
-    // gravity changes as the user rotates the tensor.
-    // the sides are springs and the area is constant.
+/// The tensor is a rectangle stapled to the coordinate axes, with sides `v1` and `v2`, and a mass
+/// in the middle with strings attaching it to the sides. The sides are springs, pulling towards the
+/// generators' lengths, but the area is fixed, so the only freedom is how long `v1` is. Gravity
+/// pulls the mass down the screen, so it changes as the user rotates the coordinate system.
+fn update(_app: &App, model: &mut Model, update_: Update) {
+    // Down the screen, in the coordinate system.
+    let down = -Vec2::Y;
+    model.gravity =
+        Vec2::new(down.dot(model.x_hat()), down.dot(model.y_hat())).normalize_or_zero() * GRAVITY;
+
+    let area = model.tensor.v1() * model.tensor.v2();
+    let (rest_1, rest_2) = (model.tensor.generator_1, model.tensor.generator_2);
+    let (shortest, longest) = (area.sqrt() / 10.0, area.sqrt() * 10.0);
+    let mut x = model.tensor.v1();
+
+    // Small equal steps, so the springs don't blow up however long the frame took.
+    let dt = update_.since_last.as_secs_f32().min(0.1);
+    let steps = (dt / STEP).ceil().max(1.0);
+    let h = dt / steps;
+    for _ in 0..steps as usize {
+        let y = area / x;
+        // How far the other side moves as `x` grows.
+        let dy_dx = -area / (x * x);
+        // Minus the derivative of the springs' energy, and gravity's pull on the mass at
+        // `(x, y) / 2`.
+        let force = -STIFFNESS * (x - rest_1) - STIFFNESS * (y - rest_2) * dy_dx
+            + MASS * (model.gravity.x + model.gravity.y * dy_dx) / 2.0;
+        // The mass moves `(1, dy_dx) / 2` for each unit `x` grows, so is heavier to move the
+        // more lopsided the rectangle is.
+        let inertia = MASS * (1.0 + dy_dx * dy_dx) / 4.0;
+        model.stretch_velocity += (force / inertia - DAMPING * model.stretch_velocity) * h;
+        x += model.stretch_velocity * h;
+        if x < shortest || x > longest {
+            x = x.clamp(shortest, longest);
+            model.stretch_velocity = 0.0;
+        }
+    }
+    model.tensor.scalar_1 = x / model.tensor.generator_1;
 }
 
 fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
         MouseMoved(mouse) => {
             model.mouse_position = mouse;
+            if let Some((start_camera, start_mouse_position)) = model.orbiting_from {
+                let delta = (model.mouse_position - start_mouse_position) * 0.01;
+                model.camera.yaw = start_camera.yaw - delta.x;
+                model.camera.pitch = (start_camera.pitch + delta.y).clamp(-PI / 2.0, PI / 2.0);
+            }
             if let Some((start_x_hat, start_mouse_position)) = model.dragging_background_from {
                 let rot = start_mouse_position.angle_between(model.mouse_position);
                 let scale = model.mouse_position.length() / start_mouse_position.length();
@@ -135,10 +733,38 @@ fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
             }
         }
         MousePressed(mouse_button) => {
-            model.dragging_background_from = Some((model.x_hat, model.mouse_position));
+            if model.view_mode == ViewMode::Flat {
+                model.dragging_background_from = Some((model.x_hat, model.mouse_position));
+            } else {
+                model.orbiting_from = Some((model.camera, model.mouse_position));
+            }
         }
         MouseReleased(mouse_button) => {
             model.dragging_background_from = None;
+            model.orbiting_from = None;
+        }
+        MouseWheel(MouseScrollDelta::LineDelta(_, y), _) => {
+            model.camera.zoom *= 1.1f32.powf(y);
+        }
+        KeyPressed(Key::F) => {
+            model.field = model.field.next();
+        }
+        KeyPressed(Key::T) => {
+            model.glyphs = !model.glyphs;
+        }
+        // The generators are what the springs pull the sides towards, so scaling them rather than
+        // the scalar keeps the rectangle from being pulled straight back.
+        KeyPressed(Key::Right) => model.tensor.scale_generators(1.25),
+        KeyPressed(Key::Left) => model.tensor.scale_generators(0.8),
+        KeyPressed(Key::Up) => model.tensor.scale_generators(0.8),
+        KeyPressed(Key::Down) => model.tensor.scale_generators(1.25),
+        KeyPressed(Key::G) => {
+            model.reference_grid = !model.reference_grid;
+        }
+        KeyPressed(Key::Tab) => {
+            model.view_mode = model.view_mode.next();
+            model.dragging_background_from = None;
+            model.orbiting_from = None;
         }
         _ => (),
     }
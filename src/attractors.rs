@@ -0,0 +1,266 @@
+//! Strange attractors: simple rules which, followed over and over, trace out endlessly detailed
+//! shapes without ever quite repeating.
+//!
+//! Each attractor is an iterator over the points it visits. They're best drawn by binning a great
+//! many of them into a `Density`, and coloring it by how often each pixel was visited; the counts
+//! vary so much that `Density::tonemap` takes their logarithm to show the faint wisps and the
+//! bright cores together.
+
+use crate::grid::FloatGrid;
+use nannou::geom::{vec2, vec3, Rect, Vector2, Vector3};
+
+/// Lorenz's model of convection, the butterfly: a continuous flow in three dimensions, stepped
+/// with RK4.
+#[derive(Clone, Debug)]
+pub struct Lorenz {
+    pub sigma: f32,
+    pub rho: f32,
+    pub beta: f32,
+    /// How far to step the flow between points.
+    pub dt: f32,
+    pos: Vector3,
+}
+
+impl Lorenz {
+    /// The classic butterfly, from just off the origin.
+    pub fn new() -> Lorenz {
+        Lorenz {
+            sigma: 10.0,
+            rho: 28.0,
+            beta: 8.0 / 3.0,
+            dt: 0.005,
+            pos: vec3(0.1, 0.0, 0.0),
+        }
+    }
+
+    fn velocity(&self, p: Vector3) -> Vector3 {
+        vec3(
+            self.sigma * (p.y - p.x),
+            p.x * (self.rho - p.z) - p.y,
+            p.x * p.y - self.beta * p.z,
+        )
+    }
+}
+
+impl Default for Lorenz {
+    fn default() -> Lorenz {
+        Lorenz::new()
+    }
+}
+
+impl Iterator for Lorenz {
+    type Item = Vector3;
+
+    fn next(&mut self) -> Option<Vector3> {
+        let (p, dt) = (self.pos, self.dt);
+        let k1 = self.velocity(p);
+        let k2 = self.velocity(p + k1 * (dt / 2.0));
+        let k3 = self.velocity(p + k2 * (dt / 2.0));
+        let k4 = self.velocity(p + k3 * dt);
+        self.pos = p + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0);
+        Some(self.pos)
+    }
+}
+
+/// Peter de Jong's map: `x' = sin(a y) - cos(b x)`, `y' = sin(c x) - cos(d y)`.
+#[derive(Clone, Debug)]
+pub struct DeJong {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pos: Vector2,
+}
+
+impl DeJong {
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> DeJong {
+        DeJong {
+            a,
+            b,
+            c,
+            d,
+            pos: vec2(0.0, 0.0),
+        }
+    }
+
+    /// Everywhere it can go.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_w_h(4.0, 4.0)
+    }
+}
+
+impl Iterator for DeJong {
+    type Item = Vector2;
+
+    fn next(&mut self) -> Option<Vector2> {
+        let Vector2 { x, y } = self.pos;
+        self.pos = vec2(
+            (self.a * y).sin() - (self.b * x).cos(),
+            (self.c * x).sin() - (self.d * y).cos(),
+        );
+        Some(self.pos)
+    }
+}
+
+/// Clifford Pickover's map: `x' = sin(a y) + c cos(a x)`, `y' = sin(b x) + d cos(b y)`.
+#[derive(Clone, Debug)]
+pub struct Clifford {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pos: Vector2,
+}
+
+impl Clifford {
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> Clifford {
+        Clifford {
+            a,
+            b,
+            c,
+            d,
+            pos: vec2(0.0, 0.0),
+        }
+    }
+
+    /// Everywhere it can go.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_w_h(2.0 + 2.0 * self.c.abs(), 2.0 + 2.0 * self.d.abs())
+    }
+}
+
+impl Iterator for Clifford {
+    type Item = Vector2;
+
+    fn next(&mut self) -> Option<Vector2> {
+        let Vector2 { x, y } = self.pos;
+        self.pos = vec2(
+            (self.a * y).sin() + self.c * (self.a * x).cos(),
+            (self.b * x).sin() + self.d * (self.b * y).cos(),
+        );
+        Some(self.pos)
+    }
+}
+
+/// How many points have landed in each pixel of an image of `bounds`.
+#[derive(Clone, Debug)]
+pub struct Density {
+    pub bounds: Rect,
+    counts: FloatGrid,
+    // the most points in any one pixel.
+    max: f32,
+}
+
+impl Density {
+    pub fn new(width: usize, height: usize, bounds: Rect) -> Density {
+        Density {
+            bounds,
+            counts: FloatGrid::new(width, height, 0.0),
+            max: 0.0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.counts.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.counts.height
+    }
+
+    /// How many points have landed in (`x`, `y`), counting pixels from the bottom left.
+    pub fn count(&self, x: usize, y: usize) -> f32 {
+        *self.counts.get(x, y)
+    }
+
+    /// Count `pos`, if it's within the bounds.
+    pub fn add(&mut self, pos: Vector2) {
+        let (w, h) = (self.width() as f32, self.height() as f32);
+        let cell = (pos - self.bounds.bottom_left()) / self.bounds.wh();
+        let (x, y) = (cell.x * w, cell.y * h);
+        if x < 0.0 || y < 0.0 || x >= w || y >= h {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.counts.add(x, y, 1.0);
+        self.max = self.max.max(*self.counts.get(x, y));
+    }
+
+    /// Count every point `points` visits.
+    pub fn extend<I: IntoIterator<Item = Vector2>>(&mut self, points: I) {
+        for pos in points {
+            self.add(pos);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.fill(0.0);
+        self.max = 0.0;
+    }
+
+    /// How bright (`x`, `y`) is, from 0 to 1: the logarithm of its count, scaled so that the most
+    /// visited pixel is 1. `exposure` brightens it, with 1 leaving it as it is.
+    pub fn tonemap(&self, x: usize, y: usize, exposure: f32) -> f32 {
+        if self.max == 0.0 {
+            return 0.0;
+        }
+        let brightness = (1.0 + self.count(x, y)).ln() / (1.0 + self.max).ln();
+        (brightness * exposure).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded() {
+        let mut dejong = DeJong::new(1.4, -2.3, 2.4, -2.1);
+        let bounds = dejong.bounds();
+        assert!(dejong.by_ref().take(10000).all(|p| bounds.contains(p)));
+        let mut clifford = Clifford::new(-1.4, 1.6, 1.0, 0.7);
+        let bounds = clifford.bounds();
+        assert!(clifford.by_ref().take(10000).all(|p| bounds.contains(p)));
+
+        // the butterfly never settles down, and never flies off.
+        let lorenz = Lorenz::new();
+        let points = lorenz.skip(1000).take(20000).collect::<Vec<_>>();
+        assert!(points
+            .iter()
+            .all(|p| p.x.abs() < 30.0 && p.z > 0.0 && p.z < 60.0));
+        // it visits both wings.
+        assert!(points.iter().any(|p| p.x > 10.0));
+        assert!(points.iter().any(|p| p.x < -10.0));
+    }
+
+    #[test]
+    fn test_density() {
+        let mut density = Density::new(4, 2, Rect::from_w_h(2.0, 2.0));
+        assert_eq!(density.tonemap(0, 0, 1.0), 0.0);
+        density.extend(vec![
+            vec2(-0.9, -0.9),
+            vec2(-0.8, -0.5),
+            vec2(0.9, 0.9),
+            // outside, so not counted.
+            vec2(1.5, 0.0),
+        ]);
+        assert_eq!(density.count(0, 0), 2.0);
+        assert_eq!(density.count(3, 1), 1.0);
+        assert_eq!(density.tonemap(0, 0, 1.0), 1.0);
+        assert!((density.tonemap(3, 1, 1.0) - 2f32.ln() / 3f32.ln()).abs() < 1e-6);
+        assert_eq!(density.tonemap(1, 0, 1.0), 0.0);
+        assert_eq!(density.tonemap(3, 1, 10.0), 1.0);
+
+        // a whole attractor's worth of points, filling in a shape with both busy and quiet parts.
+        let clifford = Clifford::new(-1.4, 1.6, 1.0, 0.7);
+        let mut density = Density::new(64, 64, clifford.bounds());
+        density.extend(clifford.take(100_000));
+        let visited = (0..64)
+            .flat_map(|y| (0..64).map(move |x| (x, y)))
+            .filter(|(x, y)| density.count(*x, *y) > 0.0)
+            .count();
+        assert!(visited > 64 * 64 / 4, "{}", visited);
+        let sum: f32 = (0..64 * 64).map(|i| density.count(i % 64, i / 64)).sum();
+        assert_eq!(sum, 100_000.0);
+    }
+}
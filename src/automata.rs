@@ -0,0 +1,250 @@
+//! Cellular automata: grids of cells, each stepped to a new state decided by a `Rule` from the
+//! cells around it.
+//!
+//! A `CellGrid` is double buffered, so every cell's next state is worked out from the grid as it
+//! was before the step, however the rule reads it. Its edges either wrap around, making the grid a
+//! torus, or are clamped, with nothing past them.
+
+/// What's past the edges of a `CellGrid`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Edges {
+    /// The other side of the grid, as if it were wrapped around a torus.
+    Wrap,
+    /// Nothing; cells on the edges just have fewer neighbours.
+    Clamp,
+}
+
+#[derive(Clone, Debug)]
+pub struct CellGrid<T> {
+    pub width: usize,
+    pub height: usize,
+    pub edges: Edges,
+    // row by row from the bottom left, and the buffer the next step writes into.
+    cells: Vec<T>,
+    next: Vec<T>,
+}
+
+/// The offsets of the eight cells around a cell.
+const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T: Clone> CellGrid<T> {
+    /// A grid with every cell `fill`, and wrapping edges.
+    pub fn new(width: usize, height: usize, fill: T) -> CellGrid<T> {
+        assert!(width > 0 && height > 0, "the grid can't be empty");
+        CellGrid {
+            width,
+            height,
+            edges: Edges::Wrap,
+            cells: vec![fill.clone(); width * height],
+            next: vec![fill; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        &self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: T) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    /// Every cell, row by row from the bottom left.
+    pub fn values(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Set every cell to `fill`.
+    pub fn fill(&mut self, fill: T) {
+        for cell in &mut self.cells {
+            *cell = fill.clone();
+        }
+    }
+
+    /// The cell at (`x`, `y`), which may be past the edges: wrapped around, or `None` if the
+    /// edges are clamped.
+    pub fn cell_at(&self, x: isize, y: isize) -> Option<&T> {
+        let (w, h) = (self.width as isize, self.height as isize);
+        match self.edges {
+            Edges::Wrap => Some(self.get(x.rem_euclid(w) as usize, y.rem_euclid(h) as usize)),
+            Edges::Clamp if x < 0 || y < 0 || x >= w || y >= h => None,
+            Edges::Clamp => Some(self.get(x as usize, y as usize)),
+        }
+    }
+
+    /// The (up to) eight cells around (`x`, `y`).
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = &T> + '_ {
+        MOORE
+            .iter()
+            .filter_map(move |(dx, dy)| self.cell_at(x as isize + dx, y as isize + dy))
+    }
+
+    /// Every cell and where it is, row by row from the bottom left.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (i % self.width, i / self.width, cell))
+    }
+
+    /// Move every cell on to the state `rule` gives it.
+    pub fn step<R: Rule<T>>(&mut self, rule: &R) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.next[y * self.width + x] = rule.next(self, x, y);
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next);
+    }
+}
+
+/// How a cell changes each step.
+pub trait Rule<T> {
+    /// The state the cell at (`x`, `y`) in `grid` moves on to.
+    fn next(&self, grid: &CellGrid<T>, x: usize, y: usize) -> T;
+}
+
+impl<T, F: Fn(&CellGrid<T>, usize, usize) -> T> Rule<T> for F {
+    fn next(&self, grid: &CellGrid<T>, x: usize, y: usize) -> T {
+        self(grid, x, y)
+    }
+}
+
+/// Conway's Game of Life: a dead cell with three living neighbours comes alive, and a living cell
+/// with two or three stays alive.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Life;
+
+impl Rule<bool> for Life {
+    fn next(&self, grid: &CellGrid<bool>, x: usize, y: usize) -> bool {
+        let living = grid.neighbours(x, y).filter(|cell| **cell).count();
+        living == 3 || (living == 2 && *grid.get(x, y))
+    }
+}
+
+/// One of Wolfram's 256 elementary cellular automata, numbered by their rule: a row of cells,
+/// each stepped according to itself and its left and right neighbours.
+///
+/// On a grid, the top row is the automaton, and every other row is the row above it a step ago,
+/// so the grid scrolls down through its history.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Elementary(pub u8);
+
+impl Rule<bool> for Elementary {
+    fn next(&self, grid: &CellGrid<bool>, x: usize, y: usize) -> bool {
+        if y + 1 < grid.height {
+            return *grid.get(x, y + 1);
+        }
+        let alive = |dx: isize| *grid.cell_at(x as isize + dx, y as isize).unwrap_or(&false);
+        let pattern = (alive(-1) as u8) << 2 | (alive(0) as u8) << 1 | alive(1) as u8;
+        self.0 >> pattern & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn living(grid: &CellGrid<bool>) -> Vec<(usize, usize)> {
+        grid.cells()
+            .filter(|(_, _, cell)| **cell)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn test_life() {
+        let mut grid = CellGrid::new(8, 8, false);
+        // a glider, heading down and right.
+        for (x, y) in &[(1, 7), (2, 6), (0, 5), (1, 5), (2, 5)] {
+            grid.set(*x, *y, true);
+        }
+        let start = living(&grid);
+        // every four steps it moves a cell diagonally, so it wraps all the way around in 32.
+        for _ in 0..4 {
+            grid.step(&Life);
+        }
+        let mut moved = start
+            .iter()
+            .map(|(x, y)| ((x + 1) % 8, (y + 7) % 8))
+            .collect::<Vec<_>>();
+        // in the same order `living` lists them.
+        moved.sort_by_key(|(x, y)| (*y, *x));
+        assert_eq!(living(&grid), moved);
+        for _ in 4..32 {
+            grid.step(&Life);
+        }
+        assert_eq!(living(&grid), start);
+
+        // without wrapping, it runs into the corner and settles into a still block.
+        grid.edges = Edges::Clamp;
+        for _ in 0..40 {
+            grid.step(&Life);
+        }
+        assert_eq!(living(&grid), vec![(6, 0), (7, 0), (6, 1), (7, 1)]);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let mut grid = CellGrid::new(4, 3, 0);
+        for (x, y, _) in grid.clone().cells() {
+            grid.set(x, y, y * 4 + x);
+        }
+        let mut corner = grid.neighbours(0, 0).cloned().collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(corner, vec![1, 3, 4, 5, 7, 8, 9, 11]);
+        grid.edges = Edges::Clamp;
+        assert_eq!(grid.neighbours(0, 0).count(), 3);
+        assert_eq!(grid.neighbours(1, 1).count(), 8);
+        assert_eq!(grid.cell_at(-1, 0), None);
+
+        // any function will do for a rule.
+        grid.step(&|grid: &CellGrid<usize>, x: usize, y: usize| {
+            grid.neighbours(x, y).sum::<usize>()
+        });
+        assert_eq!(*grid.get(0, 0), 1 + 4 + 5);
+    }
+
+    #[test]
+    fn test_elementary() {
+        // rule 90 draws a Sierpinski triangle from a single cell.
+        let mut grid = CellGrid::new(9, 4, false);
+        grid.edges = Edges::Clamp;
+        grid.set(4, 3, true);
+        for _ in 0..3 {
+            grid.step(&Elementary(90));
+        }
+        let rows = (0..4)
+            .rev()
+            .map(|y| {
+                (0..9)
+                    .map(|x| if *grid.get(x, y) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![".#.#.#.#.", "..#...#..", "...#.#...", "....#...."]
+        );
+
+        // rule 30, on the other hand, gets messy fast.
+        let mut grid = CellGrid::new(5, 1, false);
+        grid.set(2, 0, true);
+        grid.step(&Elementary(30));
+        assert_eq!(living(&grid), vec![(1, 0), (2, 0), (3, 0)]);
+        grid.step(&Elementary(30));
+        assert_eq!(living(&grid), vec![(0, 0), (1, 0), (4, 0)]);
+    }
+}
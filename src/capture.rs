@@ -0,0 +1,430 @@
+//! Saving a sketch's frames as numbered PNGs, to make a video from.
+//!
+//! Frames are saved into a directory, usually the one given with `--capture` (see
+//! `Capture::from_args`), either all of them, a range of them, or whichever are drawn while a key's
+//! switched it on:
+//!
+//! ```ignore
+//! // in the model:
+//! let mut capture = Capture::new("frames");
+//! capture.toggle = Some(Key::C);
+//! // in key presses:
+//! capture.key(app, key);
+//! // at the end of the view:
+//! capture.save(app);
+//! ```
+//!
+//! Frames are numbered from 0 in the order they're saved, so a range or several takes still make
+//! one unbroken sequence.
+//!
+//! Saving a `Metadata` with `write_metadata` puts a `metadata.json` beside the frames, recording
+//! the seed, commit, arguments and anything else they were made with, so they can be made again.
+//!
+//! `Screenshot` saves just the frame being drawn, named for when it was taken, when its key's
+//! pressed; every `Sketch` takes one of its canvas on F12.
+//!
+//! `Headless` renders frames offscreen instead, at a steady rate however long each takes and at
+//! any size, so the window can be hidden; for long renders on a machine no one's watching.
+
+use crate::cli::Args;
+use crate::viz::capture::FrameCapture;
+use nannou::event::Key;
+use nannou::window::{self, Window};
+use nannou::{App, Draw};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Capture {
+    dir: PathBuf,
+    /// Which frames to save, counted as `App::elapsed_frames` does; every one if none.
+    pub frames: Option<Range<u64>>,
+    /// A key switching saving on and off, starting off; if none, it's always on.
+    pub toggle: Option<Key>,
+    /// How many digits file names are padded to.
+    pub digits: usize,
+    // the frame saving was last switched on at, while it's on.
+    take: Option<u64>,
+    // how many frames the takes before it saved.
+    taken: u64,
+}
+
+impl Capture {
+    /// Save frames into `dir`, making it if it isn't there.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Capture {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|e| panic!("can't save frames in {}: {}", dir.display(), e));
+        Capture {
+            dir: dir.to_path_buf(),
+            frames: None,
+            toggle: None,
+            digits: 5,
+            take: None,
+            taken: 0,
+        }
+    }
+
+    /// Save frames into the `--capture` directory, if it was given.
+    pub fn from_args(args: &Args) -> Option<Capture> {
+        args.capture().map(Capture::new)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Switch saving on or off if `key` is the `toggle`.
+    pub fn key(&mut self, app: &App, key: Key) {
+        if Some(key) == self.toggle {
+            self.switch(app.elapsed_frames());
+        }
+    }
+
+    /// Switch saving on or off, at `frame`.
+    pub fn switch(&mut self, frame: u64) {
+        match self.take.take() {
+            Some(start) => self.taken += frame.saturating_sub(start),
+            None => self.take = Some(frame),
+        }
+    }
+
+    /// Whether saving is switched on; it always is without a `toggle`.
+    pub fn on(&self) -> bool {
+        self.toggle.is_none() || self.take.is_some()
+    }
+
+    /// Whether every frame in the range has been saved, so the sketch can stop.
+    pub fn done(&self, frame: u64) -> bool {
+        match &self.frames {
+            Some(frames) => frame >= frames.end,
+            None => false,
+        }
+    }
+
+    /// Where `frame` is saved, if it is.
+    pub fn path(&self, frame: u64) -> Option<PathBuf> {
+        let n = match &self.frames {
+            Some(frames) if !frames.contains(&frame) => return None,
+            Some(frames) if self.toggle.is_none() => frame - frames.start,
+            None if self.toggle.is_none() => frame,
+            _ => self.taken + frame.saturating_sub(self.take?),
+        };
+        let name = format!("{:0digits$}.png", n, digits = self.digits);
+        Some(self.dir.join(name))
+    }
+
+    /// Save the frame being drawn, if it's one to save.
+    pub fn save(&self, app: &App) {
+        if let Some(path) = self.path(app.elapsed_frames()) {
+            app.main_window().capture_frame(path);
+        }
+    }
+
+    /// Save `metadata` as `metadata.json` beside the frames, replacing any that's there.
+    pub fn write_metadata(&self, metadata: &Metadata) {
+        let path = self.dir.join("metadata.json");
+        fs::write(&path, metadata.to_json())
+            .unwrap_or_else(|e| panic!("can't save {}: {}", path.display(), e));
+    }
+}
+
+/// What a capture was made with.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Metadata {
+    pub sketch: String,
+    pub seed: Option<u64>,
+    /// The commit the sketch was built from, with `-dirty` after it if there were changes on top.
+    pub commit: Option<String>,
+    /// The command line arguments, after the program's name.
+    pub args: Vec<String>,
+    /// Anything else the sketch was made with, by name, like constants read from a config file.
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// When it was made, in seconds since 1970.
+    pub timestamp: u64,
+}
+
+impl Metadata {
+    /// The sketch called `sketch`, from `seed`, as it's running now.
+    pub fn new(sketch: &str, seed: Option<u64>) -> Metadata {
+        Metadata {
+            sketch: sketch.to_string(),
+            seed,
+            commit: commit(),
+            args: env::args().skip(1).collect(),
+            params: BTreeMap::new(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_secs())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Record a parameter, anything serde can serialize.
+    pub fn param<T: Serialize>(&mut self, name: &str, value: &T) {
+        let value =
+            serde_json::to_value(value).unwrap_or_else(|e| panic!("can't record {}: {}", name, e));
+        self.params.insert(name.to_string(), value);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+// the commit the crate's checked out at, if git can say.
+fn commit() -> Option<String> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    };
+    let commit = git(&["rev-parse", "HEAD"])?.trim().to_string();
+    let dirty = match git(&["status", "--porcelain"]) {
+        Some(status) => !status.trim().is_empty(),
+        None => false,
+    };
+    Some(if dirty { commit + "-dirty" } else { commit })
+}
+
+/// Saves the frame being drawn when its key's pressed, as `screenshots/<sketch>_<time>.png`.
+pub struct Screenshot {
+    /// F12 by default, as letters tend to be taken by the sketches' own controls.
+    pub key: Key,
+    pub dir: PathBuf,
+    /// The window screenshotted, or the focused one if none.
+    pub window: Option<window::Id>,
+    name: String,
+}
+
+impl Default for Screenshot {
+    fn default() -> Self {
+        Screenshot::new()
+    }
+}
+
+impl Screenshot {
+    /// Screenshots named after the running example.
+    pub fn new() -> Screenshot {
+        let name = env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.file_stem()?.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "sketch".to_string());
+        Screenshot {
+            key: Key::F12,
+            dir: PathBuf::from("screenshots"),
+            window: None,
+            name,
+        }
+    }
+
+    /// Take a screenshot if `key` is the `key`.
+    pub fn key(&self, app: &App, key: Key) {
+        if key == self.key {
+            self.take(app);
+        }
+    }
+
+    /// Save the frame being drawn.
+    pub fn take(&self, app: &App) {
+        let path = self.path_now();
+        let window = match self.window {
+            Some(id) => app.window(id).expect("screenshotting a closed window"),
+            None => app.main_window(),
+        };
+        println!("saving {}", path.display());
+        window.capture_frame(path);
+    }
+
+    /// Where a screenshot taken now is saved, making the directory if it isn't there; for saving
+    /// something other than the frame.
+    pub fn path_now(&self) -> PathBuf {
+        fs::create_dir_all(&self.dir)
+            .unwrap_or_else(|e| panic!("can't save screenshots in {}: {}", self.dir.display(), e));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+        self.path(now)
+    }
+
+    /// Where a screenshot taken `time` seconds after 1970 is saved.
+    pub fn path(&self, time: u64) -> PathBuf {
+        self.dir
+            .join(format!("{}_{}.png", self.name, timestamp(time)))
+    }
+}
+
+// `time`, in seconds since 1970, as a UTC date and time fit for a file name.
+fn timestamp(time: u64) -> String {
+    // Howard Hinnant's civil_from_days, which counts years from March, so leap days fall at the
+    // end of them.
+    let days = time / 86400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let (year, month) = match month {
+        0..=9 => (era * 400 + year_of_era, month + 3),
+        _ => (era * 400 + year_of_era + 1, month - 9),
+    };
+    let seconds = time % 86400;
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub struct Headless {
+    /// Which frames to save, and where; it's done at the end of its `frames`.
+    pub capture: Capture,
+    /// How far each frame moves the sketch on, in seconds.
+    pub dt: f32,
+    offscreen: FrameCapture,
+    scale: f32,
+    frame: u64,
+}
+
+impl Headless {
+    /// Render what would be drawn in `window`, at `scale` times as many pixels across, and save
+    /// frames as `capture` says.
+    pub fn new(window: &Window, scale: f32, capture: Capture) -> Headless {
+        let (w, h) = window.inner_size_points();
+        let size = [(w * scale).round() as u32, (h * scale).round() as u32];
+        Headless {
+            capture,
+            dt: 1.0 / 60.0,
+            offscreen: FrameCapture::new(window, size),
+            scale,
+            frame: 0,
+        }
+    }
+
+    /// How many frames have been rendered.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// A fresh `Draw` for the next frame, scaled up to fill it as it would the window.
+    pub fn draw(&self) -> Draw {
+        Draw::new().scale(self.scale)
+    }
+
+    /// Render `draw` as the next frame, saving it if it's one to save. Frames which aren't are
+    /// still rendered, as a sketch might draw over what it drew before.
+    pub fn render(&mut self, window: &Window, draw: &Draw) {
+        match self.capture.path(self.frame) {
+            Some(path) => self.offscreen.save(window, draw, path),
+            None => self.offscreen.capture(window, draw),
+        }
+        self.frame += 1;
+    }
+
+    /// Whether every frame to save has been rendered.
+    pub fn done(&self) -> bool {
+        self.capture.done(self.frame)
+    }
+
+    /// Wait for the frames still being saved; call before the app exits.
+    pub fn finish(&self, window: &Window) {
+        self.offscreen.finish(window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(name: &str) -> Capture {
+        Capture::new(std::env::temp_dir().join(name))
+    }
+
+    fn name(capture: &Capture, frame: u64) -> Option<String> {
+        let path = capture.path(frame)?;
+        assert_eq!(path.parent(), Some(capture.dir()));
+        Some(path.file_name().unwrap().to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_range() {
+        let mut capture = capture("capture_test_range");
+        assert_eq!(name(&capture, 7).as_deref(), Some("00007.png"));
+        capture.frames = Some(240..620);
+        capture.digits = 3;
+        assert_eq!(name(&capture, 239), None);
+        assert_eq!(name(&capture, 240).as_deref(), Some("000.png"));
+        assert_eq!(name(&capture, 619).as_deref(), Some("379.png"));
+        assert_eq!(name(&capture, 620), None);
+        assert!(!capture.done(619));
+        assert!(capture.done(620));
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut capture = capture("capture_test_toggle");
+        capture.toggle = Some(Key::C);
+        assert!(!capture.on());
+        assert_eq!(name(&capture, 3), None);
+        capture.switch(10);
+        assert!(capture.on());
+        assert_eq!(name(&capture, 10).as_deref(), Some("00000.png"));
+        assert_eq!(name(&capture, 14).as_deref(), Some("00004.png"));
+        capture.switch(15);
+        assert_eq!(name(&capture, 20), None);
+        // the second take carries on numbering from the first.
+        capture.switch(30);
+        assert_eq!(name(&capture, 30).as_deref(), Some("00005.png"));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0), "1970-01-01_00-00-00");
+        assert_eq!(timestamp(1_000_000_000), "2001-09-09_01-46-40");
+        // a leap day, and either side of new year.
+        assert_eq!(timestamp(951_782_400), "2000-02-29_00-00-00");
+        assert_eq!(timestamp(1_704_067_199), "2023-12-31_23-59-59");
+        assert_eq!(timestamp(1_704_067_200), "2024-01-01_00-00-00");
+
+        let mut screenshot = Screenshot::new();
+        screenshot.dir = PathBuf::from("shots");
+        let path = screenshot.path(0);
+        assert_eq!(path.parent(), Some(Path::new("shots")));
+        assert!(path.to_str().unwrap().ends_with("_1970-01-01_00-00-00.png"));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let capture = capture("capture_test_metadata");
+        let mut metadata = Metadata::new("test", Some(7));
+        metadata.param("count", &30);
+        metadata.param("gravity", &[0.0, -1.0]);
+        capture.write_metadata(&metadata);
+
+        let json = fs::read_to_string(capture.dir().join("metadata.json")).unwrap();
+        let read: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, metadata);
+        assert_eq!(read.seed, Some(7));
+        assert_eq!(read.params["count"], serde_json::json!(30));
+        assert_eq!(read.params["gravity"], serde_json::json!([0.0, -1.0]));
+        assert!(read.timestamp > 0);
+    }
+}
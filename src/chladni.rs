@@ -0,0 +1,198 @@
+//! Chladni figures: the patterns sand makes on a vibrating plate, gathering along the nodal lines
+//! that stay still while the rest of the plate shakes.
+//!
+//! A square plate with free edges, from -1 to 1 on each side, vibrates in modes numbered by a
+//! pair (`m`, `n`) of how many times it ripples across each way; a `Plate` driven at some
+//! frequency rings with the modes whose own frequencies are near it, and `Sand` shaken on it is
+//! bounced about wherever the plate moves, and settles where it doesn't.
+
+use nannou::geom::{vec2, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::f32::consts::PI;
+
+/// One way the plate can vibrate.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Mode {
+    pub m: u32,
+    pub n: u32,
+}
+
+impl Mode {
+    /// The frequency it rings at, in arbitrary units.
+    pub fn frequency(&self) -> f32 {
+        (self.m * self.m + self.n * self.n) as f32
+    }
+
+    /// How far the plate at `pos` moves, from -2 to 2.
+    pub fn amplitude(&self, pos: Vector2) -> f32 {
+        let (m, n) = (self.m as f32 * PI, self.n as f32 * PI);
+        (n * pos.x).cos() * (m * pos.y).cos() - (m * pos.x).cos() * (n * pos.y).cos()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Plate {
+    /// How fast the plate is driven; modes ring the louder the nearer it is to their frequency.
+    pub frequency: f32,
+    /// How far from a mode's frequency the plate can be driven and still ring it at half volume.
+    pub bandwidth: f32,
+    modes: Vec<Mode>,
+}
+
+impl Plate {
+    /// A plate which can ring in every mode up to `order` times across each way, driven at the
+    /// first one's frequency.
+    pub fn new(order: u32) -> Plate {
+        // with m == n there's no pattern, and (n, m) is just (m, n) upside down.
+        let mut modes = vec![];
+        for n in 1..=order {
+            for m in 0..n {
+                modes.push(Mode { m, n });
+            }
+        }
+        assert!(!modes.is_empty(), "the plate needs to ring somehow");
+        modes.sort_by(|a, b| a.frequency().partial_cmp(&b.frequency()).unwrap());
+        Plate {
+            frequency: modes[0].frequency(),
+            bandwidth: 0.5,
+            modes,
+        }
+    }
+
+    /// Every mode the plate can ring in, from the lowest frequency to the highest.
+    pub fn modes(&self) -> &[Mode] {
+        &self.modes
+    }
+
+    /// How loud `mode` rings, from 0 to 1.
+    pub fn weight(&self, mode: &Mode) -> f32 {
+        let detuning = (self.frequency - mode.frequency()) / self.bandwidth;
+        1.0 / (1.0 + detuning * detuning)
+    }
+
+    /// How far the plate at `pos` moves, from about -2 to 2.
+    pub fn amplitude(&self, pos: Vector2) -> f32 {
+        let (mut sum, mut total) = (0.0, 0.0);
+        for mode in &self.modes {
+            let weight = self.weight(mode);
+            sum += weight * mode.amplitude(pos);
+            total += weight;
+        }
+        sum / total
+    }
+
+    /// Which way the plate moves more, and how steeply.
+    fn shaking_gradient(&self, pos: Vector2) -> Vector2 {
+        let h = 1e-3;
+        let shaking = |pos: Vector2| self.amplitude(pos).abs();
+        vec2(
+            shaking(pos + vec2(h, 0.0)) - shaking(pos - vec2(h, 0.0)),
+            shaking(pos + vec2(0.0, h)) - shaking(pos - vec2(0.0, h)),
+        ) / (2.0 * h)
+    }
+}
+
+/// Grains of sand on a `Plate`.
+#[derive(Clone, Debug)]
+pub struct Sand {
+    /// How far a grain is bounced each second where the plate moves the most.
+    pub jitter: f32,
+    /// How fast grains slide towards where the plate moves less.
+    pub settle: f32,
+    grains: Vec<Vector2>,
+    rng: XorShiftRng,
+}
+
+impl Sand {
+    /// `grains` grains scattered evenly over the plate.
+    pub fn new(grains: usize, seed: u64) -> Sand {
+        let mut sand = Sand {
+            jitter: 0.5,
+            settle: 0.05,
+            grains: vec![vec2(0.0, 0.0); grains],
+            rng: SeedableRng::seed_from_u64(seed),
+        };
+        sand.scatter();
+        sand
+    }
+
+    pub fn grains(&self) -> &[Vector2] {
+        &self.grains
+    }
+
+    /// Scatter every grain evenly over the plate again.
+    pub fn scatter(&mut self) {
+        for grain in &mut self.grains {
+            *grain = vec2(self.rng.gen_range(-1.0, 1.0), self.rng.gen_range(-1.0, 1.0));
+        }
+    }
+
+    /// Shake the sand on `plate` for `dt` seconds.
+    pub fn step(&mut self, plate: &Plate, dt: f32) {
+        for grain in &mut self.grains {
+            let angle = self.rng.gen::<f32>() * 2.0 * PI;
+            let bounce = vec2(angle.cos(), angle.sin()) * plate.amplitude(*grain).abs() / 2.0;
+            let slide = -plate.shaking_gradient(*grain);
+            let moved = *grain + (bounce * self.jitter + slide * self.settle) * dt;
+            // held in by the edges of the plate.
+            *grain = vec2(moved.x.max(-1.0).min(1.0), moved.y.max(-1.0).min(1.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes() {
+        let plate = Plate::new(4);
+        // m from 0 up to n - 1, for each n from 1 to 4.
+        assert_eq!(plate.modes().len(), 1 + 2 + 3 + 4);
+        assert_eq!(plate.modes()[0], Mode { m: 0, n: 1 });
+        for mode in plate.modes() {
+            // every pattern is still along the diagonal.
+            for t in &[-0.9, -0.3, 0.0, 0.4, 1.0] {
+                assert!(mode.amplitude(vec2(*t, *t)).abs() < 1e-5);
+            }
+        }
+        // and most of the plate isn't still.
+        let mode = Mode { m: 1, n: 2 };
+        assert_eq!(mode.amplitude(vec2(0.0, 1.0)), -2.0);
+
+        // driven at a mode's frequency, the plate rings mostly in that mode.
+        let mut plate = Plate::new(6);
+        plate.frequency = mode.frequency();
+        let pos = vec2(0.3, -0.7);
+        assert!((plate.amplitude(pos) - mode.amplitude(pos)).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_settle() {
+        let mut plate = Plate::new(5);
+        plate.frequency = Mode { m: 2, n: 5 }.frequency();
+        let mut sand = Sand::new(500, 7);
+        let shaking = |sand: &Sand| {
+            sand.grains()
+                .iter()
+                .map(|grain| plate.amplitude(*grain).abs())
+                .sum::<f32>()
+                / sand.grains().len() as f32
+        };
+        let before = shaking(&sand);
+        for _ in 0..300 {
+            sand.step(&plate, 1.0 / 30.0);
+        }
+        // the sand has gathered where the plate's nearly still.
+        assert!(
+            shaking(&sand) < before / 3.0,
+            "{} {}",
+            before,
+            shaking(&sand)
+        );
+        for grain in sand.grains() {
+            assert!(grain.x.abs() <= 1.0 && grain.y.abs() <= 1.0);
+        }
+    }
+}
@@ -1,11 +1,39 @@
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 use petgraph::visit::EdgeRef;
 use petgraph::Direction;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+
+pub mod challenge;
+pub mod debugger;
+pub mod events;
+pub mod faults;
+pub mod fsm;
+pub mod history;
+pub mod parallel;
+pub mod save;
+pub mod testing;
+pub mod truth_table;
 
 /// The type carried by wires.
 pub type Value = bool;
 
+/// A stable name for a gate. A `NodeIndex` can change when other gates are removed, and means
+/// nothing once the circuit is saved and loaded again; a gate keeps its `GateId` through both.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct GateId(pub u32);
+
+/// The id of each node, and the node with each id.
+#[derive(Clone, Debug, Default)]
+struct GateIds {
+    /// Indexed by `NodeIndex`.
+    ids: Vec<GateId>,
+    nodes: HashMap<GateId, NodeIndex>,
+    /// Ids are never reused, even after their gate is removed.
+    next: u32,
+}
+
 /// A gate.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Gate {
@@ -16,15 +44,56 @@ pub enum Gate {
     Output,
     Input,
     MetaInput, // inserted before all inputs
+    /// The output of a D flip-flop. Like an `Input`, its stored value lives on its edge from the MetaInput.
+    FlipFlop,
+    /// The data + clock side of the D flip-flop `q`.
+    /// Latches its data input into `q` on a rising edge of the signal from `clock`.
+    FlipFlopIn {
+        q: NodeIndex,
+        clock: NodeIndex,
+        last_clock: Value,
+    },
+}
+
+impl Gate {
+    /// Whether two gates do the same thing, ignoring any simulation state they hold.
+    pub fn same_kind(&self, other: &Gate) -> bool {
+        match (self, other) {
+            (
+                Gate::FlipFlopIn { q, clock, .. },
+                Gate::FlipFlopIn {
+                    q: other_q,
+                    clock: other_clock,
+                    ..
+                },
+            ) => q == other_q && clock == other_clock,
+            _ => self == other,
+        }
+    }
 }
 
 /// A simulated digital "circuit". Must be a DAG.
 ///
 /// Input values come from a single MetaInput; their values can be changed using the `set_input` method.
 ///
+/// Sequential logic is possible using flip-flops, which are split into two nodes: a `FlipFlop`
+/// fed from the MetaInput, and a `FlipFlopIn` with no outputs. Feedback loops pass from one to the
+/// other "outside" the graph, so the graph itself stays acyclic.
+///
 /// Provides methods to build up a circuit programmatically. Methods to create some circuit node
 /// return a `NodeIndex` which can be used to read the output of that node.
-pub struct Circuit(pub DiGraph<Gate, Value>);
+/// Nodes can also be given labels, to read them by name.
+///
+/// Every node also has a `GateId`, for anything that needs to keep track of gates while others
+/// are removed or across saving and loading. The graph can only be changed through `Circuit`'s
+/// methods, which keep the ids up to date; `graph` reads it.
+#[derive(Clone)]
+pub struct Circuit {
+    graph: DiGraph<Gate, Value>,
+    /// The gate with each label.
+    labels: HashMap<String, GateId>,
+    ids: GateIds,
+}
 
 impl Circuit {
     // -- helpers --
@@ -32,72 +101,257 @@ impl Circuit {
         NodeIndex::new(0)
     }
 
+    /// The gates and the wires between them, each wire carrying the value its source drives.
+    pub fn graph(&self) -> &DiGraph<Gate, Value> {
+        &self.graph
+    }
+
     // -- construction functions; check invariants frequently, slow
     pub fn new() -> Circuit {
-        let mut graph = DiGraph::new();
-        graph.add_node(Gate::MetaInput);
-        let result = Circuit(graph);
+        let mut result = Circuit {
+            graph: DiGraph::new(),
+            labels: HashMap::new(),
+            ids: GateIds::default(),
+        };
+        result.add_node(Gate::MetaInput);
         result.check_invariants();
         result
     }
 
     /// Check a graph's invariants, panicking if they fail.
     pub fn check_invariants(&self) {
-        let meta_type = self.0[Circuit::meta_input()];
+        let meta_type = self.graph[Circuit::meta_input()];
         assert_eq!(meta_type, Gate::MetaInput, "meta input is the wrong type");
         assert!(
-            !petgraph::algo::is_cyclic_directed(&self.0),
+            !petgraph::algo::is_cyclic_directed(&self.graph),
             "graph is cyclic"
         );
         assert!(
-            self.0
+            self.graph
                 .edges_directed(Circuit::meta_input(), Direction::Incoming)
                 .next()
                 .is_none(),
             "meta input has inputs"
         );
+        assert_eq!(
+            self.ids.ids.len(),
+            self.graph.node_count(),
+            "nodes were added without ids"
+        );
+    }
+
+    /// Add a node to the graph, with a fresh id.
+    fn add_node(&mut self, gate: Gate) -> NodeIndex {
+        let id = GateId(self.ids.next);
+        self.add_node_with_id(gate, id)
+    }
+    /// Add a node to the graph, with an id that isn't in use.
+    fn add_node_with_id(&mut self, gate: Gate, id: GateId) -> NodeIndex {
+        assert!(
+            !self.ids.nodes.contains_key(&id),
+            "{:?} is already in use",
+            id
+        );
+        let node = self.graph.add_node(gate);
+        self.ids.ids.push(id);
+        self.ids.nodes.insert(id, node);
+        self.ids.next = self.ids.next.max(id.0 + 1);
+        node
+    }
+    /// Remove the last node from the graph, so that no other node is renumbered.
+    fn remove_last_node(&mut self) {
+        let last = NodeIndex::new(self.graph.node_count() - 1);
+        let id = self.ids.ids.pop().unwrap();
+        self.ids.nodes.remove(&id);
+        self.labels.retain(|_, labeled| *labeled != id);
+        self.graph.remove_node(last);
+    }
+    /// Exchange the ids of two nodes, to follow their gates being swapped in the graph.
+    fn swap_ids(&mut self, a: NodeIndex, b: NodeIndex) {
+        self.ids.ids.swap(a.index(), b.index());
+        self.ids.nodes.insert(self.ids.ids[a.index()], a);
+        self.ids.nodes.insert(self.ids.ids[b.index()], b);
+    }
+
+    /// The id of a node.
+    pub fn id(&self, node: NodeIndex) -> GateId {
+        self.ids.ids[node.index()]
+    }
+    /// The node with an id, if its gate is in the circuit.
+    pub fn node(&self, id: GateId) -> Option<NodeIndex> {
+        self.ids.nodes.get(&id).cloned()
     }
 
     pub fn add_input(&mut self) -> NodeIndex {
-        let input = self.0.add_node(Gate::Input);
-        self.0.update_edge(Circuit::meta_input(), input, false);
+        let input = self.add_node(Gate::Input);
+        self.graph.update_edge(Circuit::meta_input(), input, false);
         self.check_invariants();
         input
     }
     pub fn add_or(&mut self, a: NodeIndex, b: NodeIndex) -> NodeIndex {
-        let result = self.0.add_node(Gate::Or);
-        self.0.update_edge(a, result, false);
-        self.0.update_edge(b, result, false);
+        let result = self.add_node(Gate::Or);
+        self.graph.update_edge(a, result, false);
+        self.graph.update_edge(b, result, false);
         self.check_invariants();
         result
     }
     pub fn add_xor(&mut self, a: NodeIndex, b: NodeIndex) -> NodeIndex {
-        let result = self.0.add_node(Gate::Xor);
-        self.0.update_edge(a, result, false);
-        self.0.update_edge(b, result, false);
+        let result = self.add_node(Gate::Xor);
+        self.graph.update_edge(a, result, false);
+        self.graph.update_edge(b, result, false);
         self.check_invariants();
         result
     }
     pub fn add_and(&mut self, a: NodeIndex, b: NodeIndex) -> NodeIndex {
-        let result = self.0.add_node(Gate::And);
-        self.0.update_edge(a, result, false);
-        self.0.update_edge(b, result, false);
+        let result = self.add_node(Gate::And);
+        self.graph.update_edge(a, result, false);
+        self.graph.update_edge(b, result, false);
         self.check_invariants();
         result
     }
     pub fn add_not(&mut self, a: NodeIndex) -> NodeIndex {
-        let result = self.0.add_node(Gate::Not);
-        self.0.update_edge(a, result, false);
+        let result = self.add_node(Gate::Not);
+        self.graph.update_edge(a, result, false);
         self.check_invariants();
         result
     }
     pub fn add_output(&mut self, a: NodeIndex) -> NodeIndex {
-        let result = self.0.add_node(Gate::Output);
-        self.0.update_edge(a, result, false);
+        let result = self.add_node(Gate::Output);
+        self.graph.update_edge(a, result, false);
+        self.check_invariants();
+        result
+    }
+    /// Add an output, labeled so it can be read with `output_value`.
+    pub fn add_labeled_output(&mut self, a: NodeIndex, label: &str) -> NodeIndex {
+        let result = self.add_output(a);
+        self.set_label(result, label);
+        result
+    }
+    /// Label a node. Labels must be unique.
+    pub fn set_label(&mut self, node: NodeIndex, label: &str) {
+        assert!(
+            !self.labels.contains_key(label),
+            "label {:?} is already in use",
+            label
+        );
+        let id = self.id(node);
+        self.labels.insert(label.to_string(), id);
+    }
+    /// Look up a node by label.
+    pub fn labeled(&self, label: &str) -> Option<NodeIndex> {
+        self.labels.get(label).and_then(|id| self.node(*id))
+    }
+    /// Get the label of a node, if it has one.
+    pub fn label(&self, node: NodeIndex) -> Option<&str> {
+        let id = self.id(node);
+        self.labels
+            .iter()
+            .find(|(_, labeled)| **labeled == id)
+            .map(|(label, _)| &label[..])
+    }
+
+    /// Add a D flip-flop holding `init`. Returns its output `q`.
+    /// Its inputs are connected separately, using `connect_flip_flop`, so that feedback loops can be built.
+    pub fn add_flip_flop(&mut self, init: Value) -> NodeIndex {
+        let q = self.add_node(Gate::FlipFlop);
+        self.graph.update_edge(Circuit::meta_input(), q, init);
+        self.check_invariants();
+        q
+    }
+    /// Connect the data and clock inputs of the flip-flop `q`.
+    /// Returns the `FlipFlopIn` node.
+    pub fn connect_flip_flop(&mut self, q: NodeIndex, d: NodeIndex, clock: NodeIndex) -> NodeIndex {
+        assert_eq!(self.graph[q], Gate::FlipFlop);
+        assert_ne!(d, clock, "flip-flop data and clock must come from different nodes");
+        assert!(
+            self.graph.raw_nodes().iter().all(|node| match node.weight {
+                Gate::FlipFlopIn { q: other, .. } => other != q,
+                _ => true,
+            }),
+            "flip-flop {:?} is already connected",
+            q
+        );
+        let result = self.add_node(Gate::FlipFlopIn {
+            q,
+            clock,
+            last_clock: false,
+        });
+        self.graph.update_edge(d, result, false);
+        self.graph.update_edge(clock, result, false);
         self.check_invariants();
         result
     }
 
+    /// Stamp a copy of the circuit `sub` into this one, as a subcircuit.
+    ///
+    /// `sub`'s Inputs, in the order they were added, are replaced with the nodes in `inputs`.
+    /// Returns the nodes driving copies of `sub`'s Outputs, in the order they were added; the
+    /// Outputs themselves aren't copied, so their values can be wired onward. Flip-flops are
+    /// copied with the value they hold in `sub`. Labels aren't copied.
+    pub fn instantiate(&mut self, sub: &Circuit, inputs: &[NodeIndex]) -> Vec<NodeIndex> {
+        let meta = Circuit::meta_input();
+        let sub_inputs = sub
+            .graph
+            .node_indices()
+            .filter(|n| sub.graph[*n] == Gate::Input)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sub_inputs.len(),
+            inputs.len(),
+            "subcircuit has {} inputs, given {}",
+            sub_inputs.len(),
+            inputs.len()
+        );
+
+        let mut map = sub_inputs
+            .into_iter()
+            .zip(inputs.iter().cloned())
+            .collect::<HashMap<_, _>>();
+        for node in sub.graph.node_indices() {
+            match sub.graph[node] {
+                Gate::MetaInput | Gate::Input | Gate::Output => (),
+                gate => {
+                    map.insert(node, self.add_node(gate));
+                }
+            }
+        }
+        for node in sub.graph.node_indices() {
+            if let Gate::FlipFlopIn {
+                q,
+                clock,
+                last_clock,
+            } = sub.graph[node]
+            {
+                self.graph[map[&node]] = Gate::FlipFlopIn {
+                    q: map[&q],
+                    clock: map[&clock],
+                    last_clock,
+                };
+            }
+        }
+
+        let mut outputs = vec![];
+        for edge in sub.graph.edge_references() {
+            let (source, target) = (edge.source(), edge.target());
+            match (sub.graph[source], sub.graph[target]) {
+                // the copy keeps its own stored value.
+                (Gate::MetaInput, Gate::FlipFlop) => {
+                    self.graph.add_edge(meta, map[&target], *edge.weight());
+                }
+                (Gate::MetaInput, _) => (),
+                (_, Gate::Output) => outputs.push((target, map[&source])),
+                // parallel edges, in case `inputs` repeats a node.
+                _ => {
+                    self.graph.add_edge(map[&source], map[&target], *edge.weight());
+                }
+            }
+        }
+        self.check_invariants();
+
+        outputs.sort();
+        outputs.into_iter().map(|(_, driver)| driver).collect()
+    }
+
     // -- slow processing algorithms --
 
     /// Compute a series of ranks.
@@ -105,37 +359,131 @@ impl Circuit {
     pub fn ranks(&self) -> HashMap<NodeIndex, u32> {
         self.check_invariants();
 
-        let new_graph = self.0.map(|_, _| (), |_, _| -1.0f32);
+        let new_graph = self.graph.map(|_, _| (), |_, _| -1.0f32);
 
         let (path_lens, _) =
             petgraph::algo::bellman_ford(&new_graph, Circuit::meta_input()).unwrap();
         let mut ranks = HashMap::new();
-        for (node, path_len) in self.0.node_indices().zip(&path_lens) {
+        for (node, path_len) in self.graph.node_indices().zip(&path_lens) {
             ranks.insert(node, (-*path_len) as u32);
         }
 
         ranks
     }
 
+    /// Compare the structure of this circuit against `other`, ignoring signal values.
+    /// Gates are matched up by `GateId`, so this works best when `other` was derived from this
+    /// circuit (or vice versa), and still works after gates are removed from either.
+    pub fn diff(&self, other: &Circuit) -> CircuitDiff {
+        let mut result = CircuitDiff::default();
+
+        let gates = |circuit: &Circuit| {
+            circuit
+                .graph
+                .node_indices()
+                .map(|node| (circuit.id(node), circuit.graph[node]))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let (ours, theirs) = (gates(self), gates(other));
+        result.added = theirs.keys().filter(|id| !ours.contains_key(id)).cloned().collect();
+        result.removed = ours.keys().filter(|id| !theirs.contains_key(id)).cloned().collect();
+        for (id, before) in &ours {
+            if let Some(after) = theirs.get(id) {
+                if !before.same_kind(after) {
+                    result.retyped.push((*id, *before, *after));
+                }
+            }
+        }
+
+        // wires are counted, since there can be several between the same two gates.
+        let edges = |circuit: &Circuit| {
+            let mut counts = BTreeMap::new();
+            for edge in circuit.graph.edge_references() {
+                let pair = (circuit.id(edge.source()), circuit.id(edge.target()));
+                *counts.entry(pair).or_insert(0) += 1;
+            }
+            counts
+        };
+        let (ours, theirs) = (edges(self), edges(other));
+        // each pair once for every wire `a` has between it beyond what `b` has.
+        let surplus = |a: &BTreeMap<(GateId, GateId), usize>, b: &BTreeMap<_, usize>| {
+            a.iter()
+                .flat_map(|(pair, count)| {
+                    let extra = count.saturating_sub(b.get(pair).cloned().unwrap_or(0));
+                    iter::repeat(*pair).take(extra)
+                })
+                .collect()
+        };
+        result.added_edges = surplus(&theirs, &ours);
+        result.removed_edges = surplus(&ours, &theirs);
+        result
+    }
+
     // -- fast processing algorithms --
 
+    /// Capture the current value of every wire, including inputs and flip-flop states, so that
+    /// the simulation can be rewound with `restore`.
+    pub fn snapshot(&self) -> SimState {
+        SimState {
+            signals: self.graph.raw_edges().iter().map(|e| e.weight).collect(),
+            last_clocks: self
+                .graph
+                .node_indices()
+                .filter_map(|node| match self.graph[node] {
+                    Gate::FlipFlopIn { last_clock, .. } => Some((node, last_clock)),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+    /// Rewind the simulation to a `snapshot` of this circuit.
+    /// Panics if gates or wires have been added or removed since the snapshot was taken.
+    pub fn restore(&mut self, state: &SimState) {
+        assert_eq!(
+            self.graph.edge_count(),
+            state.signals.len(),
+            "snapshot is from a different circuit"
+        );
+        for (weight, value) in self.graph.edge_weights_mut().zip(&state.signals) {
+            *weight = *value;
+        }
+        for (node, value) in &state.last_clocks {
+            match &mut self.graph[*node] {
+                Gate::FlipFlopIn { last_clock, .. } => *last_clock = *value,
+                gate => panic!(
+                    "snapshot is from a different circuit: {:?} is {:?}",
+                    node, gate
+                ),
+            }
+        }
+    }
+
     /// Set a single input.
     pub fn set_input(&mut self, input: NodeIndex, value: Value) {
-        assert_eq!(self.0[input], Gate::Input);
-        self.0.update_edge(Circuit::meta_input(), input, value);
+        assert_eq!(self.graph[input], Gate::Input);
+        self.graph.update_edge(Circuit::meta_input(), input, value);
+    }
+
+    /// Set the state held by a flip-flop.
+    pub fn set_flip_flop(&mut self, q: NodeIndex, value: Value) {
+        assert_eq!(self.graph[q], Gate::FlipFlop);
+        self.graph.update_edge(Circuit::meta_input(), q, value);
     }
 
     /// Get 1 signal into a gate. There *must* be only 1 signal.
     pub fn get_1_in(&self, gate: NodeIndex) -> Value {
-        let gate_type = self.0[gate];
+        let gate_type = self.graph[gate];
         assert!(
-            gate_type == Gate::Input || gate_type == Gate::Output || gate_type == Gate::Not,
-            "gate {:?} should be Input, Output, or Not, is {:?}",
+            gate_type == Gate::Input
+                || gate_type == Gate::Output
+                || gate_type == Gate::Not
+                || gate_type == Gate::FlipFlop,
+            "gate {:?} should be Input, Output, Not, or FlipFlop, is {:?}",
             gate,
             gate_type
         );
 
-        let mut edges = self.0.edges_directed(gate, Direction::Incoming);
+        let mut edges = self.graph.edges_directed(gate, Direction::Incoming);
 
         let edge = edges.next();
         let none = edges.next();
@@ -145,9 +493,31 @@ impl Circuit {
             _ => panic!("gate {} should only have 1 input"),
         }
     }
+    /// Get the value of the output with the given label. There *must* be such an output.
+    pub fn output_value(&self, label: &str) -> Value {
+        let node = self
+            .labeled(label)
+            .unwrap_or_else(|| panic!("no node labeled {:?}", label));
+        assert_eq!(
+            self.graph[node],
+            Gate::Output,
+            "node labeled {:?} should be Output",
+            label
+        );
+        self.get_1_in(node)
+    }
+    /// Get the values of all labeled outputs.
+    pub fn read_outputs(&self) -> HashMap<String, Value> {
+        self.labels
+            .iter()
+            .map(|(label, id)| (label, self.node(*id).unwrap()))
+            .filter(|(_, node)| self.graph[*node] == Gate::Output)
+            .map(|(label, node)| (label.clone(), self.get_1_in(node)))
+            .collect()
+    }
     /// Get 2 signals into a gate. There *must* be precisely 2 signals.
     pub fn get_2_in(&self, gate: NodeIndex) -> (Value, Value) {
-        let gate_type = self.0[gate];
+        let gate_type = self.graph[gate];
         assert!(
             gate_type == Gate::Or || gate_type == Gate::Xor || gate_type == Gate::And,
             "gate {:?} should be Or or Xor, is {:?}",
@@ -155,7 +525,7 @@ impl Circuit {
             gate_type
         );
 
-        let mut edges = self.0.edges_directed(gate, Direction::Incoming);
+        let mut edges = self.graph.edges_directed(gate, Direction::Incoming);
 
         let a = edges.next();
         let b = edges.next();
@@ -166,48 +536,188 @@ impl Circuit {
             _ => panic!("gate {} should have precisely 2 inputs"),
         }
     }
+    /// Get the signal a gate is currently driving, or `None` if it isn't connected to anything.
+    pub fn get_out(&self, gate: NodeIndex) -> Option<Value> {
+        self.graph
+            .edges_directed(gate, Direction::Outgoing)
+            .next()
+            .map(|edge| *edge.weight())
+    }
+    /// Get the (data, clock) signals into a `FlipFlopIn`.
+    pub fn get_flip_flop_in(&self, gate: NodeIndex) -> (Value, Value) {
+        let clock = match self.graph[gate] {
+            Gate::FlipFlopIn { clock, .. } => clock,
+            gate_type => panic!("gate {:?} should be FlipFlopIn, is {:?}", gate, gate_type),
+        };
+
+        let (mut d, mut clk) = (None, None);
+        for edge in self.graph.edges_directed(gate, Direction::Incoming) {
+            if edge.source() == clock {
+                clk = Some(*edge.weight());
+            } else {
+                d = Some(*edge.weight());
+            }
+        }
+        match (d, clk) {
+            (Some(d), Some(clk)) => (d, clk),
+            _ => panic!("gate {:?} should have a data and a clock input", gate),
+        }
+    }
+    /// Describe a node for people: its gate type, label, the values going in and out, and its
+    /// rank (if `ranks` has it), one per line.
+    pub fn describe_node(&self, node: NodeIndex, ranks: &HashMap<NodeIndex, u32>) -> String {
+        let bit = |v: Value| if v { "1" } else { "0" };
+        let gate = self.graph[node];
+
+        let mut lines = vec![match gate {
+            Gate::FlipFlopIn { .. } => "FlipFlopIn".to_string(),
+            _ => format!("{:?}", gate),
+        }];
+        if let Some(label) = self.label(node) {
+            lines[0] += &format!(" {:?}", label);
+        }
+        if let Some(rank) = ranks.get(&node) {
+            lines.push(format!("rank {}", rank));
+        }
+        match gate {
+            Gate::MetaInput => (),
+            Gate::Input | Gate::FlipFlop => {
+                lines.push(format!("value {}", bit(self.get_1_in(node))))
+            }
+            Gate::FlipFlopIn { .. } => {
+                let (d, clock) = self.get_flip_flop_in(node);
+                lines.push(format!("d {}, clock {}", bit(d), bit(clock)));
+            }
+            _ => {
+                let mut inputs = self
+                    .graph
+                    .edges_directed(node, Direction::Incoming)
+                    .map(|edge| bit(*edge.weight()))
+                    .collect::<Vec<_>>();
+                // petgraph lists the newest edge first.
+                inputs.reverse();
+                lines.push(format!("in {}", inputs.join(", ")));
+            }
+        }
+        if let Some(out) = self.get_out(node) {
+            lines.push(format!("out {}", bit(out)));
+        }
+        lines.join("\n")
+    }
     /// Compute the order to update nodes in.
     pub fn update_order(&self) -> Vec<NodeIndex> {
-        let mut result = petgraph::algo::toposort(&self.0, None).unwrap();
+        let mut result = petgraph::algo::toposort(&self.graph, None).unwrap();
         result.reverse();
         result
     }
-    /// Propagate signals a single step forward.
-    pub fn update_signals_once(&mut self, order: &[NodeIndex]) {
+    /// Propagate signals a single step forward. Returns whether any signal changed.
+    pub fn update_signals_once(&mut self, order: &[NodeIndex]) -> bool {
+        let mut changed = false;
         let mut edges = vec![];
         for gate in order {
-            let gate = *gate;
-            let gate_type = self.0[gate];
+            changed |= self.update_gate_with(*gate, &mut edges);
+        }
+        changed
+    }
+    /// How many passes of `update_signals_once` the circuit can take to settle: enough for a
+    /// signal to cross every rank, as `Challenge::check` runs, again after each flip-flop latches,
+    /// and one more to see that nothing's changing.
+    pub fn settle_limit(&self) -> usize {
+        let flip_flops = self
+            .graph
+            .raw_nodes()
+            .iter()
+            .filter(|node| node.weight == Gate::FlipFlop)
+            .count();
+        (flip_ranks(&self.ranks()).len() + 1) * (flip_flops + 1) + 1
+    }
+    /// Run `update_signals_once` until nothing changes. Panics if it's still changing after
+    /// `limit` passes (the `settle_limit`, worked out once for the circuit), as it must be
+    /// oscillating.
+    pub fn settle(&mut self, order: &[NodeIndex], limit: usize) {
+        for _ in 0..limit {
+            if !self.update_signals_once(order) {
+                return;
+            }
+        }
+        panic!(
+            "the circuit is still changing after {} passes, so it must be oscillating \
+             (is a flip-flop clocked by its own output?)",
+            limit
+        );
+    }
+    /// Update the signals a single gate drives, from the signals going into it.
+    /// Returns whether any signal (or, for a `FlipFlopIn`, the clock it remembers) changed.
+    pub fn update_gate(&mut self, gate: NodeIndex) -> bool {
+        self.update_gate_with(gate, &mut vec![])
+    }
+    /// `update_gate`, reusing `edges` as scratch space.
+    fn update_gate_with(&mut self, gate: NodeIndex, edges: &mut Vec<EdgeIndex>) -> bool {
+        let mut changed = false;
+        let gate_type = self.graph[gate];
 
-            let value = match gate_type {
-                Gate::Or => {
-                    let (a, b) = self.get_2_in(gate);
-                    a | b
-                }
-                Gate::Xor => {
-                    let (a, b) = self.get_2_in(gate);
-                    a ^ b
-                }
-                Gate::And => {
-                    let (a, b) = self.get_2_in(gate);
-                    a & b
+        let value = match gate_type {
+            Gate::Or => {
+                let (a, b) = self.get_2_in(gate);
+                a | b
+            }
+            Gate::Xor => {
+                let (a, b) = self.get_2_in(gate);
+                a ^ b
+            }
+            Gate::And => {
+                let (a, b) = self.get_2_in(gate);
+                a & b
+            }
+            Gate::Not => !self.get_1_in(gate),
+            Gate::Input | Gate::Output | Gate::FlipFlop => self.get_1_in(gate),
+            Gate::FlipFlopIn { q, clock, last_clock } => {
+                let (d, clk) = self.get_flip_flop_in(gate);
+                if clk && !last_clock {
+                    let edge = self.graph.find_edge(Circuit::meta_input(), q).unwrap();
+                    changed |= self.graph[edge] != d;
+                    self.graph[edge] = d;
                 }
-                Gate::Not => !self.get_1_in(gate),
-                Gate::Input | Gate::Output => self.get_1_in(gate),
-                Gate::MetaInput => continue,
-            };
-
-            edges.extend(
-                self.0
-                    .edges_directed(gate, Direction::Outgoing)
-                    .map(|e| e.id()),
-            );
-            for edge in &edges {
-                let w = &mut self.0[*edge];
-                *w = value;
+                changed |= clk != last_clock;
+                self.graph[gate] = Gate::FlipFlopIn {
+                    q,
+                    clock,
+                    last_clock: clk,
+                };
+                return changed;
             }
-            edges.clear();
+            Gate::MetaInput => return false,
+        };
+
+        edges.extend(
+            self.graph
+                .edges_directed(gate, Direction::Outgoing)
+                .map(|e| e.id()),
+        );
+        for edge in edges.iter() {
+            let w = &mut self.graph[*edge];
+            changed |= *w != value;
+            *w = value;
+        }
+        edges.clear();
+        changed
+    }
+
+    /// Set some inputs, then run the simulation until it settles.
+    /// Returns the values of all outputs. Panics if it never settles.
+    pub fn evaluate(&mut self, inputs: &[(NodeIndex, Value)]) -> HashMap<NodeIndex, Value> {
+        for (input, value) in inputs {
+            self.set_input(*input, *value);
         }
+        let order = self.update_order();
+        let limit = self.settle_limit();
+        self.settle(&order, limit);
+
+        self.graph
+            .node_indices()
+            .filter(|node| self.graph[*node] == Gate::Output)
+            .map(|node| (node, self.get_1_in(node)))
+            .collect()
     }
 
     /// Build a half adder. Returns nodes (sum, carry).
@@ -242,144 +752,851 @@ impl Circuit {
         a: &[NodeIndex],
         b: &[NodeIndex],
     ) -> (Vec<NodeIndex>, NodeIndex) {
+        let (out, carries) = self.ripple_carry_chain(a, b);
+        (out, *carries.last().unwrap())
+    }
+    /// Build a ripple-carry adder which also flags two's-complement overflow.
+    /// Returns (sum bits, final carry bit, overflow bit). Inputs must be at least 2 bits wide.
+    pub fn ripple_carry_overflow(
+        &mut self,
+        a: &[NodeIndex],
+        b: &[NodeIndex],
+    ) -> (Vec<NodeIndex>, NodeIndex, NodeIndex) {
+        assert!(a.len() >= 2);
+        let (out, carries) = self.ripple_carry_chain(a, b);
+        let overflow = self.signed_overflow(&carries);
+        (out, *carries.last().unwrap(), overflow)
+    }
+    /// Build the chain of adders for `ripple_carry`. Returns the sum bits and the carry out of
+    /// each bit.
+    fn ripple_carry_chain(
+        &mut self,
+        a: &[NodeIndex],
+        b: &[NodeIndex],
+    ) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
         assert_eq!(a.len(), b.len());
 
         let (s0, c0) = self.half_adder(a[0], b[0]);
         let mut out = vec![s0];
-        let mut c = c0;
+        let mut carries = vec![c0];
 
         for i in 1..a.len() {
-            let (si, ci) = self.full_adder(a[i], b[i], c);
+            let (si, ci) = self.full_adder(a[i], b[i], carries[i - 1]);
             out.push(si);
-            c = ci;
+            carries.push(ci);
         }
         assert_eq!(a.len(), out.len());
-        (out, c)
+        (out, carries)
     }
-}
 
-/// Given a hash table mapping nodes to their rank in the circuit,
-/// return a vector of ranks, where each rank is a vector of the nodes in that rank.
-pub fn flip_ranks(ranks: &HashMap<NodeIndex, u32>) -> Vec<Vec<NodeIndex>> {
-    let n = ranks.values().max().unwrap();
-    let mut result = vec![];
-    for _ in 0..n + 1 {
-        result.push(vec![]);
+    /// Build a ripple-carry subtractor computing `a - b`, as `a + !b + 1`.
+    /// Returns a vector of difference bits and the final carry bit, which is 0 if a borrow was
+    /// needed, i.e. if `a < b` as unsigned numbers.
+    /// Bits are ordered by magnitude, as in `ripple_carry`. `b` may be narrower than `a`, in which
+    /// case its missing top bits are taken to be 0.
+    pub fn subtractor(&mut self, a: &[NodeIndex], b: &[NodeIndex]) -> (Vec<NodeIndex>, NodeIndex) {
+        let (out, carries) = self.subtractor_chain(a, b);
+        (out, *carries.last().unwrap())
     }
-    for (n, rank) in ranks {
-        result[*rank as usize].push(*n)
+    /// Build a ripple-carry subtractor which also flags two's-complement overflow.
+    /// Returns (difference bits, final carry bit, overflow bit). Inputs must be at least 2 bits wide.
+    pub fn subtractor_overflow(
+        &mut self,
+        a: &[NodeIndex],
+        b: &[NodeIndex],
+    ) -> (Vec<NodeIndex>, NodeIndex, NodeIndex) {
+        assert!(a.len() >= 2);
+        let (out, carries) = self.subtractor_chain(a, b);
+        let overflow = self.signed_overflow(&carries);
+        (out, *carries.last().unwrap(), overflow)
     }
-    for rank in &mut result {
-        rank.sort_by_key(|n| n.index());
+    /// Build the chain of adders for `subtractor`. Returns the difference bits and the carry out of
+    /// each bit.
+    fn subtractor_chain(
+        &mut self,
+        a: &[NodeIndex],
+        b: &[NodeIndex],
+    ) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
+        assert!(b.len() <= a.len() && !b.is_empty());
+
+        // the first bit has a carry in of 1, which simplifies to:
+        // s0 = a0 ^ !b0 ^ 1 = a0 ^ b0
+        // c0 = (a0 & !b0) | (a0 ^ !b0) = a0 | !b0
+        let nb0 = self.add_not(b[0]);
+        let s0 = self.add_xor(a[0], b[0]);
+        let c0 = self.add_or(a[0], nb0);
+        let mut out = vec![s0];
+        let mut carries = vec![c0];
+
+        for i in 1..b.len() {
+            let nbi = self.add_not(b[i]);
+            let (si, ci) = self.full_adder(a[i], nbi, carries[i - 1]);
+            out.push(si);
+            carries.push(ci);
+        }
+        // past the end of b, we're adding !0 = 1:
+        // si = ai ^ 1 ^ c = !(ai ^ c)
+        // ci = (ai & 1) | (c & (ai ^ 1)) = ai | c
+        for i in b.len()..a.len() {
+            let x = self.add_xor(a[i], carries[i - 1]);
+            let si = self.add_not(x);
+            let ci = self.add_or(a[i], carries[i - 1]);
+            out.push(si);
+            carries.push(ci);
+        }
+        (out, carries)
+    }
+    /// Signed overflow happened iff the carry into the top bit differs from the carry out of it.
+    fn signed_overflow(&mut self, carries: &[NodeIndex]) -> NodeIndex {
+        let n = carries.len();
+        self.add_xor(carries[n - 2], carries[n - 1])
     }
-    result
-}
 
-pub fn get_bit(v: usize, b: usize) -> bool {
-    ((v >> b) & 1) == 1
-}
-pub fn set_bit(v: usize, b: usize, on: bool) -> usize {
-    if on {
-        v | (1 << b)
-    } else {
-        v
+    /// Sign-extend a two's-complement number to `to_width` bits, by repeating its top bit.
+    /// No gates are needed, so this just returns the nodes to use for each bit.
+    pub fn sign_extend(&mut self, bits: &[NodeIndex], to_width: usize) -> Vec<NodeIndex> {
+        assert!(!bits.is_empty() && to_width >= bits.len());
+        let sign = bits[bits.len() - 1];
+        let mut result = bits.to_vec();
+        result.resize(to_width, sign);
+        result
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Build a comparator for two's-complement numbers of the same width (at least 2 bits).
+    /// Returns nodes (a < b, a == b).
+    pub fn signed_compare(&mut self, a: &[NodeIndex], b: &[NodeIndex]) -> (NodeIndex, NodeIndex) {
+        let (diff, _, overflow) = self.subtractor_overflow(a, b);
+        // a - b is negative, unless it overflowed.
+        let lt = self.add_xor(diff[diff.len() - 1], overflow);
 
-    #[test]
-    fn test_simple_circuit() {
-        let mut circuit = Circuit::new();
-        let a = circuit.add_input();
-        let b = circuit.add_input();
-        let x = circuit.add_xor(a, b);
-        let out = circuit.add_output(x);
-        circuit.set_input(a, true);
+        let any = self.any(&diff);
+        let eq = self.add_not(any);
+        (lt, eq)
+    }
 
-        let order = circuit.update_order();
-        for _ in 0..5 {
-            circuit.update_signals_once(&order);
+    /// Build a tree of `Or` gates, which is on if any of `bits` are.
+    pub fn any(&mut self, bits: &[NodeIndex]) -> NodeIndex {
+        assert!(!bits.is_empty());
+        let mut bits = bits.to_vec();
+        while bits.len() > 1 {
+            let next = bits
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => self.add_or(*a, *b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            bits = next;
         }
-
-        assert_eq!(circuit.get_1_in(out), true);
-
-        let ranks = circuit.ranks();
-
-        let flipped = flip_ranks(&ranks);
-        assert_eq!(&flipped[0], &[Circuit::meta_input()]);
-        assert_eq!(&flipped[1], &[a, b]);
-        assert_eq!(&flipped[2], &[x]);
-        assert_eq!(&flipped[3], &[out]);
+        bits[0]
     }
 
-    #[test]
-    fn test_full_adder() {
-        let mut circuit = Circuit::new();
-        let a = circuit.add_input();
-        let b = circuit.add_input();
-        let c_in = circuit.add_input();
+    /// Build a multiplexer over two equally wide buses.
+    /// Returns nodes carrying `if_true` when `select` is on, and `if_false` otherwise.
+    pub fn mux(
+        &mut self,
+        select: NodeIndex,
+        if_false: &[NodeIndex],
+        if_true: &[NodeIndex],
+    ) -> Vec<NodeIndex> {
+        assert_eq!(if_false.len(), if_true.len());
+        let not_select = self.add_not(select);
+        if_false
+            .iter()
+            .zip(if_true)
+            .map(|(f, t)| {
+                let f = self.add_and(*f, not_select);
+                let t = self.add_and(*t, select);
+                self.add_or(f, t)
+            })
+            .collect()
+    }
 
-        // S = A ⊕ B ⊕ Cin
-        // Cout = (A ⋅ B) + (Cin ⋅ (A ⊕ B)).
-        let (s, c_out) = circuit.full_adder(a, b, c_in);
-        let s = circuit.add_output(s);
-        let c_out = circuit.add_output(c_out);
+    /// Build a multiplexer choosing one of `buses` (all equally wide) by the unsigned number on
+    /// `select`. There must be `2^select.len()` buses.
+    pub fn mux_tree(&mut self, select: &[NodeIndex], buses: &[Vec<NodeIndex>]) -> Vec<NodeIndex> {
+        assert_eq!(buses.len(), 1 << select.len());
+        let mut buses = buses.to_vec();
+        for s in select {
+            buses = buses
+                .chunks(2)
+                .map(|pair| self.mux(*s, &pair[0], &pair[1]))
+                .collect();
+        }
+        buses.pop().unwrap()
+    }
 
-        let order = circuit.update_order();
+    /// Build a decoder, turning the unsigned number on `select` into one-hot lines.
+    /// Returns `2^select.len()` nodes; line `i` is on when `select` holds `i`.
+    pub fn decoder(&mut self, select: &[NodeIndex]) -> Vec<NodeIndex> {
+        assert!(!select.is_empty(), "decoder needs at least 1 select bit");
+        let not0 = self.add_not(select[0]);
+        let mut lines = vec![not0, select[0]];
+        for s in &select[1..] {
+            let not_s = self.add_not(*s);
+            let low = lines
+                .iter()
+                .map(|line| self.add_and(*line, not_s))
+                .collect::<Vec<_>>();
+            let high = lines
+                .iter()
+                .map(|line| self.add_and(*line, *s))
+                .collect::<Vec<_>>();
+            lines = low.into_iter().chain(high).collect();
+        }
+        lines
+    }
 
-        for a_ in [false, true].iter() {
-            for b_ in [false, true].iter() {
-                for c_in_ in [false, true].iter() {
-                    let (a_, b_, c_in_) = (*a_, *b_, *c_in_);
-                    circuit.set_input(a, a_);
-                    circuit.set_input(b, b_);
-                    circuit.set_input(c_in, c_in_);
-                    for _ in 0..32 {
-                        circuit.update_signals_once(&order);
+    /// Build a barrel shifter, shifting `bits` right (towards bit 0) by the unsigned number `amount`.
+    /// Bits shifted in at the top are 0.
+    pub fn shift_right(&mut self, bits: &[NodeIndex], amount: &[NodeIndex]) -> Vec<NodeIndex> {
+        let mut bits = bits.to_vec();
+        for (k, select) in amount.iter().enumerate() {
+            bits = self.shift_stage(&bits, *select, 1 << k, false);
+        }
+        bits
+    }
+    /// Build a barrel shifter, shifting `bits` left (away from bit 0) by the unsigned number `amount`.
+    /// Bits shifted in at the bottom are 0.
+    pub fn shift_left(&mut self, bits: &[NodeIndex], amount: &[NodeIndex]) -> Vec<NodeIndex> {
+        let mut bits = bits.to_vec();
+        for (k, select) in amount.iter().enumerate() {
+            bits = self.shift_stage(&bits, *select, 1 << k, true);
+        }
+        bits
+    }
+    /// One stage of a barrel shifter: shift by `distance` if `select` is on.
+    fn shift_stage(
+        &mut self,
+        bits: &[NodeIndex],
+        select: NodeIndex,
+        distance: usize,
+        left: bool,
+    ) -> Vec<NodeIndex> {
+        let not_select = self.add_not(select);
+        (0..bits.len())
+            .map(|i| {
+                let from = if left {
+                    i.checked_sub(distance)
+                } else {
+                    Some(i + distance).filter(|j| *j < bits.len())
+                };
+                let kept = self.add_and(bits[i], not_select);
+                match from {
+                    Some(j) => {
+                        let shifted = self.add_and(bits[j], select);
+                        self.add_or(kept, shifted)
                     }
-                    assert_eq!(circuit.get_1_in(s), a_ ^ b_ ^ c_in_);
-                    assert_eq!(circuit.get_1_in(c_out), (a_ & b_) | (c_in_ & (a_ ^ b_)));
+                    None => kept,
                 }
-            }
+            })
+            .collect()
+    }
+
+    /// Shift `bits` left until the top bit is on. Returns the shifted bits, and the distance they
+    /// were shifted as an unsigned number. If `bits` are all 0, so is the result.
+    pub fn normalize(&mut self, bits: &[NodeIndex]) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
+        let n = bits.len();
+        let mut stages = 0;
+        while (1 << stages) < n {
+            stages += 1;
+        }
+
+        // try the largest shifts first: shift by 2^k if the top 2^k bits are all 0.
+        let mut bits = bits.to_vec();
+        let mut distance = vec![];
+        for k in (0..stages).rev() {
+            let any = self.any(&bits[n - (1 << k)..]);
+            let select = self.add_not(any);
+            bits = self.shift_stage(&bits, select, 1 << k, true);
+            distance.push(select);
         }
+        distance.reverse();
+        (bits, distance)
     }
 
-    #[test]
-    fn test_ripple_carry() {
-        let mut circuit = Circuit::new();
-        let n: usize = 4;
-        let a = (0..n)
-            .into_iter()
-            .map(|_| circuit.add_input())
-            .collect::<Vec<_>>();
-        let b = (0..n)
-            .into_iter()
-            .map(|_| circuit.add_input())
-            .collect::<Vec<_>>();
-        let (s, c) = circuit.ripple_carry(&a, &b);
-        let c = circuit.add_output(c);
-        let s = s
-            .into_iter()
-            .map(|si| circuit.add_output(si))
-            .collect::<Vec<_>>();
+    /// Build an adder for tiny floating-point numbers with `exp_bits` of exponent, e.g. 8-bit
+    /// numbers with 1 sign bit, 4 exponent bits, and 3 mantissa bits.
+    /// Numbers are laid out like IEEE 754, with bits ordered by magnitude: mantissa bits, then
+    /// exponent bits, then the sign bit. The mantissa has an implicit leading 1, so with a
+    /// bias of `2^(exp_bits - 1) - 1` the value is `(-1)^sign * 1.mantissa * 2^(exponent - bias)`.
+    ///
+    /// To keep the circuit readable, it's simpler than IEEE 754: an exponent of 0 means zero (there
+    /// are no subnormals); there are no infinities or NaNs, results that are too large saturate to
+    /// the largest finite number, and results that are too small flush to zero; and bits that are
+    /// shifted out are truncated rather than rounded.
+    pub fn float_adder(&mut self, a: &[NodeIndex], b: &[NodeIndex], exp_bits: usize) -> FloatAdder {
+        assert_eq!(a.len(), b.len());
+        assert!(exp_bits >= 2 && a.len() >= exp_bits + 2);
+        let width = a.len();
+        let m = width - exp_bits - 1;
 
-        let ranks = circuit.ranks();
-        let steps = flip_ranks(&ranks).len() + 1;
+        // 1. order the operands by magnitude; bit patterns without the sign compare the same as
+        // the numbers they represent.
+        let (_, a_ge_b) = self.subtractor(&a[..width - 1], &b[..width - 1]);
+        let big = self.mux(a_ge_b, b, a);
+        let small = self.mux(a_ge_b, a, b);
+        let (big_e, small_e) = (&big[m..width - 1], &small[m..width - 1]);
+        let (big_s, small_s) = (big[width - 1], small[width - 1]);
+        let big_sig = self.significand(&big[..m], big_e);
+        let small_sig = self.significand(&small[..m], small_e);
 
-        let order = circuit.update_order();
+        // 2. line up the binary points, by shifting the smaller significand right by the
+        // difference in exponents.
+        let (shift, _) = self.subtractor(big_e, small_e);
+        let aligned = self.shift_right(&small_sig, &shift);
 
-        for a_ in 0..(2usize).pow(n as u32) {
-            for b_ in 0..(2usize).pow(n as u32) {
-                for i in 0..n {
-                    circuit.set_input(a[i], get_bit(a_, i));
-                    circuit.set_input(b[i], get_bit(b_, i));
-                }
-                for _ in 0..steps {
-                    circuit.update_signals_once(&order);
-                }
+        // 3. add the significands if the signs match, otherwise subtract. this needs one more bit.
+        let subtract = self.add_xor(big_s, small_s);
+        let (sum, carry) = self.ripple_carry(&big_sig, &aligned);
+        let (diff, _) = self.subtractor(&big_sig, &aligned);
+        let mut significand = self.mux(subtract, &sum, &diff);
+        let not_subtract = self.add_not(subtract);
+        let top = self.add_and(carry, not_subtract);
+        significand.push(top);
+
+        // 4. normalize, so the leading 1 is on top. the sum's binary point is one bit further left
+        // than the operands', so the exponent is `big_e + 1 - distance`.
+        let (normalized, normalize_shift) = self.normalize(&significand);
+        assert!(
+            normalize_shift.len() <= exp_bits + 1,
+            "mantissa is too wide for the exponent"
+        );
+        let (mut exponent, carry) = self.incrementer(big_e);
+        exponent.push(carry);
+        let (exponent, no_underflow) = self.subtractor(&exponent, &normalize_shift);
+
+        // 5. pack the result, flushing to zero or saturating if necessary.
+        let exponent_nonzero = self.any(&exponent);
+        let in_range = self.add_and(no_underflow, exponent_nonzero);
+        let nonzero = self.add_and(normalized[m + 1], in_range);
+        let overflow = self.add_and(exponent[exp_bits], no_underflow);
+
+        let mut result = vec![];
+        for bit in normalized[1..m + 1].iter().chain(&exponent[..exp_bits]) {
+            let saturated = self.add_or(*bit, overflow);
+            result.push(self.add_and(saturated, nonzero));
+        }
+        result.push(self.add_and(big_s, nonzero));
+
+        FloatAdder {
+            shift,
+            big: big_sig,
+            aligned,
+            significand,
+            normalize_shift,
+            sum: result,
+        }
+    }
+    /// The significand of a float: its mantissa with the implicit leading 1, or 0 if its exponent is 0.
+    fn significand(&mut self, mantissa: &[NodeIndex], exponent: &[NodeIndex]) -> Vec<NodeIndex> {
+        let nonzero = self.any(exponent);
+        let mut result = mantissa
+            .iter()
+            .map(|bit| self.add_and(*bit, nonzero))
+            .collect::<Vec<_>>();
+        result.push(nonzero);
+        result
+    }
+
+    /// Sum up columns of bits, where `columns[i]` holds bits of weight `2**i`.
+    /// Each column may hold at most 2 bits; carries are rippled up through the columns.
+    /// Returns one output bit per column, plus a final carry bit if one is produced.
+    fn carry_propagate(&mut self, columns: &[Vec<NodeIndex>]) -> Vec<NodeIndex> {
+        let mut out = vec![];
+        let mut carry = None;
+        for column in columns {
+            assert!(column.len() <= 2, "column has too many bits");
+            let mut bits = column.clone();
+            bits.extend(carry.take());
+            match bits[..] {
+                [] => panic!("empty column"),
+                [a] => out.push(a),
+                [a, b] => {
+                    let (s, c) = self.half_adder(a, b);
+                    out.push(s);
+                    carry = Some(c);
+                }
+                [a, b, c_in] => {
+                    let (s, c) = self.full_adder(a, b, c_in);
+                    out.push(s);
+                    carry = Some(c);
+                }
+                _ => unreachable!(),
+            }
+        }
+        out.extend(carry);
+        out
+    }
+
+    /// Build the partial products of `a * b`, sorted into columns by weight.
+    fn partial_products(&mut self, a: &[NodeIndex], b: &[NodeIndex]) -> Vec<Vec<NodeIndex>> {
+        let mut columns = vec![vec![]; a.len() + b.len() - 1];
+        for (j, bj) in b.iter().enumerate() {
+            for (i, ai) in a.iter().enumerate() {
+                let p = self.add_and(*ai, *bj);
+                columns[i + j].push(p);
+            }
+        }
+        columns
+    }
+
+    /// Build an arithmetic logic unit over two equally wide buses, performing the `AluOp` whose
+    /// opcode is on `op` (low bit first). Every operation is computed all the time, and
+    /// multiplexers pick the result.
+    pub fn alu(&mut self, a: &[NodeIndex], b: &[NodeIndex], op: [NodeIndex; 2]) -> Alu {
+        assert_eq!(a.len(), b.len());
+        let mut units = vec![];
+        let mut unit_outputs = vec![];
+        for unit in AluOp::ALL.iter() {
+            let start = self.graph.node_count();
+            let outputs = match unit {
+                AluOp::Add => {
+                    let (mut sum, carry) = self.ripple_carry(a, b);
+                    sum.push(carry);
+                    sum
+                }
+                AluOp::Sub => {
+                    let (mut difference, carry) = self.subtractor(a, b);
+                    difference.push(carry);
+                    difference
+                }
+                AluOp::And => a.iter().zip(b).map(|(a, b)| self.add_and(*a, *b)).collect(),
+                AluOp::Or => a.iter().zip(b).map(|(a, b)| self.add_or(*a, *b)).collect(),
+            };
+            units.push((start..self.graph.node_count()).map(NodeIndex::new).collect());
+            unit_outputs.push(outputs);
+        }
+
+        let start = self.graph.node_count();
+        // arithmetic results carry their carry along with them.
+        let arithmetic = self.mux(op[0], &unit_outputs[0], &unit_outputs[1]);
+        let logic = self.mux(op[0], &unit_outputs[2], &unit_outputs[3]);
+        let (arithmetic, carry) = arithmetic.split_at(a.len());
+        let result = self.mux(op[1], arithmetic, &logic);
+        let not_logic = self.add_not(op[1]);
+        let carry = self.add_and(carry[0], not_logic);
+        let nonzero = self.any(&result);
+        let zero = self.add_not(nonzero);
+        let select = (start..self.graph.node_count()).map(NodeIndex::new).collect();
+
+        Alu {
+            result,
+            carry,
+            zero,
+            units,
+            select,
+        }
+    }
+
+    /// Build an array multiplier: each row of partial products is added onto the running sum
+    /// with a ripple-carry adder.
+    /// Returns `a.len() + b.len()` product bits, ordered by magnitude.
+    /// Both inputs should be at least 2 bits wide.
+    pub fn array_multiplier(&mut self, a: &[NodeIndex], b: &[NodeIndex]) -> Vec<NodeIndex> {
+        assert!(a.len() >= 2 && b.len() >= 2);
+
+        let mut product = vec![];
+        // running sum of the rows added so far, shifted down to the weight of the current row.
+        let mut acc: Vec<NodeIndex> = vec![];
+        for bj in b {
+            let row = a.iter().map(|ai| self.add_and(*ai, *bj)).collect::<Vec<_>>();
+            let mut columns = vec![vec![]; row.len().max(acc.len())];
+            for (i, bit) in acc.iter().enumerate() {
+                columns[i].push(*bit);
+            }
+            for (i, bit) in row.iter().enumerate() {
+                columns[i].push(*bit);
+            }
+            acc = self.carry_propagate(&columns);
+            // the lowest bit can't be affected by any later row.
+            product.push(acc.remove(0));
+        }
+        product.extend(acc);
+        assert_eq!(product.len(), a.len() + b.len());
+        product
+    }
+
+    /// Build a Wallace-tree multiplier: partial products are reduced with layers of carry-save
+    /// (full and half) adders until at most 2 bits are left per column, then summed with a
+    /// single ripple-carry adder.
+    /// Returns `a.len() + b.len()` product bits, ordered by magnitude.
+    /// Both inputs should be at least 2 bits wide.
+    pub fn wallace_multiplier(&mut self, a: &[NodeIndex], b: &[NodeIndex]) -> Vec<NodeIndex> {
+        assert!(a.len() >= 2 && b.len() >= 2);
+
+        let columns = self.partial_products(a, b);
+        let columns = self.reduce_columns(columns);
+        let mut product = self.carry_propagate(&columns);
+        // any bits above `a.len() + b.len()` are always 0.
+        product.truncate(a.len() + b.len());
+        assert_eq!(product.len(), a.len() + b.len());
+        product
+    }
+
+    /// Build a circuit counting how many of `bits` are on.
+    /// Returns the count, with just enough bits to hold `bits.len()`, ordered by magnitude.
+    pub fn popcount(&mut self, bits: &[NodeIndex]) -> Vec<NodeIndex> {
+        assert!(!bits.is_empty());
+        let columns = self.reduce_columns(vec![bits.to_vec()]);
+        let mut count = self.carry_propagate(&columns);
+        let width = (usize::BITS - bits.len().leading_zeros()) as usize;
+        count.truncate(width);
+        count
+    }
+
+    /// Wallace tree reduction: combine bits within columns, where `columns[i]` holds bits of
+    /// weight `2**i`, with full and half adders until at most 2 bits are left per column.
+    fn reduce_columns(&mut self, mut columns: Vec<Vec<NodeIndex>>) -> Vec<Vec<NodeIndex>> {
+        while columns.iter().any(|c| c.len() > 2) {
+            let mut next = vec![vec![]; columns.len() + 1];
+            for (i, column) in columns.iter().enumerate() {
+                let mut chunks = column.chunks_exact(3);
+                for chunk in &mut chunks {
+                    let (s, c) = self.full_adder(chunk[0], chunk[1], chunk[2]);
+                    next[i].push(s);
+                    next[i + 1].push(c);
+                }
+                match chunks.remainder() {
+                    &[a, b] => {
+                        let (s, c) = self.half_adder(a, b);
+                        next[i].push(s);
+                        next[i + 1].push(c);
+                    }
+                    rest => next[i].extend_from_slice(rest),
+                }
+            }
+            while matches!(next.last(), Some(c) if c.is_empty()) {
+                next.pop();
+            }
+            columns = next;
+        }
+        columns
+    }
+
+    /// Build a Fibonacci linear-feedback shift register, shifting once per rising edge of `clock`.
+    ///
+    /// The register is `max(taps) + 1` bits wide, initialized from the bits of `seed`.
+    /// On each clock, bit `i` moves to bit `i + 1`, and bit 0 becomes the xor of the bits at `taps`.
+    /// Returns the flip-flops holding the register, ordered by bit; the last one is the output bit.
+    pub fn lfsr(&mut self, taps: &[usize], seed: usize, clock: NodeIndex) -> Vec<NodeIndex> {
+        let width = *taps.iter().max().expect("lfsr needs at least 1 tap") + 1;
+        for (i, t) in taps.iter().enumerate() {
+            assert!(!taps[..i].contains(t), "duplicate tap {}", t);
+        }
+
+        let q = (0..width)
+            .map(|i| self.add_flip_flop(get_bit(seed, i)))
+            .collect::<Vec<_>>();
+
+        let mut feedback = q[taps[0]];
+        for t in &taps[1..] {
+            feedback = self.add_xor(feedback, q[*t]);
+        }
+
+        self.connect_flip_flop(q[0], feedback, clock);
+        for i in 1..width {
+            self.connect_flip_flop(q[i], q[i - 1], clock);
+        }
+        q
+    }
+
+    /// Build a synchronous binary counter, counting up by 1 per rising edge of `clock` and wrapping
+    /// around to 0. Returns the flip-flops holding the count, least significant bit first.
+    pub fn counter(&mut self, width: usize, clock: NodeIndex) -> Vec<NodeIndex> {
+        assert!(width > 0, "counter needs at least 1 bit");
+        let q = (0..width)
+            .map(|_| self.add_flip_flop(false))
+            .collect::<Vec<_>>();
+
+        let (d, _) = self.incrementer(&q);
+        for (q, d) in q.iter().zip(d) {
+            self.connect_flip_flop(*q, d, clock);
+        }
+        q
+    }
+
+    /// Build a circuit adding 1 to the unsigned number on `bits`.
+    /// Returns the sum bits, as many as it was given and wrapping around to 0, and the carry out.
+    pub fn incrementer(&mut self, bits: &[NodeIndex]) -> (Vec<NodeIndex>, NodeIndex) {
+        assert!(!bits.is_empty(), "incrementer needs at least 1 bit");
+        // bit i flips when all the bits below it are set.
+        let mut result = vec![self.add_not(bits[0])];
+        let mut carry = bits[0];
+        for bit in &bits[1..] {
+            let (sum, c) = self.half_adder(*bit, carry);
+            result.push(sum);
+            carry = c;
+        }
+        (result, carry)
+    }
+
+    /// Connect the flip-flops `q` as a register: on rising edges of `clock`, they load `d` if
+    /// `load` is on, and keep their value otherwise.
+    pub fn connect_register(
+        &mut self,
+        q: &[NodeIndex],
+        d: &[NodeIndex],
+        load: NodeIndex,
+        clock: NodeIndex,
+    ) {
+        let next = self.mux(load, q, d);
+        for (q, next) in q.iter().zip(next) {
+            self.connect_flip_flop(*q, next, clock);
+        }
+    }
+}
+
+/// The state of a circuit's simulation, from `Circuit::snapshot`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimState {
+    signals: Vec<Value>,
+    last_clocks: Vec<(NodeIndex, Value)>,
+}
+
+/// Structural differences between two circuits, as computed by `Circuit::diff`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitDiff {
+    /// Gates only in the second circuit.
+    pub added: Vec<GateId>,
+    /// Gates only in the first circuit.
+    pub removed: Vec<GateId>,
+    /// Gates in both circuits, which do different things: (gate, before, after).
+    pub retyped: Vec<(GateId, Gate, Gate)>,
+    /// (source, target) pairs of wires only in the second circuit, once for each extra wire.
+    pub added_edges: Vec<(GateId, GateId)>,
+    /// (source, target) pairs of wires only in the first circuit, once for each extra wire.
+    pub removed_edges: Vec<(GateId, GateId)>,
+}
+
+impl CircuitDiff {
+    /// Whether the circuits have the same structure.
+    pub fn is_empty(&self) -> bool {
+        self == &CircuitDiff::default()
+    }
+
+    /// Gates whose inputs changed: the targets of added or removed wires which are in both circuits.
+    pub fn rewired(&self) -> Vec<GateId> {
+        let mut result = self
+            .added_edges
+            .iter()
+            .chain(&self.removed_edges)
+            .map(|(_, target)| *target)
+            .filter(|target| !self.added.contains(target) && !self.removed.contains(target))
+            .collect::<Vec<_>>();
+        result.sort();
+        result.dedup();
+        result
+    }
+}
+
+/// The interesting intermediate buses of a circuit built by `Circuit::float_adder`.
+/// All buses have their bits ordered by magnitude.
+pub struct FloatAdder {
+    /// Difference between the operands' exponents.
+    pub shift: Vec<NodeIndex>,
+    /// Significand of the operand with the larger magnitude.
+    pub big: Vec<NodeIndex>,
+    /// Significand of the operand with the smaller magnitude, shifted right by `shift`.
+    pub aligned: Vec<NodeIndex>,
+    /// Sum or difference of `big` and `aligned`, with one extra bit on top.
+    pub significand: Vec<NodeIndex>,
+    /// How far `significand` had to be shifted left to normalize it.
+    pub normalize_shift: Vec<NodeIndex>,
+    /// The result, laid out like the inputs.
+    pub sum: Vec<NodeIndex>,
+}
+
+/// The operations of a circuit built by `Circuit::alu`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AluOp {
+    Add = 0,
+    Sub = 1,
+    And = 2,
+    Or = 3,
+}
+
+impl AluOp {
+    /// Every operation, in opcode order.
+    pub const ALL: [AluOp; 4] = [AluOp::Add, AluOp::Sub, AluOp::And, AluOp::Or];
+
+    pub fn opcode(self) -> usize {
+        self as usize
+    }
+}
+
+/// The outputs and parts of a circuit built by `Circuit::alu`.
+pub struct Alu {
+    /// The result, with bits ordered by magnitude.
+    pub result: Vec<NodeIndex>,
+    /// The adder's or subtractor's carry out; always 0 for logic operations.
+    /// As with `subtractor`, subtracting sets it when there was *no* borrow.
+    pub carry: NodeIndex,
+    /// Whether the result is 0.
+    pub zero: NodeIndex,
+    /// The gates computing each operation, indexed by opcode.
+    pub units: Vec<Vec<NodeIndex>>,
+    /// The gates choosing between the operations, and computing the flags.
+    pub select: Vec<NodeIndex>,
+}
+
+/// Given a hash table mapping nodes to their rank in the circuit,
+/// return a vector of ranks, where each rank is a vector of the nodes in that rank.
+pub fn flip_ranks(ranks: &HashMap<NodeIndex, u32>) -> Vec<Vec<NodeIndex>> {
+    let n = ranks.values().max().unwrap();
+    let mut result = vec![];
+    for _ in 0..n + 1 {
+        result.push(vec![]);
+    }
+    for (n, rank) in ranks {
+        result[*rank as usize].push(*n)
+    }
+    for rank in &mut result {
+        rank.sort_by_key(|n| n.index());
+    }
+    result
+}
+
+/// How to show the value on a bus.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BusDisplay {
+    Binary,
+    Decimal,
+    Hex,
+}
+
+impl Default for BusDisplay {
+    fn default() -> Self {
+        BusDisplay::Decimal
+    }
+}
+
+impl BusDisplay {
+    /// The next way of showing values, for cycling through them with a key.
+    pub fn next(self) -> BusDisplay {
+        match self {
+            BusDisplay::Binary => BusDisplay::Decimal,
+            BusDisplay::Decimal => BusDisplay::Hex,
+            BusDisplay::Hex => BusDisplay::Binary,
+        }
+    }
+
+    /// Format the value of a bus `width` bits wide. Binary and hex show every digit of the bus.
+    pub fn format(self, value: usize, width: usize) -> String {
+        match self {
+            BusDisplay::Binary => format!("0b{:0w$b}", value, w = width),
+            BusDisplay::Decimal => format!("{}", value),
+            BusDisplay::Hex => format!("0x{:0w$x}", value, w = (width + 3) / 4),
+        }
+    }
+}
+
+pub fn get_bit(v: usize, b: usize) -> bool {
+    ((v >> b) & 1) == 1
+}
+pub fn set_bit(v: usize, b: usize, on: bool) -> usize {
+    if on {
+        v | (1 << b)
+    } else {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_circuit() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let x = circuit.add_xor(a, b);
+        let out = circuit.add_output(x);
+        circuit.set_input(a, true);
+
+        let order = circuit.update_order();
+        for _ in 0..5 {
+            circuit.update_signals_once(&order);
+        }
+
+        assert_eq!(circuit.get_1_in(out), true);
+
+        let ranks = circuit.ranks();
+
+        let flipped = flip_ranks(&ranks);
+        assert_eq!(&flipped[0], &[Circuit::meta_input()]);
+        assert_eq!(&flipped[1], &[a, b]);
+        assert_eq!(&flipped[2], &[x]);
+        assert_eq!(&flipped[3], &[out]);
+    }
+
+    #[test]
+    fn test_full_adder() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let c_in = circuit.add_input();
+
+        // S = A ⊕ B ⊕ Cin
+        // Cout = (A ⋅ B) + (Cin ⋅ (A ⊕ B)).
+        let (s, c_out) = circuit.full_adder(a, b, c_in);
+        let s = circuit.add_output(s);
+        let c_out = circuit.add_output(c_out);
+
+        let order = circuit.update_order();
+
+        for a_ in [false, true].iter() {
+            for b_ in [false, true].iter() {
+                for c_in_ in [false, true].iter() {
+                    let (a_, b_, c_in_) = (*a_, *b_, *c_in_);
+                    circuit.set_input(a, a_);
+                    circuit.set_input(b, b_);
+                    circuit.set_input(c_in, c_in_);
+                    for _ in 0..32 {
+                        circuit.update_signals_once(&order);
+                    }
+                    assert_eq!(circuit.get_1_in(s), a_ ^ b_ ^ c_in_);
+                    assert_eq!(circuit.get_1_in(c_out), (a_ & b_) | (c_in_ & (a_ ^ b_)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ripple_carry() {
+        let mut circuit = Circuit::new();
+        let n: usize = 4;
+        let a = (0..n)
+            .into_iter()
+            .map(|_| circuit.add_input())
+            .collect::<Vec<_>>();
+        let b = (0..n)
+            .into_iter()
+            .map(|_| circuit.add_input())
+            .collect::<Vec<_>>();
+        let (s, c) = circuit.ripple_carry(&a, &b);
+        let c = circuit.add_output(c);
+        let s = s
+            .into_iter()
+            .map(|si| circuit.add_output(si))
+            .collect::<Vec<_>>();
+
+        let ranks = circuit.ranks();
+        let steps = flip_ranks(&ranks).len() + 1;
+
+        let order = circuit.update_order();
+
+        for a_ in 0..(2usize).pow(n as u32) {
+            for b_ in 0..(2usize).pow(n as u32) {
+                for i in 0..n {
+                    circuit.set_input(a[i], get_bit(a_, i));
+                    circuit.set_input(b[i], get_bit(b_, i));
+                }
+                for _ in 0..steps {
+                    circuit.update_signals_once(&order);
+                }
                 let (s_, _) = a_.overflowing_add(b_);
                 let (s_) = ((s_ << (64 - n)) >> (64 - n));
                 let mut s__ = 0;
@@ -395,4 +1612,815 @@ mod tests {
             }
         }
     }
+
+    fn check_multiplier(
+        build: fn(&mut Circuit, &[NodeIndex], &[NodeIndex]) -> Vec<NodeIndex>,
+        n: usize,
+        m: usize,
+    ) -> Circuit {
+        let mut circuit = Circuit::new();
+        let a = (0..n).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..m).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let p = build(&mut circuit, &a, &b);
+        assert_eq!(p.len(), n + m);
+        let p = p
+            .into_iter()
+            .map(|pi| circuit.add_output(pi))
+            .collect::<Vec<_>>();
+
+        let steps = flip_ranks(&circuit.ranks()).len() + 1;
+        let order = circuit.update_order();
+
+        for a_ in 0..(2usize).pow(n as u32) {
+            for b_ in 0..(2usize).pow(m as u32) {
+                for i in 0..n {
+                    circuit.set_input(a[i], get_bit(a_, i));
+                }
+                for i in 0..m {
+                    circuit.set_input(b[i], get_bit(b_, i));
+                }
+                for _ in 0..steps {
+                    circuit.update_signals_once(&order);
+                }
+                let mut p_ = 0;
+                for i in 0..n + m {
+                    p_ = set_bit(p_, i, circuit.get_1_in(p[i]));
+                }
+                assert_eq!(p_, a_ * b_, "{} * {} = {} [correct: {}]", a_, b_, p_, a_ * b_);
+            }
+        }
+        circuit
+    }
+
+    #[test]
+    fn test_array_multiplier() {
+        check_multiplier(Circuit::array_multiplier, 4, 4);
+        check_multiplier(Circuit::array_multiplier, 2, 5);
+        check_multiplier(Circuit::array_multiplier, 5, 3);
+    }
+
+    #[test]
+    fn test_wallace_multiplier() {
+        check_multiplier(Circuit::wallace_multiplier, 4, 4);
+        check_multiplier(Circuit::wallace_multiplier, 2, 5);
+        check_multiplier(Circuit::wallace_multiplier, 5, 3);
+    }
+
+    #[test]
+    fn test_wallace_is_shallower() {
+        let depth = |build: fn(&mut Circuit, &[NodeIndex], &[NodeIndex]) -> Vec<NodeIndex>| {
+            let mut circuit = Circuit::new();
+            let a = (0..8).map(|_| circuit.add_input()).collect::<Vec<_>>();
+            let b = (0..8).map(|_| circuit.add_input()).collect::<Vec<_>>();
+            build(&mut circuit, &a, &b);
+            *circuit.ranks().values().max().unwrap()
+        };
+        assert!(depth(Circuit::wallace_multiplier) < depth(Circuit::array_multiplier));
+    }
+
+    #[test]
+    fn test_flip_flop() {
+        let mut circuit = Circuit::new();
+        let d = circuit.add_input();
+        let clock = circuit.add_input();
+        let q = circuit.add_flip_flop(false);
+        circuit.connect_flip_flop(q, d, clock);
+        let out = circuit.add_output(q);
+
+        let order = circuit.update_order();
+        let settle = |circuit: &mut Circuit| {
+            for _ in 0..5 {
+                circuit.update_signals_once(&order);
+            }
+        };
+
+        circuit.set_input(d, true);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out), false);
+
+        circuit.set_input(clock, true);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out), true);
+
+        // only rising edges matter.
+        circuit.set_input(d, false);
+        settle(&mut circuit);
+        circuit.set_input(clock, false);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out), true);
+
+        circuit.set_input(clock, true);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out), false);
+    }
+
+    #[test]
+    #[should_panic(expected = "oscillating")]
+    fn test_oscillating() {
+        // q xor !q is high, except for a moment whenever q changes, before !q catches up; so the
+        // clock rises again after every change, and every rise flips q.
+        let mut circuit = Circuit::new();
+        let q = circuit.add_flip_flop(false);
+        let not_q = circuit.add_not(q);
+        let clock = circuit.add_xor(q, not_q);
+        let d = circuit.add_not(q);
+        circuit.connect_flip_flop(q, d, clock);
+        circuit.add_output(q);
+        circuit.evaluate(&[]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut circuit = Circuit::new();
+        let d = circuit.add_input();
+        let clock = circuit.add_input();
+        let q = circuit.add_flip_flop(false);
+        circuit.connect_flip_flop(q, d, clock);
+        let not_d = circuit.add_not(d);
+        let out_q = circuit.add_output(q);
+        let out_not_d = circuit.add_output(not_d);
+
+        let order = circuit.update_order();
+        let settle = |circuit: &mut Circuit| {
+            for _ in 0..5 {
+                circuit.update_signals_once(&order);
+            }
+        };
+
+        circuit.set_input(d, true);
+        settle(&mut circuit);
+        let before = circuit.snapshot();
+        assert_eq!(circuit.get_1_in(out_not_d), false);
+
+        // latch a 1, and flip d.
+        circuit.set_input(clock, true);
+        settle(&mut circuit);
+        circuit.set_input(d, false);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out_q), true);
+        assert_eq!(circuit.get_1_in(out_not_d), true);
+
+        circuit.restore(&before);
+        assert_eq!(circuit.snapshot(), before);
+        assert_eq!(circuit.get_1_in(d), true);
+        assert_eq!(circuit.get_1_in(out_q), false);
+        assert_eq!(circuit.get_1_in(out_not_d), false);
+
+        // the clock was low in the snapshot, so raising it again is a rising edge.
+        circuit.set_input(clock, true);
+        settle(&mut circuit);
+        assert_eq!(circuit.get_1_in(out_q), true);
+    }
+
+    #[test]
+    fn test_bus_display() {
+        assert_eq!(BusDisplay::Binary.format(5, 4), "0b0101");
+        assert_eq!(BusDisplay::Decimal.format(5, 4), "5");
+        assert_eq!(BusDisplay::Hex.format(0x1f, 9), "0x01f");
+        assert_eq!(BusDisplay::Hex.format(0, 1), "0x0");
+
+        let mut display = BusDisplay::default();
+        for _ in 0..3 {
+            display = display.next();
+        }
+        assert_eq!(display, BusDisplay::default());
+    }
+
+    #[test]
+    fn test_describe_node() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let x = circuit.add_and(a, b);
+        let out = circuit.add_labeled_output(x, "x");
+        circuit.evaluate(&[(a, true), (b, true)]);
+        let ranks = circuit.ranks();
+
+        assert_eq!(
+            circuit.describe_node(a, &ranks),
+            "Input\nrank 1\nvalue 1\nout 1"
+        );
+        assert_eq!(
+            circuit.describe_node(x, &ranks),
+            "And\nrank 2\nin 1, 1\nout 1"
+        );
+        assert_eq!(
+            circuit.describe_node(out, &HashMap::new()),
+            "Output \"x\"\nin 1"
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        circuit.set_label(a, "a");
+        let (s, c) = circuit.half_adder(a, b);
+        let s = circuit.add_labeled_output(s, "sum");
+        circuit.add_labeled_output(c, "carry");
+        circuit.add_output(c);
+
+        assert_eq!(circuit.labeled("a"), Some(a));
+        assert_eq!(circuit.label(s), Some("sum"));
+        assert_eq!(circuit.label(b), None);
+
+        circuit.set_input(a, true);
+        let order = circuit.update_order();
+        for _ in 0..4 {
+            circuit.update_signals_once(&order);
+        }
+        assert_eq!(circuit.output_value("sum"), true);
+        assert_eq!(circuit.output_value("carry"), false);
+
+        // only labeled outputs are read.
+        let outputs = circuit.read_outputs();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs["sum"], true);
+        assert_eq!(outputs["carry"], false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_label() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        circuit.set_label(a, "x");
+        circuit.set_label(b, "x");
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (lt, eq) = circuit.signed_compare(&a, &b);
+        let (lt, eq) = (circuit.add_output(lt), circuit.add_output(eq));
+
+        // -3 < 2
+        let inputs = a
+            .iter()
+            .zip(&b)
+            .enumerate()
+            .flat_map(|(i, (a, b))| vec![(*a, get_bit(0b1101, i)), (*b, get_bit(0b0010, i))])
+            .collect::<Vec<_>>();
+        let outputs = circuit.evaluate(&inputs);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!((outputs[&lt], outputs[&eq]), (true, false));
+
+        // evaluating again with nothing changed is a no-op.
+        assert_eq!(circuit.evaluate(&[]), outputs);
+        assert!(!circuit.update_signals_once(&circuit.update_order()));
+    }
+
+    #[test]
+    fn test_alu() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let op = [circuit.add_input(), circuit.add_input()];
+        let alu = circuit.alu(&a, &b, op);
+        let result = alu
+            .result
+            .iter()
+            .map(|r| circuit.add_output(*r))
+            .collect::<Vec<_>>();
+        let carry = circuit.add_output(alu.carry);
+        let zero = circuit.add_output(alu.zero);
+
+        for operation in AluOp::ALL.iter() {
+            for x in 0..16 {
+                for y in 0..16 {
+                    let mut inputs = vec![];
+                    for i in 0..4 {
+                        inputs.push((a[i], get_bit(x, i)));
+                        inputs.push((b[i], get_bit(y, i)));
+                    }
+                    inputs.push((op[0], get_bit(operation.opcode(), 0)));
+                    inputs.push((op[1], get_bit(operation.opcode(), 1)));
+                    let outputs = circuit.evaluate(&inputs);
+
+                    let mut value = 0;
+                    for (i, r) in result.iter().enumerate() {
+                        value = set_bit(value, i, outputs[r]);
+                    }
+                    let (expected, expected_carry) = match operation {
+                        AluOp::Add => ((x + y) % 16, x + y >= 16),
+                        AluOp::Sub => ((x + 16 - y) % 16, x >= y),
+                        AluOp::And => (x & y, false),
+                        AluOp::Or => (x | y, false),
+                    };
+                    assert_eq!(value, expected, "{} {:?} {}", x, operation, y);
+                    assert_eq!(
+                        outputs[&carry], expected_carry,
+                        "{} {:?} {}",
+                        x, operation, y
+                    );
+                    assert_eq!(outputs[&zero], expected == 0, "{} {:?} {}", x, operation, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_popcount() {
+        for width in 1..10 {
+            let mut circuit = Circuit::new();
+            let bits = (0..width).map(|_| circuit.add_input()).collect::<Vec<_>>();
+            let count = circuit.popcount(&bits);
+            let count = count
+                .iter()
+                .map(|c| circuit.add_output(*c))
+                .collect::<Vec<_>>();
+            assert_eq!(count.len(), (width as f32 + 1.0).log2().ceil() as usize);
+
+            for x in 0..(1 << width) {
+                let inputs = (0..width)
+                    .map(|i| (bits[i], get_bit(x, i)))
+                    .collect::<Vec<_>>();
+                let outputs = circuit.evaluate(&inputs);
+                let mut value = 0;
+                for (i, c) in count.iter().enumerate() {
+                    value = set_bit(value, i, outputs[c]);
+                }
+                assert_eq!(value, x.count_ones() as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_instantiate() {
+        // a full adder, as a subcircuit.
+        let mut adder = Circuit::new();
+        let a = adder.add_input();
+        let b = adder.add_input();
+        let c = adder.add_input();
+        let (s, carry) = adder.full_adder(a, b, c);
+        adder.add_output(s);
+        adder.add_output(carry);
+
+        // two of them make a 2-bit adder.
+        let mut circuit = Circuit::new();
+        let a = (0..2).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..2).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let zero = circuit.add_input();
+        let low = circuit.instantiate(&adder, &[a[0], b[0], zero]);
+        let high = circuit.instantiate(&adder, &[a[1], b[1], low[1]]);
+        let sum = [low[0], high[0], high[1]]
+            .iter()
+            .map(|s| circuit.add_output(*s))
+            .collect::<Vec<_>>();
+
+        for x in 0..4 {
+            for y in 0..4 {
+                let inputs = (0..2)
+                    .flat_map(|i| vec![(a[i], get_bit(x, i)), (b[i], get_bit(y, i))])
+                    .collect::<Vec<_>>();
+                let outputs = circuit.evaluate(&inputs);
+                let mut value = 0;
+                for (i, s) in sum.iter().enumerate() {
+                    value = set_bit(value, i, outputs[s]);
+                }
+                assert_eq!(value, x + y);
+            }
+        }
+
+        // a toggle flip-flop; each copy keeps its own state.
+        let mut toggle = Circuit::new();
+        let clock = toggle.add_input();
+        let q = toggle.add_flip_flop(true);
+        let not_q = toggle.add_not(q);
+        toggle.connect_flip_flop(q, not_q, clock);
+        toggle.add_output(q);
+
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        let other_clock = circuit.add_input();
+        let q0 = circuit.instantiate(&toggle, &[clock])[0];
+        let q1 = circuit.instantiate(&toggle, &[other_clock])[0];
+        let out0 = circuit.add_output(q0);
+        let out1 = circuit.add_output(q1);
+        // settle before the first clock edge.
+        circuit.evaluate(&[]);
+        circuit.evaluate(&[(clock, true)]);
+        let outputs = circuit.evaluate(&[(clock, false)]);
+        assert!(!outputs[&out0]);
+        assert!(outputs[&out1]);
+    }
+
+    #[test]
+    fn test_decoder() {
+        let mut circuit = Circuit::new();
+        let select = (0..3).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let lines = circuit.decoder(&select);
+        let lines = lines
+            .iter()
+            .map(|l| circuit.add_output(*l))
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 8);
+
+        for x in 0..8 {
+            let inputs = (0..3)
+                .map(|i| (select[i], get_bit(x, i)))
+                .collect::<Vec<_>>();
+            let outputs = circuit.evaluate(&inputs);
+            for (i, line) in lines.iter().enumerate() {
+                assert_eq!(outputs[line], i == x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mux_tree() {
+        let mut circuit = Circuit::new();
+        let select = (0..2).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let buses = (0..4)
+            .map(|_| (0..3).map(|_| circuit.add_input()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let result = circuit.mux_tree(&select, &buses);
+        let result = result
+            .iter()
+            .map(|r| circuit.add_output(*r))
+            .collect::<Vec<_>>();
+
+        // bus i holds i + 3.
+        let mut inputs = vec![];
+        for (i, bus) in buses.iter().enumerate() {
+            for (bit, input) in bus.iter().enumerate() {
+                inputs.push((*input, get_bit(i + 3, bit)));
+            }
+        }
+        for s in 0..4 {
+            inputs.push((select[0], get_bit(s, 0)));
+            inputs.push((select[1], get_bit(s, 1)));
+            let outputs = circuit.evaluate(&inputs);
+            let mut value = 0;
+            for (bit, r) in result.iter().enumerate() {
+                value = set_bit(value, bit, outputs[r]);
+            }
+            assert_eq!(value, s + 3);
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        let load = circuit.add_input();
+        let d = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let q = (0..4)
+            .map(|_| circuit.add_flip_flop(false))
+            .collect::<Vec<_>>();
+        circuit.connect_register(&q, &d, load, clock);
+
+        let read = |circuit: &Circuit| {
+            let mut value = 0;
+            for (i, bit) in q.iter().enumerate() {
+                value = set_bit(value, i, circuit.get_1_in(*bit));
+            }
+            value
+        };
+        let mut clock_in = |circuit: &mut Circuit, value: usize, load_on: bool| {
+            let mut inputs = (0..4)
+                .map(|i| (d[i], get_bit(value, i)))
+                .collect::<Vec<_>>();
+            inputs.push((load, load_on));
+            circuit.evaluate(&inputs);
+            circuit.evaluate(&[(clock, true)]);
+            circuit.evaluate(&[(clock, false)]);
+        };
+
+        clock_in(&mut circuit, 9, true);
+        assert_eq!(read(&circuit), 9);
+        clock_in(&mut circuit, 4, false);
+        assert_eq!(read(&circuit), 9);
+        clock_in(&mut circuit, 4, true);
+        assert_eq!(read(&circuit), 4);
+    }
+
+    #[test]
+    fn test_incrementer() {
+        let mut circuit = Circuit::new();
+        let bits = (0..5).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (mut result, carry) = circuit.incrementer(&bits);
+        result.push(carry);
+        testing::assert_matches_reference(&circuit, &[&bits], &result, |x| x[0] + 1);
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        let q = circuit.counter(3, clock);
+
+        let read = |circuit: &Circuit| {
+            let mut count = 0;
+            for (i, bit) in q.iter().enumerate() {
+                count = set_bit(count, i, circuit.get_1_in(*bit));
+            }
+            count
+        };
+        for expected in (0..8).chain(0..8) {
+            assert_eq!(read(&circuit), expected);
+            circuit.evaluate(&[(clock, true)]);
+            circuit.evaluate(&[(clock, false)]);
+        }
+    }
+
+    #[test]
+    fn test_lfsr() {
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        // x^4 + x^3 + 1 is maximal-length.
+        let q = circuit.lfsr(&[3, 2], 0b0001, clock);
+        assert_eq!(q.len(), 4);
+
+        let order = circuit.update_order();
+        let mut seen = vec![];
+        for _ in 0..15 {
+            let mut state = 0;
+            for i in 0..4 {
+                state = set_bit(state, i, circuit.get_1_in(q[i]));
+            }
+            assert!(!seen.contains(&state), "state {:04b} repeated early", state);
+            seen.push(state);
+
+            for value in [true, false].iter() {
+                circuit.set_input(clock, *value);
+                for _ in 0..5 {
+                    circuit.update_signals_once(&order);
+                }
+            }
+        }
+        let mut state = 0;
+        for i in 0..4 {
+            state = set_bit(state, i, circuit.get_1_in(q[i]));
+        }
+        assert_eq!(state, 0b0001);
+    }
+
+    fn to_signed(v: usize, n: usize) -> i64 {
+        ((v << (64 - n)) as i64) >> (64 - n)
+    }
+
+    #[test]
+    fn test_signed_arithmetic() {
+        let n: usize = 4;
+        let mut circuit = Circuit::new();
+        let a = (0..n).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..n).map(|_| circuit.add_input()).collect::<Vec<_>>();
+
+        let a_wide = circuit.sign_extend(&a, n + 2);
+        let (sum, _, add_overflow) = circuit.ripple_carry_overflow(&a, &b);
+        let (diff, borrow, sub_overflow) = circuit.subtractor_overflow(&a, &b);
+        let (lt, eq) = circuit.signed_compare(&a, &b);
+
+        let mut outputs = |bits: &[NodeIndex]| {
+            bits.iter()
+                .map(|b| circuit.add_output(*b))
+                .collect::<Vec<_>>()
+        };
+        let a_wide = outputs(&a_wide);
+        let sum = outputs(&sum);
+        let diff = outputs(&diff);
+        let flags = outputs(&[add_overflow, borrow, sub_overflow, lt, eq]);
+
+        let steps = flip_ranks(&circuit.ranks()).len() + 1;
+        let order = circuit.update_order();
+        let read = |circuit: &Circuit, bits: &[NodeIndex]| {
+            let mut v = 0;
+            for (i, bit) in bits.iter().enumerate() {
+                v = set_bit(v, i, circuit.get_1_in(*bit));
+            }
+            v
+        };
+
+        for a_ in 0..(2usize).pow(n as u32) {
+            for b_ in 0..(2usize).pow(n as u32) {
+                for i in 0..n {
+                    circuit.set_input(a[i], get_bit(a_, i));
+                    circuit.set_input(b[i], get_bit(b_, i));
+                }
+                for _ in 0..steps {
+                    circuit.update_signals_once(&order);
+                }
+                let (sa, sb) = (to_signed(a_, n), to_signed(b_, n));
+                let in_range = |v: i64| v >= -8 && v < 8;
+
+                assert_eq!(to_signed(read(&circuit, &a_wide), n + 2), sa);
+                assert_eq!(
+                    to_signed(read(&circuit, &sum), n),
+                    to_signed((a_ + b_) % 16, n)
+                );
+                assert_eq!(
+                    to_signed(read(&circuit, &diff), n),
+                    to_signed((a_ + 16 - b_) % 16, n)
+                );
+
+                let flags = flags
+                    .iter()
+                    .map(|f| circuit.get_1_in(*f))
+                    .collect::<Vec<_>>();
+                assert_eq!(flags[0], !in_range(sa + sb), "{} + {}", sa, sb);
+                assert_eq!(flags[1], a_ >= b_, "{} - {} carry", a_, b_);
+                assert_eq!(flags[2], !in_range(sa - sb), "{} - {}", sa, sb);
+                assert_eq!(flags[3], sa < sb, "{} < {}", sa, sb);
+                assert_eq!(flags[4], sa == sb, "{} == {}", sa, sb);
+            }
+        }
+    }
+
+    /// Software model of `Circuit::float_adder`, with the same truncation and flushing.
+    fn float_add_reference(a: usize, b: usize, exp_bits: usize, m: usize) -> usize {
+        let width = exp_bits + m + 1;
+        let magnitude = |x: usize| x & ((1 << (width - 1)) - 1);
+        let (big, small) = if magnitude(a) >= magnitude(b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let exponent = |x: usize| (x >> m) & ((1 << exp_bits) - 1);
+        let significand = |x: usize| {
+            if exponent(x) == 0 {
+                0
+            } else {
+                (x & ((1 << m) - 1)) | (1 << m)
+            }
+        };
+        let sign = big >> (width - 1);
+
+        let aligned = significand(small) >> (exponent(big) - exponent(small));
+        let sum = if get_bit(a, width - 1) == get_bit(b, width - 1) {
+            significand(big) + aligned
+        } else {
+            significand(big) - aligned
+        };
+        if sum == 0 {
+            return 0;
+        }
+        let mut distance = 0;
+        while !get_bit(sum << distance, m + 1) {
+            distance += 1;
+        }
+        let e = exponent(big) as i64 + 1 - distance as i64;
+        if e <= 0 {
+            0
+        } else if e >= (1 << exp_bits) {
+            (sign << (width - 1)) | ((1 << (width - 1)) - 1)
+        } else {
+            let mantissa = ((sum << distance) >> 1) & ((1 << m) - 1);
+            (sign << (width - 1)) | ((e as usize) << m) | mantissa
+        }
+    }
+
+    /// Decode a 1-4-3 float with bias 7.
+    fn decode_float(x: usize) -> f64 {
+        let e = (x >> 3) & 0xf;
+        if e == 0 {
+            return 0.0;
+        }
+        let v = (1.0 + (x & 7) as f64 / 8.0) * 2.0f64.powi(e as i32 - 7);
+        if get_bit(x, 7) {
+            -v
+        } else {
+            v
+        }
+    }
+
+    #[test]
+    fn test_float_adder() {
+        let (exp_bits, m) = (4, 3);
+        let width = exp_bits + m + 1;
+        let mut circuit = Circuit::new();
+        let a = (0..width).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..width).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let adder = circuit.float_adder(&a, &b, exp_bits);
+        let sum = adder
+            .sum
+            .iter()
+            .map(|s| circuit.add_output(*s))
+            .collect::<Vec<_>>();
+
+        let mut add = |a_: usize, b_: usize| {
+            let inputs = (0..width)
+                .flat_map(|i| vec![(a[i], get_bit(a_, i)), (b[i], get_bit(b_, i))])
+                .collect::<Vec<_>>();
+            let outputs = circuit.evaluate(&inputs);
+            let mut result = 0;
+            for (i, s) in sum.iter().enumerate() {
+                result = set_bit(result, i, outputs[s]);
+            }
+            result
+        };
+
+        // some sums which are exact in 1-4-3.
+        for &(x, y, z) in &[
+            (1.0, 1.0, 2.0),
+            (1.5, -0.5, 1.0),
+            (3.0, 0.25, 3.25),
+            (-2.5, 2.5, 0.0),
+            (0.0, -6.0, -6.0),
+            (-1.0, -0.75, -1.75),
+        ] {
+            let encode = |v: f64| (0..256).find(|bits| decode_float(*bits) == v).unwrap();
+            assert_eq!(decode_float(add(encode(x), encode(y))), z, "{} + {}", x, y);
+        }
+        // saturation and flushing.
+        assert_eq!(add(0b0_1111_111, 0b0_1111_000), 0b0_1111_111);
+        assert_eq!(add(0b1_0001_100, 0b0_0001_000), 0);
+
+        for a_ in (0..256).step_by(7) {
+            for b_ in (0..256).step_by(11) {
+                assert_eq!(
+                    add(a_, b_),
+                    float_add_reference(a_, b_, exp_bits, m),
+                    "{:08b} + {:08b}",
+                    a_,
+                    b_
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let c = circuit.add_and(a, b);
+        let out = circuit.add_output(c);
+        assert!(circuit.diff(&circuit.clone()).is_empty());
+
+        // signal values aren't structure.
+        let mut other = circuit.clone();
+        other.set_input(a, true);
+        other.update_signals_once(&other.update_order());
+        assert!(circuit.diff(&other).is_empty());
+
+        // turn the And into an Or, and replace the output with a Not.
+        let mut other = circuit.clone();
+        other.graph[c] = Gate::Or;
+        let not = other.add_not(c);
+        let edge = other.graph.find_edge(c, out).unwrap();
+        other.graph.remove_edge(edge);
+        other.graph.update_edge(not, out, false);
+
+        let id = |node| other.id(node);
+        let diff = circuit.diff(&other);
+        assert_eq!(diff.added, vec![id(not)]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.retyped, vec![(id(c), Gate::And, Gate::Or)]);
+        assert_eq!(diff.added_edges, vec![(id(c), id(not)), (id(not), id(out))]);
+        assert_eq!(diff.removed_edges, vec![(id(c), id(out))]);
+        assert_eq!(diff.rewired(), vec![id(out)]);
+
+        let reverse = other.diff(&circuit);
+        assert_eq!(reverse.removed, vec![id(not)]);
+        assert_eq!(reverse.added_edges, diff.removed_edges);
+    }
+
+    #[test]
+    fn test_diff_after_removal() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let spare = circuit.add_not(a);
+        let out = circuit.add_output(a);
+        let (spare_id, out_id) = (circuit.id(spare), circuit.id(out));
+
+        // removing `spare` moves `out` into its index, which isn't a change to `out`.
+        let mut other = circuit.clone();
+        history::History::new().remove(&mut other, spare);
+        assert_eq!(other.node(out_id), Some(spare));
+        let diff = circuit.diff(&other);
+        assert_eq!(diff.removed, vec![spare_id]);
+        assert!(diff.added.is_empty());
+        assert!(diff.retyped.is_empty());
+        assert_eq!(diff.removed_edges, vec![(circuit.id(a), spare_id)]);
+        assert!(diff.added_edges.is_empty());
+    }
+
+    #[test]
+    fn test_diff_parallel_edges() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+
+        // instantiating an Xor with both inputs from `a` wires `a` into it twice.
+        let mut xor = Circuit::new();
+        let (x, y) = (xor.add_input(), xor.add_input());
+        let out = xor.add_xor(x, y);
+        xor.add_output(out);
+        let mut other = circuit.clone();
+        let sum = other.instantiate(&xor, &[a, a])[0];
+        let pair = (other.id(a), other.id(sum));
+        assert_eq!(circuit.diff(&other).added_edges, vec![pair, pair]);
+
+        // taking away one of them leaves the other.
+        let mut single = other.clone();
+        let edge = single.graph.find_edge(a, sum).unwrap();
+        single.graph.remove_edge(edge);
+        let diff = other.diff(&single);
+        assert_eq!(diff.removed_edges, vec![pair]);
+        assert_eq!(diff.rewired(), vec![pair.1]);
+    }
 }
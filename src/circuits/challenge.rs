@@ -0,0 +1,336 @@
+//! "Challenge packs": circuit-building exercises described in RON files.
+//!
+//! A pack looks like:
+//!
+//! ```ron
+//! ChallengePack(
+//!     name: "Basics",
+//!     challenges: [
+//!         Challenge(
+//!             name: "Exclusive or",
+//!             description: "Make an XOR gate without using XOR.",
+//!             inputs: ["a", "b"],
+//!             outputs: ["a ^ b"],
+//!             truth_table: [
+//!                 ([false, false], [false]),
+//!                 ([false, true], [true]),
+//!                 ([true, false], [true]),
+//!                 ([true, true], [false]),
+//!             ],
+//!             budget: Budget(total: Some(4), gates: {Xor: 0}),
+//!             hints: ["a ^ b = (a | b) & !(a & b)"],
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! Packs are loaded from every `.ron` file in a directory, and which challenges have been
+//! completed is saved next to each pack, in `<pack>.progress.ron`.
+
+use super::{flip_ranks, Circuit, Gate, Value};
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The gates a player can place.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum BudgetGate {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl BudgetGate {
+    fn of(gate: Gate) -> Option<BudgetGate> {
+        match gate {
+            Gate::And => Some(BudgetGate::And),
+            Gate::Or => Some(BudgetGate::Or),
+            Gate::Xor => Some(BudgetGate::Xor),
+            Gate::Not => Some(BudgetGate::Not),
+            _ => None,
+        }
+    }
+}
+
+/// Limits on the gates a solution may use. Gates not listed in `gates` are unlimited.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Budget {
+    pub total: Option<u32>,
+    pub gates: HashMap<BudgetGate, u32>,
+}
+
+/// A single exercise: build a circuit matching a truth table.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Challenge {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Labels of the input nodes, in order.
+    pub inputs: Vec<String>,
+    /// Labels of the output nodes, in order.
+    pub outputs: Vec<String>,
+    /// Rows of (input values, expected output values).
+    /// Input combinations that aren't listed aren't checked.
+    pub truth_table: Vec<(Vec<Value>, Vec<Value>)>,
+    #[serde(default)]
+    pub budget: Budget,
+    #[serde(default)]
+    pub hints: Vec<String>,
+}
+
+/// A named list of challenges.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ChallengePack {
+    pub name: String,
+    pub challenges: Vec<Challenge>,
+}
+
+/// Why a circuit doesn't solve a challenge.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Failure {
+    /// The circuit has the wrong number of inputs or outputs, or a row of the truth table does.
+    WrongShape,
+    /// The circuit gives the wrong outputs for row `row` of the truth table.
+    WrongOutput { row: usize, got: Vec<Value> },
+    /// The circuit uses more gates than allowed.
+    OverBudget {
+        gate: Option<BudgetGate>,
+        used: u32,
+        allowed: u32,
+    },
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::WrongShape => write!(f, "wrong number of inputs or outputs"),
+            Failure::WrongOutput { row, got } => {
+                write!(f, "wrong output for row {}: got {:?}", row, got)
+            }
+            Failure::OverBudget {
+                gate: Some(gate),
+                used,
+                allowed,
+            } => write!(f, "too many {:?} gates: {} > {}", gate, used, allowed),
+            Failure::OverBudget {
+                gate: None,
+                used,
+                allowed,
+            } => write!(f, "too many gates: {} > {}", used, allowed),
+        }
+    }
+}
+
+impl Challenge {
+    /// Check whether `circuit` solves this challenge, where `inputs` are its `Input` nodes and
+    /// `outputs` its `Output` nodes, in the order given by the challenge.
+    /// Inputs are left changed.
+    pub fn check(
+        &self,
+        circuit: &mut Circuit,
+        inputs: &[NodeIndex],
+        outputs: &[NodeIndex],
+    ) -> Result<(), Failure> {
+        if inputs.len() != self.inputs.len() || outputs.len() != self.outputs.len() {
+            return Err(Failure::WrongShape);
+        }
+        for (in_values, out_values) in &self.truth_table {
+            if in_values.len() != inputs.len() || out_values.len() != outputs.len() {
+                return Err(Failure::WrongShape);
+            }
+        }
+
+        let mut used = HashMap::new();
+        for node in circuit.graph.raw_nodes() {
+            if let Some(gate) = BudgetGate::of(node.weight) {
+                *used.entry(gate).or_insert(0) += 1;
+            }
+        }
+        let total = used.values().sum::<u32>();
+        if let Some(allowed) = self.budget.total {
+            if total > allowed {
+                return Err(Failure::OverBudget {
+                    gate: None,
+                    used: total,
+                    allowed,
+                });
+            }
+        }
+        for (gate, allowed) in &self.budget.gates {
+            let used = used.get(gate).cloned().unwrap_or(0);
+            if used > *allowed {
+                return Err(Failure::OverBudget {
+                    gate: Some(*gate),
+                    used,
+                    allowed: *allowed,
+                });
+            }
+        }
+
+        let steps = flip_ranks(&circuit.ranks()).len() + 1;
+        let order = circuit.update_order();
+        for (row, (in_values, out_values)) in self.truth_table.iter().enumerate() {
+            for (input, value) in inputs.iter().zip(in_values) {
+                circuit.set_input(*input, *value);
+            }
+            for _ in 0..steps {
+                circuit.update_signals_once(&order);
+            }
+            let got = outputs
+                .iter()
+                .map(|output| circuit.get_1_in(*output))
+                .collect::<Vec<_>>();
+            if &got != out_values {
+                return Err(Failure::WrongOutput { row, got });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ChallengePack {
+    /// Parse a pack from RON source.
+    pub fn from_ron(source: &str) -> Result<ChallengePack, ron::de::Error> {
+        ron::de::from_str(source)
+    }
+
+    /// Load every `.ron` file in `dir` as a pack, sorted by path.
+    /// Progress files are skipped; files that fail to parse are reported in the error list.
+    pub fn load_dir(
+        dir: &Path,
+    ) -> std::io::Result<(Vec<(PathBuf, ChallengePack)>, Vec<(PathBuf, String)>)> {
+        let mut paths = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.ends_with(".ron") && !name.ends_with(".progress.ron")
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut packs = vec![];
+        let mut errors = vec![];
+        for path in paths {
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| ChallengePack::from_ron(&s).map_err(|e| e.to_string()))
+            {
+                Ok(pack) => packs.push((path, pack)),
+                Err(e) => errors.push((path, e)),
+            }
+        }
+        Ok((packs, errors))
+    }
+}
+
+/// Which challenges of a pack have been completed, by name.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    pub completed: BTreeSet<String>,
+}
+
+impl Progress {
+    /// Where the progress for the pack at `pack_path` is stored.
+    pub fn path_for(pack_path: &Path) -> PathBuf {
+        pack_path.with_extension("progress.ron")
+    }
+
+    /// Load the progress for the pack at `pack_path`. Missing or unreadable progress counts as
+    /// nothing completed.
+    pub fn load(pack_path: &Path) -> Progress {
+        fs::read_to_string(Progress::path_for(pack_path))
+            .ok()
+            .and_then(|s| ron::de::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the progress for the pack at `pack_path`.
+    pub fn save(&self, pack_path: &Path) -> std::io::Result<()> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("failed to serialize progress");
+        fs::write(Progress::path_for(pack_path), source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACK: &str = r#"
+        ChallengePack(
+            name: "Test",
+            challenges: [
+                Challenge(
+                    name: "Exclusive or",
+                    inputs: ["a", "b"],
+                    outputs: ["a ^ b"],
+                    truth_table: [
+                        ([false, false], [false]),
+                        ([false, true], [true]),
+                        ([true, false], [true]),
+                        ([true, true], [false]),
+                    ],
+                    budget: Budget(total: Some(4), gates: {Xor: 0}),
+                ),
+            ],
+        )
+    "#;
+
+    #[test]
+    fn test_check() {
+        let pack = ChallengePack::from_ron(PACK).unwrap();
+        let challenge = &pack.challenges[0];
+        assert_eq!(challenge.hints.len(), 0);
+
+        // (a | b) & !(a & b)
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let or = circuit.add_or(a, b);
+        let and = circuit.add_and(a, b);
+        let nand = circuit.add_not(and);
+        let x = circuit.add_and(or, nand);
+        let out = circuit.add_output(x);
+        assert_eq!(challenge.check(&mut circuit, &[a, b], &[out]), Ok(()));
+
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let x = circuit.add_xor(a, b);
+        let out = circuit.add_output(x);
+        assert_eq!(
+            challenge.check(&mut circuit, &[a, b], &[out]),
+            Err(Failure::OverBudget {
+                gate: Some(BudgetGate::Xor),
+                used: 1,
+                allowed: 0
+            })
+        );
+
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let x = circuit.add_or(a, b);
+        let out = circuit.add_output(x);
+        assert_eq!(
+            challenge.check(&mut circuit, &[a, b], &[out]),
+            Err(Failure::WrongOutput {
+                row: 3,
+                got: vec![true]
+            })
+        );
+
+        // a row missing an input is the challenge's mistake, not the circuit's.
+        let mut short = challenge.clone();
+        short.truth_table[2].0.pop();
+        assert_eq!(
+            short.check(&mut circuit, &[a, b], &[out]),
+            Err(Failure::WrongShape)
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Stepping through a circuit's simulation, with breakpoints.
+//!
+//! A `SimDebugger` sits beside a `Circuit` and updates its gates one at a time, sweeping through
+//! them rank by rank (so a signal can cross the whole circuit in one sweep, unlike
+//! `update_signals_once`, which moves signals one gate per call):
+//!
+//! ```ignore
+//! let mut debugger = SimDebugger::new(&circuit);
+//! debugger.break_on_change(carry_out);
+//! circuit.set_input(a0, true);
+//! if let Some(hit) = debugger.continue_until_settled(&mut circuit) {
+//!     println!("{:?} flipped the carry", hit.gate);
+//! }
+//! ```
+//!
+//! The debugger remembers the circuit's gates, so it has to be remade if gates are added or
+//! removed. Changing signals from outside (`set_input`, `set_flip_flop`) is fine.
+
+use super::{flip_ranks, Circuit, Gate};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+
+/// A breakpoint being hit: a signal going into a watched node flipped.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Break {
+    /// The gate whose update flipped the signal.
+    pub gate: NodeIndex,
+    /// The watched node the signal goes into.
+    pub watched: NodeIndex,
+}
+
+/// Steps through a circuit's simulation a gate or a rank at a time, stopping at breakpoints.
+///
+/// Each sweep updates every gate once, in rank order; the circuit has settled when a whole sweep
+/// changes nothing.
+#[derive(Clone, Debug)]
+pub struct SimDebugger {
+    /// Every gate, rank by rank.
+    order: Vec<NodeIndex>,
+    /// The index in `order` where each rank ends.
+    rank_ends: Vec<usize>,
+    /// The index in `order` of the next gate to update.
+    next: usize,
+    /// Whether any signal has changed so far in this sweep.
+    changed: bool,
+    /// How many sweeps `continue_until_settled` makes before giving up on the circuit settling.
+    limit: usize,
+    watched: HashSet<NodeIndex>,
+}
+
+impl SimDebugger {
+    pub fn new(circuit: &Circuit) -> SimDebugger {
+        let ranks = flip_ranks(&circuit.ranks());
+        let mut order = vec![];
+        let mut rank_ends = vec![];
+        for rank in ranks {
+            order.extend(rank);
+            rank_ends.push(order.len());
+        }
+        SimDebugger {
+            order,
+            rank_ends,
+            next: 0,
+            changed: false,
+            limit: circuit.settle_limit(),
+            watched: HashSet::new(),
+        }
+    }
+
+    /// Stop when any signal going into `node` flips. For a flip-flop, that's the value it holds.
+    /// Inputs are changed from outside the simulation, so watching them never stops anything.
+    pub fn break_on_change(&mut self, node: NodeIndex) {
+        self.watched.insert(node);
+    }
+    pub fn clear_break(&mut self, node: NodeIndex) {
+        self.watched.remove(&node);
+    }
+    pub fn watched(&self) -> &HashSet<NodeIndex> {
+        &self.watched
+    }
+
+    /// The gate the next step will update.
+    pub fn next_gate(&self) -> NodeIndex {
+        self.order[self.next]
+    }
+    /// The rank of the next gate to update.
+    pub fn next_rank(&self) -> usize {
+        self.rank_ends
+            .iter()
+            .filter(|end| **end <= self.next)
+            .count()
+    }
+
+    /// The signals which, if `gate` changes them, hit a breakpoint: (edge, watched node).
+    fn watched_edges(&self, circuit: &Circuit, gate: NodeIndex) -> Vec<(EdgeIndex, NodeIndex)> {
+        match circuit.graph[gate] {
+            Gate::FlipFlopIn { q, .. } if self.watched.contains(&q) => {
+                let edge = circuit.graph.find_edge(Circuit::meta_input(), q).unwrap();
+                vec![(edge, q)]
+            }
+            Gate::FlipFlopIn { .. } | Gate::MetaInput => vec![],
+            _ => circuit
+                .graph
+                .edges(gate)
+                .filter(|edge| self.watched.contains(&edge.target()))
+                .map(|edge| (edge.id(), edge.target()))
+                .collect(),
+        }
+    }
+
+    /// Update the next gate. Returns the breakpoint it hit, if any.
+    pub fn step_gate(&mut self, circuit: &mut Circuit) -> Option<Break> {
+        let gate = self.order[self.next];
+        let watched = self
+            .watched_edges(circuit, gate)
+            .into_iter()
+            .map(|(edge, node)| (edge, node, circuit.graph[edge]))
+            .collect::<Vec<_>>();
+
+        self.changed |= circuit.update_gate(gate);
+        self.next += 1;
+        if self.next == self.order.len() {
+            self.next = 0;
+        }
+
+        watched
+            .into_iter()
+            .find(|(edge, _, before)| circuit.graph[*edge] != *before)
+            .map(|(_, node, _)| Break {
+                gate,
+                watched: node,
+            })
+    }
+
+    /// Update the rest of the current rank, stopping early at a breakpoint.
+    pub fn step_rank(&mut self, circuit: &mut Circuit) -> Option<Break> {
+        let rank = self.next_rank();
+        while self.next_rank() == rank {
+            if let Some(hit) = self.step_gate(circuit) {
+                return Some(hit);
+            }
+            if self.next == 0 {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Keep updating gates until a whole sweep through the circuit changes nothing, or until a
+    /// breakpoint is hit. Returns the breakpoint, or `None` once the circuit has settled.
+    /// Panics if it's still changing after `Circuit::settle_limit` sweeps, as it must be oscillating.
+    pub fn continue_until_settled(&mut self, circuit: &mut Circuit) -> Option<Break> {
+        let mut sweeps = 0;
+        loop {
+            if self.next == 0 {
+                assert!(
+                    sweeps <= self.limit,
+                    "the circuit is still changing after {} sweeps, so it must be oscillating \
+                     (is a flip-flop clocked by its own output?)",
+                    self.limit
+                );
+                sweeps += 1;
+                self.changed = false;
+            }
+            if let Some(hit) = self.step_gate(circuit) {
+                return Some(hit);
+            }
+            if self.next == 0 && !self.changed {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::get_bit;
+
+    fn adder() -> (Circuit, Vec<NodeIndex>, Vec<NodeIndex>, NodeIndex) {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (_, c) = circuit.ripple_carry(&a, &b);
+        let c = circuit.add_output(c);
+        (circuit, a, b, c)
+    }
+
+    #[test]
+    fn test_break_on_change() {
+        let (mut circuit, a, b, c) = adder();
+        let mut debugger = SimDebugger::new(&circuit);
+        assert_eq!(debugger.continue_until_settled(&mut circuit), None);
+
+        // 15 + 1 carries all the way through.
+        debugger.break_on_change(c);
+        for i in 0..4 {
+            circuit.set_input(a[i], true);
+            circuit.set_input(b[i], i == 0);
+        }
+        let driver = circuit
+            .graph
+            .neighbors_directed(c, petgraph::Direction::Incoming)
+            .next()
+            .unwrap();
+        let hit = debugger.continue_until_settled(&mut circuit);
+        assert_eq!(
+            hit,
+            Some(Break {
+                gate: driver,
+                watched: c
+            })
+        );
+        assert!(circuit.get_1_in(c));
+
+        // the rest of the circuit settles without flipping the carry again.
+        assert_eq!(debugger.continue_until_settled(&mut circuit), None);
+        let outputs = circuit.clone().evaluate(&[]);
+        assert_eq!(outputs[&c], circuit.get_1_in(c));
+    }
+
+    #[test]
+    fn test_step_rank() {
+        let (mut circuit, a, b, c) = adder();
+        let mut debugger = SimDebugger::new(&circuit);
+        let ranks = circuit.ranks();
+
+        for x in 0..16 {
+            for i in 0..4 {
+                circuit.set_input(a[i], get_bit(x, i));
+                circuit.set_input(b[i], get_bit(x, i));
+            }
+            // a single sweep, rank by rank, is enough for a circuit without flip-flops.
+            loop {
+                let rank = debugger.next_rank();
+                assert!(debugger.step_rank(&mut circuit).is_none());
+                if debugger.next_gate() == debugger.order[0] {
+                    break;
+                }
+                assert_eq!(ranks[&debugger.next_gate()] as usize, rank + 1);
+            }
+            assert_eq!(circuit.get_1_in(c), x >= 8);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "oscillating")]
+    fn test_oscillating() {
+        // two flip-flops that toggle on each other's clock: q1 on q1 xor q2 rising, q2 on it
+        // falling. Either toggling flips the xor, which clocks the other one.
+        let mut circuit = Circuit::new();
+        let q1 = circuit.add_flip_flop(false);
+        let q2 = circuit.add_flip_flop(false);
+        let d1 = circuit.add_not(q1);
+        let d2 = circuit.add_not(q2);
+        let parity = circuit.add_xor(q1, q2);
+        let not_parity = circuit.add_not(parity);
+        circuit.connect_flip_flop(q1, d1, parity);
+        circuit.connect_flip_flop(q2, d2, not_parity);
+        circuit.add_output(q1);
+        let mut debugger = SimDebugger::new(&circuit);
+        debugger.continue_until_settled(&mut circuit);
+    }
+}
@@ -0,0 +1,200 @@
+//! Event-driven simulation: updating only the gates whose inputs have changed.
+//!
+//! `update_signals_once` updates every gate on every pass, even when all that's happened is one
+//! input of a big circuit flipping. An `EventSim` sits beside a `Circuit`, like a `SimDebugger`,
+//! and keeps a queue of the gates to update instead: changing a signal queues the gates it goes
+//! into, and updating a gate queues the gates it drives, if its output changed.
+//!
+//! ```ignore
+//! let mut sim = EventSim::new(&circuit);
+//! sim.set_input(&mut circuit, a0, true);
+//! sim.settle(&mut circuit);
+//! ```
+//!
+//! Its passes are `update_signals_once`'s, leaving out the gates with nothing new to update: a
+//! gate's change reaches the gates it drives in the next pass, and a flip-flop latching reaches
+//! its output in the same pass if that's still to be updated, so both settle on the same signals.
+//! The simulator remembers the circuit's gates, so it has to be remade if gates are added or
+//! removed. It can't see signals changed from outside it (`Circuit::set_input`,
+//! `set_flip_flop`), so `schedule` the nodes they go into.
+
+use super::{Circuit, Gate, Value};
+use petgraph::graph::NodeIndex;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Settles a circuit by updating only the gates whose inputs have changed.
+#[derive(Clone, Debug)]
+pub struct EventSim {
+    /// Every gate, in `update_order`.
+    order: Vec<NodeIndex>,
+    /// Each node's index in `order`, indexed by `NodeIndex`.
+    positions: Vec<usize>,
+    /// The gates to update in this pass, by index in `order`, earliest first.
+    queue: BinaryHeap<Reverse<usize>>,
+    /// The gates to update in the next pass, by index in `order`.
+    next: Vec<usize>,
+    /// Whether each gate is in `queue` or `next`, by index in `order`.
+    queued: Vec<bool>,
+    /// How many passes `settle` makes before giving up on the circuit settling.
+    limit: usize,
+}
+
+impl EventSim {
+    /// A simulator for `circuit`, with every gate queued, so the first `settle` brings all of
+    /// its signals up to date.
+    pub fn new(circuit: &Circuit) -> EventSim {
+        let order = circuit.update_order();
+        let mut positions = vec![0; order.len()];
+        for (position, node) in order.iter().enumerate() {
+            positions[node.index()] = position;
+        }
+        EventSim {
+            queue: (0..order.len()).map(Reverse).collect(),
+            queued: vec![true; order.len()],
+            order,
+            positions,
+            next: vec![],
+            limit: circuit.settle_limit(),
+        }
+    }
+
+    /// Queue `node` to be updated in the next `settle`, after its inputs changed.
+    pub fn schedule(&mut self, node: NodeIndex) {
+        self.schedule_after(node, None);
+    }
+    /// Queue `node` after the gate at `current` in `order` changed its inputs: in this pass if
+    /// it's still to come, as `update_signals_once` would reach it, and otherwise in the next.
+    fn schedule_after(&mut self, node: NodeIndex, current: Option<usize>) {
+        let position = self.positions[node.index()];
+        if self.queued[position] {
+            return;
+        }
+        self.queued[position] = true;
+        match current {
+            Some(current) if position <= current => self.next.push(position),
+            _ => self.queue.push(Reverse(position)),
+        }
+    }
+
+    /// Set a single input, queueing it to pass its new value on.
+    pub fn set_input(&mut self, circuit: &mut Circuit, input: NodeIndex, value: Value) {
+        circuit.set_input(input, value);
+        self.schedule(input);
+    }
+
+    /// Update queued gates until nothing changes. Panics if it's still changing after the
+    /// circuit's `settle_limit` of passes, as it must be oscillating.
+    pub fn settle(&mut self, circuit: &mut Circuit) {
+        let mut edges = vec![];
+        for _ in 0..self.limit {
+            if self.queue.is_empty() {
+                return;
+            }
+            while let Some(Reverse(position)) = self.queue.pop() {
+                self.queued[position] = false;
+                let gate = self.order[position];
+                match circuit.graph[gate] {
+                    // a flip-flop's value is on its edge from the MetaInput, not an edge out.
+                    Gate::FlipFlopIn { q, .. } => {
+                        let held = circuit.get_1_in(q);
+                        circuit.update_gate_with(gate, &mut edges);
+                        if circuit.get_1_in(q) != held {
+                            self.schedule_after(q, Some(position));
+                        }
+                    }
+                    _ => {
+                        if circuit.update_gate_with(gate, &mut edges) {
+                            for target in circuit.graph.neighbors(gate) {
+                                self.schedule_after(target, Some(position));
+                            }
+                        }
+                    }
+                }
+            }
+            self.queue.extend(self.next.drain(..).map(Reverse));
+        }
+        if !self.queue.is_empty() {
+            panic!(
+                "the circuit is still changing after {} passes, so it must be oscillating \
+                 (is a flip-flop clocked by its own output?)",
+                self.limit
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::get_bit;
+
+    #[test]
+    fn test_matches_passes() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let product = circuit.wallace_multiplier(&a, &b);
+        for p in &product {
+            circuit.add_output(*p);
+        }
+
+        let mut passes = circuit.clone();
+        let order = passes.update_order();
+        let limit = passes.settle_limit();
+        let mut sim = EventSim::new(&circuit);
+        // every pattern, in an order flipping a few inputs at a time.
+        for pattern in (0..256).map(|i| (i * 37) % 256) {
+            for (i, input) in a.iter().chain(&b).enumerate() {
+                sim.set_input(&mut circuit, *input, get_bit(pattern, i));
+                passes.set_input(*input, get_bit(pattern, i));
+            }
+            sim.settle(&mut circuit);
+            passes.settle(&order, limit);
+            // every wire, not just the outputs.
+            assert_eq!(circuit.snapshot(), passes.snapshot());
+        }
+    }
+
+    #[test]
+    fn test_flip_flops() {
+        // every stage of a shift register latches at once, from what the stage before held.
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        let d = circuit.add_input();
+        let q = (0..4)
+            .map(|_| circuit.add_flip_flop(false))
+            .collect::<Vec<_>>();
+        circuit.connect_flip_flop(q[0], d, clock);
+        for i in 1..4 {
+            circuit.connect_flip_flop(q[i], q[i - 1], clock);
+        }
+
+        let mut sim = EventSim::new(&circuit);
+        sim.settle(&mut circuit);
+        let bits = [true, false, true, true];
+        for (i, bit) in bits.iter().enumerate() {
+            sim.set_input(&mut circuit, d, *bit);
+            sim.set_input(&mut circuit, clock, true);
+            sim.settle(&mut circuit);
+            sim.set_input(&mut circuit, clock, false);
+            sim.settle(&mut circuit);
+            for j in 0..=i {
+                assert_eq!(circuit.get_1_in(q[j]), bits[i - j], "after {} clocks", i + 1);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "oscillating")]
+    fn test_oscillating() {
+        // as in `circuits::tests::test_oscillating`, the clock glitches high after every change.
+        let mut circuit = Circuit::new();
+        let q = circuit.add_flip_flop(false);
+        let not_q = circuit.add_not(q);
+        let clock = circuit.add_xor(q, not_q);
+        let d = circuit.add_not(q);
+        circuit.connect_flip_flop(q, d, clock);
+        EventSim::new(&circuit).settle(&mut circuit);
+    }
+}
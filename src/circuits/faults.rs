@@ -0,0 +1,270 @@
+//! Stuck-at faults, and finding inputs which test for them.
+//!
+//! A manufacturing defect can leave a wire stuck at 0 or 1, whatever drives it. A test vector
+//! detects a fault if the circuit's outputs on that input differ with and without the fault.
+//! Some faults can't be detected at all: they're on redundant logic, whose value never reaches
+//! the outputs.
+//!
+//! This is brute force, trying every input on every fault, so it's only for circuits small
+//! enough to teach with, without flip-flops.
+
+use super::{Circuit, Gate, Value};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
+
+/// The most inputs `generate_test_vectors` will try every combination of.
+pub const MAX_INPUTS: usize = 16;
+
+/// A stuck-at fault: the wire `edge` carries `value`, whatever drives it. Wires are named by
+/// their edge rather than the gates they join, since a gate can have two wires from another.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Fault {
+    pub edge: EdgeIndex,
+    pub value: Value,
+}
+
+/// The result of `Circuit::generate_test_vectors`.
+#[derive(Clone, Debug, Default)]
+pub struct TestVectors {
+    /// The circuit's Inputs, in the order vectors give their values.
+    pub inputs: Vec<NodeIndex>,
+    /// Input values, in the order they should be applied.
+    pub vectors: Vec<Vec<Value>>,
+    /// For each detectable fault, the first vector detecting it.
+    pub detected: HashMap<Fault, usize>,
+    /// Faults no input can detect.
+    pub undetectable: Vec<Fault>,
+}
+
+impl TestVectors {
+    /// The vector as (input, value) pairs, for `Circuit::evaluate`.
+    pub fn assignment(&self, vector: usize) -> Vec<(NodeIndex, Value)> {
+        self.inputs
+            .iter()
+            .cloned()
+            .zip(self.vectors[vector].iter().cloned())
+            .collect()
+    }
+}
+
+impl Circuit {
+    /// Every stuck-at fault: each wire stuck at 0, and at 1. Wires from the MetaInput hold input
+    /// values, so they aren't included.
+    pub fn faults(&self) -> Vec<Fault> {
+        self.graph
+            .edge_references()
+            .filter(|edge| edge.source() != Circuit::meta_input())
+            .flat_map(|edge| {
+                [false, true].iter().map(move |value| Fault {
+                    edge: edge.id(),
+                    value: *value,
+                })
+            })
+            .collect()
+    }
+
+    /// A copy of this circuit with `fault` injected: the faulty wire is replaced with one from a
+    /// new Input, held at the stuck value. Every other node keeps its index.
+    pub fn with_fault(&self, fault: Fault) -> Circuit {
+        let (_, target) = self
+            .graph
+            .edge_endpoints(fault.edge)
+            .unwrap_or_else(|| panic!("no wire for {:?}", fault));
+        assert!(
+            !matches!(self.graph[target], Gate::FlipFlopIn { .. }),
+            "faults on flip-flop inputs aren't supported"
+        );
+        let mut result = self.clone();
+        result.graph.remove_edge(fault.edge);
+        let stuck = result.add_input();
+        result.set_input(stuck, fault.value);
+        result.graph.add_edge(stuck, target, false);
+        result.check_invariants();
+        result
+    }
+
+    /// Find input patterns detecting each stuck-at fault, trying every input on every fault.
+    /// Vectors are picked greedily, each detecting as many new faults as possible, so a handful
+    /// usually covers everything detectable.
+    pub fn generate_test_vectors(&self) -> TestVectors {
+        let inputs = self
+            .graph
+            .node_indices()
+            .filter(|n| self.graph[*n] == Gate::Input)
+            .collect::<Vec<_>>();
+        assert!(
+            inputs.len() <= MAX_INPUTS,
+            "too many inputs to try them all: {}",
+            inputs.len()
+        );
+        assert!(
+            self.graph.node_indices().all(|n| self.graph[n] != Gate::FlipFlop),
+            "test vectors can only be generated for circuits without flip-flops"
+        );
+
+        let patterns = 1usize << inputs.len();
+        let assignment = |pattern: usize| {
+            inputs
+                .iter()
+                .enumerate()
+                .map(|(i, input)| (*input, super::get_bit(pattern, i)))
+                .collect::<Vec<_>>()
+        };
+        let run = |circuit: &mut Circuit, order: &[NodeIndex], limit: usize, pattern: usize| {
+            for (input, value) in assignment(pattern) {
+                circuit.set_input(input, value);
+            }
+            circuit.settle(order, limit);
+            circuit
+                .graph
+                .node_indices()
+                .filter(|n| circuit.graph[*n] == Gate::Output)
+                .map(|n| circuit.get_1_in(n))
+                .collect::<Vec<_>>()
+        };
+
+        let mut good = self.clone();
+        let order = good.update_order();
+        let limit = good.settle_limit();
+        let expected = (0..patterns)
+            .map(|p| run(&mut good, &order, limit, p))
+            .collect::<Vec<_>>();
+
+        // which patterns detect each fault.
+        let faults = self.faults();
+        let mut detecting = vec![];
+        for fault in &faults {
+            let mut faulty = self.with_fault(*fault);
+            let order = faulty.update_order();
+            let limit = faulty.settle_limit();
+            let patterns = (0..patterns)
+                .filter(|p| run(&mut faulty, &order, limit, *p) != expected[*p])
+                .collect::<HashSet<_>>();
+            detecting.push(patterns);
+        }
+
+        let mut result = TestVectors {
+            inputs: inputs.clone(),
+            ..TestVectors::default()
+        };
+        let mut remaining = (0..faults.len())
+            .filter(|f| {
+                if detecting[*f].is_empty() {
+                    result.undetectable.push(faults[*f]);
+                }
+                !detecting[*f].is_empty()
+            })
+            .collect::<HashSet<_>>();
+        while !remaining.is_empty() {
+            let count = |p: usize| {
+                remaining
+                    .iter()
+                    .filter(|f| detecting[**f].contains(&p))
+                    .count()
+            };
+            let best = (0..patterns)
+                .max_by_key(|p| (count(*p), patterns - p))
+                .unwrap();
+            let vector = result.vectors.len();
+            remaining.retain(|f| {
+                if detecting[*f].contains(&best) {
+                    result.detected.insert(faults[*f], vector);
+                    false
+                } else {
+                    true
+                }
+            });
+            result
+                .vectors
+                .push(assignment(best).into_iter().map(|(_, v)| v).collect());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_adder() -> Circuit {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let c = circuit.add_input();
+        let (s, carry) = circuit.full_adder(a, b, c);
+        circuit.add_output(s);
+        circuit.add_output(carry);
+        circuit
+    }
+
+    #[test]
+    fn test_with_fault() {
+        let circuit = full_adder();
+        let fault = circuit.faults()[0];
+        let (source, target) = circuit.graph.edge_endpoints(fault.edge).unwrap();
+        let faulty = circuit.with_fault(fault);
+        assert_eq!(faulty.graph.node_count(), circuit.graph.node_count() + 1);
+        assert!(faulty.graph.find_edge(source, target).is_none());
+    }
+
+    #[test]
+    fn test_parallel_wires() {
+        // an Xor with both inputs from `a` has two wires from it, each with faults of its own.
+        let mut xor = Circuit::new();
+        let (x, y) = (xor.add_input(), xor.add_input());
+        let out = xor.add_xor(x, y);
+        xor.add_output(out);
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let sum = circuit.instantiate(&xor, &[a, a])[0];
+        let output = circuit.add_output(sum);
+
+        let faults = circuit.faults();
+        assert_eq!(faults.len(), 6);
+        assert_eq!(faults.iter().collect::<HashSet<_>>().len(), faults.len());
+
+        // each fault breaks its own wire, leaving the other from `a`.
+        for fault in &faults[..4] {
+            let mut faulty = circuit.with_fault(*fault);
+            assert_eq!(faulty.graph.edges_connecting(a, sum).count(), 1);
+            assert!(faulty.evaluate(&[(a, !fault.value)])[&output]);
+        }
+    }
+
+    #[test]
+    fn test_generate_test_vectors() {
+        let circuit = full_adder();
+        let vectors = circuit.generate_test_vectors();
+        assert!(vectors.undetectable.is_empty());
+        assert_eq!(vectors.detected.len(), circuit.faults().len());
+        assert!(vectors.vectors.len() < 8);
+
+        // each vector really does detect the faults it's credited with.
+        for (fault, vector) in &vectors.detected {
+            let assignment = vectors.assignment(*vector);
+            let good = circuit.clone().evaluate(&assignment);
+            let bad = circuit.with_fault(*fault).evaluate(&assignment);
+            assert_ne!(good, bad, "{:?}", fault);
+        }
+    }
+
+    #[test]
+    fn test_undetectable() {
+        // a | (a & b) is just a: the and gate is redundant.
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let and = circuit.add_and(a, b);
+        let or = circuit.add_or(a, and);
+        circuit.add_output(or);
+
+        let vectors = circuit.generate_test_vectors();
+        let redundant = Fault {
+            edge: circuit.graph.find_edge(and, or).unwrap(),
+            value: false,
+        };
+        assert!(vectors.undetectable.contains(&redundant));
+        assert!(!vectors.detected.contains_key(&redundant));
+    }
+}
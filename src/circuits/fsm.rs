@@ -0,0 +1,304 @@
+//! Finite state machines, synthesized into flip-flops and gates.
+//!
+//! A `StateMachine` names its states and inputs, and moves between states on conditions over
+//! the inputs:
+//!
+//! ```ignore
+//! let mut machine = StateMachine::new(&["idle", "busy"], &["start", "done"]);
+//! machine.transition("idle", "busy", &[("start", true)]);
+//! machine.transition("busy", "idle", &[("done", true)]);
+//! machine.output("light", &["busy"]);
+//! let built = machine.synthesize(&mut circuit, &[start, done], clock);
+//! ```
+//!
+//! The synthesized circuit is one-hot: each state gets a flip-flop, which is on while the
+//! machine is in that state. That takes more flip-flops than numbering the states in binary,
+//! but the next-state logic stays small and the current state can be read straight off the
+//! circuit.
+
+use super::{Circuit, Value};
+use petgraph::graph::NodeIndex;
+use std::collections::HashMap;
+
+/// A move from one state to another, taken on a rising clock edge when every input in `when`
+/// has the given value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub from: usize,
+    pub to: usize,
+    /// (input, value) pairs which must all hold. Empty means always.
+    pub when: Vec<(usize, Value)>,
+}
+
+/// A description of a state machine, with Moore outputs (depending only on the state).
+/// States, inputs and outputs are referred to by index, in the order they were named.
+#[derive(Clone, Debug)]
+pub struct StateMachine {
+    states: Vec<String>,
+    inputs: Vec<String>,
+    transitions: Vec<Transition>,
+    outputs: Vec<(String, Vec<usize>)>,
+}
+
+impl StateMachine {
+    /// Make a machine with no transitions, starting in the first state.
+    pub fn new(states: &[&str], inputs: &[&str]) -> StateMachine {
+        assert!(!states.is_empty(), "state machine needs at least 1 state");
+        let unique = |names: &[&str]| {
+            for (i, name) in names.iter().enumerate() {
+                assert!(!names[..i].contains(name), "duplicate name {:?}", name);
+            }
+            names.iter().map(|n| n.to_string()).collect()
+        };
+        StateMachine {
+            states: unique(states),
+            inputs: unique(inputs),
+            transitions: vec![],
+            outputs: vec![],
+        }
+    }
+
+    /// Move from state `from` to `to` when every input in `when` has the given value.
+    /// If several transitions out of a state could be taken, the one added first wins; if none
+    /// can, the machine stays where it is.
+    pub fn transition(&mut self, from: &str, to: &str, when: &[(&str, Value)]) {
+        let transition = Transition {
+            from: self.state_index(from),
+            to: self.state_index(to),
+            when: when
+                .iter()
+                .map(|(input, value)| (self.input_index(input), *value))
+                .collect(),
+        };
+        self.transitions.push(transition);
+    }
+
+    /// Add an output which is on while the machine is in any of `states`.
+    pub fn output(&mut self, name: &str, states: &[&str]) {
+        assert!(
+            self.outputs.iter().all(|(other, _)| other != name),
+            "duplicate output {:?}",
+            name
+        );
+        let mut states = states
+            .iter()
+            .map(|s| self.state_index(s))
+            .collect::<Vec<_>>();
+        assert!(
+            !states.is_empty(),
+            "output {:?} needs at least 1 state",
+            name
+        );
+        states.sort();
+        states.dedup();
+        self.outputs.push((name.to_string(), states));
+    }
+
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+    /// The names of the outputs.
+    pub fn outputs(&self) -> Vec<&str> {
+        self.outputs.iter().map(|(name, _)| &name[..]).collect()
+    }
+
+    fn state_index(&self, name: &str) -> usize {
+        self.states
+            .iter()
+            .position(|s| s == name)
+            .unwrap_or_else(|| panic!("no state named {:?}", name))
+    }
+    fn input_index(&self, name: &str) -> usize {
+        self.inputs
+            .iter()
+            .position(|s| s == name)
+            .unwrap_or_else(|| panic!("no input named {:?}", name))
+    }
+
+    /// The state the machine moves to from `state` with the given input values, without
+    /// building a circuit.
+    pub fn next_state(&self, state: usize, inputs: &[Value]) -> usize {
+        assert_eq!(inputs.len(), self.inputs.len());
+        self.transitions
+            .iter()
+            .find(|t| t.from == state && t.when.iter().all(|(i, v)| inputs[*i] == *v))
+            .map(|t| t.to)
+            .unwrap_or(state)
+    }
+    /// The values of the outputs in `state`.
+    pub fn output_values(&self, state: usize) -> Vec<Value> {
+        self.outputs
+            .iter()
+            .map(|(_, states)| states.contains(&state))
+            .collect()
+    }
+
+    /// Build the machine into `circuit`, reading the nodes `inputs` (in the order the inputs
+    /// were named) and changing state on rising edges of `clock`.
+    pub fn synthesize(
+        &self,
+        circuit: &mut Circuit,
+        inputs: &[NodeIndex],
+        clock: NodeIndex,
+    ) -> MachineCircuit {
+        assert_eq!(
+            inputs.len(),
+            self.inputs.len(),
+            "state machine has {} inputs, given {}",
+            self.inputs.len(),
+            inputs.len()
+        );
+        let states = (0..self.states.len())
+            .map(|s| circuit.add_flip_flop(s == 0))
+            .collect::<Vec<_>>();
+
+        // inverted inputs are shared between every condition that needs them.
+        let mut inverted = HashMap::new();
+        let mut literal = |circuit: &mut Circuit, input: usize, value: Value| {
+            if value {
+                inputs[input]
+            } else {
+                *inverted
+                    .entry(input)
+                    .or_insert_with(|| circuit.add_not(inputs[input]))
+            }
+        };
+
+        // for each state: the transitions taken out of it, and the ones coming into it.
+        let mut fired = vec![vec![]; states.len()];
+        let mut taken_into = vec![vec![]; states.len()];
+        for transition in &self.transitions {
+            let mut fires = states[transition.from];
+            for (input, value) in &transition.when {
+                let literal = literal(circuit, *input, *value);
+                fires = circuit.add_and(fires, literal);
+            }
+
+            // earlier transitions out of the same state have priority.
+            let earlier = &fired[transition.from];
+            let taken = if earlier.is_empty() {
+                fires
+            } else {
+                let blocked = circuit.any(earlier);
+                let not_blocked = circuit.add_not(blocked);
+                circuit.add_and(fires, not_blocked)
+            };
+            fired[transition.from].push(fires);
+            taken_into[transition.to].push(taken);
+        }
+
+        for (s, q) in states.iter().enumerate() {
+            let stay = if fired[s].is_empty() {
+                *q
+            } else {
+                let leaving = circuit.any(&fired[s]);
+                let not_leaving = circuit.add_not(leaving);
+                circuit.add_and(*q, not_leaving)
+            };
+            let mut next = taken_into[s].clone();
+            next.push(stay);
+            let d = circuit.any(&next);
+            circuit.connect_flip_flop(*q, d, clock);
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(_, on)| {
+                let on = on.iter().map(|s| states[*s]).collect::<Vec<_>>();
+                circuit.any(&on)
+            })
+            .collect();
+
+        MachineCircuit { states, outputs }
+    }
+}
+
+/// The nodes of a state machine built by `StateMachine::synthesize`.
+pub struct MachineCircuit {
+    /// Each state's flip-flop, on while the machine is in that state.
+    pub states: Vec<NodeIndex>,
+    /// The nodes driving each output, in the order they were added.
+    pub outputs: Vec<NodeIndex>,
+}
+
+impl MachineCircuit {
+    /// The state the machine is in. Panics if it isn't in exactly one state.
+    pub fn state(&self, circuit: &Circuit) -> usize {
+        let on = (0..self.states.len())
+            .filter(|s| circuit.get_1_in(self.states[*s]))
+            .collect::<Vec<_>>();
+        assert_eq!(on.len(), 1, "machine should be in 1 state, is in {:?}", on);
+        on[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::get_bit;
+
+    /// A machine recognizing "1, 0, 1" in a stream of bits, overlaps included.
+    fn detector() -> StateMachine {
+        let mut machine = StateMachine::new(&["start", "1", "10", "101"], &["bit"]);
+        machine.transition("start", "1", &[("bit", true)]);
+        machine.transition("1", "10", &[("bit", false)]);
+        machine.transition("10", "101", &[("bit", true)]);
+        machine.transition("10", "start", &[]);
+        machine.transition("101", "10", &[("bit", false)]);
+        machine.transition("101", "1", &[]);
+        machine.output("found", &["101"]);
+        machine
+    }
+
+    #[test]
+    fn test_next_state() {
+        let machine = detector();
+        let mut state = 0;
+        let mut found = vec![];
+        for bit in [true, false, true, false, true, true, false, false, true].iter() {
+            state = machine.next_state(state, &[*bit]);
+            found.push(machine.output_values(state)[0]);
+        }
+        assert_eq!(
+            found,
+            vec![false, false, true, false, true, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let machine = detector();
+        let mut circuit = Circuit::new();
+        let bit = circuit.add_input();
+        let clock = circuit.add_input();
+        let built = machine.synthesize(&mut circuit, &[bit], clock);
+        let found = circuit.add_output(built.outputs[0]);
+        circuit.evaluate(&[]);
+
+        // every stream of 8 bits.
+        for stream in 0..256 {
+            for q in &built.states {
+                circuit.set_flip_flop(*q, *q == built.states[0]);
+            }
+            let mut state = 0;
+            for i in 0..8 {
+                let input = get_bit(stream, i);
+                // settle the new input before the clock edge.
+                circuit.evaluate(&[(bit, input)]);
+                circuit.evaluate(&[(clock, true)]);
+                let outputs = circuit.evaluate(&[(clock, false)]);
+
+                state = machine.next_state(state, &[input]);
+                assert_eq!(built.state(&circuit), state);
+                assert_eq!(outputs[&found], machine.output_values(state)[0]);
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Undo and redo for circuit edits.
+//!
+//! A `History` sits beside a `Circuit` and makes edits to it, remembering how to reverse each one:
+//!
+//! ```ignore
+//! let mut history = History::new();
+//! let x = history.record(&mut circuit, |c| c.add_xor(a, b));
+//! let (s, carry) = history.record(&mut circuit, |c| c.ripple_carry(&a_bits, &b_bits));
+//! history.undo(&mut circuit); // the whole adder is gone again
+//! ```
+//!
+//! Node indices stay stable across undo and redo: undoing a removal puts the gate back at the
+//! same index, so any `NodeIndex` (and things keyed by them, like layout positions) held from
+//! before is still good afterwards. Gates put back keep their `GateId`s too. Edge indices are
+//! *not* kept stable.
+//!
+//! Edits must be undone against the circuit they were made on, and anything else which changes
+//! the circuit's structure in between confuses the history. Changing signals (`set_input`,
+//! `update_signals_once`) is fine.
+
+use super::{Circuit, Gate, GateId, Value};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+/// Everything needed to put a gate back.
+#[derive(Clone, Debug)]
+struct NodeRecord {
+    node: NodeIndex,
+    id: GateId,
+    gate: Gate,
+    /// Incoming edges, in the order petgraph iterates them.
+    inputs: Vec<(NodeIndex, Value)>,
+    label: Option<String>,
+}
+
+/// A single reversible edit.
+#[derive(Clone, Debug)]
+enum Edit {
+    Add(NodeRecord),
+    Remove(NodeRecord),
+    Rewire {
+        gate: NodeIndex,
+        from: NodeIndex,
+        to: NodeIndex,
+    },
+    SetInput {
+        input: NodeIndex,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl Edit {
+    fn inverse(&self) -> Edit {
+        match self {
+            Edit::Add(record) => Edit::Remove(record.clone()),
+            Edit::Remove(record) => Edit::Add(record.clone()),
+            Edit::Rewire { gate, from, to } => Edit::Rewire {
+                gate: *gate,
+                from: *to,
+                to: *from,
+            },
+            Edit::SetInput { input, from, to } => Edit::SetInput {
+                input: *input,
+                from: *to,
+                to: *from,
+            },
+        }
+    }
+
+    fn apply(&self, circuit: &mut Circuit) {
+        match self {
+            Edit::Add(record) => insert_node(circuit, record),
+            Edit::Remove(record) => {
+                remove_node(circuit, record.node);
+            }
+            Edit::Rewire { gate, from, to } => rewire(circuit, *gate, *from, *to),
+            Edit::SetInput { input, to, .. } => circuit.set_input(*input, *to),
+        }
+    }
+}
+
+/// A record of edits to a circuit, which can be undone and redone.
+///
+/// Each call which edits the circuit is one step of undo, however many gates it touched.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// Run `f`, which may only *add* gates to `circuit` (like `add_and` or `ripple_carry`), and
+    /// record what it added as one step. Labels given to the new gates are recorded too.
+    pub fn record<R, F: FnOnce(&mut Circuit) -> R>(&mut self, circuit: &mut Circuit, f: F) -> R {
+        let nodes_before = circuit.graph.node_count();
+        let edges_before = circuit.graph.edge_count();
+        let result = f(circuit);
+
+        let added = (nodes_before..circuit.graph.node_count())
+            .map(|i| node_record(circuit, NodeIndex::new(i)))
+            .collect::<Vec<_>>();
+        let edges_added = added.iter().map(|r| r.inputs.len()).sum::<usize>();
+        assert_eq!(
+            circuit.graph.edge_count(),
+            edges_before + edges_added,
+            "recorded edits may only add gates"
+        );
+
+        if !added.is_empty() {
+            self.push(added.into_iter().map(Edit::Add).collect());
+        }
+        result
+    }
+
+    /// Remove a gate which doesn't drive anything.
+    ///
+    /// Like `DiGraph::remove_node`, this moves the last node into the removed node's index.
+    pub fn remove(&mut self, circuit: &mut Circuit, node: NodeIndex) {
+        let record = remove_node(circuit, node);
+        self.push(vec![Edit::Remove(record)]);
+    }
+
+    /// Feed `gate` from `to` instead of from `from`.
+    pub fn rewire(
+        &mut self,
+        circuit: &mut Circuit,
+        gate: NodeIndex,
+        from: NodeIndex,
+        to: NodeIndex,
+    ) {
+        rewire(circuit, gate, from, to);
+        self.push(vec![Edit::Rewire { gate, from, to }]);
+    }
+
+    /// Set an input, like `Circuit::set_input`.
+    pub fn set_input(&mut self, circuit: &mut Circuit, input: NodeIndex, value: Value) {
+        let from = circuit.get_1_in(input);
+        circuit.set_input(input, value);
+        self.push(vec![Edit::SetInput {
+            input,
+            from,
+            to: value,
+        }]);
+    }
+
+    /// Undo the most recent step. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, circuit: &mut Circuit) -> bool {
+        let step = match self.undo.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        for edit in step.iter().rev() {
+            edit.inverse().apply(circuit);
+        }
+        circuit.check_invariants();
+        self.redo.push(step);
+        true
+    }
+
+    /// Redo the most recently undone step. Returns false if there was nothing to redo.
+    pub fn redo(&mut self, circuit: &mut Circuit) -> bool {
+        let step = match self.redo.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        for edit in &step {
+            edit.apply(circuit);
+        }
+        circuit.check_invariants();
+        self.undo.push(step);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget everything.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push(&mut self, step: Vec<Edit>) {
+        self.undo.push(step);
+        self.redo.clear();
+    }
+}
+
+fn node_record(circuit: &Circuit, node: NodeIndex) -> NodeRecord {
+    NodeRecord {
+        node,
+        id: circuit.id(node),
+        gate: circuit.graph[node],
+        inputs: circuit
+            .graph
+            .edges_directed(node, Direction::Incoming)
+            .map(|edge| (edge.source(), *edge.weight()))
+            .collect(),
+        label: circuit.label(node).map(String::from),
+    }
+}
+
+/// Add a gate back, at the index it was removed from.
+fn insert_node(circuit: &mut Circuit, record: &NodeRecord) {
+    let node = circuit.add_node_with_id(record.gate, record.id);
+    // petgraph iterates edges newest first, so add them back oldest first.
+    for (source, value) in record.inputs.iter().rev() {
+        circuit.graph.add_edge(*source, node, *value);
+    }
+    if let Some(label) = &record.label {
+        circuit.set_label(node, label);
+    }
+    if node != record.node {
+        swap_nodes(circuit, node, record.node);
+    }
+}
+
+fn remove_node(circuit: &mut Circuit, node: NodeIndex) -> NodeRecord {
+    assert_ne!(node, Circuit::meta_input(), "can't remove the meta input");
+    assert!(
+        circuit
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .next()
+            .is_none(),
+        "can't remove {:?}, it drives other gates",
+        node
+    );
+    assert!(
+        circuit
+            .graph
+            .raw_nodes()
+            .iter()
+            .all(|other| match other.weight {
+                Gate::FlipFlopIn { q, .. } => q != node,
+                _ => true,
+            }),
+        "can't remove flip-flop {:?} while it's connected",
+        node
+    );
+
+    let record = node_record(circuit, node);
+    // move the node to the end, so removing it doesn't renumber anything else.
+    let last = NodeIndex::new(circuit.graph.node_count() - 1);
+    if node != last {
+        swap_nodes(circuit, node, last);
+    }
+    circuit.remove_last_node();
+    record
+}
+
+/// Exchange the indices of two nodes, fixing up edges, ids and flip-flop references.
+fn swap_nodes(circuit: &mut Circuit, a: NodeIndex, b: NodeIndex) {
+    let rename = |n: NodeIndex| {
+        if n == a {
+            b
+        } else if n == b {
+            a
+        } else {
+            n
+        }
+    };
+
+    let mut edges = circuit
+        .graph
+        .edges_directed(a, Direction::Incoming)
+        .chain(circuit.graph.edges_directed(a, Direction::Outgoing))
+        .chain(circuit.graph.edges_directed(b, Direction::Incoming))
+        .chain(circuit.graph.edges_directed(b, Direction::Outgoing))
+        .map(|edge| edge.id())
+        .collect::<Vec<_>>();
+    edges.sort();
+    edges.dedup();
+    // removing an edge moves the last edge into its place, so go from the back.
+    let mut moved = vec![];
+    for edge in edges.into_iter().rev() {
+        let (source, target) = circuit.graph.edge_endpoints(edge).unwrap();
+        let value = circuit.graph.remove_edge(edge).unwrap();
+        moved.push((rename(source), rename(target), value));
+    }
+
+    let gate_a = circuit.graph[a];
+    circuit.graph[a] = circuit.graph[b];
+    circuit.graph[b] = gate_a;
+    for (source, target, value) in moved.into_iter().rev() {
+        circuit.graph.add_edge(source, target, value);
+    }
+
+    for gate in circuit.graph.node_weights_mut() {
+        if let Gate::FlipFlopIn { q, clock, .. } = gate {
+            *q = rename(*q);
+            *clock = rename(*clock);
+        }
+    }
+    circuit.swap_ids(a, b);
+}
+
+fn rewire(circuit: &mut Circuit, gate: NodeIndex, from: NodeIndex, to: NodeIndex) {
+    let edge = circuit
+        .graph
+        .find_edge(from, gate)
+        .unwrap_or_else(|| panic!("{:?} doesn't feed {:?}", from, gate));
+    assert!(
+        circuit.graph.find_edge(to, gate).is_none(),
+        "{:?} already feeds {:?}",
+        to,
+        gate
+    );
+    assert!(
+        !petgraph::algo::has_path_connecting(&circuit.graph, gate, to, None),
+        "feeding {:?} from {:?} would make a cycle",
+        gate,
+        to
+    );
+
+    let value = circuit.graph.remove_edge(edge).unwrap();
+    circuit.graph.add_edge(to, gate, value);
+    if let Gate::FlipFlopIn { clock, .. } = &mut circuit.graph[gate] {
+        if *clock == from {
+            *clock = to;
+        }
+    }
+    circuit.check_invariants();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(a: &Circuit, b: &Circuit) {
+        assert!(a.diff(b).is_empty());
+        assert_eq!(a.labels, b.labels);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut circuit = Circuit::new();
+        let mut history = History::new();
+        let a = history.record(&mut circuit, |c| c.add_input());
+        let b = history.record(&mut circuit, |c| c.add_input());
+        let x = history.record(&mut circuit, |c| c.add_xor(a, b));
+        let (s, carry) = history.record(&mut circuit, |c| c.half_adder(a, x));
+        let out = history.record(&mut circuit, |c| c.add_labeled_output(s, "s"));
+        let built = circuit.clone();
+
+        // a whole builder is one step.
+        let before_adder = circuit.graph.node_count() - 3;
+        assert!(history.undo(&mut circuit));
+        assert!(history.undo(&mut circuit));
+        assert_eq!(circuit.graph.node_count(), before_adder);
+        assert_eq!(circuit.labeled("s"), None);
+        assert!(history.redo(&mut circuit));
+        assert!(history.redo(&mut circuit));
+        assert!(!history.redo(&mut circuit));
+        assert_same(&circuit, &built);
+        assert_eq!(circuit.labeled("s"), Some(out));
+
+        history.set_input(&mut circuit, b, true);
+        assert!(circuit.evaluate(&[])[&out]);
+        history.undo(&mut circuit);
+        assert!(!circuit.evaluate(&[])[&out]);
+
+        // removing from the middle renumbers the last node, and undoing numbers it back.
+        let (carry_id, out_id) = (circuit.id(carry), circuit.id(out));
+        history.remove(&mut circuit, carry);
+        assert_eq!(circuit.labeled("s"), Some(carry));
+        assert_eq!(circuit.graph[carry], Gate::Output);
+        // ids follow the gates.
+        assert_eq!(circuit.node(out_id), Some(carry));
+        assert_eq!(circuit.node(carry_id), None);
+        history.undo(&mut circuit);
+        assert_same(&circuit, &built);
+        assert_eq!(circuit.node(carry_id), Some(carry));
+        assert_eq!(circuit.id(out), out_id);
+
+        history.rewire(&mut circuit, s, a, b);
+        assert_eq!(circuit.graph.find_edge(a, s), None);
+        history.undo(&mut circuit);
+        assert_same(&circuit, &built);
+
+        // a new edit forgets what was undone.
+        history.undo(&mut circuit);
+        assert!(history.can_redo());
+        history.record(&mut circuit, |c| c.add_not(a));
+        assert!(!history.can_redo());
+
+        while history.undo(&mut circuit) {}
+        assert_same(&circuit, &Circuit::new());
+    }
+
+    #[test]
+    fn test_flip_flop_undo() {
+        let mut circuit = Circuit::new();
+        let mut history = History::new();
+        let clock = history.record(&mut circuit, |c| c.add_input());
+        let other = history.record(&mut circuit, |c| c.add_input());
+        let q = history.record(&mut circuit, |c| c.add_flip_flop(false));
+        let not_q = history.record(&mut circuit, |c| c.add_not(q));
+        let out = history.record(&mut circuit, |c| c.add_output(q));
+        let ff_in = history.record(&mut circuit, |c| c.connect_flip_flop(q, not_q, clock));
+        let built = circuit.clone();
+
+        // the FlipFlopIn moves into the removed input's place.
+        history.remove(&mut circuit, other);
+        assert_eq!(
+            circuit.graph[other],
+            Gate::FlipFlopIn {
+                q,
+                clock,
+                last_clock: false
+            }
+        );
+        history.undo(&mut circuit);
+        assert_same(&circuit, &built);
+
+        history.rewire(&mut circuit, ff_in, clock, other);
+        match circuit.graph[ff_in] {
+            Gate::FlipFlopIn { clock: c, .. } => assert_eq!(c, other),
+            gate => panic!("expected FlipFlopIn, got {:?}", gate),
+        }
+        history.undo(&mut circuit);
+        assert_same(&circuit, &built);
+
+        // the flip-flop still toggles.
+        circuit.set_input(clock, true);
+        assert!(circuit.evaluate(&[])[&out]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rewire_cycle() {
+        let mut circuit = Circuit::new();
+        let mut history = History::new();
+        let a = circuit.add_input();
+        let n = circuit.add_not(a);
+        let m = circuit.add_not(n);
+        history.rewire(&mut circuit, n, a, m);
+    }
+}
@@ -0,0 +1,185 @@
+//! Bit-parallel simulation: evaluating a circuit on 64 input patterns at once, one in each bit of
+//! a `u64`.
+//!
+//! Trying a circuit on every input, a pattern at a time through `settle`, updates every gate once
+//! per pass and takes a pass per rank. A `BitParallel` compiles the circuit into a list of word
+//! operations in dependency order instead, so a single sweep of one bitwise operation per gate
+//! evaluates 64 patterns:
+//!
+//! ```ignore
+//! let mut sim = BitParallel::new(&circuit);
+//! // bit k of each input's word is its value in pattern k; here, the first 64 patterns.
+//! let outputs = sim.run(&counting(sim.inputs().len(), 0));
+//! ```
+//!
+//! Flip-flops would need every pattern to have its own history, so this is only for circuits
+//! without them. The simulator remembers the circuit's gates, so it has to be remade if they
+//! change; it doesn't touch the circuit's own signals.
+
+use super::{get_bit, Circuit, Gate};
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+
+/// How many patterns `BitParallel::run` evaluates at once.
+pub const LANES: usize = 64;
+
+/// How to work out a gate's word, from the words of the nodes driving it, by `NodeIndex`.
+#[derive(Copy, Clone, Debug)]
+enum Op {
+    Or(usize, usize),
+    And(usize, usize),
+    Xor(usize, usize),
+    Not(usize),
+    /// An Output, passing its input on.
+    Copy(usize),
+}
+
+/// Evaluates a circuit without flip-flops on `LANES` input patterns at once.
+#[derive(Clone, Debug)]
+pub struct BitParallel {
+    /// The circuit's Inputs, in the order `run` takes their words.
+    inputs: Vec<NodeIndex>,
+    /// The circuit's Outputs, in the order `run` returns their words.
+    outputs: Vec<NodeIndex>,
+    /// Every gate but the Inputs, in dependency order: (node, how to work out its word).
+    ops: Vec<(usize, Op)>,
+    /// The word on each node's output, by `NodeIndex`.
+    words: Vec<u64>,
+}
+
+impl BitParallel {
+    pub fn new(circuit: &Circuit) -> BitParallel {
+        let graph = &circuit.graph;
+        assert!(
+            graph.node_indices().all(|n| graph[n] != Gate::FlipFlop),
+            "bit-parallel simulation is only for circuits without flip-flops"
+        );
+        let of_kind = |gate: Gate| {
+            graph
+                .node_indices()
+                .filter(|n| graph[*n] == gate)
+                .collect::<Vec<_>>()
+        };
+
+        let mut ops = vec![];
+        // `update_order` is the reverse of the order signals flow in.
+        for node in circuit.update_order().into_iter().rev() {
+            let sources = graph
+                .neighbors_directed(node, Direction::Incoming)
+                .map(|source| source.index())
+                .collect::<Vec<_>>();
+            let op = match graph[node] {
+                Gate::Or => Op::Or(sources[0], sources[1]),
+                Gate::And => Op::And(sources[0], sources[1]),
+                Gate::Xor => Op::Xor(sources[0], sources[1]),
+                Gate::Not => Op::Not(sources[0]),
+                Gate::Output => Op::Copy(sources[0]),
+                Gate::Input | Gate::MetaInput => continue,
+                Gate::FlipFlop | Gate::FlipFlopIn { .. } => unreachable!(),
+            };
+            ops.push((node.index(), op));
+        }
+
+        BitParallel {
+            inputs: of_kind(Gate::Input),
+            outputs: of_kind(Gate::Output),
+            ops,
+            words: vec![0; graph.node_count()],
+        }
+    }
+
+    /// The circuit's Inputs, in the order `run` takes their words.
+    pub fn inputs(&self) -> &[NodeIndex] {
+        &self.inputs
+    }
+    /// The circuit's Outputs, in the order `run` returns their words.
+    pub fn outputs(&self) -> &[NodeIndex] {
+        &self.outputs
+    }
+
+    /// Evaluate the circuit on `LANES` patterns: bit k of each of `inputs` is that Input's value
+    /// in pattern k. Returns a word for each Output, in the same way.
+    pub fn run(&mut self, inputs: &[u64]) -> Vec<u64> {
+        assert_eq!(
+            inputs.len(),
+            self.inputs.len(),
+            "circuit has {} inputs, given {}",
+            self.inputs.len(),
+            inputs.len()
+        );
+        for (input, word) in self.inputs.iter().zip(inputs) {
+            self.words[input.index()] = *word;
+        }
+        for (node, op) in &self.ops {
+            let words = &self.words;
+            let word = match *op {
+                Op::Or(a, b) => words[a] | words[b],
+                Op::And(a, b) => words[a] & words[b],
+                Op::Xor(a, b) => words[a] ^ words[b],
+                Op::Not(a) => !words[a],
+                Op::Copy(a) => words[a],
+            };
+            self.words[*node] = word;
+        }
+        self.outputs
+            .iter()
+            .map(|output| self.words[output.index()])
+            .collect()
+    }
+}
+
+/// Input words for the `LANES` patterns counting up from `first`, for `inputs` inputs: pattern k
+/// sets input i to bit i of `first + k`.
+pub fn counting(inputs: usize, first: usize) -> Vec<u64> {
+    (0..inputs)
+        .map(|i| {
+            (0..LANES)
+                .filter(|k| get_bit(first + k, i))
+                .fold(0u64, |word, k| word | 1 << k)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_evaluate() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let product = circuit.wallace_multiplier(&a, &b);
+        for p in &product {
+            circuit.add_output(*p);
+        }
+
+        let mut sim = BitParallel::new(&circuit);
+        assert_eq!(sim.inputs(), &a.iter().chain(&b).cloned().collect::<Vec<_>>()[..]);
+        for first in (0..256).step_by(LANES) {
+            let outputs = sim.run(&counting(8, first));
+            for k in 0..LANES {
+                let pattern = first + k;
+                let assignment = sim
+                    .inputs()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, input)| (*input, get_bit(pattern, i)))
+                    .collect::<Vec<_>>();
+                let expected = circuit.evaluate(&assignment);
+                for (output, word) in sim.outputs().iter().zip(&outputs) {
+                    assert_eq!((word >> k) & 1 == 1, expected[output], "pattern {}", pattern);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "without flip-flops")]
+    fn test_flip_flops() {
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        circuit.counter(2, clock);
+        BitParallel::new(&circuit);
+    }
+}
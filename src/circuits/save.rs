@@ -0,0 +1,202 @@
+//! Saving and loading circuits.
+//!
+//! A `SavedCircuit` refers to gates by `GateId`, never by `NodeIndex`, so a loaded circuit has
+//! the same ids as the one saved, and anything kept outside the circuit keyed on ids (like an
+//! editor's gate positions) still lines up. It's plain serde data; with RON:
+//!
+//! ```ignore
+//! let source = ron::ser::to_string(&circuit.to_saved())?;
+//! let circuit = Circuit::from_saved(&ron::de::from_str(&source)?);
+//! ```
+//!
+//! Every wire's signal is saved too, so a loaded circuit carries on simulating from where the
+//! saved one was.
+
+use super::{Circuit, Gate, GateId, Value};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A `Gate`, with flip-flop references by id.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SavedGate {
+    Or,
+    And,
+    Xor,
+    Not,
+    Output,
+    Input,
+    FlipFlop,
+    FlipFlopIn {
+        q: GateId,
+        clock: GateId,
+        last_clock: Value,
+    },
+}
+
+/// A gate, and the wires going into it. Inputs and flip-flops have a wire from the MetaInput
+/// holding their value, whose id is always `GateId(0)`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SavedNode {
+    pub id: GateId,
+    pub gate: SavedGate,
+    /// (driver, signal) for each wire in.
+    pub inputs: Vec<(GateId, Value)>,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct SavedCircuit {
+    /// Every node but the MetaInput, in `NodeIndex` order.
+    pub nodes: Vec<SavedNode>,
+    pub labels: BTreeMap<String, GateId>,
+}
+
+impl Circuit {
+    pub fn to_saved(&self) -> SavedCircuit {
+        let nodes = self
+            .graph
+            .node_indices()
+            .filter(|node| *node != Circuit::meta_input())
+            .map(|node| {
+                let gate = match self.graph[node] {
+                    Gate::Or => SavedGate::Or,
+                    Gate::And => SavedGate::And,
+                    Gate::Xor => SavedGate::Xor,
+                    Gate::Not => SavedGate::Not,
+                    Gate::Output => SavedGate::Output,
+                    Gate::Input => SavedGate::Input,
+                    Gate::FlipFlop => SavedGate::FlipFlop,
+                    Gate::FlipFlopIn {
+                        q,
+                        clock,
+                        last_clock,
+                    } => SavedGate::FlipFlopIn {
+                        q: self.id(q),
+                        clock: self.id(clock),
+                        last_clock,
+                    },
+                    Gate::MetaInput => unreachable!("only node 0 is the MetaInput"),
+                };
+                // petgraph iterates edges newest first; save them oldest first.
+                let mut inputs = self
+                    .graph
+                    .edges_directed(node, Direction::Incoming)
+                    .map(|edge| (self.id(edge.source()), *edge.weight()))
+                    .collect::<Vec<_>>();
+                inputs.reverse();
+                SavedNode {
+                    id: self.id(node),
+                    gate,
+                    inputs,
+                }
+            })
+            .collect();
+        SavedCircuit {
+            nodes,
+            labels: self
+                .labels
+                .iter()
+                .map(|(label, id)| (label.clone(), *id))
+                .collect(),
+        }
+    }
+
+    /// Rebuild a saved circuit. Panics if the saved circuit is inconsistent: wires from or
+    /// labels on ids that don't exist, repeated ids, or cycles.
+    pub fn from_saved(saved: &SavedCircuit) -> Circuit {
+        let mut circuit = Circuit::new();
+        let meta = circuit.id(Circuit::meta_input());
+        let node = |circuit: &Circuit, id: GateId| {
+            circuit
+                .node(id)
+                .unwrap_or_else(|| panic!("no gate with {:?}", id))
+        };
+
+        // add every node first, since flip-flops refer to nodes added after them.
+        for saved_node in &saved.nodes {
+            assert_ne!(saved_node.id, meta, "{:?} is the MetaInput's", meta);
+            // a placeholder, until every node has an index.
+            circuit.add_node_with_id(Gate::Output, saved_node.id);
+        }
+        for saved_node in &saved.nodes {
+            let at = node(&circuit, saved_node.id);
+            circuit.graph[at] = match saved_node.gate {
+                SavedGate::Or => Gate::Or,
+                SavedGate::And => Gate::And,
+                SavedGate::Xor => Gate::Xor,
+                SavedGate::Not => Gate::Not,
+                SavedGate::Output => Gate::Output,
+                SavedGate::Input => Gate::Input,
+                SavedGate::FlipFlop => Gate::FlipFlop,
+                SavedGate::FlipFlopIn {
+                    q,
+                    clock,
+                    last_clock,
+                } => Gate::FlipFlopIn {
+                    q: node(&circuit, q),
+                    clock: node(&circuit, clock),
+                    last_clock,
+                },
+            };
+            for (source, value) in &saved_node.inputs {
+                let source = node(&circuit, *source);
+                circuit.graph.add_edge(source, at, *value);
+            }
+        }
+        for (label, id) in &saved.labels {
+            let labeled = node(&circuit, *id);
+            circuit.set_label(labeled, label);
+        }
+        circuit.check_invariants();
+        circuit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::history::History;
+
+    #[test]
+    fn test_round_trip() {
+        let mut circuit = Circuit::new();
+        let mut history = History::new();
+        let clock = history.record(&mut circuit, |c| c.add_input());
+        let a = history.record(&mut circuit, |c| c.add_input());
+        let unused = history.record(&mut circuit, |c| c.add_input());
+        let q = history.record(&mut circuit, |c| c.add_flip_flop(true));
+        let x = history.record(&mut circuit, |c| c.add_xor(a, q));
+        history.record(&mut circuit, |c| c.connect_flip_flop(q, x, clock));
+        let out = history.record(&mut circuit, |c| c.add_labeled_output(x, "x"));
+        let out_id = circuit.id(out);
+        // removing a gate moves the last one into its index, but doesn't change its id.
+        history.remove(&mut circuit, unused);
+        let out = unused;
+        circuit.set_input(a, true);
+        circuit.evaluate(&[]);
+
+        let source = ron::ser::to_string(&circuit.to_saved()).unwrap();
+        let mut loaded = Circuit::from_saved(&ron::de::from_str(&source).unwrap());
+        assert!(circuit.diff(&loaded).is_empty());
+        assert_eq!(circuit.labels, loaded.labels);
+        for node in circuit.graph.node_indices() {
+            assert_eq!(circuit.id(node), loaded.id(node));
+            assert_eq!(circuit.graph[node], loaded.graph[node]);
+        }
+        for edge in circuit.graph.edge_references() {
+            let same = loaded.graph.find_edge(edge.source(), edge.target()).unwrap();
+            assert_eq!(*edge.weight(), loaded.graph[same]);
+        }
+        assert_eq!(loaded.node(out_id), Some(out));
+
+        // new gates get fresh ids, and the loaded circuit still simulates the same.
+        let not = loaded.add_not(a);
+        assert!(circuit.node(loaded.id(not)).is_none());
+        for value in [true, false, true].iter() {
+            circuit.set_input(clock, *value);
+            loaded.set_input(clock, *value);
+            assert_eq!(circuit.evaluate(&[])[&out], loaded.evaluate(&[])[&out]);
+        }
+    }
+}
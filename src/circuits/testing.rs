@@ -0,0 +1,203 @@
+//! Checking circuits against reference models written in plain Rust.
+//!
+//! ```ignore
+//! let (sum, carry) = circuit.ripple_carry(&a, &b);
+//! let mut outputs = sum.clone();
+//! outputs.push(carry);
+//! assert_matches_reference(&circuit, &[&a, &b], &outputs, |x| x[0] + x[1]);
+//! ```
+//!
+//! Small circuits are checked on every input; bigger ones on the corner cases (everything off,
+//! everything on) and a few hundred random inputs, from a fixed seed so failures are repeatable.
+//! A failing input is shrunk, by turning off bits while it keeps failing, before it's reported.
+
+use super::{get_bit, set_bit, Circuit, Gate};
+use petgraph::graph::NodeIndex;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+/// Circuits with at most this many input bits are checked on every input.
+pub const EXHAUSTIVE_BITS: usize = 12;
+/// How many random inputs bigger circuits are checked on.
+pub const RANDOM_CASES: usize = 256;
+
+/// A circuit being checked, with Outputs added to read the nodes under test.
+struct Harness {
+    circuit: Circuit,
+    inputs: Vec<Vec<NodeIndex>>,
+    outputs: Vec<NodeIndex>,
+    order: Vec<NodeIndex>,
+    limit: usize,
+}
+
+impl Harness {
+    /// Run the circuit on the value of each input bus, returning the value of the outputs.
+    fn run(&mut self, values: &[usize]) -> usize {
+        for (bus, value) in self.inputs.iter().zip(values) {
+            for (i, input) in bus.iter().enumerate() {
+                self.circuit.set_input(*input, get_bit(*value, i));
+            }
+        }
+        self.circuit.settle(&self.order, self.limit);
+
+        let mut result = 0;
+        for (i, output) in self.outputs.iter().enumerate() {
+            result = set_bit(result, i, self.circuit.get_1_in(*output));
+        }
+        result
+    }
+}
+
+/// Check that `circuit` computes the same thing as `reference`, panicking with the smallest
+/// failing input it can find if it doesn't.
+///
+/// `inputs` are buses of Input nodes, and `outputs` any nodes, with bits ordered by magnitude.
+/// `reference` is given the value on each input bus, and returns the value it expects on
+/// `outputs`; bits above the width of `outputs` are ignored. The circuit shouldn't contain
+/// flip-flops, since their state would carry over from one input to the next.
+pub fn assert_matches_reference(
+    circuit: &Circuit,
+    inputs: &[&[NodeIndex]],
+    outputs: &[NodeIndex],
+    reference: impl Fn(&[usize]) -> usize,
+) {
+    let widths = inputs.iter().map(|bus| bus.len()).collect::<Vec<_>>();
+    let total = widths.iter().sum::<usize>();
+    assert!(
+        widths.iter().all(|w| *w < usize::BITS as usize),
+        "input buses must fit in a usize"
+    );
+    assert!(
+        outputs.len() < usize::BITS as usize,
+        "outputs must fit in a usize"
+    );
+    for node in inputs.iter().flat_map(|bus| bus.iter()) {
+        assert_eq!(circuit.graph[*node], Gate::Input, "{:?} isn't an Input", node);
+    }
+
+    let mut copy = circuit.clone();
+    let outputs = outputs
+        .iter()
+        .map(|node| match copy.graph[*node] {
+            Gate::Output => *node,
+            _ => copy.add_output(*node),
+        })
+        .collect();
+    let order = copy.update_order();
+    let limit = copy.settle_limit();
+    let mut harness = Harness {
+        circuit: copy,
+        inputs: inputs.iter().map(|bus| bus.to_vec()).collect(),
+        outputs,
+        order,
+        limit,
+    };
+    let mask = (1 << harness.outputs.len()) - 1;
+    let mut fails = |values: &[usize]| harness.run(values) != reference(values) & mask;
+
+    let cases: Vec<Vec<usize>> = if total <= EXHAUSTIVE_BITS {
+        (0..1 << total).map(|x| split(x, &widths)).collect()
+    } else {
+        let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
+        let all_on = widths.iter().map(|w| (1 << w) - 1).collect();
+        vec![vec![0; widths.len()], all_on]
+            .into_iter()
+            .chain((0..RANDOM_CASES).map(|_| {
+                widths
+                    .iter()
+                    .map(|w| rng.gen::<usize>() & ((1 << w) - 1))
+                    .collect()
+            }))
+            .collect()
+    };
+
+    if let Some(case) = cases.into_iter().find(|case| fails(case)) {
+        let case = shrink(case, &widths, &mut fails);
+        let got = harness.run(&case);
+        let expected = reference(&case) & mask;
+        let inputs = case
+            .iter()
+            .zip(&widths)
+            .map(|(v, w)| format!("{:0w$b}", v, w = w))
+            .collect::<Vec<_>>();
+        panic!(
+            "circuit doesn't match reference on inputs {}: expected {:0w$b}, got {:0w$b}",
+            inputs.join(", "),
+            expected,
+            got,
+            w = harness.outputs.len(),
+        );
+    }
+}
+
+/// Split the bits of `x` into values for buses of the given widths, lowest bus first.
+fn split(mut x: usize, widths: &[usize]) -> Vec<usize> {
+    widths
+        .iter()
+        .map(|w| {
+            let value = x & ((1 << w) - 1);
+            x >>= w;
+            value
+        })
+        .collect()
+}
+
+/// Turn off bits of a failing input, one at a time, for as long as it keeps failing.
+fn shrink(
+    mut case: Vec<usize>,
+    widths: &[usize],
+    fails: &mut impl FnMut(&[usize]) -> bool,
+) -> Vec<usize> {
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for bus in 0..case.len() {
+            for bit in 0..widths[bus] {
+                if !get_bit(case[bus], bit) {
+                    continue;
+                }
+                let mut smaller = case.clone();
+                smaller[bus] &= !(1 << bit);
+                if fails(&smaller) {
+                    case = smaller;
+                    progress = true;
+                }
+            }
+        }
+    }
+    case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adder(width: usize) -> (Circuit, Vec<NodeIndex>, Vec<NodeIndex>, Vec<NodeIndex>) {
+        let mut circuit = Circuit::new();
+        let a = (0..width).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..width).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (mut sum, carry) = circuit.ripple_carry(&a, &b);
+        sum.push(carry);
+        (circuit, a, b, sum)
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        // exhaustively...
+        let (circuit, a, b, sum) = adder(4);
+        assert_matches_reference(&circuit, &[&a, &b], &sum, |x| x[0] + x[1]);
+        // ...and randomly.
+        let (circuit, a, b, sum) = adder(16);
+        assert_matches_reference(&circuit, &[&a, &b], &sum, |x| x[0] + x[1]);
+        // the carry is cut off.
+        assert_matches_reference(&circuit, &[&a, &b], &sum[..16], |x| x[0] + x[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "on inputs 00000001, 00000000: expected 000000010, got 000000001")]
+    fn test_shrinks_failures() {
+        // wrong whenever a is odd.
+        let (circuit, a, b, sum) = adder(8);
+        assert_matches_reference(&circuit, &[&a, &b], &sum, |x| x[0] + x[1] + (x[0] & 1));
+    }
+}
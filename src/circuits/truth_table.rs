@@ -0,0 +1,357 @@
+//! Truth tables, and synthesizing minimal two-level circuits from them.
+//!
+//! Each output is minimized on its own with the Quine-McCluskey method: rows where the output is
+//! on are merged into as few, as large, product terms as possible, and the circuit is the Or of
+//! those terms. Entries can be don't-cares (`None`), for inputs that will never happen; the
+//! minimizer treats them as whichever value makes the circuit smaller.
+//!
+//! ```ignore
+//! // on for 1 and 2; 3 never happens.
+//! let table = TruthTable::from_fn(2, 1, |row| vec![[Some(false), Some(true), Some(true), None][row]]);
+//! let outputs = circuit.synthesize_table(&table, &[a, b]); // a | b
+//! ```
+
+use super::{get_bit, Circuit, Value};
+use petgraph::graph::NodeIndex;
+use std::collections::{BTreeSet, HashMap};
+
+/// Tables with more inputs than this are too slow to minimize.
+pub const MAX_INPUTS: usize = 12;
+
+/// The value of each output for every combination of inputs. Row `i` is the row where input `j`
+/// is bit `j` of `i`. `None` entries are don't-cares.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TruthTable {
+    inputs: usize,
+    outputs: usize,
+    rows: Vec<Vec<Option<Value>>>,
+}
+
+/// A product term: the rows where the inputs in `mask` have the values in `value`. Inputs outside
+/// `mask` can be anything.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Implicant {
+    pub mask: usize,
+    pub value: usize,
+}
+
+impl Implicant {
+    pub fn covers(self, row: usize) -> bool {
+        row & self.mask == self.value
+    }
+    /// How many inputs the term depends on.
+    pub fn literals(self) -> usize {
+        self.mask.count_ones() as usize
+    }
+}
+
+impl TruthTable {
+    /// A table with every output 0.
+    pub fn new(inputs: usize, outputs: usize) -> TruthTable {
+        TruthTable::from_fn(inputs, outputs, |_| vec![Some(false); outputs])
+    }
+
+    /// A table with the outputs for each row given by `f`.
+    pub fn from_fn(
+        inputs: usize,
+        outputs: usize,
+        f: impl Fn(usize) -> Vec<Option<Value>>,
+    ) -> TruthTable {
+        assert!(
+            inputs <= MAX_INPUTS,
+            "too many inputs to minimize: {}",
+            inputs
+        );
+        let rows = (0..1 << inputs)
+            .map(|row| {
+                let values = f(row);
+                assert_eq!(
+                    values.len(),
+                    outputs,
+                    "wrong number of outputs for row {}",
+                    row
+                );
+                values
+            })
+            .collect();
+        TruthTable {
+            inputs,
+            outputs,
+            rows,
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+    pub fn get(&self, row: usize, output: usize) -> Option<Value> {
+        self.rows[row][output]
+    }
+    pub fn set(&mut self, row: usize, output: usize, value: Option<Value>) {
+        self.rows[row][output] = value;
+    }
+
+    /// This table with every don't-care turned into a 0.
+    pub fn without_dont_cares(&self) -> TruthTable {
+        TruthTable::from_fn(self.inputs, self.outputs, |row| {
+            self.rows[row]
+                .iter()
+                .map(|v| Some(v.unwrap_or(false)))
+                .collect()
+        })
+    }
+
+    /// The prime implicants of `output`: the product terms which cover only rows where it's on
+    /// (or doesn't matter), and can't be made any larger.
+    fn prime_implicants(&self, output: usize) -> BTreeSet<Implicant> {
+        let full = (1 << self.inputs) - 1;
+        let mut terms = (0..self.rows.len())
+            .filter(|row| self.get(*row, output) != Some(false))
+            .map(|row| Implicant {
+                mask: full,
+                value: row,
+            })
+            .collect::<BTreeSet<_>>();
+        let mut primes = BTreeSet::new();
+        while !terms.is_empty() {
+            // merge pairs of terms differing in a single input.
+            let mut merged = BTreeSet::new();
+            let mut used = BTreeSet::new();
+            for a in &terms {
+                for bit in 0..self.inputs {
+                    let b = Implicant {
+                        mask: a.mask,
+                        value: a.value ^ (1 << bit),
+                    };
+                    if a.mask & (1 << bit) != 0 && terms.contains(&b) {
+                        used.insert(*a);
+                        merged.insert(Implicant {
+                            mask: a.mask & !(1 << bit),
+                            value: a.value & !(1 << bit),
+                        });
+                    }
+                }
+            }
+            primes.extend(terms.difference(&used));
+            terms = merged;
+        }
+        primes
+    }
+
+    /// The product terms of a minimal sum-of-products for `output`. Prime implicants that are the
+    /// only cover for some row go in first, then the rest greedily, largest first; so the result
+    /// isn't always the smallest possible, but it's close.
+    pub fn minimize(&self, output: usize) -> Vec<Implicant> {
+        let primes = self.prime_implicants(output);
+        let mut uncovered = (0..self.rows.len())
+            .filter(|row| self.get(*row, output) == Some(true))
+            .collect::<BTreeSet<_>>();
+        let mut result = vec![];
+
+        for row in uncovered.clone() {
+            let mut covering = primes.iter().filter(|p| p.covers(row));
+            if let (Some(only), None) = (covering.next(), covering.next()) {
+                if !result.contains(only) {
+                    result.push(*only);
+                }
+            }
+        }
+        uncovered.retain(|row| !result.iter().any(|p| p.covers(*row)));
+
+        while !uncovered.is_empty() {
+            let best = *primes
+                .iter()
+                .max_by_key(|p| {
+                    let covered = uncovered.iter().filter(|row| p.covers(**row)).count();
+                    (covered, usize::MAX - p.literals())
+                })
+                .unwrap();
+            uncovered.retain(|row| !best.covers(*row));
+            result.push(best);
+        }
+        result.sort();
+        result
+    }
+}
+
+impl Circuit {
+    /// Build a circuit computing each output of `table` from `inputs`, as a minimized sum of
+    /// products. Terms shared between outputs are only built once. Returns a node per output.
+    pub fn synthesize_table(&mut self, table: &TruthTable, inputs: &[NodeIndex]) -> Vec<NodeIndex> {
+        assert_eq!(table.inputs(), inputs.len());
+        assert!(!inputs.is_empty(), "constant tables aren't supported");
+        let mut nots = HashMap::new();
+        let mut products = HashMap::new();
+        (0..table.outputs())
+            .map(|output| {
+                let terms = table
+                    .minimize(output)
+                    .into_iter()
+                    .map(|term| {
+                        if let Some(node) = products.get(&term) {
+                            return *node;
+                        }
+                        let node = self.add_product(term, inputs, &mut nots);
+                        products.insert(term, node);
+                        node
+                    })
+                    .collect::<Vec<_>>();
+                if terms.is_empty() {
+                    // never on: a & !a.
+                    let not = self.add_literal(inputs, 0, false, &mut nots);
+                    self.add_and(inputs[0], not)
+                } else {
+                    self.any(&terms)
+                }
+            })
+            .collect()
+    }
+
+    /// Input `i`, or its negation if `value` is off. Negations are shared through `nots`.
+    fn add_literal(
+        &mut self,
+        inputs: &[NodeIndex],
+        i: usize,
+        value: Value,
+        nots: &mut HashMap<usize, NodeIndex>,
+    ) -> NodeIndex {
+        if value {
+            inputs[i]
+        } else {
+            *nots.entry(i).or_insert_with(|| self.add_not(inputs[i]))
+        }
+    }
+
+    /// Build a tree of `And` gates for a product term.
+    fn add_product(
+        &mut self,
+        term: Implicant,
+        inputs: &[NodeIndex],
+        nots: &mut HashMap<usize, NodeIndex>,
+    ) -> NodeIndex {
+        if term.mask == 0 {
+            // always on: a | !a.
+            let not = self.add_literal(inputs, 0, false, nots);
+            return self.add_or(inputs[0], not);
+        }
+        let mut literals = (0..inputs.len())
+            .filter(|i| get_bit(term.mask, *i))
+            .map(|i| self.add_literal(inputs, i, get_bit(term.value, i), nots))
+            .collect::<Vec<_>>();
+        while literals.len() > 1 {
+            literals = literals
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => self.add_and(*a, *b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        literals[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::Gate;
+
+    /// A seven-segment decoder for the digits 0-9; rows 10-15 don't matter.
+    fn seven_segment() -> TruthTable {
+        let segments = [
+            0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+            0b1111111, 0b1101111,
+        ];
+        TruthTable::from_fn(4, 7, |row| {
+            (0..7)
+                .map(|s| segments.get(row).map(|bits| get_bit(*bits, s)))
+                .collect()
+        })
+    }
+
+    fn gates(circuit: &Circuit) -> usize {
+        circuit
+            .graph
+            .node_indices()
+            .filter(|n| !matches!(circuit.graph[*n], Gate::MetaInput | Gate::Input))
+            .count()
+    }
+
+    /// Check the synthesized circuit against every row of the table that matters.
+    fn check(table: &TruthTable) -> usize {
+        let mut circuit = Circuit::new();
+        let inputs = (0..table.inputs())
+            .map(|_| circuit.add_input())
+            .collect::<Vec<_>>();
+        let outputs = circuit.synthesize_table(table, &inputs);
+        let size = gates(&circuit);
+        let outputs = outputs
+            .into_iter()
+            .map(|node| circuit.add_output(node))
+            .collect::<Vec<_>>();
+        for row in 0..1 << table.inputs() {
+            let assignment = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, input)| (*input, get_bit(row, i)))
+                .collect::<Vec<_>>();
+            let values = circuit.evaluate(&assignment);
+            for (o, output) in outputs.iter().enumerate() {
+                if let Some(expected) = table.get(row, o) {
+                    assert_eq!(values[output], expected, "row {} output {}", row, o);
+                }
+            }
+        }
+        size
+    }
+
+    #[test]
+    fn test_minimize() {
+        // on for 1, 2 and 3: a | b.
+        let table = TruthTable::from_fn(2, 1, |row| vec![Some(row != 0)]);
+        assert_eq!(
+            table.minimize(0),
+            vec![
+                Implicant { mask: 1, value: 1 },
+                Implicant { mask: 2, value: 2 }
+            ]
+        );
+        // with 3 a don't-care, the same.
+        let mut table = table;
+        table.set(3, 0, None);
+        assert_eq!(table.minimize(0).len(), 2);
+        // and with 3 off, a ^ b needs both full rows.
+        let table = table.without_dont_cares();
+        assert!(table.minimize(0).iter().all(|term| term.literals() == 2));
+    }
+
+    #[test]
+    fn test_synthesize_table() {
+        let table = TruthTable::from_fn(3, 3, |row| {
+            vec![
+                Some(row.count_ones() >= 2),
+                Some(row.count_ones() % 2 == 1),
+                Some(false),
+            ]
+        });
+        check(&table);
+        check(&TruthTable::from_fn(2, 1, |_| vec![Some(true)]));
+    }
+
+    #[test]
+    fn test_dont_cares() {
+        let table = seven_segment();
+        let with = check(&table);
+        let without = check(&table.without_dont_cares());
+        assert!(
+            with < without,
+            "{} gates with don't-cares, {} without",
+            with,
+            without
+        );
+    }
+}
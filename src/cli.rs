@@ -0,0 +1,205 @@
+//! Command line arguments shared by the sketches, so trying a different seed or size doesn't
+//! mean editing a constant and recompiling:
+//!
+//! ```text
+//! cargo run --release --example bouncing_2 -- --seed 7 --count 60 --plots
+//! ```
+//!
+//! Every sketch takes `--seed`, `--count`, `--width` and `--height`, `--capture DIR` to save each
+//! frame there as a PNG, and `--record FILE` and `--replay FILE` to record what's done to it and
+//! play it back; each decides what, if anything, its seed and count are for, and can add flags of
+//! its own.
+
+use clap::{App as ClapApp, Arg, ArgMatches};
+use std::path::Path;
+use std::str::FromStr;
+
+pub struct Cli {
+    app: ClapApp<'static, 'static>,
+}
+
+impl Cli {
+    /// The shared arguments, for the sketch called `name`.
+    pub fn new(name: &'static str) -> Cli {
+        let app = ClapApp::new(name)
+            .arg(number::<u64>("seed", "Seed for the random numbers"))
+            .arg(number::<usize>("count", "How many things to make"))
+            .arg(number::<u32>("width", "Window width, in points").requires("height"))
+            .arg(number::<u32>("height", "Window height, in points").requires("width"))
+            .arg(
+                Arg::with_name("capture")
+                    .long("capture")
+                    .value_name("DIR")
+                    .help("Save every frame as a PNG in DIR"),
+            )
+            .arg(
+                Arg::with_name("record")
+                    .long("record")
+                    .value_name("FILE")
+                    .help("Record the mouse and keys to FILE, to play back with --replay"),
+            )
+            .arg(
+                Arg::with_name("replay")
+                    .long("replay")
+                    .value_name("FILE")
+                    .conflicts_with("record")
+                    .help("Play back the mouse and keys recorded in FILE"),
+            );
+        Cli { app }
+    }
+
+    /// With a switch of the sketch's own, `--name`.
+    pub fn flag(self, name: &'static str, help: &'static str) -> Cli {
+        let arg = Arg::with_name(name).long(name).help(help);
+        Cli {
+            app: self.app.arg(arg),
+        }
+    }
+
+    /// With an option of the sketch's own, `--name VALUE`, parsed as a `T`.
+    pub fn option<T: FromStr>(self, name: &'static str, help: &'static str) -> Cli {
+        Cli {
+            app: self.app.arg(number::<T>(name, help)),
+        }
+    }
+
+    /// The program's arguments; prints the usage and exits if they're wrong or `--help`'s given.
+    pub fn parse(self) -> Args {
+        Args {
+            matches: self.app.get_matches(),
+        }
+    }
+
+    /// `args`, without the program's name, or what's wrong with them.
+    pub fn parse_from(self, args: &[&str]) -> Result<Args, String> {
+        let args = std::iter::once(self.app.get_name().to_string())
+            .chain(args.iter().map(|arg| arg.to_string()))
+            .collect::<Vec<_>>();
+        let matches = self.app.get_matches_from_safe(args);
+        matches
+            .map(|matches| Args { matches })
+            .map_err(|e| e.message)
+    }
+}
+
+// an option taking a `T`, checked as it's parsed.
+fn number<T: FromStr>(name: &'static str, help: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .takes_value(true)
+        .help(help)
+        .validator(|value| match value.parse::<T>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("{} isn't a valid value", value)),
+        })
+}
+
+#[derive(Clone, Debug)]
+pub struct Args {
+    matches: ArgMatches<'static>,
+}
+
+impl Args {
+    pub fn seed(&self) -> Option<u64> {
+        self.value("seed")
+    }
+
+    /// `--count`, or `default` if it wasn't given.
+    pub fn count(&self, default: usize) -> usize {
+        self.value("count").unwrap_or(default)
+    }
+
+    /// The window size, if it was given.
+    pub fn size(&self) -> Option<(u32, u32)> {
+        Some((self.value("width")?, self.value("height")?))
+    }
+
+    /// Where to save frames, if anywhere; `Capture::from_args` saves them there.
+    pub fn capture(&self) -> Option<&Path> {
+        self.matches.value_of("capture").map(Path::new)
+    }
+
+    /// Where to record input, if anywhere; `Replay::from_args` records it.
+    pub fn record(&self) -> Option<&Path> {
+        self.matches.value_of("record").map(Path::new)
+    }
+
+    /// The recording to play back, if any.
+    pub fn replay(&self) -> Option<&Path> {
+        self.matches.value_of("replay").map(Path::new)
+    }
+
+    /// Whether the sketch's switch `name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.matches.is_present(name)
+    }
+
+    /// The sketch's option `name`, if it was given.
+    pub fn value<T: FromStr>(&self, name: &str) -> Option<T> {
+        // already checked by its validator.
+        self.matches.value_of(name)?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli() -> Cli {
+        Cli::new("test")
+            .flag("plots", "Start with the plots shown")
+            .option::<f32>("gravity", "How hard things fall")
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = cli().parse_from(&[]).unwrap();
+        assert_eq!(args.seed(), None);
+        assert_eq!(args.count(30), 30);
+        assert_eq!(args.size(), None);
+        assert!(args.capture().is_none());
+        assert!(args.record().is_none());
+        assert!(args.replay().is_none());
+        assert!(!args.flag("plots"));
+
+        let args = cli()
+            .parse_from(&[
+                "--seed",
+                "7",
+                "--count",
+                "60",
+                "--width",
+                "800",
+                "--height",
+                "600",
+                "--plots",
+                "--gravity",
+                "2.5",
+                "--capture",
+                "frames",
+                "--record",
+                "demo.ron",
+            ])
+            .unwrap();
+        assert_eq!(args.seed(), Some(7));
+        assert_eq!(args.count(30), 60);
+        assert_eq!(args.size(), Some((800, 600)));
+        assert_eq!(args.capture(), Some(Path::new("frames")));
+        assert_eq!(args.record(), Some(Path::new("demo.ron")));
+        assert!(args.flag("plots"));
+        assert_eq!(args.value::<f32>("gravity"), Some(2.5));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(cli().parse_from(&["--count", "lots"]).is_err());
+        assert!(cli().parse_from(&["--seed", "-1"]).is_err());
+        assert!(cli().parse_from(&["--gravity", "down"]).is_err());
+        // a width needs a height.
+        assert!(cli().parse_from(&["--width", "800"]).is_err());
+        assert!(cli().parse_from(&["--colour"]).is_err());
+        assert!(cli()
+            .parse_from(&["--record", "a.ron", "--replay", "b.ron"])
+            .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Sketch constants kept in a TOML file instead of the code, and read again whenever the file's
+//! saved, so they can be tuned in a text editor while the sketch runs.
+//!
+//! A sketch describes its constants as a struct, defaulting any the file leaves out:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! #[serde(default)]
+//! struct Constants {
+//!     gravity: f32,
+//! }
+//! // when building the model:
+//! let config = Config::<Constants>::load("bouncing_2.toml");
+//! // every update:
+//! if model.config.poll() {
+//!     model.world.gravity = vec2(0.0, -model.config.get().gravity);
+//! }
+//! ```
+//!
+//! A file that doesn't parse is reported, and the last values that did are kept, so saving a
+//! half-finished edit doesn't stop the sketch.
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+// how long the file has to be left alone before it's read; editors often save in a few steps.
+const SETTLE: Duration = Duration::from_millis(100);
+
+pub struct Config<T> {
+    // as given, to read, and in full, to pick out the file's events from its neighbours'.
+    path: PathBuf,
+    watched: PathBuf,
+    value: T,
+    /// What was wrong with the file the last time it was read, if anything.
+    pub error: Option<String>,
+    // dropping the watcher stops it. The file's directory is watched rather than the file, since
+    // editors often save by replacing it. None if it couldn't be.
+    watcher: Option<(RecommendedWatcher, Receiver<DebouncedEvent>)>,
+}
+
+impl<T> Config<T>
+where
+    T: DeserializeOwned + Default,
+{
+    /// The constants in the file at `path`, and a watch on it. If it doesn't exist yet, or doesn't
+    /// parse, the constants start at their defaults.
+    pub fn load(path: impl AsRef<Path>) -> Config<T> {
+        let path = path.as_ref().to_path_buf();
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let watched = dir.join(path.file_name().expect("a config has to be a file"));
+        let watcher = watch(&dir)
+            .map_err(|e| eprintln!("can't watch {} for changes: {}", dir.display(), e))
+            .ok();
+        let mut config = Config {
+            path,
+            watched,
+            value: T::default(),
+            error: None,
+            watcher,
+        };
+        config.reload();
+        config
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the file again, returning whether its constants were taken.
+    pub fn reload(&mut self) -> bool {
+        let read = fs::read_to_string(&self.path).map_err(|e| e.to_string());
+        match read.and_then(|text| parse(&text)) {
+            Ok(value) => {
+                self.value = value;
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                eprintln!("{}: {}", self.path.display(), e);
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    /// Read the file again if it's been saved since the last poll, returning whether there are new
+    /// constants.
+    pub fn poll(&mut self) -> bool {
+        // counted, rather than stopping at the first, to use them all up.
+        let saved = match &self.watcher {
+            Some((_, events)) => events
+                .try_iter()
+                .filter(|event| touches(event, &self.watched))
+                .count(),
+            None => 0,
+        };
+        saved > 0 && self.reload()
+    }
+}
+
+fn watch(dir: &Path) -> notify::Result<(RecommendedWatcher, Receiver<DebouncedEvent>)> {
+    let (send, events) = channel();
+    let mut watcher = watcher(send, SETTLE)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok((watcher, events))
+}
+
+// whether `event` leaves new contents at `path`.
+fn touches(event: &DebouncedEvent, path: &Path) -> bool {
+    match event {
+        DebouncedEvent::Create(at) | DebouncedEvent::Write(at) => at == path,
+        DebouncedEvent::Rename(_, to) => to == path,
+        _ => false,
+    }
+}
+
+fn parse<T: DeserializeOwned>(text: &str) -> Result<T, String> {
+    toml::from_str(text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(default)]
+    struct Constants {
+        gravity: f32,
+        count: usize,
+        color: [u8; 3],
+    }
+
+    impl Default for Constants {
+        fn default() -> Self {
+            Constants {
+                gravity: 1.0,
+                count: 10,
+                color: [255, 0, 0],
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let constants = parse::<Constants>("gravity = 2.5\ncolor = [0, 110, 255]").unwrap();
+        assert_eq!(
+            constants,
+            Constants {
+                gravity: 2.5,
+                count: 10,
+                color: [0, 110, 255],
+            }
+        );
+        assert_eq!(parse::<Constants>("").unwrap(), Constants::default());
+        assert!(parse::<Constants>("gravity = \"down\"").is_err());
+        assert!(parse::<Constants>("gravity = ").is_err());
+    }
+
+    #[test]
+    fn test_reload() {
+        let path = std::env::temp_dir().join(format!("config_test_{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+        // missing, it starts at the defaults.
+        let mut config = Config::<Constants>::load(&path);
+        assert_eq!(*config.get(), Constants::default());
+        assert!(config.error.is_some());
+
+        fs::write(&path, "count = 3").unwrap();
+        assert!(config.reload());
+        assert_eq!(config.get().count, 3);
+        assert!(config.error.is_none());
+        // a bad edit keeps what was there.
+        fs::write(&path, "count = -").unwrap();
+        assert!(!config.reload());
+        assert_eq!(config.get().count, 3);
+        assert!(config.error.is_some());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Isolines by marching squares: the lines along which a grid of numbers crosses some threshold,
+//! like the contours on a map, or isotherms over a heat map.
+//!
+//! Each square of four neighbouring cells' centers is crossed wherever its corners fall either
+//! side of the threshold, at a point found by interpolating between them; the pieces from every
+//! square are then joined up into lines. Where a square's opposite corners are both above and the
+//! other two both below, the average of all four decides whether the high corners join up.
+
+use crate::grid::FloatGrid;
+use nannou::geom::{vec2, Vector2};
+use std::collections::BTreeMap;
+
+/// A line along which a grid crosses a threshold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polyline {
+    /// In cells, from the bottom left corner of the grid, with the cells above the threshold on
+    /// the left going along it. A closed line ends where it starts.
+    pub points: Vec<Vector2>,
+    /// Whether it goes all the way round, rather than running off the grid's edges.
+    pub closed: bool,
+}
+
+// an edge between two neighbouring cells' centers: the one from (x, y) to the right, or up.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Edge {
+    Across(usize, usize),
+    Up(usize, usize),
+}
+
+/// Every line along which `field` crosses `threshold`. Lines stop at the centers of the outermost
+/// cells, even if the edges wrap.
+pub fn contour(field: &FloatGrid, threshold: f32) -> Vec<Polyline> {
+    // where each piece of line starts, and where it goes next.
+    let mut next = BTreeMap::new();
+    for y in 0..field.height.saturating_sub(1) {
+        for x in 0..field.width.saturating_sub(1) {
+            // anticlockwise from the bottom left, with the edge from each corner to the next.
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let edges = [
+                Edge::Across(x, y),
+                Edge::Up(x + 1, y),
+                Edge::Across(x, y + 1),
+                Edge::Up(x, y),
+            ];
+            let values = corners
+                .iter()
+                .map(|&(x, y)| *field.get(x, y))
+                .collect::<Vec<_>>();
+            let above = values.iter().map(|&v| v > threshold).collect::<Vec<_>>();
+            // lines start on the edges going from above to below, and end on those going back,
+            // so they've the high corners on their left.
+            let starts = (0..4)
+                .filter(|&i| above[i] && !above[(i + 1) % 4])
+                .collect::<Vec<_>>();
+            match starts.len() {
+                0 => (),
+                1 => {
+                    let end = (0..4).find(|&i| !above[i] && above[(i + 1) % 4]).unwrap();
+                    next.insert(edges[starts[0]], edges[end]);
+                }
+                _ => {
+                    // a saddle: if the middle's high too, each line cuts off the low corner it
+                    // starts at, and turns on to the next edge; otherwise it cuts off the high
+                    // corner behind it, and turns back on to the previous one.
+                    let middle = values.iter().sum::<f32>() / 4.0;
+                    let turn = if middle > threshold { 1 } else { 3 };
+                    for &start in &starts {
+                        next.insert(edges[start], edges[(start + turn) % 4]);
+                    }
+                }
+            }
+        }
+    }
+
+    let crossing = |edge: Edge| {
+        let (a, b) = match edge {
+            Edge::Across(x, y) => ((x, y), (x + 1, y)),
+            Edge::Up(x, y) => ((x, y), (x, y + 1)),
+        };
+        let (va, vb) = (field.get(a.0, a.1), field.get(b.0, b.1));
+        let t = (threshold - va) / (vb - va);
+        let center = |(x, y): (usize, usize)| vec2(x as f32 + 0.5, y as f32 + 0.5);
+        center(a) + (center(b) - center(a)) * t
+    };
+    let mut lines = vec![];
+    // lines running off the edges first, from wherever nothing leads into them, then loops.
+    let ends = next.values().cloned().collect::<Vec<_>>();
+    let mut open = next
+        .keys()
+        .filter(|edge| !ends.contains(edge))
+        .cloned()
+        .collect::<Vec<_>>();
+    open.reverse();
+    while let Some(&start) = open.last().or_else(|| next.keys().next()) {
+        open.pop();
+        let mut edge = start;
+        let mut points = vec![crossing(edge)];
+        while let Some(to) = next.remove(&edge) {
+            edge = to;
+            points.push(crossing(edge));
+        }
+        lines.push(Polyline {
+            points,
+            closed: edge == start,
+        });
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// A grid of `f` at each cell's center.
+    fn field(width: usize, height: usize, f: impl Fn(f32, f32) -> f32) -> FloatGrid {
+        let mut field = FloatGrid::new(width, height, 0.0);
+        for y in 0..height {
+            for x in 0..width {
+                field.set(x, y, f(x as f32 + 0.5, y as f32 + 0.5));
+            }
+        }
+        field
+    }
+
+    #[test]
+    fn test_circle() {
+        // a hill, cut round a circle of radius 6.
+        let hill = field(20, 20, |x, y| {
+            10.0 - (vec2(x, y) - vec2(10.0, 10.0)).magnitude()
+        });
+        let lines = contour(&hill, 4.0);
+        assert_eq!(lines.len(), 1);
+        let circle = &lines[0];
+        assert!(circle.closed);
+        assert_eq!(circle.points.first(), circle.points.last());
+        for point in &circle.points {
+            let radius = (*point - vec2(10.0, 10.0)).magnitude();
+            assert!((radius - 6.0).abs() < 0.1, "{:?}", point);
+        }
+        // going anticlockwise, with the top of the hill on the left.
+        let area = circle
+            .points
+            .windows(2)
+            .map(|pair| pair[0].perp_dot(pair[1]))
+            .sum::<f32>()
+            / 2.0;
+        assert!((area - PI * 36.0).abs() < 2.0, "{}", area);
+
+        // nothing's that high.
+        assert!(contour(&hill, 20.0).is_empty());
+    }
+
+    #[test]
+    fn test_open() {
+        // a slope, crossed by a straight line right across it, the high side on the left.
+        let slope = field(8, 5, |x, _| x);
+        let lines = contour(&slope, 3.2);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].closed);
+        let points = &lines[0].points;
+        assert_eq!(points.len(), 5);
+        assert!((points[0] - vec2(3.2, 4.5)).magnitude() < 1e-5);
+        assert!((points[4] - vec2(3.2, 0.5)).magnitude() < 1e-5);
+
+        // two hills, each cut round separately, and a saddle between them.
+        let hills = field(30, 20, |x, y| {
+            let d = |cx| (vec2(x, y) - vec2(cx, 10.0)).magnitude();
+            (4.0 - d(9.0)).max(4.0 - d(21.0))
+        });
+        let lines = contour(&hills, 1.0);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.closed));
+        let lines = contour(&hills, -3.0);
+        assert_eq!(lines.len(), 1, "{:?}", lines);
+
+        // a single saddle, its high corners joined up through the middle only if it's high too.
+        let mut saddle = FloatGrid::new(2, 2, 0.0);
+        saddle.set(0, 0, 1.0);
+        saddle.set(1, 1, 1.0);
+        for &(threshold, joined) in &[(0.6, false), (0.4, true)] {
+            let lines = contour(&saddle, threshold);
+            assert_eq!(lines.len(), 2);
+            for line in &lines {
+                // each cuts off one corner; a low one if the high ones are joined.
+                let middle = (line.points[0] + line.points[1]) / 2.0;
+                let value = *saddle.get(middle.x as usize, middle.y as usize);
+                assert_eq!(value == 0.0, joined, "{} {:?}", threshold, line);
+            }
+        }
+    }
+}
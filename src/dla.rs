@@ -0,0 +1,188 @@
+//! Diffusion-limited aggregation: particles wander at random until they bump into a cluster, and
+//! stick to it, growing it into branching, frost-like fractals.
+//!
+//! Tips reach out further than the hollows between them, so they're where wandering particles
+//! usually hit first, and the branches keep branching. Walkers start just outside the cluster, and
+//! are started again if they stray far from it. Checking whether a walker touches the cluster only
+//! looks at the particles near it, through a `SpatialHash`, and walkers with nothing close by take
+//! one long stride rather than many little steps.
+
+use crate::physics::spatial_hash::SpatialHash;
+use nannou::geom::{vec2, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+/// A particle stuck to the cluster.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Deposit {
+    pub pos: Vector2,
+    /// How many steps in it stuck.
+    pub step: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Aggregate {
+    /// How likely a walker is to stick each time it touches the cluster; less than 1 lets walkers
+    /// get further into the hollows, making the cluster denser.
+    pub stickiness: f32,
+    radius: f32,
+    deposits: Vec<Deposit>,
+    hash: SpatialHash,
+    walkers: Vec<Vector2>,
+    // how far the furthest deposit is from the seed.
+    extent: f32,
+    steps: usize,
+    rng: XorShiftRng,
+}
+
+impl Aggregate {
+    /// A cluster of a single particle of `radius` at the origin, with `walkers` particles
+    /// wandering around it.
+    pub fn new(radius: f32, walkers: usize, seed: u64) -> Aggregate {
+        assert!(radius > 0.0, "particles must have a size");
+        let seed_pos = vec2(0.0, 0.0);
+        let mut aggregate = Aggregate {
+            stickiness: 1.0,
+            radius,
+            deposits: vec![Deposit {
+                pos: seed_pos,
+                step: 0,
+            }],
+            // cells as big as the distance at which particles touch.
+            hash: SpatialHash::new(radius * 2.0, &[seed_pos]),
+            walkers: vec![],
+            extent: 0.0,
+            steps: 0,
+            rng: SeedableRng::seed_from_u64(seed),
+        };
+        aggregate.walkers = (0..walkers).map(|_| aggregate.spawn()).collect();
+        aggregate
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Every particle stuck so far, in the order they stuck.
+    pub fn deposits(&self) -> &[Deposit] {
+        &self.deposits
+    }
+
+    pub fn walkers(&self) -> &[Vector2] {
+        &self.walkers
+    }
+
+    /// How far the furthest particle in the cluster is from the seed.
+    pub fn extent(&self) -> f32 {
+        self.extent
+    }
+
+    /// How many steps have been taken.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Somewhere random on a circle just outside the cluster.
+    fn spawn(&mut self) -> Vector2 {
+        let angle = self.rng.gen::<f32>() * 2.0 * std::f32::consts::PI;
+        vec2(angle.cos(), angle.sin()) * (self.extent + self.radius * 5.0)
+    }
+
+    /// Move every walker once, sticking those that touch the cluster.
+    pub fn step(&mut self) {
+        self.steps += 1;
+        let touching = self.radius * 2.0;
+        // with nothing within this, a walker can stride most of the way without touching anything.
+        let clear = self.radius * 8.0;
+        let lost = self.extent * 2.0 + self.radius * 20.0;
+        for i in 0..self.walkers.len() {
+            let start = self.walkers[i];
+            if !self.hash.within(start, touching).is_empty() {
+                // something stuck right where it was.
+                self.walkers[i] = self.spawn();
+                continue;
+            }
+            let stride = if self.hash.within(start, clear).is_empty() {
+                clear - touching
+            } else {
+                self.radius / 2.0
+            };
+            let angle = self.rng.gen::<f32>() * 2.0 * std::f32::consts::PI;
+            let direction = vec2(angle.cos(), angle.sin());
+
+            // how far along its stride it first touches the cluster, if it does.
+            let hit = self
+                .hash
+                .within(start, stride + touching)
+                .into_iter()
+                .filter_map(|j| {
+                    let to = self.deposits[j].pos - start;
+                    let along = to.dot(direction);
+                    let gap = touching * touching - (to.magnitude2() - along * along);
+                    if gap < 0.0 {
+                        return None;
+                    }
+                    let distance = along - gap.sqrt();
+                    if (0.0..=stride).contains(&distance) {
+                        Some(distance)
+                    } else {
+                        None
+                    }
+                })
+                .fold(None, |nearest: Option<f32>, distance| {
+                    Some(nearest.map_or(distance, |nearest| nearest.min(distance)))
+                });
+            let end = start + direction * stride;
+            self.walkers[i] = match hit {
+                Some(distance) if self.rng.gen::<f32>() < self.stickiness => {
+                    let stuck = start + direction * distance;
+                    self.hash.insert(stuck);
+                    self.deposits.push(Deposit {
+                        pos: stuck,
+                        step: self.steps,
+                    });
+                    self.extent = self.extent.max(stuck.magnitude());
+                    self.spawn()
+                }
+                // bounced off.
+                Some(_) => start,
+                None if end.magnitude() > lost => self.spawn(),
+                None => end,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grow() {
+        let mut aggregate = Aggregate::new(1.0, 50, 12345);
+        while aggregate.deposits().len() < 300 {
+            aggregate.step();
+        }
+        let deposits = aggregate.deposits();
+        for (i, deposit) in deposits.iter().enumerate().skip(1) {
+            // just touching something that was already there.
+            let distances = deposits[..i]
+                .iter()
+                .map(|other| (other.pos - deposit.pos).magnitude());
+            let nearest = distances.fold(f32::MAX, f32::min);
+            assert!((nearest - 2.0).abs() < 1e-3, "{} {}", i, nearest);
+            assert!(deposit.step >= deposits[i - 1].step);
+        }
+        let extent = deposits
+            .iter()
+            .map(|d| d.pos.magnitude())
+            .fold(0.0, f32::max);
+        assert_eq!(aggregate.extent(), extent);
+        // branching out, rather than packing into a disc: a disc of 300 would have a radius of
+        // about 17.
+        assert!(extent > 30.0, "{}", extent);
+        for walker in aggregate.walkers() {
+            assert!(walker.magnitude() <= extent * 2.0 + 21.0);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Easing curves: ways of getting from 0 to 1 as `t` goes from 0 to 1, starting and stopping
+//! gently, overshooting, or bouncing, rather than at a constant rate.
+//!
+//! Each curve eases in (starts slowly), eases out (stops slowly, the same curve backwards), or
+//! both, each half squeezed into half the time.
+
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Curve {
+    Linear,
+    /// A quarter of a sine wave.
+    Sine,
+    Quad,
+    Cubic,
+    /// Doubling every tenth of the way.
+    Expo,
+    /// Winding up with a few swings either side of the start, like a plucked spring.
+    Elastic,
+    /// Bouncing off the start a few times, each higher than the last.
+    Bounce,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mode {
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Ease {
+    pub curve: Curve,
+    pub mode: Mode,
+}
+
+impl Ease {
+    pub const LINEAR: Ease = Ease::new(Curve::Linear, Mode::In);
+
+    pub const fn new(curve: Curve, mode: Mode) -> Ease {
+        Ease { curve, mode }
+    }
+
+    /// How far along the curve is at `t`, held at 0 before 0 and 1 after 1. Elastic curves go a
+    /// little outside 0 to 1 on the way.
+    pub fn apply(self, t: f32) -> f32 {
+        // (NaN comes out as 0.)
+        let t = t.max(0.0).min(1.0);
+        let curve = |t: f32| ease_in(self.curve, t);
+        match self.mode {
+            Mode::In => curve(t),
+            Mode::Out => 1.0 - curve(1.0 - t),
+            Mode::InOut if t < 0.5 => curve(2.0 * t) / 2.0,
+            Mode::InOut => 1.0 - curve(2.0 - 2.0 * t) / 2.0,
+        }
+    }
+}
+
+fn ease_in(curve: Curve, t: f32) -> f32 {
+    match curve {
+        Curve::Linear => t,
+        Curve::Sine => 1.0 - (t * PI / 2.0).cos(),
+        Curve::Quad => t * t,
+        Curve::Cubic => t * t * t,
+        // exactly 0 at the start, rather than 1/1024.
+        Curve::Expo if t <= 0.0 => 0.0,
+        Curve::Expo => 2f32.powf(10.0 * t - 10.0),
+        Curve::Elastic if t <= 0.0 || t >= 1.0 => t,
+        Curve::Elastic => {
+            -(2f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+        }
+        Curve::Bounce => 1.0 - bounce_out(1.0 - t),
+    }
+}
+
+// falling from 0 to 1 under gravity, and bouncing off 1 three times, each a quarter as high.
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984_375
+    }
+}
+
+/// `from` at `t` = 0 to `to` at `t` = 1, eased along the way.
+pub fn map_eased<T>(t: f32, from: T, to: T, ease: Ease) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    from + (to - from) * ease.apply(t)
+}
+
+/// A triangle wave: up from 0 to 1 as `t` goes from 0 to 1/2, then back down to 0 at 1, and so
+/// on, for easing back and forth forever.
+pub fn ping_pong(t: f32) -> f32 {
+    1.0 - (2.0 * t.rem_euclid(1.0) - 1.0).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+
+    const CURVES: [Curve; 7] = [
+        Curve::Linear,
+        Curve::Sine,
+        Curve::Quad,
+        Curve::Cubic,
+        Curve::Expo,
+        Curve::Elastic,
+        Curve::Bounce,
+    ];
+
+    #[test]
+    fn test_ends() {
+        for curve in &CURVES {
+            for mode in &[Mode::In, Mode::Out, Mode::InOut] {
+                let ease = Ease::new(*curve, *mode);
+                assert!(ease.apply(0.0).abs() < 1e-6, "{:?}", ease);
+                assert!((ease.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", ease);
+                assert_eq!(ease.apply(-1.0), ease.apply(0.0));
+                assert_eq!(ease.apply(f32::NAN), ease.apply(0.0));
+                // in and out are the same curve turned round, and in-out goes through the middle.
+                let (t, out) = (0.3, Ease::new(*curve, Mode::Out));
+                assert!(
+                    (Ease::new(*curve, Mode::In).apply(t) - (1.0 - out.apply(1.0 - t))).abs()
+                        < 1e-6
+                );
+                assert!((Ease::new(*curve, Mode::InOut).apply(0.5) - 0.5).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shapes() {
+        let ease_in = |curve| Ease::new(curve, Mode::In);
+        assert_eq!(ease_in(Curve::Quad).apply(0.5), 0.25);
+        assert_eq!(ease_in(Curve::Cubic).apply(0.5), 0.125);
+        assert_eq!(ease_in(Curve::Expo).apply(0.9), 0.5);
+        assert!(ease_in(Curve::Elastic).apply(0.8) < 0.0);
+        // the bounces come down to the start and back.
+        let bounce = Ease::new(Curve::Bounce, Mode::Out);
+        assert!((bounce.apply(1.0 / 2.75) - 1.0).abs() < 1e-5);
+        assert!(bounce.apply(1.5 / 2.75) < 0.8);
+        // easing in and out is monotonic for everything but the elastic and bouncing curves.
+        for curve in &CURVES[..5] {
+            let ease = Ease::new(*curve, Mode::InOut);
+            for i in 0..100 {
+                assert!(ease.apply(i as f32 / 100.0) <= ease.apply((i + 1) as f32 / 100.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_eased() {
+        let ease = Ease::new(Curve::Quad, Mode::In);
+        assert_eq!(map_eased(0.5, 10.0, 20.0, ease), 12.5);
+        assert_eq!(map_eased(3.0, 10.0, 20.0, ease), 20.0);
+        assert_eq!(
+            map_eased(0.5, vec2(0.0, 4.0), vec2(8.0, 0.0), ease),
+            vec2(2.0, 3.0)
+        );
+        assert_eq!(map_eased(0.25, 1.0, -1.0, Ease::LINEAR), 0.5);
+
+        assert_eq!(ping_pong(0.25), 0.5);
+        assert_eq!(ping_pong(0.5), 1.0);
+        assert_eq!(ping_pong(-0.25), 0.5);
+        assert_eq!(ping_pong(7.0), 0.0);
+    }
+}
@@ -0,0 +1,185 @@
+//! A flow field: a swirling current, from curl noise, for carrying particles along.
+//!
+//! The velocity is the curl of a scalar Perlin noise field (its gradient turned a quarter turn),
+//! which makes it divergence free: like an incompressible fluid, it has whirls but no sources or
+//! sinks, so particles dropped in it never bunch up in one spot or thin out of another. The noise
+//! drifts through its third dimension as time passes, so the whirls slowly change.
+//!
+//! The field is sampled on a grid once per `update`, and interpolated between grid points, so
+//! lots of particles can read it cheaply.
+
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::noise::{NoiseFn, Perlin};
+
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    /// The area the grid covers; outside it, the velocity is that at the nearest edge.
+    pub bounds: Rect,
+    /// How far apart grid points are.
+    pub cell_size: f32,
+    /// How many whirls fit in a unit, roughly; the noise's frequency.
+    pub scale: f32,
+    /// How quickly the whirls change, in noise cells per second.
+    pub evolution: f32,
+    /// The speed of the current, roughly.
+    pub strength: f32,
+    columns: usize,
+    rows: usize,
+    // the velocity at each grid point, row by row from the bottom left.
+    velocities: Vec<Vector2>,
+    time: f32,
+    noise: Perlin,
+}
+
+impl FlowField {
+    /// A field over `bounds` with grid points `cell_size` apart, with about three whirls across
+    /// and a current that takes about ten seconds to cross it.
+    pub fn new(bounds: Rect, cell_size: f32) -> FlowField {
+        assert!(cell_size > 0.0, "cells must have a size");
+        let size = bounds.w().max(bounds.h());
+        let mut field = FlowField {
+            bounds,
+            cell_size,
+            scale: 3.0 / size,
+            evolution: 0.1,
+            strength: size / 10.0,
+            columns: (bounds.w() / cell_size).ceil() as usize + 1,
+            rows: (bounds.h() / cell_size).ceil() as usize + 1,
+            velocities: vec![],
+            time: 0.0,
+            noise: Perlin::new(),
+        };
+        field.update(0.0);
+        field
+    }
+
+    /// Resample the grid as the field is `time` seconds in.
+    pub fn update(&mut self, time: f32) {
+        self.time = time;
+        self.velocities = (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .map(|(column, row)| self.curl(self.node(column, row)))
+            .collect();
+    }
+
+    /// Where the grid point in `column` and `row` is.
+    fn node(&self, column: usize, row: usize) -> Vector2 {
+        self.bounds.bottom_left() + vec2(column as f32, row as f32) * self.cell_size
+    }
+
+    /// The exact velocity at `pos`, straight from the noise.
+    fn curl(&self, pos: Vector2) -> Vector2 {
+        let p = pos * self.scale;
+        let t = (self.time * self.evolution) as f64;
+        let potential = |x: f32, y: f32| self.noise.get([x as f64, y as f64, t]) as f32;
+        let h = 1e-3;
+        let dx = (potential(p.x + h, p.y) - potential(p.x - h, p.y)) / (2.0 * h);
+        let dy = (potential(p.x, p.y + h) - potential(p.x, p.y - h)) / (2.0 * h);
+        vec2(dy, -dx) * self.strength
+    }
+
+    /// The velocity at `pos`, interpolated between the grid points around it.
+    pub fn sample(&self, pos: Vector2) -> Vector2 {
+        let cell = (pos - self.bounds.bottom_left()) / self.cell_size;
+        let x = cell.x.max(0.0).min((self.columns - 1) as f32);
+        let y = cell.y.max(0.0).min((self.rows - 1) as f32);
+        let (column, row) = (
+            (x as usize).min(self.columns - 2),
+            (y as usize).min(self.rows - 2),
+        );
+        let (fx, fy) = (x - column as f32, y - row as f32);
+        let at = |c: usize, r: usize| self.velocities[r * self.columns + c];
+        let bottom = at(column, row) * (1.0 - fx) + at(column + 1, row) * fx;
+        let top = at(column, row + 1) * (1.0 - fx) + at(column + 1, row + 1) * fx;
+        bottom * (1.0 - fy) + top * fy
+    }
+
+    /// Where something at `pos` is carried to in `dt` seconds (by a midpoint step, so it follows
+    /// curves without spiraling outwards much).
+    pub fn advect(&self, pos: Vector2, dt: f32) -> Vector2 {
+        let mid = pos + self.sample(pos) * (dt / 2.0);
+        pos + self.sample(mid) * dt
+    }
+
+    /// The path from `start` along the current, `steps` pieces each `step` long, as far as it
+    /// goes before leaving `bounds` or stopping dead.
+    pub fn streamline(&self, start: Vector2, step: f32, steps: usize) -> Vec<Vector2> {
+        let mut line = vec![start];
+        let mut pos = start;
+        for _ in 0..steps {
+            let speed = self.sample(pos).magnitude();
+            if speed == 0.0 {
+                break;
+            }
+            // step by distance rather than time, so slow and fast parts are drawn as smoothly.
+            pos = self.advect(pos, step / speed);
+            if !self.bounds.contains(pos) {
+                break;
+            }
+            line.push(pos);
+        }
+        line
+    }
+
+    /// Every grid point and its velocity.
+    pub fn nodes(&self) -> impl Iterator<Item = (Vector2, Vector2)> + '_ {
+        (0..self.rows)
+            .flat_map(move |row| (0..self.columns).map(move |column| (column, row)))
+            .map(move |(column, row)| {
+                (
+                    self.node(column, row),
+                    self.velocities[row * self.columns + column],
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let bounds = Rect::from_x_y_w_h(0.0, 0.0, 4.0, 2.0);
+        let mut field = FlowField::new(bounds, 0.05);
+        let before = field.sample(vec2(0.3, 0.2));
+
+        // between grid points, the sample is close to the real thing.
+        let pos = vec2(0.312, -0.427);
+        let error = (field.sample(pos) - field.curl(pos)).magnitude();
+        assert!(error < field.strength * 0.05, "{}", error);
+
+        // nothing flows in or out of a little box.
+        let (h, c) = (0.01, vec2(-1.1, 0.6));
+        let divergence = (field.curl(c + vec2(h, 0.0)).x - field.curl(c - vec2(h, 0.0)).x
+            + field.curl(c + vec2(0.0, h)).y
+            - field.curl(c - vec2(0.0, h)).y)
+            / (2.0 * h);
+        let spin = (field.curl(c + vec2(h, 0.0)).y - field.curl(c - vec2(h, 0.0)).y) / (2.0 * h);
+        assert!(
+            divergence.abs() < spin.abs() * 0.05 + 1e-2,
+            "{} {}",
+            divergence,
+            spin
+        );
+
+        // it changes over time.
+        field.update(5.0);
+        assert!((field.sample(vec2(0.3, 0.2)) - before).magnitude() > 1e-3);
+        // and it's still defined outside the grid.
+        assert_eq!(field.sample(vec2(100.0, 0.0)), field.sample(vec2(2.0, 0.0)));
+    }
+
+    #[test]
+    fn test_streamline() {
+        let bounds = Rect::from_x_y_w_h(0.0, 0.0, 2.0, 2.0);
+        let field = FlowField::new(bounds, 0.05);
+        let line = field.streamline(vec2(0.1, 0.1), 0.01, 200);
+        assert!(line.len() > 10);
+        for (a, b) in line.iter().zip(&line[1..]) {
+            assert!(bounds.contains(*b));
+            let step = (*b - *a).magnitude();
+            assert!((step - 0.01).abs() < 2e-3, "{}", step);
+        }
+    }
+}
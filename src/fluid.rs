@@ -0,0 +1,263 @@
+//! A small 2D fluid simulation, with smoothed particle hydrodynamics (SPH).
+//!
+//! The fluid is a cloud of particles, each carrying a little of its mass. The density anywhere is
+//! the sum of the nearby particles' masses, smoothed out over `smoothing`; wherever it's above
+//! `rest_density` the fluid is squeezed, and the pressure pushes particles apart. Viscosity drags
+//! neighbouring particles towards each other's velocities. The kernels (the weightings by
+//! distance) are the usual ones from Müller et al., "Particle-Based Fluid Simulation for
+//! Interactive Applications": poly6 for density, spiky for pressure, and the viscosity kernel.
+//!
+//! Neighbours are found with a `SpatialHash`, so each step is about linear in the number of
+//! particles.
+
+use crate::physics::spatial_hash::SpatialHash;
+use nannou::geom::{vec2, Rect, Vector2};
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FluidParticle {
+    pub pos: Vector2,
+    pub vel: Vector2,
+    /// As of the last step.
+    pub density: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Fluid {
+    pub particles: Vec<FluidParticle>,
+    /// How far apart particles sit at rest.
+    pub spacing: f32,
+    /// How far each particle's influence reaches.
+    pub smoothing: f32,
+    /// Of each particle.
+    pub mass: f32,
+    pub rest_density: f32,
+    /// How hard the fluid pushes back against being squeezed; the speed of sound in it is the
+    /// square root of this.
+    pub stiffness: f32,
+    pub viscosity: f32,
+    pub gravity: Vector2,
+    /// The box the fluid is in.
+    pub bounds: Rect,
+    /// How much of their speed particles keep bouncing off the walls.
+    pub wall_restitution: f32,
+}
+
+impl Fluid {
+    /// An empty box of fluid whose particles sit `spacing` apart.
+    pub fn new(bounds: Rect, spacing: f32) -> Fluid {
+        let rest_density = 1000.0;
+        Fluid {
+            particles: vec![],
+            spacing,
+            smoothing: spacing * 2.0,
+            mass: rest_density * spacing * spacing,
+            rest_density,
+            stiffness: 200.0,
+            viscosity: 10.0,
+            gravity: vec2(0.0, -2.0),
+            bounds,
+            wall_restitution: 0.5,
+        }
+    }
+
+    pub fn add_particle(&mut self, pos: Vector2, vel: Vector2) {
+        self.particles.push(FluidParticle {
+            pos,
+            vel,
+            density: self.rest_density,
+        });
+    }
+
+    /// Fill `rect` with still fluid, at rest density.
+    pub fn add_block(&mut self, rect: Rect) {
+        let (columns, rows) = (
+            (rect.w() / self.spacing) as usize,
+            (rect.h() / self.spacing) as usize,
+        );
+        for row in 0..rows {
+            for column in 0..columns {
+                let offset = vec2(column as f32 + 0.5, row as f32 + 0.5) * self.spacing;
+                self.add_particle(rect.bottom_left() + offset, vec2(0.0, 0.0));
+            }
+        }
+    }
+
+    /// The longest step that stays stable: a fraction of the time sound takes to cross
+    /// `smoothing`.
+    pub fn max_dt(&self) -> f32 {
+        0.4 * self.smoothing / self.stiffness.sqrt()
+    }
+
+    /// Nudge the particles within `radius` of `pos` towards moving at `vel`, the closest the most.
+    pub fn stir(&mut self, pos: Vector2, vel: Vector2, radius: f32) {
+        for particle in &mut self.particles {
+            let distance = (particle.pos - pos).magnitude();
+            if distance < radius {
+                let strength = 1.0 - distance / radius;
+                particle.vel += (vel - particle.vel) * strength * 0.5;
+            }
+        }
+    }
+
+    /// Move the fluid forward `dt` seconds; `dt` should be at most `max_dt()`.
+    pub fn step(&mut self, dt: f32) {
+        let h = self.smoothing;
+        let positions = self.particles.iter().map(|p| p.pos).collect::<Vec<_>>();
+        let hash = SpatialHash::new(h, &positions);
+        let neighbours = positions
+            .iter()
+            .map(|pos| hash.within(*pos, h))
+            .collect::<Vec<_>>();
+
+        // density, and the pressure it makes. Stretched fluid doesn't pull back, or the surface
+        // would clump up.
+        let mass = self.mass;
+        for (particle, neighbours) in self.particles.iter_mut().zip(&neighbours) {
+            particle.density = neighbours
+                .iter()
+                .map(|j| mass * poly6(h, (positions[*j] - particle.pos).magnitude2()))
+                .sum();
+        }
+        let pressures = self
+            .particles
+            .iter()
+            .map(|p| (self.stiffness * (p.density - self.rest_density)).max(0.0))
+            .collect::<Vec<_>>();
+
+        let accelerations = (0..self.particles.len())
+            .map(|i| {
+                let a = &self.particles[i];
+                let mut force = vec2(0.0, 0.0);
+                for j in &neighbours[i] {
+                    let (j, b) = (*j, &self.particles[*j]);
+                    let d = a.pos - b.pos;
+                    let r = d.magnitude();
+                    if j == i || r == 0.0 {
+                        continue;
+                    }
+                    // pressure, shared evenly between the pair so it's symmetric.
+                    force += d / r
+                        * (self.mass * (pressures[i] + pressures[j]) / (2.0 * b.density)
+                            * spiky_slope(h, r));
+                    force += (b.vel - a.vel)
+                        * (self.viscosity * self.mass / b.density * viscosity_laplacian(h, r));
+                }
+                force / a.density + self.gravity
+            })
+            .collect::<Vec<_>>();
+
+        for (particle, acceleration) in self.particles.iter_mut().zip(accelerations) {
+            particle.vel += acceleration * dt;
+            particle.pos += particle.vel * dt;
+            bounce(particle, self.bounds, self.wall_restitution);
+        }
+    }
+}
+
+/// The poly6 kernel, of the squared distance.
+fn poly6(h: f32, r2: f32) -> f32 {
+    if r2 >= h * h {
+        return 0.0;
+    }
+    4.0 / (PI * h.powi(8)) * (h * h - r2).powi(3)
+}
+
+/// How steeply the spiky kernel falls off at distance `r`, made positive.
+fn spiky_slope(h: f32, r: f32) -> f32 {
+    if r >= h {
+        return 0.0;
+    }
+    30.0 / (PI * h.powi(5)) * (h - r).powi(2)
+}
+
+/// The Laplacian of the viscosity kernel.
+fn viscosity_laplacian(h: f32, r: f32) -> f32 {
+    if r >= h {
+        return 0.0;
+    }
+    40.0 / (PI * h.powi(5)) * (h - r)
+}
+
+/// Push a particle that's gone through a wall back inside, losing some of its speed.
+fn bounce(particle: &mut FluidParticle, bounds: Rect, restitution: f32) {
+    let (pos, vel) = (&mut particle.pos, &mut particle.vel);
+    if pos.x < bounds.left() {
+        pos.x = bounds.left();
+        vel.x = vel.x.abs() * restitution;
+    } else if pos.x > bounds.right() {
+        pos.x = bounds.right();
+        vel.x = -vel.x.abs() * restitution;
+    }
+    if pos.y < bounds.bottom() {
+        pos.y = bounds.bottom();
+        vel.y = vel.y.abs() * restitution;
+    } else if pos.y > bounds.top() {
+        pos.y = bounds.top();
+        vel.y = -vel.y.abs() * restitution;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels() {
+        // each kernel adds up to 1 over the plane.
+        let h = 0.1;
+        let n = 200;
+        let cell = 2.0 * h / n as f32;
+        let mut total = 0.0;
+        for x in 0..n {
+            for y in 0..n {
+                let p = vec2(x as f32 + 0.5, y as f32 + 0.5) * cell - vec2(h, h);
+                total += poly6(h, p.magnitude2()) * cell * cell;
+            }
+        }
+        assert!((total - 1.0).abs() < 1e-2, "{}", total);
+        assert_eq!(spiky_slope(h, h), 0.0);
+        assert!(spiky_slope(h, 0.01) > spiky_slope(h, 0.05));
+    }
+
+    #[test]
+    fn test_settle() {
+        // a block of fluid dropped into a box spreads out and settles, without blowing up.
+        let bounds = Rect::from_x_y_w_h(0.0, 0.0, 1.0, 1.0);
+        let mut fluid = Fluid::new(bounds, 0.04);
+        fluid.add_block(Rect::from_x_y_w_h(-0.3, 0.0, 0.4, 0.4));
+        let dt = fluid.max_dt();
+        for _ in 0..(8.0 / dt) as usize {
+            fluid.step(dt);
+        }
+        for particle in &fluid.particles {
+            assert!(bounds.contains(particle.pos), "{:?}", particle);
+            assert!(particle.vel.magnitude() < 0.2, "{:?}", particle);
+        }
+        // the fluid is a layer across the bottom of the box, about as dense as it should be.
+        let top = fluid
+            .particles
+            .iter()
+            .map(|p| p.pos.y)
+            .fold(f32::MIN, f32::max);
+        assert!(top < bounds.bottom() + 0.25, "{}", top);
+        let right = fluid
+            .particles
+            .iter()
+            .map(|p| p.pos.x)
+            .fold(f32::MIN, f32::max);
+        assert!(right > bounds.right() - 0.1, "{}", right);
+        let inside = fluid
+            .particles
+            .iter()
+            .filter(|p| p.pos.y < bounds.bottom() + 0.1)
+            .map(|p| p.density)
+            .collect::<Vec<_>>();
+        let density = inside.iter().sum::<f32>() / inside.len() as f32;
+        assert!(
+            (density / fluid.rest_density - 1.0).abs() < 0.2,
+            "{}",
+            density
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Driving sketches from a game controller, for exhibitions where there's no keyboard or mouse
+//! to hand.
+//!
+//! The sticks move a circuit's `Camera`: the left one pans, and pushing the right one up or down
+//! zooms. Buttons can flip a sketch's checkboxes and the analog triggers set its sliders, like
+//! MIDI knobs; anything else, the sketch can ask about itself:
+//!
+//! ```ignore
+//! // when building the model:
+//! let mut gamepad = Gamepad::new();
+//! gamepad.map_button(Button::South, "smooth colors");
+//! gamepad.map_trigger(Button::RightTrigger2, "gravity");
+//! // every update:
+//! gamepad.update();
+//! gamepad.apply(&mut model.params);
+//! gamepad.drive(&mut model.camera, dt);
+//! if gamepad.pressed(Button::Start) { ... }
+//! ```
+
+use crate::params::Params;
+use crate::render_circuit::Camera;
+use gilrs::{Axis, EventType, Gilrs};
+use nannou::geom::{vec2, Vector2};
+
+pub use gilrs::Button;
+
+pub struct Gamepad {
+    // none if there's no way to read controllers here.
+    gilrs: Option<Gilrs>,
+    /// How far a stick has to be pushed before it counts, as they rarely rest exactly in the
+    /// middle.
+    pub dead_zone: f32,
+    /// How many pixels a second the left stick pans at full tilt.
+    pub pan_speed: f32,
+    /// How many times a second the right stick doubles or halves the zoom at full tilt.
+    pub zoom_speed: f32,
+    // where the left and right sticks are, up being positive.
+    sticks: [Vector2; 2],
+    // the buttons pressed since the last update, and how far each trigger's been pulled.
+    pressed: Vec<Button>,
+    triggers: Vec<(Button, f32)>,
+    // which checkbox each button flips, and which slider each trigger sets.
+    buttons: Vec<(Button, String)>,
+    trigger_params: Vec<(Button, String)>,
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Gamepad::new()
+    }
+}
+
+impl Gamepad {
+    /// Read whichever controllers are plugged in, or none if they can't be read here.
+    pub fn new() -> Gamepad {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                println!("can't read game controllers: {}", e);
+                None
+            }
+        };
+        Gamepad::with(gilrs)
+    }
+
+    fn with(gilrs: Option<Gilrs>) -> Gamepad {
+        Gamepad {
+            gilrs,
+            dead_zone: 0.15,
+            pan_speed: 600.0,
+            zoom_speed: 1.0,
+            sticks: [vec2(0.0, 0.0); 2],
+            pressed: vec![],
+            triggers: vec![],
+            buttons: vec![],
+            trigger_params: vec![],
+        }
+    }
+
+    /// Pressing `button` flips the checkbox `name`.
+    pub fn map_button(&mut self, button: Button, name: &str) {
+        self.buttons.push((button, name.to_string()));
+    }
+
+    /// Pulling the analog `trigger` sets the slider `name`, from its least released to its most
+    /// pulled all the way.
+    pub fn map_trigger(&mut self, trigger: Button, name: &str) {
+        self.trigger_params.push((trigger, name.to_string()));
+    }
+
+    /// Catch up on what the controllers have done since the last update.
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        let mut events = vec![];
+        if let Some(gilrs) = &mut self.gilrs {
+            while let Some(event) = gilrs.next_event() {
+                events.push(event.event);
+            }
+        }
+        for event in events {
+            match event {
+                EventType::ButtonPressed(button, _) => self.pressed.push(button),
+                EventType::ButtonChanged(button, value, _) => self.trigger(button, value),
+                EventType::AxisChanged(axis, value, _) => self.axis(axis, value),
+                // a stick let go of, say by unplugging it, doesn't keep the camera moving.
+                EventType::Disconnected => self.sticks = [vec2(0.0, 0.0); 2],
+                _ => (),
+            }
+        }
+    }
+
+    fn axis(&mut self, axis: Axis, value: f32) {
+        match axis {
+            Axis::LeftStickX => self.sticks[0].x = value,
+            Axis::LeftStickY => self.sticks[0].y = value,
+            Axis::RightStickX => self.sticks[1].x = value,
+            Axis::RightStickY => self.sticks[1].y = value,
+            _ => (),
+        }
+    }
+
+    fn trigger(&mut self, button: Button, value: f32) {
+        match self.triggers.iter_mut().find(|(b, _)| *b == button) {
+            Some((_, v)) => *v = value,
+            None => self.triggers.push((button, value)),
+        }
+    }
+
+    /// Whether `button` was pressed since the last update.
+    pub fn pressed(&self, button: Button) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Where the left stick is, from the middle out to 1 each way, with the `dead_zone` taken out.
+    pub fn left_stick(&self) -> Vector2 {
+        self.dead(self.sticks[0])
+    }
+
+    pub fn right_stick(&self) -> Vector2 {
+        self.dead(self.sticks[1])
+    }
+
+    // `stick` without the dead zone, so it starts again from 0 at its edge.
+    fn dead(&self, stick: Vector2) -> Vector2 {
+        let length = stick.magnitude();
+        if length <= self.dead_zone {
+            return vec2(0.0, 0.0);
+        }
+        let scaled = ((length - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        stick * (scaled / length)
+    }
+
+    /// Flip the checkboxes of the buttons pressed, and set the sliders to the triggers.
+    pub fn apply(&self, params: &mut Params) {
+        for (button, name) in &self.buttons {
+            if self.pressed(*button) {
+                let on = params.bool(name);
+                params.set_fraction(name, 0, if on { 0.0 } else { 1.0 });
+            }
+        }
+        for (trigger, name) in &self.trigger_params {
+            if let Some((_, value)) = self.triggers.iter().find(|(t, _)| t == trigger) {
+                params.set_fraction(name, 0, *value);
+            }
+        }
+    }
+
+    /// Pan and zoom `camera` by the sticks, for `dt` seconds; it zooms about the window's center.
+    pub fn drive(&self, camera: &mut Camera, dt: f32) {
+        // pushing right looks further right, so the circuit moves left.
+        camera.pan -= self.left_stick() * self.pan_speed * dt;
+        let zoom = 2f32.powf(self.right_stick().y * self.zoom_speed * dt);
+        camera.zoom_about(zoom, vec2(0.0, 0.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a gamepad with nothing plugged in, fed events by hand.
+    fn unplugged() -> Gamepad {
+        Gamepad::with(None)
+    }
+
+    #[test]
+    fn test_sticks() {
+        let mut gamepad = unplugged();
+        gamepad.axis(Axis::LeftStickX, 0.1);
+        assert_eq!(gamepad.left_stick(), vec2(0.0, 0.0));
+        gamepad.axis(Axis::LeftStickX, 1.0);
+        assert_eq!(gamepad.left_stick(), vec2(1.0, 0.0));
+        gamepad.axis(Axis::LeftStickX, 0.0);
+        gamepad.axis(Axis::LeftStickY, -0.575);
+        assert!((gamepad.left_stick().y - -0.5).abs() < 1e-5);
+
+        let mut camera = Camera::default();
+        gamepad.axis(Axis::LeftStickY, 0.0);
+        gamepad.axis(Axis::RightStickY, 1.0);
+        gamepad.drive(&mut camera, 0.5);
+        assert!((camera.zoom - 2f32.sqrt()).abs() < 1e-5);
+        assert_eq!(camera.pan, vec2(0.0, 0.0));
+        gamepad.axis(Axis::RightStickY, 0.0);
+        gamepad.axis(Axis::LeftStickX, 1.0);
+        gamepad.drive(&mut camera, 0.5);
+        assert_eq!(camera.pan, vec2(-300.0, 0.0));
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut params = Params::new();
+        params.add_bool("smooth colors", true);
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        let mut gamepad = unplugged();
+        gamepad.map_button(Button::South, "smooth colors");
+        gamepad.map_trigger(Button::RightTrigger2, "gravity");
+
+        // untouched, nothing changes.
+        gamepad.apply(&mut params);
+        assert!(params.bool("smooth colors"));
+        assert_eq!(params.float("gravity"), 5.0);
+
+        gamepad.pressed.push(Button::South);
+        gamepad.trigger(Button::RightTrigger2, 0.5);
+        gamepad.apply(&mut params);
+        assert!(!params.bool("smooth colors"));
+        assert_eq!(params.float("gravity"), 10.0);
+        // a new update forgets the press, but the trigger's still held.
+        gamepad.update();
+        gamepad.apply(&mut params);
+        assert!(!params.bool("smooth colors"));
+        assert_eq!(params.float("gravity"), 10.0);
+    }
+}
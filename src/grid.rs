@@ -0,0 +1,127 @@
+//! A grid of numbers (concentrations, temperatures, trails) that spread out over it.
+//!
+//! It's a `CellGrid` of `f32`s, so edges work as they do there: wrapped around, or clamped, in
+//! which case nothing spreads past them, as if the grid were insulated.
+
+use crate::automata::{CellGrid, Edges};
+use nannou::geom::Vector2;
+
+pub type FloatGrid = CellGrid<f32>;
+
+impl CellGrid<f32> {
+    pub fn add(&mut self, x: usize, y: usize, amount: f32) {
+        let value = *self.get(x, y);
+        self.set(x, y, value + amount);
+    }
+
+    /// The cell `pos` (in cells, from the bottom left corner of the grid) is in.
+    pub fn at(&self, pos: Vector2) -> Option<f32> {
+        self.cell_at(pos.x.floor() as isize, pos.y.floor() as isize)
+            .copied()
+    }
+
+    /// Set every cell within `radius` cells of `center` (in cells, from the bottom left corner of
+    /// the grid) to `value`. Clamped edges cut the circle off; wrapped ones don't.
+    pub fn paint(&mut self, center: Vector2, radius: f32, value: f32) {
+        let (w, h) = (self.width as isize, self.height as isize);
+        let left = (center.x - radius).floor() as isize;
+        let bottom = (center.y - radius).floor() as isize;
+        let right = (center.x + radius).ceil() as isize;
+        let top = (center.y + radius).ceil() as isize;
+        for y in bottom..top {
+            for x in left..right {
+                let offset = Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                if offset.magnitude2() > radius * radius {
+                    continue;
+                }
+                match self.edges {
+                    Edges::Wrap => {
+                        self.set(x.rem_euclid(w) as usize, y.rem_euclid(h) as usize, value)
+                    }
+                    Edges::Clamp if x < 0 || y < 0 || x >= w || y >= h => (),
+                    Edges::Clamp => self.set(x as usize, y as usize, value),
+                }
+            }
+        }
+    }
+
+    /// How much more the cells around (`x`, `y`) have than it does, weighing the diagonal
+    /// neighbours less than the adjacent ones. Cells past clamped edges count as having as much
+    /// as (`x`, `y`).
+    pub fn laplacian(&self, x: usize, y: usize) -> f32 {
+        let here = *self.get(x, y);
+        let at = |dx: isize, dy: isize| {
+            self.cell_at(x as isize + dx, y as isize + dy)
+                .copied()
+                .unwrap_or(here)
+        };
+        (at(-1, 0) + at(1, 0) + at(0, -1) + at(0, 1)) * 0.2
+            + (at(-1, -1) + at(1, -1) + at(-1, 1) + at(1, 1)) * 0.05
+            - here
+    }
+
+    /// Spread every cell out into the ones around it, at `rate`, for `dt` (in which case
+    /// `rate * dt` up to 1 stays stable). Nothing is lost, except past clamped edges.
+    pub fn diffuse(&mut self, rate: f32, dt: f32) {
+        self.step(&|grid: &FloatGrid, x, y| grid.get(x, y) + rate * dt * grid.laplacian(x, y));
+    }
+
+    /// Shrink every cell towards 0 by `fraction` of itself.
+    pub fn decay(&mut self, fraction: f32) {
+        for value in self.values_mut() {
+            *value *= 1.0 - fraction;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+
+    fn total(grid: &FloatGrid) -> f32 {
+        grid.values().iter().sum()
+    }
+
+    #[test]
+    fn test_diffuse() {
+        let mut grid = FloatGrid::new(10, 10, 0.0);
+        grid.set(0, 0, 100.0);
+        for _ in 0..200 {
+            grid.diffuse(1.0, 1.0);
+        }
+        // spreading across the edges, to the other side, evening out and losing nothing.
+        assert!((grid.get(5, 5) - 1.0).abs() < 0.05, "{}", grid.get(5, 5));
+        assert!((total(&grid) - 100.0).abs() < 1e-2);
+
+        // clamped, nothing gets out either.
+        grid.fill(0.0);
+        grid.edges = Edges::Clamp;
+        grid.set(0, 0, 100.0);
+        for _ in 0..10 {
+            grid.diffuse(1.0, 1.0);
+        }
+        assert!((total(&grid) - 100.0).abs() < 1e-2);
+        // and it's spread less far than it would round the edges.
+        assert!(grid.get(9, 9) < grid.get(1, 1));
+
+        grid.decay(0.25);
+        assert!((total(&grid) - 75.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_paint() {
+        let mut grid = FloatGrid::new(10, 10, 0.0);
+        grid.paint(vec2(0.0, 0.0), 1.0, 1.0);
+        // a cell in each corner, wrapped around.
+        assert_eq!(total(&grid), 4.0);
+        assert_eq!(*grid.get(9, 9), 1.0);
+        assert_eq!(grid.at(vec2(-0.5, 10.5)), Some(1.0));
+
+        grid.fill(0.0);
+        grid.edges = Edges::Clamp;
+        grid.paint(vec2(0.0, 0.0), 1.0, 1.0);
+        assert_eq!(total(&grid), 1.0);
+        assert_eq!(grid.at(vec2(-0.5, 0.5)), None);
+    }
+}
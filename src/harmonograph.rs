@@ -0,0 +1,123 @@
+//! Harmonographs: the curves drawn by a pen moved by pendulums swinging along each axis, slowly
+//! dying down, so the curve spirals in on itself.
+//!
+//! With one undamped pendulum per axis it's a Lissajous figure, which closes up whenever their
+//! frequencies are in a whole number ratio; a slight mistuning from one makes the figure turn
+//! slowly as it's drawn.
+
+use nannou::geom::{vec2, Vector2};
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Pendulum {
+    /// How far it swings to either side, to start with.
+    pub amplitude: f32,
+    /// How many swings it makes per unit of time.
+    pub frequency: f32,
+    /// Where in its swing it starts, in radians.
+    pub phase: f32,
+    /// How fast it dies down; its swings shrink by a factor of e every `1 / damping` of time.
+    pub damping: f32,
+}
+
+impl Pendulum {
+    /// How far it's swung at time `t`.
+    pub fn at(&self, t: f32) -> f32 {
+        self.amplitude
+            * (2.0 * PI * self.frequency * t + self.phase).sin()
+            * (-self.damping * t).exp()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Harmonograph {
+    /// The pendulums moving the pen across, all adding up.
+    pub x: Vec<Pendulum>,
+    /// The pendulums moving the pen up and down.
+    pub y: Vec<Pendulum>,
+}
+
+impl Harmonograph {
+    /// A Lissajous figure `amplitude` across either way, with `a` swings across to every `b` up
+    /// and down, the latter `phase` radians ahead.
+    pub fn lissajous(amplitude: f32, a: f32, b: f32, phase: f32) -> Harmonograph {
+        let pendulum = |frequency, phase| Pendulum {
+            amplitude,
+            frequency,
+            phase,
+            damping: 0.0,
+        };
+        Harmonograph {
+            x: vec![pendulum(a, 0.0)],
+            y: vec![pendulum(b, phase)],
+        }
+    }
+
+    /// Where the pen is at time `t`.
+    pub fn at(&self, t: f32) -> Vector2 {
+        vec2(
+            self.x.iter().map(|p| p.at(t)).sum(),
+            self.y.iter().map(|p| p.at(t)).sum(),
+        )
+    }
+
+    /// `steps + 1` points along the curve, evenly spaced in time from `start` to `end`.
+    pub fn curve(&self, start: f32, end: f32, steps: usize) -> Vec<Vector2> {
+        (0..=steps)
+            .map(|i| self.at(start + (end - start) * i as f32 / steps as f32))
+            .collect()
+    }
+
+    /// How far the pen can be from the middle at time `t` at the very most: the sum of what's
+    /// left of every pendulum's swing.
+    pub fn reach(&self, t: f32) -> f32 {
+        let left = |p: &Pendulum| p.amplitude.abs() * (-p.damping * t).exp();
+        let x: f32 = self.x.iter().map(left).sum();
+        let y: f32 = self.y.iter().map(left).sum();
+        x.max(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lissajous() {
+        let figure = Harmonograph::lissajous(1.0, 3.0, 2.0, PI / 2.0);
+        assert!((figure.at(0.0) - vec2(0.0, 1.0)).magnitude() < 1e-6);
+        // a whole number ratio, so it closes up after one unit of time.
+        let curve = figure.curve(0.0, 1.0, 100);
+        assert_eq!(curve.len(), 101);
+        assert!((curve[0] - curve[100]).magnitude() < 1e-4);
+        // swinging across three times, so crossing the middle five times in between.
+        let crossings = curve[1..100]
+            .windows(2)
+            .filter(|pair| pair[0].x.signum() != pair[1].x.signum())
+            .count();
+        assert_eq!(crossings, 5);
+        assert!(curve.iter().all(|p| p.x.abs() <= 1.0 && p.y.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_damping() {
+        let mut harmonograph = Harmonograph::lissajous(1.0, 2.0, 3.01, 0.0);
+        for pendulum in harmonograph.x.iter_mut().chain(&mut harmonograph.y) {
+            pendulum.damping = 0.1;
+        }
+        harmonograph.x.push(Pendulum {
+            amplitude: 0.5,
+            frequency: 1.0,
+            phase: 1.0,
+            damping: 0.2,
+        });
+        assert_eq!(harmonograph.reach(0.0), 1.5);
+        let curve = harmonograph.curve(0.0, 50.0, 10000);
+        for (i, pos) in curve.iter().enumerate() {
+            let t = 50.0 * i as f32 / 10000.0;
+            assert!(pos.x.abs() <= harmonograph.reach(t) + 1e-5);
+        }
+        // spiralling in, almost to the middle.
+        assert!(curve[10000].magnitude() < 0.01);
+    }
+}
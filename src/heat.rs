@@ -0,0 +1,154 @@
+//! The heat equation on a grid: temperatures evening out as heat flows from hot cells to cold
+//! ones.
+//!
+//! Some cells can be made sources (or sinks, which are just cold sources) held at a temperature,
+//! like a hot plate or a block of ice, which the rest of the grid warms or cools towards. The
+//! edges are clamped by default, so no heat leaks out of them.
+
+use crate::automata::{CellGrid, Edges};
+use crate::grid::FloatGrid;
+use nannou::geom::Vector2;
+
+#[derive(Clone, Debug)]
+pub struct HeatField {
+    pub width: usize,
+    pub height: usize,
+    /// How fast heat spreads.
+    pub conductivity: f32,
+    temperature: FloatGrid,
+    // the temperature each cell is held at, if any.
+    sources: CellGrid<Option<f32>>,
+}
+
+impl HeatField {
+    /// A grid at `temperature` all over, with no sources.
+    pub fn new(width: usize, height: usize, temperature: f32) -> HeatField {
+        let mut grid = FloatGrid::new(width, height, temperature);
+        grid.edges = Edges::Clamp;
+        HeatField {
+            width,
+            height,
+            conductivity: 1.0,
+            temperature: grid,
+            sources: CellGrid::new(width, height, None),
+        }
+    }
+
+    pub fn temperature(&self, x: usize, y: usize) -> f32 {
+        *self.temperature.get(x, y)
+    }
+
+    /// Every cell's temperature, row by row from the bottom left.
+    pub fn temperatures(&self) -> &[f32] {
+        self.temperature.values()
+    }
+
+    /// The temperatures as a grid, for drawing isotherms with `contour`.
+    pub fn grid(&self) -> &FloatGrid {
+        &self.temperature
+    }
+
+    /// The temperature (`x`, `y`) is held at, if it's a source.
+    pub fn source(&self, x: usize, y: usize) -> Option<f32> {
+        *self.sources.get(x, y)
+    }
+
+    /// What's past the edges: `Edges::Clamp` keeps the heat in, and `Edges::Wrap` lets it flow
+    /// round to the other side.
+    pub fn set_edges(&mut self, edges: Edges) {
+        self.temperature.edges = edges;
+    }
+
+    pub fn edges(&self) -> Edges {
+        self.temperature.edges
+    }
+
+    /// Hold every cell within `radius` cells of `center` (in cells, from the bottom left corner of
+    /// the grid) at `temperature`, or stop holding them at anything if it's `None`.
+    pub fn paint_source(&mut self, center: Vector2, radius: f32, temperature: Option<f32>) {
+        let left = (center.x - radius).floor().max(0.0) as usize;
+        let bottom = (center.y - radius).floor().max(0.0) as usize;
+        let right = ((center.x + radius).ceil().max(0.0) as usize).min(self.width);
+        let top = ((center.y + radius).ceil().max(0.0) as usize).min(self.height);
+        for y in bottom..top {
+            for x in left..right {
+                let offset = Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                if offset.magnitude2() <= radius * radius {
+                    self.sources.set(x, y, temperature);
+                    if let Some(temperature) = temperature {
+                        self.temperature.set(x, y, temperature);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove every source, and set the whole grid to `temperature`.
+    pub fn reset(&mut self, temperature: f32) {
+        self.sources.fill(None);
+        self.temperature.fill(temperature);
+    }
+
+    /// Let heat flow for `dt` (in which case `conductivity * dt` up to 1 stays stable).
+    pub fn step(&mut self, dt: f32) {
+        self.temperature.diffuse(self.conductivity, dt);
+        for (x, y, source) in self.sources.cells() {
+            if let Some(temperature) = source {
+                self.temperature.set(x, y, *temperature);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+
+    #[test]
+    fn test_gradient() {
+        // a hot end and a cold end, with the heat flowing steadily from one to the other between.
+        let mut field = HeatField::new(11, 1, 0.5);
+        field.paint_source(vec2(0.5, 0.5), 0.5, Some(1.0));
+        field.paint_source(vec2(10.5, 0.5), 0.5, Some(0.0));
+        assert_eq!(field.source(0, 0), Some(1.0));
+        assert_eq!(field.source(5, 0), None);
+        for _ in 0..2000 {
+            field.step(1.0);
+        }
+        for x in 0..11 {
+            let expected = 1.0 - x as f32 / 10.0;
+            assert!((field.temperature(x, 0) - expected).abs() < 1e-3);
+        }
+
+        // taking the sources away, it evens out.
+        field.paint_source(vec2(0.5, 0.5), 0.5, None);
+        field.paint_source(vec2(10.5, 0.5), 0.5, None);
+        for _ in 0..2000 {
+            field.step(1.0);
+        }
+        for x in 0..11 {
+            assert!((field.temperature(x, 0) - 0.5).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_insulated() {
+        let mut field = HeatField::new(20, 20, 0.0);
+        field.paint_source(vec2(3.0, 3.0), 2.0, Some(10.0));
+        field.paint_source(vec2(3.0, 3.0), 2.0, None);
+        let total = |field: &HeatField| field.temperatures().iter().sum::<f32>();
+        let heat = total(&field);
+        assert!(heat > 0.0);
+        for _ in 0..100 {
+            field.step(0.5);
+        }
+        // it's spread out, but none has leaked away.
+        assert!(field.temperature(19, 19) > 0.0);
+        assert!((total(&field) - heat).abs() < 1e-2);
+
+        field.reset(2.0);
+        field.step(1.0);
+        assert!(field.temperatures().iter().all(|t| *t == 2.0));
+    }
+}
@@ -0,0 +1,192 @@
+//! A heads-up display of how a sketch is running: a graph of how long its frames take, and below it
+//! whatever counts, parameters and capture status the sketch reports, in the bottom left corner of
+//! the window. It starts hidden, and F3 shows it:
+//!
+//! ```ignore
+//! // on key presses:
+//! model.hud.key(key);
+//! // every update:
+//! model.hud.frame(app.window_rect(), dt);
+//! model.hud.stat("particles", model.world.particles.len());
+//! model.hud.params(&model.params);
+//! // in the view, over everything else:
+//! model.hud.draw(&draw);
+//! ```
+//!
+//! Every `Sketch` has one already, which it reports to in `Sketch::hud`.
+
+use crate::capture::Capture;
+use crate::params::Params;
+use crate::viz::plot::Plot;
+use nannou::color::rgba;
+use nannou::event::Key;
+use nannou::geom::Rect;
+use nannou::Draw;
+use std::fmt::Display;
+
+const WIDTH: f32 = 220.0;
+// how tall the frame time graph is, and how many frames it shows.
+const GRAPH: f32 = 50.0;
+const FRAMES: usize = 120;
+const ROW: f32 = 14.0;
+const PADDING: f32 = 8.0;
+const FONT_SIZE: u32 = 11;
+
+pub struct Hud {
+    /// The key showing and hiding it.
+    pub key: Key,
+    pub visible: bool,
+    frame_times: Plot,
+    // by name, in the order they were first reported.
+    stats: Vec<(String, String)>,
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Hud::new()
+    }
+}
+
+impl Hud {
+    /// A hidden HUD, shown by F3.
+    pub fn new() -> Hud {
+        let mut frame_times = Plot::new(Rect::from_w_h(WIDTH, GRAPH), FRAMES);
+        frame_times.add_series("frame ms", rgba(0.8, 0.2, 0.1, 1.0));
+        Hud {
+            key: Key::F3,
+            visible: false,
+            frame_times,
+            stats: vec![],
+        }
+    }
+
+    /// Show or hide the HUD if `key` is the `key`.
+    pub fn key(&mut self, key: Key) {
+        if key == self.key {
+            self.visible = !self.visible;
+        }
+    }
+
+    /// Record a frame taking `dt` seconds, in a window that's `win`.
+    pub fn frame(&mut self, win: Rect, dt: f32) {
+        self.frame_times.push(0, dt * 1000.0);
+        let text = Rect::from_w_h(WIDTH, self.text_height()).bottom_left_of(win.pad(PADDING));
+        self.frame_times.rect = Rect::from_w_h(WIDTH, GRAPH)
+            .above(text)
+            .align_left_of(text)
+            .shift_y(PADDING);
+    }
+
+    /// Show `name` as `value`, in place of what it was last.
+    pub fn stat<T: Display>(&mut self, name: &str, value: T) {
+        let value = value.to_string();
+        match self.stats.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.stats.push((name.to_string(), value)),
+        }
+    }
+
+    /// Show every parameter's value.
+    pub fn params(&mut self, params: &Params) {
+        for (name, value) in params.values() {
+            self.stat(name, value);
+        }
+    }
+
+    /// Show what `capture` is doing at `frame`.
+    pub fn capture(&mut self, capture: &Capture, frame: u64) {
+        let status = match capture.path(frame) {
+            Some(path) => format!("saving {}", path.display()),
+            None if capture.done(frame) => "done".to_string(),
+            None => "off".to_string(),
+        };
+        self.stat("capture", status);
+    }
+
+    /// The stats, a line each.
+    pub fn text(&self) -> String {
+        self.stats
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn text_height(&self) -> f32 {
+        self.stats.len() as f32 * ROW + PADDING * 2.0
+    }
+
+    pub fn draw(&self, draw: &Draw) {
+        if !self.visible {
+            return;
+        }
+        self.frame_times.draw(draw);
+        let graph = self.frame_times.rect;
+        let text = Rect::from_w_h(WIDTH, self.text_height())
+            .below(graph)
+            .align_left_of(graph)
+            .shift_y(-PADDING);
+        draw.rect()
+            .xy(text.xy())
+            .wh(text.wh())
+            .color(rgba(0.08, 0.08, 0.1, 0.85));
+        draw.text(&self.text())
+            .xy(text.xy())
+            .wh(text.pad(PADDING).wh())
+            .left_justify()
+            .align_text_top()
+            .font_size(FONT_SIZE)
+            .color(rgba(1.0, 1.0, 1.0, 1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut hud = Hud::new();
+        hud.key(Key::A);
+        assert!(!hud.visible);
+        hud.key(Key::F3);
+        assert!(hud.visible);
+
+        hud.stat("particles", 30);
+        hud.stat("zoom", format!("{:.1}", 745.0));
+        hud.stat("particles", 31);
+        let mut params = Params::new();
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        params.add_bool("walls", false);
+        hud.params(&params);
+        assert_eq!(
+            hud.text(),
+            "particles: 31\nzoom: 745.0\ngravity: 5.00\nwalls: off"
+        );
+
+        let mut capture = Capture::new(std::env::temp_dir().join("hud_test"));
+        capture.frames = Some(10..20);
+        hud.capture(&capture, 5);
+        assert!(hud.text().ends_with("capture: off"));
+        hud.capture(&capture, 12);
+        assert!(hud.text().ends_with("00002.png"));
+        hud.capture(&capture, 20);
+        assert!(hud.text().ends_with("capture: done"));
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut hud = Hud::new();
+        hud.stat("a", 1);
+        hud.stat("b", 2);
+        let win = Rect::from_w_h(800.0, 600.0);
+        hud.frame(win, 1.0 / 60.0);
+        // the graph sits on the text, in the bottom left corner.
+        let graph = hud.frame_times.rect;
+        assert_eq!(graph.left(), win.left() + PADDING);
+        assert_eq!(
+            graph.bottom(),
+            win.bottom() + PADDING * 2.0 + hud.text_height()
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Looking colors up in an image by where they are, rather than by pixel: `u` from 0 at its
+//! left edge to 1 at its right, and `v` from 0 at its bottom to 1 at its top, the same way up as
+//! nannou's y axis. Handy for coloring things after a picture underneath them, like the triangles
+//! in `bouncing_3`.
+//!
+//! Pixels are squares with their colors at their middles; in between, colors blend bilinearly
+//! from the four nearest. Anywhere off the image is either held at its edge or wrapped round, so
+//! a lookup can never fall outside it.
+
+use nannou::color::Srgba;
+use nannou::geom::{Rect, Vector2};
+use nannou::image::{ImageBuffer, Pixel};
+use std::ops::Deref;
+
+/// What's past the image's edges.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Edge {
+    /// The nearest pixel along the edge, out to infinity.
+    Clamp,
+    /// The image again, tiled.
+    Wrap,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Filter {
+    /// The color of whichever pixel the spot's in, blocky up close.
+    Nearest,
+    /// Blended from the four pixels whose middles are nearest, smooth up close.
+    Bilinear,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Sampler {
+    pub edge: Edge,
+    pub filter: Filter,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            edge: Edge::Clamp,
+            filter: Filter::Bilinear,
+        }
+    }
+}
+
+impl Sampler {
+    /// Bilinear lookups, held at the edges.
+    pub fn new() -> Sampler {
+        Sampler::default()
+    }
+
+    /// Which column (or row) of `len` the `i`th along is, past the edges too.
+    fn index(&self, i: i64, len: u32) -> u32 {
+        match self.edge {
+            Edge::Clamp => i.max(0).min(len as i64 - 1) as u32,
+            Edge::Wrap => i.rem_euclid(len as i64) as u32,
+        }
+    }
+
+    /// The pixel at column `x` and row `y` up from the bottom, past the edges too, from 0 to 1.
+    fn texel<P, C>(&self, image: &ImageBuffer<P, C>, x: i64, y: i64) -> [f32; 4]
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+        C: Deref<Target = [u8]>,
+    {
+        let (width, height) = image.dimensions();
+        let x = self.index(x, width);
+        // images go from the top down.
+        let y = height - 1 - self.index(y, height);
+        let rgba = image.get_pixel(x, y).to_rgba();
+        let mut texel = [0.0; 4];
+        for (t, c) in texel.iter_mut().zip(rgba.0.iter()) {
+            *t = *c as f32 / 255.0;
+        }
+        texel
+    }
+
+    /// The color of `image` at `uv`.
+    pub fn sample<P, C>(&self, image: &ImageBuffer<P, C>, uv: Vector2) -> Srgba
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+        C: Deref<Target = [u8]>,
+    {
+        let (width, height) = image.dimensions();
+        assert!(width > 0 && height > 0, "can't sample an empty image");
+        // in pixels from the bottom left corner; NaNs end up at the corner, like Clamp.
+        let (x, y) = (uv.x * width as f32, uv.y * height as f32);
+        let [r, g, b, a] = match self.filter {
+            Filter::Nearest => self.texel(image, floor(x), floor(y)),
+            Filter::Bilinear => {
+                // the pixel middles to the bottom left, and how far past them it is.
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (floor(x), floor(y));
+                let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+                let mut blended = [0.0; 4];
+                for &(dx, dy, weight) in &[
+                    (0, 0, (1.0 - fx) * (1.0 - fy)),
+                    (1, 0, fx * (1.0 - fy)),
+                    (0, 1, (1.0 - fx) * fy),
+                    (1, 1, fx * fy),
+                ] {
+                    let texel = self.texel(image, x0 + dx, y0 + dy);
+                    for (b, t) in blended.iter_mut().zip(texel.iter()) {
+                        *b += t * weight;
+                    }
+                }
+                blended
+            }
+        };
+        Srgba::new(r, g, b, a)
+    }
+
+    /// The color of `image` at `point` in `rect`, as though the image were stretched over it.
+    pub fn sample_in<P, C>(&self, image: &ImageBuffer<P, C>, rect: Rect, point: Vector2) -> Srgba
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+        C: Deref<Target = [u8]>,
+    {
+        let uv = (point - rect.bottom_left()) / rect.wh();
+        self.sample(image, uv)
+    }
+}
+
+// rounded down, and kept far enough inside an i64 to add one to (NaN turns into 0).
+fn floor(x: f32) -> i64 {
+    x.floor().max(-1e15).min(1e15) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+    use nannou::image::{Rgb, RgbImage};
+
+    // black, red / green, white, from the top left.
+    fn checks() -> RgbImage {
+        RgbImage::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => Rgb([0, 0, 0]),
+            (1, 0) => Rgb([255, 0, 0]),
+            (0, 1) => Rgb([0, 255, 0]),
+            _ => Rgb([255, 255, 255]),
+        })
+    }
+
+    fn rgb(color: Srgba) -> [f32; 3] {
+        [color.red, color.green, color.blue]
+    }
+
+    #[test]
+    fn test_nearest() {
+        let image = checks();
+        let mut sampler = Sampler::new();
+        sampler.filter = Filter::Nearest;
+        // v goes up, so the top left is at (0, 1).
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(0.25, 0.75))),
+            [0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(0.75, 0.25))),
+            [1.0, 1.0, 1.0]
+        );
+        assert_eq!(sampler.sample(&image, vec2(0.25, 0.75)).alpha, 1.0);
+        // way off the image, or nowhere at all, is still somewhere on it.
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(-9.0, 0.5))),
+            [0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(1e30, -1e30))),
+            [1.0, 1.0, 1.0]
+        );
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(f32::NAN, 0.0))),
+            [0.0, 1.0, 0.0]
+        );
+        sampler.edge = Edge::Wrap;
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(-0.25, 0.25))),
+            [1.0, 1.0, 1.0]
+        );
+        assert_eq!(
+            rgb(sampler.sample(&image, vec2(2.25, 1.75))),
+            [0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_bilinear() {
+        let image = checks();
+        let mut sampler = Sampler::new();
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-5);
+        // pixel middles are exact, and the very middle is all four blended.
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(0.75, 0.75))),
+            [1.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(0.5, 0.5))),
+            [0.5, 0.5, 0.25]
+        ));
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(0.5, 0.75))),
+            [0.5, 0.0, 0.0]
+        ));
+        // held at the edges, past the middles of the edge pixels.
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(0.0, 0.75))),
+            [0.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(-5.0, 0.5))),
+            [0.0, 0.5, 0.0]
+        ));
+        // or blended with the other side.
+        sampler.edge = Edge::Wrap;
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(0.0, 0.75))),
+            [0.5, 0.0, 0.0]
+        ));
+        assert!(close(
+            rgb(sampler.sample(&image, vec2(1.0, 1.0))),
+            [0.5, 0.5, 0.25]
+        ));
+
+        let rect = Rect::from_w_h(2.0, 4.0);
+        assert!(close(
+            rgb(sampler.sample_in(&image, rect, vec2(0.5, -1.0))),
+            [1.0, 1.0, 1.0]
+        ));
+    }
+}
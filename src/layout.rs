@@ -0,0 +1,597 @@
+//! Layered ("Sugiyama-style") layout for drawing circuits left to right.
+//!
+//! Gates are put in layers by rank, wires spanning more than one layer are split up with dummy
+//! nodes so that every wire only joins neighbouring layers, and then each layer is reordered to
+//! reduce wire crossings with the barycenter heuristic: repeatedly sort each layer by the average
+//! position of its neighbours in the layer before (or after) it.
+//!
+//! `route` then turns the layout into Manhattan wire paths. The gap between each pair of
+//! neighbouring columns is a routing channel; each wire's vertical run in a channel is given its
+//! own track with the "left-edge" algorithm, so vertical runs never overlap, and horizontal runs
+//! only pass through columns at dummy node positions, so wires never run through gates.
+//!
+//! `Springs` is a looser alternative, for circuits that are being moved around by hand.
+//! Its repulsion between every pair of gates uses a Barnes-Hut `QuadTree`, so it keeps up with
+//! circuits of thousands of gates.
+
+use crate::circuits::{Circuit, Gate};
+use crate::physics::quadtree::QuadTree;
+use nannou::geom::{vec2, Vector2};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::{HashMap, HashSet};
+
+/// Number of down-and-up passes of crossing reduction.
+const SWEEPS: usize = 12;
+/// Vertical runs closer than this (in layout coordinates) can't share a track.
+const TRACK_CLEARANCE: f32 = 0.01;
+
+/// Lay out a circuit, returning positions in the unit square. Inputs are on the left edge and
+/// outputs that don't drive anything on the right edge. The MetaInput isn't placed.
+pub fn layered(circuit: &Circuit) -> HashMap<NodeIndex, Vector2> {
+    layered_with_waypoints(circuit).0
+}
+
+/// Like `layered`, but also returns the positions of the dummy nodes along each wire spanning more
+/// than one layer, ordered from source to target. Wires drawn through them cross less.
+pub fn layered_with_waypoints(
+    circuit: &Circuit,
+) -> (
+    HashMap<NodeIndex, Vector2>,
+    HashMap<EdgeIndex, Vec<Vector2>>,
+) {
+    Layers::new(circuit).minimize_crossings().positions()
+}
+
+/// Route every wire as a Manhattan path, given node positions and waypoints from
+/// `layered_with_waypoints`. Returns each wire's path from source to target, as a polyline.
+/// Wires from the MetaInput aren't routed.
+///
+/// Other layouts work too, as long as wires run left to right between columns of gates.
+/// Segments that run right to left are left straight.
+pub fn route(
+    circuit: &Circuit,
+    positions: &HashMap<NodeIndex, Vector2>,
+    waypoints: &HashMap<EdgeIndex, Vec<Vector2>>,
+) -> HashMap<EdgeIndex, Vec<Vector2>> {
+    // each wire is a series of segments between neighbouring columns.
+    let mut paths = HashMap::new();
+    for edge in circuit.graph().edge_references() {
+        if edge.source() == Circuit::meta_input() {
+            continue;
+        }
+        let mut points = vec![positions[&edge.source()]];
+        points.extend(waypoints.get(&edge.id()).into_iter().flatten());
+        points.push(positions[&edge.target()]);
+        paths.insert(edge.id(), points);
+    }
+
+    // segments in the same channel sharing a start point (a gate with several outputs) are one
+    // net, and share a track. (channel, net) -> (low y, high y)
+    let net_of = |from: Vector2, to: Vector2| {
+        (
+            (from.x.to_bits(), to.x.to_bits()),
+            (from.x.to_bits(), from.y.to_bits()),
+        )
+    };
+    let mut nets = HashMap::new();
+    for pair in paths.values().flat_map(|points| points.windows(2)) {
+        let (from, to) = (pair[0], pair[1]);
+        if from.x >= to.x || from.y == to.y {
+            continue;
+        }
+        let span = nets.entry(net_of(from, to)).or_insert((from.y, from.y));
+        span.0 = span.0.min(to.y);
+        span.1 = span.1.max(to.y);
+    }
+
+    // left-edge algorithm: sweep nets from the bottom up, putting each on the first free track.
+    let mut channels = HashMap::new();
+    for (&(channel, net), &span) in &nets {
+        channels
+            .entry(channel)
+            .or_insert_with(Vec::new)
+            .push((span, net));
+    }
+    let mut track_x = HashMap::new();
+    for ((from_x, to_x), mut nets) in channels {
+        nets.sort_by(|a, b| (a.0).0.partial_cmp(&(b.0).0).unwrap());
+        let mut track_tops: Vec<f32> = vec![];
+        let mut assigned = vec![];
+        for ((low, high), net) in nets {
+            let track = match track_tops
+                .iter()
+                .position(|top| *top + TRACK_CLEARANCE <= low)
+            {
+                Some(track) => track,
+                None => {
+                    track_tops.push(high);
+                    track_tops.len() - 1
+                }
+            };
+            track_tops[track] = high;
+            assigned.push((net, track));
+        }
+        let (from_x, to_x) = (f32::from_bits(from_x), f32::from_bits(to_x));
+        let tracks = track_tops.len() as f32;
+        for (net, track) in assigned {
+            let x = from_x + (to_x - from_x) * (track + 1) as f32 / (tracks + 1.0);
+            track_x.insert(((from_x.to_bits(), to_x.to_bits()), net), x);
+        }
+    }
+
+    // bend each segment at its track.
+    let mut routes = HashMap::new();
+    for (edge, points) in paths {
+        let mut route = vec![points[0]];
+        for pair in points.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if let Some(x) = track_x.get(&net_of(from, to)) {
+                route.push(vec2(*x, from.y));
+                route.push(vec2(*x, to.y));
+            }
+            route.push(to);
+        }
+        routes.insert(edge, route);
+    }
+    routes
+}
+
+/// Barnes-Hut accuracy for `Springs::repulsion`; it doesn't need to be very exact.
+const REPULSION_THETA: f32 = 0.8;
+
+/// A force-directed layout: every wire is a spring pulling the gates it joins towards
+/// `goal_length` apart, every gate pushes every other away, and every gate drifts right. Inputs,
+/// Outputs and `pinned` gates stay put.
+pub struct Springs {
+    pub goal_length: f32,
+    pub stiffness: f32,
+    /// How hard gates push each other apart, falling off with distance; 0 for not at all.
+    pub repulsion: f32,
+    /// Velocities are multiplied by this every step.
+    pub friction: f32,
+    /// Gates that have been placed by hand.
+    pub pinned: HashSet<NodeIndex>,
+    velocities: HashMap<NodeIndex, Vector2>,
+}
+
+impl Springs {
+    pub fn new(goal_length: f32) -> Springs {
+        Springs {
+            goal_length,
+            stiffness: 3.0,
+            repulsion: 0.0,
+            friction: 0.96,
+            pinned: HashSet::new(),
+            velocities: HashMap::new(),
+        }
+    }
+
+    /// Move every free gate in `positions` one step of `dt` seconds.
+    pub fn step(
+        &mut self,
+        circuit: &Circuit,
+        positions: &mut HashMap<NodeIndex, Vector2>,
+        dt: f32,
+    ) {
+        let tree = if self.repulsion != 0.0 {
+            let gates = circuit
+                .graph()
+                .node_indices()
+                .filter(|node| *node != Circuit::meta_input())
+                .map(|node| (positions[&node], 1.0))
+                .collect::<Vec<_>>();
+            Some(QuadTree::new(&gates))
+        } else {
+            None
+        };
+        for node in circuit.graph().node_indices() {
+            match circuit.graph()[node] {
+                Gate::MetaInput | Gate::Input | Gate::Output => continue,
+                _ if self.pinned.contains(&node) => {
+                    self.velocities.remove(&node);
+                    continue;
+                }
+                _ => (),
+            }
+            let pos = positions[&node];
+            let mut force = vec2(0.0, 0.0);
+            for edge in circuit.graph().edges(node).chain(
+                circuit
+                    .graph()
+                    .edges_directed(node, Direction::Incoming)
+                    .filter(|edge| edge.source() != Circuit::meta_input()),
+            ) {
+                let other = if edge.source() == node {
+                    edge.target()
+                } else {
+                    edge.source()
+                };
+                let d = positions[&other] - pos;
+                force += d.normalize() * (d.magnitude() - self.goal_length) * self.stiffness;
+            }
+            if let Some(tree) = &tree {
+                force += tree.force_on(pos, REPULSION_THETA, |d, count| {
+                    -d * (self.repulsion * count / d.magnitude2())
+                });
+            }
+            force += vec2(1.0 - pos.x, 0.0);
+
+            let vel = self
+                .velocities
+                .entry(node)
+                .or_insert_with(|| vec2(0.0, 0.0));
+            *vel = (*vel + force * dt) * self.friction;
+            positions.insert(node, pos + *vel * dt);
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Slot {
+    Node(NodeIndex),
+    /// The `i`th dummy node along a wire.
+    Dummy(EdgeIndex, usize),
+}
+
+#[derive(Clone)]
+struct Layers {
+    /// The slots in each layer, from top to bottom.
+    layers: Vec<Vec<Slot>>,
+    /// `links[i]` holds the wires between layers `i` and `i + 1`.
+    links: Vec<Vec<(Slot, Slot)>>,
+}
+
+impl Layers {
+    fn new(circuit: &Circuit) -> Layers {
+        let meta = Circuit::meta_input();
+        let mut layer_of = circuit
+            .ranks()
+            .into_iter()
+            .filter(|(node, _)| *node != meta)
+            .map(|(node, rank)| (node, rank as usize - 1))
+            .collect::<HashMap<_, _>>();
+        let last = match layer_of.values().max() {
+            Some(last) => *last,
+            None => {
+                return Layers {
+                    layers: vec![],
+                    links: vec![],
+                }
+            }
+        };
+        // Outputs that drive nothing go on the right edge; ones wired onward stay by rank, so
+        // their wires still run left to right.
+        for (node, layer) in layer_of.iter_mut() {
+            if circuit.graph()[*node] == Gate::Output
+                && circuit
+                    .graph()
+                    .neighbors_directed(*node, Direction::Outgoing)
+                    .next()
+                    .is_none()
+            {
+                *layer = last;
+            }
+        }
+
+        let mut nodes = layer_of.keys().cloned().collect::<Vec<_>>();
+        nodes.sort();
+        let mut layers = vec![vec![]; last + 1];
+        for node in nodes {
+            layers[layer_of[&node]].push(Slot::Node(node));
+        }
+
+        let mut links = vec![vec![]; last];
+        for edge in circuit.graph().edge_references() {
+            if edge.source() == meta {
+                continue;
+            }
+            let (from, to) = (layer_of[&edge.source()], layer_of[&edge.target()]);
+            let mut prev = Slot::Node(edge.source());
+            for (i, layer) in (from + 1..to).enumerate() {
+                let dummy = Slot::Dummy(edge.id(), i);
+                layers[layer].push(dummy);
+                links[layer - 1].push((prev, dummy));
+                prev = dummy;
+            }
+            links[to - 1].push((prev, Slot::Node(edge.target())));
+        }
+
+        Layers { layers, links }
+    }
+
+    fn index_in_layer(&self, layer: usize) -> HashMap<Slot, usize> {
+        self.layers[layer]
+            .iter()
+            .enumerate()
+            .map(|(i, slot)| (*slot, i))
+            .collect()
+    }
+
+    /// Count the wires crossing between every pair of neighbouring layers.
+    fn crossings(&self) -> usize {
+        let mut result = 0;
+        for (i, links) in self.links.iter().enumerate() {
+            let (left, right) = (self.index_in_layer(i), self.index_in_layer(i + 1));
+            let ends = links
+                .iter()
+                .map(|(a, b)| (left[a] as isize, right[b] as isize))
+                .collect::<Vec<_>>();
+            for (j, (a, b)) in ends.iter().enumerate() {
+                for (c, d) in &ends[j + 1..] {
+                    if (a - c) * (b - d) < 0 {
+                        result += 1;
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Sort `layer` by the mean position of each slot's neighbours in the layer `fixed`.
+    /// Slots without neighbours there stay where they are.
+    fn reorder(&mut self, layer: usize, fixed: usize) {
+        let fixed_index = self.index_in_layer(fixed);
+        let links = self.links[layer.min(fixed)].iter().map(|(a, b)| {
+            if fixed < layer {
+                (*b, *a)
+            } else {
+                (*a, *b)
+            }
+        });
+
+        let mut sums = HashMap::new();
+        for (slot, neighbour) in links {
+            let sum = sums.entry(slot).or_insert((0.0, 0));
+            sum.0 += fixed_index[&neighbour] as f32;
+            sum.1 += 1;
+        }
+        let mut keyed = self.layers[layer]
+            .iter()
+            .enumerate()
+            .map(|(i, slot)| match sums.get(slot) {
+                Some((sum, count)) => (sum / *count as f32, *slot),
+                None => (i as f32, *slot),
+            })
+            .collect::<Vec<_>>();
+        keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        self.layers[layer] = keyed.into_iter().map(|(_, slot)| slot).collect();
+    }
+
+    fn minimize_crossings(self) -> Layers {
+        let n = self.layers.len();
+        let mut best_crossings = self.crossings();
+        let mut best = self.clone();
+        let mut current = self;
+        for _ in 0..SWEEPS {
+            if best_crossings == 0 {
+                break;
+            }
+            for layer in 1..n {
+                current.reorder(layer, layer - 1);
+            }
+            for layer in (0..n.saturating_sub(1)).rev() {
+                current.reorder(layer, layer + 1);
+            }
+            let crossings = current.crossings();
+            if crossings < best_crossings {
+                best_crossings = crossings;
+                best = current.clone();
+            }
+        }
+        best
+    }
+
+    fn positions(
+        &self,
+    ) -> (
+        HashMap<NodeIndex, Vector2>,
+        HashMap<EdgeIndex, Vec<Vector2>>,
+    ) {
+        let mut positions = HashMap::new();
+        let mut waypoints = HashMap::new();
+        let n = self.layers.len();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let x = if n > 1 {
+                i as f32 / (n - 1) as f32
+            } else {
+                0.5
+            };
+            for (j, slot) in layer.iter().enumerate() {
+                let pos = vec2(x, 1.0 - (j + 1) as f32 / (layer.len() + 1) as f32);
+                match slot {
+                    Slot::Node(node) => {
+                        positions.insert(*node, pos);
+                    }
+                    // layers are visited in order, so each wire's waypoints are too.
+                    Slot::Dummy(edge, _) => {
+                        waypoints.entry(*edge).or_insert_with(Vec::new).push(pos)
+                    }
+                }
+            }
+        }
+        (positions, waypoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layered() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (s, c) = circuit.ripple_carry(&a, &b);
+        for si in s {
+            circuit.add_output(si);
+        }
+        circuit.add_output(c);
+
+        let (positions, waypoints) = layered_with_waypoints(&circuit);
+        assert_eq!(positions.len(), circuit.graph().node_count() - 1);
+        assert!(!positions.contains_key(&Circuit::meta_input()));
+        for (node, pos) in &positions {
+            assert!(pos.x >= 0.0 && pos.x <= 1.0 && pos.y > 0.0 && pos.y < 1.0);
+            match circuit.graph()[*node] {
+                Gate::Input => assert_eq!(pos.x, 0.0),
+                Gate::Output => assert_eq!(pos.x, 1.0),
+                _ => (),
+            }
+        }
+
+        // wires go left to right, through their waypoints.
+        for edge in circuit.graph().edge_references() {
+            if edge.source() == Circuit::meta_input() {
+                continue;
+            }
+            let mut xs = vec![positions[&edge.source()].x];
+            xs.extend(waypoints.get(&edge.id()).into_iter().flatten().map(|p| p.x));
+            xs.push(positions[&edge.target()].x);
+            assert!(xs.windows(2).all(|w| w[0] < w[1]), "{:?}", xs);
+        }
+
+        let layers = Layers::new(&circuit);
+        let before = layers.crossings();
+        assert!(layers.minimize_crossings().crossings() < before);
+    }
+
+    #[test]
+    fn test_layered_output_driving_gate() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let x = circuit.add_and(a, b);
+        let x = circuit.add_output(x);
+        let not_x = circuit.add_not(x);
+        let not_x = circuit.add_output(not_x);
+
+        let (positions, waypoints) = layered_with_waypoints(&circuit);
+        assert!(positions[&x].x < positions[&not_x].x);
+        assert_eq!(positions[&not_x].x, 1.0);
+        for edge in circuit.graph().edge_references() {
+            if edge.source() == Circuit::meta_input() {
+                continue;
+            }
+            let mut xs = vec![positions[&edge.source()].x];
+            xs.extend(waypoints.get(&edge.id()).into_iter().flatten().map(|p| p.x));
+            xs.push(positions[&edge.target()].x);
+            assert!(xs.windows(2).all(|w| w[0] < w[1]), "{:?}", xs);
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let mut circuit = Circuit::new();
+        let a = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| circuit.add_input()).collect::<Vec<_>>();
+        let (s, c) = circuit.ripple_carry(&a, &b);
+        for si in s {
+            circuit.add_output(si);
+        }
+        circuit.add_output(c);
+
+        let (positions, waypoints) = layered_with_waypoints(&circuit);
+        let routes = route(&circuit, &positions, &waypoints);
+
+        // (x, low y, high y, start of net) for every vertical run.
+        let mut verticals = vec![];
+        for edge in circuit.graph().edge_references() {
+            if edge.source() == Circuit::meta_input() {
+                assert!(!routes.contains_key(&edge.id()));
+                continue;
+            }
+            let route = &routes[&edge.id()];
+            assert_eq!(route[0], positions[&edge.source()]);
+            assert_eq!(*route.last().unwrap(), positions[&edge.target()]);
+            for pair in route.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                assert!(from.x == to.x || from.y == to.y, "not Manhattan");
+                if from.x == to.x && from.y != to.y {
+                    verticals.push((from.x, from.y.min(to.y), from.y.max(to.y), edge.source()));
+                    // vertical runs are strictly between columns, so they can't pass through gates.
+                    assert!(positions.values().all(|p| p.x != from.x));
+                }
+            }
+        }
+
+        // only runs from the same gate may overlap.
+        for (i, (x, low, high, net)) in verticals.iter().enumerate() {
+            for (x_, low_, high_, net_) in &verticals[i + 1..] {
+                if x == x_ && net != net_ {
+                    assert!(high < low_ || high_ < low, "vertical runs overlap");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uncross() {
+        // the nots are created in the opposite order to their inputs, so they start out crossed.
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let b = circuit.add_input();
+        let not_b = circuit.add_not(b);
+        let not_a = circuit.add_not(a);
+
+        let layers = Layers::new(&circuit);
+        assert_eq!(layers.crossings(), 1);
+        assert_eq!(layers.minimize_crossings().crossings(), 0);
+
+        let positions = layered(&circuit);
+        assert_eq!(
+            positions[&a].y > positions[&b].y,
+            positions[&not_a].y > positions[&not_b].y
+        );
+    }
+
+    #[test]
+    fn test_springs() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let n = circuit.add_not(a);
+        let m = circuit.add_not(n);
+        let out = circuit.add_output(m);
+
+        let mut positions = HashMap::new();
+        positions.insert(a, vec2(0.0, 0.5));
+        positions.insert(n, vec2(0.3, 0.9));
+        positions.insert(m, vec2(0.6, 0.1));
+        positions.insert(out, vec2(1.0, 0.5));
+        let start = positions.clone();
+
+        let mut springs = Springs::new(0.3);
+        springs.pinned.insert(m);
+        for _ in 0..1000 {
+            springs.step(&circuit, &mut positions, 1.0 / 60.0);
+        }
+        assert_eq!(positions[&a], start[&a]);
+        assert_eq!(positions[&m], start[&m]);
+        assert_eq!(positions[&out], start[&out]);
+        assert!(positions[&n] != start[&n]);
+        // n hangs between its neighbours, pulled a little right.
+        assert!(positions[&n].y > start[&m].y && positions[&n].y < start[&a].y);
+
+        // unconnected gates in a pile spread out.
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input();
+        let gates = (0..50).map(|_| circuit.add_not(a)).collect::<Vec<_>>();
+        let mut positions = HashMap::new();
+        positions.insert(a, vec2(0.0, 0.5));
+        for (i, gate) in gates.iter().enumerate() {
+            positions.insert(*gate, vec2(0.5, 0.5 + i as f32 * 1e-3));
+        }
+        let mut springs = Springs::new(0.3);
+        springs.stiffness = 0.0;
+        springs.repulsion = 1e-3;
+        for _ in 0..200 {
+            springs.step(&circuit, &mut positions, 1.0 / 60.0);
+        }
+        let ys = gates.iter().map(|g| positions[g].y).collect::<Vec<_>>();
+        let spread = ys.iter().cloned().fold(f32::MIN, f32::max)
+            - ys.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(spread > 0.2, "{}", spread);
+    }
+}
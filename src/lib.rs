@@ -1 +1,38 @@
+pub mod attractors;
+pub mod automata;
+pub mod capture;
+pub mod chladni;
 pub mod circuits;
+pub mod cli;
+pub mod config;
+pub mod contour;
+pub mod dla;
+pub mod ease;
+pub mod flow;
+pub mod fluid;
+pub mod gamepad;
+pub mod grid;
+pub mod harmonograph;
+pub mod heat;
+pub mod hud;
+pub mod image_sample;
+pub mod layout;
+pub mod lsystem;
+pub mod midi;
+pub mod osc;
+pub mod params;
+pub mod physarum;
+pub mod phyllotaxis;
+pub mod physics;
+pub mod reaction_diffusion;
+pub mod recorder;
+pub mod render_circuit;
+pub mod replay;
+pub mod rng;
+pub mod sketch;
+pub mod spirograph;
+pub mod timeline;
+pub mod viz;
+pub mod wave;
+pub mod webcam;
+pub mod wind;
@@ -0,0 +1,246 @@
+//! L-systems: strings grown by rewriting every symbol in them at once, over and over, by a set of
+//! rules, then read out as instructions for a turtle to draw. A few short rules grow into
+//! plants, snowflakes and space-filling curves.
+//!
+//! The turtle understands:
+//!
+//! - `F` and `G`: move forward, drawing a line.
+//! - `f`: move forward without drawing.
+//! - `+` and `-`: turn left and right.
+//! - `|`: turn around.
+//! - `[` and `]`: remember where the turtle is and which way it's facing, and go back there,
+//!   starting a new branch.
+//!
+//! and ignores anything else, which is handy for symbols that only steer how the string grows.
+
+use nannou::geom::{vec2, Vector2};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+#[derive(Clone, Debug)]
+pub struct LSystem {
+    pub axiom: String,
+    rules: HashMap<char, String>,
+}
+
+impl LSystem {
+    /// An L-system growing from `axiom`, by `rules` like `"F -> F[+F]F"` (or `"F = F[+F]F"`),
+    /// each replacing a single symbol; symbols without a rule are left as they are.
+    pub fn parse(axiom: &str, rules: &[&str]) -> LSystem {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let (symbol, replacement) = if let Some(arrow) = rule.find("->") {
+                    (&rule[..arrow], &rule[arrow + 2..])
+                } else if let Some(equals) = rule.find('=') {
+                    (&rule[..equals], &rule[equals + 1..])
+                } else {
+                    panic!("rule {:?} should look like \"F -> FF\"", rule);
+                };
+                let mut symbol = symbol.trim().chars();
+                match (symbol.next(), symbol.next()) {
+                    (Some(symbol), None) => (symbol, replacement.trim().to_string()),
+                    _ => panic!("rule {:?} should replace a single symbol", rule),
+                }
+            })
+            .collect();
+        LSystem {
+            axiom: axiom.to_string(),
+            rules,
+        }
+    }
+
+    /// What `symbol` is replaced by, if it has a rule.
+    pub fn rule(&self, symbol: char) -> Option<&str> {
+        self.rules.get(&symbol).map(|r| r.as_str())
+    }
+
+    /// The string after `iterations` rounds of rewriting the axiom.
+    pub fn generate(&self, iterations: usize) -> String {
+        let mut string = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = String::with_capacity(string.len() * 2);
+            for symbol in string.chars() {
+                match self.rules.get(&symbol) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(symbol),
+                }
+            }
+            string = next;
+        }
+        string
+    }
+}
+
+/// A line the turtle drew without lifting its pen or going back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Branch {
+    pub points: Vec<Vector2>,
+    /// How many `[`s deep it was drawn.
+    pub depth: usize,
+    /// Which branch, and which of its points, it grew from; `None` for one the turtle started
+    /// with its pen up.
+    pub parent: Option<(usize, usize)>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Turtle {
+    /// Where it starts.
+    pub start: Vector2,
+    /// Which way it starts out facing, in radians anticlockwise from the right.
+    pub heading: f32,
+    /// How far it moves forward each step.
+    pub step: f32,
+    /// How far it turns, in radians.
+    pub angle: f32,
+    /// What its step is multiplied by for each `[` it's inside, so twigs are shorter than
+    /// branches; 1 for the same everywhere.
+    pub shrink: f32,
+}
+
+impl Turtle {
+    /// A turtle starting at the origin, facing up, taking steps of 1 and turning by `angle`
+    /// degrees.
+    pub fn new(angle: f32) -> Turtle {
+        Turtle {
+            start: vec2(0.0, 0.0),
+            heading: PI / 2.0,
+            step: 1.0,
+            angle: angle.to_radians(),
+            shrink: 1.0,
+        }
+    }
+
+    /// Follow `commands`, returning every branch drawn, each after the one it grew from.
+    pub fn interpret(&self, commands: &str) -> Vec<Branch> {
+        #[derive(Copy, Clone)]
+        struct State {
+            pos: Vector2,
+            heading: f32,
+            depth: usize,
+            // the branch and point the turtle's at, if it's on one.
+            at: Option<(usize, usize)>,
+            // whether it's just passed a `[`, so whatever it draws next is a new branch.
+            sprouting: bool,
+        }
+        let mut branches: Vec<Branch> = vec![];
+        let mut stack = vec![];
+        let mut state = State {
+            pos: self.start,
+            heading: self.heading,
+            depth: 0,
+            at: None,
+            sprouting: false,
+        };
+        for command in commands.chars() {
+            match command {
+                'F' | 'G' | 'f' => {
+                    let step = self.step * self.shrink.powi(state.depth as i32);
+                    state.pos += vec2(state.heading.cos(), state.heading.sin()) * step;
+                    if command == 'f' {
+                        state.at = None;
+                        continue;
+                    }
+                    let sprouting = std::mem::replace(&mut state.sprouting, false);
+                    state.at = Some(match state.at {
+                        // carrying on from the end of a branch.
+                        Some((i, point)) if !sprouting && point + 1 == branches[i].points.len() => {
+                            branches[i].points.push(state.pos);
+                            (i, point + 1)
+                        }
+                        // or sprouting a new one.
+                        parent => {
+                            let start = match parent {
+                                Some((i, point)) => branches[i].points[point],
+                                None => {
+                                    state.pos
+                                        - vec2(state.heading.cos(), state.heading.sin()) * step
+                                }
+                            };
+                            branches.push(Branch {
+                                points: vec![start, state.pos],
+                                depth: state.depth,
+                                parent,
+                            });
+                            (branches.len() - 1, 1)
+                        }
+                    });
+                }
+                '+' => state.heading += self.angle,
+                '-' => state.heading -= self.angle,
+                '|' => state.heading += PI,
+                '[' => {
+                    stack.push(state);
+                    state.depth += 1;
+                    state.sprouting = true;
+                }
+                ']' => state = stack.pop().expect("unmatched ]"),
+                _ => (),
+            }
+        }
+        branches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        // Lindenmayer's algae, which grow in Fibonacci numbers.
+        let algae = LSystem::parse("A", &["A -> AB", "B=A"]);
+        assert_eq!(algae.rule('A'), Some("AB"));
+        assert_eq!(algae.rule('C'), None);
+        assert_eq!(algae.generate(0), "A");
+        assert_eq!(algae.generate(4), "ABAABABA");
+        let lengths = (0..10).map(|n| algae.generate(n).len()).collect::<Vec<_>>();
+        assert_eq!(lengths, vec![1, 2, 3, 5, 8, 13, 21, 34, 55, 89]);
+    }
+
+    #[test]
+    #[should_panic(expected = "should replace a single symbol")]
+    fn test_parse_error() {
+        LSystem::parse("F", &["FF -> F"]);
+    }
+
+    #[test]
+    fn test_koch() {
+        // the Koch curve, which ends up where it would have in one step, three times as long.
+        let koch = LSystem::parse("F", &["F -> F+F--F+F"]);
+        let mut turtle = Turtle::new(60.0);
+        turtle.heading = 0.0;
+        let branches = turtle.interpret(&koch.generate(3));
+        assert_eq!(branches.len(), 1);
+        let points = &branches[0].points;
+        assert_eq!(points.len(), 4usize.pow(3) + 1);
+        assert!((points[points.len() - 1] - vec2(27.0, 0.0)).magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut turtle = Turtle::new(90.0);
+        turtle.shrink = 0.5;
+        let branches = turtle.interpret("F[+F]F[[-F]F]+F");
+        let expected = vec![
+            (
+                vec![(0.0, 0.0), (0.0, 1.0), (0.0, 2.0), (-1.0, 2.0)],
+                0,
+                None,
+            ),
+            // twigs, half as long, and their twigs a quarter.
+            (vec![(0.0, 1.0), (-0.5, 1.0)], 1, Some((0, 1))),
+            (vec![(0.0, 2.0), (0.25, 2.0)], 2, Some((0, 2))),
+            (vec![(0.0, 2.0), (0.0, 2.5)], 1, Some((0, 2))),
+        ];
+        assert_eq!(branches.len(), expected.len());
+        for (branch, (points, depth, parent)) in branches.iter().zip(expected) {
+            assert_eq!(branch.points.len(), points.len());
+            for (point, (x, y)) in branch.points.iter().zip(points) {
+                assert!((*point - vec2(x, y)).magnitude() < 1e-5, "{:?}", branch);
+            }
+            assert_eq!(branch.depth, depth);
+            assert_eq!(branch.parent, parent);
+        }
+    }
+}
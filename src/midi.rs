@@ -0,0 +1,137 @@
+//! Playing a sketch's parameters from a MIDI controller's knobs and faders, live, rather than
+//! dragging their sliders about with the mouse.
+//!
+//! Each control change (CC) number is mapped to a parameter in the sketch's `Params`, and the
+//! latest position of every knob turned since the last update is put to it then:
+//!
+//! ```ignore
+//! // when building the model:
+//! let mut midi = Midi::connect("").unwrap();
+//! midi.map(21, "gravity");
+//! // every update:
+//! midi.apply(&mut model.params);
+//! ```
+
+use crate::params::Params;
+use midir::{MidiInput, MidiInputConnection};
+use std::sync::mpsc::{channel, Receiver};
+
+pub struct Midi {
+    // messages arrive on midir's thread; this has to be kept for them to keep coming.
+    _connection: Option<MidiInputConnection<()>>,
+    // each control change's number and value, from 0 to 127.
+    changes: Receiver<(u8, u8)>,
+    // what each control change number sets: a parameter, and which channel of it.
+    mappings: Vec<(u8, String, usize)>,
+}
+
+impl Midi {
+    /// Listen to the first MIDI input whose name contains `port`, so `""` is whichever comes first.
+    pub fn connect(port: &str) -> Result<Midi, String> {
+        let input = MidiInput::new("nannou_sketches").map_err(|e| e.to_string())?;
+        let found = input
+            .ports()
+            .into_iter()
+            .find(|p| match input.port_name(p) {
+                Ok(name) => name.contains(port),
+                Err(_) => false,
+            });
+        let found = found.ok_or_else(|| format!("there's no MIDI input called {:?}", port))?;
+        let name = input.port_name(&found).map_err(|e| e.to_string())?;
+        let (send, changes) = channel();
+        let connection = input
+            .connect(
+                &found,
+                "nannou_sketches",
+                move |_, message, _| {
+                    if let Some((_, cc, value)) = control_change(message) {
+                        // nothing's listening once the sketch has gone.
+                        let _ = send.send((cc, value));
+                    }
+                },
+                (),
+            )
+            .map_err(|e| e.to_string())?;
+        println!("listening to {}", name);
+        Ok(Midi {
+            _connection: Some(connection),
+            changes,
+            mappings: vec![],
+        })
+    }
+
+    /// Turning control `cc` sets the parameter `name`.
+    pub fn map(&mut self, cc: u8, name: &str) {
+        self.map_channel(cc, name, 0);
+    }
+
+    /// Turning control `cc` sets `channel` of the parameter `name`, such as a color's green.
+    pub fn map_channel(&mut self, cc: u8, name: &str, channel: usize) {
+        assert!(cc < 128, "there's no MIDI control {}", cc);
+        self.mappings.push((cc, name.to_string(), channel));
+    }
+
+    /// Put every control turned since last time to the parameters it's mapped to.
+    pub fn apply(&self, params: &mut Params) {
+        for (cc, value) in self.changes.try_iter() {
+            for (_, name, channel) in self.mappings.iter().filter(|m| m.0 == cc) {
+                params.set_fraction(name, *channel, value as f32 / 127.0);
+            }
+        }
+    }
+}
+
+/// The MIDI channel, control number and value of `message`, if it's a control change.
+fn control_change(message: &[u8]) -> Option<(u8, u8, u8)> {
+    match message {
+        [status, cc, value] if status & 0xf0 == 0xb0 => Some((status & 0x0f, *cc, *value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Sender;
+
+    // a `Midi` that's sent changes by hand.
+    fn offline() -> (Midi, Sender<(u8, u8)>) {
+        let (send, changes) = channel();
+        let midi = Midi {
+            _connection: None,
+            changes,
+            mappings: vec![],
+        };
+        (midi, send)
+    }
+
+    #[test]
+    fn test_control_change() {
+        assert_eq!(control_change(&[0xb3, 21, 64]), Some((3, 21, 64)));
+        // a note on.
+        assert_eq!(control_change(&[0x90, 60, 100]), None);
+        assert_eq!(control_change(&[0xb0, 21]), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut params = Params::new();
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        params.add_int("n", 10, 0, 127);
+        let (mut midi, send) = offline();
+        midi.map(21, "gravity");
+        midi.map(22, "n");
+
+        midi.apply(&mut params);
+        assert_eq!(params.float("gravity"), 5.0);
+        // only the latest position counts, and unmapped controls are ignored.
+        send.send((21, 0)).unwrap();
+        send.send((21, 127)).unwrap();
+        send.send((22, 100)).unwrap();
+        send.send((23, 5)).unwrap();
+        midi.apply(&mut params);
+        assert_eq!(params.float("gravity"), 20.0);
+        assert_eq!(params.int("n"), 100);
+        assert!(params.changed("n"));
+    }
+}
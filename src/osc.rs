@@ -0,0 +1,149 @@
+//! Talking to other software over OSC, like SuperCollider making sounds from a sketch or
+//! TouchDesigner and TouchOSC driving it.
+//!
+//! Everything's addressed under the sketch's name. Messages to `/name/<param>`, with a number
+//! from 0 to 1 for each channel, set that parameter in the sketch's `Params` as a MIDI knob would
+//! (spaces in its name are underscores). The sketch sends its own events, like collisions, as
+//! `/name/<event>`:
+//!
+//! ```ignore
+//! // when building the model:
+//! let mut osc = Osc::new("bouncing_3");
+//! osc.listen(9000).unwrap();
+//! osc.send_to("127.0.0.1:57120").unwrap();
+//! // every update:
+//! osc.apply(&mut model.params);
+//! osc.send("collision", vec![Type::Float(speed)]);
+//! ```
+
+use crate::params::Params;
+use nannou_osc::{Connected, Receiver, Sender, Type};
+
+pub struct Osc {
+    name: String,
+    receiver: Option<Receiver>,
+    sender: Option<Sender<Connected>>,
+}
+
+impl Osc {
+    /// Neither listening nor sending, yet, for the sketch called `name`.
+    pub fn new(name: &str) -> Osc {
+        Osc {
+            name: name.to_string(),
+            receiver: None,
+            sender: None,
+        }
+    }
+
+    /// Listen for parameters on UDP `port`.
+    pub fn listen(&mut self, port: u16) -> Result<(), String> {
+        let receiver = nannou_osc::receiver(port).map_err(|e| e.to_string())?;
+        println!("listening for OSC on port {}", port);
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+
+    /// Send events to `addr`, like `127.0.0.1:57120` for SuperCollider on the same machine.
+    pub fn send_to(&mut self, addr: &str) -> Result<(), String> {
+        let sender = nannou_osc::sender()
+            .and_then(|sender| sender.connect(addr))
+            .map_err(|e| e.to_string())?;
+        self.sender = Some(sender);
+        Ok(())
+    }
+
+    /// Set the parameters sent since last time.
+    pub fn apply(&self, params: &mut Params) {
+        let receiver = match &self.receiver {
+            Some(receiver) => receiver,
+            None => return,
+        };
+        for (packet, _) in receiver.try_iter() {
+            for message in packet.into_msgs() {
+                let args = message.args.unwrap_or_default();
+                self.set(params, &message.addr, &args);
+            }
+        }
+    }
+
+    // set the parameter `addr` is for, if it's one of ours, from `args`.
+    fn set(&self, params: &mut Params, addr: &str, args: &[Type]) {
+        let param = match addr
+            .strip_prefix('/')
+            .and_then(|addr| addr.strip_prefix(self.name.as_str()))
+            .and_then(|addr| addr.strip_prefix('/'))
+        {
+            Some(param) => param,
+            None => return,
+        };
+        let name = params
+            .values()
+            .into_iter()
+            .map(|(name, _)| name)
+            .find(|name| name.replace(' ', "_") == param)
+            .map(str::to_string);
+        let name = match name {
+            Some(name) => name,
+            None => return,
+        };
+        for (channel, arg) in args.iter().enumerate() {
+            let fraction = match *arg {
+                Type::Float(f) => f,
+                Type::Double(f) => f as f32,
+                // like a toggle's 0 and 1.
+                Type::Int(i) => i as f32,
+                _ => continue,
+            };
+            // a color has three; anything past that's ignored.
+            if channel < params.channels(&name) {
+                params.set_fraction(&name, channel, fraction);
+            }
+        }
+    }
+
+    /// Send `/name/<event>` with `args`, if sending anywhere.
+    pub fn send(&self, event: &str, args: Vec<Type>) {
+        if let Some(sender) = &self.sender {
+            let addr = format!("/{}/{}", self.name, event);
+            // it's only UDP; a lost event's no matter.
+            let _ = sender.send((addr, args));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::color::Srgb;
+
+    #[test]
+    fn test_set() {
+        let osc = Osc::new("bouncing_3");
+        let mut params = Params::new();
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        params.add_bool("smooth colors", false);
+        params.add_color("ink", Srgb::new(0, 0, 0));
+
+        osc.set(&mut params, "/bouncing_3/gravity", &[Type::Float(0.5)]);
+        assert_eq!(params.float("gravity"), 10.0);
+        osc.set(&mut params, "/bouncing_3/smooth_colors", &[Type::Int(1)]);
+        assert!(params.bool("smooth colors"));
+        osc.set(
+            &mut params,
+            "/bouncing_3/ink",
+            &[
+                Type::Float(1.0),
+                Type::Double(0.0),
+                Type::Float(1.0),
+                Type::Float(1.0),
+            ],
+        );
+        assert_eq!(params.color("ink"), Srgb::new(255, 0, 255));
+
+        // other sketches', and parameters it hasn't got, are left alone.
+        osc.set(&mut params, "/bouncing_2/gravity", &[Type::Float(0.0)]);
+        osc.set(&mut params, "/bouncing_3/gravityx", &[Type::Float(0.0)]);
+        osc.set(&mut params, "/bouncing_3", &[Type::Float(0.0)]);
+        assert_eq!(params.float("gravity"), 10.0);
+    }
+}
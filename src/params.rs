@@ -0,0 +1,486 @@
+//! A panel of sliders and checkboxes for tweaking a sketch while it runs, rather than editing the
+//! constants at the top of it and recompiling.
+//!
+//! Parameters are registered by name once, then read back by name every frame. The panel sits in
+//! the top right corner of the window, and is worked with the mouse like a `Dragger`:
+//!
+//! ```ignore
+//! // when building the model:
+//! params.add_float("gravity", 5.0, 0.0, 20.0);
+//! // on mouse down, skipping the sketch's own handling if it lands on the panel:
+//! if !model.params.press(win, mouse) { ... }
+//! // on mouse move and up:
+//! model.params.move_to(win, mouse);
+//! model.params.release();
+//! // every update:
+//! model.world.gravity = vec2(0.0, -model.params.float("gravity"));
+//! ```
+//!
+//! (nannou_egui would be the usual way to do this, but it needs a newer nannou than this crate's.)
+
+use nannou::color::{rgba, Srgb};
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::Draw;
+
+const WIDTH: f32 = 260.0;
+const ROW: f32 = 20.0;
+const PADDING: f32 = 8.0;
+// how much of each row the name takes, and the value after the slider.
+const LABEL: f32 = 100.0;
+const VALUE: f32 = 44.0;
+const FONT_SIZE: u32 = 11;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Value {
+    Float {
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    Int {
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    Bool(bool),
+    /// A slider for each of red, green and blue.
+    Color(Srgb<u8>),
+}
+
+impl Value {
+    fn channels(&self) -> usize {
+        match self {
+            Value::Color(_) => 3,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Param {
+    name: String,
+    value: Value,
+    // whether it's been changed since the sketch last asked.
+    changed: bool,
+}
+
+/// One row of the panel, for one channel of a parameter.
+#[derive(Copy, Clone, Debug)]
+struct Row {
+    param: usize,
+    channel: usize,
+    rect: Rect,
+}
+
+impl Row {
+    // the part of the row the slider runs along.
+    fn track(&self) -> Rect {
+        let r = self.rect;
+        Rect::from_corners(
+            vec2(r.left() + LABEL, r.bottom() + 4.0),
+            vec2(r.right() - VALUE, r.top() - 4.0),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Params {
+    params: Vec<Param>,
+    /// Whether the panel's shown; hidden, it ignores the mouse.
+    pub visible: bool,
+    // the parameter and channel whose slider is held.
+    dragging: Option<(usize, usize)>,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            params: vec![],
+            visible: true,
+            dragging: None,
+        }
+    }
+}
+
+impl Params {
+    pub fn new() -> Params {
+        Params::default()
+    }
+
+    fn add(&mut self, name: &str, value: Value) {
+        assert!(
+            self.find(name).is_none(),
+            "there's already a parameter called {}",
+            name
+        );
+        self.params.push(Param {
+            name: name.to_string(),
+            value,
+            changed: false,
+        });
+    }
+
+    /// A number from `min` to `max`, starting at `value`.
+    pub fn add_float(&mut self, name: &str, value: f32, min: f32, max: f32) {
+        assert!(min < max, "{} has to have some range", name);
+        self.add(name, Value::Float { value, min, max });
+    }
+
+    /// A whole number from `min` to `max`, starting at `value`.
+    pub fn add_int(&mut self, name: &str, value: i32, min: i32, max: i32) {
+        assert!(min < max, "{} has to have some range", name);
+        self.add(name, Value::Int { value, min, max });
+    }
+
+    pub fn add_bool(&mut self, name: &str, value: bool) {
+        self.add(name, Value::Bool(value));
+    }
+
+    pub fn add_color(&mut self, name: &str, value: Srgb<u8>) {
+        self.add(name, Value::Color(value));
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        self.params.iter().position(|p| p.name == name)
+    }
+
+    fn get(&self, name: &str) -> Value {
+        match self.find(name) {
+            Some(i) => self.params[i].value,
+            None => panic!("there's no parameter called {}", name),
+        }
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        match self.get(name) {
+            Value::Float { value, .. } => value,
+            _ => panic!("{} isn't a float", name),
+        }
+    }
+
+    pub fn int(&self, name: &str) -> i32 {
+        match self.get(name) {
+            Value::Int { value, .. } => value,
+            _ => panic!("{} isn't an int", name),
+        }
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        match self.get(name) {
+            Value::Bool(value) => value,
+            _ => panic!("{} isn't a bool", name),
+        }
+    }
+
+    pub fn color(&self, name: &str) -> Srgb<u8> {
+        match self.get(name) {
+            Value::Color(value) => value,
+            _ => panic!("{} isn't a color", name),
+        }
+    }
+
+    /// Every parameter's name and value, written out, in the order they were added.
+    pub fn values(&self) -> Vec<(&str, String)> {
+        self.params
+            .iter()
+            .map(|p| {
+                let value = match p.value {
+                    Value::Float { value, .. } => format!("{:.2}", value),
+                    Value::Int { value, .. } => format!("{}", value),
+                    Value::Bool(value) => (if value { "on" } else { "off" }).to_string(),
+                    Value::Color(c) => format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue),
+                };
+                (p.name.as_str(), value)
+            })
+            .collect()
+    }
+
+    /// Whether `name` has been changed on the panel since the last time this was asked, for
+    /// parameters that mean starting over, like how many things there are.
+    pub fn changed(&mut self, name: &str) -> bool {
+        let i = self
+            .find(name)
+            .unwrap_or_else(|| panic!("there's no parameter called {}", name));
+        std::mem::replace(&mut self.params[i].changed, false)
+    }
+
+    // every row, top to bottom, placed in `win`'s top right corner.
+    fn rows(&self, win: Rect) -> Vec<Row> {
+        let mut rows = vec![];
+        let mut top = win.top() - PADDING * 2.0;
+        let left = win.right() - PADDING * 2.0 - WIDTH;
+        for (param, p) in self.params.iter().enumerate() {
+            for channel in 0..p.value.channels() {
+                let rect = Rect::from_corners(vec2(left, top - ROW), vec2(left + WIDTH, top));
+                rows.push(Row {
+                    param,
+                    channel,
+                    rect,
+                });
+                top -= ROW;
+            }
+        }
+        rows
+    }
+
+    /// The whole panel, background and all.
+    pub fn rect(&self, win: Rect) -> Rect {
+        let rows = self
+            .params
+            .iter()
+            .map(|p| p.value.channels())
+            .sum::<usize>();
+        let h = rows as f32 * ROW + PADDING * 2.0;
+        Rect::from_w_h(WIDTH + PADDING * 2.0, h).top_right_of(win.pad(PADDING))
+    }
+
+    // set a slider from how far along its track `x` is.
+    fn slide(&mut self, win: Rect, param: usize, channel: usize, x: f32) {
+        let row = self
+            .rows(win)
+            .into_iter()
+            .find(|r| r.param == param && r.channel == channel);
+        let track = row.unwrap().track();
+        self.set(param, channel, (x - track.left()) / track.w());
+    }
+
+    /// How many sliders `name` has: three for a color, and one for anything else.
+    pub fn channels(&self, name: &str) -> usize {
+        self.get(name).channels()
+    }
+
+    /// Set `channel` of `name` (there's only one unless it's a color) `fraction` of the way along
+    /// its slider, as a knob might; a checkbox is ticked past halfway.
+    pub fn set_fraction(&mut self, name: &str, channel: usize, fraction: f32) {
+        let i = self
+            .find(name)
+            .unwrap_or_else(|| panic!("there's no parameter called {}", name));
+        assert!(
+            channel < self.params[i].value.channels(),
+            "{} has no channel {}",
+            name,
+            channel
+        );
+        self.set(i, channel, fraction);
+    }
+
+    fn set(&mut self, param: usize, channel: usize, fraction: f32) {
+        let f = fraction.max(0.0).min(1.0);
+        let p = &mut self.params[param];
+        let before = p.value;
+        match &mut p.value {
+            Value::Float { value, min, max } => *value = *min + (*max - *min) * f,
+            Value::Int { value, min, max } => {
+                *value = *min + ((*max - *min) as f32 * f).round() as i32
+            }
+            Value::Color(color) => {
+                let c = (f * 255.0).round() as u8;
+                match channel {
+                    0 => color.red = c,
+                    1 => color.green = c,
+                    _ => color.blue = c,
+                }
+            }
+            Value::Bool(value) => *value = f >= 0.5,
+        }
+        p.changed |= p.value != before;
+    }
+
+    /// The mouse went down at `pos`: flip a checkbox, or grab a slider and move it there. Returns
+    /// whether it landed on the panel, so the sketch can leave it alone.
+    pub fn press(&mut self, win: Rect, pos: Vector2) -> bool {
+        if !self.visible || !self.rect(win).contains(pos) {
+            return false;
+        }
+        let row = self.rows(win).into_iter().find(|r| r.rect.contains(pos));
+        if let Some(row) = row {
+            let p = &mut self.params[row.param];
+            if let Value::Bool(value) = &mut p.value {
+                *value = !*value;
+                p.changed = true;
+            } else {
+                self.dragging = Some((row.param, row.channel));
+                self.slide(win, row.param, row.channel, pos.x);
+            }
+        }
+        true
+    }
+
+    /// The mouse moved to `pos`, taking any slider held with it.
+    pub fn move_to(&mut self, win: Rect, pos: Vector2) {
+        if let Some((param, channel)) = self.dragging {
+            self.slide(win, param, channel, pos.x);
+        }
+    }
+
+    /// Let go of whatever slider's held.
+    pub fn release(&mut self) {
+        self.dragging = None;
+    }
+
+    /// Whether a slider's held, so the sketch can ignore the mouse meanwhile.
+    pub fn dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    pub fn draw(&self, draw: &Draw, win: Rect) {
+        if !self.visible {
+            return;
+        }
+        let panel = self.rect(win);
+        draw.rect()
+            .xy(panel.xy())
+            .wh(panel.wh())
+            .color(rgba(0.08, 0.08, 0.1, 0.85))
+            .stroke(rgba(0.6, 0.6, 0.65, 1.0))
+            .stroke_weight(1.0);
+        let text = |text: &str, rect: Rect| {
+            draw.text(text)
+                .xy(rect.xy())
+                .wh(rect.wh())
+                .font_size(FONT_SIZE)
+                .left_justify()
+                .color(rgba(1.0, 1.0, 1.0, 1.0));
+        };
+        for row in self.rows(win) {
+            let p = &self.params[row.param];
+            let r = row.rect;
+            let label = Rect::from_corners(r.bottom_left(), vec2(r.left() + LABEL - 4.0, r.top()));
+            let value =
+                Rect::from_corners(vec2(r.right() - VALUE + 4.0, r.bottom()), r.top_right());
+            let track = row.track();
+            let name = match (p.value, row.channel) {
+                (Value::Color(_), channel) => format!("{} {}", p.name, ["r", "g", "b"][channel]),
+                _ => p.name.clone(),
+            };
+            text(&name, label);
+
+            // how far along the slider is, and what to write after it.
+            let (f, shown) = match p.value {
+                Value::Float { value, min, max } => {
+                    ((value - min) / (max - min), format!("{:.2}", value))
+                }
+                Value::Int { value, min, max } => (
+                    (value - min) as f32 / (max - min) as f32,
+                    format!("{}", value),
+                ),
+                Value::Color(color) => {
+                    let c = [color.red, color.green, color.blue][row.channel];
+                    (c as f32 / 255.0, format!("{}", c))
+                }
+                Value::Bool(value) => {
+                    let boxed = Rect::from_w_h(track.h(), track.h()).mid_left_of(track);
+                    draw.rect().xy(boxed.xy()).wh(boxed.wh()).color(if value {
+                        rgba(1.0, 0.8, 0.0, 1.0)
+                    } else {
+                        rgba(0.2, 0.2, 0.25, 1.0)
+                    });
+                    continue;
+                }
+            };
+            draw.rect()
+                .xy(track.xy())
+                .wh(track.wh())
+                .color(rgba(0.2, 0.2, 0.25, 1.0));
+            let filled = Rect::from_corners(
+                track.bottom_left(),
+                vec2(track.left() + track.w() * f.max(0.0).min(1.0), track.top()),
+            );
+            draw.rect()
+                .xy(filled.xy())
+                .wh(filled.wh())
+                .color(rgba(1.0, 0.8, 0.0, 1.0));
+            if let Value::Color(color) = p.value {
+                // a swatch of the whole color beside its last slider.
+                if row.channel == 2 {
+                    let swatch = Rect::from_w_h(value.h(), value.h()).mid_right_of(value);
+                    draw.rect().xy(swatch.xy()).wh(swatch.wh()).color(color);
+                }
+            }
+            text(&shown, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        let mut params = Params::new();
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        params.add_int("n", 50, 10, 110);
+        params.add_bool("smooth", true);
+        params.add_color("ink", Srgb::new(10, 20, 30));
+        assert_eq!(params.float("gravity"), 5.0);
+        assert_eq!(params.int("n"), 50);
+        assert!(params.bool("smooth"));
+        assert_eq!(params.color("ink"), Srgb::new(10, 20, 30));
+        assert!(!params.changed("n"));
+        assert_eq!(params.channels("ink"), 3);
+        assert_eq!(params.channels("n"), 1);
+        assert_eq!(
+            params.values(),
+            vec![
+                ("gravity", "5.00".to_string()),
+                ("n", "50".to_string()),
+                ("smooth", "on".to_string()),
+                ("ink", "#0a141e".to_string()),
+            ]
+        );
+
+        let win = Rect::from_w_h(800.0, 600.0);
+        let rows = params.rows(win);
+        // a row for each, and three for the color.
+        assert_eq!(rows.len(), 6);
+        assert!(rows.iter().all(|r| params.rect(win).contains(r.rect.xy())));
+
+        // clicking off the panel leaves it alone.
+        assert!(!params.press(win, vec2(0.0, 0.0)));
+        assert!(!params.dragging());
+
+        // grabbing n's slider and dragging it to the end.
+        let track = rows[1].track();
+        assert!(params.press(win, track.xy()));
+        assert!(params.dragging());
+        assert_eq!(params.int("n"), 60);
+        params.move_to(win, track.mid_right() + vec2(100.0, 0.0));
+        assert_eq!(params.int("n"), 110);
+        assert!(params.changed("n"));
+        assert!(!params.changed("n"));
+        params.release();
+        params.move_to(win, track.mid_left());
+        assert_eq!(params.int("n"), 110);
+
+        // the checkbox flips, and the color's green slider only sets green.
+        assert!(params.press(win, rows[2].track().xy()));
+        assert!(!params.bool("smooth"));
+        assert!(!params.dragging());
+        params.press(win, rows[4].track().mid_right());
+        assert_eq!(params.color("ink"), Srgb::new(10, 255, 30));
+
+        // as a knob would.
+        params.set_fraction("gravity", 0, 0.25);
+        assert_eq!(params.float("gravity"), 5.0);
+        params.set_fraction("ink", 0, 2.0);
+        assert_eq!(params.color("ink"), Srgb::new(255, 255, 30));
+        params.changed("smooth");
+        params.set_fraction("smooth", 0, 0.9);
+        assert!(params.bool("smooth"));
+        assert!(params.changed("smooth"));
+
+        // hidden, it's out of the way.
+        params.visible = false;
+        assert!(!params.press(win, rows[0].track().xy()));
+        assert_eq!(params.float("gravity"), 5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "gravity isn't an int")]
+    fn test_wrong_type() {
+        let mut params = Params::new();
+        params.add_float("gravity", 5.0, 0.0, 20.0);
+        params.int("gravity");
+    }
+}
@@ -0,0 +1,143 @@
+//! Phyllotaxis: the spirals of seeds in a sunflower head, or scales on a pine cone, laid out by
+//! turning each new one by the same divergence angle from the last, and setting it a little
+//! further out.
+//!
+//! At the golden angle, about 137.5°, no two seeds ever quite line up, so they pack evenly with no
+//! gaps, and the eye picks out spirals running each way in neighbouring Fibonacci numbers. Nudge
+//! the angle off it and the seeds bunch up into straight spokes instead.
+
+use nannou::geom::{vec2, Vector2};
+
+/// The golden angle, 360° divided in the golden ratio, in radians.
+pub const GOLDEN_ANGLE: f32 = 2.399_963_2;
+
+/// How far out the `n`th seed is set.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Scaling {
+    /// As the square root of `n`, so every seed has the same room: Vogel's model of a sunflower.
+    Sqrt,
+    /// In proportion to `n`, so the seeds spread out along an Archimedean spiral.
+    Linear,
+    /// As `n` to some power; 0.5 is the same as `Sqrt` and 1 as `Linear`.
+    Power(f32),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Phyllotaxis {
+    pub count: usize,
+    /// How far round each seed is turned from the last, in radians.
+    pub angle: f32,
+    /// How far out the seeds are, along with `scaling`; with `Sqrt`, about how far apart they are.
+    pub spacing: f32,
+    pub scaling: Scaling,
+}
+
+impl Phyllotaxis {
+    /// `count` seeds at the golden angle, packed like a sunflower's, `spacing` apart.
+    pub fn new(count: usize, spacing: f32) -> Phyllotaxis {
+        Phyllotaxis {
+            count,
+            angle: GOLDEN_ANGLE,
+            spacing,
+            scaling: Scaling::Sqrt,
+        }
+    }
+
+    /// `count` seeds packed like a sunflower's, filling a disc of `radius`.
+    pub fn disc(count: usize, radius: f32) -> Phyllotaxis {
+        assert!(count > 0, "a disc needs at least one seed");
+        Phyllotaxis::new(count, radius / ((count - 1) as f32).sqrt().max(1.0))
+    }
+
+    /// How far from the middle the `n`th seed is.
+    pub fn radius(&self, n: usize) -> f32 {
+        let n = n as f32;
+        self.spacing
+            * match self.scaling {
+                Scaling::Sqrt => n.sqrt(),
+                Scaling::Linear => n,
+                Scaling::Power(power) => n.powf(power),
+            }
+    }
+
+    /// Where the `n`th seed is, the first in the middle and the second straight out to the right.
+    pub fn point(&self, n: usize) -> Vector2 {
+        let theta = self.angle * n as f32;
+        vec2(theta.cos(), theta.sin()) * self.radius(n)
+    }
+
+    /// Where every seed is, from the middle out.
+    pub fn points(&self) -> Vec<Vector2> {
+        (0..self.count).map(|n| self.point(n)).collect()
+    }
+
+    /// How far out the furthest seed is.
+    pub fn extent(&self) -> f32 {
+        self.radius(self.count.max(1) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// How far each seed is from its nearest neighbour.
+    fn nearest(points: &[Vector2]) -> Vec<f32> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| i != *j)
+                    .map(|(_, b)| (*a - *b).magnitude())
+                    .fold(f32::MAX, f32::min)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layout() {
+        assert!((GOLDEN_ANGLE.to_degrees() - 137.508).abs() < 1e-3);
+        assert!((GOLDEN_ANGLE - PI * (3.0 - 5f32.sqrt())).abs() < 1e-6);
+
+        let mut phyllotaxis = Phyllotaxis::new(100, 2.0);
+        assert_eq!(phyllotaxis.points().len(), 100);
+        assert_eq!(phyllotaxis.point(0), vec2(0.0, 0.0));
+        assert!(
+            (phyllotaxis.point(1) - vec2(2.0 * GOLDEN_ANGLE.cos(), 2.0 * GOLDEN_ANGLE.sin()))
+                .magnitude()
+                < 1e-6
+        );
+        assert!((phyllotaxis.radius(25) - 10.0).abs() < 1e-6);
+        assert!((phyllotaxis.extent() - 2.0 * 99f32.sqrt()).abs() < 1e-5);
+        phyllotaxis.scaling = Scaling::Linear;
+        assert!((phyllotaxis.radius(25) - 50.0).abs() < 1e-5);
+        phyllotaxis.scaling = Scaling::Power(0.5);
+        assert!((phyllotaxis.radius(25) - 10.0).abs() < 1e-5);
+
+        let disc = Phyllotaxis::disc(500, 30.0);
+        assert!((disc.extent() - 30.0).abs() < 1e-4);
+        assert!(disc.points().iter().all(|p| p.magnitude() <= 30.0 + 1e-4));
+    }
+
+    #[test]
+    fn test_packing() {
+        // at the golden angle, every seed has about the same room around it.
+        let sunflower = Phyllotaxis::new(1000, 1.0);
+        let distances = nearest(&sunflower.points());
+        let (closest, furthest) = distances[1..]
+            .iter()
+            .fold((f32::MAX, 0.0f32), |(lo, hi), d| (lo.min(*d), hi.max(*d)));
+        assert!(closest > 0.8 && furthest < 2.5, "{} {}", closest, furthest);
+
+        // a few degrees off, and they crowd up along spokes.
+        let mut spokes = sunflower;
+        spokes.angle = 135f32.to_radians();
+        let distances = nearest(&spokes.points());
+        let closest = distances[1..].iter().cloned().fold(f32::MAX, f32::min);
+        assert!(closest < 0.5, "{}", closest);
+    }
+}
@@ -0,0 +1,186 @@
+//! Physarum: a slime mold, simulated as lots of little agents wandering over a trail map, each
+//! following the trail the others leave, which grows networks of veins joining up wherever they
+//! gather.
+//!
+//! Each step, every agent senses the trail a little way ahead of it and to either side, turns
+//! towards the strongest, moves forward, and deposits some trail where it lands. Then the trail
+//! diffuses and decays, so veins that agents stop following fade away. The trail map wraps around
+//! at its edges, and so do the agents.
+//!
+//! See Jeff Jones' "Characteristics of pattern formation and evolution in approximations of
+//! Physarum transport networks" for the model, and <https://cargocollective.com/sagejenson/physarum>
+//! for some lovely pictures of it.
+
+use crate::grid::FloatGrid;
+use nannou::geom::{vec2, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Agent {
+    /// Where it is, in cells from the bottom left corner of the trail map.
+    pub pos: Vector2,
+    /// Which way it's heading, in radians anticlockwise from the right.
+    pub heading: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Physarum {
+    /// How far the sensors to either side point from straight ahead, in radians.
+    pub sensor_angle: f32,
+    /// How far ahead the sensors are, in cells.
+    pub sensor_distance: f32,
+    /// How far an agent turns each step, in radians.
+    pub turn_angle: f32,
+    /// How far an agent moves each step, in cells.
+    pub speed: f32,
+    /// How much trail an agent leaves each step.
+    pub deposit: f32,
+    /// How fast the trail spreads out.
+    pub diffusion: f32,
+    /// What fraction of the trail fades each step.
+    pub decay: f32,
+    trail: FloatGrid,
+    agents: Vec<Agent>,
+    rng: XorShiftRng,
+}
+
+impl Physarum {
+    /// An empty trail map, with `agents` agents scattered over it heading every which way.
+    pub fn new(width: usize, height: usize, agents: usize, seed: u64) -> Physarum {
+        let mut rng: XorShiftRng = SeedableRng::seed_from_u64(seed);
+        let agents = (0..agents)
+            .map(|_| Agent {
+                pos: vec2(
+                    rng.gen::<f32>() * width as f32,
+                    rng.gen::<f32>() * height as f32,
+                ),
+                heading: rng.gen::<f32>() * 2.0 * PI,
+            })
+            .collect();
+        Physarum {
+            sensor_angle: PI / 4.0,
+            sensor_distance: 9.0,
+            turn_angle: PI / 4.0,
+            speed: 1.0,
+            deposit: 5.0,
+            diffusion: 0.5,
+            decay: 0.1,
+            trail: FloatGrid::new(width, height, 0.0),
+            agents,
+            rng,
+        }
+    }
+
+    pub fn trail(&self) -> &FloatGrid {
+        &self.trail
+    }
+
+    /// The trail map, to paint food onto, or wipe clean.
+    pub fn trail_mut(&mut self) -> &mut FloatGrid {
+        &mut self.trail
+    }
+
+    pub fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    /// How much trail there is `angle` radians round from `agent`'s heading, at the sensor
+    /// distance.
+    fn sense(&self, agent: &Agent, angle: f32) -> f32 {
+        let heading = agent.heading + angle;
+        let pos = agent.pos + vec2(heading.cos(), heading.sin()) * self.sensor_distance;
+        self.trail.at(pos).unwrap_or(0.0)
+    }
+
+    /// Move every agent once, then spread and fade the trail.
+    pub fn step(&mut self) {
+        let (width, height) = (self.trail.width as f32, self.trail.height as f32);
+        for i in 0..self.agents.len() {
+            let mut agent = self.agents[i];
+            let left = self.sense(&agent, self.sensor_angle);
+            let ahead = self.sense(&agent, 0.0);
+            let right = self.sense(&agent, -self.sensor_angle);
+            if ahead >= left && ahead >= right {
+                // keep going.
+            } else if ahead < left && ahead < right {
+                // either way's better than this; pick one.
+                if self.rng.gen::<bool>() {
+                    agent.heading += self.turn_angle;
+                } else {
+                    agent.heading -= self.turn_angle;
+                }
+            } else if left > right {
+                agent.heading += self.turn_angle;
+            } else {
+                agent.heading -= self.turn_angle;
+            }
+
+            let pos = agent.pos + vec2(agent.heading.cos(), agent.heading.sin()) * self.speed;
+            agent.pos = vec2(wrap(pos.x, width), wrap(pos.y, height));
+            self.trail
+                .add(agent.pos.x as usize, agent.pos.y as usize, self.deposit);
+            self.agents[i] = agent;
+        }
+        self.trail.diffuse(self.diffusion, 1.0);
+        self.trail.decay(self.decay);
+    }
+}
+
+/// `value` wrapped around into `0..max`.
+fn wrap(value: f32, max: f32) -> f32 {
+    let wrapped = value.rem_euclid(max);
+    // a tiny negative value wraps round to `max` itself, in floating point.
+    if wrapped < max {
+        wrapped
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow() {
+        let mut physarum = Physarum::new(50, 50, 1, 1);
+        physarum.diffusion = 0.0;
+        physarum.decay = 0.0;
+        physarum.agents[0] = Agent {
+            pos: vec2(25.0, 25.0),
+            heading: 0.0,
+        };
+        // a trail off to the left, which it turns to.
+        let left = vec2(25.0, 25.0) + vec2(1.0, 1.0).normalize() * 9.0;
+        physarum.trail.paint(left, 2.0, 10.0);
+        physarum.step();
+        assert_eq!(physarum.agents()[0].heading, PI / 4.0);
+        let pos = physarum.agents()[0].pos;
+        assert!((pos - (vec2(25.0, 25.0) + vec2(1.0, 1.0).normalize())).magnitude() < 1e-4);
+        assert_eq!(*physarum.trail().get(25, 25), physarum.deposit);
+
+        // straight ahead, it keeps going.
+        physarum.step();
+        assert_eq!(physarum.agents()[0].heading, PI / 4.0);
+    }
+
+    #[test]
+    fn test_network() {
+        let mut physarum = Physarum::new(64, 64, 2000, 2);
+        for _ in 0..200 {
+            physarum.step();
+        }
+        for agent in physarum.agents() {
+            assert!(agent.pos.x >= 0.0 && agent.pos.x < 64.0);
+            assert!(agent.pos.y >= 0.0 && agent.pos.y < 64.0);
+        }
+        // the agents gather into veins, leaving most of the map with much less trail than they
+        // would if they'd stayed spread out evenly.
+        let trail = physarum.trail().values();
+        let mean = trail.iter().sum::<f32>() / trail.len() as f32;
+        let sparse = trail.iter().filter(|t| **t < mean / 2.0).count();
+        assert!(sparse > trail.len() / 2, "{} of {}", sparse, trail.len());
+    }
+}
@@ -0,0 +1,692 @@
+//! A small particle and spring simulation, for the bouncing sketches.
+//!
+//! A `World` holds particles, springs joining pairs of them, and the forces acting on
+//! everything; `World::step` moves it all forward:
+//!
+//! ```ignore
+//! let mut world = World::new();
+//! let a = world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+//! let b = world.add_particle(Particle::new(vec2(0.1, 0.0), vec2(0.0, 0.0)));
+//! world.add_spring(Spring::new(a, b, 0.1, 20.0));
+//! world.step(1.0 / 60.0);
+//! ```
+//!
+//! Each `World` picks its `IntegrationMethod`. Euler is the simplest, but it's only first-order
+//! accurate, and with stiff springs or big steps its errors pile up until things fly apart;
+//! `damping` can bleed the extra energy back off. Verlet is second-order, and keeps energy
+//! steady without needing any damping, for about twice the work per step. RK4 is fourth-order,
+//! for sketches where the path drawn should be smooth and true, for about four times the work.
+//!
+//! With `collisions` on, particles also bounce off each other as circles after every step,
+//! checking every pair, so it's only for up to a few hundred particles.
+//!
+//! With `attraction` set, every particle pulls on every other by gravity, through a Barnes-Hut
+//! `QuadTree`, so it keeps up with thousands of them.
+//!
+//! Particles bounce off the inside of `bounds`, which is usually a `Rect`, but can be any
+//! `Boundary`: a circle, a star, or whatever else has a signed distance.
+
+use self::boundary::Boundary;
+use self::quadtree::QuadTree;
+use nannou::geom::{vec2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+pub mod boundary;
+pub mod cloth;
+pub mod drag;
+pub mod pendulum;
+pub mod quadtree;
+pub mod rigid;
+pub mod spatial_hash;
+
+/// How `World::step` moves particles forward in time.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IntegrationMethod {
+    /// Semi-implicit Euler: update velocity from the forces, then position from the velocity.
+    Euler,
+    /// Velocity Verlet: update position from the velocity and forces, then velocity from the
+    /// average of the forces before and after.
+    Verlet,
+    /// Classic fourth-order Runge-Kutta: a weighted average of four trial steps.
+    Rk4,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Particle {
+    pub pos: Vector2,
+    pub vel: Vector2,
+    pub mass: f32,
+    /// How far from the walls the particle's center bounces.
+    pub radius: f32,
+    /// Pinned particles stay put, whatever pulls on them.
+    pub pinned: bool,
+}
+
+impl Particle {
+    /// A free particle with mass 1 and no radius.
+    pub fn new(pos: Vector2, vel: Vector2) -> Particle {
+        Particle {
+            pos,
+            vel,
+            mass: 1.0,
+            radius: 0.0,
+            pinned: false,
+        }
+    }
+    pub fn pinned(pos: Vector2) -> Particle {
+        Particle {
+            pinned: true,
+            ..Particle::new(pos, vec2(0.0, 0.0))
+        }
+    }
+}
+
+/// A spring between particles `a` and `b`, following Hooke's law.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    /// If set, the spring snaps, and is removed, once it's stretched by more than this fraction
+    /// of its rest length.
+    pub break_strain: Option<f32>,
+}
+
+impl Spring {
+    pub fn new(a: usize, b: usize, rest_length: f32, stiffness: f32) -> Spring {
+        assert_ne!(a, b, "a spring needs two different particles");
+        Spring {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            break_strain: None,
+        }
+    }
+
+    /// How stretched the spring is, as a fraction of its rest length: 0 at rest, negative when
+    /// squeezed.
+    pub fn strain(&self, particles: &[Particle]) -> f32 {
+        let length = (particles[self.b].pos - particles[self.a].pos).magnitude();
+        (length - self.rest_length) / self.rest_length
+    }
+}
+
+/// Two particles which bounced off each other.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Collision {
+    pub a: usize,
+    pub b: usize,
+    /// How fast they were closing on each other.
+    pub speed: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct World {
+    pub particles: Vec<Particle>,
+    pub springs: Vec<Spring>,
+    /// Acceleration applied to every particle.
+    pub gravity: Vector2,
+    /// The gravitational constant pulling every pair of particles together, by their masses and
+    /// the inverse square of the distance between them; 0 for none.
+    pub attraction: f32,
+    /// Attraction acts as if particles were always at least about this far apart, so it doesn't
+    /// fling them off to infinity when they pass through each other.
+    pub softening: f32,
+    /// How far the attraction is approximated, as the `theta` of a Barnes-Hut `QuadTree`; 0 for
+    /// exact.
+    pub attraction_theta: f32,
+    /// Velocities are multiplied by this every step; 1 for none.
+    pub damping: f32,
+    /// Walls the particles bounce off the inside of, if any: a `Rect`, or any other
+    /// `Boundary`.
+    pub bounds: Option<Rc<dyn Boundary>>,
+    pub integrator: IntegrationMethod,
+    /// Whether particles bounce off each other, as circles of their `radius`.
+    pub collisions: bool,
+    /// The particles which bounced off each other in the last step, for sketches to make
+    /// something of, like a sound.
+    pub collided: Vec<Collision>,
+    /// How much of their closing speed colliding particles keep: 1 for perfectly elastic, 0 to
+    /// stop dead against each other.
+    pub restitution: f32,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World {
+            particles: vec![],
+            springs: vec![],
+            gravity: vec2(0.0, -1.0),
+            attraction: 0.0,
+            softening: 0.0,
+            attraction_theta: 0.5,
+            damping: 1.0,
+            bounds: None,
+            integrator: IntegrationMethod::Euler,
+            collisions: false,
+            collided: vec![],
+            restitution: 1.0,
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    /// Add a particle, returning its index in `particles`.
+    pub fn add_particle(&mut self, particle: Particle) -> usize {
+        self.particles.push(particle);
+        self.particles.len() - 1
+    }
+    pub fn add_spring(&mut self, spring: Spring) {
+        assert!(spring.a < self.particles.len() && spring.b < self.particles.len());
+        self.springs.push(spring);
+    }
+
+    /// The force on each particle: gravity, attraction, and the pull of the springs.
+    fn forces(&self) -> Vec<Vector2> {
+        let positions = self.particles.iter().map(|p| p.pos).collect::<Vec<_>>();
+        self.forces_at(&positions)
+    }
+    /// The force on each particle, if the particles were at `positions`.
+    fn forces_at(&self, positions: &[Vector2]) -> Vec<Vector2> {
+        let mut forces = self
+            .particles
+            .iter()
+            .map(|p| self.gravity * p.mass)
+            .collect::<Vec<_>>();
+        if self.attraction != 0.0 {
+            let bodies = positions
+                .iter()
+                .zip(&self.particles)
+                .map(|(pos, p)| (*pos, p.mass))
+                .collect::<Vec<_>>();
+            let tree = QuadTree::new(&bodies);
+            let softening = self.softening * self.softening;
+            for ((force, pos), p) in forces.iter_mut().zip(positions).zip(&self.particles) {
+                *force += tree.force_on(*pos, self.attraction_theta, |d, mass| {
+                    d * (self.attraction * p.mass * mass / (d.magnitude2() + softening).powf(1.5))
+                });
+            }
+        }
+        for spring in &self.springs {
+            let d = positions[spring.b] - positions[spring.a];
+            let length = d.magnitude();
+            if length == 0.0 {
+                // no direction to pull in.
+                continue;
+            }
+            let force = d / length * (length - spring.rest_length) * spring.stiffness;
+            forces[spring.a] += force;
+            forces[spring.b] -= force;
+        }
+        forces
+    }
+
+    /// Move everything forward `dt` seconds, then snap any springs stretched past their
+    /// `break_strain`.
+    pub fn step(&mut self, dt: f32) {
+        match self.integrator {
+            IntegrationMethod::Euler => self.step_euler(dt),
+            IntegrationMethod::Verlet => self.step_verlet(dt),
+            IntegrationMethod::Rk4 => self.step_rk4(dt),
+        }
+        if self.collisions {
+            self.collide();
+        }
+        let particles = &self.particles;
+        self.springs.retain(|spring| match spring.break_strain {
+            Some(limit) => spring.strain(particles) <= limit,
+            None => true,
+        });
+    }
+
+    /// Separate every pair of overlapping particles, and if they're moving towards each other,
+    /// bounce them apart with an impulse along the line between their centers. Heavier particles
+    /// are moved and turned less; pinned ones not at all.
+    fn collide(&mut self) {
+        self.collided.clear();
+        let inverse_mass = |p: &Particle| if p.pinned { 0.0 } else { 1.0 / p.mass };
+        for i in 0..self.particles.len() {
+            for j in i + 1..self.particles.len() {
+                let (a, b) = (self.particles[i], self.particles[j]);
+                let (wa, wb) = (inverse_mass(&a), inverse_mass(&b));
+                let d = b.pos - a.pos;
+                let distance = d.magnitude();
+                let overlap = a.radius + b.radius - distance;
+                if overlap <= 0.0 || distance == 0.0 || wa + wb == 0.0 {
+                    // apart, no direction to push in, or nothing can move.
+                    continue;
+                }
+                let normal = d / distance;
+                let push = normal * (overlap / (wa + wb));
+                self.particles[i].pos -= push * wa;
+                self.particles[j].pos += push * wb;
+
+                let closing = (b.vel - a.vel).dot(normal);
+                if closing < 0.0 {
+                    let impulse = normal * (-(1.0 + self.restitution) * closing / (wa + wb));
+                    self.particles[i].vel -= impulse * wa;
+                    self.particles[j].vel += impulse * wb;
+                    self.collided.push(Collision {
+                        a: i,
+                        b: j,
+                        speed: -closing,
+                    });
+                }
+            }
+        }
+    }
+
+    fn step_euler(&mut self, dt: f32) {
+        let forces = self.forces();
+        for (particle, force) in self.particles.iter_mut().zip(forces) {
+            if particle.pinned {
+                continue;
+            }
+            particle.vel += force / particle.mass * dt;
+            particle.pos += particle.vel * dt;
+            if let Some(bounds) = &self.bounds {
+                bounds.bounce(&mut particle.pos, &mut particle.vel, particle.radius);
+            }
+            particle.vel *= self.damping;
+        }
+    }
+
+    fn step_verlet(&mut self, dt: f32) {
+        let before = self.forces();
+        for (particle, force) in self.particles.iter_mut().zip(&before) {
+            if particle.pinned {
+                continue;
+            }
+            particle.pos += particle.vel * dt + *force / particle.mass * (0.5 * dt * dt);
+            if let Some(bounds) = &self.bounds {
+                bounds.bounce(&mut particle.pos, &mut particle.vel, particle.radius);
+            }
+        }
+        let after = self.forces();
+        for ((particle, before), after) in self.particles.iter_mut().zip(before).zip(after) {
+            if particle.pinned {
+                continue;
+            }
+            particle.vel += (before + after) / particle.mass * (0.5 * dt);
+            particle.vel *= self.damping;
+        }
+    }
+
+    fn step_rk4(&mut self, dt: f32) {
+        let pos = self.particles.iter().map(|p| p.pos).collect::<Vec<_>>();
+        let vel = self.particles.iter().map(|p| p.vel).collect::<Vec<_>>();
+        // the derivative of (position, velocity) at a trial state `h` seconds along `d`. Pinned
+        // particles don't move, even in the trial states.
+        let derivative = |d: &(Vec<Vector2>, Vec<Vector2>), h: f32| {
+            let trial_pos = pos
+                .iter()
+                .zip(&d.0)
+                .map(|(x, v)| *x + *v * h)
+                .collect::<Vec<_>>();
+            let trial_vel = vel
+                .iter()
+                .zip(&d.1)
+                .zip(&self.particles)
+                .map(|((v, a), p)| {
+                    if p.pinned {
+                        vec2(0.0, 0.0)
+                    } else {
+                        *v + *a * h
+                    }
+                })
+                .collect::<Vec<_>>();
+            let accel = self
+                .forces_at(&trial_pos)
+                .into_iter()
+                .zip(&self.particles)
+                .map(|(f, p)| if p.pinned { vec2(0.0, 0.0) } else { f / p.mass })
+                .collect::<Vec<_>>();
+            (trial_vel, accel)
+        };
+        let zero = vec![vec2(0.0, 0.0); pos.len()];
+        let k1 = derivative(&(zero.clone(), zero), 0.0);
+        let k2 = derivative(&k1, dt / 2.0);
+        let k3 = derivative(&k2, dt / 2.0);
+        let k4 = derivative(&k3, dt);
+
+        let damping = self.damping;
+        for (i, particle) in self.particles.iter_mut().enumerate() {
+            if particle.pinned {
+                continue;
+            }
+            particle.pos += (k1.0[i] + (k2.0[i] + k3.0[i]) * 2.0 + k4.0[i]) * (dt / 6.0);
+            particle.vel += (k1.1[i] + (k2.1[i] + k3.1[i]) * 2.0 + k4.1[i]) * (dt / 6.0);
+            if let Some(bounds) = &self.bounds {
+                bounds.bounce(&mut particle.pos, &mut particle.vel, particle.radius);
+            }
+            particle.vel *= damping;
+        }
+    }
+}
+
+/// Steps a simulation at a fixed rate, however fast frames come, so it behaves the same at any
+/// frame rate:
+///
+/// ```ignore
+/// for _ in 0..model.timestep.advance(update.since_last.as_secs_f32()) {
+///     model.world.step(model.timestep.dt);
+/// }
+/// ```
+///
+/// Frames usually land between steps; `interpolate` draws things that far between where they
+/// were before the last step and where they are now.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FixedTimestep {
+    pub dt: f32,
+    /// The most steps `advance` takes at once; after a long stall (like the window being dragged)
+    /// the rest of the time is dropped, rather than the sketch grinding to catch up.
+    pub max_steps: usize,
+    // time passed but not stepped yet.
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(dt: f32) -> FixedTimestep {
+        assert!(dt > 0.0, "timestep must be positive");
+        FixedTimestep {
+            dt,
+            max_steps: 10,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Let `elapsed` seconds pass, returning how many steps to take for them.
+    pub fn advance(&mut self, elapsed: f32) -> usize {
+        self.accumulator += elapsed;
+        // a little slack, so a frame of exactly n steps doesn't come out at n - 1 from rounding.
+        let steps = (self.accumulator / self.dt + 1e-3) as usize;
+        if steps > self.max_steps {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+        self.accumulator = (self.accumulator - steps as f32 * self.dt).max(0.0);
+        steps
+    }
+
+    /// How far the time not stepped yet is through the next step, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).min(1.0)
+    }
+
+    /// `alpha` of the way from `before` the last step to `after` it.
+    pub fn interpolate(&self, before: Vector2, after: Vector2) -> Vector2 {
+        before + (after - before) * self.alpha()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::Rect;
+
+    #[test]
+    fn test_bounce() {
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.bounds = Some(Rc::new(Rect::from_x_y_w_h(0.0, 0.0, 1.0, 1.0)));
+        let mut ball = Particle::new(vec2(0.0, 0.0), vec2(1.0, 0.0));
+        ball.radius = 0.1;
+        let ball = world.add_particle(ball);
+
+        // 0.4 until it touches the right wall, then 0.6 back.
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+        let ball = world.particles[ball];
+        assert!((ball.pos.x - -0.2).abs() < 1e-4, "{:?}", ball.pos);
+        assert_eq!(ball.vel, vec2(-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_spring() {
+        // a weight hanging from a pinned particle settles where the spring balances gravity.
+        let mut world = World::new();
+        world.damping = 0.98;
+        let top = world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+        let weight = world.add_particle(Particle::new(vec2(0.0, -0.1), vec2(0.0, 0.0)));
+        world.add_spring(Spring::new(top, weight, 0.1, 10.0));
+        for _ in 0..2000 {
+            world.step(1.0 / 60.0);
+        }
+        assert_eq!(world.particles[top].pos, vec2(0.0, 0.0));
+        // stretched by mg / k.
+        let y = world.particles[weight].pos.y;
+        assert!((y - -0.2).abs() < 1e-3, "{}", y);
+    }
+
+    #[test]
+    fn test_spring_mass() {
+        // a stretched spring pulls a heavy and a light ball together, moving the light one
+        // further, so their center of mass stays put.
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.integrator = IntegrationMethod::Verlet;
+        let mut heavy = Particle::new(vec2(-0.5, 0.0), vec2(0.0, 0.0));
+        heavy.mass = 3.0;
+        let heavy = world.add_particle(heavy);
+        let light = world.add_particle(Particle::new(vec2(0.5, 0.0), vec2(0.0, 0.0)));
+        world.add_spring(Spring::new(heavy, light, 0.5, 10.0));
+        for _ in 0..20 {
+            world.step(1.0 / 60.0);
+        }
+        let (heavy, light) = (world.particles[heavy], world.particles[light]);
+        let moved = (heavy.pos.x - -0.5, 0.5 - light.pos.x);
+        assert!(moved.0 > 0.01, "{:?}", moved);
+        assert!((moved.1 / moved.0 - 3.0).abs() < 1e-2, "{:?}", moved);
+        let momentum = heavy.vel * heavy.mass + light.vel * light.mass;
+        assert!(momentum.magnitude() < 1e-4, "{:?}", momentum);
+    }
+
+    #[test]
+    fn test_break() {
+        // a weight too heavy for its spring pulls it past breaking, and falls.
+        let mut world = World::new();
+        world.damping = 0.98;
+        let top = world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+        let light = world.add_particle(Particle::new(vec2(-0.1, -0.1), vec2(0.0, 0.0)));
+        let mut heavy = Particle::new(vec2(0.1, -0.1), vec2(0.0, 0.0));
+        heavy.mass = 3.0;
+        let heavy = world.add_particle(heavy);
+        for weight in &[light, heavy] {
+            let mut spring = Spring::new(top, *weight, 0.1, 10.0);
+            spring.break_strain = Some(2.0);
+            world.add_spring(spring);
+        }
+        // the light weight settles at a strain of mg / k / rest = 1.
+        for _ in 0..2000 {
+            world.step(1.0 / 60.0);
+        }
+        assert_eq!(world.springs.len(), 1);
+        assert_eq!(world.springs[0].b, light);
+        let strain = world.springs[0].strain(&world.particles);
+        assert!((strain - 1.0).abs() < 1e-2, "{}", strain);
+        assert!(world.particles[heavy].pos.y < -10.0);
+    }
+
+    #[test]
+    fn test_orbit() {
+        // a light moon circles a heavy pinned planet, at the speed that balances their attraction.
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.attraction = 2.0;
+        world.integrator = IntegrationMethod::Verlet;
+        let mut planet = Particle::pinned(vec2(0.0, 0.0));
+        planet.mass = 50.0;
+        world.add_particle(planet);
+        // G M / r = v^2.
+        let (r, speed) = (1.0, 10.0);
+        let moon = world.add_particle(Particle::new(vec2(r, 0.0), vec2(0.0, speed)));
+        let period = 2.0 * std::f32::consts::PI * r / speed;
+        for _ in 0..1000 {
+            world.step(period / 1000.0);
+            let radius = world.particles[moon].pos.magnitude();
+            assert!((radius - r).abs() < 1e-3, "{}", radius);
+        }
+        let pos = world.particles[moon].pos;
+        assert!((pos - vec2(r, 0.0)).magnitude() < 1e-2, "{:?}", pos);
+
+        // two free bodies fall together, without the pair going anywhere.
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.attraction = 1.0;
+        world.softening = 0.1;
+        let mut heavy = Particle::new(vec2(-1.0, 0.0), vec2(0.0, 0.0));
+        heavy.mass = 4.0;
+        world.add_particle(heavy);
+        world.add_particle(Particle::new(vec2(1.0, 0.0), vec2(0.0, 0.0)));
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+        let (heavy, light) = (world.particles[0], world.particles[1]);
+        assert!(heavy.vel.x > 0.0 && light.vel.x < 0.0);
+        let momentum = heavy.vel * heavy.mass + light.vel * light.mass;
+        assert!(momentum.magnitude() < 1e-5, "{:?}", momentum);
+    }
+
+    /// Two balls about to meet head on, at 1 each.
+    fn head_on_world(mass: f32, restitution: f32) -> World {
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.collisions = true;
+        world.restitution = restitution;
+        let mut a = Particle::new(vec2(-0.5, 0.0), vec2(1.0, 0.0));
+        a.radius = 0.1;
+        a.mass = mass;
+        let mut b = Particle::new(vec2(0.5, 0.0), vec2(-1.0, 0.0));
+        b.radius = 0.1;
+        world.add_particle(a);
+        world.add_particle(b);
+        world
+    }
+
+    /// Two balls meeting head on, returning their velocities after.
+    fn head_on(mass: f32, restitution: f32) -> (Vector2, Vector2) {
+        let mut world = head_on_world(mass, restitution);
+        let (a, b) = (0, 1);
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+            let (a, b) = (world.particles[a], world.particles[b]);
+            assert!(b.pos.x - a.pos.x >= 0.2 - 1e-5, "{:?} {:?}", a.pos, b.pos);
+        }
+        (world.particles[a].vel, world.particles[b].vel)
+    }
+
+    #[test]
+    fn test_collide() {
+        // equal masses swap velocities.
+        let (a, b) = head_on(1.0, 1.0);
+        assert!((a - vec2(-1.0, 0.0)).magnitude() < 1e-5, "{:?}", a);
+        assert!((b - vec2(1.0, 0.0)).magnitude() < 1e-5, "{:?}", b);
+
+        // three times the mass: momentum and energy are kept by the heavy ball stopping, and the
+        // light one going back twice as fast.
+        let (a, b) = head_on(3.0, 1.0);
+        assert!(a.magnitude() < 1e-5, "{:?}", a);
+        assert!((b - vec2(2.0, 0.0)).magnitude() < 1e-5, "{:?}", b);
+
+        // perfectly inelastic balls stick together.
+        let (a, b) = head_on(1.0, 0.0);
+        assert!(
+            a.magnitude() < 1e-5 && b.magnitude() < 1e-5,
+            "{:?} {:?}",
+            a,
+            b
+        );
+
+        // a pinned ball is a wall.
+        let mut world = World::new();
+        world.collisions = true;
+        world.add_particle(Particle {
+            radius: 0.1,
+            ..Particle::pinned(vec2(0.0, 0.0))
+        });
+        let mut ball = Particle::new(vec2(0.0, 0.5), vec2(0.0, 0.0));
+        ball.radius = 0.1;
+        let ball = world.add_particle(ball);
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+        }
+        assert_eq!(world.particles[0].pos, vec2(0.0, 0.0));
+        assert!(world.particles[ball].pos.y >= 0.2 - 1e-5);
+    }
+
+    #[test]
+    fn test_collided() {
+        let mut world = head_on_world(1.0, 1.0);
+        let mut collided = vec![];
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+            collided.extend(world.collided.iter().cloned());
+        }
+        // just the once, as they bounce apart.
+        assert_eq!(collided.len(), 1);
+        assert_eq!((collided[0].a, collided[0].b), (0, 1));
+        assert!((collided[0].speed - 2.0).abs() < 1e-5, "{:?}", collided);
+    }
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut timestep = FixedTimestep::new(0.1);
+        assert_eq!(timestep.advance(0.25), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(timestep.advance(0.05), 1);
+        assert!(timestep.alpha() < 1e-4);
+        assert_eq!(
+            timestep.interpolate(vec2(0.0, 0.0), vec2(1.0, 2.0)),
+            vec2(0.0, 0.0)
+        );
+        // frames too quick for a step add up.
+        assert_eq!(timestep.advance(0.06), 0);
+        assert_eq!(timestep.advance(0.06), 1);
+
+        // after a stall, only catch up so far.
+        assert_eq!(timestep.advance(5.0), timestep.max_steps);
+        assert_eq!(timestep.advance(0.0), 0);
+    }
+
+    /// How far a stiff undamped spring ends up from where it should be, a period and a quarter
+    /// after it's let go, when it's moving fastest (so errors in its phase show up most).
+    fn oscillator_error(integrator: IntegrationMethod) -> f32 {
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.integrator = integrator;
+        let anchor = world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+        let weight = world.add_particle(Particle::new(vec2(1.0, 0.0), vec2(0.0, 0.0)));
+        let stiffness = 400.0;
+        world.add_spring(Spring::new(anchor, weight, 0.0, stiffness));
+
+        let period = 2.0 * std::f32::consts::PI / stiffness.sqrt();
+        for _ in 0..125 {
+            world.step(period / 100.0);
+        }
+        world.particles[weight].pos.magnitude()
+    }
+
+    #[test]
+    fn test_verlet() {
+        let euler = oscillator_error(IntegrationMethod::Euler);
+        let verlet = oscillator_error(IntegrationMethod::Verlet);
+        assert!(verlet < 1e-2, "{}", verlet);
+        assert!(verlet * 10.0 < euler, "verlet {}, euler {}", verlet, euler);
+    }
+
+    #[test]
+    fn test_rk4() {
+        let verlet = oscillator_error(IntegrationMethod::Verlet);
+        let rk4 = oscillator_error(IntegrationMethod::Rk4);
+        assert!(rk4 < 1e-5, "{}", rk4);
+        assert!(rk4 * 10.0 < verlet, "rk4 {}, verlet {}", rk4, verlet);
+    }
+}
@@ -0,0 +1,249 @@
+//! Shapes for particles to bounce around inside.
+//!
+//! A `Boundary` is anything with a signed distance: how far a point is outside it, negative
+//! inside. That's enough to tell when a ball has gone through it and which way to push it back.
+//! `Rect` is the usual box. A `Polygon` is any outline made of straight edges, like a star, or
+//! letters flattened into rings of points; `Sdf` takes the distance as a function, for shapes
+//! like circles that are easier to write that way.
+
+use nannou::geom::{vec2, Rect, Vector2};
+use std::f32::consts::PI;
+use std::fmt;
+
+pub trait Boundary: fmt::Debug {
+    /// How far `pos` is outside the boundary, or minus how far inside.
+    fn distance(&self, pos: Vector2) -> f32;
+
+    /// Which way is out, near `pos`; by default, the way `distance` grows fastest.
+    fn normal(&self, pos: Vector2) -> Vector2 {
+        gradient(|p| self.distance(p), pos)
+    }
+
+    /// Reflect a ball of `radius` at `pos` back inside if it's gone through the boundary,
+    /// reversing its velocity out.
+    fn bounce(&self, pos: &mut Vector2, vel: &mut Vector2, radius: f32) {
+        let through = self.distance(*pos) + radius;
+        if through <= 0.0 {
+            return;
+        }
+        let normal = self.normal(*pos);
+        let mirrored = *pos - normal * (through * 2.0);
+        // in a tight corner the mirror image can land outside again; then just stop at the edge.
+        *pos = if self.distance(mirrored) + radius <= 0.0 {
+            mirrored
+        } else {
+            *pos - normal * through
+        };
+        let out = vel.dot(normal);
+        if out > 0.0 {
+            *vel -= normal * (out * 2.0);
+        }
+    }
+}
+
+/// The direction `distance` grows fastest at `pos`, by finite differences.
+fn gradient(distance: impl Fn(Vector2) -> f32, pos: Vector2) -> Vector2 {
+    // small, but not so small it's lost in the rounding of big coordinates.
+    let h = 1e-4 * pos.magnitude().max(1.0);
+    let slope = vec2(
+        distance(pos + vec2(h, 0.0)) - distance(pos - vec2(h, 0.0)),
+        distance(pos + vec2(0.0, h)) - distance(pos - vec2(0.0, h)),
+    );
+    if slope.magnitude2() == 0.0 {
+        return vec2(0.0, 0.0);
+    }
+    slope.normalize()
+}
+
+impl Boundary for Rect {
+    fn distance(&self, pos: Vector2) -> f32 {
+        let d = vec2(
+            (pos.x - self.x()).abs() - self.w() / 2.0,
+            (pos.y - self.y()).abs() - self.h() / 2.0,
+        );
+        let outside = vec2(d.x.max(0.0), d.y.max(0.0)).magnitude();
+        outside + d.x.max(d.y).min(0.0)
+    }
+
+    /// Each pair of walls separately, so a ball in a corner bounces off both.
+    fn bounce(&self, pos: &mut Vector2, vel: &mut Vector2, r: f32) {
+        if pos.x - r < self.left() {
+            pos.x += (self.left() - (pos.x - r)) * 2.0;
+            vel.x *= -1.0;
+        } else if pos.x + r > self.right() {
+            pos.x -= ((pos.x + r) - self.right()) * 2.0;
+            vel.x *= -1.0;
+        }
+        if pos.y - r < self.bottom() {
+            pos.y += (self.bottom() - (pos.y - r)) * 2.0;
+            vel.y *= -1.0;
+        } else if pos.y + r > self.top() {
+            pos.y -= ((pos.y + r) - self.top()) * 2.0;
+            vel.y *= -1.0;
+        }
+    }
+}
+
+/// A shape outlined by closed rings of points. A point is inside if it's inside an odd number
+/// of rings, so a ring inside another one is a hole, like the middle of an "o".
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polygon {
+    pub rings: Vec<Vec<Vector2>>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Vector2>) -> Polygon {
+        assert!(points.len() >= 3, "a polygon needs at least 3 corners");
+        Polygon {
+            rings: vec![points],
+        }
+    }
+
+    /// A star with `points` points, reaching out to `outer` and in to `inner` from `center`,
+    /// with a point straight up.
+    pub fn star(center: Vector2, points: usize, outer: f32, inner: f32) -> Polygon {
+        let corners = (0..points * 2)
+            .map(|i| {
+                let angle = PI / 2.0 + i as f32 * PI / points as f32;
+                let r = if i % 2 == 0 { outer } else { inner };
+                center + vec2(angle.cos(), angle.sin()) * r
+            })
+            .collect();
+        Polygon::new(corners)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vector2, Vector2)> + '_ {
+        self.rings.iter().flat_map(|ring| {
+            ring.iter()
+                .cloned()
+                .zip(ring.iter().cloned().cycle().skip(1))
+        })
+    }
+
+    pub fn contains(&self, pos: Vector2) -> bool {
+        // count the edges crossed heading right from `pos`.
+        self.edges()
+            .filter(|(a, b)| {
+                (a.y > pos.y) != (b.y > pos.y)
+                    && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    /// The closest point to `pos` on any edge.
+    fn nearest(&self, pos: Vector2) -> Vector2 {
+        self.edges()
+            .map(|(a, b)| {
+                let along = b - a;
+                let t = ((pos - a).dot(along) / along.magnitude2())
+                    .max(0.0)
+                    .min(1.0);
+                a + along * t
+            })
+            .fold((vec2(0.0, 0.0), f32::MAX), |(best, d), p| {
+                let dp = (p - pos).magnitude2();
+                if dp < d {
+                    (p, dp)
+                } else {
+                    (best, d)
+                }
+            })
+            .0
+    }
+}
+
+impl Boundary for Polygon {
+    fn distance(&self, pos: Vector2) -> f32 {
+        let d = (self.nearest(pos) - pos).magnitude();
+        if self.contains(pos) {
+            -d
+        } else {
+            d
+        }
+    }
+
+    fn normal(&self, pos: Vector2) -> Vector2 {
+        let away = pos - self.nearest(pos);
+        if away.magnitude2() == 0.0 {
+            return gradient(|p| self.distance(p), pos);
+        }
+        if self.contains(pos) {
+            -away.normalize()
+        } else {
+            away.normalize()
+        }
+    }
+}
+
+/// A boundary given by its signed distance function.
+#[derive(Clone)]
+pub struct Sdf<F: Fn(Vector2) -> f32>(pub F);
+
+impl<F: Fn(Vector2) -> f32> fmt::Debug for Sdf<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sdf(..)")
+    }
+}
+
+impl<F: Fn(Vector2) -> f32> Boundary for Sdf<F> {
+    fn distance(&self, pos: Vector2) -> f32 {
+        (self.0)(pos)
+    }
+}
+
+/// A circle of `radius` around `center`.
+pub fn circle(center: Vector2, radius: f32) -> Sdf<impl Fn(Vector2) -> f32 + Clone> {
+    Sdf(move |pos: Vector2| (pos - center).magnitude() - radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let rect = Rect::from_x_y_w_h(0.0, 0.0, 2.0, 2.0);
+        assert_eq!(rect.distance(vec2(0.5, 0.0)), -0.5);
+        assert_eq!(rect.distance(vec2(4.0, 5.0)), 5.0);
+
+        // a square as a polygon agrees, holes and all.
+        let square = |h: f32| vec![vec2(-h, -h), vec2(h, -h), vec2(h, h), vec2(-h, h)];
+        let mut polygon = Polygon::new(square(1.0));
+        assert_eq!(polygon.distance(vec2(0.5, 0.0)), -0.5);
+        assert_eq!(polygon.distance(vec2(4.0, 5.0)), 5.0);
+        polygon.rings.push(square(0.25));
+        assert_eq!(polygon.distance(vec2(0.0, 0.0)), 0.25);
+        assert_eq!(polygon.normal(vec2(0.1, 0.0)), vec2(-1.0, 0.0));
+
+        let star = Polygon::star(vec2(0.0, 0.0), 5, 1.0, 0.4);
+        assert!(star.contains(vec2(0.0, 0.9)));
+        assert!(!star.contains(vec2(0.0, -0.9)));
+
+        let circle = circle(vec2(1.0, 0.0), 1.0);
+        assert!((circle.distance(vec2(1.0, 0.5)) - -0.5).abs() < 1e-6);
+        let normal = circle.normal(vec2(1.6, 0.8));
+        assert!((normal - vec2(0.6, 0.8)).magnitude() < 1e-3, "{:?}", normal);
+    }
+
+    #[test]
+    fn test_bounce() {
+        // a ball rolling into the side of a circle comes back the way it came, still inside.
+        let circle = circle(vec2(0.0, 0.0), 1.0);
+        let (mut pos, mut vel) = (vec2(0.85, 0.0), vec2(1.0, 0.0));
+        circle.bounce(&mut pos, &mut vel, 0.2);
+        assert!((pos - vec2(0.75, 0.0)).magnitude() < 1e-3, "{:?}", pos);
+        assert!((vel - vec2(-1.0, 0.0)).magnitude() < 1e-3, "{:?}", vel);
+
+        // anywhere a ball starts in a star, it stays in.
+        let star = Polygon::star(vec2(0.0, 0.0), 5, 1.0, 0.4);
+        let (mut pos, mut vel) = (vec2(0.0, 0.0), vec2(0.37, 0.91));
+        for _ in 0..10_000 {
+            pos += vel * 0.01;
+            star.bounce(&mut pos, &mut vel, 0.02);
+            assert!(star.distance(pos) < 0.0, "{:?}", pos);
+            assert!((vel.magnitude() - 0.98).abs() < 1e-2, "{:?}", vel);
+        }
+    }
+}
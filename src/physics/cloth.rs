@@ -0,0 +1,218 @@
+//! Cloth: a grid of particles held together by springs, which can tear.
+//!
+//! Each particle is joined to its neighbours across and down by structural springs, which hold
+//! the cloth together; to its diagonal neighbours by shear springs, which stop each square
+//! collapsing sideways; and to the particles two along by bend springs, which stop it folding up
+//! too sharply. A spring stretched past `tear_stretch` times its rest length snaps.
+
+use super::{IntegrationMethod, Particle, Spring, World};
+use nannou::geom::{vec2, Vector2};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SpringKind {
+    Structural,
+    Shear,
+    Bend,
+}
+
+#[derive(Clone, Debug)]
+pub struct Cloth {
+    pub world: World,
+    pub width: usize,
+    pub height: usize,
+    /// What each of `world.springs` is; kept in step with them as they tear.
+    pub kinds: Vec<SpringKind>,
+    /// How far springs stretch, as a multiple of their rest length, before snapping; `None` for
+    /// cloth that never tears.
+    pub tear_stretch: Option<f32>,
+}
+
+impl Cloth {
+    /// A `width` by `height` grid of particles `spacing` apart, hanging from its top row, which is
+    /// pinned. The top left particle is at the origin, and rows go down.
+    pub fn grid(width: usize, height: usize, spacing: f32) -> Cloth {
+        assert!(
+            width >= 2 && height >= 2,
+            "cloth needs at least 2x2 particles"
+        );
+        let mut world = World::new();
+        world.integrator = IntegrationMethod::Verlet;
+        for y in 0..height {
+            for x in 0..width {
+                let pos = vec2(x as f32 * spacing, -(y as f32) * spacing);
+                world.add_particle(if y == 0 {
+                    Particle::pinned(pos)
+                } else {
+                    Particle::new(pos, vec2(0.0, 0.0))
+                });
+            }
+        }
+        let mut cloth = Cloth {
+            world,
+            width,
+            height,
+            kinds: vec![],
+            tear_stretch: None,
+        };
+
+        let diagonal = spacing * 2.0f32.sqrt();
+        for y in 0..height {
+            for x in 0..width {
+                let mut join = |(dx, dy): (usize, usize), kind, length| {
+                    if x + dx < width && y + dy < height {
+                        cloth.join((x, y), (x + dx, y + dy), kind, length);
+                    }
+                };
+                join((1, 0), SpringKind::Structural, spacing);
+                join((0, 1), SpringKind::Structural, spacing);
+                join((1, 1), SpringKind::Shear, diagonal);
+                join((2, 0), SpringKind::Bend, spacing * 2.0);
+                join((0, 2), SpringKind::Bend, spacing * 2.0);
+                // the other diagonal, from the particle to the right.
+                if x + 1 < width && y + 1 < height {
+                    cloth.join((x + 1, y), (x, y + 1), SpringKind::Shear, diagonal);
+                }
+            }
+        }
+        cloth.set_stiffness(SpringKind::Structural, 8000.0);
+        cloth.set_stiffness(SpringKind::Shear, 4000.0);
+        cloth.set_stiffness(SpringKind::Bend, 1000.0);
+        cloth
+    }
+
+    fn join(&mut self, a: (usize, usize), b: (usize, usize), kind: SpringKind, length: f32) {
+        let (a, b) = (self.particle(a.0, a.1), self.particle(b.0, b.1));
+        self.world.add_spring(Spring::new(a, b, length, 0.0));
+        self.kinds.push(kind);
+    }
+
+    /// The index in `world.particles` of the particle `x` across and `y` down.
+    pub fn particle(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height);
+        y * self.width + x
+    }
+
+    pub fn set_stiffness(&mut self, kind: SpringKind, stiffness: f32) {
+        for (spring, k) in self.world.springs.iter_mut().zip(&self.kinds) {
+            if *k == kind {
+                spring.stiffness = stiffness;
+            }
+        }
+    }
+
+    /// The springs of one kind that are still holding.
+    pub fn springs(&self, kind: SpringKind) -> impl Iterator<Item = &Spring> {
+        self.world
+            .springs
+            .iter()
+            .zip(&self.kinds)
+            .filter(move |(_, k)| **k == kind)
+            .map(|(spring, _)| spring)
+    }
+
+    /// Step the cloth forward `dt` seconds, then snap any springs stretched too far.
+    pub fn step(&mut self, dt: f32) {
+        self.world.step(dt);
+        if let Some(stretch) = self.tear_stretch {
+            let particles = self.world.particles.clone();
+            self.retain_springs(|spring| {
+                let length = (particles[spring.b].pos - particles[spring.a].pos).magnitude();
+                length <= spring.rest_length * stretch
+            });
+        }
+    }
+
+    /// Cut every spring passing within `radius` of `pos`.
+    pub fn cut(&mut self, pos: Vector2, radius: f32) {
+        let particles = self.world.particles.clone();
+        self.retain_springs(|spring| {
+            let (a, b) = (particles[spring.a].pos, particles[spring.b].pos);
+            let along = b - a;
+            let t = ((pos - a).dot(along) / along.magnitude2())
+                .max(0.0)
+                .min(1.0);
+            (a + along * t - pos).magnitude() > radius
+        });
+    }
+
+    /// Remove the springs `keep` returns false for, along with their kinds.
+    fn retain_springs(&mut self, keep: impl Fn(&Spring) -> bool) {
+        let (springs, kinds) = self
+            .world
+            .springs
+            .iter()
+            .zip(&self.kinds)
+            .filter(|(spring, _)| keep(spring))
+            .unzip();
+        self.world.springs = springs;
+        self.kinds = kinds;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let cloth = Cloth::grid(4, 3, 0.1);
+        assert_eq!(cloth.world.particles.len(), 12);
+        assert_eq!(cloth.springs(SpringKind::Structural).count(), 3 * 3 + 4 * 2);
+        assert_eq!(cloth.springs(SpringKind::Shear).count(), 2 * 3 * 2);
+        assert_eq!(cloth.springs(SpringKind::Bend).count(), 2 * 3 + 4);
+        assert_eq!(cloth.kinds.len(), cloth.world.springs.len());
+        assert!(cloth.world.particles[cloth.particle(3, 0)].pinned);
+        assert!(!cloth.world.particles[cloth.particle(3, 1)].pinned);
+        let corner = cloth.world.particles[cloth.particle(3, 2)].pos;
+        assert!(
+            (corner - vec2(0.3, -0.2)).magnitude() < 1e-6,
+            "{:?}",
+            corner
+        );
+    }
+
+    #[test]
+    fn test_hang() {
+        // without tearing, cloth just sags a little under its own weight.
+        let mut cloth = Cloth::grid(5, 5, 10.0);
+        cloth.world.gravity = vec2(0.0, -400.0);
+        cloth.world.damping = 0.99;
+        for _ in 0..1000 {
+            cloth.step(1.0 / 240.0);
+        }
+        let bottom = cloth.world.particles[cloth.particle(2, 4)].pos;
+        assert!(bottom.y < -40.0 && bottom.y > -45.0, "{:?}", bottom);
+        assert!((bottom.x - 20.0).abs() < 1e-3, "{:?}", bottom);
+    }
+
+    #[test]
+    fn test_tear() {
+        let mut cloth = Cloth::grid(5, 5, 10.0);
+        cloth.tear_stretch = Some(2.0);
+        let springs = cloth.world.springs.len();
+        cloth.step(1.0 / 240.0);
+        assert_eq!(cloth.world.springs.len(), springs);
+
+        // yanking a corner tears it off.
+        let corner = cloth.particle(4, 4);
+        cloth.world.particles[corner].pos = vec2(100.0, -100.0);
+        cloth.step(1.0 / 240.0);
+        assert!(cloth
+            .world
+            .springs
+            .iter()
+            .all(|s| s.a != corner && s.b != corner));
+        assert_eq!(cloth.kinds.len(), cloth.world.springs.len());
+
+        // cutting between two rows only cuts the springs there.
+        let joined = |cloth: &Cloth, x| {
+            let (a, b) = (cloth.particle(x, 1), cloth.particle(x, 2));
+            cloth
+                .springs(SpringKind::Structural)
+                .any(|s| s.a == a && s.b == b)
+        };
+        assert!(joined(&cloth, 2) && joined(&cloth, 1));
+        cloth.cut(vec2(20.0, -15.0), 1.0);
+        assert!(!joined(&cloth, 2) && joined(&cloth, 1));
+    }
+}
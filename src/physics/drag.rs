@@ -0,0 +1,129 @@
+//! Grabbing particles with the mouse.
+//!
+//! While the mouse is down over a particle, a `Dragger` pulls it towards the cursor with a stiff,
+//! damped spring, so it's dragged around but still tugs on whatever it's joined to. Heavy
+//! particles lag behind the cursor more than light ones. The spring isn't added to the `World`,
+//! so sketches don't have to skip drawing it or the particle at its far end:
+//!
+//! ```ignore
+//! // on mouse down:
+//! model.dragger.press(&model.world.particles, mouse);
+//! // every update, before stepping:
+//! model.dragger.move_to(mouse);
+//! model.dragger.pull(&mut model.world, dt);
+//! model.world.step(dt);
+//! // on mouse up:
+//! model.dragger.release();
+//! ```
+
+use super::{Particle, World};
+use nannou::geom::{vec2, Vector2};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Dragger {
+    /// How far outside a particle's radius it can still be grabbed.
+    pub reach: f32,
+    /// Of the spring to the cursor.
+    pub stiffness: f32,
+    /// How much the held particle's velocity is dragged down; by default just enough that a
+    /// particle of mass 1 doesn't overshoot the cursor.
+    pub damping: f32,
+    held: Option<usize>,
+    // where the cursor is.
+    target: Vector2,
+}
+
+impl Dragger {
+    pub fn new(stiffness: f32) -> Dragger {
+        Dragger {
+            reach: 0.0,
+            stiffness,
+            damping: 2.0 * stiffness.sqrt(),
+            held: None,
+            target: vec2(0.0, 0.0),
+        }
+    }
+
+    /// Grab the nearest free particle within reach of `pos`, returning it if there is one.
+    pub fn press(&mut self, particles: &[Particle], pos: Vector2) -> Option<usize> {
+        self.target = pos;
+        self.held = particles
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !p.pinned)
+            .map(|(i, p)| (i, (p.pos - pos).magnitude() - p.radius))
+            .filter(|(_, gap)| *gap <= self.reach)
+            .fold(
+                None,
+                |nearest: Option<(usize, f32)>, (i, gap)| match nearest {
+                    Some((_, closest)) if closest <= gap => nearest,
+                    _ => Some((i, gap)),
+                },
+            )
+            .map(|(i, _)| i);
+        self.held
+    }
+
+    /// Let go of whatever's held.
+    pub fn release(&mut self) {
+        self.held = None;
+    }
+
+    /// The particle being dragged, if any.
+    pub fn held(&self) -> Option<usize> {
+        self.held
+    }
+
+    /// Move the cursor end of the spring.
+    pub fn move_to(&mut self, pos: Vector2) {
+        self.target = pos;
+    }
+
+    /// Pull the held particle towards the cursor for `dt` seconds; call it before each step.
+    pub fn pull(&self, world: &mut World, dt: f32) {
+        let particle = match self.held.and_then(|i| world.particles.get_mut(i)) {
+            Some(particle) => particle,
+            None => return,
+        };
+        let force = (self.target - particle.pos) * self.stiffness - particle.vel * self.damping;
+        particle.vel += force / particle.mass * dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag() {
+        let mut world = World::new();
+        world.gravity = vec2(0.0, 0.0);
+        world.add_particle(Particle::pinned(vec2(0.0, 0.0)));
+        let mut ball = Particle::new(vec2(1.0, 0.0), vec2(0.0, 0.0));
+        ball.radius = 0.2;
+        let ball = world.add_particle(ball);
+        world.add_particle(Particle::new(vec2(1.5, 0.0), vec2(0.0, 0.0)));
+
+        let mut dragger = Dragger::new(100.0);
+        dragger.reach = 0.3;
+        // pinned particles can't be picked up, and nothing's out here.
+        assert_eq!(dragger.press(&world.particles, vec2(0.0, 0.0)), None);
+        assert_eq!(dragger.press(&world.particles, vec2(1.0, 1.0)), None);
+        // both are in reach, but the ball's edge is closer than the other particle.
+        assert_eq!(dragger.press(&world.particles, vec2(1.3, 0.0)), Some(ball));
+
+        dragger.move_to(vec2(1.0, 1.0));
+        for _ in 0..120 {
+            dragger.pull(&mut world, 1.0 / 60.0);
+            world.step(1.0 / 60.0);
+        }
+        let pos = world.particles[ball].pos;
+        assert!((pos - vec2(1.0, 1.0)).magnitude() < 1e-2, "{:?}", pos);
+
+        dragger.release();
+        assert_eq!(dragger.held(), None);
+        let vel = world.particles[ball].vel;
+        dragger.pull(&mut world, 1.0 / 60.0);
+        assert_eq!(world.particles[ball].vel, vel);
+    }
+}
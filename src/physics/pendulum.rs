@@ -0,0 +1,267 @@
+//! Pendulums of several rigid links, hanging one from the end of the next.
+//!
+//! Rather than particles held together by stiff springs, a `PendulumChain` is described by the
+//! angle of each link, so the links can't stretch at all. Each step solves Lagrange's equations
+//! for how the angles accelerate: every bob's weight and the forces applied to it pull on all the
+//! links above it, and each link swinging flings the ones below it around. Steps are RK4, so
+//! a chain left alone keeps its energy, and even the chaotic double pendulum stays true for a
+//! good while.
+
+use nannou::geom::{vec2, Vector2};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Link {
+    pub length: f32,
+    /// Of the bob at the end of the link; the rod itself weighs nothing.
+    pub mass: f32,
+    /// Radians counterclockwise from hanging straight down.
+    pub angle: f32,
+    pub ang_vel: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct PendulumChain {
+    /// Where the first link hangs from.
+    pub anchor: Vector2,
+    /// From the anchor down.
+    pub links: Vec<Link>,
+    /// Acceleration applied to every bob.
+    pub gravity: Vector2,
+    /// Angular velocities are multiplied by this every step; 1 for none.
+    pub damping: f32,
+    // forces on each bob applied since the last step.
+    forces: Vec<Vector2>,
+}
+
+impl PendulumChain {
+    /// A chain hanging straight down from `anchor`, with links of `lengths` and bobs of mass 1.
+    pub fn hanging(anchor: Vector2, lengths: &[f32]) -> PendulumChain {
+        assert!(!lengths.is_empty(), "a pendulum needs at least one link");
+        PendulumChain {
+            anchor,
+            links: lengths
+                .iter()
+                .map(|length| Link {
+                    length: *length,
+                    mass: 1.0,
+                    angle: 0.0,
+                    ang_vel: 0.0,
+                })
+                .collect(),
+            gravity: vec2(0.0, -1.0),
+            damping: 1.0,
+            forces: vec![vec2(0.0, 0.0); lengths.len()],
+        }
+    }
+
+    /// Where the bob at the end of each link is.
+    pub fn positions(&self) -> Vec<Vector2> {
+        self.links
+            .iter()
+            .scan(self.anchor, |pos, link| {
+                *pos += direction(link.angle) * link.length;
+                Some(*pos)
+            })
+            .collect()
+    }
+
+    /// How fast each bob is moving.
+    pub fn velocities(&self) -> Vec<Vector2> {
+        self.links
+            .iter()
+            .scan(vec2(0.0, 0.0), |vel, link| {
+                *vel += sideways(link.angle) * (link.length * link.ang_vel);
+                Some(*vel)
+            })
+            .collect()
+    }
+
+    /// Kinetic energy, plus potential energy from gravity with the anchor at 0.
+    pub fn energy(&self) -> f32 {
+        self.links
+            .iter()
+            .zip(self.positions())
+            .zip(self.velocities())
+            .map(|((link, pos), vel)| {
+                link.mass * (0.5 * vel.magnitude2() - self.gravity.dot(pos - self.anchor))
+            })
+            .sum()
+    }
+
+    /// Push on the bob at the end of `link` until the next step.
+    pub fn apply_force(&mut self, link: usize, force: Vector2) {
+        self.forces[link] += force;
+    }
+
+    /// Move the chain forward `dt` seconds.
+    pub fn step(&mut self, dt: f32) {
+        let angles = self.links.iter().map(|l| l.angle).collect::<Vec<_>>();
+        let ang_vels = self.links.iter().map(|l| l.ang_vel).collect::<Vec<_>>();
+        // (angles, angular velocities) `h` seconds along the derivative `d`, and the derivative
+        // there.
+        let derivative = |d: &(Vec<f32>, Vec<f32>), h: f32| {
+            let angles = angles
+                .iter()
+                .zip(&d.0)
+                .map(|(a, v)| a + v * h)
+                .collect::<Vec<_>>();
+            let ang_vels = ang_vels
+                .iter()
+                .zip(&d.1)
+                .map(|(v, a)| v + a * h)
+                .collect::<Vec<_>>();
+            let ang_accs = self.ang_accs(&angles, &ang_vels);
+            (ang_vels, ang_accs)
+        };
+        let zero = vec![0.0; angles.len()];
+        let k1 = derivative(&(zero.clone(), zero), 0.0);
+        let k2 = derivative(&k1, dt / 2.0);
+        let k3 = derivative(&k2, dt / 2.0);
+        let k4 = derivative(&k3, dt);
+
+        for (i, link) in self.links.iter_mut().enumerate() {
+            link.angle += (k1.0[i] + (k2.0[i] + k3.0[i]) * 2.0 + k4.0[i]) * (dt / 6.0);
+            link.ang_vel += (k1.1[i] + (k2.1[i] + k3.1[i]) * 2.0 + k4.1[i]) * (dt / 6.0);
+            link.ang_vel *= self.damping;
+        }
+        for force in &mut self.forces {
+            *force = vec2(0.0, 0.0);
+        }
+    }
+
+    /// The angular acceleration of each link, if the chain had `angles` and `ang_vels`.
+    ///
+    /// With `l` the lengths, `u` the directions the bobs would move as each link turned, and `μ_k`
+    /// the mass hanging from link `k` and below, Lagrange's equations are, for each link `j`:
+    ///
+    /// `Σ_k μ_max(j,k) l_j l_k (cos(θ_j - θ_k) θ''_k + sin(θ_j - θ_k) θ'_k²) = l_j u_j · F_j`
+    ///
+    /// where `F_j` is the total force on the bobs from `j` down.
+    fn ang_accs(&self, angles: &[f32], ang_vels: &[f32]) -> Vec<f32> {
+        let n = self.links.len();
+        let below = |k: usize| -> (f32, Vector2) {
+            self.links[k..].iter().zip(&self.forces[k..]).fold(
+                (0.0, vec2(0.0, 0.0)),
+                |(mass, force), (link, f)| {
+                    (mass + link.mass, force + self.gravity * link.mass + *f)
+                },
+            )
+        };
+        let below = (0..n).map(below).collect::<Vec<_>>();
+        // the augmented matrix of the equations above.
+        let mut rows = (0..n)
+            .map(|j| {
+                let lj = self.links[j].length;
+                let mut row = vec![0.0; n + 1];
+                row[n] = lj * sideways(angles[j]).dot(below[j].1);
+                for k in 0..n {
+                    let coefficient = below[j.max(k)].0 * lj * self.links[k].length;
+                    let between = angles[j] - angles[k];
+                    row[k] = coefficient * between.cos();
+                    row[n] -= coefficient * between.sin() * ang_vels[k] * ang_vels[k];
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+        solve(&mut rows)
+    }
+}
+
+/// Which way a link at `angle` points, from its top end.
+fn direction(angle: f32) -> Vector2 {
+    vec2(angle.sin(), -angle.cos())
+}
+
+/// Which way a link's bottom end moves as its `angle` increases.
+fn sideways(angle: f32) -> Vector2 {
+    vec2(angle.cos(), angle.sin())
+}
+
+/// Solve the linear equations in the augmented matrix `rows`, by Gaussian elimination.
+fn solve(rows: &mut [Vec<f32>]) -> Vec<f32> {
+    let n = rows.len();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|a, b| {
+                rows[*a][column]
+                    .abs()
+                    .partial_cmp(&rows[*b][column].abs())
+                    .unwrap()
+            })
+            .unwrap();
+        rows.swap(column, pivot);
+        let (above, below) = rows.split_at_mut(column + 1);
+        let pivot = &above[column];
+        for row in below {
+            let factor = row[column] / pivot[column];
+            for (x, p) in row.iter_mut().zip(pivot).skip(column) {
+                *x -= factor * p;
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let known = (row + 1..n)
+            .map(|k| rows[row][k] * solution[k])
+            .sum::<f32>();
+        solution[row] = (rows[row][n] - known) / rows[row][row];
+    }
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_period() {
+        // swinging a little, a pendulum's period is 2 pi sqrt(l / g).
+        let mut chain = PendulumChain::hanging(vec2(0.0, 0.0), &[2.0]);
+        chain.gravity = vec2(0.0, -9.8);
+        chain.links[0].angle = 0.01;
+        let period = 2.0 * PI * (2.0f32 / 9.8).sqrt();
+        let steps = 1000;
+        for _ in 0..steps {
+            chain.step(period / steps as f32);
+        }
+        let link = chain.links[0];
+        assert!((link.angle - 0.01).abs() < 1e-4, "{:?}", link);
+        assert!(link.ang_vel.abs() < 1e-3, "{:?}", link);
+        let bob = chain.positions()[0];
+        assert!((bob.magnitude() - 2.0).abs() < 1e-5, "{:?}", bob);
+    }
+
+    #[test]
+    fn test_energy() {
+        // a double pendulum let go from sideways swings chaotically, but keeps its energy.
+        let mut chain = PendulumChain::hanging(vec2(0.0, 0.0), &[1.0, 0.7]);
+        chain.gravity = vec2(0.0, -9.8);
+        chain.links[1].mass = 0.5;
+        chain.links[0].angle = PI / 2.0;
+        chain.links[1].angle = PI / 2.0;
+        let energy = chain.energy();
+        assert!(energy.abs() < 1e-5, "{}", energy);
+        let mut lowest = f32::MAX;
+        for _ in 0..5000 {
+            chain.step(0.001);
+            lowest = lowest.min(chain.positions()[1].y);
+        }
+        // it swung right down through the bottom.
+        assert!(lowest < -1.5, "{}", lowest);
+        let energy = chain.energy();
+        assert!(energy.abs() < 1e-3, "{}", energy);
+
+        // a steady push sideways on the bottom bob holds the chain out at an angle, with the
+        // top link less tilted (it carries both weights).
+        let mut chain = PendulumChain::hanging(vec2(0.0, 0.0), &[1.0, 1.0]);
+        chain.damping = 0.99;
+        for _ in 0..5000 {
+            chain.apply_force(1, vec2(1.0, 0.0));
+            chain.step(0.01);
+        }
+        let (top, bottom) = (chain.links[0].angle, chain.links[1].angle);
+        assert!((top - (0.5f32).atan()).abs() < 1e-3, "{}", top);
+        assert!((bottom - PI / 4.0).abs() < 1e-3, "{}", bottom);
+    }
+}
@@ -0,0 +1,183 @@
+//! A Barnes-Hut quadtree, for forces between every pair of thousands of bodies.
+//!
+//! Each cell of the tree keeps the total mass and center of mass of the bodies inside it. To
+//! find the force on a point, cells far enough away compared to their size (less than `theta`
+//! cell widths per unit of distance) are treated as a single body at their center of mass, and
+//! only closer cells are opened up; so each query takes about `O(log n)` rather than `O(n)`.
+//! `theta` of 0 is exact, and 0.5 to 1 the usual tradeoff.
+//!
+//! The force law is up to the caller, so the same tree does gravity and repulsion:
+//!
+//! ```ignore
+//! let tree = QuadTree::new(&bodies);
+//! // inverse-square attraction.
+//! let force = tree.force_on(pos, 0.7, |d, mass| d * (G * mass / d.magnitude2().powf(1.5)));
+//! ```
+
+use nannou::geom::{vec2, Vector2};
+
+/// Cells are only split this many times; bodies closer than that share a leaf.
+const MAX_DEPTH: usize = 24;
+
+#[derive(Clone, Debug)]
+pub struct QuadTree {
+    /// The root is cell 0.
+    cells: Vec<Cell>,
+}
+
+#[derive(Clone, Debug)]
+struct Cell {
+    /// Width of the (square) cell.
+    size: f32,
+    mass: f32,
+    center_of_mass: Vector2,
+    /// Indices of the child cells, or empty for a leaf.
+    children: Vec<usize>,
+}
+
+impl QuadTree {
+    /// A tree holding each `(position, mass)` in `bodies`.
+    pub fn new(bodies: &[(Vector2, f32)]) -> QuadTree {
+        let mut tree = QuadTree { cells: vec![] };
+        if bodies.is_empty() {
+            return tree;
+        }
+        let (mut min, mut max) = (bodies[0].0, bodies[0].0);
+        for (pos, _) in bodies {
+            min = vec2(min.x.min(pos.x), min.y.min(pos.y));
+            max = vec2(max.x.max(pos.x), max.y.max(pos.y));
+        }
+        let size = (max.x - min.x).max(max.y - min.y).max(1e-6) * 1.01;
+        let center = (min + max) / 2.0;
+        tree.build(bodies.to_vec(), center, size, 0);
+        tree
+    }
+
+    /// Add a cell for `bodies`, which are all inside the square at `center`, returning its index.
+    fn build(
+        &mut self,
+        bodies: Vec<(Vector2, f32)>,
+        center: Vector2,
+        size: f32,
+        depth: usize,
+    ) -> usize {
+        let mass = bodies.iter().map(|(_, m)| m).sum::<f32>();
+        let center_of_mass = if mass > 0.0 {
+            bodies
+                .iter()
+                .fold(vec2(0.0, 0.0), |sum, (pos, m)| sum + *pos * *m)
+                / mass
+        } else {
+            center
+        };
+        let index = self.cells.len();
+        self.cells.push(Cell {
+            size,
+            mass,
+            center_of_mass,
+            children: vec![],
+        });
+        if bodies.len() <= 1 || depth == MAX_DEPTH {
+            return index;
+        }
+
+        let mut quadrants = vec![vec![]; 4];
+        for body in bodies {
+            let quadrant = (body.0.x >= center.x) as usize + 2 * (body.0.y >= center.y) as usize;
+            quadrants[quadrant].push(body);
+        }
+        for (quadrant, bodies) in quadrants.into_iter().enumerate() {
+            if bodies.is_empty() {
+                continue;
+            }
+            let offset = vec2(
+                if quadrant & 1 == 0 { -0.25 } else { 0.25 },
+                if quadrant & 2 == 0 { -0.25 } else { 0.25 },
+            ) * size;
+            let child = self.build(bodies, center + offset, size / 2.0, depth + 1);
+            self.cells[index].children.push(child);
+        }
+        index
+    }
+
+    /// The total force on a point at `pos` from every body, where `law(d, mass)` is the force
+    /// from a body of `mass` at `d` away (from `pos` to the body). Bodies at exactly `pos` (like
+    /// the point itself) are skipped.
+    pub fn force_on(
+        &self,
+        pos: Vector2,
+        theta: f32,
+        law: impl Fn(Vector2, f32) -> Vector2,
+    ) -> Vector2 {
+        let mut force = vec2(0.0, 0.0);
+        if self.cells.is_empty() {
+            return force;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            let d = cell.center_of_mass - pos;
+            let distance = d.magnitude();
+            if cell.children.is_empty() || cell.size < theta * distance {
+                if distance > 0.0 && cell.mass != 0.0 {
+                    force += law(d, cell.mass);
+                }
+            } else {
+                stack.extend(&cell.children);
+            }
+        }
+        force
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    fn gravity(d: Vector2, mass: f32) -> Vector2 {
+        d * (mass / (d.magnitude2() + 1e-4).powf(1.5))
+    }
+
+    #[test]
+    fn test_force_on() {
+        let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
+        let bodies = (0..1000)
+            .map(|_| (vec2(rng.gen::<f32>(), rng.gen::<f32>()), rng.gen::<f32>()))
+            .collect::<Vec<_>>();
+        let tree = QuadTree::new(&bodies);
+        for (pos, _) in bodies.iter().take(20) {
+            let exact = bodies
+                .iter()
+                .filter(|(other, _)| other != pos)
+                .fold(vec2(0.0, 0.0), |sum, (other, mass)| {
+                    sum + gravity(*other - *pos, *mass)
+                });
+            assert!(
+                (tree.force_on(*pos, 0.0, gravity) - exact).magnitude() < exact.magnitude() * 1e-3
+            );
+            let approximate = tree.force_on(*pos, 0.5, gravity);
+            assert!(
+                (approximate - exact).magnitude() < exact.magnitude() * 0.05,
+                "{:?} {:?}",
+                approximate,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn test_coincident() {
+        // bodies on top of each other don't split forever, and one far away sees all of them.
+        let mut bodies = vec![(vec2(0.0, 0.0), 1.0); 10];
+        bodies.push((vec2(1.0, 0.0), 1.0));
+        let tree = QuadTree::new(&bodies);
+        let force = tree.force_on(vec2(1.0, 0.0), 0.5, |d, mass| d * mass);
+        assert!((force - vec2(-10.0, 0.0)).magnitude() < 1e-4, "{:?}", force);
+        assert_eq!(
+            QuadTree::new(&[]).force_on(vec2(0.0, 0.0), 0.5, |d, _| d),
+            vec2(0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Rigid polygons, which turn as well as move.
+//!
+//! A `RigidBody` has an angle and angular velocity alongside its position and velocity, and a
+//! moment of inertia saying how hard it is to spin. Forces applied off its center of mass also
+//! apply a torque. When a corner hits a wall, the impulse pushing it back out acts at that
+//! corner, so a body landing corner-first is knocked spinning, and a spinning body bouncing off
+//! the floor is thrown sideways.
+
+use nannou::geom::{vec2, Rect, Vector2};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RigidBody {
+    /// The center of mass.
+    pub pos: Vector2,
+    pub vel: Vector2,
+    /// Radians, counterclockwise.
+    pub angle: f32,
+    pub ang_vel: f32,
+    pub mass: f32,
+    /// The moment of inertia about the center of mass.
+    pub inertia: f32,
+    /// Corners, counterclockwise, relative to the center of mass at angle 0.
+    pub shape: Vec<Vector2>,
+    // force and torque applied since the last step.
+    force: Vector2,
+    torque: f32,
+}
+
+impl RigidBody {
+    /// A solid polygon of uniform density with corners `shape` (counterclockwise), moved so its
+    /// center of mass is at `pos`.
+    pub fn polygon(pos: Vector2, shape: &[Vector2], mass: f32) -> RigidBody {
+        assert!(shape.len() >= 3, "a polygon needs at least 3 corners");
+        // split into triangles fanning out from the origin.
+        let (mut area, mut centroid) = (0.0, vec2(0.0, 0.0));
+        for (a, b) in shape.iter().zip(shape.iter().cycle().skip(1)) {
+            let cross = cross(*a, *b);
+            area += cross / 2.0;
+            centroid += (*a + *b) * (cross / 6.0);
+        }
+        assert!(area > 0.0, "polygon corners must go counterclockwise");
+        let centroid = centroid / area;
+        let shape = shape.iter().map(|p| *p - centroid).collect::<Vec<_>>();
+
+        let (mut numerator, mut denominator) = (0.0, 0.0);
+        for (a, b) in shape.iter().zip(shape.iter().cycle().skip(1)) {
+            let cross = cross(*a, *b);
+            numerator += cross * (a.dot(*a) + a.dot(*b) + b.dot(*b));
+            denominator += cross;
+        }
+        RigidBody {
+            pos,
+            vel: vec2(0.0, 0.0),
+            angle: 0.0,
+            ang_vel: 0.0,
+            mass,
+            inertia: mass * numerator / (6.0 * denominator),
+            shape,
+            force: vec2(0.0, 0.0),
+            torque: 0.0,
+        }
+    }
+
+    /// Where the corners are now.
+    pub fn vertices(&self) -> Vec<Vector2> {
+        let (sin, cos) = self.angle.sin_cos();
+        self.shape
+            .iter()
+            .map(|p| self.pos + vec2(p.x * cos - p.y * sin, p.x * sin + p.y * cos))
+            .collect()
+    }
+
+    /// How fast the point of the body at `at` is moving.
+    pub fn velocity_at(&self, at: Vector2) -> Vector2 {
+        let r = at - self.pos;
+        self.vel + vec2(-r.y, r.x) * self.ang_vel
+    }
+
+    /// Push on the body at `at` until the next step, turning it if `at` isn't the center of mass.
+    pub fn apply_force(&mut self, force: Vector2, at: Vector2) {
+        self.force += force;
+        self.torque += cross(at - self.pos, force);
+    }
+
+    /// Change the body's momentum by `impulse`, at `at`.
+    pub fn apply_impulse(&mut self, impulse: Vector2, at: Vector2) {
+        self.vel += impulse / self.mass;
+        self.ang_vel += cross(at - self.pos, impulse) / self.inertia;
+    }
+
+    pub fn kinetic_energy(&self) -> f32 {
+        0.5 * self.mass * self.vel.magnitude2() + 0.5 * self.inertia * self.ang_vel * self.ang_vel
+    }
+}
+
+/// The z part of the cross product of `a` and `b`.
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+#[derive(Clone, Debug)]
+pub struct RigidWorld {
+    pub bodies: Vec<RigidBody>,
+    pub gravity: Vector2,
+    /// Walls the bodies bounce off the inside of, if any.
+    pub bounds: Option<Rect>,
+    /// How much of its speed into a wall a corner keeps bouncing off it.
+    pub restitution: f32,
+    /// How much walls grip the corners sliding along them (Coulomb friction).
+    pub friction: f32,
+}
+
+impl Default for RigidWorld {
+    fn default() -> Self {
+        RigidWorld {
+            bodies: vec![],
+            gravity: vec2(0.0, -1.0),
+            bounds: None,
+            restitution: 1.0,
+            friction: 0.0,
+        }
+    }
+}
+
+impl RigidWorld {
+    pub fn new() -> RigidWorld {
+        RigidWorld::default()
+    }
+
+    /// Move everything forward `dt` seconds: apply the forces since the last step and gravity,
+    /// then bounce off the walls.
+    pub fn step(&mut self, dt: f32) {
+        for body in &mut self.bodies {
+            body.vel += (body.force / body.mass + self.gravity) * dt;
+            body.ang_vel += body.torque / body.inertia * dt;
+            body.force = vec2(0.0, 0.0);
+            body.torque = 0.0;
+            body.pos += body.vel * dt;
+            body.angle += body.ang_vel * dt;
+            if let Some(bounds) = self.bounds {
+                collide_walls(body, bounds, self.restitution, self.friction);
+            }
+        }
+    }
+}
+
+/// Push a body back inside `bounds`, with an impulse where it's gone through each wall.
+fn collide_walls(body: &mut RigidBody, bounds: Rect, restitution: f32, friction: f32) {
+    // each wall's inward normal, and how far past it a point is.
+    let walls: [(Vector2, &dyn Fn(Vector2) -> f32); 4] = [
+        (vec2(1.0, 0.0), &|p| bounds.left() - p.x),
+        (vec2(-1.0, 0.0), &|p| p.x - bounds.right()),
+        (vec2(0.0, 1.0), &|p| bounds.bottom() - p.y),
+        (vec2(0.0, -1.0), &|p| p.y - bounds.top()),
+    ];
+    for (normal, depth) in walls.iter() {
+        // a face lying flat against the wall touches it across its middle, not at one corner.
+        let through = body
+            .vertices()
+            .into_iter()
+            .map(|v| (v, depth(v)))
+            .filter(|(_, d)| *d > 0.0)
+            .collect::<Vec<_>>();
+        if through.is_empty() {
+            continue;
+        }
+        let penetration = through.iter().map(|(_, d)| *d).fold(0.0, f32::max);
+        let contact = through
+            .iter()
+            .map(|(v, _)| *v)
+            .fold(vec2(0.0, 0.0), |a, b| a + b)
+            / through.len() as f32;
+        body.pos += *normal * penetration;
+        let contact = contact + *normal * penetration;
+
+        let into = body.velocity_at(contact).dot(*normal);
+        if into >= 0.0 {
+            continue;
+        }
+        let r = contact - body.pos;
+        let (mass, inertia) = (body.mass, body.inertia);
+        // how much a unit impulse along `direction` changes the contact's speed along it.
+        let reach = |direction: Vector2| {
+            let arm = cross(r, direction);
+            1.0 / mass + arm * arm / inertia
+        };
+        let push = -(1.0 + restitution) * into / reach(*normal);
+        body.apply_impulse(*normal * push, contact);
+
+        // friction against the sliding, up to `friction` times the push.
+        let tangent = vec2(-normal.y, normal.x);
+        let sliding = body.velocity_at(contact).dot(tangent);
+        let grip = (-sliding / reach(tangent))
+            .max(-friction * push)
+            .min(friction * push);
+        body.apply_impulse(tangent * grip, contact);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(pos: Vector2, size: f32) -> RigidBody {
+        let h = size / 2.0;
+        let shape = [vec2(-h, -h), vec2(h, -h), vec2(h, h), vec2(-h, h)];
+        RigidBody::polygon(pos, &shape, 2.0)
+    }
+
+    #[test]
+    fn test_polygon() {
+        // m (w^2 + h^2) / 12.
+        let body = square(vec2(0.0, 0.0), 0.3);
+        assert!(
+            (body.inertia - 2.0 * 0.18 / 12.0).abs() < 1e-6,
+            "{}",
+            body.inertia
+        );
+
+        // a triangle is recentered on its centroid.
+        let shape = [vec2(0.0, 0.0), vec2(3.0, 0.0), vec2(0.0, 3.0)];
+        let body = RigidBody::polygon(vec2(1.0, 1.0), &shape, 1.0);
+        assert!((body.shape[0] - vec2(-1.0, -1.0)).magnitude() < 1e-6);
+        assert!((body.vertices()[1] - vec2(3.0, 0.0)).magnitude() < 1e-6);
+
+        // pushing sideways off center spins it.
+        let mut body = square(vec2(0.0, 0.0), 1.0);
+        body.apply_impulse(vec2(1.0, 0.0), vec2(0.0, 0.5));
+        assert!(body.ang_vel < 0.0);
+        assert!((body.vel - vec2(0.5, 0.0)).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn test_walls() {
+        let mut world = RigidWorld::new();
+        world.bounds = Some(Rect::from_x_y_w_h(0.0, 0.0, 2.0, 2.0));
+
+        // landing flat, a square bounces straight back up.
+        world.bodies.push(square(vec2(0.0, 0.0), 0.2));
+        // landing on a corner, it's knocked spinning.
+        let mut tilted = square(vec2(0.5, 0.0), 0.2);
+        tilted.angle = 0.3;
+        world.bodies.push(tilted);
+        let energy = |world: &RigidWorld, i: usize| {
+            let body = &world.bodies[i];
+            body.kinetic_energy() - body.mass * world.gravity.dot(body.pos)
+        };
+        let before = [energy(&world, 0), energy(&world, 1)];
+        for _ in 0..600 {
+            world.step(1.0 / 240.0);
+        }
+        let (flat, tilted) = (&world.bodies[0], &world.bodies[1]);
+        assert!(flat.ang_vel.abs() < 1e-4, "{}", flat.ang_vel);
+        assert!(tilted.ang_vel.abs() > 0.1, "{}", tilted.ang_vel);
+        for (i, before) in before.iter().enumerate() {
+            // perfectly elastic walls don't make energy, and barely lose any, next to the 2 it
+            // took to fall to the floor.
+            let after = energy(&world, i);
+            assert!((after - before).abs() < 0.05, "{} {}", before, after);
+        }
+        for body in &world.bodies {
+            for v in body.vertices() {
+                assert!(v.y >= -1.0 - 1e-4, "{:?}", v);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! A spatial hash, for finding the points near a point without checking every one.
+//!
+//! Points are bucketed into square cells; a query only looks at the cells its circle overlaps.
+//! With cells about the size of the query radius, that's a handful of cells, so it suits
+//! short-ranged forces like collisions and fluid pressure.
+
+use nannou::geom::{vec2, Vector2};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    positions: Vec<Vector2>,
+    /// The indices of the points in each cell.
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32, positions: &[Vector2]) -> SpatialHash {
+        assert!(cell_size > 0.0, "cells must have a size");
+        let mut hash = SpatialHash {
+            cell_size,
+            positions: positions.to_vec(),
+            cells: HashMap::new(),
+        };
+        for (i, pos) in positions.iter().enumerate() {
+            let cell = hash.cell(*pos);
+            hash.cells.entry(cell).or_default().push(i);
+        }
+        hash
+    }
+
+    /// Add a point at `pos`, returning its index.
+    pub fn insert(&mut self, pos: Vector2) -> usize {
+        let i = self.positions.len();
+        self.positions.push(pos);
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().push(i);
+        i
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    fn cell(&self, pos: Vector2) -> (i32, i32) {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// The indices of every point within `radius` of `pos`, including any at `pos` itself.
+    pub fn within(&self, pos: Vector2, radius: f32) -> Vec<usize> {
+        let r = vec2(radius, radius);
+        let (min, max) = (self.cell(pos - r), self.cell(pos + r));
+        let mut found = vec![];
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(points) = self.cells.get(&(x, y)) {
+                    found.extend(
+                        points.iter().filter(|i| {
+                            (self.positions[**i] - pos).magnitude2() <= radius * radius
+                        }),
+                    );
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_within() {
+        let mut rng: XorShiftRng = SeedableRng::seed_from_u64(12345);
+        let positions = (0..500)
+            .map(|_| vec2(rng.gen::<f32>() - 0.5, rng.gen::<f32>() - 0.5) * 4.0)
+            .collect::<Vec<_>>();
+        // half of them added afterwards, which finds the same.
+        let mut hash = SpatialHash::new(0.3, &positions[..250]);
+        for pos in &positions[250..] {
+            hash.insert(*pos);
+        }
+        assert_eq!(hash.len(), 500);
+        for pos in positions.iter().take(50) {
+            let radius = rng.gen::<f32>();
+            let mut found = hash.within(*pos, radius);
+            found.sort();
+            let expected = (0..positions.len())
+                .filter(|i| (positions[*i] - *pos).magnitude2() <= radius * radius)
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Gray-Scott reaction-diffusion: two chemicals spreading over a grid and reacting, which grows
+//! spots, stripes and coral out of a small seed.
+//!
+//! `u` is fed in everywhere at the `feed` rate, and `v` eats it (`u + 2v -> 3v`) and is removed
+//! at the `kill` rate. Both diffuse, `u` twice as fast as `v`; which patterns grow depends almost
+//! entirely on `feed` and `kill`. The grid wraps around at its edges, and is double buffered: each
+//! step reads one pair of grids and writes the other.
+//!
+//! See <https://www.karlsims.com/rd.html> for a lovely explanation.
+
+use crate::grid::FloatGrid;
+use nannou::geom::Vector2;
+
+#[derive(Clone, Debug)]
+pub struct ReactionDiffusion {
+    pub width: usize,
+    pub height: usize,
+    /// How fast `u` spreads.
+    pub diffusion_u: f32,
+    /// How fast `v` spreads.
+    pub diffusion_v: f32,
+    /// How fast `u` is added.
+    pub feed: f32,
+    /// How fast `v` is removed, on top of `feed`.
+    pub kill: f32,
+    // the concentrations in each cell, row by row from the bottom left, and the buffers the next
+    // step writes into.
+    u: FloatGrid,
+    v: FloatGrid,
+    next_u: FloatGrid,
+    next_v: FloatGrid,
+}
+
+impl ReactionDiffusion {
+    /// A grid full of `u` and empty of `v` (which stays that way until it's seeded), with feed
+    /// and kill rates that grow coral.
+    pub fn new(width: usize, height: usize) -> ReactionDiffusion {
+        assert!(width > 0 && height > 0, "the grid can't be empty");
+        ReactionDiffusion {
+            width,
+            height,
+            diffusion_u: 1.0,
+            diffusion_v: 0.5,
+            feed: 0.0545,
+            kill: 0.062,
+            u: FloatGrid::new(width, height, 1.0),
+            v: FloatGrid::new(width, height, 0.0),
+            next_u: FloatGrid::new(width, height, 1.0),
+            next_v: FloatGrid::new(width, height, 0.0),
+        }
+    }
+
+    pub fn u(&self, x: usize, y: usize) -> f32 {
+        *self.u.get(x, y)
+    }
+
+    pub fn v(&self, x: usize, y: usize) -> f32 {
+        *self.v.get(x, y)
+    }
+
+    /// Every cell's `u`, row by row from the bottom left.
+    pub fn u_values(&self) -> &[f32] {
+        self.u.values()
+    }
+
+    /// Every cell's `v`, row by row from the bottom left.
+    pub fn v_values(&self) -> &[f32] {
+        self.v.values()
+    }
+
+    /// Fill every cell within `radius` cells of `center` (in cells, from the bottom left corner of
+    /// the grid) with `v`, to start a pattern growing there.
+    pub fn seed(&mut self, center: Vector2, radius: f32) {
+        self.u.paint(center, radius, 0.5);
+        self.v.paint(center, radius, 1.0);
+    }
+
+    /// Empty the grid of `v` again.
+    pub fn clear(&mut self) {
+        self.u.fill(1.0);
+        self.v.fill(0.0);
+    }
+
+    /// React and diffuse for `dt` (in which case 1 is about as big a step as stays stable).
+    pub fn step(&mut self, dt: f32) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (u, v) = (self.u.get(x, y), self.v.get(x, y));
+                let reaction = u * v * v;
+                let du =
+                    self.diffusion_u * self.u.laplacian(x, y) - reaction + self.feed * (1.0 - u);
+                let dv = self.diffusion_v * self.v.laplacian(x, y) + reaction
+                    - (self.kill + self.feed) * v;
+                self.next_u.set(x, y, u + du * dt);
+                self.next_v.set(x, y, v + dv * dt);
+            }
+        }
+        std::mem::swap(&mut self.u, &mut self.next_u);
+        std::mem::swap(&mut self.v, &mut self.next_v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+
+    #[test]
+    fn test_diffusion() {
+        // with nothing fed in or reacting, the chemicals only spread out, none lost.
+        let mut grid = ReactionDiffusion::new(20, 10);
+        grid.feed = 0.0;
+        grid.kill = 0.0;
+        grid.clear();
+        grid.seed(vec2(1.0, 1.0), 1.5);
+        assert_eq!(grid.v(0, 0), 1.0);
+        assert_eq!(grid.v(19, 9), 0.0);
+        let total = |values: &[f32]| values.iter().sum::<f32>();
+        let before = total(grid.v_values()) + total(grid.u_values());
+        for _ in 0..50 {
+            grid.step(0.5);
+        }
+        // spreading across the edges, to the far corner.
+        assert!(grid.v(19, 9) > 0.0);
+        let after = total(grid.v_values()) + total(grid.u_values());
+        assert!((after - before).abs() < 1e-2, "{} {}", before, after);
+    }
+
+    #[test]
+    fn test_growth() {
+        // left alone, the grid stays as it is.
+        let mut grid = ReactionDiffusion::new(64, 64);
+        grid.step(1.0);
+        assert!(grid.v_values().iter().all(|v| *v == 0.0));
+        assert!(grid.u_values().iter().all(|u| *u == 1.0));
+
+        // but a seed of v grows into coral.
+        grid.seed(vec2(32.0, 32.0), 4.0);
+        let count = |grid: &ReactionDiffusion| grid.v_values().iter().filter(|v| **v > 0.2).count();
+        let seeded = count(&grid);
+        for _ in 0..2000 {
+            grid.step(1.0);
+        }
+        assert!(count(&grid) > seeded * 4, "{} {}", seeded, count(&grid));
+        assert!(grid.v_values().iter().all(|v| v.is_finite() && *v <= 1.0));
+    }
+}
@@ -0,0 +1,278 @@
+//! Recording a sketch's state every frame, to rewind and play it back.
+//!
+//! Anything which can take a snapshot of itself and be put back to one implements `Record`;
+//! circuits and particle worlds do, and a sketch's model can combine them with its own state. A
+//! `Recorder` keeps the last so many snapshots, and scrubbing through them puts the model back
+//! how it was:
+//!
+//! ```ignore
+//! // every update, unless scrubbing:
+//! model.world.step(dt);
+//! model.recorder.record(&model.world);
+//! // when the left arrow's held:
+//! model.recorder.step(&mut model.world, -1);
+//! // and to carry on from there:
+//! model.recorder.resume();
+//! ```
+//!
+//! Snapshots are serde data, so a recording can be saved and loaded too.
+
+use crate::circuits::{Circuit, SimState};
+use crate::physics::{Particle, Spring, World};
+use nannou::color::rgba;
+use nannou::geom::{vec2, Rect};
+use nannou::Draw;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+pub trait Record {
+    type State: Clone + Serialize + DeserializeOwned;
+
+    fn snapshot(&self) -> Self::State;
+
+    /// Put things back as they were at `state`.
+    fn restore(&mut self, state: &Self::State);
+}
+
+pub struct Recorder<M: Record> {
+    // oldest first.
+    frames: VecDeque<M::State>,
+    capacity: usize,
+    // the frame shown while scrubbing; none while recording.
+    playhead: Option<usize>,
+}
+
+impl<M: Record> Recorder<M> {
+    /// A recorder keeping the last `capacity` frames.
+    pub fn new(capacity: usize) -> Recorder<M> {
+        assert!(capacity > 0, "a recorder has to keep at least one frame");
+        Recorder {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+            playhead: None,
+        }
+    }
+
+    /// Keep a snapshot of `model`, forgetting the oldest if it's full. Ignored while scrubbing, as
+    /// the model's only being played back.
+    pub fn record(&mut self, model: &M) {
+        if self.scrubbing() {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(model.snapshot());
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The frame being shown, counting from the oldest kept, while scrubbing.
+    pub fn playhead(&self) -> Option<usize> {
+        self.playhead
+    }
+
+    pub fn scrubbing(&self) -> bool {
+        self.playhead.is_some()
+    }
+
+    /// Put `model` back to `frame`, held to the frames there are, and hold it there until
+    /// `resume`.
+    pub fn seek(&mut self, model: &mut M, frame: usize) {
+        if self.frames.is_empty() {
+            return;
+        }
+        let frame = frame.min(self.frames.len() - 1);
+        model.restore(&self.frames[frame]);
+        self.playhead = Some(frame);
+    }
+
+    /// `seek` to `by` frames on from the playhead, or from the newest frame if recording;
+    /// negative rewinds.
+    pub fn step(&mut self, model: &mut M, by: isize) {
+        let from = match self.playhead {
+            Some(frame) => frame,
+            None => self.frames.len().saturating_sub(1),
+        };
+        let to = (from as isize + by).max(0) as usize;
+        self.seek(model, to);
+    }
+
+    /// `seek` to `fraction` of the way from the oldest frame to the newest.
+    pub fn seek_fraction(&mut self, model: &mut M, fraction: f32) {
+        let last = self.frames.len().saturating_sub(1);
+        let fraction = fraction.max(0.0).min(1.0);
+        self.seek(model, (fraction * last as f32).round() as usize);
+    }
+
+    /// Carry on recording from the playhead, forgetting the frames after it.
+    pub fn resume(&mut self) {
+        if let Some(frame) = self.playhead.take() {
+            self.frames.truncate(frame + 1);
+        }
+    }
+
+    /// The frames kept, as RON.
+    pub fn save(&self) -> String {
+        ron::ser::to_string(&self.frames).unwrap()
+    }
+
+    /// A recorder holding the frames in `source`, saved by `save`.
+    pub fn load(source: &str, capacity: usize) -> Result<Recorder<M>, String> {
+        let mut recorder = Recorder::new(capacity);
+        let frames: Vec<M::State> = ron::de::from_str(source).map_err(|e| e.to_string())?;
+        let skip = frames.len().saturating_sub(capacity);
+        recorder.frames.extend(frames.into_iter().skip(skip));
+        Ok(recorder)
+    }
+}
+
+/// Draw a bar along `rect` showing how much has been recorded and, while scrubbing, where the
+/// playhead is. Mapping the mouse across it to `seek_fraction` makes it a scrubber.
+pub fn draw_scrubber<M: Record>(draw: &Draw, rect: Rect, recorder: &Recorder<M>) {
+    draw.rect()
+        .xy(rect.xy())
+        .wh(rect.wh())
+        .color(rgba(0.2, 0.2, 0.25, 0.6));
+    let full = recorder.len() as f32 / recorder.capacity() as f32;
+    let recorded = Rect::from_corners(
+        rect.bottom_left(),
+        vec2(rect.left() + rect.w() * full, rect.top()),
+    );
+    draw.rect()
+        .xy(recorded.xy())
+        .wh(recorded.wh())
+        .color(rgba(0.5, 0.5, 0.6, 0.6));
+    if let Some(frame) = recorder.playhead() {
+        let f = frame as f32 / (recorder.len().max(2) - 1) as f32;
+        let x = recorded.left() + recorded.w() * f;
+        draw.rect()
+            .xy(vec2(x, rect.y()))
+            .w_h(3.0, rect.h() + 6.0)
+            .color(rgba(1.0, 0.8, 0.0, 1.0));
+    }
+}
+
+/// A circuit records its `SimState`; its structure isn't recorded, so it has to stay the same
+/// while it is.
+impl Record for Circuit {
+    type State = SimState;
+
+    fn snapshot(&self) -> SimState {
+        Circuit::snapshot(self)
+    }
+
+    fn restore(&mut self, state: &SimState) {
+        Circuit::restore(self, state)
+    }
+}
+
+/// The particles and springs; the world's settings, like gravity, aren't recorded.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WorldState {
+    particles: Vec<Particle>,
+    springs: Vec<Spring>,
+}
+
+impl Record for World {
+    type State = WorldState;
+
+    fn snapshot(&self) -> WorldState {
+        WorldState {
+            particles: self.particles.clone(),
+            springs: self.springs.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: &WorldState) {
+        self.particles = state.particles.clone();
+        self.springs = state.springs.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder() {
+        let mut world = World::new();
+        world.add_particle(Particle::new(vec2(0.0, 0.0), vec2(1.0, 0.0)));
+        let mut recorder = Recorder::new(5);
+        let x = |world: &World| world.particles[0].pos.x;
+        for _ in 0..8 {
+            recorder.record(&world);
+            world.step(1.0);
+        }
+        // only the last five are kept.
+        assert_eq!(recorder.len(), 5);
+        assert_eq!(x(&world), 8.0);
+        recorder.step(&mut world, -1);
+        assert_eq!(recorder.playhead(), Some(3));
+        assert_eq!(x(&world), 6.0);
+        recorder.seek_fraction(&mut world, 0.0);
+        assert_eq!(x(&world), 3.0);
+        recorder.step(&mut world, -10);
+        assert_eq!(x(&world), 3.0);
+        recorder.seek(&mut world, 100);
+        assert_eq!(x(&world), 7.0);
+        // ignored while scrubbing.
+        recorder.record(&world);
+        assert_eq!(recorder.len(), 5);
+
+        // carrying on from the middle forgets what came after.
+        recorder.seek(&mut world, 2);
+        recorder.resume();
+        assert!(!recorder.scrubbing());
+        assert_eq!(recorder.len(), 3);
+        world.step(1.0);
+        recorder.record(&world);
+        recorder.step(&mut world, 0);
+        assert_eq!(x(&world), 6.0);
+
+        let loaded = Recorder::<World>::load(&recorder.save(), 2).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.frames[1], recorder.frames[3]);
+        assert!(Recorder::<World>::load("nonsense", 2).is_err());
+    }
+
+    #[test]
+    fn test_circuit() {
+        let mut circuit = Circuit::new();
+        let clock = circuit.add_input();
+        let q = circuit.lfsr(&[3, 2], 0b1001, clock);
+        let order = circuit.update_order();
+        let bits = |circuit: &Circuit| q.iter().map(|q| circuit.get_1_in(*q)).collect::<Vec<_>>();
+        let tick = |circuit: &mut Circuit| {
+            for value in [true, false].iter() {
+                circuit.set_input(clock, *value);
+                for _ in 0..order.len() {
+                    circuit.update_signals_once(&order);
+                }
+            }
+        };
+        let mut recorder = Recorder::new(10);
+        let mut seen = vec![];
+        for _ in 0..4 {
+            recorder.record(&circuit);
+            seen.push(bits(&circuit));
+            tick(&mut circuit);
+        }
+        // rewinding and replaying from there shifts the same bits in again.
+        recorder.seek(&mut circuit, 1);
+        assert_eq!(bits(&circuit), seen[1]);
+        tick(&mut circuit);
+        assert_eq!(bits(&circuit), seen[2]);
+    }
+}
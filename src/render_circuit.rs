@@ -0,0 +1,594 @@
+//! Drawing circuits with nannou: gates with a distinct shape per type, wires colored by the value
+//! they carry, and node labels.
+//!
+//! Positions come from `layout` (or anywhere else), in whatever coordinates the caller likes;
+//! `map_pos` turns them into window coordinates.
+
+use crate::circuits::{Circuit, Gate, Value};
+use crate::viz::gradient::Gradient;
+use crate::viz::svg::VectorRecorder;
+use nannou::color::{hsl, rgba, IntoLinSrgba, LinSrgba, Rgba};
+use nannou::event::MouseScrollDelta;
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::Draw;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+/// How to draw a circuit.
+#[derive(Clone, Debug)]
+pub struct CircuitStyle {
+    /// Gates are drawn to fit in a square this many pixels across.
+    pub gate_size: f32,
+    pub wire_weight: f32,
+    /// Color of wires and gate outlines carrying a 1.
+    pub on: Rgba,
+    /// Color of wires and gate outlines carrying a 0.
+    pub off: Rgba,
+    pub gate_fill: Rgba,
+    /// Fill each gate by its rank, how many gates deep it is, instead of with `gate_fill`: from
+    /// this gradient at 0, for the inputs, to 1, for the deepest gate drawn.
+    pub rank_fill: Option<Gradient>,
+    /// Give each wire its own hue, lit up when it carries a 1, instead of using `on` and `off`.
+    pub rainbow: bool,
+    /// Draw the labels given to nodes with `Circuit::set_label`.
+    pub labels: bool,
+    pub label_color: Rgba,
+    pub font_size: u32,
+}
+
+impl Default for CircuitStyle {
+    fn default() -> Self {
+        CircuitStyle {
+            gate_size: 20.0,
+            wire_weight: 4.0,
+            on: rgba(1.0, 0.8, 0.0, 1.0),
+            off: rgba(0.1, 0.1, 0.1, 1.0),
+            gate_fill: rgba(0.4, 0.4, 0.4, 1.0),
+            rank_fill: None,
+            rainbow: false,
+            labels: true,
+            label_color: rgba(1.0, 1.0, 1.0, 1.0),
+            font_size: 12,
+        }
+    }
+}
+
+impl CircuitStyle {
+    /// This style with its sizes scaled by `zoom`, to match a `Camera`.
+    pub fn zoomed(&self, zoom: f32) -> CircuitStyle {
+        CircuitStyle {
+            gate_size: self.gate_size * zoom,
+            wire_weight: self.wire_weight * zoom,
+            font_size: ((self.font_size as f32 * zoom).round() as u32).max(1),
+            ..self.clone()
+        }
+    }
+}
+
+/// Draw the parts of `circuit` which have positions. Wires with a route (from `layout::route`)
+/// are drawn along it, and others as straight lines.
+pub fn draw_circuit<F>(
+    draw: &Draw,
+    circuit: &Circuit,
+    positions: &HashMap<NodeIndex, Vector2>,
+    routes: &HashMap<EdgeIndex, Vec<Vector2>>,
+    map_pos: F,
+    style: &CircuitStyle,
+) where
+    F: Fn(Vector2) -> Vector2,
+{
+    let svg = VectorRecorder::new("circuit");
+    draw_circuit_inner(draw, &svg, circuit, positions, routes, map_pos, style, None)
+}
+
+/// Like `draw_circuit`, drawing through `svg` so the circuit can be exported as SVG.
+pub fn draw_circuit_recorded<F>(
+    draw: &Draw,
+    svg: &VectorRecorder,
+    circuit: &Circuit,
+    positions: &HashMap<NodeIndex, Vector2>,
+    routes: &HashMap<EdgeIndex, Vec<Vector2>>,
+    map_pos: F,
+    style: &CircuitStyle,
+) where
+    F: Fn(Vector2) -> Vector2,
+{
+    draw_circuit_inner(draw, svg, circuit, positions, routes, map_pos, style, None)
+}
+
+/// Like `draw_circuit`, but wires which changed value recently are drawn with the new value
+/// travelling along them as a pulse, from source to target.
+pub fn draw_circuit_pulses<F>(
+    draw: &Draw,
+    circuit: &Circuit,
+    positions: &HashMap<NodeIndex, Vector2>,
+    routes: &HashMap<EdgeIndex, Vec<Vector2>>,
+    map_pos: F,
+    style: &CircuitStyle,
+    pulses: PulsesAt,
+) where
+    F: Fn(Vector2) -> Vector2,
+{
+    let svg = VectorRecorder::new("circuit");
+    draw_circuit_inner(
+        draw,
+        &svg,
+        circuit,
+        positions,
+        routes,
+        map_pos,
+        style,
+        Some(pulses),
+    )
+}
+
+/// Find the node drawn nearest to `point`, in window coordinates, if there's one within `radius`.
+pub fn node_at<F>(
+    positions: &HashMap<NodeIndex, Vector2>,
+    map_pos: F,
+    point: Vector2,
+    radius: f32,
+) -> Option<NodeIndex>
+where
+    F: Fn(Vector2) -> Vector2,
+{
+    positions
+        .iter()
+        .map(|(node, position)| (*node, (map_pos(*position) - point).magnitude()))
+        .filter(|(_, distance)| *distance < radius)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(node, _)| node)
+}
+
+/// Draw a box of text by the window point `at`, such as the mouse, keeping it inside `win`.
+/// Draw it last, so it covers everything else.
+pub fn draw_tooltip(draw: &Draw, win: Rect, at: Vector2, text: &str) {
+    // nannou can't measure text up front, so guess from the character count.
+    let lines = text.lines().count().max(1);
+    let chars = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let w = chars as f32 * TOOLTIP_CHAR_WIDTH + TOOLTIP_PADDING * 2.0;
+    let h = lines as f32 * TOOLTIP_LINE_HEIGHT + TOOLTIP_PADDING * 2.0;
+
+    // below and to the right of the point, unless that falls off the window.
+    let mut x = at.x + TOOLTIP_OFFSET + w / 2.0;
+    if x + w / 2.0 > win.right() {
+        x = at.x - TOOLTIP_OFFSET - w / 2.0;
+    }
+    let mut y = at.y - TOOLTIP_OFFSET - h / 2.0;
+    if y - h / 2.0 < win.bottom() {
+        y = at.y + TOOLTIP_OFFSET + h / 2.0;
+    }
+    let rect = Rect::from_x_y_w_h(x, y, w, h);
+
+    draw.rect()
+        .xy(rect.xy())
+        .wh(rect.wh())
+        .color(rgba(0.08, 0.08, 0.1, 0.9))
+        .stroke(rgba(0.6, 0.6, 0.65, 1.0))
+        .stroke_weight(1.0);
+    draw.text(text)
+        .xy(rect.xy())
+        .wh(rect.pad(TOOLTIP_PADDING).wh())
+        .font_size(TOOLTIP_FONT_SIZE)
+        .left_justify()
+        .align_text_top()
+        .color(rgba(1.0, 1.0, 1.0, 1.0));
+}
+
+const TOOLTIP_FONT_SIZE: u32 = 12;
+const TOOLTIP_CHAR_WIDTH: f32 = 7.0;
+const TOOLTIP_LINE_HEIGHT: f32 = 16.0;
+const TOOLTIP_PADDING: f32 = 6.0;
+/// Distance from the point to the nearest corner of the tooltip.
+const TOOLTIP_OFFSET: f32 = 12.0;
+
+/// A view onto a circuit that can be panned and zoomed.
+///
+/// Layout coordinates are first fitted to a rectangle of the window, so that the unit square fills
+/// it, then scaled by `zoom` about the window's center, then moved by `pan` pixels.
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    pub pan: Vector2,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            pan: vec2(0.0, 0.0),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    /// Mapping from layout coordinates to window coordinates.
+    pub fn map_pos(self, fit: Rect) -> impl Fn(Vector2) -> Vector2 {
+        let bl = fit.bottom_left();
+        move |p: Vector2| (bl + vec2(fit.w() * p.x, fit.h() * p.y)) * self.zoom + self.pan
+    }
+
+    /// Mapping from window coordinates back to layout coordinates.
+    pub fn unmap_pos(self, fit: Rect) -> impl Fn(Vector2) -> Vector2 {
+        let bl = fit.bottom_left();
+        move |p: Vector2| {
+            let q = (p - self.pan) / self.zoom - bl;
+            vec2(q.x / fit.w(), q.y / fit.h())
+        }
+    }
+
+    /// Zoom in by `factor`, keeping the window point `about` still.
+    pub fn zoom_about(&mut self, factor: f32, about: Vector2) {
+        self.pan = about - (about - self.pan) * factor;
+        self.zoom *= factor;
+    }
+
+    /// Zoom in or out in response to the mouse wheel, about the mouse.
+    pub fn scroll(&mut self, delta: MouseScrollDelta, mouse: Vector2) {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+        };
+        self.zoom_about(ZOOM_PER_LINE.powf(lines), mouse);
+    }
+}
+
+/// How much one line of mouse wheel scrolling zooms.
+const ZOOM_PER_LINE: f32 = 1.1;
+/// Trackpads scroll by pixels; count this many as one line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+// `svg` only records if it's been asked to export; `draw_circuit` passes one which never is.
+#[allow(clippy::too_many_arguments)]
+fn draw_circuit_inner<F>(
+    draw: &Draw,
+    svg: &VectorRecorder,
+    circuit: &Circuit,
+    positions: &HashMap<NodeIndex, Vector2>,
+    routes: &HashMap<EdgeIndex, Vec<Vector2>>,
+    map_pos: F,
+    style: &CircuitStyle,
+    pulses: Option<PulsesAt>,
+) where
+    F: Fn(Vector2) -> Vector2,
+{
+    let edges = circuit.graph().edge_count() as f32;
+    let wire_color = |edge: EdgeIndex, value: Value| {
+        if style.rainbow {
+            let hue = edge.index() as f32 / edges;
+            let lightness = if value { 0.7 } else { 0.1 };
+            hsl(hue, 1.0, lightness).into_lin_srgba()
+        } else {
+            value_color(style, value)
+        }
+    };
+    let polyline = |points: &[Vector2], color: LinSrgba| {
+        let points = points.iter().map(|p| map_pos(*p)).collect::<Vec<_>>();
+        svg.polyline(draw, &points, style.wire_weight, color);
+    };
+
+    for edge in circuit.graph().edge_references() {
+        let (source, target) = match (positions.get(&edge.source()), positions.get(&edge.target()))
+        {
+            (Some(source), Some(target)) => (*source, *target),
+            _ => continue,
+        };
+        if edge.source() == Circuit::meta_input() {
+            continue;
+        }
+        let points = match routes.get(&edge.id()) {
+            Some(route) => route.clone(),
+            None => vec![source, target],
+        };
+        let value = *edge.weight();
+
+        match pulses.and_then(|p| p.progress(edge.id())) {
+            Some((t, old_value)) => {
+                let (done, to_go) = split_path(&points, t);
+                polyline(&to_go, wire_color(edge.id(), old_value));
+                polyline(&done, wire_color(edge.id(), value));
+                let size = style.wire_weight * 2.5;
+                svg.ellipse(
+                    draw,
+                    map_pos(*done.last().unwrap()),
+                    vec2(size, size),
+                    wire_color(edge.id(), value),
+                );
+            }
+            None => polyline(&points, wire_color(edge.id(), value)),
+        }
+    }
+
+    // the meta input is rank 0, so the inputs are rank 1.
+    let ranks = style.rank_fill.as_ref().map(|_| circuit.ranks());
+    let deepest = match &ranks {
+        Some(ranks) => positions.keys().map(|n| ranks[n]).max().unwrap_or(0),
+        None => 0,
+    };
+    for (node, position) in positions {
+        let gate = circuit.graph()[*node];
+        if gate == Gate::MetaInput {
+            continue;
+        }
+        let pos = map_pos(*position);
+        let shape = gate_shape(gate, style.gate_size);
+        let outline = value_color(style, node_value(circuit, *node));
+
+        let fill: LinSrgba = match (&style.rank_fill, &ranks) {
+            (Some(gradient), Some(ranks)) => {
+                let t =
+                    ranks[node].saturating_sub(1) as f32 / deepest.saturating_sub(1).max(1) as f32;
+                gradient.sample(t).into_lin_srgba()
+            }
+            _ => style.gate_fill.into_lin_srgba(),
+        };
+        let body = shape.body.iter().map(|p| pos + *p).collect::<Vec<_>>();
+        svg.polygon(draw, &body, fill, Some((outline, 2.0)));
+        for line in &shape.lines {
+            let line = line.iter().map(|p| pos + *p).collect::<Vec<_>>();
+            svg.polyline(draw, &line, 2.0, outline);
+        }
+
+        if !style.labels {
+            continue;
+        }
+        if let Some(label) = circuit.label(*node) {
+            let offset = style.gate_size / 2.0 + style.font_size as f32;
+            let offset = match gate {
+                Gate::Input => vec2(-offset, 0.0),
+                Gate::Output => vec2(offset, 0.0),
+                _ => vec2(0.0, offset),
+            };
+            svg.text(
+                draw,
+                label,
+                pos + offset,
+                style.font_size,
+                style.label_color,
+            );
+        }
+    }
+}
+
+/// Split a polyline at fraction `t` of the way along it. Returns the points before the split and
+/// after it; both include the split point.
+fn split_path(points: &[Vector2], t: f32) -> (Vec<Vector2>, Vec<Vector2>) {
+    let lengths = points
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).magnitude())
+        .collect::<Vec<_>>();
+    let mut remaining = lengths.iter().sum::<f32>() * t.max(0.0).min(1.0);
+
+    let mut before = vec![points[0]];
+    for (i, length) in lengths.iter().enumerate() {
+        if remaining <= *length {
+            let split = if *length > 0.0 {
+                points[i] + (points[i + 1] - points[i]) * (remaining / length)
+            } else {
+                points[i]
+            };
+            before.push(split);
+            let mut after = vec![split];
+            after.extend(&points[i + 1..]);
+            return (before, after);
+        }
+        remaining -= length;
+        before.push(points[i + 1]);
+    }
+    (before, vec![*points.last().unwrap()])
+}
+
+/// How fast pulses travel along wires.
+#[derive(Copy, Clone, Debug)]
+pub enum PulseSpeed {
+    /// Every wire takes this many seconds. When the circuit is stepped with
+    /// `update_signals_once` at this interval, pulses arrive just as their target gate updates.
+    PerWire(f32),
+    /// Wires take this many seconds per rank they span. With a layered layout, pulses all move
+    /// at the same speed across the screen.
+    PerRank(f32),
+}
+
+/// Tracks which wires changed value when, so changes can be drawn travelling along them.
+pub struct Pulses {
+    speed: PulseSpeed,
+    ranks: HashMap<NodeIndex, u32>,
+    /// The value of each wire when last observed.
+    values: Vec<Value>,
+    /// Wires with a pulse in flight: (time it started, how long it takes, previous value).
+    active: HashMap<EdgeIndex, (f32, f32, Value)>,
+}
+
+impl Pulses {
+    pub fn new(circuit: &Circuit, speed: PulseSpeed) -> Pulses {
+        let mut result = Pulses {
+            speed,
+            ranks: HashMap::new(),
+            values: vec![],
+            active: HashMap::new(),
+        };
+        result.observe(circuit, 0.0);
+        result.active.clear();
+        result
+    }
+
+    /// Start pulses along every wire whose value changed since the last call. Call after each
+    /// simulation step, with the current time in seconds.
+    pub fn observe(&mut self, circuit: &Circuit, now: f32) {
+        // the circuit may have been edited since last time.
+        if self.values.len() != circuit.graph().edge_count() {
+            self.values = circuit.graph().raw_edges().iter().map(|e| e.weight).collect();
+            self.active.clear();
+            if let PulseSpeed::PerRank(_) = self.speed {
+                self.ranks = circuit.ranks();
+            }
+            return;
+        }
+
+        self.active
+            .retain(|_, (start, duration, _)| now - *start < *duration);
+        for (i, edge) in circuit.graph().raw_edges().iter().enumerate() {
+            if edge.weight != self.values[i] {
+                let duration = self.duration(circuit, EdgeIndex::new(i));
+                self.active
+                    .insert(EdgeIndex::new(i), (now, duration, self.values[i]));
+                self.values[i] = edge.weight;
+            }
+        }
+    }
+
+    /// Look at the pulses at time `now`, for drawing.
+    pub fn at(&self, now: f32) -> PulsesAt {
+        PulsesAt { pulses: self, now }
+    }
+
+    fn duration(&self, circuit: &Circuit, edge: EdgeIndex) -> f32 {
+        match self.speed {
+            PulseSpeed::PerWire(seconds) => seconds,
+            PulseSpeed::PerRank(seconds) => {
+                let (source, target) = circuit.graph().edge_endpoints(edge).unwrap();
+                let span = self.ranks[&target]
+                    .saturating_sub(self.ranks[&source])
+                    .max(1);
+                seconds * span as f32
+            }
+        }
+    }
+}
+
+/// `Pulses` at some moment; see `Pulses::at`.
+#[derive(Copy, Clone)]
+pub struct PulsesAt<'a> {
+    pulses: &'a Pulses,
+    now: f32,
+}
+
+impl<'a> PulsesAt<'a> {
+    /// How far along `edge` its pulse is, from 0 to 1, and the value the wire had before it.
+    fn progress(&self, edge: EdgeIndex) -> Option<(f32, Value)> {
+        let (start, duration, old_value) = self.pulses.active.get(&edge)?;
+        let t = (self.now - start) / duration;
+        if t < 1.0 {
+            Some((t.max(0.0), *old_value))
+        } else {
+            None
+        }
+    }
+}
+
+fn value_color(style: &CircuitStyle, value: Value) -> LinSrgba {
+    if value {
+        style.on.into_lin_srgba()
+    } else {
+        style.off.into_lin_srgba()
+    }
+}
+
+/// The value a node is putting out.
+fn node_value(circuit: &Circuit, node: NodeIndex) -> Value {
+    match circuit.graph()[node] {
+        Gate::Input | Gate::Output | Gate::FlipFlop => circuit.get_1_in(node),
+        Gate::FlipFlopIn { .. } | Gate::MetaInput => false,
+        // gates which don't drive anything yet are drawn as off.
+        _ => circuit.get_out(node).unwrap_or(false),
+    }
+}
+
+/// The outline of a gate, centered on the origin and pointing right.
+struct GateShape {
+    /// A closed, filled outline.
+    body: Vec<Vector2>,
+    /// Extra open lines, e.g. the second curve on the back of an Xor.
+    lines: Vec<Vec<Vector2>>,
+}
+
+const ARC_POINTS: usize = 8;
+
+/// Points along an ellipse around `center` with radii `r`, from angle `from` to `to`.
+fn arc(center: Vector2, r: Vector2, from: f32, to: f32) -> impl Iterator<Item = Vector2> {
+    (0..=ARC_POINTS).map(move |i| {
+        let a = from + (to - from) * i as f32 / ARC_POINTS as f32;
+        center + vec2(r.x * a.cos(), r.y * a.sin())
+    })
+}
+
+fn gate_shape(gate: Gate, size: f32) -> GateShape {
+    let s = size / 2.0;
+    let square = vec![vec2(-s, -s), vec2(s, -s), vec2(s, s), vec2(-s, s)];
+    // the concave back of an Or.
+    let back = |dx: f32| arc(vec2(-s + dx, 0.0), vec2(s * 0.4, s), PI / 2.0, -PI / 2.0);
+
+    match gate {
+        Gate::Input | Gate::MetaInput => GateShape {
+            body: square,
+            lines: vec![],
+        },
+        Gate::Output => GateShape {
+            body: vec![
+                vec2(-s, -s),
+                vec2(s * 0.3, -s),
+                vec2(s, 0.0),
+                vec2(s * 0.3, s),
+                vec2(-s, s),
+            ],
+            lines: vec![],
+        },
+        Gate::And => {
+            let mut body = vec![vec2(-s, s), vec2(-s, -s)];
+            body.extend(arc(vec2(0.0, 0.0), vec2(s, s), -PI / 2.0, PI / 2.0));
+            GateShape {
+                body,
+                lines: vec![],
+            }
+        }
+        Gate::Or | Gate::Xor => {
+            // an Xor is an Or with an extra curve behind it, so shift it forwards a bit.
+            let dx = if gate == Gate::Xor { s * 0.3 } else { 0.0 };
+            let mut body = arc(
+                vec2(-s + dx, 0.0),
+                vec2(2.0 * s - dx, s),
+                -PI / 2.0,
+                PI / 2.0,
+            )
+            .collect::<Vec<_>>();
+            // skip the ends of the back, which are already on the front.
+            body.extend(back(dx).skip(1).take(ARC_POINTS - 1));
+            let lines = if gate == Gate::Xor {
+                vec![back(0.0).collect()]
+            } else {
+                vec![]
+            };
+            GateShape { body, lines }
+        }
+        Gate::Not => GateShape {
+            body: vec![vec2(-s, -s), vec2(s * 0.5, 0.0), vec2(-s, s)],
+            lines: vec![
+                arc(vec2(s * 0.75, 0.0), vec2(s * 0.25, s * 0.25), 0.0, 2.0 * PI).collect(),
+            ],
+        },
+        Gate::FlipFlop => GateShape {
+            body: vec![
+                vec2(-s * 0.7, -s),
+                vec2(s * 0.7, -s),
+                vec2(s * 0.7, s),
+                vec2(-s * 0.7, s),
+            ],
+            // the clock input marker.
+            lines: vec![vec![
+                vec2(-s * 0.7, -s * 0.6),
+                vec2(-s * 0.3, -s * 0.4),
+                vec2(-s * 0.7, -s * 0.2),
+            ]],
+        },
+        Gate::FlipFlopIn { .. } => GateShape {
+            body: vec![
+                vec2(-s * 0.5, -s * 0.5),
+                vec2(s * 0.5, 0.0),
+                vec2(-s * 0.5, s * 0.5),
+            ],
+            lines: vec![],
+        },
+    }
+}
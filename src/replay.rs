@@ -0,0 +1,411 @@
+//! Recording what's done to a sketch, the mouse, keys and touches and when, to play it back
+//! exactly; for demos of interactive pieces which come out the same every time.
+//!
+//! While recording, the sketch's window events pass through `input` on their way to its handler,
+//! and its clock moves on by each update's real time, which is recorded too. Played back, the
+//! window's own input is ignored and the recorded events are handed back from `update` instead, at
+//! the start of the update they came before, with the clock moving on by as long as that update
+//! took while recording, however long it really takes. So whatever the sketch works out from the
+//! clock, like stepping a simulation every so often, happens between the same inputs as it did.
+//! Either way the sketch takes the time, the mouse and the keys held down from the `Replay`, as
+//! the `App` only knows the real ones:
+//!
+//! ```ignore
+//! // on window events:
+//! if let Some(event) = model.replay.input(event) {
+//!     handle(app, model, event);
+//! }
+//! // every update:
+//! for event in model.replay.update(upd.since_last.as_secs_f32()) {
+//!     handle(app, model, event);
+//! }
+//! let (t, dt) = (model.replay.time(), model.replay.dt());
+//! // on exit, to save what's been recorded:
+//! model.replay.finish();
+//! ```
+//!
+//! Recordings are RON, and `Replay::from_args` records to `--record FILE` and plays back
+//! `--replay FILE`. Game controllers, MIDI and OSC aren't recorded.
+
+use crate::cli::Args;
+use nannou::event::{Key, MouseButton, MouseScrollDelta, TouchEvent, TouchPhase, WindowEvent};
+use nannou::geom::{vec2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The input recorded from window events.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Input {
+    MouseMoved(Vector2),
+    MousePressed(MouseButton),
+    MouseReleased(MouseButton),
+    MouseWheel(MouseScrollDelta, TouchPhase),
+    KeyPressed(Key),
+    KeyReleased(Key),
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        position: Vector2,
+    },
+}
+
+impl Input {
+    /// The input `event` is, if it's any.
+    pub fn from_event(event: &WindowEvent) -> Option<Input> {
+        Some(match *event {
+            WindowEvent::MouseMoved(pos) => Input::MouseMoved(pos),
+            WindowEvent::MousePressed(button) => Input::MousePressed(button),
+            WindowEvent::MouseReleased(button) => Input::MouseReleased(button),
+            WindowEvent::MouseWheel(delta, phase) => Input::MouseWheel(delta, phase),
+            WindowEvent::KeyPressed(key) => Input::KeyPressed(key),
+            WindowEvent::KeyReleased(key) => Input::KeyReleased(key),
+            WindowEvent::Touch(touch) => Input::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                position: touch.position,
+            },
+            _ => return None,
+        })
+    }
+
+    /// The window event it came from.
+    pub fn to_event(&self) -> WindowEvent {
+        match *self {
+            Input::MouseMoved(pos) => WindowEvent::MouseMoved(pos),
+            Input::MousePressed(button) => WindowEvent::MousePressed(button),
+            Input::MouseReleased(button) => WindowEvent::MouseReleased(button),
+            Input::MouseWheel(delta, phase) => WindowEvent::MouseWheel(delta, phase),
+            Input::KeyPressed(key) => WindowEvent::KeyPressed(key),
+            Input::KeyReleased(key) => WindowEvent::KeyReleased(key),
+            Input::Touch {
+                id,
+                phase,
+                position,
+            } => WindowEvent::Touch(TouchEvent {
+                id,
+                phase,
+                position,
+            }),
+        }
+    }
+}
+
+// an input, and how many updates had gone by when it came.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct Timed {
+    update: usize,
+    input: Input,
+}
+
+// what's saved: how long each update was, and the input between them, oldest first.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+struct Recording {
+    steps: Vec<f32>,
+    inputs: Vec<Timed>,
+}
+
+enum Mode {
+    Live,
+    // saved to the path on `finish`.
+    Recording(PathBuf),
+    Playing,
+}
+
+pub struct Replay {
+    mode: Mode,
+    // what's been recorded, or is being played back.
+    recording: Recording,
+    // the next input to play back.
+    next: usize,
+    // how many updates have gone by.
+    updates: usize,
+    time: f32,
+    dt: f32,
+    mouse: Vector2,
+    keys: HashSet<Key>,
+}
+
+impl Replay {
+    /// Input straight from the window, neither recorded nor played back.
+    pub fn live() -> Replay {
+        Replay {
+            mode: Mode::Live,
+            recording: Recording::default(),
+            next: 0,
+            updates: 0,
+            time: 0.0,
+            dt: 0.0,
+            mouse: vec2(0.0, 0.0),
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Record input, to save to `path` on `finish`.
+    pub fn record<P: Into<PathBuf>>(path: P) -> Replay {
+        Replay {
+            mode: Mode::Recording(path.into()),
+            ..Replay::live()
+        }
+    }
+
+    /// Play back a recording, saved by `save`.
+    pub fn load(source: &str) -> Result<Replay, String> {
+        let recording = ron::de::from_str(source).map_err(|e| e.to_string())?;
+        Ok(Replay {
+            mode: Mode::Playing,
+            recording,
+            ..Replay::live()
+        })
+    }
+
+    /// Play back `--replay`, or record to `--record`, or neither.
+    pub fn from_args(args: &Args) -> Replay {
+        if let Some(path) = args.replay() {
+            let source = fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e));
+            return Replay::load(&source)
+                .unwrap_or_else(|e| panic!("can't play back {}: {}", path.display(), e));
+        }
+        match args.record() {
+            Some(path) => Replay::record(path),
+            None => Replay::live(),
+        }
+    }
+
+    pub fn recording(&self) -> bool {
+        matches!(self.mode, Mode::Recording(_))
+    }
+
+    pub fn playing(&self) -> bool {
+        matches!(self.mode, Mode::Playing)
+    }
+
+    /// Whether everything recorded has been played back.
+    pub fn done(&self) -> bool {
+        self.playing()
+            && self.next == self.recording.inputs.len()
+            && self.updates >= self.recording.steps.len()
+    }
+
+    /// Pass on an event from the window, recording it if recording. While playing back, the
+    /// mouse, keys and touches are swallowed, as the recorded ones take their place.
+    pub fn input(&mut self, event: WindowEvent) -> Option<WindowEvent> {
+        let input = match Input::from_event(&event) {
+            Some(input) => input,
+            None => return Some(event),
+        };
+        match self.mode {
+            Mode::Live => (),
+            Mode::Recording(_) => self.recording.inputs.push(Timed {
+                update: self.updates,
+                input: input.clone(),
+            }),
+            Mode::Playing => return None,
+        }
+        self.track(&input);
+        Some(event)
+    }
+
+    /// Move the clock on by `dt` seconds or, playing back, by as long as this update was while
+    /// recording. Returns the recorded events which came before it, to handle as though they'd
+    /// just come from the window, before anything else the update does.
+    pub fn update(&mut self, dt: f32) -> Vec<WindowEvent> {
+        let mut events = vec![];
+        match self.mode {
+            Mode::Live => self.dt = dt,
+            Mode::Recording(_) => {
+                self.recording.steps.push(dt);
+                self.dt = dt;
+            }
+            Mode::Playing => {
+                // the events which came since the last update, as they did while recording.
+                while let Some(timed) = self.recording.inputs.get(self.next) {
+                    if timed.update > self.updates {
+                        break;
+                    }
+                    let input = timed.input.clone();
+                    self.next += 1;
+                    self.track(&input);
+                    events.push(input.to_event());
+                }
+                // once the recording's run out, the sketch carries on in real time.
+                self.dt = self.recording.steps.get(self.updates).copied().unwrap_or(dt);
+            }
+        }
+        self.updates += 1;
+        self.time += self.dt;
+        events
+    }
+
+    /// Seconds since the sketch started, counted in updates.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// How long the last update was.
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Where the mouse is.
+    pub fn mouse(&self) -> Vector2 {
+        self.mouse
+    }
+
+    /// The keys held down.
+    pub fn keys(&self) -> &HashSet<Key> {
+        &self.keys
+    }
+
+    /// What's been recorded, as RON.
+    pub fn save(&self) -> String {
+        ron::ser::to_string(&self.recording).unwrap()
+    }
+
+    /// Save the recording, if recording. Returns where.
+    pub fn finish(&self) -> Option<&Path> {
+        let path = match &self.mode {
+            Mode::Recording(path) => path,
+            _ => return None,
+        };
+        fs::write(path, self.save())
+            .unwrap_or_else(|e| panic!("can't save {}: {}", path.display(), e));
+        println!("saved {}", path.display());
+        Some(path)
+    }
+
+    fn track(&mut self, input: &Input) {
+        match *input {
+            Input::MouseMoved(pos) => self.mouse = pos,
+            Input::KeyPressed(key) => {
+                self.keys.insert(key);
+            }
+            Input::KeyReleased(key) => {
+                self.keys.remove(&key);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let mut recording = Replay::record("unused.ron");
+        let events = [
+            WindowEvent::MouseMoved(vec2(10.0, 20.0)),
+            WindowEvent::MousePressed(MouseButton::Left),
+            WindowEvent::KeyPressed(Key::Space),
+        ];
+        // updates of uneven lengths, with input between them, and something that isn't input.
+        recording.update(0.25);
+        assert_eq!(recording.input(events[0].clone()), Some(events[0].clone()));
+        recording.input(events[1].clone());
+        recording.update(0.5);
+        let resized = WindowEvent::Resized(vec2(100.0, 100.0));
+        assert_eq!(recording.input(resized.clone()), Some(resized.clone()));
+        recording.input(events[2].clone());
+        assert_eq!(recording.mouse(), vec2(10.0, 20.0));
+        assert!(recording.keys().contains(&Key::Space));
+        assert_eq!(recording.recording.inputs.len(), 3);
+
+        let mut playing = Replay::load(&recording.save()).unwrap();
+        // the window's own input's ignored, but not the rest.
+        assert_eq!(playing.input(events[2].clone()), None);
+        assert_eq!(playing.input(resized.clone()), Some(resized));
+        assert!(playing.keys().is_empty());
+        // each update's as long as it was, however long it really is, and the mouse came before
+        // the second.
+        assert!(playing.update(1.0).is_empty());
+        assert_eq!(playing.time(), 0.25);
+        assert_eq!(playing.update(1.0), events[..2].to_vec());
+        assert_eq!(playing.mouse(), vec2(10.0, 20.0));
+        assert_eq!((playing.dt(), playing.time()), (0.5, 0.75));
+        // the key came after the last, and past the end the clock keeps real time.
+        assert!(!playing.done());
+        assert_eq!(playing.update(1.0), events[2..].to_vec());
+        assert!(playing.keys().contains(&Key::Space));
+        assert_eq!((playing.dt(), playing.time()), (1.0, 1.75));
+        assert!(playing.done());
+
+        assert!(Replay::load("nonsense").is_err());
+        assert!(Replay::live().finish().is_none());
+    }
+
+    // a sketch stepping a simulation every tenth of a second, which counts the clicks so far at
+    // each step.
+    struct Counter {
+        replay: Replay,
+        clicks: u32,
+        steps: Vec<u32>,
+    }
+
+    impl Counter {
+        fn event(&mut self, event: WindowEvent) {
+            if let Some(WindowEvent::MousePressed(_)) = self.replay.input(event) {
+                self.clicks += 1;
+            }
+        }
+
+        fn update(&mut self, dt: f32) {
+            for event in self.replay.update(dt) {
+                if let WindowEvent::MousePressed(_) = event {
+                    self.clicks += 1;
+                }
+            }
+            let (t, dt) = (self.replay.time(), self.replay.dt());
+            if (t / 0.1).floor() > ((t - dt) / 0.1).floor() {
+                self.steps.push(self.clicks);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut recorded = Counter {
+            replay: Replay::record("unused.ron"),
+            clicks: 0,
+            steps: vec![],
+        };
+        // uneven updates, clicked between some of them, each click just before or after a step.
+        let click = WindowEvent::MousePressed(MouseButton::Left);
+        let dts = [0.03, 0.09, 0.02, 0.07, 0.11, 0.05, 0.04, 0.13, 0.01, 0.06];
+        for (i, dt) in dts.iter().enumerate() {
+            if i % 3 != 1 {
+                recorded.event(click.clone());
+            }
+            recorded.update(*dt);
+        }
+
+        // played back at a steady 60 updates a second, with the window clicked all the while.
+        let mut played = Counter {
+            replay: Replay::load(&recorded.replay.save()).unwrap(),
+            clicks: 0,
+            steps: vec![],
+        };
+        while !played.replay.done() {
+            played.event(click.clone());
+            played.update(1.0 / 60.0);
+        }
+        assert_eq!(played.clicks, recorded.clicks);
+        assert_eq!(played.steps, recorded.steps);
+        assert_eq!(played.replay.time(), recorded.replay.time());
+    }
+
+    #[test]
+    fn test_input() {
+        let touch = WindowEvent::Touch(TouchEvent {
+            id: 3,
+            phase: TouchPhase::Started,
+            position: vec2(1.0, 2.0),
+        });
+        let input = Input::from_event(&touch).unwrap();
+        assert_eq!(input.to_event(), touch);
+        assert_eq!(Input::from_event(&WindowEvent::MouseEntered), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! All of a sketch's randomness from one seed, so a composition worth keeping can be made again
+//! exactly, say to render it.
+//!
+//! The seed comes from `--seed`, or the `SKETCH_SEED` environment variable, or else is picked
+//! afresh, and is printed either way. Each use of randomness then forks its own named stream from
+//! it, so drawing a few more numbers for one thing doesn't reshuffle everything after it:
+//!
+//! ```ignore
+//! let seeds = Seeds::from_args(&args);
+//! let mut balls = seeds.fork("balls");
+//! let mut springs = seeds.fork("springs");
+//! let dla = Dla::new(seeds.fork_seed("dla"));
+//! ```
+
+use crate::cli::Args;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use std::env;
+
+/// The environment variable a seed's read from when `--seed` isn't given.
+pub const SEED_VAR: &str = "SKETCH_SEED";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Seeds {
+    seed: u64,
+}
+
+impl Seeds {
+    pub fn new(seed: u64) -> Seeds {
+        Seeds { seed }
+    }
+
+    /// The seed from `--seed`, or `SKETCH_SEED`, or a new one, printed so it can be given again.
+    pub fn from_args(args: &Args) -> Seeds {
+        let seed = args.seed().or_else(|| {
+            let var = env::var(SEED_VAR).ok()?;
+            match var.trim().parse() {
+                Ok(seed) => Some(seed),
+                Err(_) => panic!("{} should be a whole number, not {:?}", SEED_VAR, var),
+            }
+        });
+        let seeds = Seeds::new(seed.unwrap_or_else(rand::random));
+        println!(
+            "seed {} (run with --seed {} for this again)",
+            seeds.seed, seeds.seed
+        );
+        seeds
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A seed of its own for `name`, for things that take one.
+    pub fn fork_seed(&self, name: &str) -> u64 {
+        // FNV-1a, which unlike std's hashers is sure to stay the same from one Rust to the next,
+        // then mixed with the seed by SplitMix64.
+        let name = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        let mut z = (self.seed ^ name).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A stream of random numbers of its own for `name`.
+    pub fn fork(&self, name: &str) -> XorShiftRng {
+        XorShiftRng::seed_from_u64(self.fork_seed(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_fork() {
+        let draw = |mut rng: XorShiftRng| (0..4).map(|_| rng.gen::<u32>()).collect::<Vec<_>>();
+        let seeds = Seeds::new(12345);
+        assert_eq!(draw(seeds.fork("balls")), draw(seeds.fork("balls")));
+        assert_ne!(draw(seeds.fork("balls")), draw(seeds.fork("springs")));
+        assert_ne!(
+            draw(seeds.fork("balls")),
+            draw(Seeds::new(12346).fork("balls"))
+        );
+        // pinned, so seeds written down keep making the same things.
+        assert_eq!(seeds.fork_seed("balls"), 11_318_176_375_358_188_592);
+        assert_ne!(seeds.fork_seed(""), seeds.fork_seed("a"));
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args = crate::cli::Cli::new("test")
+            .parse_from(&["--seed", "7"])
+            .unwrap();
+        assert_eq!(Seeds::from_args(&args).seed(), 7);
+    }
+}
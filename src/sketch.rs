@@ -0,0 +1,216 @@
+//! The plumbing every sketch otherwise repeats: building the model, matching updates, keys and the
+//! mouse out of nannou's events, and getting a `Draw` onto the frame. A sketch implements `Sketch`
+//! and its `main` is just `run_sketch::<MySketch>()`.
+//!
+//! The sketch draws onto a `Canvas` of its own, which is drawn into the window with its overlay
+//! and the HUD over it, so they're never left behind on it. Every sketch run this way saves a
+//! screenshot of the canvas, without them, on F12. A sketch can ask for a second window too, for
+//! its controls, plots and readouts; the mouse in each window goes to its own handler, and keys
+//! pressed in either to `on_key`.
+//!
+//! F11 presents the sketch, say on a projector: the canvas goes fullscreen, without its overlay,
+//! HUD or the cursor. F3 shows the HUD, of how long frames take and what the sketch reports to it.
+
+use crate::capture::Screenshot;
+use crate::hud::Hud;
+use crate::viz::canvas::Canvas;
+use nannou::color::Srgb;
+use nannou::event::WindowEvent::{KeyPressed, MouseMoved, MousePressed, MouseReleased};
+use nannou::event::{Event, Key, MouseButton};
+use nannou::frame::Frame;
+use nannou::geom::{Rect, Vector2};
+use nannou::window;
+use nannou::{App, Draw};
+
+/// What the mouse did; where it is now is in `app.mouse`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Mouse {
+    Pressed(MouseButton),
+    Released(MouseButton),
+    Moved(Vector2),
+}
+
+pub trait Sketch: Sized + 'static {
+    /// The size of a second window to draw the sketch's controls in, if it wants one.
+    const CONTROLS: Option<(u32, u32)> = None;
+
+    /// Whether the cursor stays showing while presenting, for sketches worked with the mouse.
+    const PRESENTING_CURSOR: bool = false;
+
+    fn init(app: &App) -> Self;
+
+    /// Move on by `dt` seconds.
+    fn update(&mut self, _app: &App, _dt: f32) {}
+
+    /// Report counts, parameters and the like to the HUD, after each update.
+    fn hud(&self, _hud: &mut Hud) {}
+
+    /// Draw a frame of the canvas, which is `win`, after each update. Nothing's cleared between
+    /// frames unless this draws a background, so sketches can leave trails.
+    fn draw(&self, app: &App, draw: &Draw, win: Rect);
+
+    /// Draw help, readouts and the like over the canvas, which is `win`. They're drawn afresh
+    /// every frame, rather than onto the canvas, and left out of screenshots and while presenting.
+    fn draw_overlay(&self, _app: &App, _draw: &Draw, _win: Rect) {}
+
+    /// Draw the controls window, which is `win`; it's cleared to the `background` every frame.
+    fn draw_controls(&self, _app: &App, _draw: &Draw, _win: Rect) {}
+
+    fn on_mouse(&mut self, _app: &App, _mouse: Mouse) {}
+
+    /// The mouse did something in the controls window, which is `win`.
+    fn on_controls_mouse(&mut self, _app: &App, _win: Rect, _mouse: Mouse) {}
+
+    /// A key was pressed.
+    fn on_key(&mut self, _app: &App, _key: Key) {}
+
+    /// What the canvas is cleared to before the first frame.
+    fn background(&self) -> Srgb<u8> {
+        Srgb::new(255, 255, 255)
+    }
+}
+
+// a sketch, and what the harness keeps alongside it.
+struct Harness<S> {
+    sketch: S,
+    screenshot: Screenshot,
+    hud: Hud,
+    window: window::Id,
+    canvas: Canvas,
+    // the controls window, if the sketch has one.
+    controls: Option<window::Id>,
+    // whether the canvas is fullscreen, after F11.
+    presenting: bool,
+}
+
+/// Open a window, and one for the controls if it has them, and run `S` in it.
+pub fn run_sketch<S: Sketch>() {
+    nannou::app(init::<S>)
+        .event(event::<S>)
+        .exit(exit::<S>)
+        .run();
+}
+
+fn init<S: Sketch>(app: &App) -> Harness<S> {
+    let window = app.new_window().view(view::<S>).build().unwrap();
+    let controls = S::CONTROLS.map(|(width, height)| {
+        app.new_window()
+            .title("controls")
+            .size(width, height)
+            .resizable(false)
+            .view(view_controls::<S>)
+            .build()
+            .unwrap()
+    });
+    let sketch = S::init(app);
+    let canvas = Canvas::new(&app.window(window).unwrap(), sketch.background());
+    Harness {
+        sketch,
+        screenshot: Screenshot::new(),
+        hud: Hud::new(),
+        window,
+        canvas,
+        controls,
+        presenting: false,
+    }
+}
+
+fn event<S: Sketch>(app: &App, harness: &mut Harness<S>, event: Event) {
+    let sketch = &mut harness.sketch;
+    match event {
+        Event::Update(upd) => {
+            let dt = upd.since_last.as_secs_f32();
+            sketch.update(app, dt);
+            if let Some(window) = app.window(harness.window) {
+                let draw = Draw::new();
+                sketch.draw(app, &draw, window.rect());
+                harness.canvas.render(&window, &draw);
+                harness.hud.frame(window.rect(), dt);
+            }
+            sketch.hud(&mut harness.hud);
+        }
+        Event::WindowEvent {
+            id,
+            simple: Some(event),
+        } => {
+            let mouse = match event {
+                KeyPressed(key) => {
+                    if key == Key::F11 {
+                        harness.presenting = !harness.presenting;
+                        present(
+                            app,
+                            harness.window,
+                            harness.presenting,
+                            S::PRESENTING_CURSOR,
+                        );
+                    }
+                    if key == harness.screenshot.key {
+                        if let Some(window) = app.window(harness.window) {
+                            let path = harness.screenshot.path_now();
+                            println!("saving {}", path.display());
+                            harness.canvas.save(&window, path);
+                        }
+                    }
+                    harness.hud.key(key);
+                    sketch.on_key(app, key);
+                    return;
+                }
+                MousePressed(button) => Mouse::Pressed(button),
+                MouseReleased(button) => Mouse::Released(button),
+                MouseMoved(pos) => Mouse::Moved(pos),
+                _ => return,
+            };
+            match app.window(id) {
+                Some(window) if harness.controls == Some(id) => {
+                    sketch.on_controls_mouse(app, window.rect(), mouse)
+                }
+                _ => sketch.on_mouse(app, mouse),
+            }
+        }
+        _ => (),
+    }
+}
+
+fn exit<S: Sketch>(app: &App, harness: Harness<S>) {
+    if let Some(window) = app.window(harness.window) {
+        harness.canvas.finish(&window);
+    }
+}
+
+// put `canvas` fullscreen, hiding the cursor unless `cursor`, or back in its window.
+fn present(app: &App, canvas: window::Id, presenting: bool, cursor: bool) {
+    if let Some(window) = app.window(canvas) {
+        window.set_fullscreen(presenting);
+        window.set_cursor_visible(!presenting || cursor);
+    }
+}
+
+// the `Rect` of the window `frame`'s for, rather than the focused one `App::window_rect` gives.
+fn frame_rect(app: &App, frame: &Frame) -> Rect {
+    app.window(frame.window_id())
+        .expect("drawing a frame for a closed window")
+        .rect()
+}
+
+fn view<S: Sketch>(app: &App, harness: &Harness<S>, frame: Frame) {
+    let sketch = &harness.sketch;
+    frame.clear(sketch.background());
+    let draw = app.draw();
+    let win = frame_rect(app, &frame);
+    draw.texture(harness.canvas.texture()).wh(win.wh());
+    if !harness.presenting {
+        sketch.draw_overlay(app, &draw, win);
+        harness.hud.draw(&draw);
+    }
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
+
+fn view_controls<S: Sketch>(app: &App, harness: &Harness<S>, frame: Frame) {
+    let sketch = &harness.sketch;
+    frame.clear(sketch.background());
+    let draw = app.draw();
+    sketch.draw_controls(app, &draw, frame_rect(app, &frame));
+    draw.to_frame(app, &frame).unwrap();
+    frame.submit();
+}
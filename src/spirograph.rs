@@ -0,0 +1,143 @@
+//! Spirographs: a pen held in a toothed wheel, rolled around inside (or outside) a fixed ring,
+//! traces hypotrochoids (or epitrochoids). Here wheels can be nested too, each rolling round the
+//! one before it as that rolls round its own.
+//!
+//! A wheel rolls without slipping, so as its center goes round its parent by some angle, it turns
+//! by that angle times how much bigger the circle its center moves along is than itself: the
+//! wrong way round, inside its parent, and the right way round, outside. Everything is driven by
+//! one angle, `theta`, how far the first wheel has gone round the ring.
+
+use nannou::geom::{vec2, Vector2};
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Wheel {
+    pub radius: f32,
+    /// Whether it rolls round the inside of its parent, rather than the outside.
+    pub inside: bool,
+    /// How many times round its parent it goes for each time the first wheel goes round the ring.
+    pub speed: f32,
+}
+
+impl Wheel {
+    /// A wheel of `radius`, rolling round the inside of its parent at the same speed as the first.
+    pub fn new(radius: f32) -> Wheel {
+        Wheel {
+            radius,
+            inside: true,
+            speed: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Spirograph {
+    /// The radius of the fixed ring, centered on the origin.
+    pub ring: f32,
+    /// The wheels, each rolling round the one before it, and the first round the ring.
+    pub wheels: Vec<Wheel>,
+    /// How far the pen is from the center of the last wheel.
+    pub pen: f32,
+}
+
+impl Spirograph {
+    /// A single wheel of `radius`, rolling round inside a ring of `ring`, with its pen `pen` from
+    /// its center.
+    pub fn new(ring: f32, radius: f32, pen: f32) -> Spirograph {
+        Spirograph {
+            ring,
+            wheels: vec![Wheel::new(radius)],
+            pen,
+        }
+    }
+
+    /// Where each wheel's center is at `theta`, and which way it's turned (in radians, from
+    /// pointing at where it started out touching its parent), starting with the ring's.
+    pub fn wheels_at(&self, theta: f32) -> Vec<(Vector2, f32)> {
+        let mut wheels = Vec::with_capacity(self.wheels.len() + 1);
+        let (mut center, mut turned, mut radius) = (vec2(0.0, 0.0), 0.0, self.ring);
+        wheels.push((center, turned));
+        for wheel in &self.wheels {
+            let around = theta * wheel.speed;
+            let (distance, rolled, start) = if wheel.inside {
+                let distance = radius - wheel.radius;
+                (distance, -distance / wheel.radius * around, 0.0)
+            } else {
+                let distance = radius + wheel.radius;
+                // touching its parent on the side facing back towards it.
+                (distance, distance / wheel.radius * around, PI)
+            };
+            let angle = turned + around;
+            center += vec2(angle.cos(), angle.sin()) * distance;
+            turned = turned + rolled + start;
+            radius = wheel.radius;
+            wheels.push((center, turned));
+        }
+        wheels
+    }
+
+    /// Where the pen is at `theta`.
+    pub fn pen_at(&self, theta: f32) -> Vector2 {
+        let (center, turned) = *self.wheels_at(theta).last().unwrap();
+        center + vec2(turned.cos(), turned.sin()) * self.pen
+    }
+
+    /// `steps + 1` points along the curve, from `theta` 0 to `turns` times round.
+    pub fn curve(&self, turns: f32, steps: usize) -> Vec<Vector2> {
+        (0..=steps)
+            .map(|i| self.pen_at(2.0 * PI * turns * i as f32 / steps as f32))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hypocycloid() {
+        // a wheel a third the size of the ring, with its pen on its rim, draws a deltoid: three
+        // cusps, touching the ring, and closing up after going round once.
+        let spirograph = Spirograph::new(3.0, 1.0, 1.0);
+        assert!((spirograph.pen_at(0.0) - vec2(3.0, 0.0)).magnitude() < 1e-6);
+        let curve = spirograph.curve(1.0, 300);
+        assert!((curve[0] - curve[300]).magnitude() < 1e-4);
+        assert!(curve.iter().all(|p| p.magnitude() <= 3.0 + 1e-4));
+        let cusps = curve.iter().filter(|p| p.magnitude() > 3.0 - 1e-4).count();
+        assert_eq!(cusps, 4);
+        // a deltoid's narrowest at a third of the way out.
+        let narrowest = curve.iter().map(|p| p.magnitude()).fold(f32::MAX, f32::min);
+        assert!((narrowest - 1.0).abs() < 1e-3, "{}", narrowest);
+    }
+
+    #[test]
+    fn test_rolling() {
+        for inside in &[true, false] {
+            let mut spirograph = Spirograph::new(5.0, 2.0, 2.0);
+            spirograph.wheels[0].inside = *inside;
+            // the pen, on the rim, touches the ring to start with, and so stands still there.
+            assert!((spirograph.pen_at(0.0).magnitude() - 5.0).abs() < 1e-5);
+            let h = 1e-3;
+            let velocity = (spirograph.pen_at(h) - spirograph.pen_at(-h)) / (2.0 * h);
+            assert!(velocity.magnitude() < 1e-2, "{} {:?}", inside, velocity);
+            // whereas its center moves along steadily.
+            let center = |theta| spirograph.wheels_at(theta)[1].0;
+            let velocity = (center(h) - center(-h)) / (2.0 * h);
+            let distance = if *inside { 3.0 } else { 7.0 };
+            assert!((velocity.magnitude() - distance).abs() < 1e-2);
+        }
+
+        // a nested wheel keeps rolling round its parent, wherever that's got to.
+        let mut spirograph = Spirograph::new(10.0, 4.0, 1.0);
+        spirograph.wheels.push(Wheel {
+            radius: 1.0,
+            inside: false,
+            speed: 3.0,
+        });
+        for theta in &[0.0, 0.7, 2.0, 5.0] {
+            let wheels = spirograph.wheels_at(*theta);
+            assert!((wheels[1].0.magnitude() - 6.0).abs() < 1e-4);
+            assert!(((wheels[2].0 - wheels[1].0).magnitude() - 5.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Keyframe animation: tracks of values, each eased from one keyframe to the next, played back by
+//! a `Timeline` that can loop, pause, or be scrubbed to any moment.
+//!
+//! A track holds anything that can be blended by adding and scaling, like numbers, vectors and
+//! colors in a linear space, `LinSrgb` or, for perceptual blends, `Lab` (palette only does
+//! arithmetic on those, not gamma-encoded `Srgb`). Write a
+//! choreography out as tracks up front, then each frame ask them where they are at the timeline's
+//! time.
+
+use crate::ease::{map_eased, Ease};
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe<T> {
+    /// Seconds into the timeline.
+    pub time: f32,
+    pub value: T,
+    /// How the track eases from the keyframe before to this one.
+    pub ease: Ease,
+}
+
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    // in order of time, starting at 0.
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T> Track<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    /// A track starting at `value`.
+    pub fn new(value: T) -> Track<T> {
+        Track {
+            keys: vec![Keyframe {
+                time: 0.0,
+                value,
+                ease: Ease::LINEAR,
+            }],
+        }
+    }
+
+    /// This track, going on to reach `value` at `time`, eased with `ease` from the last keyframe.
+    pub fn to(mut self, time: f32, value: T, ease: Ease) -> Track<T> {
+        assert!(
+            time >= self.duration(),
+            "keyframes have to be added in order of time"
+        );
+        self.keys.push(Keyframe { time, value, ease });
+        self
+    }
+
+    /// This track, staying where it is for `seconds` more.
+    pub fn hold(self, seconds: f32) -> Track<T> {
+        let last = *self.keys.last().unwrap();
+        self.to(last.time + seconds, last.value, Ease::LINEAR)
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    /// When the last keyframe is.
+    pub fn duration(&self) -> f32 {
+        self.keys.last().unwrap().time
+    }
+
+    /// The value at `time`, held at the first and last keyframes' values outside them.
+    pub fn at(&self, time: f32) -> T {
+        // the first keyframe after `time`, if any; NaN comes out as the start.
+        let next = match self.keys.iter().position(|key| key.time > time) {
+            Some(0) => return self.keys[0].value,
+            Some(i) => i,
+            None if time.is_nan() => return self.keys[0].value,
+            None => return self.keys.last().unwrap().value,
+        };
+        let (from, to) = (self.keys[next - 1], self.keys[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        map_eased(t, from.value, to.value, to.ease)
+    }
+}
+
+/// The playhead tracks are read at.
+#[derive(Copy, Clone, Debug)]
+pub struct Timeline {
+    /// Seconds in, from 0 to `duration`.
+    pub time: f32,
+    pub duration: f32,
+    /// Whether it starts over after `duration`, rather than stopping there.
+    pub looping: bool,
+    pub paused: bool,
+    /// How many seconds of the timeline go by each second; negative plays it backwards.
+    pub speed: f32,
+}
+
+impl Timeline {
+    /// A timeline `duration` seconds long, playing once from the start.
+    pub fn new(duration: f32) -> Timeline {
+        assert!(duration > 0.0, "a timeline has to last some time");
+        Timeline {
+            time: 0.0,
+            duration,
+            looping: false,
+            paused: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Play on for `dt` seconds, unless paused.
+    pub fn advance(&mut self, dt: f32) {
+        if !self.paused {
+            self.scrub(self.time + dt * self.speed);
+        }
+    }
+
+    /// Jump to `time`, wrapped round if looping, or held at the ends.
+    pub fn scrub(&mut self, time: f32) {
+        self.time = if self.looping {
+            time.rem_euclid(self.duration)
+        } else {
+            time.max(0.0).min(self.duration)
+        };
+    }
+
+    /// How far through it is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.time / self.duration
+    }
+
+    /// Whether it's played to the end and stopped there.
+    pub fn finished(&self) -> bool {
+        !self.looping && self.time >= self.duration
+    }
+
+    /// Where `track` is now.
+    pub fn sample<T>(&self, track: &Track<T>) -> T
+    where
+        T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+    {
+        track.at(self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ease::{Curve, Mode};
+    use nannou::color::{Lab, LinSrgb};
+    use nannou::geom::vec2;
+
+    #[test]
+    fn test_track() {
+        let quad = Ease::new(Curve::Quad, Mode::In);
+        let track = Track::new(0.0)
+            .to(2.0, 10.0, Ease::LINEAR)
+            .hold(1.0)
+            .to(5.0, 20.0, quad);
+        assert_eq!(track.duration(), 5.0);
+        assert_eq!(track.keys().len(), 4);
+        assert_eq!(track.at(1.0), 5.0);
+        assert_eq!(track.at(2.5), 10.0);
+        assert_eq!(track.at(4.0), 12.5);
+        // held at the ends.
+        assert_eq!(track.at(-1.0), 0.0);
+        assert_eq!(track.at(9.0), 20.0);
+        assert_eq!(track.at(f32::NAN), 0.0);
+
+        // jumping straight to a value.
+        let step = Track::new(vec2(0.0, 0.0)).to(0.0, vec2(1.0, 2.0), Ease::LINEAR);
+        assert_eq!(step.at(0.0), vec2(1.0, 2.0));
+
+        let fade = Track::new(LinSrgb::new(0.0, 0.0, 0.0)).to(
+            1.0,
+            LinSrgb::new(1.0, 0.5, 0.0),
+            Ease::LINEAR,
+        );
+        let half = fade.at(0.5);
+        assert_eq!((half.red, half.green, half.blue), (0.5, 0.25, 0.0));
+        let fade =
+            Track::new(Lab::new(0.0, 0.0, 0.0)).to(2.0, Lab::new(50.0, 10.0, -20.0), Ease::LINEAR);
+        assert_eq!(fade.at(1.0), Lab::new(25.0, 5.0, -10.0));
+    }
+
+    #[test]
+    fn test_timeline() {
+        let track = Track::new(0.0).to(4.0, 8.0, Ease::LINEAR);
+        let mut timeline = Timeline::new(4.0);
+        timeline.advance(1.0);
+        assert_eq!(timeline.sample(&track), 2.0);
+        timeline.speed = 2.0;
+        timeline.advance(1.0);
+        assert_eq!(timeline.progress(), 0.75);
+        timeline.paused = true;
+        timeline.advance(1.0);
+        assert_eq!(timeline.time, 3.0);
+        // stops at the end.
+        timeline.paused = false;
+        timeline.advance(10.0);
+        assert!(timeline.finished());
+        assert_eq!(timeline.sample(&track), 8.0);
+
+        // or goes round again, either way.
+        timeline.looping = true;
+        timeline.scrub(5.0);
+        assert_eq!(timeline.sample(&track), 2.0);
+        timeline.speed = -1.0;
+        timeline.advance(2.0);
+        assert_eq!(timeline.time, 3.0);
+        assert!(!timeline.finished());
+    }
+}
@@ -0,0 +1,170 @@
+//! A sketch's canvas, kept in a texture of its own rather than in the window's frame.
+//!
+//! Anything drawn over a sketch straight into the frame, like help text or the HUD, stays behind
+//! in it for the next frame, so sketches that don't clear it smear their overlays into trails, and
+//! screenshots of the frame catch them. A `Canvas` keeps what the sketch draws apart: `render`
+//! draws onto it in `update` (rendering needs the model mutably), `view` draws its `texture` into
+//! the frame and any overlays over that, and `save` saves just the canvas.
+
+use nannou::color::Srgb;
+use nannou::frame::Frame;
+use nannou::wgpu;
+use nannou::window::Window;
+use nannou::Draw;
+use std::mem;
+use std::path::PathBuf;
+
+pub struct Canvas {
+    /// What the canvas starts out as.
+    pub background: Srgb<u8>,
+    // drawn into, multisampled like the window's frames; none if they aren't.
+    multisampled: Option<wgpu::Texture>,
+    // the canvas, resolved from `multisampled` after each render.
+    texture: wgpu::Texture,
+    renderer: nannou::draw::Renderer,
+    capturer: wgpu::TextureCapturer,
+    scale_factor: f32,
+    // whether it's yet to be cleared, and what it's replacing after a resize, stretched over it.
+    fresh: bool,
+    previous: Option<wgpu::Texture>,
+}
+
+impl Canvas {
+    /// A canvas filling `window`, at its resolution and multisampling.
+    pub fn new(window: &Window, background: Srgb<u8>) -> Canvas {
+        let device = window.swap_chain_device();
+        let size = size_of(window);
+        let samples = window.msaa_samples();
+        let texture = wgpu::TextureBuilder::new()
+            .size(size)
+            .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED)
+            .sample_count(1)
+            .format(Frame::TEXTURE_FORMAT)
+            .build(device);
+        let multisampled = if samples > 1 {
+            let texture = wgpu::TextureBuilder::new()
+                .size(size)
+                .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT)
+                .sample_count(samples)
+                .format(Frame::TEXTURE_FORMAT)
+                .build(device);
+            Some(texture)
+        } else {
+            None
+        };
+        let renderer = nannou::draw::RendererBuilder::new().build(
+            device,
+            size,
+            window.scale_factor(),
+            samples,
+            Frame::TEXTURE_FORMAT,
+        );
+        Canvas {
+            background,
+            multisampled,
+            texture,
+            renderer,
+            capturer: wgpu::TextureCapturer::default(),
+            scale_factor: window.scale_factor(),
+            fresh: true,
+            previous: None,
+        }
+    }
+
+    /// The size of the canvas, in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.texture.size()
+    }
+
+    /// The canvas as it's been drawn so far, to draw into the frame.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Draw `draw`, in `window`'s points with the origin in the middle, over what's on the canvas
+    /// already. If `window` has been resized since the last render, the canvas is remade to fit it
+    /// first, with what was on it stretched to fill it.
+    pub fn render(&mut self, window: &Window, draw: &Draw) {
+        if self.size() != size_of(window) {
+            let resized = Canvas::new(window, self.background);
+            let previous = mem::replace(self, resized);
+            self.previous = Some(previous.texture);
+        }
+
+        let device = window.swap_chain_device();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("canvas"),
+        });
+        let size = self.size();
+        let view = self.texture.view().build();
+        let multisampled = self.multisampled.as_ref().map(|texture| texture.view().build());
+        let (attachment, resolve_target) = match &multisampled {
+            Some(multisampled) => (multisampled, Some(&view)),
+            None => (&view, None),
+        };
+        if self.fresh {
+            let clear = Draw::new();
+            clear.background().color(self.background);
+            if let Some(previous) = &self.previous {
+                let [w, h] = size;
+                clear
+                    .texture(previous)
+                    .w_h(w as f32 / self.scale_factor, h as f32 / self.scale_factor);
+            }
+            self.renderer.encode_render_pass(
+                device,
+                &mut encoder,
+                &clear,
+                self.scale_factor,
+                size,
+                attachment,
+                resolve_target,
+            );
+            self.fresh = false;
+        }
+        self.renderer.encode_render_pass(
+            device,
+            &mut encoder,
+            draw,
+            self.scale_factor,
+            size,
+            attachment,
+            resolve_target,
+        );
+        window.swap_chain_queue().submit(&[encoder.finish()]);
+        self.previous = None;
+    }
+
+    /// Save the canvas to `path`, once it's read back.
+    pub fn save(&self, window: &Window, path: PathBuf) {
+        let device = window.swap_chain_device();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("canvas capture"),
+        });
+        let snapshot = self.capturer.capture(device, &mut encoder, &self.texture);
+        window.swap_chain_queue().submit(&[encoder.finish()]);
+        snapshot
+            .read(move |result| {
+                let image = result
+                    .expect("failed to map the captured texture")
+                    .to_owned();
+                image
+                    .save(&path)
+                    .unwrap_or_else(|e| panic!("can't save {}: {}", path.display(), e));
+            })
+            .expect("failed to read the captured texture");
+    }
+
+    /// Wait for any saves still being read back; call before the app exits.
+    pub fn finish(&self, window: &Window) {
+        self.capturer
+            .await_active_snapshots(window.swap_chain_device())
+            .expect("failed to wait for captures");
+    }
+}
+
+// the size in pixels of a canvas filling `window`.
+fn size_of(window: &Window) -> [u32; 2] {
+    let (w, h) = window.inner_size_pixels();
+    [w.max(1), h.max(1)]
+}
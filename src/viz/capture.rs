@@ -0,0 +1,107 @@
+//! Rendering a sketch offscreen and reading it back, so post effects can work on its pixels.
+//!
+//! Reading a texture back from the GPU is asynchronous: `FrameCapture::capture` starts it, and
+//! the image turns up in `latest` a frame or two later. Draw into a fresh `Draw` in `update`,
+//! capture it there, and draw whatever's come back in `view`. `save` saves it to a file instead,
+//! for rendering without a window to show it in.
+
+use nannou::geom::Rect;
+use nannou::image::{DynamicImage, RgbaImage};
+use nannou::wgpu;
+use nannou::window::Window;
+use nannou::{App, Draw};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+pub struct FrameCapture {
+    texture: wgpu::Texture,
+    renderer: nannou::draw::Renderer,
+    capturer: wgpu::TextureCapturer,
+    // the newest image read back, filled in by the capturer's callback.
+    latest: Arc<Mutex<Option<RgbaImage>>>,
+}
+
+impl FrameCapture {
+    /// Set up to capture drawings `size` pixels across and up, on `window`'s device.
+    pub fn new(window: &Window, size: [u32; 2]) -> FrameCapture {
+        let device = window.swap_chain_device();
+        let texture = wgpu::TextureBuilder::new()
+            .size(size)
+            .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED)
+            .sample_count(1)
+            .format(wgpu::TextureFormat::Rgba16Float)
+            .build(device);
+        let renderer = nannou::draw::RendererBuilder::new()
+            .build_from_texture_descriptor(device, texture.descriptor());
+        FrameCapture {
+            texture,
+            renderer,
+            capturer: wgpu::TextureCapturer::default(),
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.texture.size()
+    }
+
+    /// Render `draw`, with the origin in the middle and a pixel to a point, and start reading it
+    /// back.
+    pub fn capture(&mut self, window: &Window, draw: &Draw) {
+        let latest = self.latest.clone();
+        self.render(window, draw, move |image| {
+            *latest.lock().unwrap() = Some(image)
+        });
+    }
+
+    /// Render `draw` like `capture`, but save it to `path` once it's read back, rather than
+    /// keeping it for `latest`.
+    pub fn save(&mut self, window: &Window, draw: &Draw, path: PathBuf) {
+        self.render(window, draw, move |image| {
+            image
+                .save(&path)
+                .unwrap_or_else(|e| panic!("can't save {}: {}", path.display(), e))
+        });
+    }
+
+    // render `draw`, and hand it to `done` once it's read back.
+    fn render<F>(&mut self, window: &Window, draw: &Draw, done: F)
+    where
+        F: FnOnce(RgbaImage) + Send + 'static,
+    {
+        let device = window.swap_chain_device();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("frame capture"),
+        });
+        self.renderer
+            .render_to_texture(device, &mut encoder, draw, &self.texture);
+        let snapshot = self.capturer.capture(device, &mut encoder, &self.texture);
+        window.swap_chain_queue().submit(&[encoder.finish()]);
+        snapshot
+            .read(move |result| {
+                let image = result
+                    .expect("failed to map the captured texture")
+                    .to_owned();
+                done(image);
+            })
+            .expect("failed to read the captured texture");
+    }
+
+    /// The newest capture to have been read back, if any has yet; its rows go from the top down.
+    pub fn latest(&self) -> Option<RgbaImage> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    /// Wait for any captures still being read back; call before the app exits.
+    pub fn finish(&self, window: &Window) {
+        self.capturer
+            .await_active_snapshots(window.swap_chain_device())
+            .expect("failed to wait for captures");
+    }
+}
+
+/// Draw `image`, a capture or what a post effect's made of one, stretched over `rect`.
+pub fn draw_image(app: &App, draw: &Draw, image: RgbaImage, rect: Rect) {
+    let texture = wgpu::Texture::from_image(app, &DynamicImage::ImageRgba8(image));
+    draw.texture(&texture).xy(rect.xy()).wh(rect.wh());
+}
@@ -0,0 +1,48 @@
+//! Drawing a `FlowField`, as streamlines or a grid of arrows.
+
+use crate::flow::FlowField;
+use nannou::color::Rgba;
+use nannou::geom::vec2;
+use nannou::Draw;
+
+/// Draw streamlines following the field, starting from a grid of points `spacing` apart, each
+/// up to `length` long (or until it leaves the field).
+pub fn draw_streamlines(
+    draw: &Draw,
+    field: &FlowField,
+    spacing: f32,
+    length: f32,
+    weight: f32,
+    color: Rgba,
+) {
+    let step = spacing / 4.0;
+    let steps = (length / step) as usize;
+    let columns = (field.bounds.w() / spacing) as usize;
+    let rows = (field.bounds.h() / spacing) as usize;
+    for row in 0..rows {
+        for column in 0..columns {
+            let start =
+                field.bounds.bottom_left() + vec2(column as f32 + 0.5, row as f32 + 0.5) * spacing;
+            let line = field.streamline(start, step, steps);
+            if line.len() >= 2 {
+                draw.polyline().weight(weight).points(line).color(color);
+            }
+        }
+    }
+}
+
+/// Draw an arrow at each grid point of the field, `scale` times as long as the velocity there.
+pub fn draw_arrows(draw: &Draw, field: &FlowField, scale: f32, weight: f32, color: Rgba) {
+    for (pos, vel) in field.nodes() {
+        if vel.magnitude2() == 0.0 {
+            continue;
+        }
+        draw.arrow()
+            .start(pos)
+            .end(pos + vel * scale)
+            .weight(weight)
+            .head_length(weight * 3.0)
+            .head_width(weight * 2.0)
+            .color(color);
+    }
+}
@@ -0,0 +1,186 @@
+//! Gradients with their colors placed anywhere from 0 to 1, rather than evenly like a `Palette`'s,
+//! and optionally cyclic, so they wrap round from 1 back to 0 for things like angles and phases.
+//!
+//! Colors blend in any of `palette::Space`'s color spaces, and `draw_gradient_rect` draws one as
+//! a key for whatever it's coloring.
+
+use super::palette::Space;
+use nannou::color::{IntoLinSrgba, LinSrgba, Rgb, Srgb};
+use nannou::geom::{vec2, Rect};
+use nannou::Draw;
+
+#[derive(Clone, Debug)]
+pub struct Gradient {
+    // in order, each stop's position, its color as given, and the same in `space`.
+    positions: Vec<f32>,
+    colors: Vec<Rgb>,
+    space: Space,
+    coords: Vec<[f32; 3]>,
+    /// Whether it wraps round, with `t` taken modulo 1, and the last stop blending on into the
+    /// first; otherwise it's held at the first and last colors past them.
+    pub cyclic: bool,
+}
+
+impl Gradient {
+    /// A gradient through `stops`, each a position and a color, with positions in order from 0
+    /// to 1; two at the same place make a hard edge. Blended in Lab, and not cyclic.
+    pub fn new(stops: &[(f32, Srgb<u8>)]) -> Gradient {
+        assert!(!stops.is_empty(), "a gradient needs at least one stop");
+        assert!(
+            stops.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+            "a gradient's stops have to be in order"
+        );
+        let colors = stops
+            .iter()
+            .map(|(_, color)| color.into_format::<f32>())
+            .collect::<Vec<_>>();
+        Gradient {
+            positions: stops.iter().map(|(position, _)| *position).collect(),
+            coords: colors.iter().map(|c| Space::Lab.encode(*c)).collect(),
+            colors,
+            space: Space::Lab,
+            cyclic: false,
+        }
+    }
+
+    /// `colors` evenly spaced from 0 to 1, like a `Palette`; cyclic ones leave room after the
+    /// last to blend back into the first.
+    pub fn even(colors: &[Srgb<u8>], cyclic: bool) -> Gradient {
+        let gaps = if cyclic {
+            colors.len()
+        } else {
+            colors.len().max(2) - 1
+        };
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| (i as f32 / gaps as f32, *color))
+            .collect::<Vec<_>>();
+        let mut gradient = Gradient::new(&stops);
+        gradient.cyclic = cyclic;
+        gradient
+    }
+
+    /// The same stops, blended in `space` instead.
+    pub fn in_space(mut self, space: Space) -> Gradient {
+        self.space = space;
+        self.coords = self.colors.iter().map(|c| space.encode(*c)).collect();
+        self
+    }
+
+    pub fn space(&self) -> Space {
+        self.space
+    }
+
+    /// The color at `t`.
+    pub fn sample(&self, t: f32) -> Rgb {
+        let last = self.positions.len() - 1;
+        if t.is_nan() {
+            return self.colors[0];
+        }
+        let t = if self.cyclic { t.rem_euclid(1.0) } else { t };
+        // the stops either side, and where they are; cyclic gradients' first and last stops have
+        // copies a whole turn on.
+        let next = self.positions.iter().position(|p| *p > t);
+        let (a, b, start, end) = match next {
+            Some(0) if self.cyclic => (last, 0, self.positions[last] - 1.0, self.positions[0]),
+            Some(0) => return self.colors[0],
+            Some(i) => (i - 1, i, self.positions[i - 1], self.positions[i]),
+            None if self.cyclic => (last, 0, self.positions[last], self.positions[0] + 1.0),
+            None => return self.colors[last],
+        };
+        let f = if end > start {
+            (t - start) / (end - start)
+        } else {
+            0.0
+        };
+        self.space
+            .decode(self.space.mix(self.coords[a], self.coords[b], f))
+    }
+
+    /// `sample(t)` as bytes, for filling images.
+    pub fn sample_u8(&self, t: f32) -> [u8; 3] {
+        let color = self.sample(t);
+        let byte = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+        [byte(color.red), byte(color.green), byte(color.blue)]
+    }
+}
+
+// strips across a drawn gradient; the GPU blends in between, so they only need to be narrow
+// enough that the color space's curve shows.
+const STRIPS: usize = 64;
+
+/// Draw `gradient` filling `rect`, from 0 at its left to 1 at its right.
+pub fn draw_gradient_rect(draw: &Draw, rect: Rect, gradient: &Gradient) {
+    let mut vertices = Vec::with_capacity((STRIPS + 1) * 2);
+    for i in 0..=STRIPS {
+        let t = i as f32 / STRIPS as f32;
+        let x = rect.left() + rect.w() * t;
+        let color: LinSrgba = gradient.sample(t).into_lin_srgba();
+        vertices.push((vec2(x, rect.bottom()), color));
+        vertices.push((vec2(x, rect.top()), color));
+    }
+    let indices = (0..STRIPS).flat_map(|i| {
+        let j = i * 2;
+        vec![j, j + 1, j + 2, j + 1, j + 3, j + 2]
+    });
+    draw.mesh().indexed_colored(vertices, indices);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let gradient = Gradient::new(&[
+            (0.2, Srgb::new(0, 0, 0)),
+            (0.6, Srgb::new(255, 0, 0)),
+            (0.6, Srgb::new(0, 0, 255)),
+            (1.0, Srgb::new(255, 255, 255)),
+        ]);
+        assert_eq!(gradient.sample_u8(0.2), [0, 0, 0]);
+        // held past the first stop.
+        assert_eq!(gradient.sample_u8(0.0), [0, 0, 0]);
+        assert_eq!(gradient.sample_u8(-5.0), [0, 0, 0]);
+        assert_eq!(gradient.sample_u8(f32::NAN), [0, 0, 0]);
+        assert_eq!(gradient.sample_u8(3.0), [255, 255, 255]);
+        // a hard edge from red to blue.
+        let red = gradient.sample_u8(0.599);
+        assert!(red[0] > 250 && red[2] < 5);
+        assert_eq!(gradient.sample_u8(0.6), [0, 0, 255]);
+        // part way to red is a darker red.
+        let dark = gradient.sample_u8(0.3);
+        assert!(dark[0] > 60 && dark[0] < 255 && dark[1] < 30 && dark[2] < 30);
+    }
+
+    #[test]
+    fn test_cyclic() {
+        let mut gradient = Gradient::even(
+            &[
+                Srgb::new(255, 0, 0),
+                Srgb::new(0, 255, 0),
+                Srgb::new(0, 0, 255),
+            ],
+            true,
+        );
+        let third = 1.0 / 3.0;
+        assert_eq!(gradient.sample_u8(third), [0, 255, 0]);
+        assert_eq!(gradient.sample_u8(1.0 + 2.0 * third), [0, 0, 255]);
+        assert_eq!(gradient.sample_u8(-third), [0, 0, 255]);
+        // and on from blue back round to red, the same either side of 0.
+        assert_eq!(gradient.sample_u8(-0.1), gradient.sample_u8(0.9));
+        let purple = gradient.sample_u8(5.0 / 6.0);
+        assert!(purple[0] > 100 && purple[2] > 100 && purple[1] < 50);
+
+        // not cyclic, it stops at blue.
+        gradient.cyclic = false;
+        assert_eq!(gradient.sample_u8(5.0 / 6.0), [0, 0, 255]);
+
+        let single = Gradient::even(&[Srgb::new(10, 20, 30)], true);
+        assert_eq!(single.sample_u8(0.7), [10, 20, 30]);
+        let ends = Gradient::even(&[Srgb::new(10, 20, 30), Srgb::new(40, 50, 60)], false);
+        assert_eq!(ends.sample_u8(1.0), [40, 50, 60]);
+        assert_eq!(ends.in_space(Space::Oklab).sample_u8(0.0), [10, 20, 30]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Halftone and ordered dither: an image redrawn in one ink on paper, either as a grid of dots
+//! sized by how dark each cell of it is, like newsprint, or as blocky pixels switched on and off
+//! against a Bayer matrix, like an old screen.
+//!
+//! The image is usually a sketch captured with `viz::capture`, so any sketch can be restyled.
+
+use nannou::color::Srgb;
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::image::{Rgba, RgbaImage};
+use nannou::Draw;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Style {
+    /// A dot in every cell, covering as much of it as the cell is dark.
+    Dots,
+    /// Every cell either inked or not, by comparing its darkness with its place in a Bayer matrix,
+    /// so patches of the same darkness come out as the same even pattern.
+    Dither,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Halftone {
+    /// How many of the image's pixels across and up each cell is.
+    pub cell: u32,
+    pub style: Style,
+    pub ink: Srgb<u8>,
+    pub paper: Srgb<u8>,
+}
+
+/// One thing to draw: a dot of `size` radius, or for `Dither`, a square `size` across, at `pos`,
+/// in the image's pixels from its bottom left corner.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Mark {
+    pub pos: Vector2,
+    pub size: f32,
+}
+
+impl Halftone {
+    /// Black dots on white paper, in cells `cell` pixels across.
+    pub fn new(cell: u32) -> Halftone {
+        assert!(cell > 0, "cells have to be at least a pixel across");
+        Halftone {
+            cell,
+            style: Style::Dots,
+            ink: Srgb::new(20, 20, 20),
+            paper: Srgb::new(245, 240, 225),
+        }
+    }
+
+    /// How many cells across and up `image` makes, counting cells cut off by its edges.
+    pub fn cells(&self, image: &RgbaImage) -> (usize, usize) {
+        let cells = |pixels: u32| (pixels as f32 / self.cell as f32).ceil() as usize;
+        (cells(image.width()), cells(image.height()))
+    }
+
+    /// How dark each cell of `image` is on average, from 0 for white to 1 for black, row by row
+    /// from the bottom left.
+    pub fn darkness(&self, image: &RgbaImage) -> Vec<f32> {
+        let (width, height) = self.cells(image);
+        let mut total = vec![0.0; width * height];
+        let mut count = vec![0.0; width * height];
+        for (x, y, pixel) in image.enumerate_pixels() {
+            // images go from the top down.
+            let (cx, cy) = (x / self.cell, (image.height() - 1 - y) / self.cell);
+            let i = cy as usize * width + cx as usize;
+            total[i] += 1.0 - luminance(pixel);
+            count[i] += 1.0;
+        }
+        total.iter().zip(&count).map(|(t, c)| t / c).collect()
+    }
+
+    /// Where the ink goes for `image`.
+    pub fn marks(&self, image: &RgbaImage) -> Vec<Mark> {
+        let (width, _) = self.cells(image);
+        let cell = self.cell as f32;
+        let mut marks = vec![];
+        for (i, darkness) in self.darkness(image).into_iter().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let pos = vec2(x as f32 + 0.5, y as f32 + 0.5) * cell;
+            match self.style {
+                Style::Dots if darkness > 0.0 => marks.push(Mark {
+                    pos,
+                    size: cell * (darkness / PI).sqrt(),
+                }),
+                Style::Dither if darkness > bayer(4, x, y) => marks.push(Mark { pos, size: cell }),
+                _ => (),
+            }
+        }
+        marks
+    }
+
+    /// Draw `image` stretched over `rect`, on paper.
+    pub fn draw(&self, draw: &Draw, image: &RgbaImage, rect: Rect) {
+        draw.rect().xy(rect.xy()).wh(rect.wh()).color(self.paper);
+        let scale = vec2(
+            rect.w() / image.width() as f32,
+            rect.h() / image.height() as f32,
+        );
+        for mark in self.marks(image) {
+            let pos = rect.bottom_left() + vec2(mark.pos.x * scale.x, mark.pos.y * scale.y);
+            match self.style {
+                Style::Dots => {
+                    draw.ellipse()
+                        .xy(pos)
+                        .radius(mark.size * scale.x)
+                        .color(self.ink);
+                }
+                Style::Dither => {
+                    draw.rect()
+                        .xy(pos)
+                        .w_h(mark.size * scale.x, mark.size * scale.y)
+                        .color(self.ink);
+                }
+            }
+        }
+    }
+}
+
+/// How bright `pixel` looks, from 0 for black to 1 for white.
+pub fn luminance(pixel: &Rgba<u8>) -> f32 {
+    let [r, g, b, _] = pixel.0;
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+/// The threshold, between 0 and 1, at (`x`, `y`) in a `size` by `size` Bayer matrix (`size` a
+/// power of two) repeated over the plane. Every threshold turns up once in each repeat, spread
+/// as evenly as they can be.
+pub fn bayer(size: usize, x: usize, y: usize) -> f32 {
+    assert!(
+        size.is_power_of_two(),
+        "Bayer matrices are a power of two across"
+    );
+    let levels = size.trailing_zeros();
+    let mut index = 0;
+    // each bit of x and y picks a place in the 2x2 matrix, [0 2; 3 1]; the lowest bits count
+    // most, so neighbouring thresholds are far apart.
+    for bit in 0..levels {
+        let (bx, by) = ((x >> bit) & 1, (y >> bit) & 1);
+        index += [[0, 2], [3, 1]][by][bx] * 4usize.pow(levels - 1 - bit);
+    }
+    (index as f32 + 0.5) / (size * size) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bayer() {
+        let mut thresholds = (0..4)
+            .flat_map(|y| (0..4).map(move |x| bayer(4, x, y)))
+            .collect::<Vec<_>>();
+        // neighbours are far apart, so even patches don't clump.
+        assert_eq!(bayer(4, 0, 0), 0.5 / 16.0);
+        assert_eq!(bayer(4, 1, 0), 8.5 / 16.0);
+        assert_eq!(bayer(4, 1, 1), 4.5 / 16.0);
+        assert_eq!(bayer(4, 2, 2), 1.5 / 16.0);
+        assert_eq!(bayer(4, 5, 4), bayer(4, 1, 0));
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (i, threshold) in thresholds.iter().enumerate() {
+            assert_eq!(*threshold, (i as f32 + 0.5) / 16.0);
+        }
+    }
+
+    #[test]
+    fn test_marks() {
+        // black on the left, white on the right, and a grey strip along the bottom.
+        let image = RgbaImage::from_fn(10, 6, |x, y| match (x, y) {
+            (_, 4..=5) => Rgba([128, 128, 128, 255]),
+            (0..=4, _) => Rgba([0, 0, 0, 255]),
+            _ => Rgba([255, 255, 255, 255]),
+        });
+        let mut halftone = Halftone::new(2);
+        assert_eq!(halftone.cells(&image), (5, 3));
+        let darkness = halftone.darkness(&image);
+        assert!((darkness[0] - 0.5).abs() < 0.01);
+        // the cell straddling black and white is half and half.
+        assert_eq!(&darkness[5..10], &[1.0, 1.0, 0.5, 0.0, 0.0]);
+
+        let marks = halftone.marks(&image);
+        // no dots on the white.
+        assert_eq!(marks.len(), 5 + 3 + 3);
+        let black = marks.iter().find(|m| m.pos == vec2(1.0, 3.0)).unwrap();
+        assert!((black.size - 2.0 / PI.sqrt()).abs() < 1e-5);
+
+        // dithered, the black's all inked, the white none, and the grey half.
+        halftone.style = Style::Dither;
+        halftone.cell = 1;
+        let marks = halftone.marks(&image);
+        let inked = |y: f32| marks.iter().filter(|m| m.pos.y == y + 0.5).count();
+        assert_eq!(inked(2.0), 5);
+        assert_eq!(inked(0.0) + inked(1.0), 10);
+        assert!(marks.iter().all(|m| m.size == 1.0));
+    }
+}
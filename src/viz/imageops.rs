@@ -0,0 +1,298 @@
+//! Morphological / filtering operations on offscreen textures, run as compute shaders.
+//!
+//! `ImageOps::render` renders a sketch offscreen, like `viz::capture`, then runs a chain of
+//! `ImageOp`s over it on the GPU; draw `output` to the frame afterwards. Unlike a capture, nothing
+//! is read back, so it's cheap enough to post-process every frame, like the glow and outlines of
+//! `attractors`.
+//!
+//! The shader source is `shaders/imageops.comp`, compiled to SPIR-V by `build.rs`.
+
+use nannou::wgpu;
+use nannou::window::Window;
+use nannou::Draw;
+
+/// The format of every texture processed by `ImageOps`.
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// A single post-processing operation.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ImageOp {
+    /// Gaussian blur, with a kernel reaching `radius` pixels out.
+    Blur { radius: u32 },
+    /// Black out pixels with luminance below `level`.
+    Threshold { level: f32 },
+    /// Replace each pixel with the brightest in a box reaching `radius` pixels out.
+    Dilate { radius: u32 },
+    /// Replace each pixel with the darkest in a box reaching `radius` pixels out.
+    Erode { radius: u32 },
+    /// Sobel edge detection on luminance. Edges come out white, scaled by `strength`.
+    EdgeDetect { strength: f32 },
+    /// Add the result so far, times `strength`, back onto the unprocessed input.
+    /// `[Threshold, Blur, AddToOriginal]` makes a glow.
+    AddToOriginal { strength: f32 },
+}
+
+// Must match `shaders/imageops.comp`.
+const OP_BLUR: i32 = 1;
+const OP_THRESHOLD: i32 = 2;
+const OP_DILATE: i32 = 3;
+const OP_ERODE: i32 = 4;
+const OP_EDGES: i32 = 5;
+const OP_ADD_ORIGINAL: i32 = 6;
+
+/// The uniform block of the shader, std140 layout.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct Params {
+    direction: [i32; 2],
+    op: i32,
+    radius: i32,
+    level: f32,
+    strength: f32,
+    _padding: [f32; 2],
+}
+
+impl Params {
+    fn new(op: i32) -> Params {
+        Params {
+            direction: [0, 0],
+            op,
+            radius: 0,
+            level: 0.0,
+            strength: 1.0,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+impl ImageOp {
+    /// The shader dispatches needed for this operation. Box filters are separable, so they run as
+    /// a horizontal then a vertical pass.
+    fn passes(&self) -> Vec<Params> {
+        let separable = |op: i32, radius: u32| -> Vec<Params> {
+            vec![[1, 0], [0, 1]]
+                .into_iter()
+                .map(|direction| Params {
+                    direction,
+                    radius: radius as i32,
+                    ..Params::new(op)
+                })
+                .collect()
+        };
+        match *self {
+            ImageOp::Blur { radius } => separable(OP_BLUR, radius),
+            ImageOp::Dilate { radius } => separable(OP_DILATE, radius),
+            ImageOp::Erode { radius } => separable(OP_ERODE, radius),
+            ImageOp::Threshold { level } => vec![Params {
+                level,
+                ..Params::new(OP_THRESHOLD)
+            }],
+            ImageOp::EdgeDetect { strength } => vec![Params {
+                strength,
+                ..Params::new(OP_EDGES)
+            }],
+            ImageOp::AddToOriginal { strength } => vec![Params {
+                strength,
+                ..Params::new(OP_ADD_ORIGINAL)
+            }],
+        }
+    }
+}
+
+// the usage of every texture `ImageOps` draws into, processes, or leaves its result in.
+fn texture_usage() -> wgpu::TextureUsage {
+    wgpu::TextureUsage::STORAGE
+        | wgpu::TextureUsage::SAMPLED
+        | wgpu::TextureUsage::OUTPUT_ATTACHMENT
+        | wgpu::TextureUsage::COPY_SRC
+}
+
+fn texture(device: &wgpu::Device, size: [u32; 2]) -> wgpu::Texture {
+    wgpu::TextureBuilder::new()
+        .size(size)
+        .format(FORMAT)
+        .usage(texture_usage())
+        .build(device)
+}
+
+/// Which of the two ping-pong textures holds the result after `passes` dispatches, or `None` if
+/// there were none, and the result's the input itself. The first pass writes the first texture,
+/// and each after it reads the one the last wrote and writes the other.
+fn result_texture(passes: usize) -> Option<usize> {
+    match passes {
+        0 => None,
+        n => Some((n - 1) % 2),
+    }
+}
+
+/// The compute pipeline, the texture sketches are rendered into, and ping-pong textures for
+/// running `ImageOp`s over it.
+pub struct ImageOps {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    renderer: nannou::draw::Renderer,
+    input: wgpu::Texture,
+    textures: [wgpu::Texture; 2],
+    // uniforms + bind groups of the last `render`, kept alive until its commands are submitted.
+    passes: Vec<(wgpu::Buffer, wgpu::BindGroup)>,
+    // where the last `render` left its result.
+    result: Option<usize>,
+}
+
+impl ImageOps {
+    /// Set up to process drawings `size` pixels across and up, on `window`'s device.
+    pub fn new(window: &Window, size: [u32; 2]) -> ImageOps {
+        let device = window.swap_chain_device();
+        let module = wgpu::shader_from_spirv_bytes(
+            device,
+            include_bytes!(concat!(env!("OUT_DIR"), "/imageops.spv")),
+        );
+
+        let stage = wgpu::ShaderStage::COMPUTE;
+        let dim = wgpu::TextureViewDimension::D2;
+        let ty = wgpu::TextureComponentType::Float;
+        let bind_group_layout = wgpu::BindGroupLayoutBuilder::new()
+            .storage_texture(stage, FORMAT, dim, ty, true)
+            .storage_texture(stage, FORMAT, dim, ty, false)
+            .storage_texture(stage, FORMAT, dim, ty, true)
+            .uniform_buffer(stage, false)
+            .build(device);
+        let pipeline_layout = wgpu::create_pipeline_layout(device, &[&bind_group_layout]);
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            layout: &pipeline_layout,
+            compute_stage: wgpu::ProgrammableStageDescriptor {
+                module: &module,
+                entry_point: "main",
+            },
+        });
+
+        let input = texture(device, size);
+        let renderer = nannou::draw::RendererBuilder::new()
+            .build_from_texture_descriptor(device, input.descriptor());
+        ImageOps {
+            bind_group_layout,
+            pipeline,
+            renderer,
+            input,
+            textures: [texture(device, size), texture(device, size)],
+            passes: vec![],
+            result: None,
+        }
+    }
+
+    /// The size of drawing this can process.
+    pub fn size(&self) -> [u32; 2] {
+        self.input.size()
+    }
+
+    /// Render `draw`, with the origin in the middle and a pixel to a point, and apply `ops` to
+    /// it, in order.
+    pub fn render(&mut self, window: &Window, draw: &Draw, ops: &[ImageOp]) {
+        let device = window.swap_chain_device();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("image ops"),
+        });
+        self.renderer
+            .render_to_texture(device, &mut encoder, draw, &self.input);
+
+        let params = ops.iter().flat_map(|op| op.passes()).collect::<Vec<_>>();
+
+        // The first pass reads the input; after that, read from the texture that was last written.
+        self.passes.clear();
+        let input_view = self.input.view().build();
+        let views = [
+            self.textures[0].view().build(),
+            self.textures[1].view().build(),
+        ];
+        for (i, params) in params.iter().enumerate() {
+            let src = if i == 0 {
+                &input_view
+            } else {
+                &views[(i + 1) % 2]
+            };
+            let dst = &views[i % 2];
+
+            let uniforms =
+                device.create_buffer_with_data(as_bytes(params), wgpu::BufferUsage::UNIFORM);
+            let bind_group = wgpu::BindGroupBuilder::new()
+                .texture_view(src)
+                .texture_view(dst)
+                .texture_view(&input_view)
+                .buffer::<Params>(&uniforms, 0..1)
+                .build(device, &self.bind_group_layout);
+            self.passes.push((uniforms, bind_group));
+        }
+
+        let [w, h] = self.size();
+        for (_, bind_group) in &self.passes {
+            let mut pass = encoder.begin_compute_pass();
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch((w + 7) / 8, (h + 7) / 8, 1);
+        }
+        window.swap_chain_queue().submit(&[encoder.finish()]);
+        self.result = result_texture(params.len());
+    }
+
+    /// The last `render`'s result, to draw with `draw.texture`.
+    pub fn output(&self) -> &wgpu::Texture {
+        match self.result {
+            Some(i) => &self.textures[i],
+            None => &self.input,
+        }
+    }
+}
+
+fn as_bytes(params: &Params) -> &[u8] {
+    // Safe: `Params` is `repr(C)` and made of plain 4-byte numbers, with no implicit padding.
+    unsafe {
+        std::slice::from_raw_parts(
+            params as *const Params as *const u8,
+            std::mem::size_of::<Params>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passes() {
+        // box filters run across, then up.
+        let blur = ImageOp::Blur { radius: 3 }.passes();
+        assert_eq!(blur.len(), 2);
+        assert_eq!((blur[0].direction, blur[1].direction), ([1, 0], [0, 1]));
+        assert!(blur
+            .iter()
+            .all(|pass| pass.op == OP_BLUR && pass.radius == 3));
+        let threshold = ImageOp::Threshold { level: 0.5 }.passes();
+        assert_eq!(threshold.len(), 1);
+        assert_eq!((threshold[0].op, threshold[0].level), (OP_THRESHOLD, 0.5));
+
+        // the passes of a glow alternate between the two textures, ending in the first.
+        let glow = [
+            ImageOp::Threshold { level: 0.5 },
+            ImageOp::Blur { radius: 3 },
+            ImageOp::AddToOriginal { strength: 1.0 },
+        ];
+        let passes = glow.iter().flat_map(|op| op.passes()).count();
+        assert_eq!(passes, 4);
+        assert_eq!(result_texture(passes), Some(1));
+        assert_eq!(result_texture(1), Some(0));
+        assert_eq!(result_texture(0), None);
+    }
+
+    #[test]
+    fn test_params_layout() {
+        // a std140 uniform block is padded out to a multiple of 16 bytes.
+        assert_eq!(std::mem::size_of::<Params>(), 32);
+        let params = Params {
+            radius: 2,
+            ..Params::new(OP_ERODE)
+        };
+        let bytes = as_bytes(&params);
+        assert_eq!(bytes[8..12], OP_ERODE.to_ne_bytes());
+        assert_eq!(bytes[12..16], 2i32.to_ne_bytes());
+    }
+}
@@ -0,0 +1,16 @@
+//! Rendering helpers shared between sketches.
+
+pub mod canvas;
+pub mod capture;
+pub mod flow;
+pub mod gradient;
+pub mod halftone;
+pub mod imageops;
+pub mod palette;
+pub mod pixelsort;
+pub mod plot;
+pub mod scale;
+pub mod svg;
+pub mod symmetry;
+pub mod tiling;
+pub mod trail;
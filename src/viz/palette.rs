@@ -0,0 +1,414 @@
+//! Gradients for coloring numbers, blended in a perceptual color space.
+//!
+//! Blending in Lab rather than RGB keeps the colors between stops about as bright and saturated as
+//! the stops themselves, instead of going muddy in the middle; it's how the bouncing sketches have
+//! always colored their balls by energy. Oklab does the same a little more evenly, keeping blues
+//! from turning purple on their way to white, and HSLuv goes round the hue wheel instead, through
+//! every hue between the stops at an even lightness.
+
+use nannou::color::{Lab, Rgb, Srgb};
+use std::f32::consts::PI;
+
+/// A color space to blend colors in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Space {
+    Lab,
+    Oklab,
+    /// Hue, saturation and lightness, with hue in degrees the short way round between stops.
+    Hsluv,
+}
+
+impl Space {
+    /// `color`'s coordinates in this space.
+    pub fn encode(self, color: Rgb) -> [f32; 3] {
+        match self {
+            Space::Lab => {
+                let lab: Lab = color.into();
+                [lab.l, lab.a, lab.b]
+            }
+            Space::Oklab => oklab::encode(linear(color)),
+            Space::Hsluv => hsluv::encode(linear(color)),
+        }
+    }
+
+    /// The color at `coords` in this space; it may be a little outside what sRGB can show.
+    pub fn decode(self, coords: [f32; 3]) -> Rgb {
+        match self {
+            Space::Lab => Rgb::from(Lab::new(coords[0], coords[1], coords[2])),
+            Space::Oklab => gamma(oklab::decode(coords)),
+            Space::Hsluv => gamma(hsluv::decode(coords)),
+        }
+    }
+
+    /// `f` of the way from `a` to `b`, both in this space.
+    pub fn mix(self, a: [f32; 3], b: [f32; 3], f: f32) -> [f32; 3] {
+        let lerp = |a: f32, b: f32| a + (b - a) * f;
+        match self {
+            Space::Hsluv => {
+                // the short way round, unless either end is grey and has no hue to speak of.
+                let hue = match (a[1] > 1e-3, b[1] > 1e-3) {
+                    (true, true) => a[0] + ((b[0] - a[0] + 540.0) % 360.0 - 180.0) * f,
+                    (true, false) => a[0],
+                    (false, _) => b[0],
+                };
+                [hue.rem_euclid(360.0), lerp(a[1], b[1]), lerp(a[2], b[2])]
+            }
+            _ => [lerp(a[0], b[0]), lerp(a[1], b[1]), lerp(a[2], b[2])],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Palette {
+    // as given, evenly spaced from 0 to 1, and the same in `space`.
+    colors: Vec<Rgb>,
+    space: Space,
+    stops: Vec<[f32; 3]>,
+}
+
+impl Palette {
+    /// A gradient through `colors`, evenly spaced from 0 to 1, blended in Lab.
+    pub fn new(colors: &[Srgb<u8>]) -> Palette {
+        assert!(!colors.is_empty(), "a palette needs at least one color");
+        let colors = colors
+            .iter()
+            .map(|color| color.into_format::<f32>())
+            .collect::<Vec<_>>();
+        Palette {
+            stops: colors.iter().map(|c| Space::Lab.encode(*c)).collect(),
+            colors,
+            space: Space::Lab,
+        }
+    }
+
+    /// The same colors, blended in `space` instead.
+    pub fn in_space(mut self, space: Space) -> Palette {
+        self.space = space;
+        self.stops = self.colors.iter().map(|c| space.encode(*c)).collect();
+        self
+    }
+
+    pub fn space(&self) -> Space {
+        self.space
+    }
+
+    /// The bouncing sketches' blue, at 0, to pink, at 1.
+    pub fn blue_pink() -> Palette {
+        Palette::new(&[Srgb::new(0, 110, 255), Srgb::new(249, 0, 229)])
+    }
+
+    /// Green, at 0, to orange, at 1: `blue_pink`'s counterpart, as for `poi`'s second poi.
+    pub fn green_orange() -> Palette {
+        Palette::new(&[Srgb::new(0, 170, 110), Srgb::new(255, 120, 0)])
+    }
+
+    /// Deep blue, at 0, to pale blue, at 1, like water catching the light.
+    pub fn ice() -> Palette {
+        Palette::new(&[Srgb::new(0, 60, 200), Srgb::new(150, 220, 255)])
+    }
+
+    /// Near black, at 0, through dark blue to pale blue, at 1, for water seen from above.
+    pub fn deep_water() -> Palette {
+        Palette::new(&[
+            Srgb::new(5, 10, 40),
+            Srgb::new(20, 70, 130),
+            Srgb::new(200, 240, 255),
+        ])
+    }
+
+    /// Near black, at 0, through `blue_pink` to cream, at 1.
+    pub fn neon() -> Palette {
+        Palette::new(&[
+            Srgb::new(10, 10, 30),
+            Srgb::new(0, 110, 255),
+            Srgb::new(249, 0, 229),
+            Srgb::new(255, 240, 220),
+        ])
+    }
+
+    /// Near black, at 0, through brown and gold to white, at 1, like glowing embers.
+    pub fn ember() -> Palette {
+        Palette::new(&[
+            Srgb::new(5, 5, 15),
+            Srgb::new(120, 60, 0),
+            Srgb::new(255, 200, 40),
+            Srgb::new(255, 255, 230),
+        ])
+    }
+
+    /// Cold blue, at 0, through purple and orange to white hot, at 1.
+    pub fn thermal() -> Palette {
+        Palette::new(&[
+            Srgb::new(10, 20, 90),
+            Srgb::new(120, 30, 140),
+            Srgb::new(240, 110, 20),
+            Srgb::new(255, 250, 220),
+        ])
+    }
+
+    /// The color `t` of the way along, with `t` from 0 to 1 (or held at the ends, outside that).
+    pub fn sample(&self, t: f32) -> Rgb {
+        let last = self.stops.len() - 1;
+        // (NaN comes out as 0.)
+        let t = t.max(0.0).min(1.0) * last as f32;
+        let i = (t as usize).min(last.saturating_sub(1));
+        let f = t - i as f32;
+        let a = self.stops[i];
+        let b = self.stops[(i + 1).min(last)];
+        self.space.decode(self.space.mix(a, b, f))
+    }
+
+    /// `sample(t)` as bytes, for filling images.
+    pub fn sample_u8(&self, t: f32) -> [u8; 3] {
+        let color = self.sample(t);
+        let byte = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+        [byte(color.red), byte(color.green), byte(color.blue)]
+    }
+}
+
+// sRGB's transfer function, both ways, carried on past 0 and 1 in a straight line.
+fn linear(color: Rgb) -> [f32; 3] {
+    let f = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [f(color.red), f(color.green), f(color.blue)]
+}
+
+fn gamma([r, g, b]: [f32; 3]) -> Rgb {
+    let f = |c: f32| {
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    };
+    Rgb::new(f(r), f(g), f(b))
+}
+
+fn times(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    let row = |r: [f32; 3]| r[0] * v[0] + r[1] * v[1] + r[2] * v[2];
+    [row(m[0]), row(m[1]), row(m[2])]
+}
+
+/// Björn Ottosson's Oklab, from and to linear sRGB.
+mod oklab {
+    use super::times;
+
+    const TO_LMS: [[f32; 3]; 3] = [
+        [0.412_221_46, 0.536_332_55, 0.051_445_995],
+        [0.211_903_5, 0.680_699_5, 0.107_396_96],
+        [0.088_302_46, 0.281_718_85, 0.629_978_7],
+    ];
+    const TO_LAB: [[f32; 3]; 3] = [
+        [0.210_454_26, 0.793_617_8, -0.004_072_047],
+        [1.977_998_5, -2.428_592_2, 0.450_593_7],
+        [0.025_904_037, 0.782_771_77, -0.808_675_77],
+    ];
+    const FROM_LAB: [[f32; 3]; 3] = [
+        [1.0, 0.396_337_78, 0.215_803_76],
+        [1.0, -0.105_561_346, -0.063_854_17],
+        [1.0, -0.089_484_18, -1.291_485_5],
+    ];
+    const FROM_LMS: [[f32; 3]; 3] = [
+        [4.076_741_7, -3.307_711_6, 0.230_969_94],
+        [-1.268_438, 2.609_757_4, -0.341_319_38],
+        [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+    ];
+
+    pub fn encode(rgb: [f32; 3]) -> [f32; 3] {
+        let [l, m, s] = times(&TO_LMS, rgb);
+        times(&TO_LAB, [l.cbrt(), m.cbrt(), s.cbrt()])
+    }
+
+    pub fn decode(lab: [f32; 3]) -> [f32; 3] {
+        let [l, m, s] = times(&FROM_LAB, lab);
+        times(&FROM_LMS, [l * l * l, m * m * m, s * s * s])
+    }
+}
+
+/// HSLuv: CIELUV's lightness, chroma and hue, with chroma stretched so that 100 is as saturated as
+/// sRGB goes at that hue and lightness. From and to linear sRGB.
+mod hsluv {
+    use super::{times, PI};
+
+    const TO_XYZ: [[f32; 3]; 3] = [
+        [0.412_390_8, 0.357_584_33, 0.180_480_8],
+        [0.212_639, 0.715_168_65, 0.072_192_32],
+        [0.019_330_818, 0.119_194_78, 0.950_532_14],
+    ];
+    const FROM_XYZ: [[f32; 3]; 3] = [
+        [3.240_97, -1.537_383_2, -0.498_610_76],
+        [-0.969_243_6, 1.875_967_5, 0.041_555_06],
+        [0.055_630_08, -0.203_976_96, 1.056_971_5],
+    ];
+    // the D65 white point's chromaticity.
+    const WHITE_U: f32 = 0.197_83;
+    const WHITE_V: f32 = 0.468_32;
+    const KAPPA: f32 = 903.296_3;
+    const EPSILON: f32 = 0.008_856_452;
+
+    fn lightness(y: f32) -> f32 {
+        if y <= EPSILON {
+            y * KAPPA
+        } else {
+            116.0 * y.cbrt() - 16.0
+        }
+    }
+
+    fn luminance(l: f32) -> f32 {
+        if l <= 8.0 {
+            l / KAPPA
+        } else {
+            ((l + 16.0) / 116.0).powi(3)
+        }
+    }
+
+    /// The most chroma any sRGB color of lightness `l` and hue `h` (in degrees) has: how far out
+    /// from grey, that way, the first of the gamut's six edges is.
+    fn max_chroma(l: f32, h: f32) -> f32 {
+        let sub1 = (l + 16.0).powi(3) / 1_560_896.0;
+        let sub2 = if sub1 > EPSILON { sub1 } else { l / KAPPA };
+        let (sin, cos) = (h * PI / 180.0).sin_cos();
+        let mut chroma = f32::MAX;
+        for [m1, m2, m3] in FROM_XYZ.iter() {
+            for t in 0..2 {
+                let t = t as f32;
+                let top1 = (284_517.0 * m1 - 94_839.0 * m3) * sub2;
+                let top2 = (838_422.0 * m3 + 769_860.0 * m2 + 731_718.0 * m1) * l * sub2
+                    - 769_860.0 * t * l;
+                let bottom = (632_260.0 * m3 - 126_452.0 * m2) * sub2 + 126_452.0 * t;
+                let (slope, intercept) = (top1 / bottom, top2 / bottom);
+                let length = intercept / (sin - slope * cos);
+                if length >= 0.0 {
+                    chroma = chroma.min(length);
+                }
+            }
+        }
+        chroma
+    }
+
+    pub fn encode(rgb: [f32; 3]) -> [f32; 3] {
+        let [x, y, z] = times(&TO_XYZ, rgb);
+        let l = lightness(y);
+        if l < 1e-5 {
+            return [0.0, 0.0, 0.0];
+        }
+        if l > 99.999 {
+            return [0.0, 0.0, 100.0];
+        }
+        let denominator = x + 15.0 * y + 3.0 * z;
+        let u = 13.0 * l * (4.0 * x / denominator - WHITE_U);
+        let v = 13.0 * l * (9.0 * y / denominator - WHITE_V);
+        let chroma = (u * u + v * v).sqrt();
+        let hue = v.atan2(u).to_degrees().rem_euclid(360.0);
+        [hue, chroma / max_chroma(l, hue) * 100.0, l]
+    }
+
+    pub fn decode([hue, saturation, l]: [f32; 3]) -> [f32; 3] {
+        if l < 1e-5 {
+            return [0.0, 0.0, 0.0];
+        }
+        if l > 99.999 {
+            return [1.0, 1.0, 1.0];
+        }
+        let chroma = max_chroma(l, hue) * saturation / 100.0;
+        let (sin, cos) = hue.to_radians().sin_cos();
+        let (u, v) = (chroma * cos, chroma * sin);
+        let u = u / (13.0 * l) + WHITE_U;
+        let v = v / (13.0 * l) + WHITE_V;
+        let y = luminance(l);
+        let x = 9.0 * y * u / (4.0 * v);
+        let z = (9.0 * y - 15.0 * v * y - v * x) / (3.0 * v);
+        times(&FROM_XYZ, [x, y, z])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let palette = Palette::new(&[
+            Srgb::new(0, 0, 0),
+            Srgb::new(255, 0, 0),
+            Srgb::new(255, 255, 255),
+        ]);
+        assert_eq!(palette.sample_u8(0.0), [0, 0, 0]);
+        assert_eq!(palette.sample_u8(0.5), [255, 0, 0]);
+        assert_eq!(palette.sample_u8(1.0), [255, 255, 255]);
+        // held at the ends.
+        assert_eq!(palette.sample_u8(-3.0), [0, 0, 0]);
+        assert_eq!(palette.sample_u8(7.0), [255, 255, 255]);
+        assert_eq!(palette.sample_u8(f32::NAN), [0, 0, 0]);
+        // part way to red is a darker red, not a muddy one.
+        let dark = palette.sample_u8(0.25);
+        assert!(dark[0] > 60 && dark[0] < 255 && dark[1] < 30 && dark[2] < 30);
+
+        let single = Palette::new(&[Srgb::new(10, 20, 30)]);
+        assert_eq!(single.sample_u8(0.7), [10, 20, 30]);
+        assert_eq!(Palette::blue_pink().sample_u8(1.0), [249, 0, 229]);
+    }
+
+    #[test]
+    fn test_spaces() {
+        let close = |a: [f32; 3], b: [f32; 3], tolerance: f32| {
+            a.iter().zip(&b).all(|(a, b)| (a - b).abs() < tolerance)
+        };
+        let red = Rgb::new(1.0, 0.0, 0.0);
+        let blue = Rgb::new(0.0, 0.0, 1.0);
+        assert!(close(Space::Oklab.encode(red), [0.628, 0.225, 0.126], 1e-3));
+        assert!(close(
+            Space::Oklab.encode(Rgb::new(1.0, 1.0, 1.0)),
+            [1.0, 0.0, 0.0],
+            1e-3
+        ));
+        assert!(close(Space::Hsluv.encode(red), [12.18, 100.0, 53.24], 0.05));
+        assert!(close(
+            Space::Hsluv.encode(blue),
+            [265.87, 100.0, 32.30],
+            0.05
+        ));
+
+        // there and back again.
+        for space in &[Space::Lab, Space::Oklab, Space::Hsluv] {
+            for color in &[red, blue, Rgb::new(0.2, 0.7, 0.4), Rgb::new(0.5, 0.5, 0.5)] {
+                let back = space.decode(space.encode(*color));
+                assert!(
+                    close(
+                        [back.red, back.green, back.blue],
+                        [color.red, color.green, color.blue],
+                        1e-3
+                    ),
+                    "{:?} {:?}",
+                    space,
+                    color
+                );
+            }
+        }
+
+        // HSLuv goes round the hue wheel the short way, from red (12) back past 0 to pink.
+        let pink = Space::Hsluv.encode(Rgb::new(1.0, 0.0, 0.6));
+        let between = Space::Hsluv.mix(Space::Hsluv.encode(red), pink, 0.5)[0];
+        assert!(between < 12.0 || between > pink[0]);
+        // and keeps grey's hue out of it.
+        let grey = Space::Hsluv.encode(Rgb::new(0.5, 0.5, 0.5));
+        assert_eq!(Space::Hsluv.mix(grey, pink, 0.3)[0], pink[0]);
+    }
+
+    #[test]
+    fn test_in_space() {
+        let palette = Palette::blue_pink().in_space(Space::Oklab);
+        assert_eq!(palette.space(), Space::Oklab);
+        assert_eq!(palette.sample_u8(0.0), [0, 110, 255]);
+        assert_eq!(palette.sample_u8(1.0), [249, 0, 229]);
+        // halfway from red to green in HSLuv is a yellow as saturated as either.
+        let colors = [Srgb::new(255, 0, 0), Srgb::new(0, 255, 0)];
+        let [r, g, b] = Palette::new(&colors).in_space(Space::Hsluv).sample_u8(0.5);
+        assert!(r > 100 && g > 100 && b < 5, "{:?}", [r, g, b]);
+    }
+}
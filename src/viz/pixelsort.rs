@@ -0,0 +1,151 @@
+//! Pixel sorting: runs of pixels along each row (or column) of an image, wherever they're within
+//! some band of brightness, sorted from darkest to brightest, smearing the picture into streaks
+//! which stop at its darkest and brightest parts.
+//!
+//! Like `viz::halftone`, it's meant for sketches captured with `viz::capture`.
+
+use super::halftone::luminance;
+use nannou::image::{Rgba, RgbaImage};
+use std::ops::Range;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Rows,
+    Columns,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PixelSort {
+    pub direction: Direction,
+    /// The band of luminance, from 0 to 1, pixels have to be in to be sorted.
+    pub low: f32,
+    pub high: f32,
+    /// Whether spans go from brightest to darkest instead, along rows from the left and along
+    /// columns from the top.
+    pub descending: bool,
+}
+
+impl PixelSort {
+    /// Sort the pixels along rows between `low` and `high` luminance, darkest first.
+    pub fn new(low: f32, high: f32) -> PixelSort {
+        PixelSort {
+            direction: Direction::Rows,
+            low,
+            high,
+            descending: false,
+        }
+    }
+
+    /// The runs of `line` in the band, each as long as it can be.
+    pub fn spans(&self, line: &[Rgba<u8>]) -> Vec<Range<usize>> {
+        let mut spans = vec![];
+        let mut start = None;
+        for (i, pixel) in line.iter().enumerate() {
+            let l = luminance(pixel);
+            match (start, l >= self.low && l <= self.high) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    spans.push(s..i);
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        if let Some(s) = start {
+            spans.push(s..line.len());
+        }
+        spans
+    }
+
+    /// Sort the spans along one line of pixels.
+    pub fn sort_line(&self, line: &mut [Rgba<u8>]) {
+        for span in self.spans(line) {
+            let span = &mut line[span];
+            span.sort_by(|a, b| luminance(a).partial_cmp(&luminance(b)).unwrap());
+            if self.descending {
+                span.reverse();
+            }
+        }
+    }
+
+    /// Sort every row, or column, of `image`.
+    pub fn apply(&self, image: &mut RgbaImage) {
+        let (width, height) = image.dimensions();
+        let (lines, length) = match self.direction {
+            Direction::Rows => (height, width),
+            Direction::Columns => (width, height),
+        };
+        let mut line = Vec::with_capacity(length as usize);
+        for i in 0..lines {
+            let at = |j: u32| match self.direction {
+                Direction::Rows => (j, i),
+                Direction::Columns => (i, j),
+            };
+            line.clear();
+            line.extend((0..length).map(|j| {
+                let (x, y) = at(j);
+                *image.get_pixel(x, y)
+            }));
+            self.sort_line(&mut line);
+            for (j, pixel) in line.iter().enumerate() {
+                let (x, y) = at(j as u32);
+                image.put_pixel(x, y, *pixel);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(level: u8) -> Rgba<u8> {
+        Rgba([level, level, level, 255])
+    }
+
+    #[test]
+    fn test_spans() {
+        let sort = PixelSort::new(0.2, 0.8);
+        let mut line = [10, 150, 60, 100, 250, 120, 90]
+            .iter()
+            .map(|l| grey(*l))
+            .collect::<Vec<_>>();
+        assert_eq!(sort.spans(&line), vec![1..4, 5..7]);
+        sort.sort_line(&mut line);
+        let levels = line.iter().map(|p| p.0[0]).collect::<Vec<_>>();
+        // the darkest and brightest stay put, and split the rest into two runs.
+        assert_eq!(levels, vec![10, 60, 100, 150, 250, 90, 120]);
+        assert!(sort.spans(&[grey(0), grey(255)]).is_empty());
+    }
+
+    #[test]
+    fn test_apply() {
+        // a column getting darker downwards, which sorting flips.
+        let mut image = RgbaImage::from_fn(2, 4, |x, y| {
+            if x == 0 {
+                grey(200 - 40 * y as u8)
+            } else {
+                grey(0)
+            }
+        });
+        let mut sort = PixelSort::new(0.0, 1.0);
+        sort.direction = Direction::Columns;
+        sort.apply(&mut image);
+        let column = (0..4)
+            .map(|y| image.get_pixel(0, y).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(column, vec![80, 120, 160, 200]);
+        sort.descending = true;
+        sort.apply(&mut image);
+        let column = (0..4)
+            .map(|y| image.get_pixel(0, y).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(column, vec![200, 160, 120, 80]);
+
+        // sorting rows instead puts the black to the left.
+        sort.direction = Direction::Rows;
+        sort.descending = false;
+        sort.apply(&mut image);
+        assert!((0..4).all(|y| image.get_pixel(0, y).0[0] == 0));
+    }
+}
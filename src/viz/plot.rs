@@ -0,0 +1,208 @@
+//! A little scrolling line chart, for watching a few quantities (like a simulation's energy) change
+//! over time in a corner of a sketch.
+//!
+//! Push a value onto each series every frame; the newest values are at the right edge, and older
+//! ones scroll off the left once the plot is full. Unless given a fixed `range`, the plot stretches
+//! to fit whatever it's showing.
+
+use nannou::color::{rgba, IntoLinSrgba, LinSrgba};
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::Draw;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug)]
+pub struct Plot {
+    /// Where the plot is drawn.
+    pub rect: Rect,
+    /// The values at the bottom and top of the plot, or `None` to fit the values showing.
+    pub range: Option<(f32, f32)>,
+    /// Behind the lines.
+    pub background: LinSrgba,
+    /// For the border and the range's labels.
+    pub foreground: LinSrgba,
+    capacity: usize,
+    series: Vec<Series>,
+}
+
+#[derive(Clone, Debug)]
+struct Series {
+    label: String,
+    color: LinSrgba,
+    values: VecDeque<f32>,
+}
+
+impl Plot {
+    /// An empty plot in `rect`, showing the last `capacity` values of each series, drawn in black
+    /// on translucent white.
+    pub fn new(rect: Rect, capacity: usize) -> Plot {
+        assert!(capacity >= 2, "a plot needs room for at least two values");
+        Plot {
+            rect,
+            range: None,
+            background: rgba(1.0, 1.0, 1.0, 0.75).into_lin_srgba(),
+            foreground: rgba(0.0, 0.0, 0.0, 0.6).into_lin_srgba(),
+            capacity,
+            series: vec![],
+        }
+    }
+
+    /// Add a line to the plot, labelled `label` and drawn in `color`, returning its index for
+    /// `push`.
+    pub fn add_series<C: IntoLinSrgba<f32>>(&mut self, label: &str, color: C) -> usize {
+        self.series.push(Series {
+            label: label.to_string(),
+            color: color.into_lin_srgba(),
+            values: VecDeque::with_capacity(self.capacity),
+        });
+        self.series.len() - 1
+    }
+
+    /// Add the newest value to `series`, scrolling off its oldest if it's full.
+    pub fn push(&mut self, series: usize, value: f32) {
+        let values = &mut self.series[series].values;
+        if values.len() == self.capacity {
+            values.pop_front();
+        }
+        values.push_back(value);
+    }
+
+    /// Forget every value, keeping the series.
+    pub fn clear(&mut self) {
+        for series in &mut self.series {
+            series.values.clear();
+        }
+    }
+
+    /// The values at the bottom and top of the plot: `range`, or else just wide enough for every
+    /// (finite) value showing.
+    pub fn bounds(&self) -> (f32, f32) {
+        if let Some(range) = self.range {
+            return range;
+        }
+        let values = self
+            .series
+            .iter()
+            .flat_map(|series| series.values.iter())
+            .filter(|value| value.is_finite());
+        let (min, max) = values.fold((f32::MAX, f32::MIN), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+        if min > max {
+            // nothing to show yet.
+            (0.0, 1.0)
+        } else if max - min < 1e-6 {
+            // a flat line, in the middle.
+            (min - 1.0, max + 1.0)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Where each of `series`' values is drawn, oldest first, leaving out any that aren't finite.
+    fn points(&self, series: usize) -> Vec<Vector2> {
+        let values = &self.series[series].values;
+        let (bottom, top) = self.bounds();
+        let spacing = self.rect.w() / (self.capacity - 1) as f32;
+        let newest = values.len() as f32 - 1.0;
+        values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(i, value)| {
+                let height = (value - bottom) / (top - bottom);
+                vec2(
+                    self.rect.right() - (newest - i as f32) * spacing,
+                    self.rect.bottom() + height.max(0.0).min(1.0) * self.rect.h(),
+                )
+            })
+            .collect()
+    }
+
+    /// Draw the plot: its lines, each series' label and newest value at the top left, and the
+    /// range at the right.
+    pub fn draw(&self, draw: &Draw) {
+        let rect = self.rect;
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.wh())
+            .color(self.background)
+            .stroke_weight(1.0)
+            .stroke(self.foreground);
+        for (i, series) in self.series.iter().enumerate() {
+            let points = self.points(i);
+            if points.len() >= 2 {
+                draw.polyline()
+                    .weight(1.5)
+                    .points(points)
+                    .color(series.color);
+            }
+            let label = match series.values.back() {
+                Some(value) => format!("{}: {:.3}", series.label, value),
+                None => series.label.clone(),
+            };
+            draw.text(&label)
+                .xy(rect.xy() - vec2(0.0, 14.0 * i as f32))
+                .wh(rect.pad(4.0).wh())
+                .left_justify()
+                .align_text_top()
+                .font_size(11)
+                .color(series.color);
+        }
+        let (bottom, top) = self.bounds();
+        for (value, align_top) in &[(top, true), (bottom, false)] {
+            let text = draw
+                .text(&format!("{:.3}", value))
+                .xy(rect.xy())
+                .wh(rect.pad(4.0).wh())
+                .right_justify()
+                .font_size(10)
+                .color(self.foreground);
+            if *align_top {
+                text.align_text_top();
+            } else {
+                text.align_text_bottom();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::color::rgb;
+
+    #[test]
+    fn test_points() {
+        let mut plot = Plot::new(Rect::from_x_y_w_h(0.0, 0.0, 100.0, 50.0), 11);
+        assert_eq!(plot.bounds(), (0.0, 1.0));
+        let energy = plot.add_series("energy", rgb(1.0, 0.0, 0.0));
+        let strain = plot.add_series("strain", rgb(0.0, 0.0, 1.0));
+        plot.push(energy, 2.0);
+        // a flat line sits in the middle.
+        assert_eq!(plot.bounds(), (1.0, 3.0));
+        assert_eq!(plot.points(energy), vec![vec2(50.0, 0.0)]);
+
+        for i in 0..20 {
+            plot.push(energy, i as f32);
+        }
+        plot.push(strain, f32::NAN);
+        plot.push(strain, -5.0);
+        // only the last 11 are kept, the newest at the right edge, scaled to fit.
+        assert_eq!(plot.bounds(), (-5.0, 19.0));
+        let points = plot.points(energy);
+        assert_eq!(points.len(), 11);
+        assert_eq!(points[10], vec2(50.0, 25.0));
+        assert_eq!(points[0].x, -50.0);
+        assert!((points[0].y - (-25.0 + 14.0 / 24.0 * 50.0)).abs() < 1e-4);
+        // the NaN is left out, and the rest starts at the right.
+        assert_eq!(plot.points(strain), vec![vec2(50.0, -25.0)]);
+
+        // values outside a fixed range stay inside the plot.
+        plot.range = Some((0.0, 10.0));
+        assert_eq!(plot.points(energy)[10], vec2(50.0, 25.0));
+        assert_eq!(plot.points(strain)[0], vec2(50.0, -25.0));
+
+        plot.clear();
+        assert!(plot.points(energy).is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+//! Sizing a sketch's world to its window, so it looks the same on any display.
+//!
+//! nannou draws in points, which a hiDPI display shows with two or more pixels each; a scale
+//! tuned by eye in pixels on one monitor comes out the wrong size on another, and even in points
+//! it only suits one size of window. Scaling the world to a share of the window instead suits
+//! them all:
+//!
+//! ```ignore
+//! // a unit of simulation spans 90% of the window's shorter side.
+//! let draw = draw.scale(window_scale(&app.main_window(), 0.9));
+//! ```
+
+use nannou::window::Window;
+
+/// How many points a unit of the world should be for it to span `fraction` of the shorter side of
+/// a window `physical` pixels in size, shown at `scale_factor` pixels a point.
+pub fn world_scale(physical: (u32, u32), scale_factor: f32, fraction: f32) -> f32 {
+    assert!(
+        scale_factor > 0.0,
+        "a window has to have some pixels a point"
+    );
+    let (w, h) = physical;
+    w.min(h) as f32 / scale_factor * fraction
+}
+
+/// `world_scale` for `window`, as it is now.
+pub fn window_scale(window: &Window, fraction: f32) -> f32 {
+    world_scale(window.inner_size_pixels(), window.scale_factor(), fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_world_scale() {
+        // the same window on a normal display and a hiDPI one.
+        assert_eq!(world_scale((1024, 768), 1.0, 0.5), 384.0);
+        assert_eq!(world_scale((2048, 1536), 2.0, 0.5), 384.0);
+        // by the shorter side, whichever way round.
+        assert_eq!(world_scale((600, 1200), 1.5, 1.0), 400.0);
+    }
+}
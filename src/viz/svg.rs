@@ -0,0 +1,373 @@
+//! Exporting what a sketch draws as SVG, at full vector resolution for printing.
+//!
+//! nannou draws straight to the GPU, so there's nothing to read shapes back from; instead the
+//! shapes worth exporting are drawn through a `VectorRecorder`, which hands them on to the `Draw`
+//! and, for the frame after its key's pressed, writes them out as SVG too:
+//!
+//! ```ignore
+//! // in key presses:
+//! model.svg.key(key);
+//! // in the view:
+//! model.svg.begin(app.window_rect());
+//! model.svg.ellipse(&draw, pos, vec2(10.0, 10.0), color);
+//! model.svg.finish();
+//! ```
+//!
+//! Only lines, polylines, polygons, triangles, ellipses and text are recorded. They're recorded
+//! where they are in the window, so have to be drawn with a `Draw` which hasn't been moved,
+//! scaled or rotated.
+
+use nannou::color::{IntoLinSrgba, LinSrgba, Srgb};
+use nannou::event::Key;
+use nannou::geom::{Rect, Vector2};
+use nannou::Draw;
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct VectorRecorder {
+    /// The key which exports the next frame.
+    pub key: Key,
+    /// Where SVGs are saved.
+    pub dir: PathBuf,
+    name: String,
+    // whether the next frame's to be exported.
+    requested: Cell<bool>,
+    // the frame being exported: its bounds, and its elements so far.
+    recording: RefCell<Option<(Rect, String)>>,
+    exported: Cell<usize>,
+}
+
+impl VectorRecorder {
+    /// Export the frame after `V`'s pressed, as `name_000.svg` and on, in the current directory.
+    pub fn new(name: &str) -> VectorRecorder {
+        VectorRecorder {
+            key: Key::V,
+            dir: PathBuf::from("."),
+            name: name.to_string(),
+            requested: Cell::new(false),
+            recording: RefCell::new(None),
+            exported: Cell::new(0),
+        }
+    }
+
+    /// Export the next frame if `key` is the `key`.
+    pub fn key(&self, key: Key) {
+        if key == self.key {
+            self.export();
+        }
+    }
+
+    /// Export the next frame.
+    pub fn export(&self) {
+        self.requested.set(true);
+    }
+
+    /// Start a frame, the window being `bounds`; it's recorded if an export's been asked for.
+    pub fn begin(&self, bounds: Rect) {
+        if self.requested.get() {
+            *self.recording.borrow_mut() = Some((bounds, String::new()));
+        }
+    }
+
+    /// Finish the frame, saving it if it was recorded. Returns where.
+    pub fn finish(&self) -> Option<PathBuf> {
+        let svg = self.take()?;
+        let path = self
+            .dir
+            .join(format!("{}_{:03}.svg", self.name, self.exported.get()));
+        fs::write(&path, svg).unwrap_or_else(|e| panic!("can't save {}: {}", path.display(), e));
+        println!("saved {}", path.display());
+        self.exported.set(self.exported.get() + 1);
+        Some(path)
+    }
+
+    // the recorded frame, as an SVG document.
+    fn take(&self) -> Option<String> {
+        let (bounds, elements) = self.recording.borrow_mut().take()?;
+        self.requested.set(false);
+        Some(format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            elements,
+            w = bounds.w(),
+            h = bounds.h(),
+        ))
+    }
+
+    pub fn recording(&self) -> bool {
+        self.recording.borrow().is_some()
+    }
+
+    /// Fill the window with `color`.
+    pub fn background<C: IntoLinSrgba<f32>>(&self, draw: &Draw, color: C) {
+        let color = color.into_lin_srgba();
+        draw.background().color(color);
+        self.record(|bounds| {
+            format!(
+                "<rect width=\"{}\" height=\"{}\" {}/>",
+                bounds.w(),
+                bounds.h(),
+                paint("fill", color)
+            )
+        });
+    }
+
+    pub fn line<C: IntoLinSrgba<f32>>(
+        &self,
+        draw: &Draw,
+        start: Vector2,
+        end: Vector2,
+        weight: f32,
+        color: C,
+    ) {
+        let color = color.into_lin_srgba();
+        draw.line()
+            .start(start)
+            .end(end)
+            .weight(weight)
+            .color(color);
+        self.record(|bounds| {
+            let (a, b) = (point(bounds, start), point(bounds, end));
+            format!(
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke-width=\"{}\" {}/>",
+                a.0,
+                a.1,
+                b.0,
+                b.1,
+                weight,
+                paint("stroke", color)
+            )
+        });
+    }
+
+    pub fn polyline<C: IntoLinSrgba<f32>>(
+        &self,
+        draw: &Draw,
+        points: &[Vector2],
+        weight: f32,
+        color: C,
+    ) {
+        let color = color.into_lin_srgba();
+        draw.polyline()
+            .weight(weight)
+            .points(points.iter().cloned())
+            .color(color);
+        self.record(|bounds| {
+            format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke-width=\"{}\" {}/>",
+                points_attr(bounds, points),
+                weight,
+                paint("stroke", color)
+            )
+        });
+    }
+
+    /// A polygon filled with `color`, and outlined if there's a `stroke` color and weight.
+    pub fn polygon<C: IntoLinSrgba<f32>>(
+        &self,
+        draw: &Draw,
+        points: &[Vector2],
+        color: C,
+        stroke: Option<(LinSrgba, f32)>,
+    ) {
+        let color = color.into_lin_srgba();
+        let polygon = draw.polygon().color(color);
+        let polygon = match stroke {
+            Some((stroke, weight)) => polygon.stroke(stroke).stroke_weight(weight),
+            None => polygon,
+        };
+        polygon.points(points.iter().cloned());
+        self.record(|bounds| {
+            let stroke = match stroke {
+                Some((stroke, weight)) => {
+                    format!(" stroke-width=\"{}\" {}", weight, paint("stroke", stroke))
+                }
+                None => String::new(),
+            };
+            format!(
+                "<polygon points=\"{}\" {}{}/>",
+                points_attr(bounds, points),
+                paint("fill", color),
+                stroke
+            )
+        });
+    }
+
+    pub fn tri<C: IntoLinSrgba<f32>>(&self, draw: &Draw, points: [Vector2; 3], color: C) {
+        let color = color.into_lin_srgba();
+        draw.tri()
+            .points(points[0], points[1], points[2])
+            .color(color);
+        self.record(|bounds| {
+            format!(
+                "<polygon points=\"{}\" {}/>",
+                points_attr(bounds, &points),
+                paint("fill", color)
+            )
+        });
+    }
+
+    /// An ellipse `wh` across, centered on `xy`.
+    pub fn ellipse<C: IntoLinSrgba<f32>>(&self, draw: &Draw, xy: Vector2, wh: Vector2, color: C) {
+        let color = color.into_lin_srgba();
+        draw.ellipse().xy(xy).wh(wh).color(color);
+        self.record(|bounds| {
+            let (x, y) = point(bounds, xy);
+            format!(
+                "<ellipse cx=\"{:.2}\" cy=\"{:.2}\" rx=\"{:.2}\" ry=\"{:.2}\" {}/>",
+                x,
+                y,
+                wh.x / 2.0,
+                wh.y / 2.0,
+                paint("fill", color)
+            )
+        });
+    }
+
+    /// Text centered on `xy`, a line at a time.
+    pub fn text<C: IntoLinSrgba<f32>>(
+        &self,
+        draw: &Draw,
+        text: &str,
+        xy: Vector2,
+        font_size: u32,
+        color: C,
+    ) {
+        let color = color.into_lin_srgba();
+        draw.text(text).xy(xy).font_size(font_size).color(color);
+        self.record(|bounds| {
+            let (x, y) = point(bounds, xy);
+            // nannou spaces lines about this far apart.
+            let line_height = font_size as f32 * 1.2;
+            let lines = text.lines().collect::<Vec<_>>();
+            let top = y - line_height * (lines.len() as f32 - 1.0) / 2.0;
+            let mut element = String::new();
+            for (i, line) in lines.iter().enumerate() {
+                write!(
+                    element,
+                    "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" text-anchor=\"middle\" \
+                     dominant-baseline=\"middle\" {}>{}</text>",
+                    x,
+                    top + line_height * i as f32,
+                    font_size,
+                    paint("fill", color),
+                    escape(line)
+                )
+                .unwrap();
+            }
+            element
+        });
+    }
+
+    // add an element made by `element` from the frame's bounds, if the frame's being recorded.
+    fn record<F: FnOnce(Rect) -> String>(&self, element: F) {
+        if let Some((bounds, elements)) = &mut *self.recording.borrow_mut() {
+            elements.push_str(&element(*bounds));
+            elements.push('\n');
+        }
+    }
+}
+
+// SVG's y axis points down, from the top left corner.
+fn point(bounds: Rect, p: Vector2) -> (f32, f32) {
+    (p.x - bounds.left(), bounds.top() - p.y)
+}
+
+fn points_attr(bounds: Rect, points: &[Vector2]) -> String {
+    points
+        .iter()
+        .map(|p| {
+            let (x, y) = point(bounds, *p);
+            format!("{:.2},{:.2}", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// `attribute` (fill or stroke) set to `color`, and its opacity if it's see-through.
+fn paint(attribute: &str, color: LinSrgba) -> String {
+    let srgb = Srgb::from_linear(color.color);
+    let byte = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+    let mut paint = format!(
+        "{}=\"#{:02x}{:02x}{:02x}\"",
+        attribute,
+        byte(srgb.red),
+        byte(srgb.green),
+        byte(srgb.blue)
+    );
+    if color.alpha < 1.0 {
+        write!(paint, " {}-opacity=\"{:.3}\"", attribute, color.alpha).unwrap();
+    }
+    paint
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::color::{rgba, Rgba};
+    use nannou::geom::vec2;
+
+    #[test]
+    fn test_export() {
+        let svg = VectorRecorder::new("test");
+        let draw = Draw::new();
+        let bounds = Rect::from_w_h(200.0, 100.0);
+        let red = rgba(1.0, 0.0, 0.0, 1.0);
+
+        // nothing's recorded until an export's asked for.
+        svg.begin(bounds);
+        svg.line(&draw, vec2(0.0, 0.0), vec2(10.0, 0.0), 2.0, red);
+        assert!(!svg.recording());
+        assert_eq!(svg.take(), None);
+
+        svg.key(Key::A);
+        svg.begin(bounds);
+        assert!(!svg.recording());
+        svg.key(Key::V);
+        svg.begin(bounds);
+        assert!(svg.recording());
+        svg.line(&draw, vec2(-100.0, 50.0), vec2(100.0, -50.0), 2.0, red);
+        svg.ellipse(
+            &draw,
+            vec2(0.0, 0.0),
+            vec2(20.0, 10.0),
+            rgba(0.0, 0.0, 1.0, 0.5),
+        );
+        svg.tri(
+            &draw,
+            [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)],
+            red,
+        );
+        svg.text(&draw, "a < b\nc", vec2(0.0, 0.0), 10, red);
+        let doc = svg.take().unwrap();
+        assert!(doc.starts_with("<svg"));
+        assert!(doc.contains("viewBox=\"0 0 200 100\""));
+        // flipped, from the top left corner.
+        assert!(doc.contains(
+            "<line x1=\"0.00\" y1=\"0.00\" x2=\"200.00\" y2=\"100.00\" stroke-width=\"2\" \
+             stroke=\"#ff0000\"/>"
+        ));
+        assert!(doc.contains("rx=\"10.00\" ry=\"5.00\" fill=\"#0000ff\" fill-opacity=\"0.500\""));
+        assert!(doc.contains("points=\"100.00,50.00 110.00,50.00 100.00,40.00\""));
+        assert!(doc.contains(">a &lt; b</text>"));
+        assert!(doc.contains("y=\"56.00\""));
+
+        // only the one frame.
+        svg.begin(bounds);
+        assert!(!svg.recording());
+    }
+
+    #[test]
+    fn test_paint() {
+        let gray: Rgba = rgba(0.5, 0.5, 0.5, 1.0);
+        assert_eq!(paint("fill", gray.into_lin_srgba()), "fill=\"#808080\"");
+    }
+}
@@ -0,0 +1,106 @@
+//! Kaleidoscopes: whatever's drawn in one wedge, repeated round a center, and optionally mirrored
+//! too, the way a kaleidoscope's mirrors repeat the beads between them.
+//!
+//! The copies are made with `Draw`'s own transforms, so anything drawn through them, lines,
+//! meshes, trails and all, is copied exactly.
+
+use nannou::geom::{vec2, Vector2};
+use nannou::Draw;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Symmetry {
+    /// How many times round the center things are repeated; 1 for just once.
+    pub folds: usize,
+    /// Whether each copy is mirrored as well, across the line from the center through the
+    /// middle of its wedge.
+    pub mirror: bool,
+    pub center: Vector2,
+}
+
+impl Symmetry {
+    /// `folds` copies round the origin, unmirrored.
+    pub fn new(folds: usize) -> Symmetry {
+        assert!(folds > 0, "there has to be at least one copy");
+        Symmetry {
+            folds,
+            mirror: false,
+            center: vec2(0.0, 0.0),
+        }
+    }
+
+    /// How wide each wedge is, in radians.
+    pub fn wedge(&self) -> f32 {
+        2.0 * PI / self.folds as f32
+    }
+
+    /// How many copies there are in all.
+    pub fn copies(&self) -> usize {
+        if self.mirror {
+            self.folds * 2
+        } else {
+            self.folds
+        }
+    }
+
+    /// Call `wedge` with `draw` transformed for each copy in turn, starting with one which leaves
+    /// things where they are.
+    pub fn draw(&self, draw: &Draw, mut wedge: impl FnMut(&Draw)) {
+        for i in 0..self.folds {
+            // turned round the center, moved to the origin and back; mirrored ones are flipped
+            // across the x axis through the center first.
+            let turned = draw.xy(self.center).rotate(self.wedge() * i as f32);
+            wedge(&turned.xy(-self.center));
+            if self.mirror {
+                wedge(&turned.scale_y(-1.0).xy(-self.center));
+            }
+        }
+    }
+
+    /// Where every copy of `pos` ends up, in the order `draw` draws them.
+    pub fn images(&self, pos: Vector2) -> Vec<Vector2> {
+        let d = pos - self.center;
+        let mut images = Vec::with_capacity(self.copies());
+        for i in 0..self.folds {
+            let angle = self.wedge() * i as f32;
+            images.push(self.center + d.rotate(angle));
+            if self.mirror {
+                images.push(self.center + vec2(d.x, -d.y).rotate(angle));
+            }
+        }
+        images
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_images() {
+        let mut symmetry = Symmetry::new(4);
+        symmetry.center = vec2(1.0, 1.0);
+        let images = symmetry.images(vec2(3.0, 2.0));
+        let expected = [(3.0, 2.0), (0.0, 3.0), (-1.0, 0.0), (2.0, -1.0)];
+        assert_eq!(images.len(), expected.len());
+        for (image, (x, y)) in images.iter().zip(&expected) {
+            assert!((*image - vec2(*x, *y)).magnitude() < 1e-5, "{:?}", images);
+        }
+
+        // mirrored, every point has a twin the other side of its wedge's middle, and points on
+        // the middle are their own twins.
+        symmetry.mirror = true;
+        symmetry.folds = 3;
+        assert_eq!(symmetry.copies(), 6);
+        let images = symmetry.images(vec2(3.0, 2.0));
+        assert_eq!(images.len(), 6);
+        assert!((images[1] - vec2(3.0, 0.0)).magnitude() < 1e-5);
+        for image in &images {
+            assert!(((*image - symmetry.center).magnitude() - 5f32.sqrt()).abs() < 1e-5);
+        }
+        let images = symmetry.images(vec2(2.0, 1.0));
+        for pair in images.chunks(2) {
+            assert!((pair[0] - pair[1]).magnitude() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Wallpaper: whatever's drawn around the origin, repeated across a whole window under one of
+//! the wallpaper groups, so it tiles seamlessly.
+//!
+//! Each group here turns and mirrors the drawing about the origin some number of ways (its point
+//! group), then repeats all of those copies at every point of a lattice. That covers the groups
+//! without glide reflections; named as crystallographers do, p4m is a square lattice with the
+//! eight turns and mirrors of a square at each point, p6 a hexagonal one with six turns, and so
+//! on.
+
+use nannou::geom::{vec2, Rect, Vector2};
+use nannou::Draw;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Group {
+    P1,
+    P2,
+    Pm,
+    Pmm,
+    Cmm,
+    P4,
+    P4m,
+    P3,
+    P3m1,
+    P31m,
+    P6,
+    P6m,
+}
+
+/// The shape of the grid the copies are repeated on.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Lattice {
+    Square,
+    Rectangular,
+    /// A rectangular lattice with another point in the middle of each rectangle.
+    Rhombic,
+    Hexagonal,
+}
+
+impl Group {
+    pub const ALL: [Group; 12] = [
+        Group::P1,
+        Group::P2,
+        Group::Pm,
+        Group::Pmm,
+        Group::Cmm,
+        Group::P4,
+        Group::P4m,
+        Group::P3,
+        Group::P3m1,
+        Group::P31m,
+        Group::P6,
+        Group::P6m,
+    ];
+
+    pub fn lattice(self) -> Lattice {
+        match self {
+            Group::P1 | Group::P2 | Group::P4 | Group::P4m => Lattice::Square,
+            Group::Pm | Group::Pmm => Lattice::Rectangular,
+            Group::Cmm => Lattice::Rhombic,
+            Group::P3 | Group::P3m1 | Group::P31m | Group::P6 | Group::P6m => Lattice::Hexagonal,
+        }
+    }
+
+    /// The copies made at each lattice point: how far each is turned, in radians, and whether
+    /// it's mirrored (across the x axis) before being turned.
+    pub fn copies(self) -> Vec<(f32, bool)> {
+        let turns = |n: usize, offset: f32| -> Vec<f32> {
+            (0..n)
+                .map(|i| offset + 2.0 * PI * i as f32 / n as f32)
+                .collect()
+        };
+        let (turned, mirrored) = match self {
+            Group::P1 => (turns(1, 0.0), vec![]),
+            Group::P2 => (turns(2, 0.0), vec![]),
+            Group::Pm => (turns(1, 0.0), turns(1, 0.0)),
+            Group::Pmm | Group::Cmm => (turns(2, 0.0), turns(2, 0.0)),
+            Group::P4 => (turns(4, 0.0), vec![]),
+            Group::P4m => (turns(4, 0.0), turns(4, 0.0)),
+            Group::P3 => (turns(3, 0.0), vec![]),
+            // mirrored across lines at right angles to the lattice's, through the 3-fold centers.
+            Group::P3m1 => (turns(3, 0.0), turns(3, PI)),
+            // and across the lattice's own lines, missing them.
+            Group::P31m => (turns(3, 0.0), turns(3, 0.0)),
+            Group::P6 => (turns(6, 0.0), vec![]),
+            Group::P6m => (turns(6, 0.0), turns(6, 0.0)),
+        };
+        let turned = turned.into_iter().map(|angle| (angle, false));
+        turned
+            .chain(mirrored.into_iter().map(|angle| (angle, true)))
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tiling {
+    pub group: Group,
+    /// How far apart the lattice points are along x.
+    pub size: f32,
+    /// How tall a rectangular or rhombic lattice's rectangles are, as a fraction of `size`.
+    pub aspect: f32,
+    /// Where one of the lattice points is.
+    pub origin: Vector2,
+}
+
+impl Tiling {
+    /// `group` repeated every `size` across, on a lattice through the origin.
+    pub fn new(group: Group, size: f32) -> Tiling {
+        Tiling {
+            group,
+            size,
+            aspect: 0.75,
+            origin: vec2(0.0, 0.0),
+        }
+    }
+
+    /// The two steps between neighbouring lattice points.
+    pub fn basis(&self) -> (Vector2, Vector2) {
+        let (w, h) = (self.size, self.size * self.aspect);
+        match self.group.lattice() {
+            Lattice::Square => (vec2(w, 0.0), vec2(0.0, w)),
+            Lattice::Rectangular => (vec2(w, 0.0), vec2(0.0, h)),
+            Lattice::Rhombic => (vec2(w / 2.0, h / 2.0), vec2(w / 2.0, -h / 2.0)),
+            Lattice::Hexagonal => (vec2(w, 0.0), vec2(w / 2.0, w * 3f32.sqrt() / 2.0)),
+        }
+    }
+
+    /// Every lattice point close enough to `rect` that copies drawn up to `size` around it might
+    /// show.
+    pub fn points(&self, rect: Rect) -> Vec<Vector2> {
+        let (a, b) = self.basis();
+        // how many of each step it takes to get to each corner.
+        let det = a.perp_dot(b);
+        let steps = |p: Vector2| {
+            let d = p - self.origin;
+            vec2(d.perp_dot(b) / det, a.perp_dot(d) / det)
+        };
+        let corners = [
+            rect.bottom_left(),
+            rect.bottom_right(),
+            rect.top_left(),
+            rect.top_right(),
+        ]
+        .iter()
+        .map(|c| steps(*c))
+        .collect::<Vec<_>>();
+        let range = |f: fn(&Vector2) -> f32| {
+            let lo = corners.iter().map(f).fold(f32::MAX, f32::min).floor() as i32 - 1;
+            let hi = corners.iter().map(f).fold(f32::MIN, f32::max).ceil() as i32 + 1;
+            lo..=hi
+        };
+        // a slanted lattice's steps cover a slanted patch, so its corners are cut off.
+        let margin = a.magnitude() + b.magnitude();
+        let (left, right) = (rect.left() - margin, rect.right() + margin);
+        let (bottom, top) = (rect.bottom() - margin, rect.top() + margin);
+        let mut points = vec![];
+        for i in range(|s| s.x) {
+            for j in range(|s| s.y) {
+                let p = self.origin + a * i as f32 + b * j as f32;
+                if p.x >= left && p.x <= right && p.y >= bottom && p.y <= top {
+                    points.push(p);
+                }
+            }
+        }
+        points
+    }
+
+    /// Call `motif` with `draw` transformed for every copy at every lattice point over `rect`.
+    pub fn draw(&self, draw: &Draw, rect: Rect, mut motif: impl FnMut(&Draw)) {
+        let copies = self.group.copies();
+        for point in self.points(rect) {
+            let moved = draw.xy(point);
+            for &(angle, mirrored) in &copies {
+                let turned = moved.rotate(angle);
+                if mirrored {
+                    motif(&turned.scale_y(-1.0));
+                } else {
+                    motif(&turned);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // where a copy puts `p`, about the origin.
+    fn apply((angle, mirrored): (f32, bool), p: Vector2) -> Vector2 {
+        let p = if mirrored { vec2(p.x, -p.y) } else { p };
+        p.rotate(angle)
+    }
+
+    #[test]
+    fn test_groups() {
+        let orders = Group::ALL
+            .iter()
+            .map(|g| g.copies().len())
+            .collect::<Vec<_>>();
+        assert_eq!(orders, vec![1, 2, 2, 4, 4, 4, 8, 3, 6, 6, 6, 12]);
+
+        let p = vec2(0.3, 0.1);
+        for group in &Group::ALL {
+            let copies = group.copies();
+            let images = copies.iter().map(|c| apply(*c, p)).collect::<Vec<_>>();
+            // each copy of a copy is another copy.
+            for a in &copies {
+                for image in &images {
+                    let twice = apply(*a, *image);
+                    assert!(
+                        images.iter().any(|i| (*i - twice).magnitude() < 1e-5),
+                        "{:?}",
+                        group
+                    );
+                }
+            }
+            // and turns and mirrors the lattice onto itself.
+            let tiling = Tiling::new(*group, 1.0);
+            let (a, b) = tiling.basis();
+            let points = tiling.points(Rect::from_w_h(10.0, 10.0));
+            for copy in &copies {
+                for step in &[a, b] {
+                    let moved = apply(*copy, *step);
+                    assert!(
+                        points.iter().any(|p| (*p - moved).magnitude() < 1e-4),
+                        "{:?} {:?}",
+                        group,
+                        copy
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_points() {
+        let mut tiling = Tiling::new(Group::P6, 10.0);
+        tiling.origin = vec2(3.0, 4.0);
+        let rect = Rect::from_w_h(100.0, 60.0);
+        let points = tiling.points(rect);
+        assert!(points.contains(&vec2(3.0, 4.0)));
+        // every spot on the window is within a step of some lattice point.
+        for x in -5..=5 {
+            for y in -3..=3 {
+                let spot = vec2(x as f32 * 10.0, y as f32 * 10.0);
+                assert!(points.iter().any(|p| (*p - spot).magnitude() <= 10.0));
+            }
+        }
+        // and they don't go too far past it.
+        assert!(points
+            .iter()
+            .all(|p| p.x.abs() <= 50.0 + 20.0 && p.y.abs() <= 30.0 + 20.0));
+    }
+}
@@ -0,0 +1,164 @@
+//! Trails: where something has been lately, drawn as a line tapering and fading towards its tail.
+//!
+//! The older sketches leave trails by covering each frame with a nearly transparent rect rather
+//! than clearing it, so everything they've drawn fades slowly into the background. That only
+//! works if nothing else on screen moves, and never quite fades away. A `Trail` instead keeps its
+//! last few positions in a ring buffer, and is redrawn crisply every frame.
+
+use nannou::color::IntoLinSrgba;
+use nannou::geom::{vec2, Vector2};
+use nannou::Draw;
+
+#[derive(Clone, Debug)]
+pub struct Trail {
+    /// How wide the trail is at its head; it narrows to nothing at its tail.
+    pub weight: f32,
+    capacity: usize,
+    // the last `capacity` positions; once full, `next` is where the oldest is, about to be
+    // overwritten.
+    points: Vec<Vector2>,
+    next: usize,
+}
+
+impl Trail {
+    /// An empty trail, which will remember the last `capacity` positions.
+    pub fn new(capacity: usize) -> Trail {
+        assert!(
+            capacity >= 2,
+            "a trail needs at least two points to be seen"
+        );
+        Trail {
+            weight: 1.0,
+            capacity,
+            points: Vec::with_capacity(capacity),
+            next: 0,
+        }
+    }
+
+    /// Add the newest position, forgetting the oldest if the trail is full.
+    pub fn push(&mut self, pos: Vector2) {
+        if self.points.len() < self.capacity {
+            self.points.push(pos);
+        } else {
+            self.points[self.next] = pos;
+            self.next = (self.next + 1) % self.capacity;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.next = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The positions remembered, from the oldest to the newest.
+    pub fn points(&self) -> impl Iterator<Item = Vector2> + '_ {
+        self.points[self.next..]
+            .iter()
+            .chain(&self.points[..self.next])
+            .cloned()
+    }
+
+    /// The newest position.
+    pub fn head(&self) -> Option<Vector2> {
+        self.points().last()
+    }
+
+    /// The trail as a strip of triangles: a pair of vertices either side of each position, each
+    /// with how far it is from the tail (from just above 0) to the head (1), and the indices of
+    /// the triangles between them.
+    fn strip(&self) -> (Vec<(Vector2, f32)>, Vec<usize>) {
+        let points = self.points().collect::<Vec<_>>();
+        let n = points.len();
+        let mut vertices = Vec::with_capacity(n * 2);
+        let mut normal = vec2(0.0, 0.0);
+        for (i, pos) in points.iter().enumerate() {
+            // square to the line through the neighbours, so the joints bend smoothly; where the
+            // trail stood still, the same way as the last point.
+            let along = points[(i + 1).min(n - 1)] - points[i.max(1) - 1];
+            if along.magnitude2() > 0.0 {
+                normal = vec2(-along.y, along.x).normalize();
+            }
+            let t = (i + 1) as f32 / n as f32;
+            let side = normal * (self.weight * t / 2.0);
+            vertices.push((*pos + side, t));
+            vertices.push((*pos - side, t));
+        }
+        let indices = (0..n.saturating_sub(1))
+            .flat_map(|i| {
+                let j = i * 2;
+                vec![j, j + 1, j + 2, j + 1, j + 3, j + 2]
+            })
+            .collect();
+        (vertices, indices)
+    }
+
+    /// Draw the trail in `color`, fading to transparent at its tail.
+    pub fn draw<C: IntoLinSrgba<f32>>(&self, draw: &Draw, color: C) {
+        if self.len() < 2 {
+            return;
+        }
+        let color = color.into_lin_srgba();
+        let (vertices, indices) = self.strip();
+        let vertices = vertices.into_iter().map(|(pos, t)| {
+            let mut color = color;
+            color.alpha *= t;
+            (pos, color)
+        });
+        draw.mesh().indexed_colored(vertices, indices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut trail = Trail::new(3);
+        assert!(trail.is_empty());
+        assert_eq!(trail.head(), None);
+        for i in 0..5 {
+            trail.push(vec2(i as f32, 0.0));
+        }
+        // only the last three are kept, oldest first.
+        let xs = trail.points().map(|p| p.x).collect::<Vec<_>>();
+        assert_eq!(xs, vec![2.0, 3.0, 4.0]);
+        assert_eq!(trail.head(), Some(vec2(4.0, 0.0)));
+        trail.clear();
+        assert_eq!(trail.len(), 0);
+        trail.push(vec2(7.0, 0.0));
+        assert_eq!(trail.points().collect::<Vec<_>>(), vec![vec2(7.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_strip() {
+        let mut trail = Trail::new(10);
+        trail.weight = 2.0;
+        for i in 0..4 {
+            trail.push(vec2(i as f32, 0.0));
+        }
+        // stopped at the end.
+        trail.push(vec2(3.0, 0.0));
+        let (vertices, indices) = trail.strip();
+        assert_eq!(vertices.len(), 10);
+        assert_eq!(indices.len(), 4 * 6);
+        assert!(indices.iter().all(|i| *i < vertices.len()));
+        // full width and opacity at the head, and narrowing and fading towards the tail.
+        let (left, t) = vertices[8];
+        assert_eq!((left, t), (vec2(3.0, 1.0), 1.0));
+        assert_eq!(vertices[9].0, vec2(3.0, -1.0));
+        for pair in vertices.chunks(2).collect::<Vec<_>>().windows(2) {
+            let width = |v: &[(Vector2, f32)]| (v[0].0 - v[1].0).magnitude();
+            assert!(width(pair[0]) < width(pair[1]));
+            assert!(pair[0][0].1 < pair[1][0].1);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! The wave equation on a grid: ripples on a pond, or a drumskin.
+//!
+//! Each cell's displacement accelerates towards the cells around it, stepped with the usual
+//! leapfrog finite differences: the next displacement comes from this one and the last, so a
+//! `WaveField` keeps both. What happens at the edges is up to its `Boundary`.
+
+use crate::automata::Edges;
+use crate::grid::FloatGrid;
+use nannou::geom::Vector2;
+use std::f32::consts::PI;
+
+/// How many cells in from the edges `Boundary::Absorbing` soaks waves up over.
+const SPONGE: usize = 16;
+
+/// What happens to waves when they reach the edges of a `WaveField`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Boundary {
+    /// The edges are held still, like a drumskin's, and waves reflect off them upside down.
+    Fixed,
+    /// The edges move freely, and waves reflect off them the right way up.
+    Free,
+    /// Waves carry on round to the other side.
+    Wrap,
+    /// Waves die away near the edges, as if the field went on forever.
+    Absorbing,
+}
+
+#[derive(Clone, Debug)]
+pub struct WaveField {
+    pub width: usize,
+    pub height: usize,
+    /// How fast waves move, in cells per step; up to about 0.85 stays stable.
+    pub speed: f32,
+    /// What fraction of each cell's velocity is lost each step.
+    pub damping: f32,
+    pub boundary: Boundary,
+    // the displacements now and a step ago, and the buffer the next step writes into.
+    current: FloatGrid,
+    previous: FloatGrid,
+    next: FloatGrid,
+}
+
+impl WaveField {
+    /// A flat, still field, with absorbing edges.
+    pub fn new(width: usize, height: usize) -> WaveField {
+        WaveField {
+            width,
+            height,
+            speed: 0.5,
+            damping: 0.002,
+            boundary: Boundary::Absorbing,
+            current: FloatGrid::new(width, height, 0.0),
+            previous: FloatGrid::new(width, height, 0.0),
+            next: FloatGrid::new(width, height, 0.0),
+        }
+    }
+
+    pub fn displacement(&self, x: usize, y: usize) -> f32 {
+        *self.current.get(x, y)
+    }
+
+    /// Every cell's displacement, row by row from the bottom left.
+    pub fn displacements(&self) -> &[f32] {
+        self.current.values()
+    }
+
+    /// Push the field down by `depth` (or up, if it's negative) in a smooth dimple `radius` cells
+    /// across around `center` (in cells, from the bottom left corner of the field), as if
+    /// something had dropped in, to ripple out from there.
+    pub fn splash(&mut self, center: Vector2, radius: f32, depth: f32) {
+        let left = (center.x - radius).floor().max(0.0) as usize;
+        let bottom = (center.y - radius).floor().max(0.0) as usize;
+        let right = ((center.x + radius).ceil().max(0.0) as usize).min(self.width);
+        let top = ((center.y + radius).ceil().max(0.0) as usize).min(self.height);
+        for y in bottom..top {
+            for x in left..right {
+                let offset = Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                let distance = offset.magnitude();
+                if distance < radius {
+                    let dip = depth * (1.0 + (PI * distance / radius).cos()) / 2.0;
+                    // both now and a step ago, so it starts out still.
+                    self.current.add(x, y, -dip);
+                    self.previous.add(x, y, -dip);
+                }
+            }
+        }
+    }
+
+    /// Flatten the field and bring it to rest.
+    pub fn clear(&mut self) {
+        self.current.fill(0.0);
+        self.previous.fill(0.0);
+    }
+
+    /// How much of its velocity (`x`, `y`) loses each step, including any soaked up near the
+    /// edges.
+    fn damping_at(&self, x: usize, y: usize) -> f32 {
+        if self.boundary != Boundary::Absorbing {
+            return self.damping;
+        }
+        let edge = x.min(y).min(self.width - 1 - x).min(self.height - 1 - y);
+        if edge >= SPONGE {
+            return self.damping;
+        }
+        // ramping up gently, so the waves don't reflect off the sponge itself.
+        let depth = 1.0 - edge as f32 / SPONGE as f32;
+        self.damping + 0.3 * depth * depth
+    }
+
+    pub fn step(&mut self) {
+        let edges = match self.boundary {
+            Boundary::Wrap => Edges::Wrap,
+            _ => Edges::Clamp,
+        };
+        self.current.edges = edges;
+        // the grid's laplacian is the weighted average of the cells around less the cell, which
+        // is 0.3 of the usual one.
+        let stiffness = self.speed * self.speed / 0.3;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (now, before) = (self.current.get(x, y), self.previous.get(x, y));
+                let velocity = (now - before) * (1.0 - self.damping_at(x, y));
+                let next = now + velocity + stiffness * self.current.laplacian(x, y);
+                self.next.set(x, y, next);
+            }
+        }
+        if self.boundary == Boundary::Fixed {
+            for x in 0..self.width {
+                self.next.set(x, 0, 0.0);
+                self.next.set(x, self.height - 1, 0.0);
+            }
+            for y in 0..self.height {
+                self.next.set(0, y, 0.0);
+                self.next.set(self.width - 1, y, 0.0);
+            }
+        }
+        // the step ago becomes the buffer for the next one.
+        std::mem::swap(&mut self.previous, &mut self.current);
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::geom::vec2;
+
+    /// How much the field is moving.
+    fn motion(field: &WaveField) -> f32 {
+        let (now, before) = (field.current.values(), field.previous.values());
+        now.iter().zip(before).map(|(a, b)| (a - b).abs()).sum()
+    }
+
+    #[test]
+    fn test_ripple() {
+        let mut field = WaveField::new(101, 101);
+        field.damping = 0.0;
+        field.splash(vec2(50.5, 50.5), 4.0, 1.0);
+        assert_eq!(field.displacement(50, 50), -1.0);
+        assert_eq!(field.displacement(80, 50), 0.0);
+        // still, until stepped.
+        assert_eq!(field.displacement(50, 50), *field.previous.get(50, 50));
+
+        // the ripple reaches 30 cells away after about 30 / speed steps, in every direction.
+        let mut arrived = [None, None];
+        for step in 0..100 {
+            field.step();
+            let far = [field.displacement(80, 50), field.displacement(71, 71)];
+            for (arrived, far) in arrived.iter_mut().zip(&far) {
+                if arrived.is_none() && far.abs() > 0.01 {
+                    *arrived = Some(step as f32);
+                }
+            }
+        }
+        let (across, diagonal) = (arrived[0].unwrap(), arrived[1].unwrap());
+        assert!(across > 45.0 && across < 60.0, "{}", across);
+        assert!((across - diagonal).abs() <= 3.0, "{} {}", across, diagonal);
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let mut ends = vec![];
+        for boundary in &[
+            Boundary::Fixed,
+            Boundary::Free,
+            Boundary::Wrap,
+            Boundary::Absorbing,
+        ] {
+            let mut field = WaveField::new(60, 60);
+            field.damping = 0.0;
+            field.boundary = *boundary;
+            field.splash(vec2(30.0, 30.0), 4.0, 1.0);
+            for _ in 0..400 {
+                field.step();
+            }
+            assert!(field.displacements().iter().all(|d| d.is_finite()));
+            if *boundary == Boundary::Fixed {
+                assert_eq!(field.displacement(0, 30), 0.0);
+                assert_eq!(field.displacement(30, 59), 0.0);
+            }
+            ends.push(motion(&field));
+        }
+        // once it's reached the edges, the absorbing field has gone much stiller than the rest,
+        // which keep their waves bouncing around.
+        for end in &ends[..3] {
+            assert!(ends[3] * 10.0 < *end, "{:?}", ends);
+        }
+    }
+
+    #[test]
+    fn test_damping() {
+        let mut fields = vec![];
+        for damping in &[0.0, 0.05] {
+            let mut field = WaveField::new(40, 40);
+            field.boundary = Boundary::Wrap;
+            field.damping = *damping;
+            field.splash(vec2(20.0, 20.0), 4.0, 1.0);
+            for _ in 0..200 {
+                field.step();
+            }
+            fields.push(motion(&field));
+        }
+        assert!(fields[1] < fields[0] / 100.0, "{:?}", fields);
+    }
+}
@@ -0,0 +1,116 @@
+//! Live video from a webcam, as images to sample or post-process like any other: the picture
+//! under `bouncing_3`'s triangles, say, or under `pattern_3`'s halftone.
+//!
+//! Frames are read on a thread of their own, as waiting on the camera would hold up drawing, and
+//! the sketch takes the newest each update:
+//!
+//! ```ignore
+//! // when building the model:
+//! let webcam = Webcam::open(0);
+//! // every update:
+//! if let Some(frame) = model.webcam.latest() {
+//!     model.image = frame;
+//! }
+//! // and if the camera couldn't be opened, or stopped, `model.webcam.error` says why.
+//! ```
+
+use nannou::image::RgbImage;
+use nokhwa::Camera;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+pub struct Webcam {
+    // frames, and then why they stopped coming, if they did.
+    frames: Receiver<Result<RgbImage, String>>,
+    /// Why the camera couldn't be opened, or stopped, once `latest` has heard; no more frames
+    /// come after.
+    pub error: Option<String>,
+}
+
+impl Webcam {
+    /// Start reading camera `index`, 0 being the first. If it can't be opened, or stops, that's
+    /// printed and kept in `error`, and no more frames come, so the sketch can carry on with
+    /// whatever it had.
+    pub fn open(index: usize) -> Webcam {
+        // room for one frame, which the sketch hasn't taken yet; the camera doesn't wait for it,
+        // but skips the frames after until it has.
+        let (send, frames) = sync_channel(1);
+        thread::spawn(move || {
+            if let Err(e) = read(index, &send) {
+                println!("{}", e);
+                // waits for the sketch to take the frame before it, unless the sketch has gone.
+                send.send(Err(e)).ok();
+            }
+        });
+        Webcam {
+            frames,
+            error: None,
+        }
+    }
+
+    /// The newest frame since the last time this was asked, if there's been one.
+    pub fn latest(&mut self) -> Option<RgbImage> {
+        let mut latest = None;
+        for frame in self.frames.try_iter() {
+            match frame {
+                Ok(frame) => latest = Some(frame),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        latest
+    }
+}
+
+// send camera `index`'s frames to `send` until the sketch has gone, or the camera fails.
+fn read(index: usize, send: &SyncSender<Result<RgbImage, String>>) -> Result<(), String> {
+    let mut camera = Camera::new(index, None)
+        .map_err(|e| format!("can't open webcam {}: {}", index, e))?;
+    camera
+        .open_stream()
+        .map_err(|e| format!("can't start webcam {}: {}", index, e))?;
+    loop {
+        let frame = camera
+            .frame()
+            .map_err(|e| format!("webcam {} stopped: {}", index, e))?;
+        match send.try_send(Ok(frame)) {
+            Ok(()) | Err(TrySendError::Full(_)) => (),
+            // the sketch has gone.
+            Err(TrySendError::Disconnected(_)) => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest() {
+        let (send, frames) = sync_channel(2);
+        let mut webcam = Webcam {
+            frames,
+            error: None,
+        };
+        assert!(webcam.latest().is_none());
+        send.send(Ok(RgbImage::new(1, 1))).unwrap();
+        send.send(Ok(RgbImage::new(2, 2))).unwrap();
+        // only the newest, once.
+        assert_eq!(webcam.latest().unwrap().dimensions(), (2, 2));
+        assert!(webcam.latest().is_none());
+        assert!(webcam.error.is_none());
+    }
+
+    #[test]
+    fn test_error() {
+        let (send, frames) = sync_channel(2);
+        let mut webcam = Webcam {
+            frames,
+            error: None,
+        };
+        send.send(Ok(RgbImage::new(1, 1))).unwrap();
+        send.send(Err("webcam 0 stopped".to_string())).unwrap();
+        // the frame before it still comes.
+        assert_eq!(webcam.latest().unwrap().dimensions(), (1, 1));
+        assert_eq!(webcam.error.as_deref(), Some("webcam 0 stopped"));
+    }
+}
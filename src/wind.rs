@@ -0,0 +1,86 @@
+//! Gusty wind, from Perlin noise, first blown through `pattern_2`'s hangers.
+//!
+//! The wind only blows sideways, and only changes along x: the wind at `x` is the wind at 0,
+//! `x * scale` seconds later, so gusts sweep from right to left across a sketch at `1 / scale`
+//! units a second. More `octaves` add smaller, quicker flutters on top of the slow gusts.
+
+use nannou::geom::{vec2, Vector2};
+use nannou::noise::{NoiseFn, Perlin};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Wind {
+    /// How quickly the wind changes, in noise cells per second.
+    pub velocity: f32,
+    /// The strongest the wind blows.
+    pub magnitude: f32,
+    /// How many layers of noise make up the wind, each twice as quick and half as strong as the
+    /// last; 1 for just the slow gusts.
+    pub octaves: usize,
+    /// How many seconds behind the wind at 0 the wind at each unit of x is.
+    pub scale: f32,
+    noise: Perlin,
+}
+
+impl Wind {
+    /// `pattern_2`'s wind, blowing up to `magnitude`, with gusts sweeping across at 125 units a
+    /// second.
+    pub fn new(magnitude: f32) -> Wind {
+        Wind {
+            velocity: 0.9,
+            magnitude,
+            octaves: 1,
+            scale: 0.008,
+            noise: Perlin::new(),
+        }
+    }
+
+    /// The wind's force on something at `pos`, `time` seconds in.
+    pub fn force(&self, pos: Vector2, time: f32) -> Vector2 {
+        let t = 5.0 + (time + pos.x * self.scale) as f64 * self.velocity as f64;
+        let (mut total, mut amplitude, mut frequency, mut sum) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..self.octaves.max(1) {
+            total += self.noise.get([t * frequency, 0.0]) * amplitude;
+            sum += amplitude;
+            amplitude /= 2.0;
+            frequency *= 2.0;
+        }
+        vec2((total / sum) as f32 * self.magnitude, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_force() {
+        let mut wind = Wind::new(7.0);
+        // the same wind as pattern_2 always blew.
+        let noise = Perlin::new();
+        let (pos, time) = (vec2(120.0, -40.0), 3.5);
+        let expected = noise.get([
+            5.0 + (time * 0.9) as f64 + (pos.x * 0.9 * 0.008) as f64,
+            0.0,
+        ]) as f32
+            * 7.0;
+        let force = wind.force(pos, time);
+        assert!(
+            (force.x - expected).abs() < 1e-4,
+            "{:?} {}",
+            force,
+            expected
+        );
+        assert_eq!(force.y, 0.0);
+
+        // a gust at 0 reaches x = -125 a second later.
+        let gust = wind.force(vec2(0.0, 0.0), 1.0);
+        assert!((wind.force(vec2(-125.0, 0.0), 2.0) - gust).magnitude() < 1e-4);
+
+        // more octaves are rougher, but no stronger.
+        wind.octaves = 4;
+        for i in 0..1000 {
+            let force = wind.force(vec2(i as f32, 0.0), 0.0);
+            assert!(force.x.abs() <= 7.0, "{:?}", force);
+        }
+    }
+}